@@ -32,6 +32,8 @@ mod io_utils;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
 
 /// This structure uses `clap-derive` annotations and define the arguments that can
 /// be passed on to the executable solver.
@@ -98,7 +100,7 @@ fn main() {
     );
 
     let start = Instant::now();
-    let Completion{ is_exact, best_value } = solver.maximize();
+    let Completion{ is_exact, best_value, termination, .. } = solver.maximize();
     
     let duration = start.elapsed();
     let upper_bound = - solver.best_upper_bound();
@@ -111,25 +113,32 @@ fn main() {
     println!("Lower Bnd:  {}",            lower_bound);
     println!("Gap:        {:.3}",         gap);
     println!("Aborted:    {}",            !is_exact);
+    println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+    println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
     
-    let mut runways = vec![(RunwayState {prev_time:-1, prev_class: -1}, vec![]); problem.instance.nb_runways];
-    let mut cur = problem.initial_state();
     if let Some(decisions) = solver.best_solution() {
-        for decision in decisions {
-            let AlpDecision { class, runway } = problem.from_decision(decision.value);
-            let aircraft = problem.next[class][cur.rem[class]];
-            let arrival = problem.get_arrival_time(&cur.info, aircraft, runway);
-            
-            runways[runway].0.prev_time = arrival;
-            runways[runway].0.prev_class = problem.instance.classes[aircraft] as isize;
-            runways[runway].1.push((arrival, aircraft));
-            runways.sort_unstable();
-
-            cur = problem.transition(&cur, decision);
+        for runway in landings(&problem, decisions) {
+            println!("{:?}", runway);
         }
+    }
+}
+
+/// Replays the given solution and returns the (arrival time, aircraft) pairs 
+/// of the landings that take place on each runway
+fn landings(problem: &Alp, decisions: Vec<Decision>) -> Vec<Vec<(isize, usize)>> {
+    let mut runways = vec![(RunwayState {prev_time:-1, prev_class: -1}, vec![]); problem.instance.nb_runways];
+    let mut cur = problem.initial_state();
+    for decision in decisions {
+        let AlpDecision { class, runway } = problem.from_decision(decision.value);
+        let aircraft = problem.next[class][cur.rem[class]];
+        let arrival = problem.get_arrival_time(&cur.info, aircraft, runway);
         
-        for runway in runways {
-            println!("{:?}", runway.1);
-        }
+        runways[runway].0.prev_time = arrival;
+        runways[runway].0.prev_class = problem.instance.classes[aircraft] as isize;
+        runways[runway].1.push((arrival, aircraft));
+        runways.sort_unstable();
+
+        cur = problem.transition(&cur, decision);
     }
+    runways.into_iter().map(|runway| runway.1).collect()
 }
@@ -0,0 +1,97 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: landing five aircrafts on a single runway
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand. Five aircrafts of two classes must land on
+//! one runway as close as possible to their target time:
+//!
+//! | aircraft | class | target |
+//! |----------|-------|--------|
+//! | 0        | 1     | 1      |
+//! | 1        | 0     | 1      |
+//! | 2        | 0     | 5      |
+//! | 3        | 1     | 7      |
+//! | 4        | 1     | 7      |
+//!
+//! A class 0 aircraft must land at least 2 (resp. 3) time units before the
+//! next aircraft if that one is of class 0 (resp. 1). A class 1 aircraft must
+//! leave 1 (resp. 2) time units to the next aircraft of class 0 (resp. 1).
+//! The aircrafts of a given class land in the order of their targets.
+//!
+//! The best schedule lands the aircrafts at times 1, 2, 5, 8 and 10, which
+//! totals 5 time units of delay. As the model maximizes the opposite of the
+//! total delay, the optimum is -5. Every step of `walkthrough()` asserts what
+//! a user typically wants to look at while the solver runs: the bound derived
+//! from the root, the first solution found, the final gap and the decoded
+//! solution.
+
+use ddo::*;
+
+use crate::{io_utils::AlpInstance, landings, model::{Alp, AlpRanking, AlpRelax}, dominance::AlpDominance};
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation, its ranking and its dominance relation
+    let instance = AlpInstance {
+        nb_classes: 2,
+        nb_aircrafts: 5,
+        nb_runways: 1,
+        classes: vec![1, 0, 0, 1, 1],
+        target: vec![1, 1, 5, 7, 7],
+        latest: vec![20; 5],
+        separation: vec![vec![2, 3], vec![1, 2]],
+    };
+    let problem = Alp::new(instance);
+    let relaxation = AlpRelax::new(problem.clone());
+    let ranking = AlpRanking;
+    let dominance = SimpleDominanceChecker::new(AlpDominance, problem.nb_variables());
+
+    // 2. The solver: no cutoff and a single node per layer so that the root
+    //    must be restricted and relaxed
+    let width = FixedWidth(1);
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above. With a
+    //    single node per layer, it cannot tell any delay is unavoidable
+    assert_eq!(Some(0), solver.root_bound());
+    // 4. The restricted DD of the root yields a first schedule which
+    //    totals 15 time units of delay
+    assert_eq!(Some(-15), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(-5), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution is replayed to tell when each aircraft lands
+    let schedule = landings(&problem, solver.best_solution().unwrap());
+    assert_eq!(vec![vec![(1, 0), (2, 1), (5, 2), (8, 3), (10, 4)]], schedule);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
@@ -42,7 +42,7 @@ pub fn solve_golomb(n: usize) -> isize {
         &mut fringe,
     );
 
-    let Completion{ is_exact: _, best_value } = solver.maximize();
+    let Completion{ is_exact: _, best_value, .. } = solver.maximize();
     best_value.map(|x| -x).unwrap_or(-1)
 }
 
@@ -0,0 +1,81 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: finding the shortest golomb ruler with four marks
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand: placing four marks on a ruler so that all
+//! pairwise distances between them differ. The shortest such ruler measures
+//! 6 units; for instance:
+//!
+//! ```text
+//!   0   1           4       6
+//!   |---|---|---|---|---|---|
+//! ```
+//!
+//! where the six distances 1, 2, 3, 4, 5 and 6 all appear exactly once. As
+//! the model maximizes the opposite of the ruler length, the optimum is -6.
+//! Every step of `walkthrough()` asserts what a user typically wants to look
+//! at while the solver runs: the bound derived from the root, the first
+//! solution found, the final gap and the decoded solution.
+
+use ddo::*;
+
+use crate::{Golomb, GolombRanking, GolombRelax};
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation and its ranking
+    let problem = Golomb::new(4);
+    let relaxation = GolombRelax { pb: &problem };
+    let ranking = GolombRanking;
+
+    // 2. The solver: no dominance, no cutoff and a single node per layer
+    //    so that the root must be restricted and relaxed
+    let width = FixedWidth(1);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above
+    assert_eq!(Some(-3), solver.root_bound());
+    // 4. The restricted DD of the root yields a first ruler of length 7
+    assert_eq!(Some(-7), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(-6), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution gives the position of each mark on the ruler
+    let marks = solver.best_solution().map(|mut decisions| {
+        decisions.sort_unstable_by_key(|d| d.variable.id());
+        decisions.iter().map(|d| d.value).collect::<Vec<_>>()
+    });
+    assert_eq!(Some(vec![0, 1, 4, 6]), marks);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
@@ -28,6 +28,10 @@ use ordered_float::OrderedFloat;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
+#[cfg(test)]
+mod owned;
 
 /// In our DP model, we consider a state that simply consists of the remaining 
 /// capacity in the knapsack. Additionally, we also consider the *depth* (number
@@ -50,6 +54,7 @@ pub struct KnapsackState {
 /// a maximum (weight) capacity, and a set of items to chose from. Each of these
 /// items having a weight and a profit, the goal is to select the best subset of
 /// the items to place them in the sack so as to maximize the profit.
+#[derive(Debug, Clone)]
 pub struct Knapsack {
     /// The maximum capacity of the sack (when empty)
     capacity: usize,
@@ -59,6 +64,11 @@ pub struct Knapsack {
     weight: Vec<usize>,
     /// the order in which the items are considered
     order: Vec<usize>,
+    /// the width multiplier of each layer (see `Problem::width_profile`)
+    width_profile: Option<Vec<usize>>,
+    /// whether the items which fit in none of the sacks of a layer are 
+    /// filtered out of that layer at once (see `Problem::filter_layer`)
+    layer_filter: bool,
 }
 
 impl Knapsack {
@@ -66,7 +76,18 @@ impl Knapsack {
         let mut order = (0..profit.len()).collect::<Vec<usize>>();
         order.sort_unstable_by_key(|i| OrderedFloat(- profit[*i] as f64 / weight[*i] as f64));
 
-        Knapsack { capacity, profit, weight, order }
+        Knapsack { capacity, profit, weight, order, width_profile: None, layer_filter: false }
+    }
+    /// Spends the width budget according to the given per-depth multipliers
+    pub fn with_width_profile(mut self, profile: Option<Vec<usize>>) -> Self {
+        self.width_profile = profile;
+        self
+    }
+    /// Enables (or disables) the filtering of the items which fit in none of
+    /// the sacks of a layer
+    pub fn with_layer_filter(mut self, enabled: bool) -> Self {
+        self.layer_filter = enabled;
+        self
     }
 }
 
@@ -123,6 +144,19 @@ impl Problem for Knapsack {
             None
         }
     }
+    fn width_profile(&self) -> Option<Vec<usize>> {
+        self.width_profile.clone()
+    }
+    fn filter_layer(&self, variable: Variable, states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+        let mut mask = ValueMask::all();
+        if self.layer_filter {
+            let largest = states.map(|s| s.capacity).max().unwrap_or(0);
+            if self.weight[variable.id()] > largest {
+                mask.skip(TAKE_IT);
+            }
+        }
+        mask
+    }
 }
 
 /// In addition to a dynamic programming (DP) model of the problem you want to solve, 
@@ -217,6 +251,35 @@ impl Dominance for KPDominance {
     }
 }
 
+/// Optionally, the instance can be reduced before it is even handed over to the solver.
+/// The reduction implemented here is the simplest one can think of: all the items which
+/// are heavier than the capacity of the sack can never be taken, so these are simply
+/// removed from the problem (and the decision to leave them out is forced).
+pub struct KPPreprocess;
+impl Preprocess<Knapsack> for KPPreprocess {
+    type Reduced = Knapsack;
+    type Lifter  = ForcedDecisionsLifter;
+
+    fn preprocess(&self, problem: &Knapsack) -> (Knapsack, ForcedDecisionsLifter) {
+        let mut mapping = vec![];
+        let mut forced  = vec![];
+        let mut profit  = vec![];
+        let mut weight  = vec![];
+
+        for (i, (p, w)) in problem.profit.iter().copied().zip(problem.weight.iter().copied()).enumerate() {
+            if w > problem.capacity {
+                forced.push(Decision { variable: Variable(i), value: LEAVE_IT_OUT });
+            } else {
+                mapping.push(Variable(i));
+                profit.push(p);
+                weight.push(w);
+            }
+        }
+
+        (Knapsack::new(problem.capacity, profit, weight), ForcedDecisionsLifter::new(mapping, forced))
+    }
+}
+
 // #########################################################################################
 // # THE INFORMATION BEYOND THIS LINE IS NOT DIRECTLY RELATED TO THE IMPLEMENTATION OF     #
 // # A SOLVER BASED ON DDO. INSTEAD, THAT PORTION OF THE CODE CONTAINS GENERIC FUNCTION    #
@@ -233,15 +296,73 @@ struct Args {
     /// The path to the instance file
     fname: String,
     /// The number of concurrent threads
-    #[clap(short, long, default_value = "8")]
-    threads: usize,
+    #[clap(short, long)]
+    threads: Option<usize>,
     /// The maximum amount of time you would like this solver to run
-    #[clap(short, long, default_value = "30")]
-    duration: u64,
+    #[clap(short, long)]
+    duration: Option<u64>,
     /// The maximum width of a layer when solving an instance. By default, it will allow
     /// as many nodes in a layer as there are unassigned variables in the global problem.
     #[clap(short, long)]
     width: Option<usize>,
+    /// Multiplies the maximum width of each layer by the given factor, e.g.
+    /// `--width-profile 1,1,4,4,1` allows four times as many nodes in the third
+    /// and fourth layers. The layers past the end of the profile are unaffected.
+    #[clap(long, value_delimiter = ',')]
+    width_profile: Option<Vec<usize>>,
+    /// Whether or not the instance should be reduced before it is solved
+    #[clap(long)]
+    preprocess: bool,
+    /// A TOML file holding the configuration of the solver. The other flags (threads,
+    /// duration, width) override the values from that file when they are specified.
+    #[cfg(feature = "config-file")]
+    #[clap(long)]
+    config: Option<String>,
+    /// Saves the configuration that was actually used to solve the instance in the
+    /// given TOML file.
+    #[cfg(feature = "config-file")]
+    #[clap(long)]
+    save_config: Option<String>,
+    /// Evaluates the candidate solution stored in the given JSON file instead of 
+    /// solving the instance. The file must hold an array with the value of each 
+    /// variable (null when the variable is not assigned).
+    #[clap(long)]
+    evaluate: Option<String>,
+    /// Completes the evaluated candidate with a greedy dive when it is incomplete 
+    /// or infeasible (rather than reporting the error).
+    #[clap(long, requires = "evaluate")]
+    repair: bool,
+    /// Lets a few candidate configurations explore that many nodes each, and
+    /// commits to the one which made the most progress to solve the instance.
+    #[clap(long)]
+    autotune: Option<usize>,
+    /// Pops the subproblems by decreasing `ub - penalty * estimated cost` rather
+    /// than by decreasing ub (see `BestUbPerCost`), with the given penalty.
+    #[clap(long)]
+    cost_penalty: Option<f64>,
+    /// Prints the structural features of the instance (as a CSV line) rather
+    /// than solving it. The duration (5 seconds by default) bounds the time
+    /// spent profiling the instance.
+    #[clap(long)]
+    profile_only: bool,
+    /// Runs the self-diagnostics of the model on the instance (see `diagnose`)
+    /// rather than solving it, and prints the failures it found.
+    #[clap(long)]
+    diagnose: bool,
+    /// Saves the report of the run (its value, whether it was proved optimal,
+    /// ...) in the given JSON file (see `RunReport`).
+    #[clap(long)]
+    report: Option<String>,
+    /// Fails (with a non-zero exit code) when the run does not reproduce the
+    /// results recorded in the given report of a previous run: the value must
+    /// be the same, and it must still be proved optimal if it was.
+    #[clap(long)]
+    assert_matches: Option<String>,
+    /// Also requires the run to explore as many nodes as the previous one when
+    /// checking it with `--assert-matches`. Only use it when the search is 
+    /// deterministic (e.g. with one single thread).
+    #[clap(long, requires = "assert_matches")]
+    strict_determinism: bool,
 }
 
 /// This enumeration simply groups the kind of errors that might occur when parsing a
@@ -313,13 +434,96 @@ fn max_width<T>(nb_vars: usize, w: Option<usize>) -> Box<dyn WidthHeuristic<T> +
 /// to create a fast an effective solver for the knapsack problem.
 fn main() {
     let args = Args::parse();
-    let problem = read_instance(&args.fname).unwrap();
+    let original = read_instance(&args.fname).unwrap();
+    if let Some(fname) = args.evaluate.as_ref() {
+        return evaluate_candidate(&original, fname, args.repair);
+    }
+    let (problem, lifter): (Knapsack, Box<dyn SolutionLifter>) = if args.preprocess {
+        let (reduced, lifter) = KPPreprocess.preprocess(&original);
+        (reduced, Box::new(lifter))
+    } else {
+        let (reduced, lifter) = NoPreprocessing.preprocess(&original);
+        (reduced, Box::new(lifter))
+    };
+    let problem = problem.with_width_profile(args.width_profile.clone());
     let relaxation= KPRelax{pb: &problem};
     let heuristic= KPRanking;
-    let width = max_width(problem.nb_variables(), args.width);
     let dominance = SimpleDominanceChecker::new(KPDominance, problem.nb_variables());
+
+    if args.profile_only {
+        let config = ProfileConfig { max_width: args.width.unwrap_or(problem.nb_variables()), ..Default::default() };
+        let budget = Duration::from_secs(args.duration.unwrap_or(5));
+        let profile = profile_instance(&problem, &relaxation, &heuristic, &config, budget);
+        println!("{}", InstanceProfile::CSV_HEADER);
+        println!("{}", profile.to_csv());
+        return;
+    }
+    if args.diagnose {
+        let report = diagnose(&problem, &relaxation, 100, 0);
+        println!("{report}");
+        return;
+    }
+
+    #[cfg(feature = "config-file")]
+    if let Some(fname) = args.config.as_ref() {
+        let mut config = Config::from_toml(fname).unwrap_or_else(|e| panic!("{fname}: {e}"));
+        if let Some(t) = args.threads  { config.threads = t; }
+        if let Some(d) = args.duration { config.cutoff  = CutoffConfig::TimeBudget { seconds: d }; }
+        if let Some(w) = args.width    { config.width   = WidthConfig::Fixed { width: w }; }
+        if let Some(out) = args.save_config.as_ref() {
+            std::fs::write(out, config.to_toml().unwrap()).expect("could not save the configuration");
+        }
+
+        let width = config.width_heuristic(problem.nb_variables());
+        let cutoff = config.cutoff();
+        let mut fringe = config.fringe(&heuristic);
+        let mut solver = config.solver(
+            &problem, 
+            &relaxation, 
+            &heuristic, 
+            width.as_ref(), 
+            &dominance,
+            cutoff.as_ref(), 
+            fringe.as_mut(),
+        );
+        return solve(solver.as_mut(), lifter.as_ref(), &args);
+    }
+
+    let width = max_width(problem.nb_variables(), args.width);
     let cutoff = TimeBudget::new(Duration::from_secs(15));//NoCutoff;
-    let mut fringe = SimpleFringe::new(MaxUB::new(&heuristic));
+
+    if let Some(nodes) = args.autotune {
+        // the candidates only differ by the width of their DDs
+        let widths: Vec<Box<dyn WidthHeuristic<KnapsackState> + Send + Sync>> = vec![
+            width,
+            Box::new(FixedWidth(10)),
+            Box::new(FixedWidth(1000)),
+        ];
+        let mut fringes = widths.iter().map(|_| SimpleFringe::new(MaxUB::new(&heuristic))).collect::<Vec<_>>();
+        let candidates = widths.iter().zip(fringes.iter_mut())
+            .map(|(width, fringe)| Box::new(DefaultCachingSolver::new(
+                &problem,
+                &relaxation,
+                &heuristic,
+                width.as_ref(),
+                &dominance,
+                &cutoff,
+                fringe,
+            )) as Box<dyn Solver>)
+            .collect();
+        let mut solver = AutoTuneSolver::new(candidates).with_tuning_budget(nodes);
+        solve(&mut solver, lifter.as_ref(), &args);
+        println!("Winner:     {:?}", solver.winner());
+        return;
+    }
+    let mut fringe: Box<dyn Fringe<State = KnapsackState> + Send + Sync> = match args.cost_penalty {
+        // on average, the NbUnassignedWidth allows half as many nodes as there are variables
+        Some(penalty) => {
+            let avg_width = args.width.unwrap_or(problem.nb_variables() / 2);
+            Box::new(SimpleFringe::new(BestUbPerCost::new(&heuristic, problem.nb_variables(), avg_width, penalty)))
+        },
+        None => Box::new(SimpleFringe::new(MaxUB::new(&heuristic))),
+    };
 
     let mut solver = DefaultCachingSolver::new(
         &problem, 
@@ -328,17 +532,27 @@ fn main() {
         width.as_ref(), 
         &dominance,
         &cutoff, 
-        &mut fringe,
+        fringe.as_mut(),
     );
 
+    solve(&mut solver, lifter.as_ref(), &args);
+}
+
+/// Solves the problem and prints the outcome of the resolution. Then, saves
+/// the report of the run and checks it against that of a previous run when 
+/// the arguments ask for it.
+fn solve(solver: &mut dyn Solver, lifter: &dyn SolutionLifter, args: &Args) {
     let start = Instant::now();
-    let Completion{ is_exact, best_value } = solver.maximize();
+    let completion = solver.maximize();
     
     let duration = start.elapsed();
     let upper_bound = solver.best_upper_bound();
+    let report = RunReport::new(&completion, upper_bound);
+    let Completion{ is_exact, best_value, termination, .. } = completion;
     let lower_bound = solver.best_lower_bound();
     let gap = solver.gap();
-    let best_solution  = solver.best_solution().map(|mut decisions|{
+    let best_solution  = solver.best_solution().map(|decisions|{
+        let mut decisions = lifter.lift(&decisions);
         decisions.sort_unstable_by_key(|d| d.variable.id());
         decisions.iter().map(|d| d.value).collect::<Vec<_>>()
     });
@@ -349,5 +563,47 @@ fn main() {
     println!("Lower Bnd:  {}",            lower_bound);
     println!("Gap:        {:.3}",         gap);
     println!("Aborted:    {}",            !is_exact);
+    println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+    println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
     println!("Solution:   {:?}",          best_solution.unwrap_or_default());
+
+    if let Some(fname) = args.report.as_ref() {
+        report.save(fname).unwrap_or_else(|e| panic!("{fname}: {e}"));
+    }
+    if let Some(fname) = args.assert_matches.as_ref() {
+        let previous = RunReport::load(fname).unwrap_or_else(|e| panic!("{fname}: {e}"));
+        if let Err(regression) = report.check_matches(&previous, args.strict_determinism) {
+            eprintln!("{regression}");
+            std::process::exit(1);
+        }
+        println!("Matches:    {fname}");
+    }
+}
+
+/// Reads a candidate solution from the given JSON file, evaluates it against the
+/// model and prints the outcome of the evaluation
+fn evaluate_candidate(problem: &Knapsack, fname: &str, with_repair: bool) {
+    let file = File::open(fname).expect("could not open the candidate file");
+    let values: Vec<Option<isize>> = serde_json::from_reader(BufReader::new(file))
+        .expect("the candidate must be a json array of values");
+    let assignment = values.iter().enumerate()
+        .filter_map(|(i, v)| v.map(|value| Decision { variable: Variable(i), value }))
+        .collect::<Vec<_>>();
+
+    if with_repair {
+        match repair(problem, &assignment) {
+            Ok(Repaired { value, mut solution, repaired_from }) => {
+                solution.sort_unstable_by_key(|d| d.variable.id());
+                println!("Objective:  {}",   value);
+                println!("Repaired:   {:?}", repaired_from.map(|v| v.id()));
+                println!("Solution:   {:?}", solution.iter().map(|d| d.value).collect::<Vec<_>>());
+            },
+            Err(e) => println!("Error:      {}", e),
+        }
+    } else {
+        match evaluate(problem, &assignment) {
+            Ok(value) => println!("Objective:  {}", value),
+            Err(e)    => println!("Error:      {}", e),
+        }
+    }
 }
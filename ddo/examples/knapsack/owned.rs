@@ -0,0 +1,98 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Owning the problem
+//!
+//! The relaxation of the knapsack example borrows the problem (`KPRelax<'a>`).
+//! That is fine as long as the solver is configured and used in the same
+//! function, but it makes it impossible to return the configured pieces from
+//! a constructor. This module shows how to get around that limitation: the
+//! problem is shared behind an `Arc` which is owned by the relaxation and by
+//! the configuration itself (`Arc<P>` is a `Problem` whenever `P` is one).
+
+use std::sync::Arc;
+
+use ddo::*;
+
+use crate::{KPDominance, KPRanking, KPRelax, Knapsack, KnapsackState};
+
+/// A relaxation which owns (a shared handle on) the problem it relaxes
+pub struct OwnedKPRelax {
+    pb: Arc<Knapsack>,
+}
+impl Relaxation for OwnedKPRelax {
+    type State = KnapsackState;
+
+    fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+        KPRelax { pb: &self.pb }.merge(states)
+    }
+    fn relax(&self, source: &Self::State, dest: &Self::State, merged: &Self::State, decision: Decision, cost: isize) -> isize {
+        KPRelax { pb: &self.pb }.relax(source, dest, merged, decision, cost)
+    }
+    fn fast_upper_bound(&self, state: &Self::State) -> isize {
+        KPRelax { pb: &self.pb }.fast_upper_bound(state)
+    }
+}
+
+/// A fully configured knapsack solver which does not borrow anything: it can
+/// be built in a function and returned to its caller
+pub struct OwnedSolver {
+    problem: Arc<Knapsack>,
+    relaxation: OwnedKPRelax,
+    ranking: Arc<KPRanking>,
+    width: FixedWidth,
+    dominance: SimpleDominanceChecker<KPDominance>,
+    cutoff: NoCutoff,
+}
+impl OwnedSolver {
+    /// Solves the problem to optimality and returns the outcome of the search
+    pub fn maximize(&self) -> Completion {
+        let mut fringe = NoDupFringe::new(MaxUB::new(&self.ranking));
+        let mut solver = SeqNoCachingSolverLel::new(
+            &self.problem,
+            &self.relaxation,
+            &self.ranking,
+            &self.width,
+            &self.dominance,
+            &self.cutoff,
+            &mut fringe,
+        );
+        solver.maximize()
+    }
+}
+
+/// Configures a solver for the given problem and hands it over to the caller
+pub fn configured_solver(problem: Knapsack, width: usize) -> OwnedSolver {
+    let problem = Arc::new(problem);
+    OwnedSolver {
+        relaxation: OwnedKPRelax { pb: Arc::clone(&problem) },
+        ranking: Arc::new(KPRanking),
+        width: FixedWidth(width),
+        dominance: SimpleDominanceChecker::new(KPDominance, problem.nb_variables()),
+        cutoff: NoCutoff,
+        problem,
+    }
+}
+
+#[test]
+fn a_returned_solver_finds_the_optimum() {
+    let problem = Knapsack::new(10, vec![12, 11, 11, 5], vec![6, 5, 5, 2]);
+    let solver = configured_solver(problem, 1);
+    assert_eq!(Some(22), solver.maximize().best_value);
+}
@@ -23,7 +23,7 @@ use std::path::PathBuf;
 
 use ddo::*;
 
-use crate::{KPRelax, KPRanking, read_instance, KPDominance};
+use crate::{KPRelax, KPRanking, read_instance, KPDominance, Knapsack, KPPreprocess};
 
 fn locate(id: &str) -> PathBuf {
     PathBuf::new()
@@ -61,6 +61,194 @@ pub fn solve_id(id: &str) -> isize {
     best_value.map(|x| x).unwrap_or(-1)
 }
 
+/// Solves the given instance while pruning the subproblems which cannot beat 
+/// the best solution by more than `slack`, and returns the value it finds
+pub fn solve_id_with_slack(id: &str, slack: isize) -> isize {
+    let fname = locate(id);
+    let problem = read_instance(fname.to_str().unwrap()).unwrap();
+    let relaxation = KPRelax{pb: &problem};
+    let ranking = KPRanking;
+
+    let width = FixedWidth(10);
+    let dominance = SimpleDominanceChecker::new(KPDominance, problem.nb_variables());
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+
+    let mut solver = SeqCachingSolverLel::new(
+        &problem, 
+        &relaxation, 
+        &ranking, 
+        &width, 
+        &dominance,
+        &cutoff, 
+        &mut fringe,
+    ).with_absolute_slack(slack);
+
+    let Completion { best_value, guaranteed_within, .. } = solver.maximize();
+    assert_eq!(Some(slack).filter(|s| *s != 0), guaranteed_within);
+    best_value.unwrap()
+}
+
+/// Solves the given instance in the verification mode of the sequential solver,
+/// which panics as soon as a node fails one of its sanity checks
+pub fn solve_id_verified(id: &str) -> isize {
+    let fname = locate(id);
+    let problem = read_instance(fname.to_str().unwrap()).unwrap();
+    let relaxation = KPRelax{pb: &problem};
+    let ranking = KPRanking;
+
+    let width = FixedWidth(10);
+    let dominance = SimpleDominanceChecker::new(KPDominance, problem.nb_variables());
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+
+    let mut solver = SeqCachingSolverLel::new(
+        &problem, 
+        &relaxation, 
+        &ranking, 
+        &width, 
+        &dominance,
+        &cutoff, 
+        &mut fringe,
+    ).with_verify();
+
+    let Completion { best_value, .. } = solver.maximize();
+    best_value.unwrap()
+}
+
+/// Solves the given instance with the sequential solver (with or without the
+/// layer filter) and returns its optimal value along with the number of 
+/// explored nodes
+fn solve_id_with_layer_filter(id: &str, enabled: bool) -> (isize, usize) {
+    let fname = locate(id);
+    let problem = read_instance(fname.to_str().unwrap()).unwrap().with_layer_filter(enabled);
+    let relaxation = KPRelax{pb: &problem};
+    let ranking = KPRanking;
+
+    let width = FixedWidth(10);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, 
+        &relaxation, 
+        &ranking, 
+        &width, 
+        &dominance,
+        &cutoff, 
+        &mut fringe,
+    );
+
+    let Completion { best_value, .. } = solver.maximize();
+    (best_value.unwrap(), solver.explored())
+}
+
+/// Solves the given problem and returns its optimal value along with the best solution
+fn solve(problem: &Knapsack) -> (Option<isize>, Option<Solution>) {
+    let relaxation = KPRelax{pb: problem};
+    let ranking = KPRanking;
+
+    let width = NbUnassignedWidth(problem.nb_variables());
+    let dominance = SimpleDominanceChecker::new(KPDominance, problem.nb_variables());
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+
+    let mut solver = DefaultCachingSolver::new(
+        problem, 
+        &relaxation, 
+        &ranking, 
+        &width, 
+        &dominance,
+        &cutoff, 
+        &mut fringe,
+    );
+
+    let Completion { best_value , ..} = solver.maximize();
+    (best_value, solver.best_solution())
+}
+
+/// Checks that solving the preprocessed problem and lifting its solution yields
+/// a complete and feasible solution having the same value as the original optimum
+fn check_preprocessing(problem: &Knapsack) {
+    let (expected, _) = solve(problem);
+    let (reduced, lifter) = KPPreprocess.preprocess(problem);
+    let (actual, solution) = solve(&reduced);
+    assert_eq!(expected, actual);
+
+    let lifted = lifter.lift(&solution.unwrap());
+    assert_eq!(problem.nb_variables(), lifted.len());
+    for (i, d) in lifted.iter().enumerate() {
+        assert_eq!(i, d.variable.id());
+    }
+    let weight = lifted.iter().map(|d| problem.weight[d.variable.id()] * d.value as usize).sum::<usize>();
+    let profit = lifted.iter().map(|d| problem.profit[d.variable.id()] * d.value).sum::<isize>();
+    assert!(weight <= problem.capacity);
+    assert_eq!(expected, Some(profit));
+}
+
+#[test]
+fn preprocessing_removes_the_items_that_never_fit() {
+    let problem = Knapsack::new(10, vec![5, 100, 4, 3, 50], vec![4, 11, 5, 6, 20]);
+    let (reduced, lifter) = KPPreprocess.preprocess(&problem);
+    assert_eq!(3, reduced.nb_variables());
+    assert_eq!(2, lifter.forced().len());
+    check_preprocessing(&problem);
+}
+#[test]
+fn preprocessing_an_instance_where_nothing_fits() {
+    let problem = Knapsack::new(3, vec![5, 100], vec![4, 11]);
+    let (reduced, _) = KPPreprocess.preprocess(&problem);
+    assert_eq!(0, reduced.nb_variables());
+    check_preprocessing(&problem);
+}
+#[test]
+fn preprocessing_preserves_the_optimum_f1_l_d_kp_10_269() {
+    check_preprocessing(&read_instance(locate("f1_l-d_kp_10_269")).unwrap());
+}
+#[test]
+fn preprocessing_preserves_the_optimum_f8_l_d_kp_23_10000() {
+    check_preprocessing(&read_instance(locate("f8_l-d_kp_23_10000")).unwrap());
+}
+
+
+#[test]
+fn evaluating_the_optimal_solution_yields_the_optimum() {
+    let problem = read_instance(locate("f1_l-d_kp_10_269")).unwrap();
+    let (expected, solution) = solve(&problem);
+    assert_eq!(expected, evaluate(&problem, &solution.unwrap()).ok());
+}
+#[test]
+fn repairing_an_overloaded_candidate_yields_a_feasible_solution() {
+    let problem = read_instance(locate("f1_l-d_kp_10_269")).unwrap();
+    let candidate = (0..problem.nb_variables())
+        .map(|i| Decision{variable: Variable(i), value: 1})
+        .collect::<Vec<_>>();
+    assert!(matches!(evaluate(&problem, &candidate), Err(EvalError::Infeasible{..})));
+
+    let repaired = repair(&problem, &candidate).unwrap();
+    assert!(repaired.repaired_from.is_some());
+    assert_eq!(Ok(repaired.value), evaluate(&problem, &repaired.solution));
+}
+
+#[test]
+fn the_layer_filter_changes_neither_the_optimum_nor_the_explored_nodes() {
+    for (id, optimum) in [("f1_l-d_kp_10_269", 295), ("f10_l-d_kp_20_879", 1025), ("f8_l-d_kp_23_10000", 9767)] {
+        let without = solve_id_with_layer_filter(id, false);
+        let with    = solve_id_with_layer_filter(id, true);
+        assert_eq!(optimum, without.0);
+        assert_eq!(without, with);
+    }
+}
+
+#[test]
+fn the_golden_instances_pass_the_verification() {
+    assert_eq!(solve_id_verified("f9_l-d_kp_5_80"), 130);
+    assert_eq!(solve_id_verified("f7_l-d_kp_7_50"), 107);
+    assert_eq!(solve_id_verified("f3_l-d_kp_4_20"), 35);
+    assert_eq!(solve_id_verified("f4_l-d_kp_4_11"), 23);
+    assert_eq!(solve_id_verified("f10_l-d_kp_20_879"), 1025);
+}
 
 #[test]
 fn f9_l_d_kp_5_80() {
@@ -204,3 +392,66 @@ fn knappi_2_1000_1000_1() {
 fn knappi_2_500_1000_1() {
     assert_eq!(solve_id("knapPI_2_500_1000_1"), 4566);
 }
+
+#[test]
+fn the_value_found_with_a_slack_is_within_that_slack_of_the_optimum() {
+    for (id, optimum) in [("f1_l-d_kp_10_269", 295), ("f8_l-d_kp_23_10000", 9767), ("knapPI_1_100_1000_1", 9147), ("knapPI_3_200_1000_1", 2697)] {
+        assert_eq!(optimum, solve_id_with_slack(id, 0));
+        for slack in [1, 10, 100] {
+            assert!(solve_id_with_slack(id, slack) >= optimum - slack);
+        }
+    }
+}
+
+#[test]
+fn a_width_profile_preserves_the_optimum() {
+    let problem = read_instance(locate("f8_l-d_kp_23_10000")).unwrap()
+        .with_width_profile(Some(vec![1, 1, 1, 4, 4, 4, 4, 1]));
+    let relaxation = KPRelax{pb: &problem};
+    let ranking = KPRanking;
+
+    let width = FixedWidth(2);
+    let dominance = SimpleDominanceChecker::new(KPDominance, problem.nb_variables());
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+
+    let mut solver = DefaultCachingSolver::new(
+        &problem, 
+        &relaxation, 
+        &ranking, 
+        &width, 
+        &dominance,
+        &cutoff, 
+        &mut fringe,
+    );
+
+    let Completion { best_value , ..} = solver.maximize();
+    assert_eq!(Some(9767), best_value);
+}
+
+#[test]
+fn ranking_the_subproblems_by_ub_per_cost_preserves_the_optimum() {
+    let problem = read_instance(locate("f8_l-d_kp_23_10000")).unwrap();
+    let relaxation = KPRelax{pb: &problem};
+    let ranking = KPRanking;
+
+    let width = FixedWidth(2);
+    let dominance = SimpleDominanceChecker::new(KPDominance, problem.nb_variables());
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(BestUbPerCost::new(&ranking, problem.nb_variables(), 2, 10.0));
+
+    let mut solver = SeqCachingSolverLel::new(
+        &problem, 
+        &relaxation, 
+        &ranking, 
+        &width, 
+        &dominance,
+        &cutoff, 
+        &mut fringe,
+    );
+
+    let Completion { is_exact, best_value, .. } = solver.maximize();
+    assert!(is_exact);
+    assert_eq!(Some(9767), best_value);
+    assert_eq!(9767, solver.best_upper_bound());
+}
@@ -0,0 +1,83 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: solving a tiny knapsack instance
+//!
+//! This module walks through the resolution of a knapsack instance which is
+//! small enough to be checked by hand: a sack of capacity 10 and four items.
+//!
+//! | item | profit | weight |
+//! |------|--------|--------|
+//! |   0  |   12   |    6   |
+//! |   1  |   11   |    5   |
+//! |   2  |   11   |    5   |
+//! |   3  |    5   |    2   |
+//!
+//! The item 0 has the best profit/weight ratio, yet the best load takes the
+//! items 1 and 2 (weight 10, profit 22). Every step
+//! of `walkthrough()` asserts what a user typically wants to look at while 
+//! the solver runs: the bound derived from the root, the first solution 
+//! found, the final gap and the decoded solution.
+
+use ddo::*;
+
+use crate::{KPRanking, KPRelax, Knapsack, TAKE_IT};
+
+/// Builds the model of the tiny instance, solves it with an explicitly 
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation and its ranking
+    let problem = Knapsack::new(10, vec![12, 11, 11, 5], vec![6, 5, 5, 2]);
+    let relaxation = KPRelax { pb: &problem };
+    let ranking = KPRanking;
+
+    // 2. The solver: no dominance, no cutoff and a single node per layer
+    //    so that the root must be restricted and relaxed
+    let width = FixedWidth(1);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above
+    assert_eq!(Some(39), solver.root_bound());
+    // 4. The restricted DD of the root keeps the most valuable node of each
+    //    layer: it takes the items 3 and 1, which is the first incumbent
+    assert_eq!(Some(16), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(22), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution tells which items go in the sack
+    let mut taken = solver.best_solution().unwrap().iter()
+        .filter(|d| d.value == TAKE_IT)
+        .map(|d| d.variable.id())
+        .collect::<Vec<_>>();
+    taken.sort_unstable();
+    assert_eq!(vec![1, 2], taken);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
@@ -55,24 +55,26 @@ pub fn read_instance<P: AsRef<Path>>(fname: P) -> Result<Lcs, Error> {
         return Err(Error::Format);
     }
     
-    let n_strings = params[0];
     let n_chars = params[1];
 
     let mut strings = vec![];
-    let mut alphabet = BTreeSet::default();
-    
-    for (i, line) in (&mut lines).enumerate() {
+    for line in &mut lines {
         let line = line?;
         let mut data = line.split_ascii_whitespace();
 
         data.next().ok_or(Error::Format)?; // string length
         strings.push(data.next().ok_or(Error::Format)?.to_string());
-
-        for char in strings[i].chars() {
-            alphabet.insert(char);
-        }
     }
 
+    Ok(instance_from_strings(strings, n_chars))
+}
+
+/// Creates the lcs instance bearing on the given strings, whose characters
+/// are drawn from an alphabet of `n_chars` symbols
+pub fn instance_from_strings(mut strings: Vec<String>, n_chars: usize) -> Lcs {
+    let n_strings = strings.len();
+    let alphabet = strings.iter().flat_map(|s| s.chars()).collect::<BTreeSet<_>>();
+
     let mut mapping = BTreeMap::default();
     let mut inverse_mapping = BTreeMap::default();
     for (i, char) in alphabet.iter().enumerate() {
@@ -121,7 +123,7 @@ pub fn read_instance<P: AsRef<Path>>(fname: P) -> Result<Lcs, Error> {
         rem.push(rem_for_string);
     }
 
-    Ok(Lcs::new(
+    Lcs::new(
         strings,
         n_strings,
         n_chars,
@@ -129,5 +131,5 @@ pub fn read_instance<P: AsRef<Path>>(fname: P) -> Result<Lcs, Error> {
         next,
         rem,
         mapping,
-    ))
+    )
 }
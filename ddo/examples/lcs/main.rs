@@ -25,7 +25,7 @@ use std::{time::{Duration, Instant}};
 use clap::Parser;
 use ddo::*;
 
-use crate::{io_utils::read_instance, model::{LcsRelax, LcsRanking, GO_TO_END_OF_STRINGS}, dominance::LcsDominance};
+use crate::{io_utils::read_instance, model::{Lcs, LcsRelax, LcsRanking, GO_TO_END_OF_STRINGS}, dominance::LcsDominance};
 
 mod model;
 mod dp;
@@ -34,6 +34,8 @@ mod io_utils;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
 
 /// This structure uses `clap-derive` annotations and define the arguments that can
 /// be passed on to the executable solver.
@@ -75,6 +77,21 @@ fn cutoff(timeout: Option<u64>) -> Box<dyn Cutoff + Send + Sync> {
 
 /// This is your executable's entry point. It is the place where all the pieces are put together
 /// to create a fast an effective solver for the knapsack problem.
+/// Spells the common subsequence which is described by the given solution
+fn common_subsequence(problem: &Lcs, solution: Option<Vec<Decision>>) -> String {
+    let best_solution: Option<Vec<_>>  = solution
+        .map(|mut decisions|{
+            decisions.sort_unstable_by_key(|d| d.variable.id());
+            decisions.iter()
+                .map(|d| d.value)
+                .collect()
+        });
+    best_solution.unwrap_or_default().iter()
+        .filter(|char| **char != GO_TO_END_OF_STRINGS)
+        .map(|char| *problem.chars.get(&(*char as usize)).unwrap()).collect::<Vec<char>>()
+        .iter().collect::<String>()
+}
+
 fn main() {
     let args = Args::parse();
     let fname = &args.fname;
@@ -100,23 +117,13 @@ fn main() {
     );
 
     let start = Instant::now();
-    let Completion{ is_exact, best_value } = solver.maximize();
+    let Completion{ is_exact, best_value, termination, .. } = solver.maximize();
     
     let duration = start.elapsed();
     let upper_bound = solver.best_upper_bound();
     let lower_bound = solver.best_lower_bound();
     let gap = solver.gap();
-    let best_solution: Option<Vec<_>>  = solver.best_solution()
-        .map(|mut decisions|{
-            decisions.sort_unstable_by_key(|d| d.variable.id());
-            decisions.iter()
-                .map(|d| d.value)
-                .collect()
-        });
-    let best_solution = best_solution.unwrap_or_default().iter()
-        .filter(|char| **char != GO_TO_END_OF_STRINGS)
-        .map(|char| *problem.chars.get(&(*char as usize)).unwrap()).collect::<Vec<char>>()
-        .iter().collect::<String>();
+    let best_solution = common_subsequence(&problem, solver.best_solution());
     
     println!("Duration:   {:.3} seconds", duration.as_secs_f32());
     println!("Objective:  {}",            best_value.unwrap_or(-1));
@@ -124,5 +131,7 @@ fn main() {
     println!("Lower Bnd:  {}",            lower_bound);
     println!("Gap:        {:.3}",         gap);
     println!("Aborted:    {}",            !is_exact);
+    println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+    println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
     println!("Solution:   {}",            best_solution);
 }
@@ -0,0 +1,80 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: the longest common subsequence of three short strings
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand. It looks for the longest subsequence which
+//! is common to the following strings:
+//!
+//! ```text
+//!   A B C B D A B
+//!     B D C A B A
+//!         B C B A
+//! ```
+//!
+//! The third string is itself a subsequence of the other two, and no longer
+//! common subsequence exists: the optimum is "BCBA" (length 4). Every step of
+//! `walkthrough()` asserts what a user typically wants to look at while the
+//! solver runs: the bound derived from the root, the first solution found,
+//! the final gap and the decoded solution.
+
+use ddo::*;
+
+use crate::{common_subsequence, dominance::LcsDominance, io_utils::instance_from_strings, model::{LcsRanking, LcsRelax}};
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation, its ranking and its dominance relation
+    let strings = ["ABCBDAB", "BDCABA", "BCBA"].map(String::from).to_vec();
+    let problem = instance_from_strings(strings, 4);
+    let relaxation = LcsRelax::new(&problem);
+    let ranking = LcsRanking;
+    let dominance = SimpleDominanceChecker::new(LcsDominance, problem.nb_variables());
+
+    // 2. The solver: no cutoff and a single node per layer so that the root
+    //    must be restricted and relaxed
+    let width = FixedWidth(1);
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above. The
+    //    pairwise dp tables make that bound tight on this instance
+    assert_eq!(Some(4), solver.root_bound());
+    // 4. The restricted DD of the root finds a solution which meets that
+    //    bound: the optimum is known right after the root is processed
+    assert_eq!(Some(4), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(4), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution spells the common subsequence
+    assert_eq!("BCBA", common_subsequence(&problem, solver.best_solution()));
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
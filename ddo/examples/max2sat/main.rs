@@ -13,6 +13,8 @@ mod model;
 mod relax;
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
 
 /// Solve max2sat instance
 #[derive(Debug, Parser)]
@@ -50,7 +52,7 @@ fn main() {
     );
 
         let start = Instant::now();
-        let Completion{ is_exact, best_value } = solver.maximize();
+        let Completion{ is_exact, best_value, termination, .. } = solver.maximize();
         
         let duration = start.elapsed();
         let upper_bound = solver.best_upper_bound();
@@ -67,6 +69,8 @@ fn main() {
         println!("Lower Bnd:  {}",            lower_bound);
         println!("Gap:        {:.3}",         gap);
         println!("Aborted:    {}",            !is_exact);
+        println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+        println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
         println!("Cost:       {:?}",          solution_cost(&problem, &solver.best_solution()));
         println!("Solution:   {:?}",          best_solution.unwrap_or_default());
 }
@@ -0,0 +1,90 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: satisfying a tiny weighted 2-sat formula
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand. It bears on three variables x1, x2 and x3
+//! and comprises the following weighted clauses (17 in total):
+//!
+//! | clause         | weight |
+//! |----------------|--------|
+//! | x1 \/ x2       | 4      |
+//! | -x1 \/ x3      | 3      |
+//! | -x2 \/ -x3     | 5      |
+//! | x1             | 2      |
+//! | x2 \/ -x3      | 1      |
+//! | -x1 \/ -x2     | 2      |
+//!
+//! The best assignment (x1, -x2, x3) satisfies all clauses but `x2 \/ -x3`
+//! and hence weighs 16. Every step of `walkthrough()` asserts what a user
+//! typically wants to look at while the solver runs: the bound derived from
+//! the root, the first solution found, the final gap and the decoded
+//! solution.
+
+use ddo::*;
+
+use crate::{data::{BinaryClause, Weighed2Sat}, heuristics::Max2SatRanking, model::{Max2Sat, v}, relax::Max2SatRelax};
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation and its ranking
+    let mut instance = Weighed2Sat { nb_vars: 3, ..Default::default() };
+    for (x, y, w) in [(1, 2, 4), (-1, 3, 3), (-2, -3, 5), (1, 1, 2), (2, -3, 1), (-1, -2, 2)] {
+        instance.weights.insert(BinaryClause::new(x, y), w);
+    }
+    let problem = Max2Sat::new(instance);
+    let relaxation = Max2SatRelax(&problem);
+    let ranking = Max2SatRanking;
+
+    // 2. The solver: no dominance, no cutoff and a single node per layer
+    //    so that the root must be restricted and relaxed
+    let width = FixedWidth(1);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above. On this
+    //    instance, that bound happens to be tight
+    assert_eq!(Some(16), solver.root_bound());
+    // 4. The restricted DD of the root finds a solution which meets that
+    //    bound: the optimum is known right after the root is processed
+    assert_eq!(Some(16), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(16), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution is decoded as the list of literals which are true
+    let literals = solver.best_solution().map(|mut decisions| {
+        decisions.sort_unstable_by_key(|d| d.variable.id());
+        decisions.iter().map(|d| v(d.variable) * d.value).collect::<Vec<_>>()
+    });
+    assert_eq!(Some(vec![1, -2, 3]), literals);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
@@ -17,18 +17,36 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
+//! The instances of the max cut problem. The weights of the edges are stored
+//! in one flat, row-major, n×n buffer which is allocated once (when the number
+//! of vertices is known) and filled in place by the loaders. Two formats are
+//! supported:
+//!
+//! * the text format of the instances in `resources/mcp`: a header line 
+//!   `n m` followed by one line `x y w` per edge (vertices start at 1), and
+//!   comment lines starting with `c`;
+//! * the binary NPY format of numpy (version 1.0): a two dimensional, C order,
+//!   array of little endian 64 (or 32) bits integers. It is what `np.save` 
+//!   writes for a square integer matrix, and it skips text parsing entirely.
+
 use std::fs::File;
-use std::io::{BufRead, BufReader, Lines, Read};
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Read, Write};
 use std::ops::{Index, IndexMut};
-
-use regex::Regex;
+use std::path::Path;
 
 use ddo::Variable;
 
+/// The magic string opening an NPY file
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+/// The size of the chunks in which the weights of an NPY file are read
+const NPY_CHUNK: usize = 1 << 16;
+
 /// The graph is represented by its adjacency matrix
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Graph {
     pub nb_vertices: usize,
+    /// The weights of the edges, row after row: the weight of the edge (x, y)
+    /// sits at offset `x * nb_vertices + y`
     pub adj_matrix : Vec<isize>
 }
 
@@ -46,43 +64,189 @@ impl Graph {
         self[(x, y)] = w;
         self[(y, x)] = w;
     }
+    /// Returns the weights of the edges incident to `x` (indexed by the other
+    /// endpoint of the edge)
+    pub fn row(&self, x: usize) -> &[isize] {
+        let n = self.nb_vertices;
+        &self.adj_matrix[x * n..(x + 1) * n]
+    }
 
-    pub fn from_lines<B: BufRead>(lines: Lines<B>) -> Graph {
-        let graph   = Regex::new(r"^(?P<vars>\d+)\s+(?P<edges>\d+)$").unwrap();
-        let edge    = Regex::new(r"^(?P<src>\d+)\s+(?P<dst>\d+)\s+(?P<w>-?\d+)$").unwrap();
+    /// Generates a pseudo random (yet deterministic) dense instance with `n`
+    /// vertices, where about half of the pairs of vertices are connected by
+    /// an edge weighing between -10 and 10
+    pub fn synthetic(n: usize, seed: u64) -> Graph {
+        let mut graph = Graph::new(n);
+        let mut rng = seed | 1;
+        for x in 0..n {
+            for y in x + 1..n {
+                // xorshift64
+                rng ^= rng << 13;
+                rng ^= rng >> 7;
+                rng ^= rng << 17;
+                if rng & 1 == 1 {
+                    graph.add_bidir_edge(x, y, (rng >> 1) as isize % 21 - 10);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Loads an instance file, be it in the text or in the NPY format (the 
+    /// format is told by the first bytes of the file)
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Graph> {
+        let mut reader = BufReader::new(File::open(path)?);
+        if reader.fill_buf()?.starts_with(NPY_MAGIC) {
+            Self::read_npy(reader)
+        } else {
+            Self::read_text(reader)
+        }
+    }
 
-        let mut result = Graph::new(0);
+    /// Parses an instance in the text format. The lines are read one at a time
+    /// in the same buffer, and the weights are written straight into the 
+    /// adjacency matrix.
+    pub fn read_text<B: BufRead>(mut reader: B) -> io::Result<Graph> {
+        let mut graph = None;
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            Self::parse_line(&mut graph, &line)?;
+            line.clear();
+        }
+        Ok(graph.unwrap_or_else(|| Graph::new(0)))
+    }
+    pub fn from_lines<B: BufRead>(lines: Lines<B>) -> Graph {
+        let mut graph = None;
         for line in lines {
-            let line = line.unwrap();
-            let line = line.trim();
+            Self::parse_line(&mut graph, &line.unwrap()).unwrap();
+        }
+        graph.unwrap_or_else(|| Graph::new(0))
+    }
+    /// Writes this instance in the text format
+    pub fn write_text<W: Write>(&self, out: W) -> io::Result<()> {
+        let n = self.nb_vertices;
+        let edges = (0..n).flat_map(|x| (x + 1..n).map(move |y| (x, y)))
+            .filter(|&(x, y)| self[(x, y)] != 0);
+        let mut out = BufWriter::new(out);
+        writeln!(out, "{n} {}", edges.clone().count())?;
+        for (x, y) in edges {
+            writeln!(out, "{} {} {}", x + 1, y + 1, self[(x, y)])?;
+        }
+        out.flush()
+    }
 
-            if line.is_empty() {
-                continue;
-            }
-            // skip comments
-            if line.starts_with("c ") {
-                continue;
-            }
+    /// Reads an instance in the NPY format. The adjacency matrix is allocated
+    /// once, and the weights are decoded in place, one chunk at a time.
+    pub fn read_npy<R: Read>(mut input: R) -> io::Result<Graph> {
+        let mut preamble = [0_u8; 10];
+        input.read_exact(&mut preamble)?;
+        if &preamble[..6] != NPY_MAGIC || preamble[6] != 1 {
+            return Err(invalid("not an NPY file (version 1.0)"));
+        }
+        let mut header = vec![0_u8; u16::from_le_bytes([preamble[8], preamble[9]]) as usize];
+        input.read_exact(&mut header)?;
+        let (n, width) = parse_npy_header(&String::from_utf8_lossy(&header))?;
 
-            if let Some(caps) = graph.captures(line) {
-                result = Graph::new(caps["vars"].to_string().parse::<usize>().unwrap());
-                continue;
+        let mut graph = Graph::new(n);
+        let mut chunk = vec![0_u8; NPY_CHUNK * width];
+        for weights in graph.adj_matrix.chunks_mut(NPY_CHUNK) {
+            let bytes = &mut chunk[..weights.len() * width];
+            input.read_exact(bytes)?;
+            for (w, b) in weights.iter_mut().zip(bytes.chunks_exact(width)) {
+                *w = match width {
+                    4 => i32::from_le_bytes(b.try_into().unwrap()) as isize,
+                    _ => i64::from_le_bytes(b.try_into().unwrap()) as isize,
+                };
             }
+        }
+        Ok(graph)
+    }
+    /// Writes this instance in the NPY format (as 64 bits integers)
+    pub fn write_npy<W: Write>(&self, out: W) -> io::Result<()> {
+        let n = self.nb_vertices;
+        let mut header = format!("{{'descr': '<i8', 'fortran_order': False, 'shape': ({n}, {n}), }}");
+        // the data must start at an offset which is a multiple of 64
+        while !(NPY_MAGIC.len() + 4 + header.len() + 1).is_multiple_of(64) {
+            header.push(' ');
+        }
+        header.push('\n');
 
-            if let Some(caps) = edge.captures(line) {
-                let x = caps["src"].to_string().parse::<usize>().unwrap() - 1;
-                let y = caps["dst"].to_string().parse::<usize>().unwrap() - 1;
-                let w = caps["w"].to_string().parse::<isize>().unwrap();
-                result.add_bidir_edge(x, y, w);
-            }
+        let mut out = BufWriter::new(out);
+        out.write_all(NPY_MAGIC)?;
+        out.write_all(&[1, 0])?;
+        out.write_all(&(header.len() as u16).to_le_bytes())?;
+        out.write_all(header.as_bytes())?;
+        for w in self.adj_matrix.iter() {
+            out.write_all(&(*w as i64).to_le_bytes())?;
         }
-        result
+        out.flush()
     }
 
+    /// Parses one line of the text format into the given graph (which is 
+    /// created by the header line). Like comments, the lines which are neither
+    /// a header nor an edge are ignored.
+    fn parse_line(graph: &mut Option<Graph>, line: &str) -> io::Result<()> {
+        let mut tokens = line.split_ascii_whitespace().map(|t| t.parse::<isize>().ok());
+        match (tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+            (Some(Some(n)), Some(Some(_)), None, _) if n >= 0 => {
+                *graph = Some(Graph::new(n as usize));
+            }
+            (Some(Some(x)), Some(Some(y)), Some(Some(w)), None) if x > 0 && y > 0 => {
+                let g = graph.as_mut().ok_or_else(|| invalid("an edge comes before the header"))?;
+                let (x, y) = (x as usize - 1, y as usize - 1);
+                if x >= g.nb_vertices || y >= g.nb_vertices {
+                    return Err(invalid(&format!("invalid edge '{}'", line.trim())));
+                }
+                g.add_bidir_edge(x, y, w);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
     fn offset(&self, x: usize, y: usize) -> usize {
         x * self.nb_vertices + y
     }
 }
+
+/// Returns the number of rows of the square matrix described by the given 
+/// NPY header, along with the size of its elements (in bytes)
+fn parse_npy_header(header: &str) -> io::Result<(usize, usize)> {
+    let entry = |key| npy_entry(header, key);
+    let descr = entry("descr")?;
+    let width = if descr.starts_with("'<i8'") {
+        8
+    } else if descr.starts_with("'<i4'") {
+        4
+    } else {
+        return Err(invalid("the NPY matrix must hold little endian integers ('<i8' or '<i4')"));
+    };
+    if !entry("fortran_order")?.starts_with("False") {
+        return Err(invalid("the NPY matrix must be stored in C (row-major) order"));
+    }
+    let dims = entry("shape")?
+        .strip_prefix('(')
+        .and_then(|shape| shape.split_once(')'))
+        .map(|(dims, _)| dims)
+        .ok_or_else(|| invalid("invalid shape in the NPY header"))?
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| d.parse::<usize>().map_err(|_| invalid("invalid shape in the NPY header")))
+        .collect::<io::Result<Vec<_>>>()?;
+    match dims.as_slice() {
+        [n, m] if n == m => Ok((*n, width)),
+        _ => Err(invalid("the NPY matrix must be square")),
+    }
+}
+/// Returns the part of the header which follows the given key
+fn npy_entry<'h>(header: &'h str, key: &str) -> io::Result<&'h str> {
+    let start = header.find(&format!("'{key}':"))
+        .ok_or_else(|| invalid(&format!("no '{key}' in the NPY header")))?;
+    Ok(header[start + key.len() + 3..].trim_start())
+}
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
 impl Index<(Variable, Variable)> for Graph {
     type Output = isize;
     fn index(&self, xy: (Variable, Variable)) -> &isize {
@@ -114,7 +278,7 @@ impl From<File> for Graph {
 }
 impl <S: Read> From<BufReader<S>> for Graph {
     fn from(buf: BufReader<S>) -> Graph {
-        buf.lines().into()
+        Graph::read_text(buf).expect("invalid instance")
     }
 }
 impl <B: BufRead> From<Lines<B>> for Graph {
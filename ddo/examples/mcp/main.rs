@@ -1,34 +1,58 @@
-use std::{time::{Duration, Instant}, fs::File};
+use std::{time::{Duration, Instant}, fs::{self, File}, process::Command, env};
 
 use clap::Parser;
 use ddo::*;
 
-use crate::{graph::Graph, model::{Mcp, McpRanking}, relax::McpRelax};
+use crate::{graph::Graph, model::{Mcp, McpRanking, Side}, relax::McpRelax};
 
 mod graph;
 mod model;
 mod relax;
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
 
 /// Solve max2sat instance
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
 struct Params {
-    /// the instance file
-    #[arg(short, long)]
-    file: String,
+    /// the instance file (in the text or in the NPY format)
+    #[arg(short, long, required_unless_present = "compare_loading")]
+    file: Option<String>,
     /// maximum width in a layer
     #[arg(short, long)]
     width: Option<usize>,
     /// max time to find the solution
     #[arg(short, long)]
     timeout: Option<u64>,
+    /// compares the time and memory it takes to load a synthetic instance 
+    /// with the given number of vertices from the text and NPY formats
+    #[arg(long, value_name = "N")]
+    compare_loading: Option<usize>,
+    /// only loads the instance file, then reports the time and peak memory
+    /// it took (this is what --compare-loading runs for each format)
+    #[arg(long, hide = true)]
+    load_only: bool,
+    /// seeds the search with the best of N restricted compilations of the 
+    /// root problem, whose widths double from 1 (see `PresolveConfig`)
+    #[arg(long, value_name = "N")]
+    presolve: Option<usize>,
 }
 
 fn main() {
-    let Params{file, width, timeout} = Params::parse();
-    let graph = Graph::from(File::open(file).expect("could not open file"));
+    let Params{file, width, timeout, compare_loading, load_only, presolve} = Params::parse();
+    if let Some(n) = compare_loading {
+        compare_loading_times(n);
+        return;
+    }
+    let file = file.expect("an instance file is required");
+    let start = Instant::now();
+    let graph = Graph::load(&file).expect("could not load the instance");
+    if load_only {
+        println!("{:.3} {}", start.elapsed().as_secs_f32(), peak_memory().unwrap_or_else(|| "n/a".to_string()));
+        return;
+    }
     let problem = Mcp::from(graph);
     let relax = McpRelax::new(&problem);
     let rank = McpRanking;
@@ -46,15 +70,23 @@ fn main() {
         cutoff.as_ref(), 
         &mut fringe,
     );
+    if let Some(iterations) = presolve {
+        solver = solver.with_presolve(PresolveConfig::doubling(iterations, 1, 0));
+    }
 
         let start = Instant::now();
-        let Completion{ is_exact, best_value } = solver.maximize();
+        let Completion{ is_exact, best_value, termination, .. } = solver.maximize();
         
         let duration = start.elapsed();
         let upper_bound = solver.best_upper_bound();
         let lower_bound = solver.best_lower_bound();
         let gap = solver.gap();
-        let best_solution  = solver.best_solution();
+        let best_solution  = solver.best_solution()
+            .and_then(|mut decisions| {
+                decisions.sort_unstable_by_key(|d| d.variable.id());
+                decisions.typed_decisions(&EnumCodec::<Side>::new())
+            })
+            .map(|decisions| decisions.iter().map(|d| d.value).collect::<Vec<_>>());
     
         println!("Duration:   {:.3} seconds", duration.as_secs_f32());
         println!("Objective:  {}",            best_value.unwrap_or(-1));
@@ -62,9 +94,50 @@ fn main() {
         println!("Lower Bnd:  {}",            lower_bound);
         println!("Gap:        {:.3}",         gap);
         println!("Aborted:    {}",            !is_exact);
+        println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+        println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
+        if let Some(record) = solver.presolve_record() {
+            println!("Presolve:   {}",        record);
+        }
         println!("Solution:   {:?}",          best_solution.unwrap_or_default());
 }
 
+/// Writes a synthetic instance with n vertices in both formats, then loads
+/// each of them in a fresh process (so that their peak memory usages do not
+/// interfere) and reports how long and how much memory it took.
+fn compare_loading_times(n: usize) {
+    let dir = env::temp_dir().join(format!("ddo-mcp-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("could not create a temporary directory");
+    let graph = Graph::synthetic(n, 0x5eed);
+    let text = dir.join("instance.mcp");
+    let npy = dir.join("instance.npy");
+    graph.write_text(File::create(&text).expect("could not create file")).expect("could not write instance");
+    graph.write_npy(File::create(&npy).expect("could not create file")).expect("could not write instance");
+    drop(graph);
+
+    let exe = env::current_exe().expect("could not locate the executable");
+    println!("{:<8} {:>12} {:>10} {:>12}", "Format", "Size (MiB)", "Time (s)", "Peak memory");
+    for (format, path) in [("text", &text), ("npy", &npy)] {
+        let size = fs::metadata(path).map(|m| m.len()).unwrap_or_default();
+        let out = Command::new(&exe)
+            .arg("--load-only").arg("--file").arg(path)
+            .output()
+            .expect("could not load the instance");
+        let out = String::from_utf8_lossy(&out.stdout);
+        let mut report = out.split_whitespace();
+        println!("{:<8} {:>12.1} {:>10} {:>12}", 
+            format, size as f64 / (1024.0 * 1024.0),
+            report.next().unwrap_or("n/a"), report.next().unwrap_or("n/a"));
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
+/// Returns the peak resident memory of this process (when the platform tells it)
+fn peak_memory() -> Option<String> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let peak = status.lines().find_map(|l| l.strip_prefix("VmHWM:"))?;
+    Some(peak.split_whitespace().collect::<Vec<_>>().join(""))
+}
+
 fn cutoff(timeout: Option<u64>) -> Box<dyn Cutoff + Send + Sync> {
     if let Some(t) = timeout {
         Box::new(TimeBudget::new(Duration::from_secs(t)))
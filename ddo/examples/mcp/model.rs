@@ -26,14 +26,28 @@ use ddo::*;
 use crate::graph::Graph;
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "checkpoint", derive(serde::Serialize, serde::Deserialize))]
 pub struct McpState {
     pub benef  : Vec<isize>,
     pub depth  : u16,
+    /// A checksum of the benefits vector which lets us tell most distinct 
+    /// states apart without comparing their benefits element by element
+    pub checksum: u64,
+}
+impl McpState {
+    pub fn new(depth: u16, benef: Vec<isize>) -> Self {
+        let checksum = benef.iter()
+            .fold(0xcbf29ce484222325_u64, |acc, b| (acc ^ *b as u64).wrapping_mul(0x100000001b3));
+        McpState { benef, depth, checksum }
+    }
 }
 
-// Define a few constants to improve readability
-const S      : isize = 1;
-const T      : isize =-1;
+categorical! {
+    /// The side of the cut where a vertex is placed. The code of each side is
+    /// the sign it contributes to the benefits of the remaining vertices.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Side { S = 1, T = -1 }
+}
 
 #[derive(Debug, Clone)]
 pub struct Mcp {
@@ -50,7 +64,7 @@ impl Problem for Mcp {
     }
 
     fn initial_state(&self) -> McpState {
-        McpState {depth: 0, benef: vec![0; self.nb_variables()]}
+        McpState::new(0, vec![0; self.nb_variables()])
     }
 
     fn initial_value(&self) -> isize {
@@ -58,31 +72,33 @@ impl Problem for Mcp {
     }
 
     fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+        let codec = EnumCodec::new();
         if state.depth == 0 { 
-            f.apply(Decision{variable, value: S});
+            f.apply(TypedDecision{variable, value: Side::S}.encode(&codec));
         } else { 
-            f.apply(Decision{variable, value: S});
-            f.apply(Decision{variable, value: T});
+            f.apply(TypedDecision{variable, value: Side::S}.encode(&codec));
+            f.apply(TypedDecision{variable, value: Side::T}.encode(&codec));
         }
     }
 
     fn transition(&self, state: &McpState, d: Decision) -> McpState {
-        let mut benefits = vec![0; self.nb_variables()];
-        
-        let x = d.variable.id();
-        let n = self.nb_variables();
-        for (v, item) in benefits.iter_mut().enumerate().take(n).skip(x) { // for all unassigned vars
-            *item = state.benef[v] + d.value * self.graph[(d.variable, Variable(v))];
-        }
-        McpState {depth: 1 + state.depth, benef: benefits}
+        let mut scratch = McpScratch::default();
+        self.next_state(state, d, scratch.neighbours_of(&self.graph, d.variable))
     }
 
     fn transition_cost(&self, state: &McpState, _: &Self::State, d: Decision) -> isize {
-        match d.value {
-            S => if state.depth == 0 { 0 } else { self.branch_on_s(state, d) },
-            T => if state.depth == 0 { 0 } else { self.branch_on_t(state, d) },
-            _ => unreachable!()
-        }
+        let mut scratch = McpScratch::default();
+        self.branch_cost(state, d, scratch.neighbours_of(&self.graph, d.variable))
+    }
+
+    fn transition_with_ctx(&self, state: &McpState, d: Decision, ctx: &mut ProblemCtx) -> McpState {
+        let scratch = ctx.get::<McpScratch>();
+        self.next_state(state, d, scratch.neighbours_of(&self.graph, d.variable))
+    }
+
+    fn transition_cost_with_ctx(&self, state: &McpState, _: &Self::State, d: Decision, ctx: &mut ProblemCtx) -> isize {
+        let scratch = ctx.get::<McpScratch>();
+        self.branch_cost(state, d, scratch.neighbours_of(&self.graph, d.variable))
     }
 
     fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>)
@@ -93,36 +109,79 @@ impl Problem for Mcp {
             None
         }
     }
+    fn quick_neq(&self, a: &Self::State, b: &Self::State) -> bool {
+        a.checksum != b.checksum
+    }
+}
+/// The per-layer scratch of the model: the (non zero) edges between the vertex
+/// being decided and the vertices which are not assigned yet. They are the 
+/// same for all the nodes of a layer, and the vector holding them is reused 
+/// from one layer to the next.
+#[derive(Debug, Default)]
+pub struct McpScratch {
+    /// The vertex whose neighbours are listed
+    vertex: Option<Variable>,
+    /// The neighbours `(v, w_{kv})` of the vertex such that `v >= k`
+    pub neighbours: Vec<(usize, isize)>,
 }
+impl McpScratch {
+    /// Returns the neighbours of the given vertex which are not assigned yet
+    /// (listing them if they were not already listed)
+    pub fn neighbours_of(&mut self, graph: &Graph, x: Variable) -> &[(usize, isize)] {
+        if self.vertex != Some(x) {
+            let row = &graph.row(x.id())[x.id()..];
+            self.vertex = Some(x);
+            self.neighbours.clear();
+            self.neighbours.reserve(row.len());
+            self.neighbours.extend(row.iter().copied()
+                .enumerate()
+                .map(|(i, w)| (x.id() + i, w))
+                .filter(|(_, w)| *w != 0));
+        }
+        &self.neighbours
+    }
+}
+
 // private methods
 impl Mcp {
-    fn branch_on_s(&self, state: &McpState, d: Decision) -> isize {
-        let n = self.nb_variables();
+    fn next_state(&self, state: &McpState, d: Decision, neighbours: &[(usize, isize)]) -> McpState {
+        let mut benefits = vec![0; self.nb_variables()];
+        
         let x = d.variable.id();
-
+        benefits[x..].copy_from_slice(&state.benef[x..]); // for all unassigned vars
+        for (v, w) in neighbours.iter().copied() {
+            benefits[v] += d.value * w;
+        }
+        McpState::new(1 + state.depth, benefits)
+    }
+    fn branch_cost(&self, state: &McpState, d: Decision, neighbours: &[(usize, isize)]) -> isize {
+        match Side::from_code(d.value) {
+            Some(Side::S) => if state.depth == 0 { 0 } else { self.branch_on_s(state, d, neighbours) },
+            Some(Side::T) => if state.depth == 0 { 0 } else { self.branch_on_t(state, d, neighbours) },
+            None          => unreachable!()
+        }
+    }
+    // The edges which are not listed among the neighbours have a zero weight:
+    // they contribute nothing to the sums below.
+    fn branch_on_s(&self, state: &McpState, d: Decision, neighbours: &[(usize, isize)]) -> isize {
         // The \( (- s^k_k)^+ \) component
         let res = max(0, -state.benef[d.variable.id()]);
         // The \( \sum_{l > k, s^k_l w_{kl} \le 0} \min\left\{ |s^k_l|, |w_{kl}| \right\} \)
         let mut sum = 0;
-        for v in x..n {
+        for (v, wkl) in neighbours.iter().copied() {
             let skl = state.benef[v];
-            let wkl = self.graph[(d.variable, Variable(v))];
 
             if skl * wkl <= 0 { sum += min(skl.abs(), wkl.abs()); }
         }
         res + sum
     }
-    fn branch_on_t(&self, state: &McpState, d: Decision) -> isize {
-        let n = self.nb_variables();
-        let x = d.variable.id();
-
+    fn branch_on_t(&self, state: &McpState, d: Decision, neighbours: &[(usize, isize)]) -> isize {
         // The \( (s^k_k)^+ \) component
         let res = max(0, state.benef[d.variable.id()]);
         // The \( \sum_{l > k, s^k_l w_{kl} \le 0} \min\left\{ |s^k_l|, |w_{kl}| \right\} \)
         let mut sum = 0;
-        for v in x..n {
+        for (v, wkl) in neighbours.iter().copied() {
             let skl = state.benef[v];
-            let wkl = self.graph[(d.variable, Variable(v))];
 
             if skl * wkl >= 0 { sum += min(skl.abs(), wkl.abs()); }
         }
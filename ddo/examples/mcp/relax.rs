@@ -68,12 +68,9 @@ impl <'a> McpRelax<'a> {
         let mut value = 0_isize;
         let n_vars = pb.nb_variables();
         for source in depth..n_vars {
-            for destination in source + 1 .. n_vars {
-                let weight = pb.graph[(source, destination)];
-                if weight > 0 {
-                    value += weight;
-                }
-            }
+            value += pb.graph.row(source)[source + 1..].iter()
+                .filter(|w| **w > 0)
+                .sum::<isize>();
         }
         value
     }
@@ -92,13 +89,11 @@ impl <'a> McpRelax<'a> {
     fn precompute_nk(pb: &Mcp, depth: usize) -> isize {
         let mut sum = 0_isize;
 
+        // the graph is symmetrical: the weight of (i, j) is also that of (j, i)
         for j in 0..depth {
-            for i in 0..j {
-               let w = pb.graph[(i, j)];
-               if w < 0 {
-                   sum += w;
-               }
-            }
+            sum += pb.graph.row(j)[..j].iter()
+                .filter(|w| **w < 0)
+                .sum::<isize>();
         }
 
         sum
@@ -145,7 +140,7 @@ impl McpRelax<'_> {
             *item = self.merge_substates(Variable(v), nodes);
         }
 
-        McpState{ depth: nodes[0].depth, benef: data }
+        McpState::new(nodes[0].depth, data)
     }
 
     fn merge_substates(&self, v: Variable, nodes: &[&McpState]) -> isize {
@@ -23,7 +23,7 @@ use std::{path::PathBuf, fs::File};
 
 use ddo::*;
 
-use crate::{graph::Graph, model::{Mcp, McpRanking}, relax::McpRelax};
+use crate::{graph::Graph, model::{Mcp, McpRanking, McpScratch, Side}, relax::McpRelax};
 
 
 fn locate(id: &str) -> PathBuf {
@@ -63,6 +63,90 @@ pub fn solve_id(id: &str) -> isize {
     best_value.map(|x| x).unwrap_or(-1)
 }
 
+/// Solves the given instance with the sequential solver when `nb_threads` is
+/// None, and with the parallel solver using that many threads otherwise
+fn solve_with(id: &str, nb_threads: Option<usize>) -> Option<isize> {
+    let graph = Graph::from(File::open(locate(id)).expect("could not open file"));
+    let problem = Mcp::from(graph);
+    let relaxation = McpRelax::new(&problem);
+    let ranking = McpRanking;
+
+    let width = NbUnassignedWidth(problem.nb_variables());
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+
+    let completion = match nb_threads {
+        None => SeqNoCachingSolverLel::new(
+            &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe).maximize(),
+        Some(n) => ParNoCachingSolverLel::custom(
+            &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe, n).maximize(),
+    };
+    assert!(completion.is_exact);
+    completion.best_value
+}
+
+/// Solves the given instance with the sequential solver, preempting the search
+/// every `hop` nodes. Each time, the search is saved to a checkpoint which is
+/// resumed by a brand new solver.
+#[cfg(feature = "checkpoint")]
+fn solve_by_hops(id: &str, hop: usize) -> Option<isize> {
+    let graph = Graph::from(File::open(locate(id)).expect("could not open file"));
+    let problem = Mcp::from(graph);
+    let relaxation = McpRelax::new(&problem);
+    let ranking = McpRanking;
+
+    let width = FixedWidth(2);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+
+    let mut checkpoint = vec![];
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    SeqNoCachingSolverLel::new(&problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe)
+        .with_checkpointing()
+        .checkpoint(&mut checkpoint)
+        .expect("could not save the search");
+    loop {
+        let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::new(
+            &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe)
+            .with_checkpointing();
+        solver.resume(&mut checkpoint.as_slice()).expect("could not resume the search");
+        solver.set_node_budget(Some(solver.explored() + hop));
+
+        let completion = solver.maximize();
+        if completion.is_exact {
+            return completion.best_value;
+        }
+        checkpoint.clear();
+        solver.checkpoint(&mut checkpoint).expect("could not save the search");
+    }
+}
+
+#[test]
+fn the_scratch_is_not_reallocated_in_steady_state() {
+    let graph = Graph::from(File::open(locate("mcp_n30_p0.1_000.mcp")).expect("could not open file"));
+    let problem = Mcp::from(graph);
+    let codec = EnumCodec::new();
+    let mut ctx = ProblemCtx::default();
+
+    let mut state = problem.initial_state();
+    let mut buffer = None;
+    for x in 0..problem.nb_variables() {
+        let side = if x % 2 == 0 { Side::S } else { Side::T };
+        let d = TypedDecision { variable: Variable(x), value: side }.encode(&codec);
+        let next = problem.transition_with_ctx(&state, d, &mut ctx);
+        let cost = problem.transition_cost_with_ctx(&state, &next, d, &mut ctx);
+        // the scratch yields the same transitions as the plain model
+        assert_eq!(problem.transition(&state, d), next);
+        assert_eq!(problem.transition_cost(&state, &next, d), cost);
+        // the neighbours of all the layers are listed in the same buffer
+        let ptr = ctx.get::<McpScratch>().neighbours.as_ptr();
+        assert_eq!(*buffer.get_or_insert(ptr), ptr);
+        state = next;
+    }
+}
+
 
 #[test]
 fn mcp_n30_p01_000() {
@@ -103,4 +187,78 @@ fn mcp_n30_p01_008() {
 #[test]
 fn mcp_n30_p01_009() {
     assert_eq!(solve_id("mcp_n30_p0.1_009.mcp"), 22);
-}
\ No newline at end of file
+}
+#[cfg(feature = "checkpoint")]
+#[test]
+fn resuming_from_checkpoints_reaches_the_same_optimum() {
+    for id in ["mcp_n30_p0.1_000.mcp", "mcp_n30_p0.1_004.mcp", "mcp_n30_p0.1_009.mcp"] {
+        assert_eq!(solve_with(id, None), solve_by_hops(id, 10), "{id}");
+    }
+}
+#[test]
+fn the_parallel_solver_matches_the_sequential_one() {
+    for id in ["mcp_n30_p0.1_000.mcp", "mcp_n30_p0.1_004.mcp", "mcp_n30_p0.1_009.mcp"] {
+        let optimum = solve_with(id, None);
+        for nb_threads in [1, 2, 4] {
+            assert_eq!(optimum, solve_with(id, Some(nb_threads)), "{id} with {nb_threads} threads");
+        }
+    }
+}
+#[test]
+fn the_npy_format_round_trips() {
+    for (n, seed) in [(0, 1), (1, 2), (17, 3), (100, 4)] {
+        let graph = Graph::synthetic(n, seed);
+        let mut bytes = vec![];
+        graph.write_npy(&mut bytes).unwrap();
+        assert_eq!(graph, Graph::read_npy(bytes.as_slice()).unwrap());
+    }
+}
+#[test]
+fn the_text_format_round_trips() {
+    let graph = Graph::synthetic(50, 7);
+    let mut bytes = vec![];
+    graph.write_text(&mut bytes).unwrap();
+    assert_eq!(graph, Graph::read_text(bytes.as_slice()).unwrap());
+}
+#[test]
+fn both_formats_load_the_same_instance() {
+    let path = locate("mcp_n30_p0.1_004.mcp");
+    let graph = Graph::from(File::open(&path).expect("could not open file"));
+    assert_eq!(graph, Graph::load(&path).unwrap());
+
+    let npy = std::env::temp_dir().join(format!("mcp_n30_p0.1_004-{}.npy", std::process::id()));
+    graph.write_npy(File::create(&npy).unwrap()).unwrap();
+    let loaded = Graph::load(&npy);
+    let _ = std::fs::remove_file(&npy);
+    assert_eq!(graph, loaded.unwrap());
+}
+#[test]
+fn the_npy_reader_accepts_32_bits_integers() {
+    let header = "{'descr': '<i4', 'fortran_order': False, 'shape': (2, 2), }\n";
+    let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    for w in [0_i32, -3, -3, 0] {
+        bytes.extend_from_slice(&w.to_le_bytes());
+    }
+    let graph = Graph::read_npy(bytes.as_slice()).unwrap();
+    assert_eq!(vec![0, -3, -3, 0], graph.adj_matrix);
+}
+#[test]
+fn the_npy_reader_rejects_invalid_files() {
+    let mut valid = vec![];
+    Graph::synthetic(5, 9).write_npy(&mut valid).unwrap();
+    // truncated data
+    assert!(Graph::read_npy(&valid[..valid.len() - 1]).is_err());
+    // bad magic
+    let mut bad = valid.clone();
+    bad[1] = b'X';
+    assert!(Graph::read_npy(bad.as_slice()).is_err());
+    // unsupported element type, fortran order and non square shape
+    for (from, to) in [("<i8", "<f8"), ("False", "True "), ("(5, 5)", "(5, 4)")] {
+        let at = valid.windows(from.len()).position(|w| w == from.as_bytes()).unwrap();
+        let mut bad = valid.clone();
+        bad[at..at + from.len()].copy_from_slice(to.as_bytes());
+        assert!(Graph::read_npy(bad.as_slice()).is_err(), "{to}");
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: cutting a tiny weighted graph
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand: a pentagon whose edges weigh 3, 2, 4, 1 and
+//! 5 plus a chord of weight -3 between the vertices 0 and 2.
+//!
+//! ```text
+//!            3           2
+//!      (0) ------ (1) ------ (2)
+//!       |  \_______-3_______/ |
+//!     5 |                     | 4
+//!       |                     |
+//!      (4) ------------------ (3)
+//!                  1
+//! ```
+//!
+//! The maximum cut separates {0, 2} from {1, 3, 4} and weighs 14: it cuts
+//! every edge but the lightest one of the pentagon and keeps the negative
+//! chord uncut. Every step of `walkthrough()` asserts what a user typically
+//! wants to look at while the solver runs: the bound derived from the root,
+//! the first solution found, the final gap and the decoded solution.
+
+use ddo::*;
+
+use crate::{graph::Graph, model::{Mcp, McpRanking, Side}, relax::McpRelax};
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation and its ranking
+    let mut graph = Graph::new(5);
+    graph.add_bidir_edge(0, 1, 3);
+    graph.add_bidir_edge(1, 2, 2);
+    graph.add_bidir_edge(2, 3, 4);
+    graph.add_bidir_edge(3, 4, 1);
+    graph.add_bidir_edge(4, 0, 5);
+    graph.add_bidir_edge(0, 2, -3);
+    let problem = Mcp::from(graph);
+    let relaxation = McpRelax::new(&problem);
+    let ranking = McpRanking;
+
+    // 2. The solver: no dominance, no cutoff and a single node per layer
+    //    so that the root must be restricted and relaxed
+    let width = FixedWidth(1);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above
+    assert_eq!(Some(15), solver.root_bound());
+    // 4. The restricted DD of the root already finds the optimum; what
+    //    remains of the search only serves to prove it
+    assert_eq!(Some(14), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(14), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution tells on which side of the cut each vertex lies. The
+    //    first vertex is always placed on the side S to break the symmetry.
+    let sides = solver.best_solution()
+        .and_then(|mut decisions| {
+            decisions.sort_unstable_by_key(|d| d.variable.id());
+            decisions.typed_decisions(&EnumCodec::<Side>::new())
+        })
+        .map(|decisions| decisions.iter().map(|d| d.value).collect::<Vec<_>>());
+    assert_eq!(Some(vec![Side::S, Side::T, Side::S, Side::T, Side::T]), sides);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
@@ -23,17 +23,21 @@
 use std::{cell::RefCell, path::Path, fs::File, io::{BufReader, BufRead}, num::ParseIntError, time::{Duration, Instant}};
 
 use bit_set::BitSet;
+use bit_vec::BitVec;
 use clap::Parser;
 use ddo::*;
 use regex::Regex;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
 
 /// This structure represents an instance of the Maximum Independent Set Problem. 
 /// It is this structure that implements a simple dynamic programming model for the
 /// MISP. In that model, the state is simply a bitset where each bit represents 
 /// a node that may be kept or left out of the MIS. 
+#[derive(Debug, Clone)]
 pub struct Misp {
     /// The number of variables in the problem instance
     nb_vars: usize,
@@ -48,6 +52,9 @@ pub struct Misp {
     /// from the underlying graph such that the resulting set is an independent set
     /// where the sum of the weights of selected vertices is maximum.
     weight: Vec<isize>,
+    /// The weight of the vertices that have been taken in the independent set
+    /// before the search even started (e.g. during preprocessing).
+    offset: isize,
 }
 
 /// A constant to mean take the node in the independent set.
@@ -71,7 +78,7 @@ impl Problem for Misp {
     }
 
     fn initial_value(&self) -> isize {
-        0
+        self.offset
     }
 
     fn transition(&self, state: &Self::State, decision: Decision) -> Self::State {
@@ -194,6 +201,24 @@ impl Relaxation for MispRelax<'_> {
 }
 
 
+/// The states of the MISP are the sets of vertices which may still be added
+/// to the independent set. Viewing them as bitsets is all it takes to relax the
+/// problem with a generic `SetRelaxation`: a union of such sets is admissible 
+/// ("still available" semantics), hence `SetRelaxation::union(&problem)` is an 
+/// alternative to the `MispRelax` above (minus its rough upper bound).
+impl StateAsBitset for Misp {
+    type State = BitSet;
+    type Word  = u32;
+
+    fn bits<'a>(&self, state: &'a BitSet) -> &'a [u32] {
+        state.get_ref().storage()
+    }
+
+    fn rebuild(&self, words: Vec<u32>) -> BitSet {
+        BitSet::from_bit_vec(BitVec::from_fn(words.len() * 32, |i| (words[i / 32] >> (i % 32)) & 1 == 1))
+    }
+}
+
 /// The last bit of information which we need to provide when implementing a ddo-based
 /// solver is a `StateRanking`. This is an heuristic which is used to select the most
 /// and least promising nodes as a means to only delete/merge the *least* promising nodes
@@ -209,6 +234,46 @@ impl StateRanking for MispRanking {
 }
 
 
+/// Optionally, the instance can be reduced before it is handed over to the solver.
+/// Here, the vertices having no neighbor at all are removed from the graph: the
+/// ones with a positive weight are greedily taken in the independent set (their 
+/// weight is accounted for in the initial value of the reduced problem) while the
+/// others are simply left out.
+pub struct MispPreprocess;
+impl Preprocess<Misp> for MispPreprocess {
+    type Reduced = Misp;
+    type Lifter  = ForcedDecisionsLifter;
+
+    fn preprocess(&self, problem: &Misp) -> (Misp, ForcedDecisionsLifter) {
+        let n = problem.nb_variables();
+        let mut mapping = vec![];
+        let mut forced  = vec![];
+        let mut offset  = problem.offset;
+
+        // 'neighbors' holds the complement of the adjacency lists (the vertex itself included)
+        for (i, complement) in problem.neighbors.iter().enumerate() {
+            if complement.len() == n {
+                let value = if problem.weight[i] > 0 { YES } else { NO };
+                offset += value * problem.weight[i];
+                forced.push(Decision { variable: Variable(i), value });
+            } else {
+                mapping.push(Variable(i));
+            }
+        }
+
+        let neighbors = mapping.iter()
+            .map(|x| mapping.iter().enumerate()
+                .filter(|(_, y)| problem.neighbors[x.id()].contains(y.id()))
+                .map(|(j, _)| j)
+                .collect())
+            .collect();
+        let weight = mapping.iter().map(|x| problem.weight[x.id()]).collect();
+
+        let reduced = Misp { nb_vars: mapping.len(), neighbors, weight, offset };
+        (reduced, ForcedDecisionsLifter::new(mapping, forced))
+    }
+}
+
 // #########################################################################################
 // # THE INFORMATION BEYOND THIS LINE IS NOT DIRECTLY RELATED TO THE IMPLEMENTATION OF     #
 // # A SOLVER BASED ON DDO. INSTEAD, THAT PORTION OF THE CODE CONTAINS GENERIC FUNCTION    #
@@ -233,6 +298,9 @@ struct Args {
     /// The maximum number of nodes per layer
     #[clap(short, long)]
     width: Option<usize>,
+    /// Whether or not the instance should be reduced before it is solved
+    #[clap(long)]
+    preprocess: bool,
 }
 
 /// This enumeration simply groups the kind of errors that might occur when parsing a
@@ -264,7 +332,7 @@ fn read_instance<P: AsRef<Path>>(fname: P) -> Result<Misp, Error> {
     let node_decl = Regex::new(r"^n\s+(?P<node>\d+)\s+(?P<weight>-?\d+)").unwrap();
     let edge_decl = Regex::new(r"^e\s+(?P<src>\d+)\s+(?P<dst>\d+)").unwrap();
 
-    let mut g = Misp{nb_vars: 0, neighbors: vec![], weight: vec![]};
+    let mut g = Misp{nb_vars: 0, neighbors: vec![], weight: vec![], offset: 0};
     for line in f.lines() {
         let line = line?;
         let line = line.trim();
@@ -341,7 +409,14 @@ fn cutoff(timeout: Option<u64>) -> Box<dyn Cutoff + Send + Sync> {
 fn main() {
     let args = Args::parse();
     let fname = &args.fname;
-    let problem = read_instance(fname).unwrap();
+    let original = read_instance(fname).unwrap();
+    let (problem, lifter): (Misp, Box<dyn SolutionLifter>) = if args.preprocess {
+        let (reduced, lifter) = MispPreprocess.preprocess(&original);
+        (reduced, Box::new(lifter))
+    } else {
+        let (reduced, lifter) = NoPreprocessing.preprocess(&original);
+        (reduced, Box::new(lifter))
+    };
     let relaxation = MispRelax {pb: &problem};
     let ranking = MispRanking;
 
@@ -363,13 +438,14 @@ fn main() {
     );
 
     let start = Instant::now();
-    let Completion{ is_exact, best_value } = solver.maximize();
+    let Completion{ is_exact, best_value, termination, .. } = solver.maximize();
     
     let duration = start.elapsed();
     let upper_bound = solver.best_upper_bound();
     let lower_bound = solver.best_lower_bound();
     let gap = solver.gap();
-    let best_solution: Option<Vec<_>>  = solver.best_solution().map(|mut decisions|{
+    let best_solution: Option<Vec<_>>  = solver.best_solution().map(|decisions|{
+        let mut decisions = lifter.lift(&decisions);
         decisions.sort_unstable_by_key(|d| d.variable.id());
         decisions.iter()
             .filter(|d| d.value == 1)
@@ -381,7 +457,7 @@ fn main() {
     if let Some(bs) = best_solution.as_ref() {
         for (i, a) in bs.iter().copied().enumerate() {
             for b in bs.iter().copied().skip(i+1) {
-                if !problem.neighbors[a].contains(b) {
+                if !original.neighbors[a].contains(b) {
                     println!("not a solution ! {a} -- {b}");
                 }
             }
@@ -394,5 +470,7 @@ fn main() {
     println!("Lower Bnd:  {}",            lower_bound);
     println!("Gap:        {:.3}",         gap);
     println!("Aborted:    {}",            !is_exact);
+    println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+    println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
     println!("Solution:   {:?}",          best_solution.unwrap_or_default());
 }
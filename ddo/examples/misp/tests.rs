@@ -19,11 +19,13 @@
 
 //! This module is meant to tests the correctness of our misp example
 
-use std::{path::PathBuf};
+use std::{path::PathBuf, sync::Arc};
 
 use ddo::*;
 
-use crate::{read_instance, MispRelax, MispRanking};
+use bit_set::BitSet;
+
+use crate::{read_instance, MispRelax, MispRanking, Misp, MispPreprocess};
 
 
 fn locate(id: &str) -> PathBuf {
@@ -62,6 +64,158 @@ pub fn solve_id(id: &str) -> isize {
     best_value.map(|x| x).unwrap_or(-1)
 }
 
+/// Solves the given problem and returns its optimal value along with the best solution
+fn solve(problem: &Misp) -> (Option<isize>, Option<Solution>) {
+    let relaxation = MispRelax {pb: problem};
+    let ranking = MispRanking;
+
+    let width = NbUnassignedWidth(problem.nb_variables());
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+
+    let mut solver = DefaultSolver::new(
+        problem, 
+        &relaxation, 
+        &ranking, 
+        &width, 
+        &dominance,
+        &cutoff, 
+        &mut fringe,
+    );
+
+    let Completion { best_value , ..} = solver.maximize();
+    (best_value, solver.best_solution())
+}
+
+/// Creates a misp instance with the given vertex weights and edges
+fn graph(weight: Vec<isize>, edges: &[(usize, usize)]) -> Misp {
+    let n = weight.len();
+    let mut neighbors = vec![(0..n).collect::<BitSet>(); n];
+    for (a, b) in edges.iter().copied() {
+        neighbors[a].remove(b);
+        neighbors[b].remove(a);
+    }
+    Misp { nb_vars: n, neighbors, weight, offset: 0 }
+}
+
+/// Checks that solving the preprocessed problem and lifting its solution yields
+/// a complete and feasible solution having the same value as the original optimum
+fn check_preprocessing(problem: &Misp) {
+    let (expected, _) = solve(problem);
+    let (reduced, lifter) = MispPreprocess.preprocess(problem);
+    let (actual, solution) = solve(&reduced);
+    assert_eq!(expected, actual);
+
+    let lifted = lifter.lift(&solution.unwrap());
+    assert_eq!(problem.nb_variables(), lifted.len());
+    for (i, d) in lifted.iter().enumerate() {
+        assert_eq!(i, d.variable.id());
+    }
+    let chosen = lifted.iter().filter(|d| d.value == 1).map(|d| d.variable.id()).collect::<Vec<_>>();
+    for (i, a) in chosen.iter().copied().enumerate() {
+        for b in chosen.iter().copied().skip(i+1) {
+            assert!(problem.neighbors[a].contains(b));
+        }
+    }
+    let value = chosen.iter().map(|x| problem.weight[*x]).sum::<isize>();
+    assert_eq!(expected, Some(value));
+}
+
+#[test]
+fn preprocessing_takes_the_isolated_vertices() {
+    let problem = graph(vec![3, 1, 2, 5, -4, 2], &[(0, 1), (1, 2), (2, 0)]);
+    let (reduced, lifter) = MispPreprocess.preprocess(&problem);
+    assert_eq!(3, reduced.nb_variables());
+    assert_eq!(7, reduced.initial_value());
+    assert_eq!(3, lifter.forced().len());
+    check_preprocessing(&problem);
+}
+#[test]
+fn preprocessing_an_edgeless_graph() {
+    let problem = graph(vec![3, 1, 2], &[]);
+    let (reduced, _) = MispPreprocess.preprocess(&problem);
+    assert_eq!(0, reduced.nb_variables());
+    check_preprocessing(&problem);
+}
+#[test]
+fn preprocessing_preserves_the_optimum_hamming6_4() {
+    check_preprocessing(&read_instance(locate("hamming6-4.clq")).unwrap());
+}
+
+/// Returns the best value of an independent set of the graph, by brute force
+fn brute_force(problem: &Misp) -> isize {
+    let n = problem.nb_variables();
+    (0_u32..1 << n)
+        .filter(|set| (0..n).filter(|a| set & (1 << a) != 0)
+            .all(|a| (a + 1..n).filter(|b| set & (1 << b) != 0)
+                .all(|b| problem.neighbors[a].contains(b))))
+        .map(|set| (0..n).filter(|x| set & (1 << x) != 0).map(|x| problem.weight[x]).sum::<isize>())
+        .max()
+        .unwrap_or(0)
+}
+/// Returns the best value of the relaxed DD of the problem compiled with the
+/// given relaxation and maximum width
+fn relaxed_bound(problem: &Misp, relaxation: &dyn Relaxation<State = BitSet>, max_width: usize) -> Option<isize> {
+    let cache = EmptyCache::default();
+    let dominance = EmptyDominanceChecker::default();
+    let input = CompilationInput {
+        comp_type: CompilationType::Relaxed,
+        problem,
+        relaxation,
+        ranking: &MispRanking,
+        cutoff: &NoCutoff,
+        max_width,
+        best_lb: isize::MIN,
+        cache: &cache,
+        dominance: &dominance,
+    };
+    let root = SubProblem {
+        state: Arc::new(problem.initial_state()),
+        value: problem.initial_value(),
+        path: vec![],
+        ub: isize::MAX,
+        depth: 0,
+        priority_bonus: 0,
+    };
+    let mut mdd = DefaultMDDLEL::default();
+    mdd.compile(&input, root).unwrap();
+    mdd.best_value()
+}
+
+#[test]
+fn the_set_relaxation_is_admissible_on_random_graphs() {
+    let mut rng = 0x9e37_79b9_7f4a_7c15_u64;
+    let mut next = |m: u64| {
+        rng ^= rng << 13;
+        rng ^= rng >> 7;
+        rng ^= rng << 17;
+        rng % m
+    };
+    for _ in 0..50 {
+        let n = 3 + next(10) as usize;
+        let weight = (0..n).map(|_| next(10) as isize - 2).collect();
+        let edges = (0..n).flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+            .filter(|_| next(3) == 0)
+            .collect::<Vec<_>>();
+        let problem = graph(weight, &edges);
+        let relaxation = SetRelaxation::union(&problem);
+        let optimum = brute_force(&problem);
+
+        for width in 1..=3 {
+            let bound = relaxed_bound(&problem, &relaxation, width).unwrap();
+            assert!(bound >= optimum, "width {width}: {bound} < {optimum}");
+        }
+        let ranking = MispRanking;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+        let mut solver = DefaultSolver::new(
+            &problem, &relaxation, &ranking, &width, &dominance, &NoCutoff, &mut fringe);
+        assert_eq!(Some(optimum), solver.maximize().best_value);
+    }
+}
+
 
 /// This test takes > 60s to solve on my machine
 #[ignore] #[test]
@@ -0,0 +1,96 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: solving a tiny weighted independent set instance
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand: a cycle of six weighted vertices with one
+//! chord between the vertices 0 and 3.
+//!
+//! ```text
+//!        (0:3) ---- (1:2)
+//!        /   \          \
+//!    (5:2)    \         (2:4)
+//!        \     \        /
+//!        (4:5) ---- (3:1)
+//! ```
+//!
+//! The heaviest independent set is {0, 2, 4} (weight 12). Every step of
+//! `walkthrough()` asserts what a user typically wants to look at while the
+//! solver runs: the bound derived from the root, the first solution found,
+//! the final gap and the decoded solution.
+
+use bit_set::BitSet;
+use ddo::*;
+
+use crate::{Misp, MispRanking, MispRelax, YES};
+
+/// Creates a misp instance from the weight of its vertices and its edges
+fn instance(weight: Vec<isize>, edges: &[(usize, usize)]) -> Misp {
+    let nb_vars = weight.len();
+    let mut neighbors = vec![(0..nb_vars).collect::<BitSet>(); nb_vars];
+    for (a, b) in edges.iter().copied() {
+        neighbors[a].remove(b);
+        neighbors[b].remove(a);
+    }
+    Misp { nb_vars, neighbors, weight, offset: 0 }
+}
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation and its ranking
+    let problem = instance(vec![3, 2, 4, 1, 5, 2], &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0), (0, 3)]);
+    let relaxation = MispRelax { pb: &problem };
+    let ranking = MispRanking;
+
+    // 2. The solver: no dominance, no cutoff and a single node per layer
+    //    so that the root must be restricted and relaxed
+    let width = FixedWidth(1);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above
+    assert_eq!(Some(14), solver.root_bound());
+    // 4. The restricted DD of the root already finds the optimum; what
+    //    remains of the search only serves to prove it
+    assert_eq!(Some(12), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(12), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution tells which vertices belong to the independent set
+    let mut vertices = solver.best_solution().unwrap().iter()
+        .filter(|d| d.value == YES)
+        .map(|d| d.variable.id())
+        .collect::<Vec<_>>();
+    vertices.sort_unstable();
+    assert_eq!(vec![0, 2, 4], vertices);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
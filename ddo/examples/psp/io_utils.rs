@@ -47,8 +47,8 @@ pub fn read_instance<P: AsRef<Path>>(fname: P) -> Result<Psp, Error> {
     
     let mut lines = f.lines();
 
-    let nb_periods = lines.next().ok_or(Error::Format)??.parse::<usize>()?;
-    let nb_items   = lines.next().ok_or(Error::Format)??.parse::<usize>()?;
+    let _nb_periods= lines.next().ok_or(Error::Format)??.parse::<usize>()?;
+    let _nb_items  = lines.next().ok_or(Error::Format)??.parse::<usize>()?;
     let _nb_orders = lines.next().ok_or(Error::Format)??.parse::<usize>()?;
     
     let _blank  = lines.next();
@@ -91,6 +91,15 @@ pub fn read_instance<P: AsRef<Path>>(fname: P) -> Result<Psp, Error> {
         demands.push(demands_for_item);
     }
 
+    Ok(instance_from_demands(changeover_cost, stocking_cost, demands))
+}
+
+/// Creates the psp instance where `demands[i][t]` tells whether one unit of 
+/// item `i` must be delivered at the end of period `t`
+pub fn instance_from_demands(changeover_cost: Vec<Vec<usize>>, stocking_cost: Vec<usize>, demands: Vec<Vec<usize>>) -> Psp {
+    let nb_periods = demands.first().map(|d| d.len()).unwrap_or(0);
+    let nb_items = demands.len();
+
     let mut prev_demands = vec![ vec![-1; nb_periods + 1] ; nb_items];
     for t in 1..=nb_periods {
         for i in 0..nb_items {
@@ -113,12 +122,12 @@ pub fn read_instance<P: AsRef<Path>>(fname: P) -> Result<Psp, Error> {
         }
     }
     
-    Ok(Psp { 
+    Psp { 
         n_items: nb_items, 
         horizon: nb_periods, 
         stocking: stocking_cost, 
         changeover: changeover_cost, 
         prev_demands,
         rem_demands 
-    })
+    }
 }
@@ -34,6 +34,8 @@ mod ub_utils;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
 
 
 // #########################################################################################
@@ -109,7 +111,7 @@ fn main() {
     );
 
     let start = Instant::now();
-    let Completion{ is_exact, best_value } = solver.maximize();
+    let Completion{ is_exact, best_value, termination, .. } = solver.maximize();
     
     let duration = start.elapsed();
     let upper_bound = solver.best_upper_bound();
@@ -129,5 +131,7 @@ fn main() {
     println!("Lower Bnd:  {}",            -lower_bound);
     println!("Gap:        {:.3}",         gap);
     println!("Aborted:    {}",            !is_exact);
+    println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+    println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
     println!("Solution:   {:?}",          best_solution.unwrap_or_default());
 }
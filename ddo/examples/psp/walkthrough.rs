@@ -0,0 +1,89 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: planning the production of two items over five periods
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand. A machine produces at most one unit of some
+//! item per period and must meet the following demands:
+//!
+//! | period     | 0 | 1 | 2 | 3 | 4 |
+//! |------------|---|---|---|---|---|
+//! | item 0     |   |   | 1 |   | 1 |
+//! | item 1     |   |   |   | 1 |   |
+//!
+//! Keeping a unit of item 0 (resp. 1) in stock costs 1 (resp. 2) per period.
+//! Switching the machine from item 0 to item 1 costs 3 and switching it back
+//! costs 2.
+//!
+//! The cheapest plan produces every unit at the very period it is due. It
+//! pays no stocking cost but 5 in changeovers; which is why the optimum of
+//! the model (which maximizes the opposite of the costs) is -5. Every step of
+//! `walkthrough()` asserts what a user typically wants to look at while the
+//! solver runs: the bound derived from the root, the first solution found,
+//! the final gap and the decoded solution.
+
+use ddo::*;
+
+use crate::{io_utils::instance_from_demands, model::{PspRanking, PspRelax, IDLE}};
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation and its ranking
+    let problem = instance_from_demands(
+        vec![vec![0, 3], vec![2, 0]],
+        vec![1, 2],
+        vec![vec![0, 0, 1, 0, 1], vec![0, 0, 0, 1, 0]]);
+    let relaxation = PspRelax::new(&problem);
+    let ranking = PspRanking;
+
+    // 2. The solver: no dominance, no cutoff and a single node per layer
+    //    so that the root must be restricted and relaxed
+    let width = FixedWidth(1);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above. With a
+    //    single node per layer, it cannot tell any cost is unavoidable
+    assert_eq!(Some(0), solver.root_bound());
+    // 4. The restricted DD of the root yields a first plan which costs 11
+    assert_eq!(Some(-11), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(-5), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution tells which item is produced during each period
+    let plan = solver.best_solution().map(|mut decisions| {
+        decisions.sort_unstable_by_key(|d| d.variable.id());
+        decisions.iter().map(|d| d.value).collect::<Vec<_>>()
+    });
+    assert_eq!(Some(vec![IDLE, IDLE, 0, 1, 0]), plan);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
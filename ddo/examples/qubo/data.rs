@@ -0,0 +1,299 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module defines the data of a qubo instance along with the parser of
+//! the "i j value" file format and the conversions from and to max-cut.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::errors::Error;
+
+/// A quadratic unconstrained binary optimization problem. The objective of an
+/// assignment `x` of the binary variables is
+///
+/// `offset + sum_i c[i][i] x_i + sum_{i < j} c[i][j] x_i x_j`
+///
+/// where `c` is a symmetric matrix of coefficients: `c[i][j]` is the complete
+/// coefficient of the product `x_i x_j` (and not half of it as in the x'Qx 
+/// notation). This objective is to be *maximized*. Minimization problems are
+/// handled by negating the instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Qubo {
+    pub nb_vars: usize,
+    pub coef: Vec<isize>,
+    pub offset: isize,
+}
+
+impl Qubo {
+    /// Creates a qubo of `n` variables whose coefficients are all zero
+    pub fn new(n: usize) -> Self {
+        Qubo { nb_vars: n, coef: vec![0; n * n], offset: 0 }
+    }
+    /// Creates a qubo from the dense symmetric matrix `q` (in row major order)
+    /// whose objective is x'Qx. Hence, the coefficient of `x_i x_j` is 
+    /// `2 q[i][j]` whenever `i != j`.
+    pub fn from_dense(n: usize, q: &[isize]) -> Result<Self, Error> {
+        if q.len() != n * n {
+            return Err(Error::WrongSize { expected: n * n, actual: q.len() });
+        }
+        let mut qubo = Qubo::new(n);
+        for i in 0..n {
+            qubo.add(i, i, q[i * n + i]);
+            for j in i + 1..n {
+                if q[i * n + j] != q[j * n + i] {
+                    return Err(Error::NotSymmetric(i, j));
+                }
+                qubo.add(i, j, 2 * q[i * n + j]);
+            }
+        }
+        Ok(qubo)
+    }
+    /// Creates a qubo of `n` variables from the sparse list of `(i, j, c)` 
+    /// entries. Each of them adds `c` to the coefficient of `x_i x_j`; so every
+    /// pair of variables must only be listed once, in either order.
+    pub fn from_sparse<I>(n: usize, entries: I) -> Self 
+        where I: IntoIterator<Item = (usize, usize, isize)>
+    {
+        let mut qubo = Qubo::new(n);
+        for (i, j, c) in entries {
+            qubo.add(i, j, c);
+        }
+        qubo
+    }
+    /// Adds `c` to the coefficient of `x_i x_j`
+    pub fn add(&mut self, i: usize, j: usize, c: isize) {
+        let n = self.nb_vars;
+        self.coef[i * n + j] += c;
+        if i != j {
+            self.coef[j * n + i] += c;
+        }
+    }
+    /// Returns the coefficient of `x_i x_j`
+    pub fn get(&self, i: usize, j: usize) -> isize {
+        self.coef[i * self.nb_vars + j]
+    }
+    /// Returns the same qubo with the opposite objective. This is what lets
+    /// you minimize an objective function.
+    pub fn negated(&self) -> Self {
+        Qubo { 
+            nb_vars: self.nb_vars, 
+            coef:    self.coef.iter().map(|c| -c).collect(), 
+            offset: -self.offset 
+        }
+    }
+
+    /// Converts this qubo into a max-cut problem over `n + 1` vertices. The
+    /// extra vertex 0 is an anchor: variable `i` is true iff vertex `i + 1`
+    /// is not on the same side of the cut as the anchor.
+    ///
+    /// # Note
+    /// As `x_i x_j = (x_i + x_j - [x_i != x_j]) / 2`, the reduction halves the
+    /// quadratic coefficients. In order to keep the weights integer, the graph
+    /// encodes *twice* the objective of the qubo: `2 f(x) = cut_value(g, x)`.
+    pub fn to_max_cut(&self) -> MaxCut {
+        let n = self.nb_vars;
+        let mut graph = MaxCut::new(n + 1);
+        graph.offset = 2 * self.offset;
+        for i in 0..n {
+            let mut anchor = 2 * self.get(i, i);
+            for j in (0..n).filter(|j| *j != i) {
+                anchor += self.get(i, j);
+            }
+            graph.add_edge(0, i + 1, anchor);
+            for j in i + 1..n {
+                graph.add_edge(i + 1, j + 1, -self.get(i, j));
+            }
+        }
+        graph
+    }
+    /// Converts a max-cut problem into a qubo having one variable per vertex
+    /// which tells whether that vertex belongs to the 'S' side of the cut. 
+    /// Because `[x_i != x_j] = x_i + x_j - 2 x_i x_j`, the objective of the 
+    /// qubo is exactly the value of the cut: `f(x) = cut_value(g, x)`.
+    pub fn from_max_cut(graph: &MaxCut) -> Self {
+        let n = graph.nb_vertices;
+        let mut qubo = Qubo::new(n);
+        qubo.offset = graph.offset;
+        for i in 0..n {
+            for j in i + 1..n {
+                let w = graph.weight(i, j);
+                qubo.add(i, i, w);
+                qubo.add(j, j, w);
+                qubo.add(i, j, -2 * w);
+            }
+        }
+        qubo
+    }
+}
+
+/// A weighted max-cut problem. The value of a cut is the sum of the weights
+/// of the edges that cross the cut plus some constant `offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxCut {
+    pub nb_vertices: usize,
+    pub adj_matrix: Vec<isize>,
+    pub offset: isize,
+}
+impl MaxCut {
+    /// Creates a graph of `n` vertices without any edge
+    pub fn new(n: usize) -> Self {
+        MaxCut { nb_vertices: n, adj_matrix: vec![0; n * n], offset: 0 }
+    }
+    /// Adds `w` to the weight of the edge between `x` and `y`
+    pub fn add_edge(&mut self, x: usize, y: usize, w: isize) {
+        let n = self.nb_vertices;
+        self.adj_matrix[x * n + y] += w;
+        self.adj_matrix[y * n + x] += w;
+    }
+    /// Returns the weight of the edge between `x` and `y`
+    pub fn weight(&self, x: usize, y: usize) -> isize {
+        self.adj_matrix[x * self.nb_vertices + y]
+    }
+    /// Writes this graph in the format of the mcp example (vertices are one
+    /// based). The offset is written in a comment since the format has no 
+    /// room for it.
+    pub fn to_mcp_string(&self) -> String {
+        let n = self.nb_vertices;
+        let edges = (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .filter(|(i, j)| self.weight(*i, *j) != 0)
+            .collect::<Vec<_>>();
+        
+        let mut out = format!("c offset {}\n{} {}\n", self.offset, n, edges.len());
+        for (i, j) in edges {
+            out.push_str(&format!("{} {} {}\n", i + 1, j + 1, self.weight(i, j)));
+        }
+        out
+    }
+}
+
+/// This function reads a qubo from a file in the "i j value" format. 
+///
+/// * Empty lines and the lines starting with 'c' or '#' are ignored.
+/// * An optional header 'p qubo topology max_nodes nb_nodes nb_couplers' 
+///   gives the number of variables.
+/// * Each other line 'i j value' adds value to the coefficient of `x_i x_j`
+///   (the variables are zero based).
+///
+/// When there is no header, the number of variables is one more than the
+/// largest variable index that appears in the file.
+pub fn read_instance<P: AsRef<Path>>(fname: P) -> Result<Qubo, Error> {
+    parse_instance(BufReader::new(File::open(fname)?))
+}
+
+/// This function reads a qubo from a file holding a dense symmetric matrix Q
+/// (the objective being x'Qx). The first number of the file is the number of 
+/// variables n, it is followed by the n * n entries of Q in row major order.
+/// The lines starting with 'c' or '#' are ignored.
+pub fn read_dense_instance<P: AsRef<Path>>(fname: P) -> Result<Qubo, Error> {
+    parse_dense_instance(BufReader::new(File::open(fname)?))
+}
+
+/// Parses a qubo in the "i j value" format (see `read_instance`)
+pub fn parse_instance<B: BufRead>(buf: B) -> Result<Qubo, Error> {
+    let mut nb_vars = 0;
+    let mut entries = vec![];
+    for (number, line) in buf.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') || line.starts_with('#') {
+            continue;
+        }
+
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            ["p", "qubo", _topology, max_nodes, _nodes, _couplers] => {
+                nb_vars = nb_vars.max(max_nodes.parse::<usize>()?);
+            },
+            [i, j, c] => {
+                let i = i.parse::<usize>()?;
+                let j = j.parse::<usize>()?;
+                let c = c.parse::<isize>()?;
+                nb_vars = nb_vars.max(i.max(j) + 1);
+                entries.push((i, j, c));
+            },
+            _ => return Err(Error::Format { line: number + 1, text: line.to_string() }),
+        }
+    }
+    Ok(Qubo::from_sparse(nb_vars, entries))
+}
+
+/// This function reads a max-cut instance from a file in the format of the
+/// mcp example (see `MaxCut::to_mcp_string`): a 'vertices edges' header line
+/// followed by one 'src dst weight' line per edge (vertices are one based). 
+/// The comment 'c offset value' sets the offset of the graph; all other lines 
+/// starting with 'c' are ignored.
+pub fn read_max_cut<P: AsRef<Path>>(fname: P) -> Result<MaxCut, Error> {
+    parse_max_cut(BufReader::new(File::open(fname)?))
+}
+
+/// Parses a max-cut instance in the mcp format (see `read_max_cut`)
+pub fn parse_max_cut<B: BufRead>(buf: B) -> Result<MaxCut, Error> {
+    let mut graph = MaxCut::new(0);
+    for (number, line) in buf.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            ["c", "offset", offset] => graph.offset = offset.parse::<isize>()?,
+            ["c", ..] => {},
+            [n, _edges] => {
+                let offset = graph.offset;
+                graph = MaxCut::new(n.parse::<usize>()?);
+                graph.offset = offset;
+            },
+            [x, y, w] => {
+                let x = x.parse::<usize>()?;
+                let y = y.parse::<usize>()?;
+                let w = w.parse::<isize>()?;
+                if x == 0 || y == 0 || x > graph.nb_vertices || y > graph.nb_vertices {
+                    return Err(Error::Format { line: number + 1, text: line.to_string() });
+                }
+                graph.add_edge(x - 1, y - 1, w);
+            },
+            _ => return Err(Error::Format { line: number + 1, text: line.to_string() }),
+        }
+    }
+    Ok(graph)
+}
+
+/// Parses a qubo given as a dense matrix (see `read_dense_instance`)
+pub fn parse_dense_instance<B: BufRead>(buf: B) -> Result<Qubo, Error> {
+    let mut numbers = vec![];
+    for line in buf.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.starts_with('c') || line.starts_with('#') {
+            continue;
+        }
+        for token in line.split_whitespace() {
+            numbers.push(token.parse::<isize>()?);
+        }
+    }
+    match numbers.split_first() {
+        Some((n, q)) if *n >= 0 => Qubo::from_dense(*n as usize, q),
+        _ => Err(Error::WrongSize { expected: 1, actual: 0 }),
+    }
+}
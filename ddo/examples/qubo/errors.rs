@@ -0,0 +1,44 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module contains the definition of the errors that can be triggered when
+//! parsing an instance of the qubo problem.
+
+use std::num::ParseIntError;
+
+/// This enumeration groups the kind of errors that might occur when parsing an
+/// instance file or building a qubo matrix.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// There was an io related error
+    #[error("io error {0}")]
+    Io(#[from] std::io::Error),
+    /// The parser expected to read an integer number but got some garbage
+    #[error("parse int {0}")]
+    ParseInt(#[from] ParseIntError),
+    /// The line could not be understood
+    #[error("line {line}: cannot parse '{text}'")]
+    Format { line: usize, text: String },
+    /// A dense matrix was not square, or not symmetric
+    #[error("the matrix is not symmetric: q[{0}][{1}] != q[{1}][{0}]")]
+    NotSymmetric(usize, usize),
+    /// A dense matrix did not have the expected number of entries
+    #[error("expected {expected} entries but got {actual}")]
+    WrongSize { expected: usize, actual: usize },
+}
@@ -0,0 +1,127 @@
+use std::{time::{Duration, Instant}, fs};
+
+use clap::Parser;
+use ddo::*;
+
+use crate::{data::{read_instance, read_dense_instance, read_max_cut, Qubo}, model::{QuboModel, QuboRanking, ONE}, relax::QuboRelax};
+
+mod errors;
+mod data;
+mod model;
+mod relax;
+#[cfg(test)]
+mod tests;
+#[cfg(test)]
+mod walkthrough;
+
+/// Solve a qubo instance given in the "i j value" format
+#[derive(Debug, Parser)]
+#[command(author, version, about, long_about = None)]
+struct Params {
+    /// the instance file
+    #[arg(short, long)]
+    file: String,
+    /// maximum width in a layer
+    #[arg(short, long)]
+    width: Option<usize>,
+    /// max time to find the solution
+    #[arg(short, long)]
+    timeout: Option<u64>,
+    /// the instance file holds a dense matrix rather than "i j value" entries
+    #[arg(long)]
+    dense: bool,
+    /// the instance file holds a max-cut problem (mcp format) which is solved
+    /// as a qubo
+    #[arg(long, conflicts_with = "dense")]
+    from_max_cut: bool,
+    /// minimize the objective instead of maximizing it
+    #[arg(long)]
+    minimize: bool,
+    /// if set, writes the equivalent max-cut instance (mcp format) to this file
+    /// instead of solving the qubo
+    #[arg(long)]
+    max_cut: Option<String>,
+}
+
+fn main() {
+    let Params{file, width, timeout, dense, from_max_cut, minimize, max_cut} = Params::parse();
+    let qubo = if dense { 
+        read_dense_instance(file) 
+    } else if from_max_cut {
+        read_max_cut(file).map(|graph| Qubo::from_max_cut(&graph))
+    } else { 
+        read_instance(file) 
+    };
+    let qubo = qubo.expect("could not parse instance");
+    if let Some(output) = max_cut {
+        fs::write(output, qubo.to_max_cut().to_mcp_string()).expect("could not write max-cut instance");
+        return;
+    }
+
+    let qubo = if minimize { qubo.negated() } else { qubo };
+    let problem = QuboModel::new(qubo);
+    let relax = QuboRelax::new(&problem);
+    let rank = QuboRanking;
+    let width = max_width(&problem, width);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = cutoff(timeout);
+    let mut fringe = NoDupFringe::new(MaxUB::new(&rank));
+
+    let mut solver = DefaultSolver::new(
+        &problem, 
+        &relax, 
+        &rank, 
+        width.as_ref(), 
+        &dominance,
+        cutoff.as_ref(), 
+        &mut fringe,
+    );
+
+    let start = Instant::now();
+    let Completion{ is_exact, best_value, termination, .. } = solver.maximize();
+    
+    let duration = start.elapsed();
+    // when minimizing, the solver maximizes the negated objective
+    let sign = if minimize { -1 } else { 1 };
+    let (upper_bound, lower_bound) = if minimize {
+        (-solver.best_lower_bound(), -solver.best_upper_bound())
+    } else {
+        (solver.best_upper_bound(), solver.best_lower_bound())
+    };
+    let gap = solver.gap();
+    let best_solution = solver.best_solution()
+        .map(|decisions| assignment(&problem.qubo, &decisions));
+
+    println!("Duration:   {:.3} seconds", duration.as_secs_f32());
+    println!("Objective:  {}",            best_value.map(|v| sign * v).unwrap_or(-1));
+    println!("Upper Bnd:  {}",            upper_bound);
+    println!("Lower Bnd:  {}",            lower_bound);
+    println!("Gap:        {:.3}",         gap);
+    println!("Aborted:    {}",            !is_exact);
+    println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+    println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
+    println!("Solution:   {:?}",          best_solution.unwrap_or_default());
+}
+
+/// Returns the assignment of all variables that corresponds to the decisions
+fn assignment(qubo: &Qubo, decisions: &[Decision]) -> Vec<bool> {
+    let mut x = vec![false; qubo.nb_vars];
+    for d in decisions {
+        x[d.variable.id()] = d.value == ONE;
+    }
+    x
+}
+fn cutoff(timeout: Option<u64>) -> Box<dyn Cutoff + Send + Sync> {
+    if let Some(t) = timeout {
+        Box::new(TimeBudget::new(Duration::from_secs(t)))
+    } else {
+        Box::new(NoCutoff)
+    }
+}
+fn max_width<P: Problem>(p: &P, w: Option<usize>) -> Box<dyn WidthHeuristic<P::State> + Send + Sync> {
+    if let Some(w) = w {
+        Box::new(FixedWidth(w))
+    } else {
+        Box::new(NbUnassignedWidth(p.nb_variables()))
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module defines the dynamic programming model of the qubo. Much like
+//! the model of the max-cut problem (mcp example), the state is a vector of
+//! benefits: `benef[v]` is the gain of setting the (yet unassigned) variable
+//! `v` to true given the variables that have already been set to true.
+
+use ddo::*;
+
+use crate::data::Qubo;
+
+/// The value of a variable that is set to true
+pub const ONE: isize = 1;
+/// The value of a variable that is set to false
+pub const ZERO: isize = 0;
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct QuboState {
+    /// The gain of setting each unassigned variable to true (the benefit of
+    /// the variables that have already been assigned is always zero)
+    pub benef: Vec<isize>,
+    pub depth: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct QuboModel {
+    pub qubo: Qubo,
+}
+impl QuboModel {
+    pub fn new(qubo: Qubo) -> Self { QuboModel { qubo } }
+}
+impl Problem for QuboModel {
+    type State = QuboState;
+
+    fn nb_variables(&self) -> usize {
+        self.qubo.nb_vars
+    }
+
+    fn initial_state(&self) -> QuboState {
+        let benef = (0..self.nb_variables()).map(|i| self.qubo.get(i, i)).collect();
+        QuboState { benef, depth: 0 }
+    }
+
+    fn initial_value(&self) -> isize {
+        self.qubo.offset
+    }
+
+    fn for_each_in_domain(&self, variable: Variable, _: &Self::State, f: &mut dyn DecisionCallback) {
+        f.apply(Decision { variable, value: ONE });
+        f.apply(Decision { variable, value: ZERO });
+    }
+
+    fn transition(&self, state: &QuboState, d: Decision) -> QuboState {
+        let x = d.variable.id();
+        let mut benef = vec![0; self.nb_variables()];
+        for (v, item) in benef.iter_mut().enumerate().skip(x + 1) {
+            *item = state.benef[v] + d.value * self.qubo.get(x, v);
+        }
+        QuboState { benef, depth: 1 + state.depth }
+    }
+
+    fn transition_cost(&self, state: &QuboState, _: &Self::State, d: Decision) -> isize {
+        d.value * state.benef[d.variable.id()]
+    }
+
+    fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+        if depth < self.nb_variables() {
+            Some(Variable(depth))
+        } else {
+            None
+        }
+    }
+}
+
+/// The most promising states are those which can still gain the most
+pub struct QuboRanking;
+impl StateRanking for QuboRanking {
+    type State = QuboState;
+
+    fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+        let xa = a.benef.iter().map(|v| (*v).max(0)).sum::<isize>();
+        let xb = b.benef.iter().map(|v| (*v).max(0)).sum::<isize>();
+
+        xa.cmp(&xb)
+    }
+}
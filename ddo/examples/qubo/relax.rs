@@ -0,0 +1,75 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use ddo::*;
+
+use crate::model::{QuboModel, QuboState};
+
+/// The relaxation of the qubo merges states by taking the smallest benefit of
+/// each variable. The difference between the benefits of the original and the
+/// merged states is moved onto the relaxed arcs. Because each variable can be
+/// set to true at most once, this never underestimates the objective.
+#[derive(Debug, Clone)]
+pub struct QuboRelax<'a> {
+    pb: &'a QuboModel,
+    /// `estimates[k]` is the sum of the positive quadratic coefficients among
+    /// the variables >= k
+    estimates: Vec<isize>,
+}
+impl <'a> QuboRelax<'a> {
+    pub fn new(pb: &'a QuboModel) -> Self {
+        let n = pb.nb_variables();
+        let mut estimates = vec![0; n + 1];
+        for k in (0..n).rev() {
+            let positive = (k + 1..n)
+                .map(|j| pb.qubo.get(k, j).max(0))
+                .sum::<isize>();
+            estimates[k] = estimates[k + 1] + positive;
+        }
+        QuboRelax { pb, estimates }
+    }
+}
+impl Relaxation for QuboRelax<'_> {
+    type State = QuboState;
+
+    fn merge(&self, states: &mut dyn Iterator<Item = &QuboState>) -> QuboState {
+        let mut states = states.peekable();
+        let depth = states.peek().map(|s| s.depth).unwrap_or(0);
+        let mut benef = vec![isize::MAX; self.pb.nb_variables()];
+        for state in states {
+            for (m, b) in benef.iter_mut().zip(state.benef.iter()) {
+                *m = (*m).min(*b);
+            }
+        }
+        QuboState { benef, depth }
+    }
+
+    fn relax(&self, _: &QuboState, dst: &QuboState, mrg: &QuboState, _: Decision, cost: isize) -> isize {
+        let shift = dst.benef.iter().zip(mrg.benef.iter())
+            .map(|(d, m)| d - m)
+            .sum::<isize>();
+        cost + shift
+    }
+
+    fn fast_upper_bound(&self, state: &QuboState) -> isize {
+        let k = state.depth as usize;
+        let linear = state.benef.iter().skip(k).map(|b| (*b).max(0)).sum::<isize>();
+        linear + self.estimates[k]
+    }
+}
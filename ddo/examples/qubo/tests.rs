@@ -0,0 +1,233 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module is meant to tests the correctness of our qubo example
+
+use std::path::PathBuf;
+
+use ddo::*;
+
+use crate::{data::{read_instance, parse_instance, parse_dense_instance, parse_max_cut, Qubo, MaxCut}, model::{QuboModel, QuboRanking}, relax::QuboRelax};
+
+fn locate(id: &str) -> PathBuf {
+    PathBuf::new()
+        .join(env!("CARGO_MANIFEST_DIR"))
+        .join("../resources/qubo/")
+        .join(id)
+}
+
+/// A tiny xorshift generator: good enough to draw reproducible instances
+struct Rng(u64);
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    /// Draws a value in `-bound..=bound`
+    fn coef(&mut self, bound: isize) -> isize {
+        (self.next() % (2 * bound as u64 + 1)) as isize - bound
+    }
+}
+
+/// Returns the objective value of the assignment `x`
+fn evaluate(qubo: &Qubo, x: &[bool]) -> isize {
+    let mut value = qubo.offset;
+    for i in (0..qubo.nb_vars).filter(|i| x[*i]) {
+        value += qubo.get(i, i);
+        for j in (i + 1..qubo.nb_vars).filter(|j| x[*j]) {
+            value += qubo.get(i, j);
+        }
+    }
+    value
+}
+/// Returns the value of the cut which places each vertex `v` on the side 
+/// `side[v]`
+fn cut_value(graph: &MaxCut, side: &[bool]) -> isize {
+    let mut value = graph.offset;
+    for i in 0..graph.nb_vertices {
+        for j in i + 1..graph.nb_vertices {
+            if side[i] != side[j] {
+                value += graph.weight(i, j);
+            }
+        }
+    }
+    value
+}
+
+fn random_qubo(n: usize, seed: u64) -> Qubo {
+    let mut rng = Rng(seed.wrapping_mul(0x9e3779b97f4a7c15) | 1);
+    let mut qubo = Qubo::new(n);
+    qubo.offset = rng.coef(5);
+    for i in 0..n {
+        for j in i..n {
+            // about one coefficient out of three is zero
+            if rng.next() % 3 != 0 {
+                qubo.add(i, j, rng.coef(10));
+            }
+        }
+    }
+    qubo
+}
+
+fn assignments(n: usize) -> impl Iterator<Item = Vec<bool>> {
+    (0..1_usize << n).map(move |bits| (0..n).map(|i| bits & (1 << i) != 0).collect())
+}
+
+fn brute_force(qubo: &Qubo) -> isize {
+    assignments(qubo.nb_vars).map(|x| evaluate(qubo, &x)).max().unwrap()
+}
+
+fn solve(qubo: Qubo, width: usize) -> isize {
+    let problem = QuboModel::new(qubo);
+    let relaxation = QuboRelax::new(&problem);
+    let ranking = QuboRanking;
+
+    let width = FixedWidth(width);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+
+    let mut solver = DefaultSolver::new(
+        &problem, 
+        &relaxation, 
+        &ranking, 
+        &width, 
+        &dominance,
+        &cutoff, 
+        &mut fringe,
+    );
+
+    let Completion { best_value, is_exact, .. } = solver.maximize();
+    assert!(is_exact);
+    let solution = solver.best_solution().unwrap();
+    let mut x = vec![false; problem.qubo.nb_vars];
+    for d in solution {
+        x[d.variable.id()] = d.value == 1;
+    }
+    assert_eq!(best_value, Some(evaluate(&problem.qubo, &x)));
+    best_value.unwrap()
+}
+
+#[test]
+fn random_qubos_are_solved_to_optimality() {
+    for seed in 0..10 {
+        let qubo = random_qubo(12, seed);
+        let expected = brute_force(&qubo);
+        assert_eq!(expected, solve(qubo.clone(), 2),    "seed {seed}");
+        assert_eq!(expected, solve(qubo.clone(), 4),    "seed {seed}");
+        assert_eq!(expected, solve(qubo,         1000), "seed {seed}");
+    }
+}
+#[test]
+fn random_qubos_are_minimized_when_negated() {
+    for seed in 0..5 {
+        let qubo = random_qubo(12, seed);
+        let expected = assignments(12).map(|x| evaluate(&qubo, &x)).min().unwrap();
+        assert_eq!(expected, -solve(qubo.negated(), 3), "seed {seed}");
+    }
+}
+#[test]
+fn the_small_instance_is_parsed_and_solved() {
+    let qubo = read_instance(locate("small.qubo")).expect("could not parse instance");
+    assert_eq!(4, qubo.nb_vars);
+    assert_eq!(-9, qubo.get(1, 0));
+    assert_eq!(11, solve(qubo.clone(), 2));
+    assert_eq!(-4, -solve(qubo.negated(), 2));
+}
+#[test]
+fn without_header_the_number_of_variables_is_inferred() {
+    let qubo = parse_instance("# comment\n0 0 1\n4 2 -3\n".as_bytes()).unwrap();
+    assert_eq!(5, qubo.nb_vars);
+    assert_eq!(-3, qubo.get(2, 4));
+}
+#[test]
+fn garbage_lines_are_reported() {
+    assert!(parse_instance("0 0 1\n1 2\n".as_bytes()).is_err());
+    assert!(parse_instance("0 0 one\n".as_bytes()).is_err());
+}
+#[test]
+fn dense_matrices_describe_x_q_x() {
+    let dense = Qubo::from_dense(3, &[
+        1, 2, 0,
+        2, -1, -3,
+        0, -3, 4,
+    ]).unwrap();
+    let sparse = Qubo::from_sparse(3, [(0, 0, 1), (1, 1, -1), (2, 2, 4), (0, 1, 4), (2, 1, -6)]);
+    assert_eq!(sparse, dense);
+    assert!(Qubo::from_dense(2, &[0, 1, 2, 0]).is_err());
+    assert!(Qubo::from_dense(2, &[0, 1, 1]).is_err());
+
+    let parsed = parse_dense_instance("c a comment\n3\n1 2 0\n2 -1 -3\n0 -3 4\n".as_bytes()).unwrap();
+    assert_eq!(dense, parsed);
+    assert!(parse_dense_instance("".as_bytes()).is_err());
+}
+#[test]
+fn the_max_cut_form_encodes_twice_the_objective() {
+    for seed in 0..5 {
+        let qubo = random_qubo(8, seed);
+        let graph = qubo.to_max_cut();
+        assert_eq!(9, graph.nb_vertices);
+        for x in assignments(8) {
+            // the anchor (vertex 0) is on the 'false' side
+            let side = std::iter::once(false).chain(x.iter().copied()).collect::<Vec<_>>();
+            assert_eq!(2 * evaluate(&qubo, &x), cut_value(&graph, &side));
+        }
+    }
+}
+#[test]
+fn a_max_cut_is_a_qubo_with_the_same_objective() {
+    for seed in 0..5 {
+        let mut rng = Rng(seed + 1);
+        let mut graph = MaxCut::new(10);
+        graph.offset = rng.coef(5);
+        for i in 0..10 {
+            for j in i + 1..10 {
+                graph.add_edge(i, j, rng.coef(10));
+            }
+        }
+        let qubo = Qubo::from_max_cut(&graph);
+        for x in assignments(10) {
+            assert_eq!(cut_value(&graph, &x), evaluate(&qubo, &x));
+        }
+        let best_cut = assignments(10).map(|x| cut_value(&graph, &x)).max().unwrap();
+        assert_eq!(best_cut, solve(qubo, 3));
+    }
+}
+#[test]
+fn the_max_cut_form_is_written_in_the_mcp_format() {
+    let mut graph = MaxCut::new(3);
+    graph.offset = -2;
+    graph.add_edge(0, 2, 5);
+    graph.add_edge(1, 2, -1);
+    assert_eq!("c offset -2\n3 2\n1 3 5\n2 3 -1\n", graph.to_mcp_string());
+    assert_eq!(graph, parse_max_cut(graph.to_mcp_string().as_bytes()).unwrap());
+    assert!(parse_max_cut("2 1\n1 3 4\n".as_bytes()).is_err());
+}
+#[test]
+fn the_mcp_instances_can_be_solved_as_qubos() {
+    let path = PathBuf::new()
+        .join(env!("CARGO_MANIFEST_DIR"))
+        .join("../resources/mcp/mcp_n30_p0.1_000.mcp");
+    let graph = crate::data::read_max_cut(path).unwrap();
+    // this is the optimum found by the mcp example
+    let qubo = Qubo::from_max_cut(&graph);
+    assert_eq!(13, solve(qubo, 10));
+}
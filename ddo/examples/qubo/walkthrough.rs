@@ -0,0 +1,84 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: maximizing a tiny quadratic binary objective
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand. It bears on four binary variables and its
+//! (symmetric) matrix of coefficients reads:
+//!
+//! |       | x0 | x1 | x2 | x3 |
+//! |-------|----|----|----|----|
+//! | x0    |  3 | -4 |  2 | -2 |
+//! | x1    | -4 |  2 |  3 | -3 |
+//! | x2    |  2 |  3 | -1 |  1 |
+//! | x3    | -2 | -3 |  1 |  4 |
+//!
+//! The best assignment sets x0, x2 and x3 and leaves x1 unset. Its objective
+//! is 3 - 1 + 4 + 2 - 2 + 1 = 7. Every step of `walkthrough()` asserts what
+//! a user typically wants to look at while the solver runs: the bound derived
+//! from the root, the first solution found, the final gap and the decoded
+//! solution.
+
+use ddo::*;
+
+use crate::{assignment, data::Qubo, model::{QuboModel, QuboRanking}, relax::QuboRelax};
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation and its ranking
+    let qubo = Qubo::from_sparse(4, [
+        (0, 0, 3), (1, 1, 2), (2, 2, -1), (3, 3, 4),
+        (0, 1, -4), (0, 2, 2), (0, 3, -2), (1, 2, 3), (1, 3, -3), (2, 3, 1),
+    ]);
+    let problem = QuboModel::new(qubo);
+    let relaxation = QuboRelax::new(&problem);
+    let ranking = QuboRanking;
+
+    // 2. The solver: no dominance, no cutoff and a single node per layer
+    //    so that the root must be restricted and relaxed
+    let width = FixedWidth(1);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above
+    assert_eq!(Some(8), solver.root_bound());
+    // 4. The restricted DD of the root already finds the optimum; what
+    //    remains of the search only serves to prove it
+    assert_eq!(Some(7), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(7), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution is decoded as the value of each binary variable
+    let x = solver.best_solution().map(|decisions| assignment(&problem.qubo, &decisions));
+    assert_eq!(Some(vec![true, false, true, true]), x);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
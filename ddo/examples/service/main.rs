@@ -0,0 +1,321 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This example shows how to embed ddo in a long running service. A dedicated
+//! thread owns the solver and serves the requests it receives over a command
+//! channel (solve an instance, cancel a resolution, or report on the
+//! progress). The outcome of each request flows back over an event channel.
+//!
+//! The very same solver object is reused to serve all the requests: the
+//! instance being solved is swapped in between two requests and the solver is
+//! `reset()` so that nothing leaks from one resolution to the next. The model
+//! itself is a bare bones version of the knapsack example.
+use std::{cell::{Cell, RefCell}, collections::{HashSet, VecDeque}, sync::mpsc::{channel, Receiver, Sender}, thread, time::{Duration, Instant}};
+
+use ddo::*;
+
+#[cfg(test)]
+mod tests;
+
+/// An instance of the knapsack problem, as it is submitted to the service
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Instance {
+    /// The maximum capacity of the sack (when empty)
+    pub capacity: usize,
+    /// the profit of each item
+    pub profit: Vec<isize>,
+    /// the weight of each item
+    pub weight: Vec<usize>,
+}
+
+/// The state of the DP model: the number of items which have been decided
+/// upon, and the remaining capacity in the sack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KnapsackState {
+    depth: usize,
+    capacity: usize,
+}
+
+/// The problem which is solved by the service. Because the solver only holds
+/// a shared reference to its problem, the instance of the current request
+/// lives in a `RefCell`: this is what makes it possible to reuse one solver
+/// for all the requests.
+#[derive(Debug, Default)]
+pub struct Knapsack {
+    instance: RefCell<Instance>,
+}
+impl Knapsack {
+    /// Replaces the instance being solved
+    fn load(&self, instance: Instance) {
+        *self.instance.borrow_mut() = instance;
+    }
+}
+
+const TAKE_IT: isize = 1;
+const LEAVE_IT_OUT: isize = 0;
+
+impl Problem for Knapsack {
+    type State = KnapsackState;
+
+    fn nb_variables(&self) -> usize {
+        self.instance.borrow().profit.len()
+    }
+    fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+        if state.capacity >= self.instance.borrow().weight[variable.id()] {
+            f.apply(Decision { variable, value: TAKE_IT });
+        }
+        f.apply(Decision { variable, value: LEAVE_IT_OUT });
+    }
+    fn initial_state(&self) -> Self::State {
+        KnapsackState { depth: 0, capacity: self.instance.borrow().capacity }
+    }
+    fn initial_value(&self) -> isize {
+        0
+    }
+    fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+        let mut ret = *state;
+        ret.depth += 1;
+        if dec.value == TAKE_IT {
+            ret.capacity -= self.instance.borrow().weight[dec.variable.id()]
+        }
+        ret
+    }
+    fn transition_cost(&self, _: &Self::State, _: &Self::State, dec: Decision) -> isize {
+        self.instance.borrow().profit[dec.variable.id()] * dec.value
+    }
+    fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+        if depth < self.nb_variables() {
+            Some(Variable(depth))
+        } else {
+            None
+        }
+    }
+}
+
+/// The relaxation merges states by keeping the largest remaining capacity
+pub struct KPRelax<'a> { pub pb: &'a Knapsack }
+impl Relaxation for KPRelax<'_> {
+    type State = KnapsackState;
+
+    fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+        states.max_by_key(|s| s.capacity).copied().unwrap()
+    }
+    fn relax(&self, _: &Self::State, _: &Self::State, _: &Self::State, _: Decision, cost: isize) -> isize {
+        cost
+    }
+    fn fast_upper_bound(&self, state: &Self::State) -> isize {
+        let instance = self.pb.instance.borrow();
+        instance.profit[state.depth..].iter().sum()
+    }
+}
+
+/// The states with the largest remaining capacity are the most promising ones
+pub struct KPRanking;
+impl StateRanking for KPRanking {
+    type State = KnapsackState;
+
+    fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+        a.capacity.cmp(&b.capacity)
+    }
+}
+
+/// The commands which can be sent to the service
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Solves the given instance. The requests are served one at a time, in
+    /// the order they were submitted.
+    Solve { id: usize, instance: Instance },
+    /// Cancels the resolution of request `id`. When that request is still
+    /// waiting to be served, it is canceled as soon as it starts.
+    Cancel { id: usize },
+    /// Asks the service to report on its progress
+    QueryProgress,
+    /// Stops the service (the ongoing resolution is canceled)
+    Shutdown,
+}
+
+/// The outcome of a resolution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub best_value: Option<isize>,
+    pub best_solution: Option<Solution>,
+    pub is_exact: bool,
+    pub explored: usize,
+}
+
+/// The events which are emitted by the service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// The resolution of request `id` has started
+    Started { id: usize },
+    /// The request being served (None when the service is idle) and how long
+    /// it has been running
+    Progress { id: Option<usize>, elapsed: Duration },
+    /// The resolution of request `id` is over (either because it completed
+    /// or because it was canceled)
+    Finished { id: usize, outcome: Outcome },
+}
+
+/// This is the cutoff of the solver. Since the solver consults it on a regular
+/// basis, this is where the commands which arrive while a resolution is ongoing
+/// get served: a cancellation stops the search, a progress query is answered
+/// right away, and the other commands are queued until the solver is done.
+struct Mailbox<'a> {
+    commands: Receiver<Command>,
+    events: &'a Sender<Event>,
+    /// The commands which will be served once the ongoing resolution is over
+    pending: RefCell<VecDeque<Command>>,
+    /// The id of the request being served and the time when it started
+    ongoing: Cell<Option<(usize, Instant)>>,
+    /// The ids of the requests which have been canceled
+    canceled: RefCell<HashSet<usize>>,
+    /// Set when the service must stop as soon as possible
+    shutdown: Cell<bool>,
+}
+impl<'a> Mailbox<'a> {
+    fn new(commands: Receiver<Command>, events: &'a Sender<Event>) -> Self {
+        Self {
+            commands,
+            events,
+            pending: Default::default(),
+            ongoing: Cell::new(None),
+            canceled: Default::default(),
+            shutdown: Cell::new(false),
+        }
+    }
+    /// Blocks until the next command is available (None when all the senders
+    /// are gone)
+    fn next_command(&self) -> Option<Command> {
+        let pending = self.pending.borrow_mut().pop_front();
+        pending.or_else(|| self.commands.recv().ok())
+    }
+    fn start(&self, id: usize) {
+        self.ongoing.set(Some((id, Instant::now())));
+        self.notify(Event::Started { id });
+    }
+    fn finish(&self, outcome: Outcome) {
+        if let Some((id, _)) = self.ongoing.take() {
+            self.notify(Event::Finished { id, outcome });
+        }
+    }
+    fn report_progress(&self) {
+        let (id, elapsed) = match self.ongoing.get() {
+            Some((id, start)) => (Some(id), start.elapsed()),
+            None => (None, Duration::ZERO),
+        };
+        self.notify(Event::Progress { id, elapsed });
+    }
+    fn notify(&self, event: Event) {
+        // nobody listening is not an error: the results are simply dropped
+        let _ = self.events.send(event);
+    }
+}
+impl Cutoff for Mailbox<'_> {
+    fn must_stop(&self) -> bool {
+        while let Ok(command) = self.commands.try_recv() {
+            match command {
+                Command::Cancel { id } => { self.canceled.borrow_mut().insert(id); },
+                Command::QueryProgress => self.report_progress(),
+                Command::Shutdown => {
+                    self.shutdown.set(true);
+                    self.pending.borrow_mut().push_back(Command::Shutdown);
+                },
+                solve => self.pending.borrow_mut().push_back(solve),
+            }
+        }
+        let canceled = self.ongoing.get().is_some_and(|(id, _)| self.canceled.borrow().contains(&id));
+        canceled || self.shutdown.get()
+    }
+}
+
+/// This is the body of the thread which owns the solver. It serves the
+/// commands until it is asked to shut down, or until the command channel is
+/// closed.
+pub fn serve(commands: Receiver<Command>, events: Sender<Event>) {
+    let problem = Knapsack::default();
+    let relaxation = KPRelax { pb: &problem };
+    let ranking = KPRanking;
+    let width = FixedWidth(100);
+    let dominance = EmptyDominanceChecker::default();
+    let mailbox = Mailbox::new(commands, &events);
+    let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+
+    let mut solver = SeqNoCachingSolverLel::custom(
+        &problem,
+        &relaxation,
+        &ranking,
+        &width,
+        &dominance,
+        &mailbox,
+        &mut fringe);
+
+    while let Some(command) = mailbox.next_command() {
+        match command {
+            Command::Solve { id, instance } => {
+                problem.load(instance);
+                solver.reset();
+
+                mailbox.start(id);
+                let Completion { is_exact, best_value, .. } = solver.maximize();
+                mailbox.finish(Outcome {
+                    best_value,
+                    best_solution: solver.best_solution(),
+                    is_exact,
+                    explored: solver.explored(),
+                });
+            },
+            Command::Cancel { id } => { mailbox.canceled.borrow_mut().insert(id); },
+            Command::QueryProgress => mailbox.report_progress(),
+            Command::Shutdown => break,
+        }
+    }
+}
+
+/// Starts the service in a thread of its own. It returns the endpoints of the
+/// command and event channels, as well as the handle of the service thread.
+pub fn spawn() -> (Sender<Command>, Receiver<Event>, thread::JoinHandle<()>) {
+    let (cmd_tx, cmd_rx) = channel();
+    let (evt_tx, evt_rx) = channel();
+    let handle = thread::spawn(move || serve(cmd_rx, evt_tx));
+    (cmd_tx, evt_rx, handle)
+}
+
+fn main() {
+    let (commands, events, handle) = spawn();
+
+    let small = Instance { capacity: 50, profit: vec![60, 100, 120], weight: vec![10, 20, 30] };
+    let large = Instance {
+        capacity: 1_000,
+        profit: (1..=60).map(|i| (i * 37 % 101) as isize).collect(),
+        weight: (1..=60).map(|i| i * 53 % 97 + 1).collect(),
+    };
+
+    commands.send(Command::Solve { id: 0, instance: small.clone() }).unwrap();
+    commands.send(Command::Solve { id: 1, instance: large }).unwrap();
+    commands.send(Command::Solve { id: 2, instance: small }).unwrap();
+    commands.send(Command::QueryProgress).unwrap();
+    commands.send(Command::Cancel { id: 1 }).unwrap();
+    // the service stops once all the requests have been served
+    drop(commands);
+
+    for event in events.iter() {
+        println!("{event:?}");
+    }
+    handle.join().unwrap();
+}
@@ -0,0 +1,132 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module is meant to test that one service (hence one solver object)
+//! can serve several requests without any interference between them.
+
+use std::{sync::mpsc::Receiver, time::Duration};
+
+use crate::{spawn, Command, Event, Instance, Outcome};
+
+fn small() -> Instance {
+    Instance { capacity: 50, profit: vec![60, 100, 120], weight: vec![10, 20, 30] }
+}
+fn tiny() -> Instance {
+    Instance { capacity: 10, profit: vec![5, 6, 7], weight: vec![4, 5, 6] }
+}
+/// An instance which takes way too long to be solved to optimality
+fn huge() -> Instance {
+    Instance {
+        capacity: 5_000,
+        profit: (1..=300).map(|i| (i * 37 % 101) as isize + 1).collect(),
+        weight: (1..=300).map(|i| i * 53 % 97 + 1).collect(),
+    }
+}
+
+/// Submits all the commands at once and collects the outcome of each request
+fn serve_all(commands: Vec<Command>) -> Vec<(usize, Outcome)> {
+    let (sender, events, handle) = spawn();
+    commands.into_iter().for_each(|c| sender.send(c).unwrap());
+    drop(sender);
+
+    let outcomes = events.iter()
+        .filter_map(|e| match e {
+            Event::Finished { id, outcome } => Some((id, outcome)),
+            _ => None,
+        })
+        .collect();
+    handle.join().unwrap();
+    outcomes
+}
+/// Solves the given instance with a brand new service
+fn solve_alone(instance: Instance) -> Outcome {
+    serve_all(vec![Command::Solve { id: 0, instance }]).pop().unwrap().1
+}
+/// Waits until the service emits an event that satisfies the given predicate
+fn wait_for(events: &Receiver<Event>, pred: impl Fn(&Event) -> bool) -> Event {
+    events.iter().find(pred).expect("the service stopped unexpectedly")
+}
+
+#[test]
+fn each_request_is_solved_to_optimality() {
+    let outcomes = serve_all(vec![
+        Command::Solve { id: 0, instance: small() },
+        Command::Solve { id: 1, instance: tiny() },
+    ]);
+    assert_eq!(2, outcomes.len());
+    assert_eq!(0, outcomes[0].0);
+    assert_eq!(Some(220), outcomes[0].1.best_value);
+    assert!(outcomes[0].1.is_exact);
+    assert_eq!(1, outcomes[1].0);
+    assert_eq!(Some(12), outcomes[1].1.best_value);
+    assert!(outcomes[1].1.is_exact);
+}
+#[test]
+fn two_sequential_solves_are_isolated_from_one_another() {
+    let outcomes = serve_all(vec![
+        Command::Solve { id: 0, instance: small() },
+        Command::Solve { id: 1, instance: tiny() },
+        Command::Solve { id: 2, instance: small() },
+    ]);
+    // the incumbent of the first request does not leak into the second one
+    // and vice versa: every resolution behaves as with a fresh solver
+    assert_eq!(solve_alone(small()), outcomes[0].1);
+    assert_eq!(solve_alone(tiny()),  outcomes[1].1);
+    assert_eq!(solve_alone(small()), outcomes[2].1);
+}
+#[test]
+fn a_canceled_solve_does_not_leak_into_the_next_one() {
+    // the request is canceled before it even starts. Hence it is canceled
+    // as soon as the solver consults its cutoff
+    let outcomes = serve_all(vec![
+        Command::Cancel { id: 0 },
+        Command::Solve  { id: 0, instance: small() },
+        Command::Solve  { id: 1, instance: tiny() },
+    ]);
+    assert_eq!(2, outcomes.len());
+    assert!(!outcomes[0].1.is_exact);
+    assert_eq!(solve_alone(tiny()), outcomes[1].1);
+}
+#[test]
+fn an_ongoing_solve_reports_its_progress_and_can_be_canceled() {
+    let (sender, events, handle) = spawn();
+    sender.send(Command::Solve { id: 0, instance: huge() }).unwrap();
+    sender.send(Command::Solve { id: 1, instance: small() }).unwrap();
+    wait_for(&events, |e| matches!(e, Event::Started { id: 0 }));
+
+    sender.send(Command::QueryProgress).unwrap();
+    wait_for(&events, |e| matches!(e, Event::Progress { id: Some(0), .. }));
+
+    sender.send(Command::Cancel { id: 0 }).unwrap();
+    let Event::Finished { outcome, .. } = wait_for(&events, |e| matches!(e, Event::Finished { id: 0, .. }))
+        else { unreachable!() };
+    assert!(!outcome.is_exact);
+
+    // the request which was waiting is served normally
+    let Event::Finished { outcome, .. } = wait_for(&events, |e| matches!(e, Event::Finished { id: 1, .. }))
+        else { unreachable!() };
+    assert_eq!(solve_alone(small()), outcome);
+
+    sender.send(Command::QueryProgress).unwrap();
+    let progress = wait_for(&events, |e| matches!(e, Event::Progress { .. }));
+    assert_eq!(Event::Progress { id: None, elapsed: Duration::ZERO }, progress);
+
+    sender.send(Command::Shutdown).unwrap();
+    handle.join().unwrap();
+}
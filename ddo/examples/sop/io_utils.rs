@@ -38,6 +38,23 @@ pub struct SopInstance {
     pub n_predecessors: Vec<usize>,
 }
 
+impl SopInstance {
+    /// Creates the instance whose distance matrix is given. Just like in the
+    /// instance files, `distances[i][j] == -1` means job `j` must precede job `i`.
+    pub fn new(distances: Vec<Vec<isize>>) -> Self {
+        let mut predecessors = vec![BitSet::empty(); distances.len()];
+        for (i, row) in distances.iter().enumerate() {
+            for (j, distance) in row.iter().enumerate() {
+                if *distance == -1 {
+                    predecessors[i].add_inplace(j);
+                }
+            }
+        }
+        let n_predecessors = predecessors.iter().map(|b| b.len()).collect();
+        SopInstance { nb_jobs: distances.len(), distances, predecessors, n_predecessors }
+    }
+}
+
 /// This enumeration simply groups the kind of errors that might occur when parsing a
 /// sop instance from file. There can be io errors (file unavailable ?), format error
 /// (e.g. the file is not an instance but contains the text of your next paper), 
@@ -64,7 +81,6 @@ pub fn read_instance<P: AsRef<Path>>(fname: P) -> Result<SopInstance, Error> {
     let mut lc          = 0;
     let mut nb_nodes    = 0;
     let mut distances   = vec![];
-    let mut predecessors= vec![];
 
     let mut edge_weight_section = false;
 
@@ -84,7 +100,6 @@ pub fn read_instance<P: AsRef<Path>>(fname: P) -> Result<SopInstance, Error> {
         if lc == 0 { 
             nb_nodes  = line.split_whitespace().next().unwrap().to_string().parse::<usize>().unwrap();
             distances = vec![vec![0; nb_nodes]; nb_nodes];
-            (0..nb_nodes).for_each(|_| predecessors.push(BitSet::empty()));
         }
         // The next 'nb_nodes' lines represent the distances matrix
         else if (1..=nb_nodes).contains(&lc) {
@@ -92,15 +107,11 @@ pub fn read_instance<P: AsRef<Path>>(fname: P) -> Result<SopInstance, Error> {
             for (j, distance) in line.split_whitespace().enumerate() {
                 let distance = distance.to_string().parse::<isize>().unwrap();
                 distances[i][j] = distance;
-                if distance == -1 {
-                    predecessors[i].add_inplace(j);
-                }
             }
         }
         
         lc += 1;
     }
 
-    let n_predecessors = predecessors.iter().map(|b| b.len()).collect();
-    Ok(SopInstance{nb_jobs: nb_nodes, distances, predecessors, n_predecessors})
+    Ok(SopInstance::new(distances))
 }
@@ -39,6 +39,8 @@ mod io_utils;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
 
 /// This structure uses `clap-derive` annotations and define the arguments that can
 /// be passed on to the executable solver.
@@ -95,7 +97,7 @@ fn main() {
     );
 
     let start = Instant::now();
-    let Completion{ is_exact, best_value } = solver.maximize();
+    let Completion{ is_exact, best_value, termination, .. } = solver.maximize();
     
     let duration = start.elapsed();
     let upper_bound = - solver.best_upper_bound();
@@ -110,5 +112,7 @@ fn main() {
     println!("Lower Bnd:  {}",            lower_bound);
     println!("Gap:        {:.3}",         gap);
     println!("Aborted:    {}",            !is_exact);
+    println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+    println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
     println!("Solution:   {:?}",          best_solution);
 }
@@ -0,0 +1,94 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: ordering three jobs under one precedence constraint
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand. All tours start with job 0 and end with
+//! job 4; in between, jobs 1, 2 and 3 must be visited with job 1 coming
+//! before job 3. The distance matrix reads (-1 marks a predecessor):
+//!
+//! | from \ to | 0  | 1  | 2  | 3  | 4  |
+//! |-----------|----|----|----|----|----|
+//! | 0         |  0 |  1 |  2 |  9 |  9 |
+//! | 1         | -1 |  0 |  3 |  4 |  2 |
+//! | 2         | -1 |  1 |  0 |  6 |  3 |
+//! | 3         | -1 | -1 |  2 |  0 |  1 |
+//! | 4         | -1 | -1 | -1 | -1 |  0 |
+//!
+//! Heading to the closest job first is a trap: the tour 0 -> 1 -> 2 -> 3 -> 4
+//! has length 11. The shortest feasible tour 0 -> 2 -> 1 -> 3 -> 4 has
+//! length 8; which is why the optimum of the model (which maximizes the
+//! opposite of the tour length) is -8. Every step of `walkthrough()` asserts
+//! what a user typically wants to look at while the solver runs: the bound
+//! derived from the root, the first solution found, the final gap and the
+//! decoded solution.
+
+use ddo::*;
+
+use crate::{heuristics::SopRanking, io_utils::SopInstance, model::Sop, relax::SopRelax};
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation and its ranking
+    let problem = Sop::new(SopInstance::new(vec![
+        vec![ 0,  1,  2,  9,  9],
+        vec![-1,  0,  3,  4,  2],
+        vec![-1,  1,  0,  6,  3],
+        vec![-1, -1,  2,  0,  1],
+        vec![-1, -1, -1, -1,  0],
+    ]));
+    let relaxation = SopRelax::new(&problem);
+    let ranking = SopRanking;
+
+    // 2. The solver: no dominance, no cutoff and a single node per layer
+    //    so that the root must be restricted and relaxed
+    let width = FixedWidth(1);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above
+    assert_eq!(Some(-4), solver.root_bound());
+    // 4. The restricted DD of the root falls into the trap: its best tour
+    //    has length 11
+    assert_eq!(Some(-11), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(-8), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution lists the jobs in the order of the tour (the initial
+    //    job is implicit)
+    let tour = solver.best_solution().map(|mut decisions| {
+        decisions.sort_unstable_by_key(|d| d.variable.id());
+        decisions.iter().map(|d| d.value).collect::<Vec<_>>()
+    });
+    assert_eq!(Some(vec![2, 1, 3, 4]), tour);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
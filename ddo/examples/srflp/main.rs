@@ -38,6 +38,8 @@ mod io_utils;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
 
 /// This structure uses `clap-derive` annotations and define the arguments that can
 /// be passed on to the executable solver.
@@ -79,6 +81,12 @@ fn cutoff(timeout: Option<u64>) -> Box<dyn Cutoff + Send + Sync> {
 
 /// This is your executable's entry point. It is the place where all the pieces are put together
 /// to create a fast an effective solver for the srflp problem.
+/// Converts the value of a solution into the cost of the layout it describes:
+/// the model only accounts for the part of the cost that depends on the layout
+fn layout_cost(problem: &Srflp, value: isize) -> f64 {
+    - value as f64 + problem.root_value()
+}
+
 fn main() {
     let args = Args::parse();
     let fname = &args.fname;
@@ -104,7 +112,7 @@ fn main() {
     );
 
     let start = Instant::now();
-    let Completion{ is_exact, best_value } = solver.maximize();
+    let Completion{ is_exact, best_value, termination, .. } = solver.maximize();
     
     let duration = start.elapsed();
     let upper_bound = - solver.best_upper_bound();
@@ -118,7 +126,7 @@ fn main() {
                 .collect()
         });
     let best_solution = best_solution.unwrap_or_default();
-    let best_value = best_value.map(|v| - v as f64 + problem.root_value()).unwrap_or(-1.0);
+    let best_value = best_value.map(|v| layout_cost(&problem, v)).unwrap_or(-1.0);
     
     println!("Duration:   {:.3} seconds", duration.as_secs_f32());
     println!("Objective:  {}",            best_value);
@@ -126,5 +134,7 @@ fn main() {
     println!("Lower Bnd:  {}",            lower_bound);
     println!("Gap:        {:.3}",         gap);
     println!("Aborted:    {}",            !is_exact);
+    println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+    println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
     println!("Solution:   {:?}",          best_solution);
 }
@@ -0,0 +1,97 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: laying four departments out along a corridor
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand. Four departments of lengths 2, 4, 3 and 1
+//! must be placed side by side along a corridor so as to minimize the sum of
+//! the distances between their centers, weighted by the following flows:
+//!
+//! |       | 0 | 1 | 2 | 3 |
+//! |-------|---|---|---|---|
+//! | 0     | - | 3 | 4 | 5 |
+//! | 1     | 3 | - | 0 | 1 |
+//! | 2     | 4 | 0 | - | 5 |
+//! | 3     | 5 | 1 | 5 | - |
+//!
+//! The best layout places the departments in the order 2, 3, 0, 1 (or in
+//! the reverse order) and costs 45. The model only accounts for the part of
+//! that cost which depends on the layout (6 here, hence an optimum of -6),
+//! the remaining 39 are the same for all layouts. Every step of
+//! `walkthrough()` asserts what a user typically wants to look at while the
+//! solver runs: the bound derived from the root, the first solution found,
+//! the final gap and the decoded solution.
+
+use ddo::*;
+
+use crate::{heuristics::SrflpRanking, io_utils::SrflpInstance, layout_cost, model::Srflp, relax::SrflpRelax};
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation and its ranking
+    let problem = Srflp::new(SrflpInstance {
+        nb_departments: 4,
+        lengths: vec![2, 4, 3, 1],
+        flows: vec![
+            vec![0, 3, 4, 5],
+            vec![3, 0, 0, 1],
+            vec![4, 0, 0, 5],
+            vec![5, 1, 5, 0],
+        ],
+    });
+    let relaxation = SrflpRelax::new(&problem);
+    let ranking = SrflpRanking;
+
+    // 2. The solver: no dominance, no cutoff and a single node per layer
+    //    so that the root must be restricted and relaxed
+    let width = FixedWidth(1);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above
+    assert_eq!(Some(-3), solver.root_bound());
+    // 4. The restricted DD of the root yields a first layout which costs 63
+    assert_eq!(Some(-24), solver.first_incumbent());
+    assert_eq!(63.0, layout_cost(&problem, -24));
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(-6), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+    assert_eq!(45.0, layout_cost(&problem, -6));
+
+    // 6. The solution lists the departments from one end of the corridor
+    //    to the other. Of two mirrored layouts, the solver reports one
+    let layout = solver.best_solution().map(|mut decisions| {
+        decisions.sort_unstable_by_key(|d| d.variable.id());
+        decisions.iter().map(|d| d.value).collect::<Vec<_>>()
+    });
+    assert_eq!(Some(vec![2, 3, 0, 1]), layout);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
@@ -32,6 +32,8 @@ mod io_utils;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
 
 /// This structure uses `clap-derive` annotations and define the arguments that can
 /// be passed on to the executable solver.
@@ -98,19 +100,18 @@ fn main() {
     );
 
     let start = Instant::now();
-    let Completion{ is_exact, best_value } = solver.maximize();
+    let Completion{ is_exact, best_value, termination, .. } = solver.maximize();
     
     let duration = start.elapsed();
     let upper_bound = - solver.best_upper_bound();
     let lower_bound = - solver.best_lower_bound();
     let gap = solver.gap();
-    let best_solution: Option<Vec<_>>  = solver.best_solution()
-        .map(|mut decisions|{
+    let best_solution: Option<Vec<usize>>  = solver.best_solution()
+        .and_then(|mut decisions|{
             decisions.sort_unstable_by_key(|d| d.variable.id());
-            decisions.iter()
-                .map(|d| d.value)
-                .collect()
-        });
+            decisions.typed_decisions(&IndexCodec)
+        })
+        .map(|decisions| decisions.iter().map(|d| d.value).collect());
     let best_solution = best_solution.unwrap_or_default();
     
     println!("Duration:   {:.3} seconds", duration.as_secs_f32());
@@ -119,5 +120,7 @@ fn main() {
     println!("Lower Bnd:  {}",            lower_bound);
     println!("Gap:        {:.3}",         gap);
     println!("Aborted:    {}",            !is_exact);
+    println!("Ended:      {}",            termination.map(|t| t.to_string()).unwrap_or_default());
+    println!("Stats:      {}",            solver.statistics().map(|s| s.to_string()).unwrap_or_default());
     println!("Solution:   {:?}",          best_solution);
 }
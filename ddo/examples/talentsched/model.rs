@@ -54,6 +54,13 @@ impl TalentSched {
         TalentSched {instance, actors }
     }
 
+    /// Returns the scene which is scheduled by the given decision
+    pub fn scene(&self, decision: Decision) -> usize {
+        TypedDecision::decode(decision, &IndexCodec)
+            .map(|d| d.value)
+            .expect("a decision must schedule a valid scene")
+    }
+
     fn get_present(&self, state: &TalentSchedState) -> Set64 {
         let mut before = Set64::default();
         let mut after = Set64::default();
@@ -103,15 +110,16 @@ impl Problem for TalentSched {
 
     fn transition(&self, state: &Self::State, decision: ddo::Decision) -> Self::State {
         let mut ret = state.clone();
+        let scene = self.scene(decision);
         
-        ret.scenes.remove_inplace(decision.value as usize);
-        ret.maybe_scenes.remove_inplace(decision.value as usize);
+        ret.scenes.remove_inplace(scene);
+        ret.maybe_scenes.remove_inplace(scene);
 
         ret
     }
 
     fn transition_cost(&self, state: &Self::State, _: &Self::State, decision: ddo::Decision) -> isize {
-        let scene = decision.value as usize;
+        let scene = self.scene(decision);
 
         let pay = self.get_present(state).diff(self.actors[scene]);
 
@@ -136,13 +144,13 @@ impl Problem for TalentSched {
         let mut count = 0;
 
         for i in state.scenes.iter() {
-            f.apply(Decision { variable, value: i as isize });
+            f.apply(TypedDecision { variable, value: i }.encode(&IndexCodec));
             count += 1;
         }
 
         if variable.id() + count < self.instance.nb_scenes {
             for i in state.maybe_scenes.iter() {
-                f.apply(Decision { variable, value: i as isize });
+                f.apply(TypedDecision { variable, value: i }.encode(&IndexCodec));
                 count += 1;
             }
         }
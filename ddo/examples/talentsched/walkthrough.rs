@@ -0,0 +1,98 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: shooting four scenes with three actors
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand. Each actor is paid for every day between
+//! the first and the last scene they play in, even the days when they wait:
+//!
+//! | actor | daily cost | scenes |
+//! |-------|------------|--------|
+//! | 0     | 3          | 0, 1   |
+//! | 1     | 2          | 1, 2   |
+//! | 2     | 1          | 2, 3   |
+//!
+//! Shooting the scenes 0, 1, 2 and 3 respectively takes 1, 2, 1 and 3 days.
+//! Only the orders 0, 1, 2, 3 and 3, 2, 1, 0 let no actor wait. Both cost
+//! what the actors must be paid anyway: 3 * 3 + 2 * 3 + 1 * 4 = 19; which is
+//! why the optimum of the model (which maximizes the opposite of the costs)
+//! is -19. Every step of `walkthrough()` asserts what a user typically wants
+//! to look at while the solver runs: the bound derived from the root, the
+//! first solution found, the final gap and the decoded solution.
+
+use ddo::*;
+
+use crate::{io_utils::TalentSchedInstance, model::{TalentSched, TalentSchedRanking, TalentSchedRelax}};
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation and its ranking
+    let problem = TalentSched::new(TalentSchedInstance {
+        nb_scenes: 4,
+        nb_actors: 3,
+        cost: vec![3, 2, 1],
+        duration: vec![1, 2, 1, 3],
+        actors: vec![
+            vec![1, 1, 0, 0],
+            vec![0, 1, 1, 0],
+            vec![0, 0, 1, 1],
+        ],
+    });
+    let relaxation = TalentSchedRelax::new(problem.clone());
+    let ranking = TalentSchedRanking;
+
+    // 2. The solver: no dominance, no cutoff and a single node per layer
+    //    so that the root must be restricted and relaxed
+    let width = FixedWidth(1);
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above. On this
+    //    instance, that bound is tight but the search still has to find a
+    //    schedule which meets it
+    assert_eq!(Some(-19), solver.root_bound());
+    // 4. The restricted DD of the root yields a first schedule which costs
+    //    22 because some actor has to wait
+    assert_eq!(Some(-22), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(-19), outcome.best_value);
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution tells in which order the scenes are shot
+    let order = solver.best_solution()
+        .and_then(|mut decisions| {
+            decisions.sort_unstable_by_key(|d| d.variable.id());
+            decisions.typed_decisions(&IndexCodec)
+        })
+        .map(|decisions| decisions.iter().map(|d| d.value).collect::<Vec<usize>>());
+    assert_eq!(Some(vec![0, 1, 2, 3]), order);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
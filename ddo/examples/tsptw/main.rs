@@ -23,7 +23,7 @@
 use std::{fs::File, path::Path, time::{Duration, Instant}};
 
 use clap::Parser;
-use ddo::{Completion, TimeBudget, NoDupFringe, MaxUB, Solution, SimpleDominanceChecker, Problem, DefaultCachingSolver, Solver};
+use ddo::{Completion, TimeBudget, NoDupFringe, MaxUB, Solution, SimpleDominanceChecker, Problem, DefaultCachingSolver, Solver, Statistics};
 use dominance::TsptwDominance;
 use heuristics::{TsptwWidth, TsptwRanking};
 use instance::TsptwInstance;
@@ -39,6 +39,8 @@ mod dominance;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod walkthrough;
 
 /// TSPTW is a solver based on branch-and-bound mdd which solves the traveling
 /// salesman problem with time windows to optimality. 
@@ -65,12 +67,16 @@ struct Args {
     /// (in seconds)
     #[clap(short, long)]
     duration: Option<u64>,
+    /// Filter the cities which would make one of the cities that must still
+    /// be visited unreachable in all the states of a layer out of that layer
+    #[clap(short, long)]
+    layer_filter: bool,
 }
 
 fn main() {
     let args = Args::parse();
     let inst = TsptwInstance::from(File::open(&args.instance).unwrap());
-    let pb = Tsptw::new(inst);
+    let pb = Tsptw::new(inst).with_layer_filter(args.layer_filter);
     let relax    = TsptwRelax::new(&pb);
     let width = TsptwWidth::new(pb.nb_variables(), args.width.unwrap_or(1));
     let dominance = SimpleDominanceChecker::new(TsptwDominance, pb.nb_variables());
@@ -96,16 +102,19 @@ fn main() {
     let lb       = objective(solver.best_lower_bound());
     let ub       = objective(solver.best_upper_bound());
     let solution = solver.best_solution();
+    let stats    = solver.statistics();
     let duration = finish - start;
 
-    print_solution(&instance, nb_vars, outcome, &lb, &ub, duration, solution);
+    print_solution(&instance, nb_vars, outcome, &lb, &ub, duration, solution, stats);
 }
-fn print_solution(name: &str, n: usize, completion: Completion, lb: &str, ub: &str, duration: Duration, solution: Option<Solution>) {
+#[allow(clippy::too_many_arguments)]
+fn print_solution(name: &str, n: usize, completion: Completion, lb: &str, ub: &str, duration: Duration, solution: Option<Solution>, stats: Option<Statistics>) {
     println!("instance : {name}");
     println!("status   : {}", status(completion));
     println!("lower bnd: {lb}");
     println!("upper bnd: {ub}");
     println!("duration : {}", duration.as_secs_f32());
+    println!("stats    : {}", stats.map(|s| s.to_string()).unwrap_or_default());
     println!("solution : {}", solution_to_string(n, solution));
 }
 fn instance_name<P: AsRef<Path>>(fname: P) -> String {
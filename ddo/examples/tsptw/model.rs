@@ -20,7 +20,7 @@
 //! This module contains the definition of the dynamic programming formulation 
 //! of the TSP+TW. (Implementation of the `Problem` trait).
 
-use ddo::{Problem, Variable, Decision};
+use ddo::{Problem, Variable, Decision, ValueMask};
 use smallbitset::Set256;
 
 use crate::{instance::TsptwInstance, state::{ElapsedTime, Position, TsptwState}};
@@ -31,6 +31,13 @@ use crate::{instance::TsptwInstance, state::{ElapsedTime, Position, TsptwState}}
 pub struct Tsptw {
     pub instance: TsptwInstance,
     pub initial : TsptwState,
+    /// The length of the shortest path between any two nodes when the cities
+    /// that would leave one of the cities which must still be visited out of
+    /// reach in all the states of a layer are filtered out of that layer at
+    /// once (see `Problem::filter_layer`). These are the distances that bound
+    /// the time needed to reach a node through any other ones (the instance
+    /// distances need not satisfy the triangle inequality)
+    shortest: Option<Vec<Vec<usize>>>,
 }
 impl Tsptw {
     pub fn new(inst: TsptwInstance) -> Self {
@@ -43,7 +50,33 @@ impl Tsptw {
             maybe_visit: None,
             depth : 0
         };
-        Self { instance: inst, initial: state }
+        Self { instance: inst, initial: state, shortest: None }
+    }
+    /// Enables (or disables) the filtering of the cities which would doom all
+    /// the states of a layer
+    pub fn with_layer_filter(mut self, enabled: bool) -> Self {
+        self.shortest = if enabled {
+            Some(Self::shortest_paths(&self.instance.distances))
+        } else {
+            None
+        };
+        self
+    }
+    /// Computes the all pairs shortest paths between the nodes (Floyd-Warshall)
+    fn shortest_paths(distances: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let mut shortest = distances.to_vec();
+        let n = shortest.len();
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    let via_k = shortest[i][k].saturating_add(shortest[k][j]);
+                    if via_k < shortest[i][j] {
+                        shortest[i][j] = via_k;
+                    }
+                }
+            }
+        }
+        shortest
     }
 }
 
@@ -145,9 +178,48 @@ impl Problem for Tsptw {
             Some(Variable(depth))
         }
     }
+
+    fn filter_layer(&self, variable: Variable, states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+        let mut mask = ValueMask::all();
+        let Some(shortest) = self.shortest.as_ref() else {
+            return mask;
+        };
+        // Going back to the depot is the only decision of the last layer.
+        if variable.id() == self.nb_variables() - 1 {
+            return mask;
+        }
+        // A city is kept as soon as moving to it does not doom one of the states
+        let mut kept = Set256::default();
+        for state in states {
+            let candidates = state.must_visit.iter()
+                .chain(state.maybe_visit.iter().flat_map(|maybe| maybe.iter()));
+            for j in candidates {
+                if !kept.contains(j) && !self.dooms(shortest, state, j) {
+                    kept.add_inplace(j);
+                }
+            }
+        }
+        (1..self.nb_variables())
+            .filter(|j| !kept.contains(*j))
+            .for_each(|j| mask.skip(j as isize));
+        mask
+    }
 }
 
 impl Tsptw {
+    /// Returns true iff moving to `j` from the given state makes it impossible
+    /// to reach one of the cities that must still be visited in time (or when
+    /// `j` itself cannot be reached in time).
+    fn dooms(&self, shortest: &[Vec<usize>], state: &TsptwState, j: usize) -> bool {
+        if !self.can_move_to(state, j) {
+            return true;
+        }
+        let twj     = self.instance.timewindows[j];
+        let arrival = twj.earliest.max(state.elapsed.earliest() + self.min_distance_to(state, j));
+        state.must_visit.iter()
+            .filter(|i| *i != j)
+            .any(|i| arrival + shortest[j][i] > self.instance.timewindows[i].latest)
+    }
     pub fn can_move_to(&self, state: &TsptwState, j: usize) -> bool {
         let twj         = self.instance.timewindows[j];
         let min_arrival = state.elapsed.add_duration(self.min_distance_to(state, j));
@@ -76,6 +76,46 @@ fn solve_solomon_potvin_bengio(id: &str) -> f32 {
     solve(&id, Some(1), Some(1))
 }
 
+/// Solves the given instance with or without the layer filter and returns its
+/// optimal value along with the number of explored nodes
+fn solve_with_layer_filter(instance: &str, enabled: bool) -> (f32, usize) {
+    let file = File::open(locate(instance)).expect("file not found");
+    let inst = TsptwInstance::from(file);
+    let pb = Tsptw::new(inst).with_layer_filter(enabled);
+    let mut fringe = NoDupFringe::new(MaxUB::new(&TsptwRanking));
+    let relax = TsptwRelax::new(&pb);
+    let width = TsptwWidth::new(pb.nb_variables(), 1);
+    let dominance = SimpleDominanceChecker::new(TsptwDominance, pb.nb_variables());
+    let cutoff = NoCutoff;
+    let mut solver = DefaultCachingSolver::custom(
+        &pb,
+        &relax,
+        &TsptwRanking,
+        &width,
+        &dominance,
+        &cutoff,
+        &mut fringe,
+        1,
+    );
+    let outcome = solver.maximize();
+    let value = outcome
+        .best_value
+        .map(|v| -(v as f32) / 10000.0)
+        .unwrap_or(-1.0);
+
+    (value, solver.explored())
+}
+
+#[test]
+fn the_layer_filter_keeps_the_optimum_and_explores_fewer_nodes() {
+    let (unfiltered, explored_unfiltered) = solve_with_layer_filter("SolomonPotvinBengio/rc_201.1.txt", false);
+    let (filtered, explored_filtered) = solve_with_layer_filter("SolomonPotvinBengio/rc_201.1.txt", true);
+
+    assert_eq!(592.0611, unfiltered);
+    assert_eq!(592.0611, filtered);
+    assert!(explored_filtered < explored_unfiltered, "{explored_filtered} >= {explored_unfiltered}");
+}
+
 
 #[test]
 fn n20ft301_dat() {
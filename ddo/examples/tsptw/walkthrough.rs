@@ -0,0 +1,106 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! # Walkthrough: a tour of three cities with time windows
+//!
+//! This module walks through the resolution of an instance which is small
+//! enough to be checked by hand. A vehicle leaves the depot (node 0), visits
+//! the nodes 1, 2 and 3 and comes back. Node 2 must be reached by time 5 and
+//! node 3 cannot be serviced before time 6. The instance reads (in the format
+//! of the instance files):
+//!
+//! ```text
+//! 4
+//! 0 2 4 3
+//! 2 0 3 5
+//! 4 3 0 2
+//! 3 5 2 0
+//! 0 100
+//! 0 100
+//! 0 5
+//! 6 100
+//! ```
+//!
+//! The tour 0 -> 3 -> 2 -> 1 -> 0 is as short as 0 -> 1 -> 2 -> 3 -> 0 but it
+//! misses the time window of node 2. Hence, the optimal tour is the latter:
+//! it has length 10. The model maximizes the opposite of the tour length, in
+//! ten thousandths of a unit, hence its optimum is -100 000. Every step of
+//! `walkthrough()` asserts what a user typically wants to look at while the
+//! solver runs: the bound derived from the root, the first solution found,
+//! the final gap and the decoded solution.
+
+use std::io::BufReader;
+
+use ddo::*;
+
+use crate::{dominance::TsptwDominance, heuristics::TsptwRanking, instance::TsptwInstance, model::Tsptw, objective, relax::TsptwRelax, solution_to_string};
+
+/// The tiny instance, in the format of the instance files
+const INSTANCE: &str = "
+4
+0 2 4 3
+2 0 3 5
+4 3 0 2
+3 5 2 0
+0 100
+0 100
+0 5
+6 100
+";
+
+/// Builds the model of the tiny instance, solves it with an explicitly
+/// configured solver and checks every intermediate artifact
+pub fn walkthrough() {
+    // 1. The model, its relaxation, its ranking and its dominance relation
+    let problem = Tsptw::new(TsptwInstance::from(BufReader::new(INSTANCE.as_bytes())));
+    let relaxation = TsptwRelax::new(&problem);
+    let ranking = TsptwRanking;
+    let dominance = SimpleDominanceChecker::new(TsptwDominance, problem.nb_variables());
+
+    // 2. The solver: no cutoff and a single node per layer so that the root
+    //    must be restricted and relaxed
+    let width = FixedWidth(1);
+    let cutoff = NoCutoff;
+    let mut fringe = NoDupFringe::new(MaxUB::new(&ranking));
+    let mut solver = SeqNoCachingSolverLel::new(
+        &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+    let outcome = solver.maximize();
+
+    // 3. The relaxed DD of the root bounds the optimum from above. On this
+    //    instance, that bound happens to be tight
+    assert_eq!(Some(-100_000), solver.root_bound());
+    // 4. The restricted DD of the root finds a tour which meets that bound:
+    //    the optimum is known right after the root is processed
+    assert_eq!(Some(-100_000), solver.first_incumbent());
+    // 5. The search completes: optimality is proved and the gap is closed
+    assert!(outcome.is_exact);
+    assert_eq!(Some(-100_000), outcome.best_value);
+    assert_eq!("10.00", objective(outcome.best_value.unwrap()));
+    assert_eq!(0.0, solver.gap());
+
+    // 6. The solution lists the nodes in the order of the tour
+    let tour = solution_to_string(problem.nb_variables(), solver.best_solution());
+    assert_eq!(" 1 2 3 0", tour);
+}
+
+#[test]
+fn the_walkthrough_holds() {
+    walkthrough()
+}
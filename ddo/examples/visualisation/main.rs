@@ -270,7 +270,8 @@ fn main() {
         value: 0, 
         path: vec![], 
         ub: isize::MAX, 
-        depth: 0
+        depth: 0,
+        priority_bonus: 0,
      };
     let input = CompilationInput {
         comp_type: CompilationType::Relaxed,
@@ -279,14 +280,13 @@ fn main() {
         ranking: &ranking,
         cutoff: &NoCutoff,
         max_width: 5,
-        residual: &residual,
         best_lb: isize::MIN,
         cache: &cache,
         dominance: &dominance,
     };
 
     let mut clean = Mdd::<KnapsackState, {FRONTIER}>::new();
-    _ = clean.compile(&input);
+    _ = clean.compile(&input, residual);
 
     let config = VizConfigBuilder::default()
         .show_deleted(true)
@@ -0,0 +1,62 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module defines the `StateAsBitset` abstraction: a view of the states
+//! of a problem as bitsets. It is what lets the generic `SetRelaxation` merge 
+//! the states of the problems whose states are sets of small integers.
+
+use std::ops::{BitAnd, BitOr};
+
+/// One word of the storage of a bitset (e.g. an `u32` or an `u64`)
+pub trait BitsetWord: Copy + BitAnd<Output = Self> + BitOr<Output = Self> {}
+impl<W: Copy + BitAnd<Output = W> + BitOr<Output = W>> BitsetWord for W {}
+
+/// Views the states of a problem as bitsets: the bit `i` of the word `w` 
+/// stands for the element `w * BITS + i` of the set (where BITS is the number
+/// of bits of a word). A view is typically implemented by the problem itself,
+/// since it may need to know the size of the universe to rebuild a state.
+///
+/// # Note
+/// The trailing words of a bitset may be omitted when they are zero: the 
+/// missing words are considered to be empty. In particular, the words given
+/// to `rebuild` may be fewer (after an intersection) or more (after a union)
+/// than those of some of the states they were computed from.
+pub trait StateAsBitset {
+    /// The states which are viewed as bitsets
+    type State;
+    /// The words the bitsets are made of
+    type Word: BitsetWord;
+
+    /// Borrows the words of the bitset standing for the given state
+    fn bits<'a>(&self, state: &'a Self::State) -> &'a [Self::Word];
+    /// Builds the state whose bitset is made of the given words
+    fn rebuild(&self, words: Vec<Self::Word>) -> Self::State;
+}
+
+impl<V: StateAsBitset + ?Sized> StateAsBitset for &V {
+    type State = V::State;
+    type Word = V::Word;
+
+    fn bits<'a>(&self, state: &'a Self::State) -> &'a [Self::Word] {
+        (**self).bits(state)
+    }
+    fn rebuild(&self, words: Vec<Self::Word>) -> Self::State {
+        (**self).rebuild(words)
+    }
+}
@@ -52,5 +52,22 @@ pub trait Cache {
 
     /// Clears the data structure.
     fn clear(&self);
+
+    /// Forgets which states have been explored while keeping their thresholds.
+    /// This is used when the solver restarts its search: the nodes which were
+    /// explored have left some of their descendants on the fringe which is
+    /// cleared upon restart. Hence, reaching one of these states again with
+    /// the same value must no longer be considered as a duplicate.
+    ///
+    /// By default, this clears the whole cache.
+    fn forget_explored(&self) {
+        self.clear()
+    }
+
+    /// Returns an estimate of the memory used by the cache (in bytes). 
+    /// By default, this is zero.
+    fn estimated_bytes(&self) -> usize {
+        0
+    }
     
 }
\ No newline at end of file
@@ -0,0 +1,73 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module defines the abstractions which let you work with typed values
+//! (e.g. colors, machines, or any categorical choice) rather than with the raw
+//! `isize` values carried by the decisions. The solver itself only ever deals
+//! with integer values; codecs are merely used at the boundaries of your model
+//! to encode the typed values into decisions and to decode them back.
+
+use crate::{Decision, Variable, TypedDecision};
+
+/// A decision codec is a bijection between the values of some type `V` and 
+/// (a subset of) the integers which are used as values of the decisions.
+pub trait DecisionCodec<V> {
+    /// Returns the integer value which encodes the given typed value
+    fn encode(&self, value: &V) -> isize;
+    /// Returns the typed value encoded by the given integer, or None when 
+    /// that integer does not encode any value of type `V`
+    fn decode(&self, value: isize) -> Option<V>;
+}
+
+/// This trait is implemented by the categorical types (typically field-less 
+/// enums) whose values can be identified with an integer code. You will most
+/// likely not want to implement it by hand: the `categorical!` macro derives
+/// it for your enums. Any `Categorical` type can then be encoded in decisions
+/// with the `EnumCodec`.
+pub trait Categorical: Sized {
+    /// Returns the code of this value
+    fn code(&self) -> isize;
+    /// Returns the value identified by the given code (if any)
+    fn from_code(code: isize) -> Option<Self>;
+}
+
+/// This trait provides typed accessors to the values of a solution. It is 
+/// implemented for any slice of decisions; hence for the `Solution` type too.
+pub trait TypedSolution {
+    /// Returns the value assigned to the given variable (if any)
+    fn value_of(&self, variable: Variable) -> Option<isize>;
+    /// Returns the decoded value assigned to the given variable. This method
+    /// returns None when the variable is not assigned in the solution or when
+    /// its value cannot be decoded by the codec.
+    fn typed_value_of<V>(&self, variable: Variable, codec: &impl DecisionCodec<V>) -> Option<V>;
+    /// Decodes all the decisions of the solution at once
+    fn typed_decisions<V>(&self, codec: &impl DecisionCodec<V>) -> Option<Vec<TypedDecision<V>>>;
+}
+
+impl TypedSolution for [Decision] {
+    fn value_of(&self, variable: Variable) -> Option<isize> {
+        self.iter().find(|d| d.variable == variable).map(|d| d.value)
+    }
+    fn typed_value_of<V>(&self, variable: Variable, codec: &impl DecisionCodec<V>) -> Option<V> {
+        self.value_of(variable).and_then(|v| codec.decode(v))
+    }
+    fn typed_decisions<V>(&self, codec: &impl DecisionCodec<V>) -> Option<Vec<TypedDecision<V>>> {
+        self.iter().map(|d| TypedDecision::decode(*d, codec)).collect()
+    }
+}
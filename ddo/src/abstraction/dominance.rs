@@ -122,7 +122,12 @@ pub trait DominanceChecker {
 
     /// Comparator to order states by increasing value, regardless of their key
     fn cmp(&self, a: &Self::State, val_a: isize, b: &Self::State, val_b: isize) -> Ordering;
-    
+
+    /// Returns an estimate of the memory used by the checker (in bytes). 
+    /// By default, this is zero.
+    fn estimated_bytes(&self) -> usize {
+        0
+    }
 }
 
 #[cfg(test)]
@@ -24,7 +24,9 @@
 //! The most important abstractions that should be provided by a client are
 //! `Problem` and `Relaxation`.
 
-use crate::{Variable, Decision};
+use std::{any::Any, sync::Arc};
+
+use crate::{Variable, Decision, ValueMask};
 
 /// This trait defines the "contract" of what defines an optimization problem
 /// solvable with the branch-and-bound with DD paradigm. An implementation of
@@ -48,6 +50,20 @@ pub trait Problem {
     /// This method is an implementation of the transition cost function mentioned
     /// in the mathematical model of a DP formulation for some problem.
     fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: Decision) -> isize;
+    /// This method is the same as `transition` except that it gets to use the
+    /// scratch context of the decision diagram being compiled (see 
+    /// `ProblemCtx`). By default, the context is ignored. When you override 
+    /// it, it must yield the same state as `transition`.
+    fn transition_with_ctx(&self, state: &Self::State, decision: Decision, _ctx: &mut ProblemCtx) -> Self::State {
+        self.transition(state, decision)
+    }
+    /// This method is the same as `transition_cost` except that it gets to 
+    /// use the scratch context of the decision diagram being compiled (see 
+    /// `ProblemCtx`). By default, the context is ignored. When you override 
+    /// it, it must yield the same cost as `transition_cost`.
+    fn transition_cost_with_ctx(&self, source: &Self::State, dest: &Self::State, decision: Decision, _ctx: &mut ProblemCtx) -> isize {
+        self.transition_cost(source, dest, decision)
+    }
     /// Any problem needs to be able to specify an ordering on the variables
     /// in order to decide which variable should be assigned next. This choice
     /// is an **heuristic** choice. The variable ordering does not need to be
@@ -68,6 +84,67 @@ pub trait Problem {
     fn is_impacted_by(&self, _var: Variable, _state: &Self::State) -> bool {
         true
     }
+    /// This method returns the depth $k$ after which the objective value is 
+    /// fully determined. In other words, if it returns `Some(k)`, all the 
+    /// transitions made from a state at depth $k$ or deeper must have a zero
+    /// cost: the remaining variables only matter for the sake of feasibility.
+    /// 
+    /// When that is the case, the restricted and relaxed DDs keep only one 
+    /// node per `feasibility_class` in the layers past that depth. 
+    fn objective_depth(&self) -> Option<usize> {
+        None
+    }
+    /// This method returns the feasibility class of the given state. It is 
+    /// only ever consulted past the `objective_depth` and two states may only
+    /// belong to the same class when they admit exactly the same feasible 
+    /// completions. States for which it returns `None` are never collapsed.
+    fn feasibility_class(&self, _state: &Self::State) -> Option<u64> {
+        None
+    }
+    /// This method returns true when it can cheaply tell that states `a` and
+    /// `b` are different (for instance, by comparing a fingerprint cached in 
+    /// the states). It is consulted before the full equality test when 
+    /// deduplicating the nodes of a layer, which pays off when your states
+    /// are large and `Eq` is expensive. Returning false means "I don't know":
+    /// the decision is then deferred to `Eq`.
+    fn quick_neq(&self, _a: &Self::State, _b: &Self::State) -> bool {
+        false
+    }
+    /// This method returns a per-depth multiplier of the maximum width given
+    /// by the width heuristic. This lets you spend the width budget on the 
+    /// layers which are known to explode rather than uniformly. When it 
+    /// returns `Some(profile)`, the layer at depth $k$ may comprise at most
+    /// `profile[k]` times the width of the heuristic; the layers deeper than
+    /// the profile keep the width of the heuristic. By default, there is no
+    /// profile.
+    fn width_profile(&self) -> Option<Vec<usize>> {
+        None
+    }
+    /// This method is called once per layer, before its nodes are expanded
+    /// by branching on `var`. It lets you skip the values which you can 
+    /// cheaply tell are infeasible for all the `states` of the layer (think of
+    /// a global constraint view) rather than filtering them node by node. The
+    /// values which are skipped are removed from the domain of every node of
+    /// the layer. By default, all values are allowed.
+    fn filter_layer(&self, _var: Variable, _states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+        ValueMask::all()
+    }
+    /// This method returns true when the domain of `var` is the same in all
+    /// states (think of a binary variable which can always be set to 0 or 1).
+    /// When that is the case, the DDs only call `for_each_in_domain` once per
+    /// layer (with any state of the layer) and reuse the values it yields for
+    /// all the nodes of that layer. By default, the domains are assumed to 
+    /// depend on the state.
+    fn domain_is_state_independent(&self, _var: Variable) -> bool {
+        false
+    }
+    /// This method returns a dump of the given state (typically, its `Debug`
+    /// output). It is only used to tell which state made the compilation 
+    /// stop when the progress check (`progress-check` feature) fails (see
+    /// `Reason::NoProgress`). By default, the states are not dumped.
+    fn dump_state(&self, _state: &Self::State) -> Option<String> {
+        None
+    }
 }
 
 /// A relaxation encapsulates the relaxation $\Gamma$ and $\oplus$ which are
@@ -83,13 +160,27 @@ pub trait Relaxation {
     /// and yields a new state which is supposed to stand for all the other
     /// merged states. In the mathematical model, this operation was denoted
     /// with the $\oplus$ operator.
+    ///
+    /// # Important
+    /// A large group of states is not merged in one call: the DD merges it by
+    /// chunks (of at most 1024 states by default) and then merges the partial
+    /// results. Hence, merging the result of some merges must yield a state
+    /// that is (at least) as relaxed as merging all the states at once. This
+    /// holds as soon as the merge is associative, e.g. a component-wise max,
+    /// union or intersection.
+    ///
+    /// # Note
+    /// This method is always given at least two states. When a group of
+    /// nodes to merge boils down to one single node, nothing needs to be
+    /// relaxed: that node is left untouched and remains exact.
     fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State;
     
     /// This method relaxes the cost associated to a particular decision. It
     /// is called for any arc labeled `decision` whose weight needs to be 
     /// adjusted because it is redirected from connecting `src` with `dst` to 
     /// connecting `src` with `new`. In the mathematical model, this operation
-    /// is denoted by the operator $\Gamma$.
+    /// is denoted by the operator $\Gamma$. Like `merge`, it is never called
+    /// for the arcs of a node which is alone in its group.
     fn relax(
         &self,
         source: &Self::State,
@@ -100,12 +191,97 @@ pub trait Relaxation {
     ) -> isize;
 
     /// Returns a very rough estimation (upper bound) of the optimal value that 
-    /// could be reached if state were the initial state
+    /// could be reached if state were the initial state. Besides pruning the
+    /// nodes of the decision diagrams as they are compiled, the solvers use it
+    /// to tighten the upper bound of the cut-set nodes before pushing them 
+    /// onto the fringe.
     fn fast_upper_bound(&self, _state: &Self::State) -> isize {
         isize::MAX
     }
 }
 
+/// A problem which is shared behind an `Arc` is a problem as well. This lets
+/// you own the problem (e.g. in a relaxation which needs to access it) rather
+/// than borrowing it.
+impl<P: Problem + ?Sized> Problem for Arc<P> {
+    type State = P::State;
+
+    fn nb_variables(&self) -> usize {
+        (**self).nb_variables()
+    }
+    fn initial_state(&self) -> Self::State {
+        (**self).initial_state()
+    }
+    fn initial_value(&self) -> isize {
+        (**self).initial_value()
+    }
+    fn transition(&self, state: &Self::State, decision: Decision) -> Self::State {
+        (**self).transition(state, decision)
+    }
+    fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: Decision) -> isize {
+        (**self).transition_cost(source, dest, decision)
+    }
+    fn transition_with_ctx(&self, state: &Self::State, decision: Decision, ctx: &mut ProblemCtx) -> Self::State {
+        (**self).transition_with_ctx(state, decision, ctx)
+    }
+    fn transition_cost_with_ctx(&self, source: &Self::State, dest: &Self::State, decision: Decision, ctx: &mut ProblemCtx) -> isize {
+        (**self).transition_cost_with_ctx(source, dest, decision, ctx)
+    }
+    fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+        -> Option<Variable> {
+        (**self).next_variable(depth, next_layer)
+    }
+    fn for_each_in_domain(&self, var: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+        (**self).for_each_in_domain(var, state, f)
+    }
+    fn is_impacted_by(&self, var: Variable, state: &Self::State) -> bool {
+        (**self).is_impacted_by(var, state)
+    }
+    fn objective_depth(&self) -> Option<usize> {
+        (**self).objective_depth()
+    }
+    fn feasibility_class(&self, state: &Self::State) -> Option<u64> {
+        (**self).feasibility_class(state)
+    }
+    fn quick_neq(&self, a: &Self::State, b: &Self::State) -> bool {
+        (**self).quick_neq(a, b)
+    }
+    fn width_profile(&self) -> Option<Vec<usize>> {
+        (**self).width_profile()
+    }
+    fn filter_layer(&self, var: Variable, states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+        (**self).filter_layer(var, states)
+    }
+    fn domain_is_state_independent(&self, var: Variable) -> bool {
+        (**self).domain_is_state_independent(var)
+    }
+    fn dump_state(&self, state: &Self::State) -> Option<String> {
+        (**self).dump_state(state)
+    }
+}
+
+/// A relaxation which is shared behind an `Arc` is a relaxation as well
+impl<R: Relaxation + ?Sized> Relaxation for Arc<R> {
+    type State = R::State;
+
+    fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+        (**self).merge(states)
+    }
+    fn relax(
+        &self,
+        source: &Self::State,
+        dest: &Self::State,
+        new: &Self::State,
+        decision: Decision,
+        cost: isize,
+    ) -> isize {
+        (**self).relax(source, dest, new, decision, cost)
+    }
+    fn fast_upper_bound(&self, state: &Self::State) -> isize {
+        (**self).fast_upper_bound(state)
+    }
+}
+
 /// This trait basically defines a callback which is passed on to the problem
 /// so as to let it efficiently enumerate the domain values of some given 
 /// variable.
@@ -121,9 +297,52 @@ impl <X: FnMut(Decision)> DecisionCallback for X {
     }
 }
 
+/// The scratch context which a decision diagram passes on to the transition
+/// functions of the problem (see `Problem::transition_with_ctx`). It gives 
+/// your model a mutable place for its temporary data (a reusable buffer, a 
+/// memo table, ..) without resorting to interior mutability: each decision 
+/// diagram owns its own context (hence, each worker of a parallel solver has
+/// its own) and keeps it from one compilation to the next.
+///
+/// The context holds at most one value whose type is chosen by the problem.
+/// That value is created with its default value the first time it is needed.
+#[derive(Default)]
+pub struct ProblemCtx {
+    data: Option<Box<dyn Any + Send>>,
+}
+impl ProblemCtx {
+    /// Returns the scratch value of type `C`. It is created (with its default
+    /// value) the first time this method is called; and it replaces whatever
+    /// value of some other type the context used to hold.
+    pub fn get<C: Any + Send + Default>(&mut self) -> &mut C {
+        if !self.data.as_deref().is_some_and(|d| d.is::<C>()) {
+            self.data = Some(Box::new(C::default()));
+        }
+        self.data.as_deref_mut()
+            .and_then(|d| d.downcast_mut::<C>())
+            .expect("the scratch value has just been created")
+    }
+}
+/// Cloning a context yields an empty context: the scratch value of a decision
+/// diagram is never shared with another one.
+impl Clone for ProblemCtx {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+impl std::fmt::Debug for ProblemCtx {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProblemCtx")
+            .field("is_empty", &self.data.is_none())
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{Relaxation, DecisionCallback, Decision, Problem};
+    use std::sync::Arc;
+
+    use crate::{Relaxation, DecisionCallback, Decision, Problem, ProblemCtx};
     
     #[test]
     fn by_default_fast_upperbound_yields_positive_max() {
@@ -136,6 +355,69 @@ mod tests {
         assert!(pb.is_impacted_by(crate::Variable(10), &'x'));
     }
 
+    #[test]
+    fn by_default_the_objective_depends_on_all_variables() {
+        let pb = DummyProblem;
+        assert_eq!(None, pb.objective_depth());
+        assert_eq!(None, pb.feasibility_class(&'x'));
+    }
+    #[test]
+    fn by_default_quick_neq_defers_to_eq() {
+        let pb = DummyProblem;
+        assert!(!pb.quick_neq(&'x', &'y'));
+        assert!(!pb.quick_neq(&'x', &'x'));
+    }
+    #[test]
+    fn by_default_there_is_no_width_profile() {
+        let pb = DummyProblem;
+        assert_eq!(None, pb.width_profile());
+    }
+    #[test]
+    fn by_default_no_value_is_filtered_out_of_a_layer() {
+        let pb = DummyProblem;
+        let mask = pb.filter_layer(crate::Variable(0), &mut ['x', 'y'].iter());
+        assert!(mask.allows_all());
+    }
+
+    #[test]
+    fn a_shared_problem_delegates_to_the_problem_it_wraps() {
+        let pb: Arc<dyn Problem<State = char>> = Arc::new(DummyProblem);
+        assert!(pb.is_impacted_by(crate::Variable(10), &'x'));
+        assert_eq!(None, pb.objective_depth());
+        assert_eq!(None, pb.width_profile());
+        assert!(!pb.quick_neq(&'x', &'y'));
+    }
+    #[test]
+    fn by_default_the_ctx_is_ignored_by_the_transitions() {
+        let mut ctx = ProblemCtx::default();
+        let decision = Decision{variable: crate::Variable(0), value: 4};
+        let pb = DummyProblem;
+        assert_eq!(pb.transition(&'x', decision), pb.transition_with_ctx(&'x', decision, &mut ctx));
+        assert_eq!(pb.transition_cost(&'x', &'y', decision), pb.transition_cost_with_ctx(&'x', &'y', decision, &mut ctx));
+        assert!(ctx.data.is_none());
+    }
+    #[test]
+    fn the_ctx_keeps_its_value_as_long_as_the_same_type_is_asked() {
+        let mut ctx = ProblemCtx::default();
+        ctx.get::<Vec<usize>>().push(42);
+        assert_eq!(&vec![42], ctx.get::<Vec<usize>>());
+        // asking for another type replaces the value
+        *ctx.get::<usize>() += 1;
+        assert_eq!(1, *ctx.get::<usize>());
+        assert!(ctx.get::<Vec<usize>>().is_empty());
+    }
+    #[test]
+    fn a_cloned_ctx_is_empty() {
+        let mut ctx = ProblemCtx::default();
+        *ctx.get::<usize>() = 42;
+        assert!(ctx.clone().data.is_none());
+    }
+    #[test]
+    fn a_shared_relaxation_delegates_to_the_relaxation_it_wraps() {
+        let rlx = Arc::new(DummyRelax);
+        assert_eq!(isize::MAX, rlx.fast_upper_bound(&'x'));
+    }
+
     #[test]
     fn any_closure_is_a_decision_callback() {
         let mut changed = false;
@@ -162,11 +444,11 @@ mod tests {
         fn initial_value(&self) -> isize {
             todo!()
         }
-        fn transition(&self, _: &Self::State, _: Decision) -> Self::State {
-            todo!()
+        fn transition(&self, state: &Self::State, _: Decision) -> Self::State {
+            *state
         }
-        fn transition_cost(&self, _: &Self::State, _: &Self::State, _: Decision) -> isize {
-            todo!()
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, d: Decision) -> isize {
+            d.value
         }
         fn next_variable(&self, _: usize, _: &mut dyn Iterator<Item = &Self::State>)
             -> Option<crate::Variable> {
@@ -0,0 +1,98 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module defines the `TreeExport` trait which lets a solver report the
+//! search tree it explores to some external tool (e.g. a tree visualizer), 
+//! along with the events it is made of.
+
+/// The reason why a node of the search tree has not been explored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PruneReason {
+    /// Its upper bound could not improve on the best known lower bound
+    Bound,
+    /// The cache told that a node dominating it had been explored already
+    Cache,
+    /// It was removed from the fringe without being processed. This happens
+    /// when the fringe discards the nodes that cannot improve on a new 
+    /// incumbent, when it merges duplicate nodes, and when the search is 
+    /// interrupted or restarted.
+    Dropped,
+}
+impl PruneReason {
+    /// Returns the name of this reason (the one used in the JSON exports)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PruneReason::Bound   => "bound",
+            PruneReason::Cache   => "cache",
+            PruneReason::Dropped => "dropped",
+        }
+    }
+}
+
+/// An event of the search tree. The nodes are identified by consecutive ids
+/// starting at zero, in the order of their creation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TreeEvent {
+    /// A node was created. The root nodes have no parent; the others are the
+    /// nodes of the cut-set of the relaxed DD compiled for their parent.
+    Created {
+        /// The id of the new node
+        node: usize,
+        /// The id of the node whose DD produced it
+        parent: Option<usize>,
+        /// The depth of the node
+        depth: usize,
+        /// The value of the longest path from the root of the problem
+        value: isize,
+        /// The upper bound of the node
+        ub: isize,
+    },
+    /// A node was closed without being explored
+    Pruned {
+        /// The id of the node
+        node: usize,
+        /// Why it was not explored
+        reason: PruneReason,
+    },
+    /// A node was closed because the DD compiled for it was exact
+    Fathomed {
+        /// The id of the node
+        node: usize,
+        /// The best value of the node (None when it is infeasible)
+        value: Option<isize>,
+    },
+    /// The best known bounds of the problem have changed
+    Bounds {
+        /// The best lower bound
+        lb: isize,
+        /// The best upper bound
+        ub: isize,
+    },
+}
+
+/// A tree export is a sink which gets told about all the events of the search 
+/// tree explored by a solver, in the order where they occur. The nodes which
+/// have been created are eventually either pruned, fathomed or branched on
+/// (when they are the parent of other nodes).
+pub trait TreeExport {
+    /// Records the given event
+    fn export(&mut self, event: &TreeEvent);
+}
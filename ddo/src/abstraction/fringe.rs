@@ -17,7 +17,7 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use crate::SubProblem;
+use crate::{SubProblem, subproblem_bytes};
 
 
 /// This trait abstracts away the implementation details of the solver fringe.
@@ -30,10 +30,21 @@ pub trait Fringe {
     fn push(&mut self, node: SubProblem<Self::State>);
     /// This method yields the most promising node from the fringe.
     /// # Note:
-    /// The solvers rely on the assumption that a fringe will pop nodes in
-    /// descending upper bound order. Hence, it is a requirement for any fringe
-    /// implementation to enforce that requirement.
+    /// The nodes are popped in the order of the ranking of the fringe, which
+    /// need not be the descending upper bound order: the solvers never infer
+    /// their best upper bound from the popped nodes (see `best_ub`).
     fn pop(&mut self) -> Option<SubProblem<Self::State>>;
+    /// This method returns a reference to the node that would be popped next
+    /// without removing it from the fringe.
+    fn peek(&self) -> Option<&SubProblem<Self::State>>;
+    /// Returns the best upper bound of all the nodes which are still open (None
+    /// when the fringe is empty). The solvers rely on it to tighten their
+    /// best upper bound. By default, this is the upper bound of the node that
+    /// would be popped next: a fringe which does not pop its nodes in 
+    /// descending upper bound order must override this method.
+    fn best_ub(&self) -> Option<isize> {
+        self.peek().map(|n| n.ub)
+    }
     /// This method clears the fringe: it removes all nodes from the queue.
     fn clear(&mut self);
     /// Yields the length of the queue.
@@ -42,4 +53,47 @@ pub trait Fringe {
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
+    /// Returns an estimate of the memory used by the nodes of the fringe (in 
+    /// bytes). By default, this is the number of nodes times the estimated
+    /// size of one node (see `subproblem_bytes`).
+    fn estimated_bytes(&self) -> usize {
+        self.len() * subproblem_bytes::<Self::State>()
+    }
+    /// This method is called by the solvers whenever the best lower bound 
+    /// improves. It gives the fringe an opportunity to drop the nodes whose 
+    /// upper bound is no greater than `best_lb`: these can no longer improve 
+    /// the best solution. The depth of each dropped node must be reported to
+    /// `dropped`. By default, nothing is dropped: the solvers discard these 
+    /// nodes when they are popped anyway.
+    fn prune(&mut self, _best_lb: isize, _dropped: &mut dyn FnMut(usize)) {}
+    /// Applies the given function `f` to each node of the fringe, in no 
+    /// particular order and without removing any of them. This is what lets
+    /// the solvers take snapshots of the composition of the fringe (see 
+    /// `FringeSnapshot`). By default, no node is visited.
+    fn inspect(&self, _f: &mut dyn FnMut(&SubProblem<Self::State>)) {}
+    /// This method is called by the solvers once they are done pushing the 
+    /// nodes of a cut-set. It gives a fringe which has a limited capacity 
+    /// (see `BoundedFringe`) an opportunity to drop the nodes which do not 
+    /// fit. The depth of each dropped node must be reported to `dropped`. By
+    /// default, the capacity of a fringe is unlimited and nothing is dropped.
+    fn shed_excess(&mut self, _dropped: &mut dyn FnMut(usize)) {}
+    /// Returns a summary of the nodes which were dropped by `shed_excess`. 
+    /// The solvers rely on it to tell whether the optimality of their best
+    /// solution could still be proved, and to keep a valid upper bound.
+    fn shed(&self) -> ShedNodes {
+        ShedNodes::default()
+    }
+}
+
+/// The open nodes which a fringe dropped for lack of room (see 
+/// `Fringe::shed_excess`). Unlike the pruned nodes, these could still lead 
+/// to a better solution: a search whose fringe shed any node of which the 
+/// upper bound exceeds the best solution is no longer exact.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShedNodes {
+    /// The number of nodes which were dropped
+    pub count: usize,
+    /// The best upper bound of the nodes which were dropped since the fringe
+    /// was last cleared (None when there is none)
+    pub best_ub: Option<isize>,
 }
\ No newline at end of file
@@ -25,10 +25,14 @@
 //!  - the `StateRanking` heuristic which is used to guess the nodes promising-ness
 //!  - the `Cutoff` heuristic which is used to impose a stopping criterion on the
 //!    solver resolution.
+//!  - the `Reseed` trait which lets the solver re-seed the randomized heuristics
+//!    whenever it restarts the search.
+//!  - the `CarryOver` trait which lets the adaptive heuristics learn from the
+//!    compilations performed over the course of the search.
 
-use std::cmp::Ordering;
+use std::{cmp::Ordering, sync::Arc};
 
-use crate::SubProblem;
+use crate::{CompilationType, Decision, SubProblem};
 
 /// This trait encapsulates the behavior of the heuristic that determines
 /// the maximum permitted width of a decision diagram.
@@ -79,6 +83,24 @@ pub trait StateRanking {
     /// This method compares two states and determines which is the most 
     /// desirable to keep. In this ordering, 
     fn compare(&self, a: &Self::State, b: &Self::State) -> Ordering;
+    /// Returns true iff the node having the given state must survive the 
+    /// restriction or relaxation of its layer (e.g. because it lies on the 
+    /// path of the incumbent). These nodes are kept ahead of all the others
+    /// as long as there are fewer of them than the number of nodes which can
+    /// be kept in the layer. Otherwise, the conflict is resolved according to
+    /// the `WidthConflictPolicy` of the DD (by default, the layer exceeds its
+    /// width) and `MddStats::keep_list_overflow` is set. By default, no node
+    /// must be kept.
+    fn must_keep(&self, _state: &Self::State) -> bool {
+        false
+    }
+    /// Returns a dump of the given state (typically, its `Debug` output). It
+    /// is only used to describe the states that were being compared when this
+    /// ranking panics while the ranking check (`ranking-check` feature) is 
+    /// enabled. By default, the states are not described.
+    fn dump_state(&self, _state: &Self::State) -> Option<String> {
+        None
+    }
 }
 
 /// A subproblem ranking is an heuristic that imposes a partial order on
@@ -94,6 +116,22 @@ pub trait SubProblemRanking {
     /// that needs to be popped off the fringe first. In this ordering, greater
     /// means more likely to be popped first.
     fn compare(&self, a: &SubProblem<Self::State>, b: &SubProblem<Self::State>) -> Ordering;
+
+    /// Returns true iff this ranking pops the sub-problems in descending upper
+    /// bound order (which is the case by default). This is only informative:
+    /// the fringes keep track of the upper bounds of their nodes whatever 
+    /// their ranking, so that the best upper bound they report does not 
+    /// depend on the order in which they pop their nodes (see `Fringe::best_ub`).
+    fn orders_by_ub(&self) -> bool {
+        true
+    }
+    /// Returns a dump of the given state (typically, its `Debug` output). It
+    /// is only used to describe the sub-problems that were being compared when
+    /// this ranking panics while the ranking check (`ranking-check` feature)
+    /// is enabled. By default, the states are not described.
+    fn dump_state(&self, _state: &Self::State) -> Option<String> {
+        None
+    }
 }
 
 /// This trait encapsulates a criterion (external to the solver) which imposes
@@ -102,4 +140,113 @@ pub trait SubProblemRanking {
 pub trait Cutoff {
     /// Returns true iff the criterion is met and the search must stop.
     fn must_stop(&self) -> bool;
-}
\ No newline at end of file
+}
+
+/// This trait is implemented by the randomized heuristics (a randomized variable
+/// ordering in `Problem::next_variable`, for instance) whose seed is controlled
+/// by the solver. When restarts are enabled, the solver calls `reseed` before
+/// each run of the search with a seed derived from its master seed. Therefore
+/// the same master seed always yields the same sequence of runs.
+///
+/// # Note
+/// The solver only calls this method when no node is being processed. Still,
+/// since it only gets a shared reference, an implementation will typically
+/// store its seed in a `Cell` or an atomic.
+pub trait Reseed {
+    /// Re-initializes the pseudo random number generator with the given seed
+    fn reseed(&self, seed: u64);
+}
+
+/// What the solver tells the adaptive heuristics about a compilation it has
+/// just performed (see `CarryOver`)
+#[derive(Debug, Clone, Copy)]
+pub struct CompilationOutcome<'a> {
+    /// The type of the DD which was compiled
+    pub comp_type: CompilationType,
+    /// The depth of the subproblem the DD was rooted in
+    pub depth: usize,
+    /// The upper bound of that subproblem before the compilation
+    pub node_ub: isize,
+    /// The best value of the DD (None when it has no feasible path)
+    pub best_value: Option<isize>,
+    /// Whether the DD was exact
+    pub is_exact: bool,
+    /// The decisions of the best path of the DD which were made within the
+    /// DD (the ones leading to its root are left out), in no particular order
+    pub best_path: &'a [Decision],
+}
+
+/// This trait is implemented by the adaptive heuristics (e.g. a variable
+/// ordering which learns the scores of the variables, see `ActivityOrder`) 
+/// whose learned state persists from one compilation to the next. When such
+/// a heuristic is given to the solver (`with_carry_over`), the solver calls 
+/// `carry_over` after each compilation of a subproblem, in this order:
+///
+/// 1. after the restricted DD has been compiled;
+/// 2. after the relaxed DD has been compiled (unless the restricted DD was
+///    exact);
+/// 3. after the relaxed DD has been compiled once again with a larger width
+///    (only when `with_wider_recompile` asks so).
+///
+/// Each call happens once the incumbent has been updated with the DD and 
+/// before its cut-set is enqueued; hence the next compilation always sees
+/// what was learned from the previous ones.
+///
+/// # Note
+/// Since it only gets a shared reference, an implementation will typically
+/// store its learned state in a `Cell`, a `Mutex` or an atomic. With the 
+/// parallel solver, the calls made by the different threads interleave.
+pub trait CarryOver {
+    /// Updates the learned state of the heuristic with the outcome of the 
+    /// compilation which was just performed
+    fn carry_over(&self, from: &CompilationOutcome);
+}
+
+// The heuristics which are shared behind an `Arc` are heuristics as well.
+// This lets you own them rather than borrowing them.
+impl<State, W: WidthHeuristic<State> + ?Sized> WidthHeuristic<State> for Arc<W> {
+    fn max_width(&self, state: &SubProblem<State>) -> usize {
+        (**self).max_width(state)
+    }
+}
+impl<R: StateRanking + ?Sized> StateRanking for Arc<R> {
+    type State = R::State;
+
+    fn compare(&self, a: &Self::State, b: &Self::State) -> Ordering {
+        (**self).compare(a, b)
+    }
+    fn must_keep(&self, state: &Self::State) -> bool {
+        (**self).must_keep(state)
+    }
+    fn dump_state(&self, state: &Self::State) -> Option<String> {
+        (**self).dump_state(state)
+    }
+}
+impl<R: SubProblemRanking + ?Sized> SubProblemRanking for Arc<R> {
+    type State = R::State;
+
+    fn compare(&self, a: &SubProblem<Self::State>, b: &SubProblem<Self::State>) -> Ordering {
+        (**self).compare(a, b)
+    }
+    fn orders_by_ub(&self) -> bool {
+        (**self).orders_by_ub()
+    }
+    fn dump_state(&self, state: &Self::State) -> Option<String> {
+        (**self).dump_state(state)
+    }
+}
+impl<C: Cutoff + ?Sized> Cutoff for Arc<C> {
+    fn must_stop(&self) -> bool {
+        (**self).must_stop()
+    }
+}
+impl<R: Reseed + ?Sized> Reseed for Arc<R> {
+    fn reseed(&self, seed: u64) {
+        (**self).reseed(seed)
+    }
+}
+impl<H: CarryOver + ?Sized> CarryOver for Arc<H> {
+    fn carry_over(&self, from: &CompilationOutcome) {
+        (**self).carry_over(from)
+    }
+}
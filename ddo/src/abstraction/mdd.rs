@@ -17,7 +17,9 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use crate::{SubProblem, Completion, Reason, Problem, Relaxation, StateRanking, Solution, Cutoff, Cache, DominanceChecker};
+use std::fmt;
+
+use crate::{Decision, SubProblem, Completion, Reason, Problem, Relaxation, StateRanking, Solution, Cutoff, Cache, DominanceChecker};
 
 // FIXME: Replace that with the following enum definition when const generics allow enum types
 /// What type of cut-set are we using for relaxed DDs ?
@@ -26,6 +28,9 @@ pub type CutsetType = u8;
 pub const LAST_EXACT_LAYER: u8 = 1;
 /// enqueue all exact nodes that have at least a relaxed child node
 pub const FRONTIER: u8 = 2;
+/// choose between the last exact layer and the frontier cut-set upon each 
+/// compilation, depending on how deep the first inexact layer of the DD is
+pub const ADAPTIVE: u8 = 3;
 /*
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CutsetType {
@@ -61,8 +66,6 @@ pub struct CompilationInput<'a, State> {
     pub cutoff: &'a dyn Cutoff,
     /// What is the maximum width of the mdd ?
     pub max_width: usize,
-    /// The subproblem whose state space must be explored
-    pub residual: &'a SubProblem<State>,
     /// The best known lower bound at the time when the dd is being compiled
     pub best_lb: isize,
     /// Data structure containing info about past compilations used to prune the search
@@ -70,6 +73,121 @@ pub struct CompilationInput<'a, State> {
     pub dominance: &'a dyn DominanceChecker<State = State>,
 }
 
+/// Some information about the shape of the last DD that was compiled. It is
+/// non exhaustive: outside of this crate, start from its default value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MddStats {
+    /// The number of layers of the DD (including its root layer)
+    pub nb_layers: usize,
+    /// The index of the first layer that had to be restricted or relaxed. 
+    /// This is None when the DD is exact.
+    pub first_inexact_layer: Option<usize>,
+    /// The type of cut-set which has been computed for the DD. With an 
+    /// `ADAPTIVE` DD, this is either `LAST_EXACT_LAYER` or `FRONTIER`.
+    pub cutset_type: CutsetType,
+    /// True iff some layer had more nodes flagged by `StateRanking::must_keep`
+    /// than it could keep. These nodes were then handled according to the
+    /// `WidthConflictPolicy` of the DD.
+    pub keep_list_overflow: bool,
+    /// The number of layers which had more nodes flagged by 
+    /// `StateRanking::must_keep` than they could keep (see `WidthConflictPolicy`)
+    pub width_conflicts: usize,
+}
+
+/// Tells whether the cut-set of the last DD that was compiled can still be
+/// iterated, or whether it has been drained already (see 
+/// `DecisionDiagram::drain_cutset`). Each compilation yields a fresh cut-set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CutsetState {
+    /// The cut-set has not been drained since the DD was last compiled
+    #[default]
+    Fresh,
+    /// The cut-set has been drained: it is empty until the DD is compiled again
+    Consumed,
+}
+
+/// The error returned by `DecisionDiagram::try_for_each_cutset_node` when the
+/// cut-set of the DD has been drained already
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CutsetConsumed;
+
+impl fmt::Display for CutsetConsumed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the cut-set has already been drained")
+    }
+}
+impl std::error::Error for CutsetConsumed {}
+
+/// Tells what a DD does when a layer has more nodes flagged by 
+/// `StateRanking::must_keep` than its width lets it keep. Either the exactness
+/// of these nodes is preserved and the width is exceeded, or the width is 
+/// honored and some of these nodes are dropped (restriction) or merged 
+/// (relaxation), which weakens the cut-set.
+///
+/// # Note
+/// The DDs have no memory cap of their own: the width is the only thing which
+/// bounds the size of their layers. With `ExceedWidth`, a layer may hold as
+/// many nodes as were flagged (plus one merged node when it is relaxed); the
+/// peak layer estimate (see `DecisionDiagram::peak_layer_bytes`) accounts for
+/// these extra nodes. Whenever the memory must remain bounded by the width, 
+/// `MergeExact` must be used instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum WidthConflictPolicy {
+    /// All the flagged nodes are kept, and the layer exceeds its width. When
+    /// the layer is relaxed, the other nodes are merged into one single node.
+    #[default]
+    ExceedWidth,
+    /// The width is honored: the layer is trimmed as if no node was flagged
+    MergeExact,
+    /// The compilation fails with `Reason::WidthConflict`
+    Error,
+}
+
+/// Tells whether the node reached by branching from a parent node is exact,
+/// that is, whether its state and its longest path from the root are the ones
+/// of the exact DD. This is the rule which every DD implementation follows to
+/// propagate the exactness of its nodes (and hence to pick its cut-set):
+///
+/// - a node is exact only if it is reached from an exact parent;
+/// - a node which results from a merge (relaxation) is never exact;
+/// - the siblings which were pruned from the layer of the parent (by the 
+///   restriction, the cache, the dominance or the feasibility classes) do not
+///   matter: they cannot be part of a better path than the remaining ones, so
+///   the nodes below them stay exact.
+///
+/// A stricter rule may be installed with an `ExactnessPolicy`.
+pub fn child_exactness(parent_exact: bool, merged: bool, _pruned_siblings: bool) -> bool {
+    parent_exact && !merged
+}
+
+/// The rule a DD uses to tell whether a node is exact (see `child_exactness`).
+/// This only matters to the nodes of the DD: whether the DD as a whole is 
+/// exact and where its last exact layer lies do not depend on it. A stricter
+/// policy yields fewer exact nodes, hence a cut-set which lies higher up in
+/// the DD (and which remains sound).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ExactnessPolicy {
+    /// The rule of `child_exactness`
+    #[default]
+    Standard,
+    /// The nodes lying below a layer from which some nodes were pruned are
+    /// treated as inexact (this is meant for research on cut-set definitions)
+    PrunedLayersInexact,
+}
+impl ExactnessPolicy {
+    /// Tells whether the node reached by branching from a parent node is 
+    /// exact according to this policy
+    pub fn child_exactness(self, parent_exact: bool, merged: bool, pruned_siblings: bool) -> bool {
+        match self {
+            ExactnessPolicy::Standard => 
+                child_exactness(parent_exact, merged, pruned_siblings),
+            ExactnessPolicy::PrunedLayersInexact => 
+                child_exactness(parent_exact, merged, pruned_siblings) && !pruned_siblings,
+        }
+    }
+}
+
 /// This trait describes the operations that can be expected from an abstract
 /// decision diagram regardless of the way it is implemented.
 pub trait DecisionDiagram {
@@ -78,9 +196,21 @@ pub trait DecisionDiagram {
     type State;
 
     /// This method provokes the compilation of the DD based on the given 
-    /// compilation input (compilation type, and root subproblem)
-    fn compile(&mut self, input: &CompilationInput<Self::State>) 
+    /// compilation input (compilation type, width, ...) and rooted in the
+    /// `residual` subproblem whose state space must be explored.
+    ///
+    /// # Note
+    /// The residual subproblem is taken by value so that the DD can move its 
+    /// state and path into the root layer instead of copying them. Callers 
+    /// that need to compile several DDs from the same subproblem get it back
+    /// with `take_residual` rather than cloning it beforehand.
+    fn compile(&mut self, input: &CompilationInput<Self::State>, residual: SubProblem<Self::State>) 
         -> Result<Completion, Reason>;
+    /// Gives back the residual subproblem which the last DD was compiled from
+    /// (see `compile`), or None when it has already been taken. Its path is
+    /// moved out of the DD: hence, the solutions and the cut-set of the DD
+    /// must not be queried anymore until it is compiled again.
+    fn take_residual(&mut self) -> Option<SubProblem<Self::State>>;
     /// Returns true iff the DD which has been compiled is an exact DD.
     fn is_exact(&self) -> bool;
     /// Returns the optimal value of the objective function or None when no 
@@ -92,6 +222,20 @@ pub trait DecisionDiagram {
     /// maximizing the objective value. When no feasible solution exists in the
     /// approximate DD, it returns the value None instead.
     fn best_solution(&self) -> Option<Solution>;
+    /// Iteratively applies the given function `f` to each decision of the
+    /// best solution of this DD (see `best_solution`) and does nothing when 
+    /// there is no such solution. Unlike `best_solution`, implementations may
+    /// walk the DD without allocating a vector which makes this the method of
+    /// choice when the decisions only need to be inspected.
+    ///
+    /// # Iteration order
+    /// The decisions leading to the root of the DD come first, in the order 
+    /// in which they were made. They are followed by the decisions of the DD
+    /// itself, from the deepest one up to the root of the DD. By default, the 
+    /// decisions are those of `best_solution`, in that same order.
+    fn for_each_best_decision(&self, f: &mut dyn FnMut(Decision)) {
+        self.best_solution().into_iter().flatten().for_each(f)
+    }
     /// Returns the value of the objective function for the best exact node in the DD
     /// or None when no feasible solution has been identified (no r-t path) either because
     /// the subproblem at the root of this DD is infeasible or because restriction/relaxation
@@ -101,14 +245,139 @@ pub trait DecisionDiagram {
     /// maximizing the objective value. When no feasible solution exists in the
     /// approximate DD, it returns the value None instead.
     fn best_exact_solution(&self) -> Option<Solution>;
+    /// Returns the state of the terminal node reached by the best solution of
+    /// this DD, or None when the DD has no feasible solution. By default, this
+    /// is None.
+    ///
+    /// # Caveat
+    /// In a relaxed DD, the best terminal node may be a merged node. Its state
+    /// then stands for several states and needs not be reachable by applying
+    /// the decisions of the best solution.
+    fn best_state(&self) -> Option<&Self::State> {
+        None
+    }
+    /// Returns the state of the terminal node reached by the best exact 
+    /// solution of this DD, or None when no such solution exists. By default,
+    /// this is None.
+    fn best_exact_state(&self) -> Option<&Self::State> {
+        None
+    }
+    /// Iteratively applies the given function `func` to the value and the 
+    /// solution of each exact terminal node of the DD (hence, to the best 
+    /// exact solution as well). By default, only the best exact solution is 
+    /// reported.
+    fn for_each_exact_solution(&self, func: &mut dyn FnMut(isize, Solution)) {
+        if let (Some(value), Some(solution)) = (self.best_exact_value(), self.best_exact_solution()) {
+            func(value, solution)
+        }
+    }
+    /// Returns the number of subproblems in the exact cut-set that was 
+    /// computed during DD compilation (that is, the number of times 
+    /// `for_each_cutset_node` or `drain_cutset` would call their function).
+    ///
+    /// # Important:
+    /// This is only meaningful if the DD was compiled in relaxed mode and its
+    /// cut-set has not been drained yet.
+    fn cutset_len(&self) -> usize;
     /// Iteratively applies the given function `func` to each element of the
-    /// exact cut-set that was computed during DD compilation.
+    /// exact cut-set that was computed during DD compilation, leaving the 
+    /// cut-set untouched. 
     ///
     /// # Important:
-    /// This can only be called if the DD was compiled in relaxed mode.
-    /// All implementations of the DecisionDiagram trait are allowed to assume
-    /// this method will be called at most once per relaxed DD compilation.
+    /// This can only be called if the DD was compiled in relaxed mode. Unlike
+    /// `drain_cutset`, it may be called any number of times until the cut-set
+    /// is drained or the DD is compiled again. This is what lets a solver 
+    /// inspect the cut-set before deciding to compile the same subproblem 
+    /// with different parameters. Once the cut-set has been drained, `func` 
+    /// is never called (see `try_for_each_cutset_node` to tell this case
+    /// apart from an empty cut-set).
+    fn for_each_cutset_node<F>(&self, func: F)
+    where
+        F: FnMut(SubProblem<Self::State>);
+    /// Iteratively applies the given function `func` to each element of the
+    /// exact cut-set that was computed during DD compilation, and empties the
+    /// cut-set.
+    ///
+    /// # Important:
+    /// This can only be called if the DD was compiled in relaxed mode. Each
+    /// node of the cut-set is delivered at most once per compilation: once
+    /// the cut-set has been drained, calling this method again is a no-op 
+    /// (`func` is never called) until the DD is compiled again.
     fn drain_cutset<F>(&mut self, func: F)
     where
         F: FnMut(SubProblem<Self::State>);
+    /// Tells whether the cut-set of the last compilation has been drained
+    /// already. By default, this is always `CutsetState::Fresh`.
+    fn cutset_state(&self) -> CutsetState {
+        CutsetState::Fresh
+    }
+    /// Behaves as `for_each_cutset_node` except that it fails when the cut-set
+    /// has been drained already (rather than silently doing nothing).
+    fn try_for_each_cutset_node<F>(&self, func: F) -> Result<(), CutsetConsumed>
+    where
+        F: FnMut(SubProblem<Self::State>)
+    {
+        match self.cutset_state() {
+            CutsetState::Consumed => Err(CutsetConsumed),
+            CutsetState::Fresh    => {
+                self.for_each_cutset_node(func);
+                Ok(())
+            }
+        }
+    }
+    /// Returns an estimate of the memory used by the largest layer that was
+    /// ever developed by this DD (in bytes). By default, this is zero.
+    fn peak_layer_bytes(&self) -> usize {
+        0
+    }
+    /// Returns some information about the last DD that was compiled (see
+    /// `MddStats`). By default, this is empty.
+    fn stats(&self) -> MddStats {
+        MddStats::default()
+    }
+}
+
+#[cfg(test)]
+mod test_exactness {
+    use super::*;
+
+    #[test]
+    fn child_exactness_truth_table() {
+        // parent_exact, merged, pruned_siblings => exact
+        let table = [
+            (false, false, false, false),
+            (false, false, true,  false),
+            (false, true,  false, false),
+            (false, true,  true,  false),
+            (true,  false, false, true ),
+            (true,  false, true,  true ),
+            (true,  true,  false, false),
+            (true,  true,  true,  false),
+        ];
+        for (parent_exact, merged, pruned, exact) in table {
+            assert_eq!(exact, child_exactness(parent_exact, merged, pruned));
+            assert_eq!(exact, ExactnessPolicy::Standard.child_exactness(parent_exact, merged, pruned));
+        }
+    }
+    #[test]
+    fn pruned_layers_inexact_truth_table() {
+        // parent_exact, merged, pruned_siblings => exact
+        let table = [
+            (false, false, false, false),
+            (false, false, true,  false),
+            (false, true,  false, false),
+            (false, true,  true,  false),
+            (true,  false, false, true ),
+            (true,  false, true,  false),
+            (true,  true,  false, false),
+            (true,  true,  true,  false),
+        ];
+        for (parent_exact, merged, pruned, exact) in table {
+            assert_eq!(exact, ExactnessPolicy::PrunedLayersInexact.child_exactness(parent_exact, merged, pruned));
+        }
+    }
+    #[test]
+    fn by_default_the_policy_is_the_standard_one() {
+        assert_eq!(ExactnessPolicy::Standard, ExactnessPolicy::default());
+    }
 }
\ No newline at end of file
@@ -31,6 +31,14 @@ mod fringe;
 mod mdd;
 mod cache;
 mod dominance;
+mod preprocess;
+mod codec;
+mod projection;
+mod export;
+mod reuse;
+mod progress;
+mod transform;
+mod bitset;
 
 pub use dp::*;
 pub use heuristics::*;
@@ -38,4 +46,12 @@ pub use solver::*;
 pub use fringe::*;
 pub use mdd::*;
 pub use cache::*;
-pub use dominance::*;
\ No newline at end of file
+pub use dominance::*;
+pub use preprocess::*;
+pub use codec::*;
+pub use projection::*;
+pub use export::*;
+pub use reuse::*;
+pub use progress::*;
+pub use transform::*;
+pub use bitset::*;
\ No newline at end of file
@@ -0,0 +1,56 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module defines the abstractions which are used to reduce a problem
+//! instance *before* it is handed over to a solver, and to map the solutions
+//! of the reduced problem back onto the original one.
+
+use crate::{Decision, Problem, Solution};
+
+/// A preprocessing step takes an instance of some problem `P` and produces a
+/// (hopefully smaller) equivalent instance along with a `SolutionLifter` which
+/// knows how to translate the solutions of the reduced problem into solutions
+/// of the original problem.
+///
+/// # Note:
+/// A reduction is only safe when the optimal value of the reduced problem is
+/// the same as that of the original problem. Hence, whenever a reduction fixes
+/// the value of some variable, the profit of that forced decision must be
+/// accounted for by the reduced problem (typically in its `initial_value()`).
+pub trait Preprocess<P: Problem> {
+    /// The type of the problem produced by this reduction
+    type Reduced: Problem;
+    /// The type of the object used to map the reduced solutions back
+    type Lifter: SolutionLifter;
+
+    /// Reduces the given problem and returns the reduced instance along with
+    /// the lifter that must be used to map its solutions back.
+    fn preprocess(&self, problem: &P) -> (Self::Reduced, Self::Lifter);
+}
+
+/// A solution lifter maps the decisions made on the variables of a reduced
+/// problem back onto the variables of the original problem. The lifted
+/// solution is expected to be complete: it must comprise one decision for
+/// each variable of the original problem (including the ones that were fixed
+/// by the preprocessing).
+pub trait SolutionLifter {
+    /// Translates a solution of the reduced problem into a solution of the
+    /// original problem.
+    fn lift(&self, reduced: &[Decision]) -> Solution;
+}
@@ -0,0 +1,59 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module defines the `Progress` trait which lets a solver report how its
+//! search is going to some listener (e.g. a logger or a CSV writer), in a 
+//! structured way.
+
+/// An event of the progress of a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProgressEvent {
+    /// A subproblem has been explored
+    NodeExplored {
+        /// The number of subproblems explored so far
+        explored: usize,
+        /// The best known lower bound
+        lb: isize,
+        /// The best known upper bound
+        ub: isize,
+        /// The number of subproblems which remain in the fringe
+        fringe_len: usize,
+    },
+    /// A new best solution has been found
+    NewIncumbent {
+        /// The value of that solution
+        value: isize,
+    },
+    /// The search is over (be it complete or not)
+    Finished {
+        /// The value of the best solution (None when none was found)
+        value: Option<isize>,
+        /// The number of subproblems which were explored
+        explored: usize,
+    },
+}
+
+/// A progress listener gets told about the progress of the search carried out
+/// by a solver (see `with_progress`), in the order where the events occur.
+pub trait Progress {
+    /// Records the given event
+    fn report(&mut self, event: &ProgressEvent);
+}
@@ -0,0 +1,78 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module defines the `Projection` abstraction which lets the relaxed
+//! DDs be compiled in a smaller state space than the exact and restricted ones.
+
+use std::cmp::Ordering;
+
+use crate::{Decision, DecisionCallback, Variable};
+
+/// A projection maps the (possibly large) states of a problem onto smaller
+/// *abstract* states which drop the components that are not needed to derive
+/// a relaxation. Along with the mapping itself, a projection provides the DP
+/// model and the relaxation of the problem in the abstract state space.
+///
+/// When a relaxed DD is compiled with a projection (see `ProjectedMdd`), its
+/// layers remain concrete for as long as they are exact. The last exact layer
+/// is then projected and the rest of the DD is compiled in the abstract space.
+/// This way, the nodes of the cut-set (the last exact layer) are still concrete
+/// states from which the search can be resumed.
+///
+/// # Important
+/// The projection must preserve the bounds: the value of the longest path from
+/// `project(s)` in the abstract model may never be lower than that of the
+/// longest path from `s` in the concrete model. Otherwise, the relaxed DD
+/// might prune the optimal solution.
+pub trait Projection {
+    /// The state of the concrete problem
+    type State;
+    /// The state of the abstract model
+    type Abstract;
+
+    /// Maps a concrete state onto its abstract counterpart
+    fn project(&self, state: &Self::State) -> Self::Abstract;
+    /// Merges several abstract states into one abstract state which stands
+    /// for all of them (this is the $\oplus$ operator in the abstract space)
+    fn merge(&self, states: &[Self::Abstract]) -> Self::Abstract;
+    /// Adjusts the cost of an arc which is redirected from `dest` to the merged
+    /// state `merged` (this is the $\Gamma$ operator in the abstract space).
+    /// By default, the cost is left untouched.
+    fn relax(&self, _source: &Self::Abstract, _dest: &Self::Abstract, _merged: &Self::Abstract, _decision: Decision, cost: isize) -> isize {
+        cost
+    }
+    /// Returns the variable to branch on at the given depth of the abstract
+    /// model (see `Problem::next_variable`)
+    fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::Abstract>) -> Option<Variable>;
+    /// Calls `f` for each value in the domain of `var` in the abstract state
+    /// `state` (see `Problem::for_each_in_domain`)
+    fn for_each_in_domain(&self, var: Variable, state: &Self::Abstract, f: &mut dyn DecisionCallback);
+    /// The transition function of the abstract model
+    fn transition_abstract(&self, state: &Self::Abstract, decision: Decision) -> Self::Abstract;
+    /// The transition cost function of the abstract model
+    fn transition_cost_abstract(&self, source: &Self::Abstract, dest: &Self::Abstract, decision: Decision) -> isize;
+    /// Compares two abstract states. Greater means more likely to be kept
+    /// intact when a layer of the DD must be merged (see `StateRanking`)
+    fn compare(&self, a: &Self::Abstract, b: &Self::Abstract) -> Ordering;
+    /// Returns a rough upper bound on the value of the longest path from the
+    /// given abstract state (see `Relaxation::fast_upper_bound`)
+    fn fast_upper_bound(&self, _state: &Self::Abstract) -> isize {
+        isize::MAX
+    }
+}
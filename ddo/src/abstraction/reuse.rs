@@ -0,0 +1,59 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module defines the `BoundTransfer` abstraction which lets the bounds
+//! derived while solving one instance of a model be reused when solving other
+//! instances of that same model (see `SharedBounds`).
+
+/// A bound transfer tells how an upper bound derived for some state of one 
+/// instance translates into an upper bound on the same state of an other 
+/// instance of the model. It is meant for batches of instances which only
+/// differ by a few coefficients: the instance being solved is identified by 
+/// its `fingerprint` and the states are identified by a `state_key` which 
+/// does not depend on the instance.
+///
+/// # Important
+/// The transfer must preserve the bounds: whenever `bound` is an upper bound
+/// on the longest path from `state` in the instance `from`, the outcome of 
+/// `transfer` must be an upper bound on the longest path from `state` in
+/// this instance. Otherwise, the solver might prune the optimal solution.
+pub trait BoundTransfer {
+    /// The state of the problem
+    type State;
+
+    /// Returns the fingerprint of this instance: a hash of whatever sets it
+    /// apart from the other instances of the model
+    fn fingerprint(&self) -> u64;
+    /// Returns a key identifying `state` regardless of the instance. Two 
+    /// states having the same key must be the same state for the transfer.
+    fn state_key(&self, state: &Self::State) -> u64;
+    /// Returns the delta bound of `state` in this instance. It is stored along
+    /// with the bounds recorded for this instance and handed back to `transfer`
+    /// when these are carried to another instance, so that the correction can
+    /// be computed without knowing the source instance. By default, this is 0.
+    fn delta(&self, _state: &Self::State) -> isize {
+        0
+    }
+    /// Corrects `bound`, an upper bound on the longest path from `state` in
+    /// the instance whose fingerprint is `from` (and whose delta bound was 
+    /// `delta`), into an upper bound valid in this instance. It returns None 
+    /// when the bound cannot be transferred.
+    fn transfer(&self, state: &Self::State, from: u64, bound: isize, delta: isize) -> Option<isize>;
+}
@@ -17,9 +17,12 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-//! This module defines the `Solver` trait.
+//! This module defines the `Solver` trait, along with the `IncumbentListener`
+//! trait which lets the solvers report their new best solutions.
 
-use crate::{Decision, Completion};
+use std::time::Duration;
+
+use crate::{Decision, Completion, ObjectiveSense, Statistics};
 
 /// A decision is nothing but a sequence of decision covering all problem
 /// variables.
@@ -54,6 +57,17 @@ pub trait Solver {
     ///   cutoff occurred.
     ///
     fn maximize(&mut self) -> Completion;
+    /// This method searches for the solution which minimizes the objective of
+    /// a problem which is modeled as a minimization. The problem and its 
+    /// relaxation must have been wrapped in a `Minimize` when creating the 
+    /// solver: the solver then maximizes the opposite of the objective, and 
+    /// the `best_value` of the returned completion is converted back into 
+    /// the original sense. The other values reported by the solver (e.g. 
+    /// `best_lower_bound`) remain in the sense of the solver; they are 
+    /// converted with `ObjectiveSense::Minimize`.
+    fn minimize(&mut self) -> Completion {
+        self.maximize().in_sense(ObjectiveSense::Minimize)
+    }
     /// This method returns the value of the objective function for the best
     /// solution that has been found. It returns `None` when no solution exists
     /// to the problem.
@@ -76,6 +90,43 @@ pub trait Solver {
     /// Sets a primal (best known value and solution) of the problem.
     fn set_primal(&mut self, value: isize, solution: Solution);
 
+    /// Tells the solver that a solution worth `bound` is known, although that
+    /// solution is not given. The search then only looks for solutions which 
+    /// are strictly better than `bound`, and it prunes the nodes which cannot
+    /// beat it from the very first compilations. Any incumbent which is not 
+    /// better than `bound` is forgotten.
+    ///
+    /// # Note
+    /// When the search completes without beating the bound, the completion
+    /// is exact but its `best_value` is None: this proves that no solution
+    /// is better than `bound`. Use `set_primal` (or the `set_initial_solution`
+    /// method of the solvers) when the solution itself should be returned.
+    fn set_primal_bound(&mut self, bound: isize);
+
+    /// Forgets everything about the previous resolutions so that the solver
+    /// object can be reused to solve the problem once again: the fringe, the
+    /// cache, the dominance checker, the incumbent solution, the bounds and
+    /// all the statistics are cleared. The configuration of the solver (the
+    /// heuristics and the `with_xxx` options) is kept untouched.
+    ///
+    /// # Note
+    /// When the problem is mutated in between two resolutions (for instance, 
+    /// to solve a different instance), `reset` must be called *after* that
+    /// change since the solver sizes its data structures after the number of
+    /// variables of the problem.
+    fn reset(&mut self);
+
+    /// Limits the number of nodes that may be explored by the solver (see 
+    /// `explored`), or lifts that limit when `budget` is None. Once the budget
+    /// is exhausted, the search stops with the best incumbent found so far and
+    /// the completion tells so with `stopped_by == Some(Reason::NodeLimit)`.
+    fn set_node_budget(&mut self, budget: Option<usize>);
+
+    /// Limits the wall-clock time `maximize` may run, or lifts that limit 
+    /// when `limit` is None. Once the time is up, the search stops and the
+    /// completion tells so with `stopped_by == Some(Reason::Timeout)`.
+    fn set_time_limit(&mut self, limit: Option<Duration>);
+
     /// Computes the optimality gap
     fn gap(&self) -> f32 {
         let ub = self.best_upper_bound();
@@ -94,4 +145,38 @@ pub trait Solver {
 
     /// Returns the number of nodes that have been explored during the search.
     fn explored(&self) -> usize;
+
+    /// Returns the statistics of the search (see `Statistics`), or None when
+    /// the solver does not collect them
+    fn statistics(&self) -> Option<Statistics> {
+        None
+    }
+}
+
+/// An incumbent listener gets told about each new best solution found by a 
+/// solver while it is searching (see `with_incumbent_listener`). This lets 
+/// one stream the solutions of a long run to disk, or plot the evolution of 
+/// the incumbent without polling the solver.
+///
+/// Any closure `FnMut(isize, &[Decision])` is an incumbent listener.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// let mut values = vec![];
+/// let mut listener = |value: isize, _: &[Decision]| values.push(value);
+/// listener.on_new_best(42, &[]);
+/// assert_eq!(vec![42], values);
+/// ```
+pub trait IncumbentListener {
+    /// This method is called every time the value of the best solution 
+    /// improves. The `solution` comprises the decisions of all the variables,
+    /// sorted by variable (the same as `Solver::best_solution` would return 
+    /// once the search is over).
+    fn on_new_best(&mut self, value: isize, solution: &[Decision]);
+}
+impl<F: FnMut(isize, &[Decision])> IncumbentListener for F {
+    fn on_new_best(&mut self, value: isize, solution: &[Decision]) {
+        self(value, solution)
+    }
 }
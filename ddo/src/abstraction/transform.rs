@@ -0,0 +1,50 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module defines the `CostTransform` abstraction: an exact change of
+//! the scale of the costs on which the DDs are compiled (see `Transformed`).
+
+/// A transformation of the costs of a problem. The DDs are compiled on the
+/// transformed costs (`forward`) while the values and bounds are reported on
+/// the original scale (`backward`). The classic use case is lexicographic 
+/// optimization: the primary costs are multiplied by a factor which is large
+/// enough for a secondary objective to fit in the low-order digits.
+///
+/// # Exactness contract
+/// The transformation must be exact, this is why it only deals in integers:
+/// * `forward` must be additive (`forward(a + b) == forward(a) + forward(b)`)
+///   and strictly increasing, so that the best path on the transformed costs
+///   is a best path on the original ones;
+/// * `backward` must invert `forward` while ignoring the low-order digits:
+///   `backward(forward(v) + r) == v` for all `0 <= r < forward(1)`.
+pub trait CostTransform {
+    /// Maps a cost of the original problem onto the transformed scale
+    fn forward(&self, cost: isize) -> isize;
+    /// Maps a value of the transformed scale back onto the original one
+    fn backward(&self, value: isize) -> isize;
+    /// Maps a bound of the transformed scale back onto the original one. The
+    /// infinite bounds (isize::MIN and isize::MAX) are left untouched.
+    fn backward_bound(&self, bound: isize) -> isize {
+        match bound {
+            isize::MIN | isize::MAX => bound,
+            bound => self.backward(bound),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module tells how the library was compiled. Because the behavior of
+//! the solvers depends on the optional features which were enabled (and on the
+//! width of the objective values), the logs of an experiment are only really
+//! interpretable when they mention the build which produced them.
+
+use std::fmt;
+
+/// The optional features of the library, each with a flag telling whether it
+/// was enabled at compile time. Any new optional subsystem must be listed here
+/// (this is checked against the manifest by the tests of this module).
+const FEATURES: &[(&str, bool)] = &[
+    ("checkpoint",     cfg!(feature = "checkpoint")),
+    ("config-file",    cfg!(feature = "config-file")),
+    ("ooc",            cfg!(feature = "ooc")),
+    ("progress-check", cfg!(feature = "progress-check")),
+    ("ranking-check",  cfg!(feature = "ranking-check")),
+    ("rss",            cfg!(feature = "rss")),
+    ("serde",          cfg!(feature = "serde")),
+];
+
+/// A summary of the way the library was built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// The version of the ddo crate
+    pub version: &'static str,
+    /// The name of the optional features that were enabled (sorted by name)
+    pub features: Vec<&'static str>,
+    /// The number of bits used to represent the objective values
+    pub value_bits: u32,
+    /// The name of the hash function used by the caches and the layers
+    pub hasher: &'static str,
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let features = if self.features.is_empty() { 
+            "none".to_string() 
+        } else { 
+            self.features.join(", ") 
+        };
+        write!(f, "ddo {} (features: {}; values: {} bits; hasher: {})", 
+            self.version, features, self.value_bits, self.hasher)
+    }
+}
+
+/// Returns a summary of the way the library was built.
+///
+/// # Example
+/// ```
+/// let info = ddo::build_info();
+/// assert_eq!(env!("CARGO_PKG_VERSION"), info.version);
+/// assert_eq!(isize::BITS, info.value_bits);
+/// ```
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        features: FEATURES.iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| *name)
+            .collect(),
+        value_bits: isize::BITS,
+        hasher: "fxhash",
+    }
+}
+
+// ############################################################################
+// #### TESTS #################################################################
+// ############################################################################
+
+#[cfg(test)]
+mod test_build_info {
+    use crate::*;
+    use super::FEATURES;
+
+    /// Returns the names of the features declared in the manifest, along with
+    /// the optional dependencies which cargo turns into implicit features.
+    fn declared_features() -> Vec<String> {
+        let manifest = include_str!("../Cargo.toml");
+        let mut names = vec![];
+        let mut section = "";
+        for line in manifest.lines().map(str::trim) {
+            if line.starts_with('[') {
+                section = line;
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let name = line.split('=').next().unwrap().trim();
+            match section {
+                "[features]" => names.push(name.to_string()),
+                "[dependencies]" if line.contains("optional = true") => names.push(name.to_string()),
+                _ => {}
+            }
+        }
+        names
+    }
+
+    #[test]
+    fn every_feature_of_the_manifest_is_reported() {
+        for name in declared_features() {
+            // optional dependencies that only exist to support a feature
+            if name == "toml" || name == "bincode" {
+                continue;
+            }
+            assert!(FEATURES.iter().any(|(n, _)| *n == name), "{name} is not reported");
+        }
+    }
+    #[test]
+    fn the_report_matches_the_enabled_features() {
+        let info = build_info();
+        assert_eq!(info.features.contains(&"checkpoint"),     cfg!(feature = "checkpoint"));
+        assert_eq!(info.features.contains(&"config-file"),    cfg!(feature = "config-file"));
+        assert_eq!(info.features.contains(&"ooc"),            cfg!(feature = "ooc"));
+        assert_eq!(info.features.contains(&"progress-check"), cfg!(feature = "progress-check"));
+        assert_eq!(info.features.contains(&"ranking-check"),  cfg!(feature = "ranking-check"));
+        assert_eq!(info.features.contains(&"rss"),            cfg!(feature = "rss"));
+        assert_eq!(info.features.contains(&"serde"),          cfg!(feature = "serde"));
+    }
+    #[test]
+    fn features_are_sorted_by_name() {
+        let info = build_info();
+        let mut sorted = info.features.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, info.features);
+    }
+    #[test]
+    #[cfg(not(any(feature = "checkpoint", feature = "config-file", feature = "ooc", feature = "progress-check", feature = "ranking-check", feature = "rss", feature = "serde")))]
+    fn the_default_build_enables_no_optional_feature() {
+        let info = build_info();
+        assert!(info.features.is_empty());
+        assert_eq!(format!("ddo {} (features: none; values: {} bits; hasher: fxhash)", 
+            env!("CARGO_PKG_VERSION"), isize::BITS), info.to_string());
+    }
+    #[test]
+    fn the_value_width_is_that_of_isize() {
+        assert_eq!(isize::BITS, build_info().value_bits);
+    }
+}
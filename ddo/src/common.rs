@@ -21,7 +21,11 @@
 //! the code of our library (both at the abstraction and implementation levels).
 //! These are also the types your client library is likely to work with.
 
-use std::sync::Arc;
+use std::{fmt, sync::Arc, time::Duration};
+
+use fxhash::FxHashSet;
+
+use crate::{CostTransform, DecisionCodec, CompilationType};
 
 // ----------------------------------------------------------------------------
 // --- VARIABLE ---------------------------------------------------------------
@@ -30,6 +34,7 @@ use std::sync::Arc;
 /// In this case, each variable is assumed to be identified with an integer
 /// ranging from 0 until `problem.nb_vars()`
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Variable(pub usize);
 impl Variable {
     #[inline]
@@ -54,12 +59,84 @@ impl Variable {
 /// This denotes a decision that was made during the search. It affects a given
 /// `value` to the specified `variable`. Any given `Decision` should be
 /// understood as ```[[ variable = value ]]````
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Decision {
     pub variable : Variable,
     pub value    : isize
 }
 
+/// This is the typed counterpart of a `Decision`: it affects a value of some
+/// arbitrary type `V` to the given variable. Typed decisions are converted
+/// from and to plain decisions by means of a `DecisionCodec<V>`.
+///
+/// # Examples:
+/// ```
+/// # use ddo::*;
+/// let typed = TypedDecision{ variable: Variable(2), value: 7_usize };
+/// let plain = typed.encode(&IndexCodec);
+/// assert_eq!(Decision{ variable: Variable(2), value: 7 }, plain);
+/// assert_eq!(Some(typed), TypedDecision::decode(plain, &IndexCodec));
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TypedDecision<V> {
+    pub variable : Variable,
+    pub value    : V
+}
+impl <V> TypedDecision<V> {
+    /// Encodes this typed decision into a plain decision
+    pub fn encode(&self, codec: &impl DecisionCodec<V>) -> Decision {
+        Decision { variable: self.variable, value: codec.encode(&self.value) }
+    }
+    /// Decodes the given plain decision. This returns None when the value of
+    /// the decision cannot be decoded by the codec
+    pub fn decode(decision: Decision, codec: &impl DecisionCodec<V>) -> Option<Self> {
+        codec.decode(decision.value)
+            .map(|value| TypedDecision { variable: decision.variable, value })
+    }
+}
+
+// ----------------------------------------------------------------------------
+// --- VALUE MASK -------------------------------------------------------------
+// ----------------------------------------------------------------------------
+/// A value mask tells which values must be skipped when branching on some
+/// variable for all the nodes of a layer (see `Problem::filter_layer`). The 
+/// default mask allows all values.
+///
+/// # Examples:
+/// ```
+/// # use ddo::ValueMask;
+/// let mut mask = ValueMask::all();
+/// assert!(mask.allows(1));
+/// mask.skip(1);
+/// assert!(!mask.allows(1));
+/// assert!(mask.allows(0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValueMask {
+    /// The values which must be skipped
+    skipped: FxHashSet<isize>,
+}
+impl ValueMask {
+    /// Returns the mask which allows all values
+    pub fn all() -> Self {
+        Self::default()
+    }
+    /// Skips the given value
+    pub fn skip(&mut self, value: isize) {
+        self.skipped.insert(value);
+    }
+    /// Returns true iff the given value is not skipped
+    #[inline]
+    pub fn allows(&self, value: isize) -> bool {
+        self.skipped.is_empty() || !self.skipped.contains(&value)
+    }
+    /// Returns true iff this mask allows all values
+    pub fn allows_all(&self) -> bool {
+        self.skipped.is_empty()
+    }
+}
+
 
 // ----------------------------------------------------------------------------
 // --- SUBPROBLEM -------------------------------------------------------------
@@ -71,7 +148,8 @@ pub struct Decision {
 /// Sub-problems are automatically instantiated from nodes in the exact cut-sets 
 /// of relaxed decision diagrams. If you are only discovering the API, rest 
 /// assured.. you don't need to implement any subproblem yourself.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(not(test), derive(Clone))]
 pub struct SubProblem<T> {
     /// The root state of this sub problem
     pub state: Arc<T>,
@@ -84,6 +162,31 @@ pub struct SubProblem<T> {
     pub ub: isize,
     /// The depth of the subproblem with respect to the root problem
     pub depth: usize,
+    /// The number of generations of descendants of this subproblem which 
+    /// still inherit a priority bonus (see `BonusFirst`). Zero when this 
+    /// subproblem carries no bonus at all.
+    pub priority_bonus: u8,
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Counts the subproblems cloned by the current thread, so that the tests
+    /// can check which nodes get copied by the solvers
+    pub(crate) static SUBPROBLEM_CLONES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+#[cfg(test)]
+impl<T: Clone> Clone for SubProblem<T> {
+    fn clone(&self) -> Self {
+        SUBPROBLEM_CLONES.with(|clones| clones.set(clones.get() + 1));
+        SubProblem {
+            state: self.state.clone(),
+            value: self.value,
+            path: self.path.clone(),
+            ub: self.ub,
+            depth: self.depth,
+            priority_bonus: self.priority_bonus,
+        }
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -103,21 +206,313 @@ pub struct Threshold {
 // ----------------------------------------------------------------------------
 // --- Results ----------------------------------------------------------------
 // ----------------------------------------------------------------------------
+/// The sense of the objective of a problem. The solvers and DDs always 
+/// maximize: a minimization problem is solved by maximizing the opposite of
+/// its objective (see `Minimize`). The objective sense then converts the 
+/// values and bounds computed by the solver back into the sense of the user.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ObjectiveSense {
+    /// The objective is to be maximized
+    #[default]
+    Maximize,
+    /// The objective is to be minimized
+    Minimize,
+}
+impl ObjectiveSense {
+    /// Converts a value between the sense of the user and the one of the 
+    /// solver (this conversion is its own inverse). The infinite values are
+    /// swapped: isize::MIN stands for -inf and isize::MAX for +inf.
+    pub fn value(self, value: isize) -> isize {
+        match (self, value) {
+            (ObjectiveSense::Maximize, v) => v,
+            (ObjectiveSense::Minimize, isize::MIN) => isize::MAX,
+            (ObjectiveSense::Minimize, isize::MAX) => isize::MIN,
+            (ObjectiveSense::Minimize, v) => -v,
+        }
+    }
+    /// Converts the lower and upper bounds computed by a solver into the 
+    /// lower and upper bounds on the objective of the user. When minimizing, 
+    /// the upper bound of the solver is the opposite of the lower bound of the
+    /// user (and vice versa).
+    pub fn bounds(self, best_lb: isize, best_ub: isize) -> (isize, isize) {
+        match self {
+            ObjectiveSense::Maximize => (best_lb, best_ub),
+            ObjectiveSense::Minimize => (self.value(best_ub), self.value(best_lb)),
+        }
+    }
+}
+
 /// A reason explaining why the mdd stopped developing
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Reason {
     /// It stopped because the configured cutoff criterion was met
-    CutoffOccurred
+    CutoffOccurred,
+    /// It stopped because the time limit of the solver was exhausted (see
+    /// `set_time_limit`)
+    Timeout,
+    /// It stopped because the solver explored as many nodes as its budget
+    /// allowed (see `set_node_budget`)
+    NodeLimit,
+    /// It stopped because the problem asked to branch on `variable` while
+    /// the diagram already had one layer per variable of the problem. This
+    /// means that `next_variable` never returns `None` and that the 
+    /// compilation would otherwise loop forever.
+    LayerLimitExceeded {
+        /// The variable that was returned by `next_variable`
+        variable: Variable,
+        /// The depth of the layer that was about to be expanded
+        depth: usize,
+    },
+    /// It stopped because the progress check (`progress-check` feature) 
+    /// found that the set of free variables did not strictly decrease: 
+    /// either `variable` had already been assigned on the path leading to 
+    /// the current layer, or the layer limit was exceeded.
+    NoProgress {
+        /// The offending variable
+        variable: Variable,
+        /// The depth of the layer that was about to be expanded
+        depth: usize,
+        /// The dump of one state of the offending layer, when the problem
+        /// provides one (see `Problem::dump_state`)
+        state: Option<String>,
+    },
+    /// It stopped because the relative gap between the best bounds dropped
+    /// below the gap tolerance of the solver (see `with_gap_tolerance`)
+    GapReached,
+    /// It stopped because it found a solution reaching the target of the
+    /// solver (see `with_target`)
+    TargetReached,
+    /// It stopped because it proved that no solution reaches the target of
+    /// the solver (see `with_target`)
+    TargetUnreachable,
+    /// It stopped because it was asked to through its abort handle (see 
+    /// `abort_handle`)
+    Aborted,
+    /// It stopped because a layer had more nodes flagged by 
+    /// `StateRanking::must_keep` than it could keep, and the DD was told to
+    /// fail in that case (see `WidthConflictPolicy::Error`).
+    WidthConflict {
+        /// The depth of the offending layer
+        depth: usize,
+        /// The number of nodes of the layer which were flagged
+        flagged: usize,
+        /// The number of nodes which could be kept in the layer
+        slots: usize,
+    },
+}
+impl Reason {
+    /// Returns a short and stable name of this reason (e.g. to be written in
+    /// a report, see `RunReport`). Unlike `Display`, it leaves out the details
+    /// of the reason.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Reason::CutoffOccurred              => "cutoff",
+            Reason::Timeout                     => "timeout",
+            Reason::NodeLimit                   => "node_limit",
+            Reason::LayerLimitExceeded { .. }   => "layer_limit",
+            Reason::NoProgress { .. }           => "no_progress",
+            Reason::GapReached                  => "gap_reached",
+            Reason::TargetReached               => "target_reached",
+            Reason::TargetUnreachable           => "target_unreachable",
+            Reason::Aborted                     => "aborted",
+            Reason::WidthConflict { .. }        => "width_conflict",
+        }
+    }
+}
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::CutoffOccurred => write!(f, "cutoff"),
+            Reason::Timeout        => write!(f, "time limit"),
+            Reason::NodeLimit      => write!(f, "node budget"),
+            Reason::GapReached     => write!(f, "gap tolerance"),
+            Reason::TargetReached  => write!(f, "target reached"),
+            Reason::TargetUnreachable => write!(f, "target unreachable"),
+            Reason::Aborted        => write!(f, "abort handle"),
+            Reason::LayerLimitExceeded { variable, depth } => 
+                write!(f, "layer limit exceeded (variable {} at depth {depth})", variable.id()),
+            Reason::NoProgress { variable, depth, .. } => 
+                write!(f, "no progress (variable {} at depth {depth})", variable.id()),
+            Reason::WidthConflict { depth, flagged, slots } => 
+                write!(f, "width conflict ({flagged} nodes must be kept in {slots} slots at depth {depth})"),
+        }
+    }
+}
+
+/// The circumstances in which a search ended: why it ended, and how far it 
+/// had gone by then
+#[derive(Debug, Clone, PartialEq)]
+pub struct Termination {
+    /// The reason why the search was stopped before it could be carried out
+    /// to its end (None when it was)
+    pub reason: Option<Reason>,
+    /// The time spent in `maximize`
+    pub elapsed: Duration,
+    /// The number of subproblems that were explored
+    pub explored: usize,
+    /// The relative gap between the best bounds when the search ended
+    pub gap: f64,
+    /// The number of open nodes which the fringe dropped for lack of room 
+    /// (see `BoundedFringe`)
+    pub shed: usize,
+}
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.reason {
+            None         => write!(f, "completed")?,
+            Some(reason) => write!(f, "stopped by the {reason}")?,
+        }
+        write!(f, " after {} nodes and {:.3} seconds (gap {:.4})", 
+            self.explored, self.elapsed.as_secs_f64(), self.gap)?;
+        if self.shed > 0 {
+            write!(f, ", {} open nodes were shed", self.shed)?;
+        }
+        Ok(())
+    }
+}
+
+/// The statistics of the search which tell how the solver spent its effort.
+/// They help comparing heuristics quantitatively. Like the number of explored
+/// nodes, they add up over the calls to `maximize` until the solver is reset.
+///
+/// # Note
+/// Only the compilations of the nodes popped from the fringe are accounted 
+/// for (the ones of the presolve and of the dual refreshes are not). With the
+/// parallel solver, the compilation times are summed over all the threads; 
+/// hence they may exceed the wall time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Statistics {
+    /// The number of nodes which were explored
+    pub explored: usize,
+    /// The largest number of nodes which were simultaneously in the fringe
+    pub peak_fringe: usize,
+    /// The number of restricted DDs which were compiled
+    pub restricted: usize,
+    /// The number of relaxed DDs which were compiled
+    pub relaxed: usize,
+    /// The time spent compiling restricted DDs
+    pub restricted_time: Duration,
+    /// The time spent compiling relaxed DDs
+    pub relaxed_time: Duration,
+    /// The wall time spent in `maximize` (during the last call)
+    pub elapsed: Duration,
+    /// The number of nodes found in the cut-sets of the relaxed DDs
+    pub cutset_nodes: usize,
+    /// The number of nodes which were discarded because their upper bound
+    /// could not improve on the best solution: when they were popped, when 
+    /// they were found in a cut-set, or when a better solution was found 
+    /// while they were in the fringe
+    pub pruned_by_bound: usize,
+}
+impl Statistics {
+    /// Returns the total time spent compiling DDs
+    pub fn compilation_time(&self) -> Duration {
+        self.restricted_time + self.relaxed_time
+    }
+    /// Records a compilation of the given type which took `elapsed`
+    pub(crate) fn record_compilation(&mut self, comp_type: CompilationType, elapsed: Duration) {
+        match comp_type {
+            CompilationType::Restricted => {
+                self.restricted += 1;
+                self.restricted_time += elapsed;
+            },
+            CompilationType::Relaxed => {
+                self.relaxed += 1;
+                self.relaxed_time += elapsed;
+            },
+            _ => {}
+        }
+    }
+    /// Adds the counters of the `other` statistics (those of a worker thread)
+    /// to these ones. The explored nodes, the peak of the fringe and the 
+    /// elapsed time are left untouched.
+    pub(crate) fn merge(&mut self, other: &Statistics) {
+        self.restricted      += other.restricted;
+        self.relaxed         += other.relaxed;
+        self.restricted_time += other.restricted_time;
+        self.relaxed_time    += other.relaxed_time;
+        self.cutset_nodes    += other.cutset_nodes;
+        self.pruned_by_bound += other.pruned_by_bound;
+    }
+}
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} nodes explored in {:.3}s, peak fringe {}, {} restricted ({:.3}s) and {} relaxed ({:.3}s) DDs, {} cut-set nodes, {} pruned by bound",
+            self.explored, self.elapsed.as_secs_f64(), self.peak_fringe,
+            self.restricted, self.restricted_time.as_secs_f64(),
+            self.relaxed, self.relaxed_time.as_secs_f64(),
+            self.cutset_nodes, self.pruned_by_bound)
+    }
 }
 
 /// The outcome of an mdd development
+///
+/// # Note
+/// This structure is non exhaustive so that new fields can be added without
+/// breaking your code. Outside of this crate, it must be created with `new`.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct Completion {
     /// is the given solution exact (proved optimal for the given [sub-]problem)?
     /// or is it an approximation ?
     pub is_exact: bool,
     /// if present the value of the best solution derived from this mdd
     pub best_value: Option<isize>,
+    /// if present, the search was carried out to its end while pruning the
+    /// subproblems that could not beat the best solution by more than this
+    /// slack. The best value then lies within that slack of the optimum.
+    pub guaranteed_within: Option<isize>,
+    /// if present, the search was stopped for this reason before it could be
+    /// carried out to its end
+    pub stopped_by: Option<Reason>,
+    /// if present, the search was stopped because the relative gap between
+    /// the best bounds dropped below the gap tolerance; this is that gap
+    pub achieved_gap: Option<f64>,
+    /// if present, the circumstances in which the search ended. This is 
+    /// only set by the solvers (not by the compilation of a single DD).
+    pub termination: Option<Termination>,
+}
+impl Completion {
+    /// Converts the best value of this completion into the given objective 
+    /// sense (see `ObjectiveSense::value`)
+    pub fn in_sense(mut self, sense: ObjectiveSense) -> Self {
+        self.best_value = self.best_value.map(|value| sense.value(value));
+        self
+    }
+    /// Converts the best value of this completion back onto the original 
+    /// scale of a problem whose costs were transformed (see `Transformed`)
+    pub fn in_original_scale(mut self, transform: &dyn CostTransform) -> Self {
+        self.best_value = self.best_value.map(|value| transform.backward(value));
+        self
+    }
+    /// Creates the outcome of a search (or of a compilation) which did not
+    /// prune anything beyond what is needed to prove optimality
+    pub fn new(is_exact: bool, best_value: Option<isize>) -> Self {
+        Self { is_exact, best_value, guaranteed_within: None, stopped_by: None, achieved_gap: None, termination: None }
+    }
+    /// Tells that the best value lies within the given `slack` of the optimum
+    pub fn with_guarantee(mut self, slack: isize) -> Self {
+        self.guaranteed_within = Some(slack);
+        self
+    }
+    /// Tells that the search was stopped for the given `reason`
+    pub fn with_stop_reason(mut self, reason: Reason) -> Self {
+        self.stopped_by = Some(reason);
+        self
+    }
+    /// Tells that the search was stopped once the relative gap between the
+    /// best bounds had dropped to `gap`, below the gap tolerance
+    pub fn with_achieved_gap(mut self, gap: f64) -> Self {
+        self.stopped_by = Some(Reason::GapReached);
+        self.achieved_gap = Some(gap);
+        self
+    }
+    /// Tells in which circumstances the search ended
+    pub fn with_termination(mut self, termination: Termination) -> Self {
+        self.termination = Some(termination);
+        self
+    }
 }
 
 
@@ -136,4 +531,35 @@ mod test_var {
         assert_eq!(2, Variable(2).id());
         assert_eq!(3, Variable(3).id());
     }
+}
+#[cfg(test)]
+mod test_objective_sense {
+    use crate::{Completion, ObjectiveSense};
+
+    #[test]
+    fn maximizing_leaves_the_values_untouched() {
+        assert_eq!(42, ObjectiveSense::Maximize.value(42));
+        assert_eq!(isize::MIN, ObjectiveSense::Maximize.value(isize::MIN));
+        assert_eq!((3, 7), ObjectiveSense::Maximize.bounds(3, 7));
+    }
+    #[test]
+    fn minimizing_negates_the_values_and_swaps_the_infinities() {
+        assert_eq!(-42, ObjectiveSense::Minimize.value(42));
+        assert_eq!(42, ObjectiveSense::Minimize.value(-42));
+        assert_eq!(isize::MAX, ObjectiveSense::Minimize.value(isize::MIN));
+        assert_eq!(isize::MIN, ObjectiveSense::Minimize.value(isize::MAX));
+    }
+    #[test]
+    fn minimizing_swaps_the_bounds() {
+        assert_eq!((-7, -3), ObjectiveSense::Minimize.bounds(3, 7));
+        assert_eq!((isize::MIN, isize::MAX), ObjectiveSense::Minimize.bounds(isize::MIN, isize::MAX));
+    }
+    #[test]
+    fn the_best_value_of_a_completion_is_converted() {
+        let completion = Completion::new(true, Some(12)).in_sense(ObjectiveSense::Minimize);
+        assert!(completion.is_exact);
+        assert_eq!(Some(-12), completion.best_value);
+        let completion = Completion::new(true, None).in_sense(ObjectiveSense::Minimize);
+        assert_eq!(None, completion.best_value);
+    }
 }
\ No newline at end of file
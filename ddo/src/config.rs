@@ -0,0 +1,640 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module (only available with the `config-file` feature) lets you keep
+//! the configuration of a solver in a version-controlled TOML file rather than
+//! in code. A typical configuration file looks like the following:
+//!
+//! ```toml
+//! preset   = "thorough"
+//! threads  = 8
+//! caching  = true
+//! cutset   = "frontier"
+//! fringe   = "no_dup"
+//! ordering = "bonus_first"
+//! seed     = 42
+//!
+//! [width]
+//! heuristic = "fixed"
+//! width     = 100
+//!
+//! [cutoff]
+//! heuristic = "time_budget"
+//! seconds   = 30
+//!
+//! [restarts]
+//! schedule = "luby"
+//! unit     = 1000
+//! ```
+//!
+//! All entries are optional: the missing ones take the value of the preset
+//! (or their default value when no preset is given).
+
+use std::{fmt, fs, hash::Hash, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// The configuration of a solver, as it can be read from (or written to) a
+/// TOML file. It is non exhaustive: outside of this crate, start from its 
+/// default value and use the `with_xxx` methods.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Overrides")]
+#[non_exhaustive]
+pub struct Config {
+    /// The preset from which the entries which are not configured explicitly
+    /// take their value (see `Config::preset`)
+    pub preset: PresetConfig,
+    /// The number of threads used by the solver
+    pub threads: usize,
+    /// Whether or not the solver should use a cache to avoid the re-exploration
+    /// of nodes that have already been explored
+    pub caching: bool,
+    /// The type of cutset used by the decision diagrams
+    pub cutset: CutsetConfig,
+    /// The type of fringe used to store the open subproblems
+    pub fringe: FringeConfig,
+    /// The maximum width heuristic
+    pub width: WidthConfig,
+    /// The cutoff heuristic
+    pub cutoff: CutoffConfig,
+    /// The order in which the fringe yields the open subproblems
+    pub ordering: OrderingConfig,
+    /// The restarts of the search
+    pub restarts: RestartsConfig,
+    /// The master seed of the randomized parts of the search (see `Restarts`)
+    pub seed: u64,
+}
+
+/// The entries of a configuration file, each of which overrides the value of
+/// the preset when it is given
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Overrides {
+    preset: PresetConfig,
+    threads: Option<usize>,
+    caching: Option<bool>,
+    cutset: Option<CutsetConfig>,
+    fringe: Option<FringeConfig>,
+    width: Option<WidthConfig>,
+    cutoff: Option<CutoffConfig>,
+    ordering: Option<OrderingConfig>,
+    restarts: Option<RestartsConfig>,
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresetConfig {
+    /// The default configuration
+    #[default]
+    Default,
+    /// Bounds the memory of the search: the subproblems are explored in 
+    /// depth-first order, and without any cache
+    LowMemory,
+    /// Spends more time on each subproblem to explore fewer of them: the DDs
+    /// are twice as wide and the nodes are cached
+    Thorough,
+}
+
+/// The cutset types which can be used in a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CutsetConfig {
+    /// See `LAST_EXACT_LAYER`
+    LastExactLayer,
+    /// See `FRONTIER`
+    Frontier,
+}
+
+/// The fringes which can be used in a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FringeConfig {
+    /// See `SimpleFringe`
+    Simple,
+    /// See `NoDupFringe`
+    NoDup,
+    /// See `StackFringe` (depth-first order)
+    DepthFirst,
+}
+
+/// The maximum width heuristics which can be used in a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "heuristic", rename_all = "snake_case", deny_unknown_fields)]
+pub enum WidthConfig {
+    /// See `FixedWidth`
+    Fixed { width: usize },
+    /// See `NbUnassignedWidth`. The resulting width can optionally be 
+    /// multiplied (`Times`) and/or divided (`DivBy`) by a constant factor.
+    NbUnassigned {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        times: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        div_by: Option<usize>,
+    },
+}
+
+/// The cutoff heuristics which can be used in a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "heuristic", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CutoffConfig {
+    /// See `NoCutoff`
+    None,
+    /// See `TimeBudget`
+    TimeBudget { seconds: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderingConfig {
+    /// See `MaxUB`
+    MaxUb,
+    /// See `BonusFirst` (the subproblems are then ordered by `MaxUB`)
+    BonusFirst,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "schedule", rename_all = "snake_case", deny_unknown_fields)]
+pub enum RestartsConfig {
+    /// The search is never restarted
+    None,
+    /// See `RestartSchedule::Fixed`
+    Fixed { nodes: usize },
+    /// See `RestartSchedule::Luby`
+    Luby { unit: usize },
+}
+
+/// The errors that can occur when loading or saving a configuration
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The configuration file could not be read or written
+    Io(std::io::Error),
+    /// The file is not a valid configuration. The error message tells what
+    /// is wrong (e.g. it lists the available names when an unknown heuristic
+    /// is used) and where.
+    Parse(toml::de::Error),
+    /// The configuration could not be serialized
+    Serialize(toml::ser::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e)        => write!(f, "io error: {e}"),
+            ConfigError::Parse(e)     => write!(f, "invalid configuration: {e}"),
+            ConfigError::Serialize(e) => write!(f, "cannot serialize configuration: {e}"),
+        }
+    }
+}
+impl std::error::Error for ConfigError {}
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {
+        ConfigError::Parse(e)
+    }
+}
+impl From<toml::ser::Error> for ConfigError {
+    fn from(e: toml::ser::Error) -> Self {
+        ConfigError::Serialize(e)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            preset  : PresetConfig::Default,
+            threads : num_cpus::get(),
+            caching : false,
+            cutset  : CutsetConfig::LastExactLayer,
+            fringe  : FringeConfig::NoDup,
+            width   : WidthConfig::NbUnassigned { times: None, div_by: None },
+            cutoff  : CutoffConfig::None,
+            ordering: OrderingConfig::MaxUb,
+            restarts: RestartsConfig::None,
+            seed    : 0,
+        }
+    }
+}
+
+impl From<Overrides> for Config {
+    fn from(overrides: Overrides) -> Self {
+        let preset = Config::preset(overrides.preset);
+        Config {
+            preset  : overrides.preset,
+            threads : overrides.threads.unwrap_or(preset.threads),
+            caching : overrides.caching.unwrap_or(preset.caching),
+            cutset  : overrides.cutset.unwrap_or(preset.cutset),
+            fringe  : overrides.fringe.unwrap_or(preset.fringe),
+            width   : overrides.width.unwrap_or(preset.width),
+            cutoff  : overrides.cutoff.unwrap_or(preset.cutoff),
+            ordering: overrides.ordering.unwrap_or(preset.ordering),
+            restarts: overrides.restarts.unwrap_or(preset.restarts),
+            seed    : overrides.seed.unwrap_or(preset.seed),
+        }
+    }
+}
+
+impl Config {
+    /// Returns the configuration of the given preset
+    pub fn preset(preset: PresetConfig) -> Self {
+        let default = Config { preset, ..Config::default() };
+        match preset {
+            PresetConfig::Default   => default,
+            PresetConfig::LowMemory => Config { fringe: FringeConfig::DepthFirst, caching: false, ..default },
+            PresetConfig::Thorough  => Config { 
+                caching: true, 
+                width  : WidthConfig::NbUnassigned { times: Some(2), div_by: None }, 
+                ..default 
+            },
+        }
+    }
+    /// Sets the number of threads used by the solver
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+    /// Sets whether or not the solver should use a cache
+    pub fn with_caching(mut self, caching: bool) -> Self {
+        self.caching = caching;
+        self
+    }
+    /// Sets the type of cutset used by the decision diagrams
+    pub fn with_cutset(mut self, cutset: CutsetConfig) -> Self {
+        self.cutset = cutset;
+        self
+    }
+    /// Sets the type of fringe used to store the open subproblems
+    pub fn with_fringe(mut self, fringe: FringeConfig) -> Self {
+        self.fringe = fringe;
+        self
+    }
+    /// Sets the maximum width heuristic
+    pub fn with_width(mut self, width: WidthConfig) -> Self {
+        self.width = width;
+        self
+    }
+    /// Sets the cutoff heuristic
+    pub fn with_cutoff(mut self, cutoff: CutoffConfig) -> Self {
+        self.cutoff = cutoff;
+        self
+    }
+    /// Sets the order in which the fringe yields the open subproblems
+    pub fn with_ordering(mut self, ordering: OrderingConfig) -> Self {
+        self.ordering = ordering;
+        self
+    }
+    /// Sets the restarts of the search
+    pub fn with_restarts(mut self, restarts: RestartsConfig) -> Self {
+        self.restarts = restarts;
+        self
+    }
+    /// Sets the master seed of the randomized parts of the search
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+    /// Loads the configuration stored in the given TOML file
+    pub fn from_toml<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_toml_str(&text)
+    }
+    /// Parses the configuration from the given TOML text
+    pub fn from_toml_str(text: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(text)?)
+    }
+    /// Returns the TOML representation of this configuration. This is useful
+    /// to save the exact configuration that was used alongside the results of
+    /// a run.
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Instantiates the configured maximum width heuristic
+    pub fn width_heuristic<T>(&self, nb_vars: usize) -> Box<dyn WidthHeuristic<T> + Send + Sync> {
+        match self.width {
+            WidthConfig::Fixed { width } => Box::new(FixedWidth(width)),
+            WidthConfig::NbUnassigned { times, div_by } => {
+                let heu = NbUnassignedWidth(nb_vars);
+                match (times, div_by) {
+                    (None,    None   ) => Box::new(heu),
+                    (Some(t), None   ) => Box::new(Times(t, heu)),
+                    (None,    Some(d)) => Box::new(DivBy(d, heu)),
+                    (Some(t), Some(d)) => Box::new(DivBy(d, Times(t, heu))),
+                }
+            }
+        }
+    }
+    /// Instantiates the configured cutoff heuristic
+    pub fn cutoff(&self) -> Box<dyn Cutoff + Send + Sync> {
+        match self.cutoff {
+            CutoffConfig::None => Box::new(NoCutoff),
+            CutoffConfig::TimeBudget { seconds } => Box::new(TimeBudget::new(Duration::from_secs(seconds))),
+        }
+    }
+    /// Instantiates the configured fringe which yields the subproblems in the
+    /// configured order, based on the given ranking of the states. The 
+    /// depth-first fringe ignores that order.
+    pub fn fringe<'a, O>(&self, ranking: &'a O) -> Box<dyn Fringe<State = O::State> + Send + Sync + 'a>
+    where O: StateRanking + Send + Sync,
+          O::State: Eq + Hash + Clone + Send + Sync,
+    {
+        match (self.fringe, self.ordering) {
+            (FringeConfig::Simple, OrderingConfig::MaxUb)      => Box::new(SimpleFringe::new(MaxUB::new(ranking))),
+            (FringeConfig::Simple, OrderingConfig::BonusFirst) => Box::new(SimpleFringe::new(BonusFirst::new(MaxUB::new(ranking)))),
+            (FringeConfig::NoDup,  OrderingConfig::MaxUb)      => Box::new(NoDupFringe::new(MaxUB::new(ranking))),
+            (FringeConfig::NoDup,  OrderingConfig::BonusFirst) => Box::new(NoDupFringe::new(BonusFirst::new(MaxUB::new(ranking)))),
+            (FringeConfig::DepthFirst, _)                      => Box::new(StackFringe::new()),
+        }
+    }
+    /// Returns the configured restarts policy (None when the search is never
+    /// restarted)
+    pub fn restarts(&self) -> Option<Restarts> {
+        let schedule = match self.restarts {
+            RestartsConfig::None            => return None,
+            RestartsConfig::Fixed { nodes } => RestartSchedule::Fixed { nodes },
+            RestartsConfig::Luby  { unit }  => RestartSchedule::Luby  { unit },
+        };
+        Some(Restarts { schedule, seed: self.seed, ..Restarts::default() })
+    }
+    /// Instantiates a parallel solver complying with this configuration (cutset
+    /// type, caching, number of threads and restarts).
+    #[allow(clippy::too_many_arguments)]
+    pub fn solver<'a, State>(&self,
+        problem: &'a (dyn Problem<State = State> + Send + Sync),
+        relaxation: &'a (dyn Relaxation<State = State> + Send + Sync),
+        ranking: &'a (dyn StateRanking<State = State> + Send + Sync),
+        width: &'a (dyn WidthHeuristic<State> + Send + Sync),
+        dominance: &'a (dyn DominanceChecker<State = State> + Send + Sync),
+        cutoff: &'a (dyn Cutoff + Send + Sync), 
+        fringe: &'a mut (dyn Fringe<State = State> + Send + Sync),
+    ) -> Box<dyn Solver + 'a> 
+    where State: Eq + Hash + Clone + Send + Sync + 'a
+    {
+        let n = self.threads;
+        match (self.caching, self.cutset) {
+            (false, CutsetConfig::LastExactLayer) => self.restarting(ParNoCachingSolverLel::custom(problem, relaxation, ranking, width, dominance, cutoff, fringe, n)),
+            (false, CutsetConfig::Frontier)       => self.restarting(ParNoCachingSolverFc::custom(problem, relaxation, ranking, width, dominance, cutoff, fringe, n)),
+            (true,  CutsetConfig::LastExactLayer) => self.restarting(ParCachingSolverLel::custom(problem, relaxation, ranking, width, dominance, cutoff, fringe, n)),
+            (true,  CutsetConfig::Frontier)       => self.restarting(ParCachingSolverFc::custom(problem, relaxation, ranking, width, dominance, cutoff, fringe, n)),
+        }
+    }
+    /// Enables the configured restarts of the given solver
+    fn restarting<'a, State, D, C>(&self, solver: ParallelSolver<'a, State, D, C>) -> Box<dyn Solver + 'a>
+    where State: Eq + Hash + Clone + Send + Sync + 'a,
+          D: DecisionDiagram<State = State> + Default + 'a,
+          C: Cache<State = State> + Send + Sync + Default + 'a,
+    {
+        match self.restarts() {
+            Some(restarts) => Box::new(solver.with_restarts(restarts)),
+            None           => Box::new(solver),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_config {
+    use crate::*;
+
+    #[test]
+    fn an_empty_file_yields_the_default_config() {
+        assert_eq!(Config::default(), Config::from_toml_str("").unwrap());
+    }
+    #[test]
+    fn missing_entries_take_their_default_value() {
+        let config = Config::from_toml_str("threads = 3").unwrap();
+        assert_eq!(3, config.threads);
+        assert_eq!(Config { threads: 3, ..Config::default() }, config);
+    }
+    #[test]
+    fn all_entries_can_be_configured() {
+        let config = Config::from_toml_str(r#"
+            preset   = "low_memory"
+            threads  = 2
+            caching  = true
+            cutset   = "frontier"
+            fringe   = "simple"
+            ordering = "bonus_first"
+            seed     = 42
+
+            [width]
+            heuristic = "fixed"
+            width     = 100
+
+            [cutoff]
+            heuristic = "time_budget"
+            seconds   = 30
+
+            [restarts]
+            schedule = "luby"
+            unit     = 10
+        "#).unwrap();
+
+        assert_eq!(Config {
+            preset  : PresetConfig::LowMemory,
+            threads : 2,
+            caching : true,
+            cutset  : CutsetConfig::Frontier,
+            fringe  : FringeConfig::Simple,
+            width   : WidthConfig::Fixed { width: 100 },
+            cutoff  : CutoffConfig::TimeBudget { seconds: 30 },
+            ordering: OrderingConfig::BonusFirst,
+            restarts: RestartsConfig::Luby { unit: 10 },
+            seed    : 42,
+        }, config);
+    }
+    #[test]
+    fn the_builders_set_the_entries() {
+        let config = Config::default()
+            .with_threads(2)
+            .with_caching(true)
+            .with_cutset(CutsetConfig::Frontier)
+            .with_fringe(FringeConfig::Simple)
+            .with_width(WidthConfig::Fixed { width: 100 })
+            .with_cutoff(CutoffConfig::TimeBudget { seconds: 30 })
+            .with_ordering(OrderingConfig::BonusFirst)
+            .with_restarts(RestartsConfig::Fixed { nodes: 10 })
+            .with_seed(42);
+        assert_eq!(Config {
+            preset  : PresetConfig::Default,
+            threads : 2,
+            caching : true,
+            cutset  : CutsetConfig::Frontier,
+            fringe  : FringeConfig::Simple,
+            width   : WidthConfig::Fixed { width: 100 },
+            cutoff  : CutoffConfig::TimeBudget { seconds: 30 },
+            ordering: OrderingConfig::BonusFirst,
+            restarts: RestartsConfig::Fixed { nodes: 10 },
+            seed    : 42,
+        }, config);
+    }
+    #[test]
+    fn to_toml_and_from_toml_round_trip() {
+        let configs = [
+            Config::default(),
+            Config {
+                preset  : PresetConfig::Default,
+                threads : 2,
+                caching : true,
+                cutset  : CutsetConfig::Frontier,
+                fringe  : FringeConfig::Simple,
+                width   : WidthConfig::Fixed { width: 100 },
+                cutoff  : CutoffConfig::TimeBudget { seconds: 30 },
+                ordering: OrderingConfig::BonusFirst,
+                restarts: RestartsConfig::Luby { unit: 10 },
+                seed    : 42,
+            },
+            Config {
+                width: WidthConfig::NbUnassigned { times: Some(2), div_by: Some(3) },
+                ..Config::default()
+            },
+            // the entries which differ from the preset are kept as well
+            Config { 
+                caching: false, 
+                ..Config::preset(PresetConfig::Thorough) 
+            },
+        ];
+        for config in configs {
+            let text = config.to_toml().unwrap();
+            assert_eq!(config, Config::from_toml_str(&text).unwrap());
+        }
+    }
+    #[test]
+    fn unknown_heuristic_names_are_reported_along_with_the_available_ones() {
+        let err = Config::from_toml_str(r#"
+            [width]
+            heuristic = "magic"
+        "#).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("magic"));
+        assert!(msg.contains("fixed"));
+        assert!(msg.contains("nb_unassigned"));
+    }
+    #[test]
+    fn unknown_cutset_names_are_reported_along_with_the_available_ones() {
+        let err = Config::from_toml_str(r#"cutset = "whatever""#).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("whatever"));
+        assert!(msg.contains("last_exact_layer"));
+        assert!(msg.contains("frontier"));
+    }
+    #[test]
+    fn missing_entries_take_the_value_of_the_preset() {
+        let config = Config::from_toml_str(r#"preset = "thorough""#).unwrap();
+        assert_eq!(Config::preset(PresetConfig::Thorough), config);
+        assert!(config.caching);
+        assert_eq!(WidthConfig::NbUnassigned { times: Some(2), div_by: None }, config.width);
+
+        let config = Config::from_toml_str(r#"preset = "low_memory""#).unwrap();
+        assert_eq!(FringeConfig::DepthFirst, config.fringe);
+    }
+    #[test]
+    fn the_given_entries_override_the_preset() {
+        let config = Config::from_toml_str(r#"
+            preset  = "thorough"
+            caching = false
+            seed    = 7
+        "#).unwrap();
+        assert_eq!(Config { 
+            caching: false, 
+            seed   : 7, 
+            ..Config::preset(PresetConfig::Thorough) 
+        }, config);
+    }
+    #[test]
+    fn unknown_preset_names_are_reported_along_with_the_available_ones() {
+        let err = Config::from_toml_str(r#"preset = "turbo""#).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("turbo"));
+        assert!(msg.contains("low_memory"));
+        assert!(msg.contains("thorough"));
+    }
+    #[test]
+    fn the_restarts_are_seeded_with_the_configured_seed() {
+        assert_eq!(None, Config::default().restarts());
+
+        let config = Config::default()
+            .with_restarts(RestartsConfig::Fixed { nodes: 10 })
+            .with_seed(42);
+        let restarts = config.restarts().unwrap();
+        assert_eq!(RestartSchedule::Fixed { nodes: 10 }, restarts.schedule);
+        assert_eq!(42, restarts.seed);
+    }
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Char(char);
+    struct CharRanking;
+    impl StateRanking for CharRanking {
+        type State = Char;
+        fn compare(&self, a: &Char, b: &Char) -> std::cmp::Ordering {
+            a.0.cmp(&b.0)
+        }
+    }
+    fn sub(state: char, bonus: u8) -> SubProblem<Char> {
+        SubProblem { state: std::sync::Arc::new(Char(state)), value: 0, ub: 10, path: vec![], depth: 0, priority_bonus: bonus }
+    }
+    #[test]
+    fn the_fringe_complies_with_the_configured_ordering() {
+        for (fringe, ordering, expected) in [
+            (FringeConfig::Simple, OrderingConfig::MaxUb,      'b'),
+            (FringeConfig::NoDup,  OrderingConfig::MaxUb,      'b'),
+            (FringeConfig::Simple, OrderingConfig::BonusFirst, 'a'),
+            (FringeConfig::NoDup,  OrderingConfig::BonusFirst, 'a'),
+        ] {
+            let config = Config::default().with_fringe(fringe).with_ordering(ordering);
+            let mut fringe = config.fringe(&CharRanking);
+            fringe.push(sub('a', 1));
+            fringe.push(sub('b', 0));
+            assert_eq!(Char(expected), *fringe.pop().unwrap().state);
+        }
+    }
+    #[test]
+    fn the_depth_first_fringe_can_be_configured() {
+        let config = Config::from_toml_str(r#"fringe = "depth_first""#).unwrap();
+        assert_eq!(FringeConfig::DepthFirst, config.fringe);
+    }
+    #[test]
+    fn unknown_entries_are_rejected() {
+        assert!(Config::from_toml_str("thread = 3").is_err());
+    }
+    #[test]
+    fn a_missing_heuristic_parameter_is_reported() {
+        let err = Config::from_toml_str(r#"
+            [width]
+            heuristic = "fixed"
+        "#).unwrap_err();
+        assert!(err.to_string().contains("width"));
+    }
+    #[test]
+    fn from_toml_reports_missing_files() {
+        assert!(matches!(Config::from_toml("/this/file/does/not/exist.toml"), Err(ConfigError::Io(_))));
+    }
+    #[test]
+    fn width_heuristic_complies_with_the_config() {
+        let sub = SubProblem { state: std::sync::Arc::new(()), value: 0, ub: 0, path: vec![], depth: 0, priority_bonus: 0 };
+        let fixed = Config { width: WidthConfig::Fixed { width: 7 }, ..Config::default() };
+        assert_eq!(7, fixed.width_heuristic(10).max_width(&sub));
+        let scaled = Config { width: WidthConfig::NbUnassigned { times: Some(6), div_by: Some(4) }, ..Config::default() };
+        assert_eq!(15, scaled.width_heuristic(10).max_width(&sub));
+    }
+}
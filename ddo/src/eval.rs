@@ -0,0 +1,569 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides utilities to evaluate the assignments which were 
+//! produced outside of ddo (e.g. by some other heuristic) against the DP model
+//! of a problem. An assignment is evaluated by replaying the transitions of
+//! the model in the order chosen by the problem; which makes it possible to
+//! compute its exact objective value or to tell where it becomes infeasible.
+//! The same replay drives random dives through the model, which yield random
+//! feasible solutions (see `sample_solutions`) to serve as a baseline.
+
+use std::{fmt, iter};
+
+use crate::{seed_of_run, Decision, Problem, Solution, Variable};
+
+/// The reasons why an assignment cannot be evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError<S> {
+    /// The assignment comprises several decisions about the same variable
+    Duplicate { variable: Variable },
+    /// The assignment does not give any value to a variable that must be 
+    /// decided. The state is the one that was reached before that variable.
+    MissingValue { variable: Variable, depth: usize, state: S },
+    /// The decision is not part of the domain of its variable in the state 
+    /// that was reached by the previous decisions of the assignment.
+    Infeasible { decision: Decision, depth: usize, state: S },
+    /// There is no feasible decision at all for the variable in the given 
+    /// state. This may only happen while repairing an assignment or while
+    /// sampling random solutions.
+    DeadEnd { variable: Variable, depth: usize, state: S },
+    /// The assignment comprises decisions about variables which were never 
+    /// decided before the model reached a terminal state.
+    Unused { decisions: Vec<Decision> },
+}
+
+impl <S: fmt::Debug> fmt::Display for EvalError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Duplicate { variable } => 
+                write!(f, "variable {} is assigned more than once", variable.id()),
+            EvalError::MissingValue { variable, depth, state } => 
+                write!(f, "variable {} is not assigned (depth {depth}, state {state:?})", variable.id()),
+            EvalError::Infeasible { decision, depth, state } => 
+                write!(f, "infeasible decision x{} = {} (depth {depth}, state {state:?})", 
+                    decision.variable.id(), decision.value),
+            EvalError::DeadEnd { variable, depth, state } => 
+                write!(f, "no feasible value for variable {} (depth {depth}, state {state:?})", variable.id()),
+            EvalError::Unused { decisions } => 
+                write!(f, "{} decisions bear on variables that are never decided", decisions.len()),
+        }
+    }
+}
+impl <S: fmt::Debug> std::error::Error for EvalError<S> {}
+
+/// The reasons why a solution cannot be used to warm start a solver (see
+/// `set_initial_solution`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarmStartError<S> {
+    /// The solution cannot be replayed through the problem
+    Invalid(EvalError<S>),
+    /// The solution is feasible, but it is not worth the announced value
+    WrongValue { announced: isize, actual: isize },
+}
+
+impl <S: fmt::Debug> fmt::Display for WarmStartError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarmStartError::Invalid(error) => 
+                write!(f, "the initial solution is invalid: {error}"),
+            WarmStartError::WrongValue { announced, actual } => 
+                write!(f, "the initial solution is worth {actual} and not {announced}"),
+        }
+    }
+}
+impl <S: fmt::Debug> std::error::Error for WarmStartError<S> {}
+
+/// Checks that the given solution is feasible and worth the announced value
+/// before a solver is warm started with it
+pub(crate) fn check_warm_start<P>(problem: &P, solution: &[Decision], announced: isize) -> Result<(), WarmStartError<P::State>>
+    where P: Problem + ?Sized
+{
+    let actual = evaluate(problem, solution).map_err(WarmStartError::Invalid)?;
+    if actual == announced {
+        Ok(())
+    } else {
+        Err(WarmStartError::WrongValue { announced, actual })
+    }
+}
+
+/// The outcome of a successful repair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repaired {
+    /// The objective value of the (repaired) solution
+    pub value: isize,
+    /// The complete solution, in the order the variables were decided
+    pub solution: Solution,
+    /// The first variable whose value had to be changed (or chosen) by the 
+    /// repair. It is None when the assignment did not need any repair.
+    pub repaired_from: Option<Variable>,
+}
+
+/// A random feasible solution (see `sample_solutions`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sampled {
+    /// The objective value of the solution
+    pub value: isize,
+    /// The complete solution, in the order the variables were decided
+    pub solution: Solution,
+}
+
+/// Computes the exact objective value of the given assignment. 
+///
+/// The transitions of the model are replayed from the initial state, following
+/// the variable ordering of the problem, until `next_variable` tells there is 
+/// no variable left to decide. Each decision must belong to the domain of its
+/// variable in the state reached by the previous ones. The first decision 
+/// which is missing or infeasible is reported along with a snapshot of the
+/// state in which it was to be made.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// # struct Sum;
+/// # impl Problem for Sum {
+/// #     type State = usize;
+/// #     fn nb_variables(&self) -> usize { 2 }
+/// #     fn initial_state(&self) -> usize { 0 }
+/// #     fn initial_value(&self) -> isize { 0 }
+/// #     fn transition(&self, s: &usize, _: Decision) -> usize { s + 1 }
+/// #     fn transition_cost(&self, _: &usize, _: &usize, d: Decision) -> isize { d.value }
+/// #     fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+/// #         if depth < 2 { Some(Variable(depth)) } else { None }
+/// #     }
+/// #     fn for_each_in_domain(&self, variable: Variable, _: &usize, f: &mut dyn DecisionCallback) {
+/// #         f.apply(Decision{variable, value: 0});
+/// #         f.apply(Decision{variable, value: 1});
+/// #     }
+/// # }
+/// let assignment = [
+///     Decision{variable: Variable(1), value: 1},
+///     Decision{variable: Variable(0), value: 1},
+/// ];
+/// assert_eq!(Ok(2), evaluate(&Sum, &assignment));
+///
+/// let infeasible = [
+///     Decision{variable: Variable(0), value: 1},
+///     Decision{variable: Variable(1), value: 5},
+/// ];
+/// assert!(matches!(evaluate(&Sum, &infeasible), Err(EvalError::Infeasible{depth: 1, ..})));
+/// ```
+pub fn evaluate<P>(problem: &P, assignment: &[Decision]) -> Result<isize, EvalError<P::State>>
+    where P: Problem + ?Sized
+{
+    replay(problem, assignment, false).map(|r| r.value)
+}
+
+/// Evaluates the given assignment just like `evaluate`, except that it does 
+/// not give up when the assignment is incomplete or becomes infeasible. 
+/// Instead, the solution is completed with a greedy dive from the failing 
+/// state: each remaining variable gets the feasible value having the best 
+/// transition cost. Whatever values the assignment gives to these remaining
+/// variables are discarded; and so are the decisions about variables which
+/// are never decided.
+pub fn repair<P>(problem: &P, assignment: &[Decision]) -> Result<Repaired, EvalError<P::State>>
+    where P: Problem + ?Sized
+{
+    replay(problem, assignment, true)
+}
+
+fn replay<P>(problem: &P, assignment: &[Decision], allow_repair: bool) -> Result<Repaired, EvalError<P::State>>
+    where P: Problem + ?Sized
+{
+    let mut values  = vec![None; problem.nb_variables()];
+    let mut unused  = vec![];
+    for d in assignment.iter().copied() {
+        match values.get_mut(d.variable.id()) {
+            None                 => unused.push(d),
+            Some(Some(_))        => return Err(EvalError::Duplicate { variable: d.variable }),
+            Some(x)              => *x = Some(d.value),
+        }
+    }
+
+    let mut state         = problem.initial_state();
+    let mut value         = problem.initial_value();
+    let mut depth         = 0;
+    let mut solution      = vec![];
+    let mut repaired_from = None;
+
+    while let Some(variable) = problem.next_variable(depth, &mut iter::once(&state)) {
+        let assigned = values.get_mut(variable.id()).and_then(|v| v.take());
+        let decision = match (repaired_from, assigned) {
+            (None, Some(value)) => {
+                let decision = Decision { variable, value };
+                if is_feasible(problem, &state, decision) {
+                    Some(decision)
+                } else if allow_repair {
+                    None
+                } else {
+                    return Err(EvalError::Infeasible { decision, depth, state });
+                }
+            },
+            (None, None) if !allow_repair => 
+                return Err(EvalError::MissingValue { variable, depth, state }),
+            _ => None,
+        };
+
+        let decision = match decision {
+            Some(decision) => decision,
+            None => {
+                repaired_from = repaired_from.or(Some(variable));
+                match greedy_decision(problem, &state, variable) {
+                    Some(decision) => decision,
+                    None => return Err(EvalError::DeadEnd { variable, depth, state }),
+                }
+            }
+        };
+
+        let next = problem.transition(&state, decision);
+        value   += problem.transition_cost(&state, &next, decision);
+        state    = next;
+        depth   += 1;
+        solution.push(decision);
+    }
+
+    if !allow_repair {
+        unused.extend(values.iter().enumerate()
+            .filter_map(|(i, v)| v.map(|value| Decision { variable: Variable(i), value })));
+        if !unused.is_empty() {
+            return Err(EvalError::Unused { decisions: unused });
+        }
+    }
+
+    Ok(Repaired { value, solution, repaired_from })
+}
+
+/// Performs `n` random dives from the initial state of the problem and 
+/// returns the solutions they reach: a baseline of "random feasible solutions"
+/// for the given instance. 
+///
+/// At each step, the dive picks one of the feasible values of the variable 
+/// chosen by `next_variable` uniformly at random. The feasible values are the
+/// ones of `for_each_in_domain` which are not skipped by `filter_layer` (the 
+/// layer being made of the current state only). A variable whose value is 
+/// forced thus always gets that value. A dive which reaches a state where the
+/// variable has no feasible value is recorded as an `EvalError::DeadEnd`.
+///
+/// The same `seed` always yields the same samples.
+///
+/// # Note
+/// When the problem was reduced by a preprocessing (see `Preprocess`), the
+/// samples are solutions of the reduced problem. They must be lifted with the
+/// `SolutionLifter` of the preprocessing to get back the forced decisions.
+pub fn sample_solutions<P>(problem: &P, n: usize, seed: u64) -> Vec<Result<Sampled, EvalError<P::State>>>
+    where P: Problem + ?Sized
+{
+    (0..n).map(|i| random_dive(problem, seed_of_run(seed, i))).collect()
+}
+
+/// Performs one random dive from the initial state of the problem
+fn random_dive<P>(problem: &P, mut rng: u64) -> Result<Sampled, EvalError<P::State>>
+    where P: Problem + ?Sized
+{
+    let mut state    = problem.initial_state();
+    let mut value    = problem.initial_value();
+    let mut depth    = 0;
+    let mut solution = vec![];
+    let mut feasible = vec![];
+
+    while let Some(variable) = problem.next_variable(depth, &mut iter::once(&state)) {
+        let mask = problem.filter_layer(variable, &mut iter::once(&state));
+        feasible.clear();
+        problem.for_each_in_domain(variable, &state, &mut |d: Decision| {
+            if mask.allows(d.value) {
+                feasible.push(d);
+            }
+        });
+        if feasible.is_empty() {
+            return Err(EvalError::DeadEnd { variable, depth, state });
+        }
+
+        rng = seed_of_run(rng, depth);
+        let decision = feasible[(rng % feasible.len() as u64) as usize];
+        let next = problem.transition(&state, decision);
+        value   += problem.transition_cost(&state, &next, decision);
+        state    = next;
+        depth   += 1;
+        solution.push(decision);
+    }
+
+    Ok(Sampled { value, solution })
+}
+
+/// Returns true iff the decision belongs to the domain of its variable in the
+/// given state
+fn is_feasible<P>(problem: &P, state: &P::State, decision: Decision) -> bool
+    where P: Problem + ?Sized
+{
+    let mut found = false;
+    problem.for_each_in_domain(decision.variable, state, &mut |d: Decision| {
+        found |= d.value == decision.value;
+    });
+    found
+}
+
+/// Returns the feasible decision about `variable` having the best transition
+/// cost from the given state (ties are broken in favor of the first decision
+/// being enumerated).
+fn greedy_decision<P>(problem: &P, state: &P::State, variable: Variable) -> Option<Decision>
+    where P: Problem + ?Sized
+{
+    let mut best: Option<(isize, Decision)> = None;
+    problem.for_each_in_domain(variable, state, &mut |d: Decision| {
+        let next = problem.transition(state, d);
+        let cost = problem.transition_cost(state, &next, d);
+        if best.is_none_or(|(c, _)| cost > c) {
+            best = Some((cost, d));
+        }
+    });
+    best.map(|(_, d)| d)
+}
+
+#[cfg(test)]
+mod test_eval {
+    use crate::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct KnapsackState {
+        depth: usize,
+        capacity: usize
+    }
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<usize>,
+        weight: Vec<usize>,
+    }
+
+    const TAKE_IT: isize = 1;
+    const LEAVE_IT_OUT: isize = 0;
+
+    impl Problem for Knapsack {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> Self::State {
+            KnapsackState{ depth: 0, capacity: self.capacity }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = state.clone();
+            ret.depth  += 1;
+            if dec.value == TAKE_IT { 
+                ret.capacity -= self.weight[dec.variable.id()] 
+            }
+            ret
+        }
+        fn transition_cost(&self, _state: &Self::State, _next: &Self::State, dec: Decision) -> isize {
+            self.profit[dec.variable.id()] as isize * dec.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            let n = self.nb_variables();
+            if depth < n {
+                Some(Variable(depth))
+            } else {
+                None
+            }
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback)
+        {
+            if state.capacity >= self.weight[variable.id()] {
+                f.apply(Decision { variable, value: TAKE_IT });
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            } else {
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            }
+        }
+    }
+
+    fn knapsack() -> Knapsack {
+        Knapsack {
+            capacity: 10,
+            profit  : vec![6, 5, 4],
+            weight  : vec![6, 4, 3],
+        }
+    }
+    fn assignment(values: &[isize]) -> Vec<Decision> {
+        values.iter().enumerate()
+            .map(|(i, v)| Decision{variable: Variable(i), value: *v})
+            .collect()
+    }
+
+    #[test]
+    fn evaluate_yields_the_objective_of_a_feasible_assignment() {
+        let pb = knapsack();
+        assert_eq!(Ok(11), evaluate(&pb, &assignment(&[1, 1, 0])));
+        assert_eq!(Ok(9),  evaluate(&pb, &assignment(&[0, 1, 1])));
+        assert_eq!(Ok(0),  evaluate(&pb, &assignment(&[0, 0, 0])));
+    }
+    #[test]
+    fn evaluate_does_not_depend_on_the_order_of_the_decisions() {
+        let pb = knapsack();
+        let mut a = assignment(&[1, 1, 0]);
+        a.reverse();
+        assert_eq!(Ok(11), evaluate(&pb, &a));
+    }
+    #[test]
+    fn evaluate_reports_the_first_infeasible_decision_and_its_state() {
+        let pb = knapsack();
+        let err = evaluate(&pb, &assignment(&[1, 1, 1])).unwrap_err();
+        assert_eq!(EvalError::Infeasible { 
+            decision: Decision{variable: Variable(2), value: 1}, 
+            depth: 2, 
+            state: KnapsackState{depth: 2, capacity: 0}
+        }, err);
+    }
+    #[test]
+    fn evaluate_reports_the_values_which_are_not_in_the_domain() {
+        let pb = knapsack();
+        let err = evaluate(&pb, &assignment(&[2, 0, 0])).unwrap_err();
+        assert!(matches!(err, EvalError::Infeasible { depth: 0, .. }));
+    }
+    #[test]
+    fn evaluate_reports_the_missing_values() {
+        let pb = knapsack();
+        let err = evaluate(&pb, &assignment(&[1])).unwrap_err();
+        assert_eq!(EvalError::MissingValue { 
+            variable: Variable(1), 
+            depth: 1, 
+            state: KnapsackState{depth: 1, capacity: 4}
+        }, err);
+    }
+    #[test]
+    fn evaluate_reports_the_duplicate_decisions() {
+        let pb = knapsack();
+        let mut a = assignment(&[1, 1, 0]);
+        a.push(Decision{variable: Variable(1), value: 0});
+        assert_eq!(Err(EvalError::Duplicate { variable: Variable(1) }), evaluate(&pb, &a));
+    }
+    #[test]
+    fn evaluate_reports_the_decisions_on_unknown_variables() {
+        let pb = knapsack();
+        let mut a = assignment(&[1, 1, 0]);
+        a.push(Decision{variable: Variable(7), value: 0});
+        assert_eq!(
+            Err(EvalError::Unused { decisions: vec![Decision{variable: Variable(7), value: 0}] }), 
+            evaluate(&pb, &a));
+    }
+    #[test]
+    fn repair_leaves_a_feasible_assignment_untouched() {
+        let pb = knapsack();
+        let a  = assignment(&[0, 1, 1]);
+        let r  = repair(&pb, &a).unwrap();
+        assert_eq!(Repaired { value: 9, solution: a, repaired_from: None }, r);
+    }
+    #[test]
+    fn repair_completes_an_infeasible_assignment_with_a_greedy_dive() {
+        let pb = knapsack();
+        let r  = repair(&pb, &assignment(&[0, 0, 5])).unwrap();
+        assert_eq!(Some(Variable(2)), r.repaired_from);
+        assert_eq!(assignment(&[0, 0, 1]), r.solution);
+        assert_eq!(4, r.value);
+    }
+    #[test]
+    fn repair_discards_the_values_following_the_failure() {
+        let pb = knapsack();
+        let r  = repair(&pb, &assignment(&[1, 3, 0])).unwrap();
+        assert_eq!(Some(Variable(1)), r.repaired_from);
+        assert_eq!(assignment(&[1, 1, 0]), r.solution);
+        assert_eq!(11, r.value);
+        assert_eq!(Ok(r.value), evaluate(&pb, &r.solution));
+    }
+    #[test]
+    fn repair_completes_a_partial_assignment() {
+        let pb = knapsack();
+        let r  = repair(&pb, &[]).unwrap();
+        assert_eq!(Some(Variable(0)), r.repaired_from);
+        assert_eq!(assignment(&[1, 1, 0]), r.solution);
+        assert_eq!(11, r.value);
+    }
+    #[test]
+    fn the_samples_are_feasible_solutions() {
+        let pb = knapsack();
+        let samples = sample_solutions(&pb, 50, 42);
+        assert_eq!(50, samples.len());
+        for sample in samples {
+            let sample = sample.unwrap();
+            assert_eq!(pb.nb_variables(), sample.solution.len());
+            assert_eq!(Ok(sample.value), evaluate(&pb, &sample.solution));
+        }
+    }
+    #[test]
+    fn the_same_seed_yields_the_same_samples() {
+        let pb = knapsack();
+        assert_eq!(sample_solutions(&pb, 20, 7), sample_solutions(&pb, 20, 7));
+        assert_ne!(sample_solutions(&pb, 20, 7), sample_solutions(&pb, 20, 8));
+    }
+    #[test]
+    fn the_samples_cover_several_solutions() {
+        let pb = knapsack();
+        let values = sample_solutions(&pb, 50, 42).into_iter()
+            .map(|s| s.unwrap().value)
+            .collect::<std::collections::HashSet<_>>();
+        assert!(values.len() > 1);
+    }
+
+    /// Two binary variables: the second one can only be decided when the 
+    /// first one is zero. Its value 2 is never allowed by the layer filter.
+    struct Stuck;
+    impl Problem for Stuck {
+        type State = isize;
+        fn nb_variables(&self) -> usize { 2 }
+        fn initial_state(&self) -> isize { 0 }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, state: &isize, d: Decision) -> isize { state + d.value }
+        fn transition_cost(&self, _: &isize, _: &isize, d: Decision) -> isize { d.value }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &isize>) -> Option<Variable> {
+            Some(Variable(depth)).filter(|_| depth < 2)
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &isize, f: &mut dyn DecisionCallback) {
+            if variable.id() == 0 || *state == 0 {
+                (0..=2).for_each(|value| f.apply(Decision { variable, value }));
+            }
+        }
+        fn filter_layer(&self, _: Variable, _: &mut dyn Iterator<Item = &isize>) -> ValueMask {
+            let mut mask = ValueMask::all();
+            mask.skip(2);
+            mask
+        }
+    }
+
+    #[test]
+    fn the_dead_ends_are_recorded() {
+        let samples = sample_solutions(&Stuck, 50, 3);
+        let dead_ends = samples.iter().filter(|s| s.is_err()).count();
+        assert!(dead_ends > 0);
+        assert!(dead_ends < 50);
+        for sample in samples {
+            match sample {
+                Ok(sample) => assert_eq!(Ok(sample.value), evaluate(&Stuck, &sample.solution)),
+                Err(e)     => assert_eq!(EvalError::DeadEnd { variable: Variable(1), depth: 1, state: 1 }, e),
+            }
+        }
+    }
+    #[test]
+    fn the_values_skipped_by_the_layer_filter_are_never_sampled() {
+        let samples = sample_solutions(&Stuck, 50, 3);
+        assert!(samples.iter().flatten()
+            .flat_map(|s| s.solution.iter())
+            .all(|d| d.value != 2));
+    }
+}
@@ -24,11 +24,11 @@
 //! `Relaxation` are defined. These are the two abstractions that one *must*
 //! implement in order to be able to use our library.
 
-use std::{sync::Arc, hash::Hash};
+use std::{sync::Arc, hash::Hash, mem::size_of};
 
 use dashmap::DashMap;
 
-use crate::{Cache, Threshold};
+use crate::{Cache, Threshold, arc_bytes};
 
 /// Simple implementation of Cache using one hashmap for each layer,
 /// each protected with a read-write lock.
@@ -72,4 +72,66 @@ where T: Hash + Eq {
     fn clear(&self) {
         self.thresholds_by_layer.iter().for_each(|l| l.clear());
     }
-}
\ No newline at end of file
+
+    fn forget_explored(&self) {
+        self.thresholds_by_layer.iter()
+            .for_each(|l| l.iter_mut().for_each(|mut t| t.explored = false));
+    }
+
+    fn estimated_bytes(&self) -> usize {
+        let per_entry = size_of::<Arc<T>>() + size_of::<Threshold>() + arc_bytes::<T>();
+        self.thresholds_by_layer.iter().map(|l| l.len() * per_entry).sum()
+    }
+}
+#[cfg(test)]
+mod test_simple_cache {
+    use std::sync::Arc;
+
+    use crate::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct DummyState(usize);
+    struct DummyProblem;
+    impl Problem for DummyProblem {
+        type State = DummyState;
+        fn nb_variables(&self) -> usize { 2 }
+        fn initial_state(&self) -> DummyState { DummyState(0) }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, _: &DummyState, _: Decision) -> DummyState { todo!() }
+        fn transition_cost(&self, _: &DummyState, _: &DummyState, _: Decision) -> isize { todo!() }
+        fn next_variable(&self, _: usize, _: &mut dyn Iterator<Item = &DummyState>) -> Option<Variable> { todo!() }
+        fn for_each_in_domain(&self, _: Variable, _: &DummyState, _: &mut dyn DecisionCallback) { todo!() }
+    }
+
+    fn subproblem(value: isize) -> SubProblem<DummyState> {
+        SubProblem { state: Arc::new(DummyState(1)), value, path: vec![], ub: isize::MAX, depth: 1, priority_bonus: 0 }
+    }
+
+    #[test]
+    fn forget_explored_keeps_the_thresholds() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&DummyProblem);
+        cache.update_threshold(Arc::new(DummyState(1)), 1, 5, true);
+        assert!(!cache.must_explore(&subproblem(5)));
+
+        cache.forget_explored();
+        assert_eq!(Some(Threshold { value: 5, explored: false }), cache.get_threshold(&DummyState(1), 1));
+        assert!(!cache.must_explore(&subproblem(4)));
+        assert!(cache.must_explore(&subproblem(5)));
+        assert!(cache.must_explore(&subproblem(6)));
+    }
+    #[test]
+    fn the_estimate_accounts_for_each_threshold() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&DummyProblem);
+        assert_eq!(0, cache.estimated_bytes());
+
+        cache.update_threshold(Arc::new(DummyState(1)), 1, 5, true);
+        cache.update_threshold(Arc::new(DummyState(2)), 1, 5, true);
+        cache.update_threshold(Arc::new(DummyState(3)), 2, 5, true);
+        cache.update_threshold(Arc::new(DummyState(3)), 2, 7, true);
+
+        let per_entry = std::mem::size_of::<Arc<DummyState>>() + std::mem::size_of::<Threshold>() + arc_bytes::<DummyState>();
+        assert_eq!(3 * per_entry, cache.estimated_bytes());
+    }
+}
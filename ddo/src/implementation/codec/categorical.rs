@@ -0,0 +1,192 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use std::marker::PhantomData;
+
+use crate::{Categorical, DecisionCodec};
+
+/// This codec encodes the values of any `Categorical` type using their codes.
+/// 
+/// # Example
+/// ```
+/// # use ddo::*;
+/// categorical! {
+///     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///     pub enum Color { Red, Green, Blue }
+/// }
+///
+/// let solution = vec![
+///     Decision{ variable: Variable(0), value: 2 },
+///     Decision{ variable: Variable(1), value: 0 },
+/// ];
+/// let codec = EnumCodec::<Color>::new();
+/// assert_eq!(Some(Color::Blue), solution.typed_value_of(Variable(0), &codec));
+/// assert_eq!(Some(Color::Red),  solution.typed_value_of(Variable(1), &codec));
+/// assert_eq!(None,              solution.typed_value_of(Variable(2), &codec));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct EnumCodec<V>(PhantomData<V>);
+
+impl <V> EnumCodec<V> {
+    pub fn new() -> Self {
+        EnumCodec(PhantomData)
+    }
+}
+impl <V> Default for EnumCodec<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl <V: Categorical> DecisionCodec<V> for EnumCodec<V> {
+    #[inline]
+    fn encode(&self, value: &V) -> isize {
+        value.code()
+    }
+    #[inline]
+    fn decode(&self, value: isize) -> Option<V> {
+        V::from_code(value)
+    }
+}
+
+/// This macro declares a field-less enum and implements the `Categorical` 
+/// trait for it. By default, the code of each variant is its discriminant;
+/// which means you can pick the integer encoding of each value by giving it
+/// an explicit discriminant.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// categorical! {
+///     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///     enum Side { Left = -1, Right = 1 }
+/// }
+///
+/// assert_eq!(-1, Side::Left.code());
+/// assert_eq!(Some(Side::Right), Side::from_code(1));
+/// assert_eq!(None, Side::from_code(0));
+/// ```
+#[macro_export]
+macro_rules! categorical {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident $(= $discr:expr)?),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($variant $(= $discr)?),*
+        }
+
+        impl $crate::Categorical for $name {
+            #[inline]
+            fn code(&self) -> isize {
+                match self {
+                    $($name::$variant => $name::$variant as isize),*
+                }
+            }
+            #[inline]
+            fn from_code(code: isize) -> Option<Self> {
+                $(
+                    if code == $name::$variant as isize {
+                        return Some($name::$variant);
+                    }
+                )*
+                None
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test_enum_codec {
+    use crate::*;
+
+    categorical! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Color { Red, Green, Blue }
+    }
+
+    categorical! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Side { Left = -1, Right = 1, }
+    }
+
+    #[test]
+    fn implicit_discriminants_are_used_as_codes() {
+        let codec = EnumCodec::<Color>::new();
+        assert_eq!(0, codec.encode(&Color::Red));
+        assert_eq!(1, codec.encode(&Color::Green));
+        assert_eq!(2, codec.encode(&Color::Blue));
+    }
+    #[test]
+    fn explicit_discriminants_are_used_as_codes() {
+        let codec = EnumCodec::<Side>::new();
+        assert_eq!(-1, codec.encode(&Side::Left));
+        assert_eq!( 1, codec.encode(&Side::Right));
+    }
+    #[test]
+    fn decode_is_the_inverse_of_encode() {
+        let codec = EnumCodec::<Color>::new();
+        for c in [Color::Red, Color::Green, Color::Blue] {
+            assert_eq!(Some(c), codec.decode(codec.encode(&c)));
+        }
+    }
+    #[test]
+    fn unknown_codes_cannot_be_decoded() {
+        let codec = EnumCodec::<Side>::new();
+        assert_eq!(None, codec.decode(0));
+        assert_eq!(None, codec.decode(2));
+    }
+    #[test]
+    fn typed_decisions_decode_the_whole_solution() {
+        let codec = EnumCodec::<Color>::new();
+        let solution = [
+            Decision{variable: Variable(0), value: 1},
+            Decision{variable: Variable(1), value: 2},
+        ];
+        let expected = vec![
+            TypedDecision{variable: Variable(0), value: Color::Green},
+            TypedDecision{variable: Variable(1), value: Color::Blue},
+        ];
+        assert_eq!(Some(expected), solution.typed_decisions(&codec));
+    }
+    #[test]
+    fn typed_decisions_fail_when_one_value_cannot_be_decoded() {
+        let codec = EnumCodec::<Color>::new();
+        let solution = [
+            Decision{variable: Variable(0), value: 1},
+            Decision{variable: Variable(1), value: 7},
+        ];
+        assert_eq!(None, solution.typed_decisions(&codec));
+    }
+    #[test]
+    fn typed_value_of_looks_up_the_variable() {
+        let codec = EnumCodec::<Color>::new();
+        let solution = [
+            Decision{variable: Variable(3), value: 0},
+            Decision{variable: Variable(1), value: 2},
+        ];
+        assert_eq!(Some(Color::Blue), solution.typed_value_of(Variable(1), &codec));
+        assert_eq!(Some(Color::Red),  solution.typed_value_of(Variable(3), &codec));
+        assert_eq!(None,              solution.typed_value_of(Variable(0), &codec));
+        assert_eq!(Some(2),           solution.value_of(Variable(1)));
+    }
+}
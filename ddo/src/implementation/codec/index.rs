@@ -0,0 +1,57 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use crate::DecisionCodec;
+
+/// This codec is meant to be used when the values of your decisions are the
+/// indices of some items (jobs, scenes, cities, ...): it maps any `usize` onto
+/// the very same (non-negative) integer.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IndexCodec;
+
+impl DecisionCodec<usize> for IndexCodec {
+    #[inline]
+    fn encode(&self, value: &usize) -> isize {
+        *value as isize
+    }
+    #[inline]
+    fn decode(&self, value: isize) -> Option<usize> {
+        usize::try_from(value).ok()
+    }
+}
+
+#[cfg(test)]
+mod test_index_codec {
+    use crate::*;
+
+    #[test]
+    fn encode_is_the_identity() {
+        assert_eq!(0, IndexCodec.encode(&0));
+        assert_eq!(42, IndexCodec.encode(&42));
+    }
+    #[test]
+    fn decode_is_the_identity_on_non_negative_values() {
+        assert_eq!(Some(0), IndexCodec.decode(0));
+        assert_eq!(Some(42), IndexCodec.decode(42));
+    }
+    #[test]
+    fn negative_values_cannot_be_decoded() {
+        assert_eq!(None, IndexCodec.decode(-1));
+    }
+}
@@ -17,10 +17,10 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use std::{hash::Hash, cmp::Ordering, sync::Arc, fmt::Debug};
+use std::{hash::Hash, cmp::Ordering, sync::Arc, fmt::Debug, mem::size_of};
 use dashmap::{DashMap, mapref::entry::Entry};
 
-use crate::{Dominance, DominanceChecker, DominanceCmpResult, DominanceCheckResult};
+use crate::{Dominance, DominanceChecker, DominanceCmpResult, DominanceCheckResult, arc_bytes};
 
 /// Simple implementation of a dominance checker that stores a vector of non-dominated
 /// states for each distinct key.
@@ -113,13 +113,23 @@ where
     fn cmp(&self, a: &Self::State, val_a: isize, b: &Self::State, val_b: isize) -> Ordering {
         self.dominance.cmp(a, val_a, b, val_b)
     }
+
+    fn estimated_bytes(&self) -> usize {
+        let per_key   = size_of::<D::Key>() + size_of::<Vec<DominanceEntry<D::State>>>();
+        let per_entry = size_of::<DominanceEntry<D::State>>() + arc_bytes::<D::State>();
+        self.data.iter()
+            .flat_map(|layer| layer.iter().map(|e| per_key + e.value().len() * per_entry))
+            .sum()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{sync::Arc, mem::size_of};
+
+    use crate::{Dominance, SimpleDominanceChecker, DominanceChecker, DominanceCheckResult, arc_bytes};
 
-    use crate::{Dominance, SimpleDominanceChecker, DominanceChecker, DominanceCheckResult};
+    use super::DominanceEntry;
 
     #[test]
     fn not_dominated_when_keys_are_different() {
@@ -223,6 +233,20 @@ mod tests {
         assert_eq!(1, dominance.data[0].get(&0).unwrap().len());
     }
 
+    #[test]
+    fn the_estimate_accounts_for_each_key_and_entry() {
+        let dominance = SimpleDominanceChecker::new(DummyDominance, 0);
+        assert_eq!(0, dominance.estimated_bytes());
+
+        dominance.is_dominated_or_insert(Arc::new(vec![0, 1, 0]), 0, 0);
+        dominance.is_dominated_or_insert(Arc::new(vec![0, 0, 1]), 0, 0);
+        dominance.is_dominated_or_insert(Arc::new(vec![1, 0, 0]), 0, 0);
+
+        let per_key   = size_of::<isize>() + size_of::<Vec<DominanceEntry<Vec<isize>>>>();
+        let per_entry = size_of::<DominanceEntry<Vec<isize>>>() + arc_bytes::<Vec<isize>>();
+        assert_eq!(2 * per_key + 3 * per_entry, dominance.estimated_bytes());
+    }
+
     struct DummyDominance;
     impl Dominance for DummyDominance {
         type State = Vec<isize>;
@@ -0,0 +1,182 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides a tree export which writes each event of the search
+//! tree as one JSON object per line (JSON lines). 
+//!
+//! # Schema
+//! Each line is a JSON object whose `"event"` field tells the kind of event. 
+//! All the other fields are integers, except for the reason of a pruning. 
+//! Fields which have no value are `null`.
+//!
+//! ```text
+//! {"event":"created","node":1,"parent":0,"depth":2,"value":60,"ub":220}
+//! {"event":"pruned","node":1,"reason":"bound"}
+//! {"event":"fathomed","node":2,"value":null}
+//! {"event":"bounds","lb":160,"ub":220}
+//! ```
+//!
+//!  + `created`: `node`, `parent` (null for a root), `depth`, `value` and `ub`
+//!  + `pruned`: `node` and `reason` (one of `"bound"`, `"cache"`, `"dropped"`)
+//!  + `fathomed`: `node` and `value` (null when the node is infeasible)
+//!  + `bounds`: `lb` and `ub`
+//!
+//! # Note
+//! The bounds are 64 bit integers. The unknown bounds (e.g. the initial upper
+//! bound) are written as `isize::MIN` and `isize::MAX`, which a JavaScript 
+//! reader cannot represent exactly.
+
+use std::{fmt::Display, io::{self, Write}};
+
+use crate::{TreeEvent, TreeExport};
+
+/// This tree export writes the events of the search tree to `W` as JSON lines
+/// (see the module documentation for the schema). The first error raised by
+/// the writer stops the export; it is reported by `finish`.
+#[derive(Debug)]
+pub struct JsonLinesExport<W: Write> {
+    /// Where the events are written
+    out: W,
+    /// The first error which was raised by the writer
+    error: Option<io::Error>,
+}
+
+impl<W: Write> JsonLinesExport<W> {
+    /// Creates an export which writes to the given writer
+    pub fn new(out: W) -> Self {
+        Self { out, error: None }
+    }
+    /// Flushes the writer and returns it, or the first error which occurred
+    /// during the export
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+impl<W: Write> TreeExport for JsonLinesExport<W> {
+    fn export(&mut self, event: &TreeEvent) {
+        if self.error.is_none() {
+            if let Err(error) = write_event(&mut self.out, event) {
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+/// Writes the given event as one line of JSON
+fn write_event(out: &mut dyn Write, event: &TreeEvent) -> io::Result<()> {
+    match *event {
+        TreeEvent::Created { node, parent, depth, value, ub } => writeln!(out,
+            r#"{{"event":"created","node":{},"parent":{},"depth":{},"value":{},"ub":{}}}"#,
+            node, Nullable(parent), depth, value, ub),
+        TreeEvent::Pruned { node, reason } => writeln!(out,
+            r#"{{"event":"pruned","node":{},"reason":"{}"}}"#,
+            node, reason.as_str()),
+        TreeEvent::Fathomed { node, value } => writeln!(out,
+            r#"{{"event":"fathomed","node":{},"value":{}}}"#,
+            node, Nullable(value)),
+        TreeEvent::Bounds { lb, ub } => writeln!(out,
+            r#"{{"event":"bounds","lb":{},"ub":{}}}"#,
+            lb, ub),
+    }
+}
+
+/// Displays an optional value as JSON
+struct Nullable<T>(Option<T>);
+impl<T: Display> Display for Nullable<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(x) => write!(f, "{x}"),
+            None => write!(f, "null"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_json_lines {
+    use crate::*;
+
+    fn export(events: &[TreeEvent]) -> String {
+        let mut export = JsonLinesExport::new(vec![]);
+        events.iter().for_each(|e| export.export(e));
+        String::from_utf8(export.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn each_event_is_written_according_to_the_schema() {
+        let text = export(&[
+            TreeEvent::Bounds { lb: isize::MIN, ub: isize::MAX },
+            TreeEvent::Created { node: 0, parent: None, depth: 0, value: 0, ub: isize::MAX },
+            TreeEvent::Created { node: 1, parent: Some(0), depth: 2, value: 60, ub: 220 },
+            TreeEvent::Created { node: 2, parent: Some(0), depth: 2, value: -5, ub: 180 },
+            TreeEvent::Fathomed { node: 1, value: Some(160) },
+            TreeEvent::Fathomed { node: 3, value: None },
+            TreeEvent::Pruned { node: 2, reason: PruneReason::Bound },
+            TreeEvent::Pruned { node: 4, reason: PruneReason::Cache },
+            TreeEvent::Pruned { node: 5, reason: PruneReason::Dropped },
+            TreeEvent::Bounds { lb: 160, ub: 160 },
+        ]);
+        assert_eq!(text, concat!(
+            r#"{"event":"bounds","lb":-9223372036854775808,"ub":9223372036854775807}"#, "\n",
+            r#"{"event":"created","node":0,"parent":null,"depth":0,"value":0,"ub":9223372036854775807}"#, "\n",
+            r#"{"event":"created","node":1,"parent":0,"depth":2,"value":60,"ub":220}"#, "\n",
+            r#"{"event":"created","node":2,"parent":0,"depth":2,"value":-5,"ub":180}"#, "\n",
+            r#"{"event":"fathomed","node":1,"value":160}"#, "\n",
+            r#"{"event":"fathomed","node":3,"value":null}"#, "\n",
+            r#"{"event":"pruned","node":2,"reason":"bound"}"#, "\n",
+            r#"{"event":"pruned","node":4,"reason":"cache"}"#, "\n",
+            r#"{"event":"pruned","node":5,"reason":"dropped"}"#, "\n",
+            r#"{"event":"bounds","lb":160,"ub":160}"#, "\n",
+        ));
+    }
+    #[test]
+    fn each_line_is_valid_json() {
+        let text = export(&[
+            TreeEvent::Created { node: 0, parent: None, depth: 0, value: 0, ub: 12 },
+            TreeEvent::Pruned { node: 0, reason: PruneReason::Bound },
+        ]);
+        for line in text.lines() {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(value["event"].is_string());
+            assert_eq!(0, value["node"].as_u64().unwrap());
+        }
+    }
+    #[test]
+    fn the_first_error_is_reported_by_finish() {
+        #[derive(Debug)]
+        struct Broken;
+        impl std::io::Write for Broken {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("broken"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut export = JsonLinesExport::new(Broken);
+        export.export(&TreeEvent::Bounds { lb: 0, ub: 1 });
+        export.export(&TreeEvent::Bounds { lb: 1, ub: 1 });
+        assert_eq!("broken", export.finish().unwrap_err().to_string());
+    }
+}
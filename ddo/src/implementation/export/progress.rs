@@ -0,0 +1,260 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the implementations of the `Progress` abstraction: 
+//! one which ignores the progress of the search altogether, one which prints
+//! it on the standard output and one which writes it to any `io::Write` (e.g.
+//! a file).
+
+use std::io::{self, Write};
+
+use crate::{Progress, ProgressEvent};
+
+/// _This is the default progress listener._ It ignores all events.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentProgress;
+impl Progress for SilentProgress {
+    fn report(&mut self, _: &ProgressEvent) {}
+}
+
+/// This progress listener prints one line per event on the standard output.
+/// Since a line per explored node is a lot, it only prints one every `period`
+/// explored nodes (see `with_period`).
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// let mut progress = StdoutProgress::default().with_period(100);
+/// progress.report(&ProgressEvent::NewIncumbent { value: 42 });
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct StdoutProgress {
+    /// A line is printed every `period` explored nodes
+    period: usize,
+}
+impl Default for StdoutProgress {
+    fn default() -> Self {
+        Self { period: 1 }
+    }
+}
+impl StdoutProgress {
+    /// Only prints one line every `period` explored nodes (the other events
+    /// are always printed)
+    pub fn with_period(mut self, period: usize) -> Self {
+        self.period = period.max(1);
+        self
+    }
+    /// Returns the line which is printed for the given event (if any)
+    fn line(&self, event: &ProgressEvent) -> Option<String> {
+        line(event, self.period)
+    }
+}
+impl Progress for StdoutProgress {
+    fn report(&mut self, event: &ProgressEvent) {
+        if let Some(line) = self.line(event) {
+            println!("{line}");
+        }
+    }
+}
+
+/// This progress listener writes the same lines as `StdoutProgress` to any 
+/// writer, for instance a file or a buffer. Each line is flushed as soon as
+/// it is written (see `with_flush`) so that the trace of a long run can be 
+/// followed while it is being written.
+///
+/// # Note
+/// A failure to write does not interrupt the search: the listener stops 
+/// writing and remembers the error (see `error`).
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// let mut progress = WriterProgress::new(vec![]).with_period(100);
+/// progress.report(&ProgressEvent::NewIncumbent { value: 42 });
+/// assert_eq!(b"New best 42\n", progress.into_inner().as_slice());
+/// ```
+#[derive(Debug)]
+pub struct WriterProgress<W: Write> {
+    /// Where the lines are written
+    writer: W,
+    /// A line is written every `period` explored nodes
+    period: usize,
+    /// Whether the writer is flushed after each line
+    flush: bool,
+    /// The error which stopped the listener from writing (if any)
+    error: Option<io::Error>,
+}
+impl<W: Write> WriterProgress<W> {
+    /// Creates a listener which writes one line per event to `writer`
+    pub fn new(writer: W) -> Self {
+        Self { writer, period: 1, flush: true, error: None }
+    }
+    /// Only writes one line every `period` explored nodes (the other events
+    /// are always written)
+    pub fn with_period(mut self, period: usize) -> Self {
+        self.period = period.max(1);
+        self
+    }
+    /// Tells whether the writer is flushed after each line (it is by default).
+    /// Not flushing is cheaper when the writer is already buffered and when 
+    /// nobody follows the trace as it is written.
+    pub fn with_flush(mut self, flush: bool) -> Self {
+        self.flush = flush;
+        self
+    }
+    /// Returns the error which stopped the listener from writing (if any)
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+    /// Returns the writer of this listener
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+    /// Writes the given line (and flushes it when that is required)
+    fn write(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.writer, "{line}")?;
+        if self.flush {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+}
+impl<W: Write> Progress for WriterProgress<W> {
+    fn report(&mut self, event: &ProgressEvent) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Some(line) = line(event, self.period) {
+            self.error = self.write(&line).err();
+        }
+    }
+}
+
+/// Any closure which takes an event is a progress listener too. This lets
+/// you route the progress of the search wherever you want without writing a
+/// dedicated type.
+impl<F: FnMut(&ProgressEvent)> Progress for F {
+    fn report(&mut self, event: &ProgressEvent) {
+        self(event)
+    }
+}
+
+/// Returns the line which describes the given event, if any: the explored 
+/// nodes are only described once every `period` nodes.
+fn line(event: &ProgressEvent, period: usize) -> Option<String> {
+    match *event {
+        ProgressEvent::NodeExplored { explored, lb, ub, fringe_len } => 
+            (explored % period == 0).then(|| 
+                format!("Explored {explored}, LB {lb}, UB {ub}, Fringe sz {fringe_len}")),
+        ProgressEvent::NewIncumbent { value } => 
+            Some(format!("New best {value}")),
+        ProgressEvent::Finished { value: Some(value), explored } => 
+            Some(format!("Finished with {value} after {explored} nodes")),
+        ProgressEvent::Finished { value: None, explored } => 
+            Some(format!("Finished without any solution after {explored} nodes")),
+    }
+}
+
+#[cfg(test)]
+mod test_progress {
+    use crate::*;
+
+    #[test]
+    fn the_explored_nodes_are_printed_once_every_period() {
+        let progress = StdoutProgress::default().with_period(10);
+        let event = |explored| ProgressEvent::NodeExplored { explored, lb: 10, ub: 20, fringe_len: 3 };
+        assert_eq!(None, progress.line(&event(9)));
+        assert_eq!(Some("Explored 10, LB 10, UB 20, Fringe sz 3".to_string()), progress.line(&event(10)));
+    }
+    #[test]
+    fn the_other_events_are_always_printed() {
+        let progress = StdoutProgress::default().with_period(10);
+        assert_eq!(Some("New best 42".to_string()), 
+            progress.line(&ProgressEvent::NewIncumbent { value: 42 }));
+        assert_eq!(Some("Finished with 42 after 7 nodes".to_string()), 
+            progress.line(&ProgressEvent::Finished { value: Some(42), explored: 7 }));
+        assert_eq!(Some("Finished without any solution after 7 nodes".to_string()), 
+            progress.line(&ProgressEvent::Finished { value: None, explored: 7 }));
+    }
+    #[test]
+    fn the_writer_gets_the_same_lines_as_the_standard_output() {
+        let mut progress = WriterProgress::new(vec![]).with_period(10);
+        for explored in 1..=20 {
+            progress.report(&ProgressEvent::NodeExplored { explored, lb: 10, ub: 20, fringe_len: 3 });
+        }
+        progress.report(&ProgressEvent::Finished { value: Some(42), explored: 20 });
+        assert!(progress.error().is_none());
+        let text = String::from_utf8(progress.into_inner()).unwrap();
+        assert_eq!("Explored 10, LB 10, UB 20, Fringe sz 3\n\
+                    Explored 20, LB 10, UB 20, Fringe sz 3\n\
+                    Finished with 42 after 20 nodes\n", text);
+    }
+    #[test]
+    fn the_writer_is_flushed_after_each_line_unless_told_otherwise() {
+        struct Flushes { lines: usize, flushes: usize }
+        impl std::io::Write for Flushes {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.lines += buf.iter().filter(|b| **b == b'\n').count();
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.flushes += 1;
+                Ok(())
+            }
+        }
+        let event = ProgressEvent::NewIncumbent { value: 42 };
+        let mut progress = WriterProgress::new(Flushes { lines: 0, flushes: 0 });
+        progress.report(&event);
+        progress.report(&event);
+        let writer = progress.into_inner();
+        assert_eq!((2, 2), (writer.lines, writer.flushes));
+
+        let mut progress = WriterProgress::new(Flushes { lines: 0, flushes: 0 }).with_flush(false);
+        progress.report(&event);
+        let writer = progress.into_inner();
+        assert_eq!((1, 0), (writer.lines, writer.flushes));
+    }
+    #[test]
+    fn the_writer_stops_at_the_first_error() {
+        struct Broken { attempts: usize }
+        impl std::io::Write for Broken {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                self.attempts += 1;
+                Err(std::io::Error::other("broken"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let event = ProgressEvent::NewIncumbent { value: 42 };
+        let mut progress = WriterProgress::new(Broken { attempts: 0 });
+        progress.report(&event);
+        progress.report(&event);
+        assert!(progress.error().is_some());
+        assert_eq!(1, progress.into_inner().attempts);
+    }
+    #[test]
+    fn a_closure_is_a_progress_listener() {
+        let mut seen = vec![];
+        let mut listener = |event: &ProgressEvent| seen.push(*event);
+        listener.report(&ProgressEvent::NewIncumbent { value: 42 });
+        assert_eq!(vec![ProgressEvent::NewIncumbent { value: 42 }], seen);
+    }
+}
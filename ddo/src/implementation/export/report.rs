@@ -0,0 +1,477 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the report of a run (see `RunReport`): a small JSON
+//! document which records the outcome of a resolution, and which can be
+//! compared with the report of a later run to make sure that a change of the
+//! model did not alter its results (see `RunReport::check_matches`).
+//!
+//! # Schema
+//! The report is one JSON object. The fields which have no value are `null`,
+//! and the unknown fields are ignored when a report is read.
+//!
+//! ```text
+//! {"schema":1,"value":380,"proved":true,"upper_bound":380,"gap":0,"explored":42,"termination":null}
+//! ```
+//!
+//!  + `value`: the value of the best solution (null when none was found)
+//!  + `proved`: true iff that value was proved optimal
+//!  + `upper_bound`: the best upper bound (null when it is unknown)
+//!  + `gap`: the relative gap between the bounds (null when it is infinite)
+//!  + `explored`: the number of explored subproblems (null when unknown)
+//!  + `termination`: the reason why the search stopped early (see
+//!    `Reason::code`), or null when it ran to its end
+
+use std::{fmt, fs, io, iter::Peekable, path::Path, str::Chars};
+
+use crate::Completion;
+
+/// The version of the schema of the reports
+const SCHEMA: i64 = 1;
+
+/// The outcome of a resolution, as it is written to (or read from) a report
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct RunReport {
+    /// The value of the best solution (if one was found)
+    pub value: Option<isize>,
+    /// Whether or not that value was proved optimal
+    pub proved: bool,
+    /// The best upper bound (None when it is unknown)
+    pub upper_bound: Option<isize>,
+    /// The relative gap between the bounds (None when it is infinite)
+    pub gap: Option<f64>,
+    /// The number of explored subproblems (if it is known)
+    pub explored: Option<usize>,
+    /// The reason why the search stopped early (see `Reason::code`), or None
+    /// when it ran to its end
+    pub termination: Option<String>,
+}
+
+impl RunReport {
+    /// Creates the report of the resolution which ended with the given 
+    /// completion and upper bound (see `Solver::best_upper_bound`)
+    pub fn new(completion: &Completion, upper_bound: isize) -> Self {
+        let termination = completion.termination.as_ref();
+        Self {
+            value: completion.best_value,
+            proved: completion.is_exact,
+            upper_bound: Some(upper_bound).filter(|ub| *ub != isize::MAX),
+            gap: termination.map(|t| t.gap).filter(|gap| gap.is_finite()),
+            explored: termination.map(|t| t.explored),
+            termination: completion.stopped_by.as_ref().map(|reason| reason.code().to_string()),
+        }
+    }
+
+    /// Returns the JSON representation of this report (on one line)
+    pub fn to_json(&self) -> String {
+        format!(
+            r#"{{"schema":{},"value":{},"proved":{},"upper_bound":{},"gap":{},"explored":{},"termination":{}}}"#,
+            SCHEMA,
+            Json(&self.value),
+            self.proved,
+            Json(&self.upper_bound),
+            Json(&self.gap),
+            Json(&self.explored),
+            Json(&self.termination.as_ref().map(|t| Quoted(t))),
+        )
+    }
+    /// Parses a report from its JSON representation
+    pub fn from_json(text: &str) -> Result<Self, ReportError> {
+        let mut report = RunReport { 
+            value: None, proved: false, upper_bound: None, gap: None, explored: None, termination: None 
+        };
+        let mut proved = None;
+        for (key, scalar) in parse_object(text)? {
+            match key.as_str() {
+                "schema" if scalar.integer::<i64>(&key)? != Some(SCHEMA) => {
+                    return Err(ReportError::Parse(format!("unsupported schema (expected {SCHEMA})")));
+                },
+                "value"       => report.value       = scalar.integer(&key)?,
+                "upper_bound" => report.upper_bound = scalar.integer(&key)?,
+                "explored"    => report.explored    = scalar.integer(&key)?,
+                "gap"         => report.gap         = scalar.float(&key)?,
+                "termination" => report.termination = scalar.text(&key)?,
+                "proved"      => proved             = Some(scalar.boolean(&key)?),
+                _ => {}
+            }
+        }
+        report.proved = proved.ok_or_else(|| ReportError::Parse("missing field 'proved'".to_string()))?;
+        Ok(report)
+    }
+    /// Loads the report stored in the given JSON file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReportError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+    /// Saves this report to the given JSON file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ReportError> {
+        Ok(fs::write(path, self.to_json() + "\n")?)
+    }
+
+    /// Checks that this run reproduces the results of the `previous` one: it
+    /// must have found the same value, and it must have proved it optimal if
+    /// the previous run did. When `strict` is set (which only makes sense when
+    /// the search is deterministic, e.g. with one single thread), both runs 
+    /// must also have explored the same number of subproblems.
+    pub fn check_matches(&self, previous: &RunReport, strict: bool) -> Result<(), Box<Regression>> {
+        let mut mismatches = vec![];
+        if self.value != previous.value {
+            mismatches.push(Mismatch::Value);
+        }
+        if previous.proved && !self.proved {
+            mismatches.push(Mismatch::Proof);
+        }
+        if strict && self.explored != previous.explored {
+            mismatches.push(Mismatch::Explored);
+        }
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(Box::new(Regression { previous: previous.clone(), current: self.clone(), mismatches }))
+        }
+    }
+}
+
+/// What differs between a run and the previous one it was checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The best values differ
+    Value,
+    /// The previous run proved its value optimal but the current one did not
+    Proof,
+    /// The numbers of explored subproblems differ (only checked when strict)
+    Explored,
+}
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Value    => write!(f, "the value differs"),
+            Mismatch::Proof    => write!(f, "the optimality proof was lost"),
+            Mismatch::Explored => write!(f, "the number of explored nodes differs"),
+        }
+    }
+}
+
+/// The failure of `RunReport::check_matches`. Its `Display` tells what went 
+/// wrong and shows the previous and current outcomes side by side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    /// The report of the previous run
+    pub previous: RunReport,
+    /// The report of the current run
+    pub current: RunReport,
+    /// What differs between them
+    pub mismatches: Vec<Mismatch>,
+}
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let causes = self.mismatches.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        writeln!(f, "the run does not match the previous one: {}", causes.join(", "))?;
+        let (old, new) = (&self.previous, &self.current);
+        let rows = [
+            ("value",       Json(&old.value).to_string(),       Json(&new.value).to_string()),
+            ("proved",      old.proved.to_string(),             new.proved.to_string()),
+            ("upper bound", Json(&old.upper_bound).to_string(), Json(&new.upper_bound).to_string()),
+            ("gap",         gap(old.gap),                       gap(new.gap)),
+            ("explored",    Json(&old.explored).to_string(),    Json(&new.explored).to_string()),
+            ("termination", termination(&old.termination),      termination(&new.termination)),
+        ];
+        write!(f, "  {:<12} {:>12} {:>12}", "", "previous", "current")?;
+        for (name, old, new) in rows {
+            let mark = if old == new { "" } else { "  <--" };
+            write!(f, "\n  {name:<12} {old:>12} {new:>12}{mark}")?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for Regression {}
+
+fn gap(gap: Option<f64>) -> String {
+    gap.map_or_else(|| "inf".to_string(), |gap| format!("{gap:.4}"))
+}
+fn termination(reason: &Option<String>) -> String {
+    reason.clone().unwrap_or_else(|| "completed".to_string())
+}
+
+/// The errors that can occur when loading or saving a report
+#[derive(Debug)]
+pub enum ReportError {
+    /// The report could not be read or written
+    Io(io::Error),
+    /// The text is not a valid report. The message tells what is wrong.
+    Parse(String),
+}
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportError::Io(e)    => write!(f, "io error: {e}"),
+            ReportError::Parse(e) => write!(f, "invalid report: {e}"),
+        }
+    }
+}
+impl std::error::Error for ReportError {}
+impl From<io::Error> for ReportError {
+    fn from(e: io::Error) -> Self {
+        ReportError::Io(e)
+    }
+}
+
+/// Displays an optional value as JSON
+struct Json<'a, T>(&'a Option<T>);
+impl<T: fmt::Display> fmt::Display for Json<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(x) => write!(f, "{x}"),
+            None    => write!(f, "null"),
+        }
+    }
+}
+/// Displays a text as a JSON string
+struct Quoted<'a>(&'a str);
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"'  => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                c    => write!(f, "{c}")?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+/// The value of one field of a report
+#[derive(Debug, Clone, PartialEq)]
+enum Scalar {
+    Null,
+    Bool(bool),
+    Number(String),
+    Text(String),
+}
+impl Scalar {
+    fn integer<T: std::str::FromStr>(&self, key: &str) -> Result<Option<T>, ReportError> {
+        match self {
+            Scalar::Null => Ok(None),
+            Scalar::Number(n) => n.parse().map(Some).map_err(|_| invalid(key, "an integer")),
+            _ => Err(invalid(key, "an integer")),
+        }
+    }
+    fn float(&self, key: &str) -> Result<Option<f64>, ReportError> {
+        match self {
+            Scalar::Null => Ok(None),
+            Scalar::Number(n) => n.parse().map(Some).map_err(|_| invalid(key, "a number")),
+            _ => Err(invalid(key, "a number")),
+        }
+    }
+    fn boolean(&self, key: &str) -> Result<bool, ReportError> {
+        match self {
+            Scalar::Bool(b) => Ok(*b),
+            _ => Err(invalid(key, "a boolean")),
+        }
+    }
+    fn text(&self, key: &str) -> Result<Option<String>, ReportError> {
+        match self {
+            Scalar::Null => Ok(None),
+            Scalar::Text(t) => Ok(Some(t.clone())),
+            _ => Err(invalid(key, "a string")),
+        }
+    }
+}
+fn invalid(key: &str, expected: &str) -> ReportError {
+    ReportError::Parse(format!("field '{key}' must be {expected} or null"))
+}
+
+/// Parses a JSON object whose fields are all scalars (which is what a report
+/// is made of)
+fn parse_object(text: &str) -> Result<Vec<(String, Scalar)>, ReportError> {
+    let mut chars = text.chars().peekable();
+    let mut fields = vec![];
+    expect(&mut chars, '{')?;
+    if skip_whitespace(&mut chars) == Some('}') {
+        chars.next();
+    } else {
+        loop {
+            expect(&mut chars, '"')?;
+            let key = parse_string(&mut chars)?;
+            expect(&mut chars, ':')?;
+            let value = parse_scalar(&mut chars)?;
+            fields.push((key, value));
+            match skip_whitespace(&mut chars) {
+                Some(',') => { chars.next(); },
+                Some('}') => { chars.next(); break; },
+                _ => return Err(ReportError::Parse("expected ',' or '}'".to_string())),
+            }
+        }
+    }
+    if skip_whitespace(&mut chars).is_some() {
+        return Err(ReportError::Parse("trailing characters after the report".to_string()));
+    }
+    Ok(fields)
+}
+fn skip_whitespace(chars: &mut Peekable<Chars>) -> Option<char> {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    chars.peek().copied()
+}
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Result<(), ReportError> {
+    if skip_whitespace(chars) == Some(expected) {
+        chars.next();
+        Ok(())
+    } else {
+        Err(ReportError::Parse(format!("expected '{expected}'")))
+    }
+}
+/// Parses the rest of a string (whose opening quote was consumed)
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, ReportError> {
+    let unterminated = || ReportError::Parse("unterminated string".to_string());
+    let mut text = String::new();
+    loop {
+        match chars.next().ok_or_else(unterminated)? {
+            '"'  => return Ok(text),
+            '\\' => match chars.next().ok_or_else(unterminated)? {
+                'n' => text.push('\n'),
+                't' => text.push('\t'),
+                'r' => text.push('\r'),
+                'b' => text.push('\u{8}'),
+                'f' => text.push('\u{c}'),
+                'u' => {
+                    let hex = chars.by_ref().take(4).collect::<String>();
+                    let c = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                        .ok_or_else(|| ReportError::Parse(format!("invalid escape '\\u{hex}'")))?;
+                    text.push(c);
+                },
+                c => text.push(c),
+            },
+            c => text.push(c),
+        }
+    }
+}
+fn parse_scalar(chars: &mut Peekable<Chars>) -> Result<Scalar, ReportError> {
+    match skip_whitespace(chars) {
+        Some('"') => {
+            chars.next();
+            Ok(Scalar::Text(parse_string(chars)?))
+        },
+        Some(c) if c == '-' || c.is_ascii_digit() => {
+            let mut number = String::new();
+            while let Some(c) = chars.next_if(|c| matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E')) {
+                number.push(c);
+            }
+            Ok(Scalar::Number(number))
+        },
+        Some(c) if c.is_ascii_alphabetic() => {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                word.push(c);
+            }
+            match word.as_str() {
+                "null"  => Ok(Scalar::Null),
+                "true"  => Ok(Scalar::Bool(true)),
+                "false" => Ok(Scalar::Bool(false)),
+                _ => Err(ReportError::Parse(format!("unexpected '{word}'"))),
+            }
+        },
+        _ => Err(ReportError::Parse("expected a scalar value".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod test_report {
+    use crate::*;
+
+    fn report(value: Option<isize>, proved: bool, explored: Option<usize>) -> RunReport {
+        RunReport { value, proved, upper_bound: value, gap: Some(0.0), explored, termination: None }
+    }
+
+    #[test]
+    fn a_report_survives_a_round_trip_through_json() {
+        let mut original = report(Some(-42), false, Some(1234));
+        original.upper_bound = None;
+        original.gap = Some(0.25);
+        original.termination = Some("time\"out\\".to_string());
+
+        let parsed = RunReport::from_json(&original.to_json()).unwrap();
+        assert_eq!(original, parsed);
+    }
+    #[test]
+    fn the_unknown_fields_are_ignored_and_the_missing_ones_are_null() {
+        let parsed = RunReport::from_json(r#" { "proved" : true, "extra": "xA" , "value": 7 } "#).unwrap();
+        assert_eq!(RunReport { value: Some(7), proved: true, upper_bound: None, gap: None, explored: None, termination: None }, parsed);
+    }
+    #[test]
+    fn an_invalid_report_cannot_be_parsed() {
+        assert!(matches!(RunReport::from_json(r#"{"value": 7}"#), Err(ReportError::Parse(_))));
+        assert!(matches!(RunReport::from_json(r#"{"proved": 1}"#), Err(ReportError::Parse(_))));
+        assert!(matches!(RunReport::from_json(r#"{"proved": true"#), Err(ReportError::Parse(_))));
+        assert!(matches!(RunReport::from_json(r#"{"schema": 2, "proved": true}"#), Err(ReportError::Parse(_))));
+    }
+    #[test]
+    fn the_report_of_a_completion_tells_why_the_search_stopped() {
+        let completion = Completion::new(false, Some(10)).with_stop_reason(Reason::Timeout);
+        let report = RunReport::new(&completion, isize::MAX);
+        assert_eq!(Some(10), report.value);
+        assert!(!report.proved);
+        assert_eq!(None, report.upper_bound);
+        assert_eq!(Some("timeout".to_string()), report.termination);
+    }
+
+    #[test]
+    fn a_run_which_reproduces_the_previous_one_matches() {
+        let previous = report(Some(380), true, Some(100));
+        let current = report(Some(380), true, Some(120));
+        assert_eq!(Ok(()), current.check_matches(&previous, false));
+        // it is fine to prove a value which was not proved before
+        assert_eq!(Ok(()), current.check_matches(&report(Some(380), false, Some(100)), false));
+    }
+    #[test]
+    fn a_different_value_is_a_regression() {
+        let previous = report(Some(380), true, Some(100));
+        let current = report(Some(379), true, Some(100));
+        let regression = current.check_matches(&previous, false).unwrap_err();
+        assert_eq!(vec![Mismatch::Value], regression.mismatches);
+
+        let text = regression.to_string();
+        assert!(text.contains("the value differs"));
+        assert!(text.contains("380"));
+        assert!(text.contains("379"));
+    }
+    #[test]
+    fn losing_the_optimality_proof_is_a_regression() {
+        let previous = report(Some(380), true, Some(100));
+        let mut current = report(Some(380), false, Some(100));
+        current.termination = Some("timeout".to_string());
+        let regression = current.check_matches(&previous, false).unwrap_err();
+        assert_eq!(vec![Mismatch::Proof], regression.mismatches);
+
+        let text = regression.to_string();
+        assert!(text.contains("the optimality proof was lost"));
+        assert!(text.contains("completed"));
+        assert!(text.contains("timeout"));
+    }
+    #[test]
+    fn the_explored_nodes_are_only_compared_when_strict() {
+        let previous = report(Some(380), true, Some(100));
+        let current = report(Some(380), true, Some(101));
+        assert_eq!(Ok(()), current.check_matches(&previous, false));
+        let regression = current.check_matches(&previous, true).unwrap_err();
+        assert_eq!(vec![Mismatch::Explored], regression.mismatches);
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides a tree export which rebuilds the explored search tree
+//! in memory so that it can be navigated once the search is over.
+
+use crate::{PruneReason, TreeEvent, TreeExport};
+
+/// What eventually happened to a node of the search tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeStatus {
+    /// The node has neither been explored nor pruned (yet)
+    Open,
+    /// The relaxed DD compiled for the node was not exact: its cut-set nodes
+    /// became the children of the node
+    Branched,
+    /// The node was closed without being explored
+    Pruned(PruneReason),
+    /// The node was closed by an exact DD, which found the given best value
+    /// (None when the node is infeasible)
+    Fathomed(Option<isize>),
+}
+
+/// A node of the search tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeNode {
+    /// The id of this node
+    pub id: usize,
+    /// The id of the parent of this node (None for a root)
+    pub parent: Option<usize>,
+    /// The ids of the children of this node
+    pub children: Vec<usize>,
+    /// The depth of the node
+    pub depth: usize,
+    /// The value of the longest path from the root of the problem
+    pub value: isize,
+    /// The upper bound of the node when it was created
+    pub ub: isize,
+    /// What happened to this node
+    pub status: NodeStatus,
+}
+
+/// This tree export rebuilds the search tree from its events. The events must
+/// be those of a solver: the ids of the nodes are consecutive and the nodes are
+/// created before anything happens to them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchTree {
+    /// All the nodes of the tree, indexed by their id
+    nodes: Vec<TreeNode>,
+    /// The successive best bounds (lb, ub) of the problem
+    bounds: Vec<(isize, isize)>,
+}
+
+impl SearchTree {
+    /// Creates an empty tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the number of nodes in the tree
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    /// Returns true iff the tree has no node
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// Returns all the nodes of the tree, indexed by their id
+    pub fn nodes(&self) -> &[TreeNode] {
+        &self.nodes
+    }
+    /// Returns the node having the given id
+    pub fn node(&self, id: usize) -> Option<&TreeNode> {
+        self.nodes.get(id)
+    }
+    /// Returns the parent of the given node
+    pub fn parent(&self, id: usize) -> Option<&TreeNode> {
+        self.node(id)?.parent.and_then(|p| self.node(p))
+    }
+    /// Iterates over the children of the given node
+    pub fn children(&self, id: usize) -> impl Iterator<Item = &TreeNode> {
+        self.node(id).into_iter().flat_map(|n| n.children.iter()).filter_map(move |c| self.node(*c))
+    }
+    /// Iterates over the nodes which have no parent
+    pub fn roots(&self) -> impl Iterator<Item = &TreeNode> {
+        self.nodes.iter().filter(|n| n.parent.is_none())
+    }
+    /// Returns the successive best bounds (lb, ub) of the problem
+    pub fn bounds(&self) -> &[(isize, isize)] {
+        &self.bounds
+    }
+}
+
+impl TreeExport for SearchTree {
+    fn export(&mut self, event: &TreeEvent) {
+        match *event {
+            TreeEvent::Created { node, parent, depth, value, ub } => {
+                debug_assert_eq!(node, self.nodes.len(), "the node ids must be consecutive");
+                if let Some(p) = parent.and_then(|p| self.nodes.get_mut(p)) {
+                    p.children.push(node);
+                    if p.status == NodeStatus::Open {
+                        p.status = NodeStatus::Branched;
+                    }
+                }
+                self.nodes.push(TreeNode { id: node, parent, children: vec![], depth, value, ub, status: NodeStatus::Open });
+            },
+            TreeEvent::Pruned { node, reason } => {
+                if let Some(n) = self.nodes.get_mut(node) {
+                    n.status = NodeStatus::Pruned(reason);
+                }
+            },
+            TreeEvent::Fathomed { node, value } => {
+                if let Some(n) = self.nodes.get_mut(node) {
+                    n.status = NodeStatus::Fathomed(value);
+                }
+            },
+            TreeEvent::Bounds { lb, ub } => self.bounds.push((lb, ub)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_search_tree {
+    use crate::*;
+
+    #[test]
+    fn the_tree_is_rebuilt_from_its_events() {
+        let mut tree = SearchTree::new();
+        assert!(tree.is_empty());
+        for event in [
+            TreeEvent::Created { node: 0, parent: None, depth: 0, value: 0, ub: 100 },
+            TreeEvent::Created { node: 1, parent: Some(0), depth: 2, value: 10, ub: 90 },
+            TreeEvent::Created { node: 2, parent: Some(0), depth: 2, value: 20, ub: 40 },
+            TreeEvent::Pruned { node: 2, reason: PruneReason::Bound },
+            TreeEvent::Fathomed { node: 1, value: Some(80) },
+            TreeEvent::Bounds { lb: 80, ub: 80 },
+        ] {
+            tree.export(&event);
+        }
+
+        assert_eq!(3, tree.len());
+        assert_eq!(vec![0], tree.roots().map(|n| n.id).collect::<Vec<_>>());
+        assert_eq!(vec![1, 2], tree.children(0).map(|n| n.id).collect::<Vec<_>>());
+        assert_eq!(Some(0), tree.parent(2).map(|n| n.id));
+        assert!(tree.parent(0).is_none());
+        assert_eq!(NodeStatus::Branched, tree.node(0).unwrap().status);
+        assert_eq!(NodeStatus::Fathomed(Some(80)), tree.node(1).unwrap().status);
+        assert_eq!(NodeStatus::Pruned(PruneReason::Bound), tree.node(2).unwrap().status);
+        assert_eq!(&[(80, 80)], tree.bounds());
+    }
+    #[test]
+    fn unknown_nodes_are_ignored() {
+        let mut tree = SearchTree::new();
+        tree.export(&TreeEvent::Pruned { node: 3, reason: PruneReason::Cache });
+        tree.export(&TreeEvent::Fathomed { node: 3, value: None });
+        assert!(tree.is_empty());
+        assert!(tree.node(3).is_none());
+        assert_eq!(0, tree.children(3).count());
+    }
+}
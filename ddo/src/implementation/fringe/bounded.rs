@@ -0,0 +1,205 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides a fringe wrapper which bounds the number of open
+//! nodes. It is what lets a search which would otherwise run out of memory
+//! degrade into a beam search over the subproblems.
+
+use std::cmp::Reverse;
+
+use crate::*;
+
+/// A fringe which holds at most `capacity` nodes. Whenever the solver is done
+/// pushing the nodes of a cut-set and the fringe holds more nodes than that,
+/// the nodes having the lowest upper bounds are dropped until only the best
+/// `retained` ones remain (this is 90% of the capacity unless told otherwise,
+/// so that the fringe is not reorganized after each node). The order of the
+/// wrapped fringe breaks the ties between nodes having the same upper bound.
+///
+/// The solvers keep track of the dropped nodes (see `Fringe::shed`): when 
+/// any of them could still improve on the best solution, the search is not 
+/// exact anymore. Its best value is then a heuristic one; yet the best upper
+/// bound of the solver remains valid since it accounts for the dropped nodes.
+///
+/// # Note
+/// Dropping the nodes requires to pop all of them from the wrapped fringe,
+/// which takes O(n log n) time. This is why the fringe is trimmed down to 
+/// `retained` nodes rather than to its capacity.
+#[derive(Debug, Clone)]
+pub struct BoundedFringe<F> {
+    /// The wrapped fringe
+    inner: F,
+    /// The maximum number of nodes in the fringe
+    capacity: usize,
+    /// The number of nodes which are kept when the capacity is exceeded
+    retained: usize,
+    /// The nodes which were dropped
+    shed: ShedNodes,
+}
+impl<F> BoundedFringe<F> {
+    /// Wraps the given fringe so that it holds at most `capacity` nodes
+    pub fn new(inner: F, capacity: usize) -> Self {
+        Self { inner, capacity, retained: capacity - capacity / 10, shed: ShedNodes::default() }
+    }
+    /// Sets the number of nodes which are kept when the capacity is exceeded 
+    /// (it cannot be more than the capacity)
+    pub fn with_retained(mut self, retained: usize) -> Self {
+        self.retained = retained.min(self.capacity);
+        self
+    }
+    /// Returns the maximum number of nodes in the fringe
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Returns the wrapped fringe
+    pub fn inner(&self) -> &F {
+        &self.inner
+    }
+    /// Unwraps the fringe
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+}
+
+impl<F: Fringe> Fringe for BoundedFringe<F> {
+    type State = F::State;
+
+    fn push(&mut self, node: SubProblem<Self::State>) {
+        self.inner.push(node)
+    }
+
+    fn pop(&mut self) -> Option<SubProblem<Self::State>> {
+        self.inner.pop()
+    }
+
+    fn peek(&self) -> Option<&SubProblem<Self::State>> {
+        self.inner.peek()
+    }
+
+    fn best_ub(&self) -> Option<isize> {
+        self.inner.best_ub()
+    }
+
+    fn clear(&mut self) {
+        // the dropped nodes no longer matter: whatever is pushed next covers
+        // the problem anew
+        self.shed.best_ub = None;
+        self.inner.clear()
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn estimated_bytes(&self) -> usize {
+        self.inner.estimated_bytes()
+    }
+
+    fn prune(&mut self, best_lb: isize, dropped: &mut dyn FnMut(usize)) {
+        self.inner.prune(best_lb, dropped)
+    }
+
+    fn inspect(&self, f: &mut dyn FnMut(&SubProblem<Self::State>)) {
+        self.inner.inspect(f)
+    }
+
+    fn shed_excess(&mut self, dropped: &mut dyn FnMut(usize)) {
+        if self.inner.len() <= self.capacity {
+            return;
+        }
+        let mut nodes = Vec::with_capacity(self.inner.len());
+        while let Some(node) = self.inner.pop() {
+            nodes.push(node);
+        }
+        nodes.sort_by_key(|node| Reverse(node.ub));
+        for node in nodes.drain(self.retained.min(nodes.len())..) {
+            self.shed.count  += 1;
+            self.shed.best_ub = self.shed.best_ub.max(Some(node.ub));
+            dropped(node.depth);
+        }
+        for node in nodes {
+            self.inner.push(node);
+        }
+    }
+
+    fn shed(&self) -> ShedNodes {
+        self.shed
+    }
+}
+
+#[cfg(test)]
+mod test_bounded_fringe {
+    use crate::*;
+    use std::sync::Arc;
+
+    fn sub(state: char, ub: isize, depth: usize) -> SubProblem<char> {
+        SubProblem { state: Arc::new(state), value: 0, ub, path: vec![], depth, priority_bonus: 0 }
+    }
+
+    #[test]
+    fn nothing_is_shed_while_the_capacity_is_not_exceeded() {
+        let mut fringe = BoundedFringe::new(StackFringe::new(), 3);
+        fringe.push(sub('a', 30, 0));
+        fringe.push(sub('b', 10, 1));
+        fringe.push(sub('c', 20, 1));
+        fringe.shed_excess(&mut |_| panic!("nothing should be shed"));
+        assert_eq!(3, fringe.len());
+        assert_eq!(ShedNodes::default(), fringe.shed());
+    }
+    #[test]
+    fn the_nodes_with_the_lowest_ub_are_shed() {
+        let mut fringe = BoundedFringe::new(StackFringe::new(), 3).with_retained(2);
+        fringe.push(sub('a', 30, 0));
+        fringe.push(sub('b', 10, 1));
+        fringe.push(sub('c', 20, 2));
+        fringe.push(sub('d', 40, 3));
+
+        let mut depths = vec![];
+        fringe.shed_excess(&mut |depth| depths.push(depth));
+        depths.sort_unstable();
+        assert_eq!(vec![1, 2], depths);
+        assert_eq!(ShedNodes { count: 2, best_ub: Some(20) }, fringe.shed());
+
+        let mut kept = std::iter::from_fn(|| fringe.pop()).map(|n| *n.state).collect::<Vec<_>>();
+        kept.sort_unstable();
+        assert_eq!(vec!['a', 'd'], kept);
+    }
+    #[test]
+    fn by_default_ninety_percent_of_the_capacity_is_retained() {
+        let mut fringe = BoundedFringe::new(StackFringe::new(), 20);
+        for i in 0..21 {
+            fringe.push(sub('x', i, 0));
+        }
+        fringe.shed_excess(&mut |_| {});
+        assert_eq!(18, fringe.len());
+        assert_eq!(ShedNodes { count: 3, best_ub: Some(2) }, fringe.shed());
+        assert_eq!(Some(20), fringe.best_ub());
+    }
+    #[test]
+    fn clearing_forgets_the_bound_but_not_the_count() {
+        let mut fringe = BoundedFringe::new(StackFringe::new(), 1).with_retained(1);
+        fringe.push(sub('a', 30, 0));
+        fringe.push(sub('b', 10, 1));
+        fringe.shed_excess(&mut |_| {});
+        assert_eq!(ShedNodes { count: 1, best_ub: Some(10) }, fringe.shed());
+        fringe.clear();
+        assert!(fringe.is_empty());
+        assert_eq!(ShedNodes { count: 1, best_ub: None }, fringe.shed());
+    }
+}
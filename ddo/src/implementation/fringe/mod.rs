@@ -21,6 +21,18 @@
 
 mod simple;
 mod no_duplicate;
+mod state_dedup;
+mod stack;
+mod bounded;
+mod ub_tracker;
+#[cfg(feature = "ooc")]
+mod ooc;
 
 pub use simple::*;
-pub use no_duplicate::*;
\ No newline at end of file
+pub use no_duplicate::*;
+pub use state_dedup::*;
+pub use stack::*;
+pub use bounded::*;
+pub(crate) use ub_tracker::*;
+#[cfg(feature = "ooc")]
+pub use ooc::*;
\ No newline at end of file
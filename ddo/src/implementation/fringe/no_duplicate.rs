@@ -66,6 +66,8 @@ where
     heap: Vec<NodeId>,
     /// The positions in the `nodes` vector that can be recycled.
     recycle_bin: Vec<NodeId>,
+    /// The upper bounds of the nodes (whatever the order of the ranking)
+    ubs: UbTracker,
 }
 
 impl<O> Fringe for NoDupFringe<O>
@@ -100,6 +102,9 @@ where
                 let new_ub = node.ub;
                 // make sure that ub is the max of the known ubs
                 node.ub = new_ub.max(old_ub);
+                // and that the node keeps the largest of the priority bonuses
+                let bonus = node.priority_bonus.max(self.nodes[id.0].priority_bonus);
+                node.priority_bonus = bonus;
 
                 let action = if self.cmp.compare(&node, &self.nodes[id.0]) == Greater {
                     BubbleUp(id)
@@ -113,10 +118,14 @@ where
                 if new_ub > old_ub {
                     self.nodes[id.0].ub = new_ub;
                 }
+                self.nodes[id.0].priority_bonus = bonus;
+                self.ubs.remove(old_ub);
+                self.ubs.insert(self.nodes[id.0].ub);
 
                 action
             }
             Vacant(e) => {
+                self.ubs.insert(node.ub);
                 let id = if self.recycle_bin.is_empty() {
                     let id = NodeId(self.nodes.len());
                     self.nodes.push(node);
@@ -159,10 +168,22 @@ where
 
         let node = self.nodes[id.0].clone();
         self.states.remove(&node.state);
+        self.ubs.remove(node.ub);
 
         Some(node)
     }
 
+    /// Returns the best node of the heap (the one that would be popped next)
+    /// without removing it from the heap.
+    fn peek(&self) -> Option<&SubProblem<Self::State>> {
+        self.heap.first().map(|id| &self.nodes[id.0])
+    }
+
+    /// Returns the best upper bound of the nodes of the heap
+    fn best_ub(&self) -> Option<isize> {
+        self.ubs.best()
+    }
+
     /// Clears the content of the heap to reset it to a state equivalent to
     /// a fresh instantiation of the heap.
     fn clear(&mut self) {
@@ -171,6 +192,7 @@ where
         self.pos.clear();
         self.heap.clear();
         self.recycle_bin.clear();
+        self.ubs.clear();
     }
 
     /// Returns the 'length' of the heap. That is, the number of items that
@@ -178,6 +200,11 @@ where
     fn len(&self) -> usize {
         self.heap.len()
     }
+
+    /// Visits the nodes of the heap (in heap order)
+    fn inspect(&self, f: &mut dyn FnMut(&SubProblem<Self::State>)) {
+        self.heap.iter().for_each(|id| f(&self.nodes[id.0]))
+    }
 }
 
 impl<O> NoDupFringe<O>
@@ -189,6 +216,7 @@ where
     /// comparison criterion.
     pub fn new(ranking: O) -> Self {
         Self {
+            ubs: UbTracker::default(),
             cmp: CompareSubProblem::new(ranking),
             states: Default::default(),
             nodes: vec![],
@@ -361,6 +389,7 @@ mod test_no_dup_fringe {
             path : vec![],
             ub   : 0,
             depth: 0,
+            priority_bonus: 0,
         });
         assert_eq!(fringe.len(), 1);
         fringe.push(SubProblem{
@@ -369,6 +398,7 @@ mod test_no_dup_fringe {
             path : vec![],
             ub: 0,
             depth: 0,
+            priority_bonus: 0,
         });
         assert_eq!(fringe.len(), 2);
     }
@@ -382,6 +412,7 @@ mod test_no_dup_fringe {
             path : vec![],
             ub   : 0,
             depth: 0,
+            priority_bonus: 0,
         });
         assert_eq!(fringe.len(), 1);
         fringe.push(SubProblem {
@@ -390,6 +421,7 @@ mod test_no_dup_fringe {
             path : vec![],
             ub   : 5,
             depth: 0,
+            priority_bonus: 0,
         });
         assert_eq!(fringe.len(), 1);
     }
@@ -419,6 +451,7 @@ mod test_no_dup_fringe {
             path : vec![],
             ub   : 1,
             depth: 0,
+            priority_bonus: 0,
         };
         let b = SubProblem {
             state: Arc::new(2),
@@ -426,6 +459,7 @@ mod test_no_dup_fringe {
             path : vec![],
             ub   : 2,
             depth: 0,
+            priority_bonus: 0,
         };
         let c = SubProblem {
             state: Arc::new(3),
@@ -433,6 +467,7 @@ mod test_no_dup_fringe {
             path : vec![],
             ub   : 3,
             depth: 0,
+            priority_bonus: 0,
         };
         let d = SubProblem {
             state: Arc::new(4),
@@ -440,6 +475,7 @@ mod test_no_dup_fringe {
             value: 4,
             ub: 4,
             depth: 0,
+            priority_bonus: 0,
         };
         let e = SubProblem{
             state: Arc::new(5),
@@ -447,6 +483,7 @@ mod test_no_dup_fringe {
             value: 4,
             ub: 5,
             depth: 0,
+            priority_bonus: 0,
         };
         let f = SubProblem{
             state: Arc::new(5),
@@ -454,6 +491,7 @@ mod test_no_dup_fringe {
             value: 5,
             ub: 5,
             depth: 0,
+            priority_bonus: 0,
         };
 
         fringe.push(a.clone());
@@ -489,6 +527,7 @@ mod test_no_dup_fringe {
             value: 4,
             ub: 5,
             depth: 1,
+            priority_bonus: 0,
         };
         let nf = SubProblem{
             state: Arc::new(5),
@@ -496,6 +535,7 @@ mod test_no_dup_fringe {
             value: 5,
             ub: 5,
             depth: 1,
+            priority_bonus: 0,
         };
 
         let mut fringe = empty_fringe();
@@ -546,6 +586,7 @@ mod test_no_dup_fringe {
             value: 0,
             ub: 0,
             depth: 0,
+            priority_bonus: 0,
         });
         fringe
     }
@@ -574,6 +615,32 @@ mod test_no_dup_fringe {
         assert!(heap.is_empty());
     }
     #[test]
+    fn peek_yields_the_node_that_would_be_popped_without_removing_it() {
+        let nodes = [
+            fnode(1, 10, 100),
+            fnode(2, 10, 104),
+            fnode(3, 10, 102),
+        ];
+
+        let mut heap = empty_fringe();
+        assert!(heap.peek().is_none());
+        push_all(&mut heap, &nodes);
+        assert_eq!(2, *heap.peek().unwrap().state);
+        assert_eq!(3, heap.len());
+        assert_eq!(2, *heap.pop().unwrap().state);
+        assert_eq!(3, *heap.peek().unwrap().state);
+    }
+    #[test]
+    fn peek_reflects_the_improved_ub_of_a_duplicate_node() {
+        let mut heap = empty_fringe();
+        push_all(&mut heap, &[fnode(1, 10, 100), fnode(2, 10, 101)]);
+        assert_eq!(101, heap.peek().unwrap().ub);
+        heap.push(fnode(1, 5, 110));
+        assert_eq!(1,   *heap.peek().unwrap().state);
+        assert_eq!(110, heap.peek().unwrap().ub);
+        assert_eq!(10,  heap.peek().unwrap().value);
+    }
+    #[test]
     fn pushing_same_node_multiple_times_does_not_alter_pop_order() {
         let nodes = [
             fnode(1, 10, 100),
@@ -659,6 +726,7 @@ mod test_no_dup_fringe {
             value,
             ub,
             depth: 0,
+            priority_bonus: 0,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,521 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//! This module provides the implementation of an out-of-core solver fringe.
+//! That is, a fringe which spills the nodes that do not fit in its in-memory
+//! working set to a file on disk.
+
+use std::cmp::Ordering;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use binary_heap_plus::BinaryHeap;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::*;
+
+/// The parameters of an out-of-core fringe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OocConfig {
+    /// The maximum number of nodes which are kept in memory. Whenever that
+    /// limit is exceeded, the least promising half of these nodes is spilled
+    /// to disk as one sorted run.
+    pub working_set: usize,
+    /// The directory where the spill file is created
+    pub dir: PathBuf,
+    /// The number of runs which must have been spilled before the runs are
+    /// compacted (see `Fringe::prune`)
+    pub compact_every: usize,
+}
+impl Default for OocConfig {
+    fn default() -> Self {
+        Self { working_set: 1 << 20, dir: std::env::temp_dir(), compact_every: 8 }
+    }
+}
+
+/// The volume of data that has been exchanged between an out-of-core fringe
+/// and its spill file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OocStats {
+    /// The number of nodes written to disk (compactions included)
+    pub spilled_nodes: usize,
+    /// The number of bytes written to disk (compactions included)
+    pub spilled_bytes: usize,
+    /// The number of nodes read back from disk (compactions included)
+    pub reloaded_nodes: usize,
+    /// The number of bytes read back from disk (compactions included)
+    pub reloaded_bytes: usize,
+    /// The number of compactions of the spill file
+    pub compactions: usize,
+    /// The number of nodes dropped during compactions
+    pub dropped_nodes: usize,
+}
+
+/// A sorted sequence of nodes which have been spilled to disk. The head of
+/// the run (its most promising node) is always kept in memory.
+struct Run<T> {
+    /// The most promising node of the run
+    head: SubProblem<T>,
+    /// The position of the next node of the run in the spill file
+    cursor: u64,
+    /// The number of nodes of the run which are still on disk
+    remaining: usize,
+}
+
+/// How a node is written to the spill file
+#[derive(Serialize)]
+struct SpilledNode<'a, T> {
+    state: &'a T,
+    value: isize,
+    path: &'a [Decision],
+    ub: isize,
+    depth: usize,
+    priority_bonus: u8,
+}
+/// How a node is read back from the spill file
+#[derive(Deserialize)]
+struct ReloadedNode<T> {
+    state: T,
+    value: isize,
+    path: Vec<Decision>,
+    ub: isize,
+    depth: usize,
+    priority_bonus: u8,
+}
+
+/// Used to give a distinct name to the spill file of each fringe
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// An out-of-core fringe (feature `ooc`): it keeps at most `working_set` nodes 
+/// in a binary heap and spills the remaining ones to an append-only file in 
+/// the form of sorted runs. The nodes are popped from a merge of the in-memory 
+/// heap and of the heads of all runs, so that the fringe order is preserved.
+/// 
+/// # Note
+/// The spill file is only ever appended to, even when the nodes of a run are
+/// reloaded. It is periodically compacted (when the solver calls `prune`): all
+/// runs are then merged into one while the nodes which can no longer improve
+/// the best solution are dropped.
+///
+/// # Panics
+/// Because the `Fringe` operations are infallible, any I/O error occurring 
+/// after the spill file was created makes the fringe panic.
+pub struct OocFringe<O: SubProblemRanking> {
+    order: O,
+    config: OocConfig,
+    heap: BinaryHeap<SubProblem<O::State>, CompareSubProblem<O>>,
+    runs: Vec<Run<O::State>>,
+    file: File,
+    path: PathBuf,
+    /// The size of the spill file
+    end: u64,
+    /// The number of runs spilled since the last compaction
+    pending: usize,
+    /// The upper bounds of the nodes, be they in memory or on disk
+    ubs: UbTracker,
+    stats: OocStats,
+}
+impl <O> OocFringe<O> 
+where O: SubProblemRanking + Clone,
+      O::State: Serialize + DeserializeOwned,
+{
+    /// Creates a new out-of-core fringe which uses a custom fringe order. This
+    /// fails when the spill file cannot be created in `config.dir`.
+    pub fn new(o: O, config: OocConfig) -> io::Result<Self> {
+        let path = Self::spill_file(&config);
+        let file = Self::create(&path)?;
+        Ok(Self {
+            heap: BinaryHeap::from_vec_cmp(vec![], CompareSubProblem::new(o.clone())),
+            order: o,
+            config,
+            runs: vec![],
+            file,
+            path,
+            end: 0,
+            pending: 0,
+            ubs: UbTracker::default(),
+            stats: OocStats::default(),
+        })
+    }
+    /// Returns the volume of data exchanged with the spill file so far
+    pub fn stats(&self) -> OocStats {
+        self.stats
+    }
+    /// Returns the number of runs which currently live in the spill file
+    pub fn nb_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    fn spill_file(config: &OocConfig) -> PathBuf {
+        let id = SPILL_FILES.fetch_add(1, AtomicOrdering::Relaxed);
+        config.dir.join(format!("ddo-fringe-{}-{id}.bin", std::process::id()))
+    }
+    fn create(path: &PathBuf) -> io::Result<File> {
+        OpenOptions::new().read(true).write(true).create_new(true).open(path)
+    }
+    /// Returns the index of the run having the most promising head
+    fn best_run(&self) -> Option<usize> {
+        (0..self.runs.len()).max_by(|a, b| self.order.compare(&self.runs[*a].head, &self.runs[*b].head))
+    }
+    /// Returns true iff the next node to pop is the head of the given run
+    fn run_goes_first(&self, run: Option<usize>) -> bool {
+        match (run, self.heap.peek()) {
+            (None, _)          => false,
+            (Some(_), None)    => true,
+            (Some(r), Some(n)) => self.order.compare(&self.runs[r].head, n) == Ordering::Greater,
+        }
+    }
+    /// Moves the least promising half of the in-memory nodes to a new run
+    fn spill(&mut self) {
+        let keep = self.config.working_set / 2;
+        let mut kept = Vec::with_capacity(keep);
+        while kept.len() < keep {
+            if let Some(node) = self.heap.pop() { kept.push(node) } else { break }
+        }
+        if let Some(head) = self.heap.pop() {
+            let mut rest = vec![];
+            while let Some(node) = self.heap.pop() {
+                rest.push(node);
+            }
+            let cursor = self.end;
+            let written = Self::write(&mut self.file, &mut self.stats, cursor, rest.iter())
+                .expect("out-of-core fringe: cannot write to the spill file");
+            self.end += written;
+            self.runs.push(Run { head, cursor, remaining: rest.len() });
+            self.pending += 1;
+        }
+        for node in kept {
+            self.heap.push(node);
+        }
+    }
+    /// Writes the given nodes at position `at` of the file and returns the
+    /// number of bytes written
+    fn write<'x>(file: &mut File, stats: &mut OocStats, at: u64, nodes: impl Iterator<Item = &'x SubProblem<O::State>>) -> io::Result<u64> 
+    where O::State: 'x
+    {
+        file.seek(SeekFrom::Start(at))?;
+        let mut out = BufWriter::new(file);
+        let mut written = 0;
+        for node in nodes {
+            let spilled = SpilledNode { 
+                state: node.state.as_ref(), 
+                value: node.value, 
+                path: &node.path, 
+                ub: node.ub, 
+                depth: node.depth,
+                priority_bonus: node.priority_bonus,
+            };
+            let bytes = bincode::serialize(&spilled).map_err(io::Error::other)?;
+            out.write_all(&(bytes.len() as u64).to_le_bytes())?;
+            out.write_all(&bytes)?;
+            written += 8 + bytes.len() as u64;
+            stats.spilled_nodes += 1;
+            stats.spilled_bytes += 8 + bytes.len();
+        }
+        out.flush()?;
+        Ok(written)
+    }
+    /// Reads the node at position `at` of the file and returns it along with 
+    /// the position of the next node
+    fn read(file: &mut File, stats: &mut OocStats, at: u64) -> io::Result<(SubProblem<O::State>, u64)> {
+        file.seek(SeekFrom::Start(at))?;
+        let mut len = [0_u8; 8];
+        file.read_exact(&mut len)?;
+        let len = u64::from_le_bytes(len);
+        let mut bytes = vec![0_u8; len as usize];
+        file.read_exact(&mut bytes)?;
+        let node: ReloadedNode<O::State> = bincode::deserialize(&bytes).map_err(io::Error::other)?;
+        stats.reloaded_nodes += 1;
+        stats.reloaded_bytes += 8 + bytes.len();
+        let node = SubProblem { 
+            state: Arc::new(node.state), 
+            value: node.value, 
+            path: node.path, 
+            ub: node.ub, 
+            depth: node.depth,
+            priority_bonus: node.priority_bonus,
+        };
+        Ok((node, at + 8 + len))
+    }
+    /// Pops the head of the given run and reloads the next one from disk
+    fn pop_run(&mut self, r: usize) -> SubProblem<O::State> {
+        let run = &mut self.runs[r];
+        if run.remaining == 0 {
+            return self.runs.swap_remove(r).head;
+        }
+        let (next, cursor) = Self::read(&mut self.file, &mut self.stats, run.cursor)
+            .expect("out-of-core fringe: cannot read from the spill file");
+        run.cursor = cursor;
+        run.remaining -= 1;
+        std::mem::replace(&mut run.head, next)
+    }
+    /// Merges all runs into one single run written to a fresh spill file and
+    /// drops the nodes whose ub is no greater than `best_lb`
+    fn compact(&mut self, best_lb: isize, dropped: &mut dyn FnMut(usize)) -> io::Result<()> {
+        let path = Self::spill_file(&self.config);
+        let mut file = Self::create(&path)?;
+        let mut head = None;
+        let mut rest = vec![];
+        let mut end = 0;
+        let mut remaining = 0;
+        while let Some(r) = self.best_run() {
+            let node = self.pop_run(r);
+            if node.ub <= best_lb {
+                self.ubs.remove(node.ub);
+                self.stats.dropped_nodes += 1;
+                dropped(node.depth);
+            } else if head.is_none() {
+                head = Some(node);
+            } else {
+                rest.push(node);
+                // nodes are written in batches to bound the memory usage
+                if rest.len() >= self.config.working_set.max(1) {
+                    end += Self::write(&mut file, &mut self.stats, end, rest.iter())?;
+                    remaining += rest.len();
+                    rest.clear();
+                }
+            }
+        }
+        end += Self::write(&mut file, &mut self.stats, end, rest.iter())?;
+        remaining += rest.len();
+
+        std::fs::remove_file(&self.path)?;
+        self.file = file;
+        self.path = path;
+        self.end = end;
+        self.pending = 0;
+        self.stats.compactions += 1;
+        if let Some(head) = head {
+            self.runs.push(Run { head, cursor: 0, remaining });
+        }
+        Ok(())
+    }
+}
+impl <O> Fringe for OocFringe<O> 
+where O: SubProblemRanking + Clone,
+      O::State: Serialize + DeserializeOwned,
+{
+    type State = O::State;
+
+    fn push(&mut self, node: SubProblem<Self::State>) {
+        self.ubs.insert(node.ub);
+        self.heap.push(node);
+        if self.heap.len() > self.config.working_set {
+            self.spill();
+        }
+    }
+
+    fn pop(&mut self) -> Option<SubProblem<Self::State>> {
+        let run = self.best_run();
+        let node = if self.run_goes_first(run) {
+            run.map(|r| self.pop_run(r))
+        } else {
+            self.heap.pop()
+        };
+        if let Some(node) = node.as_ref() {
+            self.ubs.remove(node.ub);
+        }
+        node
+    }
+
+    fn peek(&self) -> Option<&SubProblem<Self::State>> {
+        let run = self.best_run();
+        if self.run_goes_first(run) {
+            run.map(|r| &self.runs[r].head)
+        } else {
+            self.heap.peek()
+        }
+    }
+
+    fn best_ub(&self) -> Option<isize> {
+        self.ubs.best()
+    }
+
+    fn clear(&mut self) {
+        self.ubs.clear();
+        self.heap.clear();
+        self.runs.clear();
+        self.end = 0;
+        self.pending = 0;
+        self.file.set_len(0).expect("out-of-core fringe: cannot truncate the spill file");
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len() + self.runs.iter().map(|r| 1 + r.remaining).sum::<usize>()
+    }
+
+    /// Only the spilled nodes are pruned, and only when at least 
+    /// `compact_every` runs have been spilled since the last compaction.
+    fn prune(&mut self, best_lb: isize, dropped: &mut dyn FnMut(usize)) {
+        if self.pending >= self.config.compact_every && !self.runs.is_empty() {
+            self.compact(best_lb, dropped).expect("out-of-core fringe: cannot compact the spill file");
+        }
+    }
+
+    /// Only the nodes which are kept in memory are accounted for
+    fn estimated_bytes(&self) -> usize {
+        (self.heap.len() + self.runs.len()) * subproblem_bytes::<Self::State>()
+    }
+
+    /// Only the nodes which are kept in memory are visited: the heads of the
+    /// runs are, the nodes which remain on disk are not
+    fn inspect(&self, f: &mut dyn FnMut(&SubProblem<Self::State>)) {
+        self.heap.iter().for_each(&mut *f);
+        self.runs.iter().for_each(|r| f(&r.head));
+    }
+}
+impl <O: SubProblemRanking> Drop for OocFringe<O> {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod test_ooc_fringe {
+    use crate::*;
+    use std::{sync::Arc, cmp::Ordering};
+
+    /// A dummy state comparator for use in the tests
+    struct CharRanking;
+    impl StateRanking for CharRanking {
+        type State = char;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> Ordering {
+            a.cmp(b)
+        }
+    }
+
+    fn config(working_set: usize, compact_every: usize) -> OocConfig {
+        OocConfig { working_set, compact_every, ..OocConfig::default() }
+    }
+    fn node(ub: isize) -> SubProblem<char> {
+        let state = char::from(b'a' + (ub.unsigned_abs() % 26) as u8);
+        SubProblem { state: Arc::new(state), value: ub / 2, ub, path: vec![Decision { variable: Variable(0), value: ub }], depth: ub.unsigned_abs() % 5, priority_bonus: 0 }
+    }
+    /// A deterministic shuffle of 0..n
+    fn upper_bounds(n: isize) -> Vec<isize> {
+        (0..n).map(|i| (i * 37) % n).collect()
+    }
+
+    #[test]
+    fn it_pops_the_nodes_in_the_same_order_as_the_in_memory_fringe() {
+        let order = MaxUB::new(&CharRanking);
+        let mut ooc = OocFringe::new(order, config(4, 8)).unwrap();
+        let mut mem = SimpleFringe::new(order);
+
+        for (i, ub) in upper_bounds(101).into_iter().enumerate() {
+            ooc.push(node(ub));
+            mem.push(node(ub));
+            if i % 3 == 0 {
+                assert_eq!(mem.pop(), ooc.pop());
+            }
+            assert_eq!(mem.len(), ooc.len());
+            assert_eq!(mem.peek(), ooc.peek());
+        }
+        assert!(ooc.nb_runs() > 1);
+        while !mem.is_empty() {
+            assert_eq!(mem.pop(), ooc.pop());
+        }
+        assert!(ooc.is_empty());
+        assert_eq!(None, ooc.pop());
+    }
+    #[test]
+    fn the_stats_report_the_spilled_and_reloaded_volumes() {
+        let order = MaxUB::new(&CharRanking);
+        let mut ooc = OocFringe::new(order, config(2, 8)).unwrap();
+        assert_eq!(OocStats::default(), ooc.stats());
+
+        upper_bounds(10).into_iter().for_each(|ub| ooc.push(node(ub)));
+        let stats = ooc.stats();
+        assert!(stats.spilled_nodes > 0);
+        assert!(stats.spilled_bytes > 8 * stats.spilled_nodes);
+        assert_eq!(0, stats.reloaded_nodes);
+
+        while ooc.pop().is_some() {}
+        let stats = ooc.stats();
+        assert_eq!(stats.spilled_nodes, stats.reloaded_nodes);
+        assert_eq!(stats.spilled_bytes, stats.reloaded_bytes);
+    }
+    #[test]
+    fn pruning_drops_the_spilled_nodes_that_cannot_improve_the_best_solution() {
+        let order = MaxUB::new(&CharRanking);
+        let mut ooc = OocFringe::new(order, config(2, 1)).unwrap();
+        upper_bounds(20).into_iter().for_each(|ub| ooc.push(node(ub)));
+
+        let mut dropped = vec![];
+        ooc.prune(9, &mut |depth| dropped.push(depth));
+        let stats = ooc.stats();
+        assert_eq!(1, stats.compactions);
+        assert_eq!(1, ooc.nb_runs());
+        assert_eq!(dropped.len(), stats.dropped_nodes);
+        assert_eq!(20 - dropped.len(), ooc.len());
+        // at most two nodes (the working set) could escape the compaction
+        assert!(dropped.len() >= 8);
+
+        let mut popped = vec![];
+        while let Some(n) = ooc.pop() {
+            popped.push(n.ub);
+        }
+        assert!(popped.windows(2).all(|w| w[0] > w[1]));
+        assert_eq!((10..20).rev().collect::<Vec<_>>(), popped[..10]);
+    }
+    #[test]
+    fn pruning_waits_for_enough_runs_to_be_spilled() {
+        let order = MaxUB::new(&CharRanking);
+        let mut ooc = OocFringe::new(order, config(4, 100)).unwrap();
+        upper_bounds(20).into_iter().for_each(|ub| ooc.push(node(ub)));
+
+        ooc.prune(9, &mut |_| panic!("nothing should be dropped"));
+        assert_eq!(0, ooc.stats().compactions);
+        assert_eq!(20, ooc.len());
+    }
+    #[test]
+    fn clear_empties_the_fringe_and_the_spill_file() {
+        let order = MaxUB::new(&CharRanking);
+        let mut ooc = OocFringe::new(order, config(2, 8)).unwrap();
+        upper_bounds(10).into_iter().for_each(|ub| ooc.push(node(ub)));
+        ooc.clear();
+        assert!(ooc.is_empty());
+        assert_eq!(0, ooc.nb_runs());
+        assert_eq!(None, ooc.peek());
+
+        ooc.push(node(3));
+        assert_eq!(Some(node(3)), ooc.pop());
+    }
+    #[test]
+    fn the_spill_file_is_removed_when_the_fringe_is_dropped() {
+        let order = MaxUB::new(&CharRanking);
+        let dir = std::env::temp_dir().join(format!("ddo-ooc-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ooc = OocFringe::new(order, OocConfig { working_set: 2, dir: dir.clone(), compact_every: 8 }).unwrap();
+        assert_eq!(1, std::fs::read_dir(&dir).unwrap().count());
+        drop(ooc);
+        assert_eq!(0, std::fs::read_dir(&dir).unwrap().count());
+        std::fs::remove_dir(&dir).unwrap();
+    }
+    #[test]
+    fn it_fails_when_the_spill_file_cannot_be_created() {
+        let order = MaxUB::new(&CharRanking);
+        let dir = std::env::temp_dir().join("ddo-ooc-this-directory-does-not-exist");
+        assert!(OocFringe::new(order, OocConfig { dir, ..OocConfig::default() }).is_err());
+    }
+}
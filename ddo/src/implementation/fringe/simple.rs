@@ -33,29 +33,49 @@ use crate::*;
 /// `SimpleFringe`.
 /// 
 pub struct SimpleFringe<O: SubProblemRanking> {
-    heap: BinaryHeap<SubProblem<O::State>, CompareSubProblem<O>>
+    heap: BinaryHeap<SubProblem<O::State>, CompareSubProblem<O>>,
+    /// The upper bounds of the nodes (whatever the order of the ranking)
+    ubs: UbTracker,
 }
 impl <O> SimpleFringe<O> where O: SubProblemRanking {
     /// This creates a new simple fringe which uses a custom fringe order.
     pub fn new(o: O) -> Self {
-        Self{ heap: BinaryHeap::from_vec_cmp(vec![], CompareSubProblem::new(o)) }
+        Self{ heap: BinaryHeap::from_vec_cmp(vec![], CompareSubProblem::new(o)), ubs: UbTracker::default() }
     }
 }
 impl <O> Fringe for SimpleFringe<O> where O: SubProblemRanking {
     type State = O::State;
     
     fn push(&mut self, node: SubProblem<Self::State>) {
+        self.ubs.insert(node.ub);
         self.heap.push(node)
     }
 
     fn pop(&mut self) -> Option<SubProblem<Self::State>> {
-        self.heap.pop()
+        let node = self.heap.pop();
+        if let Some(node) = node.as_ref() {
+            self.ubs.remove(node.ub);
+        }
+        node
+    }
+
+    fn peek(&self) -> Option<&SubProblem<Self::State>> {
+        self.heap.peek()
+    }
+
+    fn best_ub(&self) -> Option<isize> {
+        self.ubs.best()
     }
 
     fn clear(&mut self) {
+        self.ubs.clear();
         self.heap.clear()
     }
 
+    fn inspect(&self, f: &mut dyn FnMut(&SubProblem<Self::State>)) {
+        self.heap.iter().for_each(f)
+    }
+
     fn len(&self) -> usize {
         self.heap.len()
     }
@@ -106,6 +126,7 @@ mod test_simple_fringe {
             ub   : 10,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
         assert_eq!(fringe.len(), 1);
         assert!(!fringe.is_empty());
@@ -122,6 +143,7 @@ mod test_simple_fringe {
             ub   : 10,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
         fringe.push(SubProblem {
             state: Arc::new('b'),
@@ -129,6 +151,7 @@ mod test_simple_fringe {
             ub   : 20,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
 
         assert_eq!(fringe.len(), 2);
@@ -144,6 +167,7 @@ mod test_simple_fringe {
             ub   : 10,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
         fringe.push(SubProblem {
             state: Arc::new('b'),
@@ -151,6 +175,7 @@ mod test_simple_fringe {
             ub   : 20,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
 
         assert_eq!(fringe.len(), 2);
@@ -179,6 +204,7 @@ mod test_simple_fringe {
             ub   : 1,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
         fringe.push(SubProblem {
             state: Arc::new('b'),
@@ -186,6 +212,7 @@ mod test_simple_fringe {
             ub   : 2,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
         fringe.push(SubProblem {
             state: Arc::new('c'),
@@ -193,6 +220,7 @@ mod test_simple_fringe {
             ub   : 3,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
         fringe.push(SubProblem {
             state: Arc::new('d'),
@@ -200,6 +228,7 @@ mod test_simple_fringe {
             ub   : 4,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
         fringe.push(SubProblem {
             state: Arc::new('e'),
@@ -207,6 +236,7 @@ mod test_simple_fringe {
             ub   : 5,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
         fringe.push(SubProblem {
             state: Arc::new('f'),
@@ -214,6 +244,7 @@ mod test_simple_fringe {
             ub   : 5,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
         
         assert_eq!(fringe.pop().unwrap().state.deref(), &'f');
@@ -223,6 +254,35 @@ mod test_simple_fringe {
         assert_eq!(fringe.pop().unwrap().state.deref(), &'b');
         assert_eq!(fringe.pop().unwrap().state.deref(), &'a');
     }
+    // when I peek at the fringe, I see the node that would be popped but it is not removed
+    #[test]
+    fn when_i_peek_at_the_fringe_i_see_the_next_node_but_it_is_not_removed() {
+        let order = MaxUB::new(&CharRanking); 
+        let mut fringe = SimpleFringe::new(order);
+        assert!(fringe.peek().is_none());
+        fringe.push(SubProblem {
+            state: Arc::new('a'),
+            value: 1,
+            ub   : 7,
+            path : vec![],
+            depth: 0,
+            priority_bonus: 0,
+        });
+        fringe.push(SubProblem {
+            state: Arc::new('b'),
+            value: 2,
+            ub   : 3,
+            path : vec![],
+            depth: 0,
+            priority_bonus: 0,
+        });
+        
+        assert_eq!(fringe.peek().unwrap().state.deref(), &'a');
+        assert_eq!(fringe.peek().unwrap().ub, 7);
+        assert_eq!(fringe.len(), 2);
+        assert_eq!(fringe.pop().unwrap().state.deref(), &'a');
+        assert_eq!(fringe.peek().unwrap().state.deref(), &'b');
+    }
     // when I clear an empty fringe, it remains empty
     #[test]
     fn when_i_clear_an_empty_fringe_it_remains_empty() {
@@ -243,10 +303,48 @@ mod test_simple_fringe {
             ub   : 5,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         });
 
         assert!(!fringe.is_empty());
         fringe.clear();
         assert!(fringe.is_empty());
     }
+    // the best ub does not depend on the order in which the nodes are popped
+    #[test]
+    fn the_best_ub_is_the_greatest_one_even_when_the_ranking_does_not_order_by_ub() {
+        /// Pops the deepest nodes first (without telling it does not order by ub)
+        struct DeepestFirst;
+        impl SubProblemRanking for DeepestFirst {
+            type State = char;
+
+            fn compare(&self, a: &SubProblem<char>, b: &SubProblem<char>) -> Ordering {
+                a.depth.cmp(&b.depth)
+            }
+        }
+        let mut fringe = SimpleFringe::new(DeepestFirst);
+        fringe.push(SubProblem {
+            state: Arc::new('a'),
+            value: 1,
+            ub   : 300,
+            path : vec![],
+            depth: 0,
+            priority_bonus: 0,
+        });
+        fringe.push(SubProblem {
+            state: Arc::new('b'),
+            value: 2,
+            ub   : 100,
+            path : vec![],
+            depth: 3,
+            priority_bonus: 0,
+        });
+
+        assert_eq!(fringe.peek().unwrap().state.deref(), &'b');
+        assert_eq!(Some(300), fringe.best_ub());
+        assert_eq!(fringe.pop().unwrap().state.deref(), &'b');
+        assert_eq!(Some(300), fringe.best_ub());
+        assert_eq!(fringe.pop().unwrap().state.deref(), &'a');
+        assert_eq!(None, fringe.best_ub());
+    }
 }
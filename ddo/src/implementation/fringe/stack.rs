@@ -0,0 +1,174 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the implementation of a depth-first solver fringe. 
+//! That is, a fringe which pops the subproblems in the reverse order of their
+//! insertion.
+
+use crate::*;
+
+/// The order in which the subproblems are explored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FringeOrder {
+    /// The most promising subproblem is explored first (see `MaxUB`). This is
+    /// the order which proves the optimum with the fewest nodes.
+    #[default]
+    BestFirst,
+    /// The subproblem which was pushed last is explored first (see 
+    /// `StackFringe`). This is the order which needs the least memory.
+    DepthFirst,
+}
+
+/// A fringe which pops the subproblem which was pushed last (LIFO). Because 
+/// the subproblems of the cut-set of a node are explored before those of its
+/// siblings, the size of the fringe remains proportional to the depth of the
+/// search tree (times the size of the cut-sets) rather than to its width. 
+/// This comes at the price of a weaker upper bound, which improves less often
+/// than with a best-first fringe.
+///
+/// # Note
+/// The subproblems are still discarded as soon as their upper bound no longer
+/// exceeds the best known lower bound (see `Fringe::prune`), so that only 
+/// the final optimum is affected by the order, not its value.
+#[derive(Debug, Clone)]
+pub struct StackFringe<T> {
+    stack: Vec<SubProblem<T>>,
+    /// The upper bounds of the nodes (the stack is not sorted by upper bound)
+    ubs: UbTracker,
+}
+impl<T> Default for StackFringe<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> StackFringe<T> {
+    /// Creates a new empty stack
+    pub fn new() -> Self {
+        Self { stack: vec![], ubs: UbTracker::default() }
+    }
+}
+
+impl<T> Fringe for StackFringe<T> {
+    type State = T;
+
+    fn push(&mut self, node: SubProblem<T>) {
+        self.ubs.insert(node.ub);
+        self.stack.push(node)
+    }
+
+    fn pop(&mut self) -> Option<SubProblem<T>> {
+        let node = self.stack.pop()?;
+        self.ubs.remove(node.ub);
+        Some(node)
+    }
+
+    fn peek(&self) -> Option<&SubProblem<T>> {
+        self.stack.last()
+    }
+
+    fn best_ub(&self) -> Option<isize> {
+        self.ubs.best()
+    }
+
+    fn clear(&mut self) {
+        self.ubs.clear();
+        self.stack.clear()
+    }
+
+    fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn prune(&mut self, best_lb: isize, dropped: &mut dyn FnMut(usize)) {
+        let ubs = &mut self.ubs;
+        self.stack.retain(|node| {
+            let keep = node.ub > best_lb;
+            if !keep {
+                ubs.remove(node.ub);
+                dropped(node.depth);
+            }
+            keep
+        })
+    }
+
+    fn inspect(&self, f: &mut dyn FnMut(&SubProblem<T>)) {
+        self.stack.iter().for_each(f)
+    }
+}
+
+#[cfg(test)]
+mod test_stack_fringe {
+    use crate::*;
+    use std::sync::Arc;
+
+    fn sub(state: char, ub: isize, depth: usize) -> SubProblem<char> {
+        SubProblem { state: Arc::new(state), value: 0, ub, path: vec![], depth, priority_bonus: 0 }
+    }
+
+    #[test]
+    fn by_default_it_is_empty() {
+        let fringe = StackFringe::<char>::default();
+        assert!(fringe.is_empty());
+        assert_eq!(None, fringe.peek());
+        assert_eq!(None, fringe.best_ub());
+    }
+    #[test]
+    fn the_last_pushed_node_is_popped_first() {
+        let mut fringe = StackFringe::new();
+        fringe.push(sub('a', 30, 0));
+        fringe.push(sub('b', 10, 1));
+        fringe.push(sub('c', 20, 1));
+        assert_eq!(3, fringe.len());
+        assert_eq!('c', *fringe.peek().unwrap().state);
+
+        let popped = std::iter::from_fn(|| fringe.pop()).map(|n| *n.state).collect::<String>();
+        assert_eq!("cba", popped);
+        assert!(fringe.is_empty());
+    }
+    #[test]
+    fn the_best_ub_is_the_one_of_any_node() {
+        let mut fringe = StackFringe::new();
+        fringe.push(sub('a', 30, 0));
+        fringe.push(sub('b', 10, 1));
+        assert_eq!(Some(30), fringe.best_ub());
+        fringe.push(sub('c', 40, 1));
+        assert_eq!(Some(40), fringe.best_ub());
+        fringe.pop();
+        fringe.pop();
+        assert_eq!(Some(30), fringe.best_ub());
+        fringe.clear();
+        assert_eq!(None, fringe.best_ub());
+    }
+    #[test]
+    fn prune_drops_the_nodes_which_cannot_improve_the_best_solution() {
+        let mut fringe = StackFringe::new();
+        fringe.push(sub('a', 30, 0));
+        fringe.push(sub('b', 10, 1));
+        fringe.push(sub('c', 20, 2));
+        fringe.push(sub('d', 40, 3));
+
+        let mut depths = vec![];
+        fringe.prune(20, &mut |depth| depths.push(depth));
+        assert_eq!(vec![1, 2], depths);
+        assert_eq!(2, fringe.len());
+        assert_eq!(Some(40), fringe.best_ub());
+        assert_eq!('d', *fringe.pop().unwrap().state);
+        assert_eq!('a', *fringe.pop().unwrap().state);
+    }
+}
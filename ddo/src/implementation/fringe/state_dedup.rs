@@ -0,0 +1,391 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the implementation of a solver fringe which lazily
+//! deduplicates the sub-problems having the same root state. Unlike the 
+//! `NoDupFringe` which updates the nodes in place, this fringe is a plain 
+//! binary heap: the nodes which are superseded stay in the heap and they are
+//! skipped when they are eventually popped.
+
+use std::{hash::Hash, sync::Arc};
+
+use binary_heap_plus::BinaryHeap;
+use fxhash::FxHashMap;
+
+use crate::*;
+
+/// The number of pushes and pops which were saved by a `DedupFringe`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DedupStats {
+    /// The number of pushed nodes which were merged with a queued node having
+    /// the same state (be it that the pushed node was dropped or that it 
+    /// superseded the queued one)
+    pub merged: usize,
+    /// The number of superseded nodes which were skipped when they reached 
+    /// the top of the heap
+    pub skipped: usize,
+}
+
+/// What is known about the queued nodes having a given state
+#[derive(Debug, Clone, Copy)]
+struct Tracked {
+    /// The length of the longest path to the state among the queued nodes
+    value: isize,
+    /// The tightest upper bound known for the node having that longest path
+    ub: isize,
+    /// The largest priority bonus of the nodes having the state
+    priority_bonus: u8,
+    /// True iff the node having the longest path is still queued
+    live: bool,
+    /// The number of nodes of the heap having the state (live or not)
+    copies: usize,
+}
+
+/// A fringe which only ever pops one node per state. When a node is pushed 
+/// while another one having the same state is queued, only the one with the
+/// longest path is kept. It is given the tightest of the upper bounds which 
+/// follow from both nodes (the state has the same completions regardless of
+/// the path leading to it) and the largest of their priority bonuses.
+///
+/// # Note
+/// The states are tracked in a map next to the binary heap. To bound its
+/// memory, that map holds at most `capacity` states: once it is full, the 
+/// nodes whose state is not tracked yet are queued without deduplication
+/// (as with a `SimpleFringe`) until the fringe is cleared.
+pub struct DedupFringe<O>
+where
+    O: SubProblemRanking,
+    O::State: Eq + Hash + Clone,
+{
+    heap: BinaryHeap<SubProblem<O::State>, CompareSubProblem<O>>,
+    /// The states of the queued nodes
+    states: FxHashMap<Arc<O::State>, Tracked>,
+    /// The maximum number of states which are tracked
+    capacity: usize,
+    /// True iff the map of states has been full since the last clear
+    saturated: bool,
+    /// The number of superseded nodes which are still in the heap
+    stale: usize,
+    /// The upper bounds of the nodes (whatever the order of the ranking)
+    ubs: UbTracker,
+    stats: DedupStats,
+}
+
+impl<O> DedupFringe<O>
+where
+    O: SubProblemRanking,
+    O::State: Eq + Hash + Clone,
+{
+    /// Creates a new fringe which uses a custom fringe order and tracks the
+    /// states of at most `capacity` nodes.
+    pub fn new(o: O, capacity: usize) -> Self {
+        Self {
+            ubs: UbTracker::default(),
+            heap: BinaryHeap::from_vec_cmp(vec![], CompareSubProblem::new(o)),
+            states: FxHashMap::default(),
+            capacity,
+            saturated: false,
+            stale: 0,
+            stats: DedupStats::default(),
+        }
+    }
+    /// Returns the number of pushes and pops which were saved so far
+    pub fn stats(&self) -> DedupStats {
+        self.stats
+    }
+
+    /// Returns true iff the given node has been superseded by a node having
+    /// the same state and a longer path
+    fn is_stale(&self, node: &SubProblem<O::State>) -> bool {
+        self.states.get(&node.state).is_some_and(|t| !t.live || t.value != node.value)
+    }
+    /// Forgets about one node of the heap having the given state
+    fn forget_copy(&mut self, state: &Arc<O::State>) -> Option<Tracked> {
+        let tracked = self.states.get_mut(state)?;
+        tracked.copies -= 1;
+        let tracked = *tracked;
+        if tracked.copies == 0 {
+            self.states.remove(state);
+        }
+        Some(tracked)
+    }
+    /// Pops the superseded nodes off the top of the heap, so that the node 
+    /// at the top of the heap is always one that can be popped
+    fn drop_stale_head(&mut self) {
+        while self.heap.peek().is_some_and(|head| self.is_stale(head)) {
+            if let Some(node) = self.heap.pop() {
+                self.ubs.remove(node.ub);
+                self.forget_copy(&node.state);
+                self.stale -= 1;
+                self.stats.skipped += 1;
+            }
+        }
+    }
+}
+
+/// Returns the upper bound of the `kept` node once it is tightened with the
+/// completion bound which follows from the `other` node having the same state
+/// (both nodes are given as a pair `(value, ub)`)
+fn tighten(kept: (isize, isize), other: (isize, isize)) -> isize {
+    let (value, ub) = kept;
+    ub.min(value.saturating_add(other.1.saturating_sub(other.0)))
+}
+
+impl<O> Fringe for DedupFringe<O>
+where
+    O: SubProblemRanking,
+    O::State: Eq + Hash + Clone,
+{
+    type State = O::State;
+
+    fn push(&mut self, node: SubProblem<O::State>) {
+        let dropped = match self.states.get_mut(&node.state) {
+            Some(t) if t.live && node.value <= t.value => {
+                t.ub = tighten((t.value, t.ub), (node.value, node.ub));
+                t.priority_bonus = t.priority_bonus.max(node.priority_bonus);
+                self.stats.merged += 1;
+                true
+            }
+            Some(t) => {
+                if t.live {
+                    t.ub = tighten((node.value, node.ub), (t.value, t.ub));
+                    t.priority_bonus = t.priority_bonus.max(node.priority_bonus);
+                    self.stale += 1;
+                    self.stats.merged += 1;
+                } else {
+                    t.ub = node.ub;
+                    t.priority_bonus = node.priority_bonus;
+                }
+                t.value = node.value;
+                t.live = true;
+                t.copies += 1;
+                false
+            }
+            None => {
+                self.saturated |= self.states.len() >= self.capacity;
+                if !self.saturated {
+                    self.states.insert(Arc::clone(&node.state), Tracked { 
+                        value: node.value, 
+                        ub: node.ub, 
+                        priority_bonus: node.priority_bonus, 
+                        live: true, 
+                        copies: 1 
+                    });
+                }
+                false
+            }
+        };
+        if !dropped {
+            self.ubs.insert(node.ub);
+            self.heap.push(node);
+            self.drop_stale_head();
+        }
+    }
+
+    fn pop(&mut self) -> Option<SubProblem<Self::State>> {
+        let mut node = self.heap.pop()?;
+        self.ubs.remove(node.ub);
+        if let Some(tracked) = self.forget_copy(&node.state) {
+            node.ub = tracked.ub;
+            node.priority_bonus = tracked.priority_bonus;
+            if let Some(t) = self.states.get_mut(&node.state) {
+                t.live = false;
+            }
+        }
+        self.drop_stale_head();
+        Some(node)
+    }
+
+    fn peek(&self) -> Option<&SubProblem<Self::State>> {
+        self.heap.peek()
+    }
+
+    fn best_ub(&self) -> Option<isize> {
+        self.ubs.best()
+    }
+
+    fn clear(&mut self) {
+        self.heap.clear();
+        self.states.clear();
+        self.saturated = false;
+        self.stale = 0;
+        self.ubs.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len() - self.stale
+    }
+
+    /// The superseded nodes also take room in the heap
+    fn estimated_bytes(&self) -> usize {
+        self.heap.len() * subproblem_bytes::<Self::State>()
+    }
+
+    /// Visits the nodes of the heap which have not been superseded
+    fn inspect(&self, f: &mut dyn FnMut(&SubProblem<Self::State>)) {
+        self.heap.iter()
+            .filter(|node| !self.is_stale(node))
+            .for_each(f)
+    }
+}
+
+#[cfg(test)]
+mod test_dedup_fringe {
+    use crate::*;
+    use std::{sync::Arc, cmp::Ordering};
+
+    /// A dummy state comparator for use in the tests
+    struct CharRanking;
+    impl StateRanking for CharRanking {
+        type State = char;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> Ordering {
+            a.cmp(b)
+        }
+    }
+
+    fn fringe(capacity: usize) -> DedupFringe<MaxUB<'static, CharRanking>> {
+        DedupFringe::new(MaxUB::new(&CharRanking), capacity)
+    }
+    fn sub(state: char, value: isize, ub: isize) -> SubProblem<char> {
+        SubProblem { state: Arc::new(state), value, ub, path: vec![], depth: 0, priority_bonus: 0 }
+    }
+
+    #[test]
+    fn by_default_it_is_empty() {
+        let fringe = fringe(16);
+        assert!(fringe.is_empty());
+        assert_eq!(0, fringe.len());
+        assert_eq!(DedupStats::default(), fringe.stats());
+    }
+    #[test]
+    fn nodes_having_distinct_states_are_all_popped() {
+        let mut fringe = fringe(16);
+        fringe.push(sub('a', 0, 10));
+        fringe.push(sub('b', 0, 30));
+        fringe.push(sub('c', 0, 20));
+        assert_eq!(3, fringe.len());
+
+        let popped = std::iter::from_fn(|| fringe.pop()).map(|n| *n.state).collect::<String>();
+        assert_eq!("bca", popped);
+        assert_eq!(DedupStats::default(), fringe.stats());
+    }
+    #[test]
+    fn a_node_with_a_shorter_path_is_merged_into_the_queued_one() {
+        let mut fringe = fringe(16);
+        fringe.push(sub('a', 10, 50));
+        // the completions of 'a' are worth at most 32
+        fringe.push(sub('a',  8, 40));
+        assert_eq!(1, fringe.len());
+
+        let node = fringe.pop().unwrap();
+        assert_eq!(10, node.value);
+        assert_eq!(42, node.ub);
+        assert!(fringe.is_empty());
+        assert_eq!(1, fringe.stats().merged);
+        assert_eq!(0, fringe.stats().skipped);
+    }
+    #[test]
+    fn a_node_with_a_longer_path_supersedes_the_queued_one() {
+        let mut fringe = fringe(16);
+        fringe.push(sub('a', 5, 100));
+        fringe.push(sub('b', 0,  50));
+        fringe.push(sub('a', 7,  20));
+        assert_eq!(2, fringe.len());
+        // the superseded node was at the top of the heap
+        assert_eq!(1, fringe.stats().skipped);
+        assert_eq!('b', *fringe.peek().unwrap().state);
+
+        assert_eq!('b', *fringe.pop().unwrap().state);
+        let node = fringe.pop().unwrap();
+        assert_eq!(('a', 7, 20), (*node.state, node.value, node.ub));
+        assert_eq!(None, fringe.pop());
+        assert_eq!(1, fringe.stats().merged);
+    }
+    #[test]
+    fn superseded_nodes_are_skipped_when_they_reach_the_top() {
+        let mut fringe = fringe(16);
+        fringe.push(sub('b', 0,  50));
+        fringe.push(sub('a', 5,  40));
+        fringe.push(sub('a', 7,  30));
+        assert_eq!(2, fringe.len());
+        assert_eq!(0, fringe.stats().skipped);
+
+        assert_eq!('b', *fringe.pop().unwrap().state);
+        assert_eq!(1, fringe.stats().skipped);
+        let node = fringe.pop().unwrap();
+        assert_eq!(('a', 7, 30), (*node.state, node.value, node.ub));
+        assert!(fringe.is_empty());
+    }
+    #[test]
+    fn a_state_can_be_pushed_again_once_it_has_been_popped() {
+        let mut fringe = fringe(16);
+        fringe.push(sub('a', 5, 40));
+        assert_eq!(5, fringe.pop().unwrap().value);
+        fringe.push(sub('a', 3, 40));
+        assert_eq!(1, fringe.len());
+        assert_eq!(3, fringe.pop().unwrap().value);
+        assert_eq!(DedupStats::default(), fringe.stats());
+    }
+    #[test]
+    fn the_merged_node_keeps_the_largest_priority_bonus() {
+        let mut fringe = fringe(16);
+        fringe.push(sub('a', 10, 50));
+        fringe.push(SubProblem { priority_bonus: 3, ..sub('a', 8, 50) });
+        assert_eq!(3, fringe.pop().unwrap().priority_bonus);
+    }
+    #[test]
+    fn the_states_are_no_longer_deduplicated_once_the_map_is_full() {
+        let mut fringe = fringe(1);
+        fringe.push(sub('a', 0, 10));
+        fringe.push(sub('b', 0, 20));
+        fringe.push(sub('b', 0, 20));
+        fringe.push(sub('a', 0, 10));
+        assert_eq!(3, fringe.len());
+        assert_eq!(1, fringe.stats().merged);
+    }
+    #[test]
+    fn clear_forgets_about_the_states() {
+        let mut fringe = fringe(1);
+        fringe.push(sub('a', 5, 10));
+        fringe.push(sub('a', 7, 10));
+        fringe.push(sub('b', 0, 20));
+        fringe.clear();
+        assert!(fringe.is_empty());
+        assert_eq!(None, fringe.peek());
+
+        // the map is no longer full either
+        fringe.push(sub('b', 0, 20));
+        fringe.push(sub('b', 0, 20));
+        assert_eq!(1, fringe.len());
+        assert_eq!(2, fringe.stats().merged);
+    }
+    #[test]
+    fn only_the_nodes_which_are_not_superseded_are_inspected() {
+        let mut fringe = fringe(16);
+        fringe.push(sub('b', 0, 50));
+        fringe.push(sub('a', 5, 40));
+        fringe.push(sub('a', 7, 30));
+        let mut values = vec![];
+        fringe.inspect(&mut |n| values.push((*n.state, n.value)));
+        values.sort_unstable();
+        assert_eq!(vec![('a', 7), ('b', 0)], values);
+    }
+}
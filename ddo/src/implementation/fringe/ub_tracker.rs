@@ -0,0 +1,90 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the bookkeeping which lets a fringe report the best
+//! upper bound of its nodes whatever the order in which it pops them (see 
+//! `Fringe::best_ub`).
+
+use std::collections::BTreeMap;
+
+/// The multiset of the upper bounds of the nodes of a fringe
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UbTracker {
+    /// Maps each upper bound to the number of nodes having that bound
+    ubs: BTreeMap<isize, usize>,
+}
+
+impl UbTracker {
+    /// Records a node having the given upper bound
+    pub fn insert(&mut self, ub: isize) {
+        *self.ubs.entry(ub).or_default() += 1;
+    }
+    /// Forgets about one node having the given upper bound
+    pub fn remove(&mut self, ub: isize) {
+        if let Some(count) = self.ubs.get_mut(&ub) {
+            *count -= 1;
+            if *count == 0 {
+                self.ubs.remove(&ub);
+            }
+        }
+    }
+    /// Forgets about all the nodes
+    pub fn clear(&mut self) {
+        self.ubs.clear();
+    }
+    /// Returns the greatest upper bound of the recorded nodes
+    pub fn best(&self) -> Option<isize> {
+        self.ubs.keys().next_back().copied()
+    }
+}
+
+#[cfg(test)]
+mod test_ub_tracker {
+    use super::UbTracker;
+
+    #[test]
+    fn an_empty_tracker_has_no_best_ub() {
+        let mut tracker = UbTracker::default();
+        assert_eq!(None, tracker.best());
+        tracker.insert(10);
+        tracker.remove(10);
+        assert_eq!(None, tracker.best());
+    }
+    #[test]
+    fn the_best_ub_is_the_greatest_recorded_one() {
+        let mut tracker = UbTracker::default();
+        tracker.insert(10);
+        tracker.insert(30);
+        tracker.insert(20);
+        assert_eq!(Some(30), tracker.best());
+    }
+    #[test]
+    fn a_bound_is_kept_as_long_as_some_node_has_it() {
+        let mut tracker = UbTracker::default();
+        tracker.insert(30);
+        tracker.insert(30);
+        tracker.insert(10);
+        tracker.remove(30);
+        assert_eq!(Some(30), tracker.best());
+        tracker.remove(30);
+        assert_eq!(Some(10), tracker.best());
+        tracker.clear();
+        assert_eq!(None, tracker.best());
+    }
+}
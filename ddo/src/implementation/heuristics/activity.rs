@@ -0,0 +1,263 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides an adaptive variable ordering which learns from the
+//! compilations performed by the solver. It is a simple take on the activity
+//! based heuristics of the SAT solvers: the variables which keep showing up 
+//! along the best paths of the inexact relaxed DDs are the ones whose 
+//! branching is most likely to tighten the bounds, hence they are branched 
+//! on first.
+
+use parking_lot::Mutex;
+
+use crate::{CarryOver, CompilationOutcome, CompilationType, Variable};
+
+/// The default factor by which the scores decay after each bump
+const DEFAULT_DECAY: f64 = 0.95;
+
+/// A variable ordering whose scores are carried over from one compilation to
+/// the next (see `CarryOver`). Whenever a relaxed DD turns out to be inexact,
+/// each variable decided along its best path gets its score bumped by one, 
+/// after which all scores decay. The variables that were involved in the 
+/// recent compilations thus outweigh those that were involved a long time ago.
+///
+/// The problem is meant to delegate its `next_variable` to `pick`, and the 
+/// ordering must be given to the solver with `with_carry_over`.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// let order = ActivityOrder::new(3).with_decay(0.5);
+/// order.carry_over(&CompilationOutcome {
+///     comp_type : CompilationType::Relaxed,
+///     depth     : 0,
+///     node_ub   : 10,
+///     best_value: Some(8),
+///     is_exact  : false,
+///     best_path : &[Decision { variable: Variable(2), value: 1 }],
+/// });
+/// assert_eq!(0.5, order.score(Variable(2)));
+/// assert_eq!(Some(Variable(2)), order.pick(&mut (0..3).map(Variable)));
+/// ```
+#[derive(Debug)]
+pub struct ActivityOrder {
+    /// The score of each variable
+    scores: Mutex<Vec<f64>>,
+    /// The factor by which all scores are multiplied after each bump
+    decay: f64,
+}
+impl ActivityOrder {
+    /// Creates an ordering of `nb_vars` variables which all start with a 
+    /// zero score
+    pub fn new(nb_vars: usize) -> Self {
+        Self { scores: Mutex::new(vec![0.0; nb_vars]), decay: DEFAULT_DECAY }
+    }
+    /// Sets the factor (in ]0, 1]) by which the scores decay after each bump.
+    /// With a factor of one, the scores never decay.
+    pub fn with_decay(mut self, decay: f64) -> Self {
+        self.decay = decay;
+        self
+    }
+    /// Returns the current score of the given variable
+    pub fn score(&self, var: Variable) -> f64 {
+        self.scores.lock()[var.id()]
+    }
+    /// Returns the variable with the highest score among the given ones. Ties
+    /// are broken in favor of the variable that comes first.
+    pub fn pick(&self, vars: &mut dyn Iterator<Item = Variable>) -> Option<Variable> {
+        let scores = self.scores.lock();
+        let mut best: Option<Variable> = None;
+        for var in vars {
+            if best.is_none_or(|b| scores[var.id()] > scores[b.id()]) {
+                best = Some(var);
+            }
+        }
+        best
+    }
+}
+impl CarryOver for ActivityOrder {
+    fn carry_over(&self, from: &CompilationOutcome) {
+        if from.comp_type != CompilationType::Relaxed || from.is_exact {
+            return;
+        }
+        let mut scores = self.scores.lock();
+        for decision in from.best_path {
+            scores[decision.variable.id()] += 1.0;
+        }
+        for score in scores.iter_mut() {
+            *score *= self.decay;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_activity {
+    use crate::*;
+
+    fn outcome(comp_type: CompilationType, is_exact: bool, best_path: &[Decision]) -> CompilationOutcome<'_> {
+        CompilationOutcome { comp_type, depth: 0, node_ub: 100, best_value: Some(90), is_exact, best_path }
+    }
+    fn take(var: usize) -> Decision {
+        Decision { variable: Variable(var), value: 1 }
+    }
+
+    #[test]
+    fn the_variables_of_an_inexact_relaxed_best_path_are_bumped() {
+        let order = ActivityOrder::new(4).with_decay(1.0);
+        order.carry_over(&outcome(CompilationType::Relaxed, false, &[take(1), take(3)]));
+        assert_eq!(0.0, order.score(Variable(0)));
+        assert_eq!(1.0, order.score(Variable(1)));
+        assert_eq!(0.0, order.score(Variable(2)));
+        assert_eq!(1.0, order.score(Variable(3)));
+    }
+    #[test]
+    fn restricted_and_exact_outcomes_are_ignored() {
+        let order = ActivityOrder::new(2).with_decay(1.0);
+        order.carry_over(&outcome(CompilationType::Restricted, false, &[take(0)]));
+        order.carry_over(&outcome(CompilationType::Relaxed, true, &[take(1)]));
+        assert_eq!(0.0, order.score(Variable(0)));
+        assert_eq!(0.0, order.score(Variable(1)));
+    }
+    #[test]
+    fn the_old_bumps_decay() {
+        let order = ActivityOrder::new(2).with_decay(0.5);
+        order.carry_over(&outcome(CompilationType::Relaxed, false, &[take(0)]));
+        order.carry_over(&outcome(CompilationType::Relaxed, false, &[take(1)]));
+        assert_eq!(0.25, order.score(Variable(0)));
+        assert_eq!(0.5,  order.score(Variable(1)));
+        assert_eq!(Some(Variable(1)), order.pick(&mut (0..2).map(Variable)));
+    }
+    #[test]
+    fn ties_are_broken_in_favor_of_the_first_variable() {
+        let order = ActivityOrder::new(3);
+        assert_eq!(Some(Variable(2)), order.pick(&mut [2, 0, 1].into_iter().map(Variable)));
+        assert_eq!(None, order.pick(&mut std::iter::empty::<Variable>()));
+    }
+
+    #[test]
+    fn the_solver_carries_the_scores_over_between_the_compilations() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(2);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe)
+            .with_carry_over(&problem.order);
+
+        let Completion{is_exact, best_value, ..} = solver.maximize();
+        assert!(is_exact);
+        assert_eq!(Some(134), best_value);
+        assert!((0..problem.nb_variables()).any(|v| problem.order.score(Variable(v)) > 0.0));
+    }
+
+    fn knapsack() -> Knapsack {
+        let profit = vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27];
+        Knapsack {
+            capacity: 60,
+            order   : ActivityOrder::new(profit.len()),
+            profit,
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        }
+    }
+
+    /// The state remembers which items are still to be decided since the 
+    /// order in which they are decided changes over the course of the search
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct KnapsackState {
+        free: u64,
+        capacity: usize
+    }
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<usize>,
+        weight: Vec<usize>,
+        order: ActivityOrder,
+    }
+
+    const TAKE_IT: isize = 1;
+    const LEAVE_IT_OUT: isize = 0;
+
+    impl Problem for Knapsack {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> Self::State {
+            KnapsackState{ free: (1 << self.nb_variables()) - 1, capacity: self.capacity }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = *state;
+            ret.free &= !(1 << dec.variable.id());
+            if dec.value == TAKE_IT { 
+                ret.capacity -= self.weight[dec.variable.id()] 
+            }
+            ret
+        }
+        fn transition_cost(&self, _state: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            self.profit[dec.variable.id()] as isize * dec.value
+        }
+        fn next_variable(&self, _: usize, next_layer: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            let free = next_layer.next()?.free;
+            self.order.pick(&mut (0..self.nb_variables()).filter(|v| free & (1 << v) != 0).map(Variable))
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback)
+        {
+            if state.capacity >= self.weight[variable.id()] {
+                f.apply(Decision { variable, value: TAKE_IT });
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            } else {
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            }
+        }
+    }
+    struct KPRelax<'a>{pb: &'a Knapsack}
+    impl Relaxation for KPRelax<'_> {
+        type State = KnapsackState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            states.max_by_key(|node| node.capacity).copied().unwrap()
+        }
+        fn relax(&self, _source: &Self::State, _dest: &Self::State, _merged: &Self::State, _decision: Decision, cost: isize) -> isize {
+            cost
+        }
+        fn fast_upper_bound(&self, state: &Self::State) -> isize {
+            let mut tot = 0;
+            for var in 0..self.pb.nb_variables() {
+                if state.free & (1 << var) != 0 && self.pb.weight[var] <= state.capacity {
+                    tot += self.pb.profit[var];
+                }
+            }
+            tot as isize
+        }
+    }
+    struct KPRanking;
+    impl StateRanking for KPRanking {
+        type State = KnapsackState;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.capacity.cmp(&b.capacity)
+        }
+    }
+}
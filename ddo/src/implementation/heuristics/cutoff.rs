@@ -321,6 +321,35 @@ impl Cutoff for TimeBudget {
         self.stop.load(std::sync::atomic::Ordering::Relaxed)
     }
 }
+/// This cutoff adapts a user-supplied closure: the search stops as soon as
+/// the closure returns true. The closure is consulted by the solver before
+/// each node it explores and by the DDs while they are being compiled, hence
+/// it should be cheap to evaluate.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// # use std::sync::atomic::{AtomicBool, Ordering};
+/// let interrupted = AtomicBool::new(false);
+/// let cutoff = CutoffFn::new(|| interrupted.load(Ordering::Relaxed));
+/// assert!(!cutoff.must_stop());
+/// interrupted.store(true, Ordering::Relaxed);
+/// assert!(cutoff.must_stop());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CutoffFn<F> {
+    criterion: F,
+}
+impl<F: Fn() -> bool> CutoffFn<F> {
+    pub fn new(criterion: F) -> Self {
+        Self { criterion }
+    }
+}
+impl<F: Fn() -> bool> Cutoff for CutoffFn<F> {
+    fn must_stop(&self) -> bool {
+        (self.criterion)()
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -334,6 +363,18 @@ mod tests {
         assert!(!cutoff.must_stop());
     }
 
+    #[test]
+    fn a_closure_cutoff_stops_when_the_closure_says_so() {
+        let calls = std::cell::Cell::new(0);
+        let cutoff = CutoffFn::new(|| {
+            calls.set(calls.get() + 1);
+            calls.get() > 2
+        });
+        assert!(!cutoff.must_stop());
+        assert!(!cutoff.must_stop());
+        assert!(cutoff.must_stop());
+    }
+
     #[test]
     fn time_budget_must_stop_only_when_elapsed() {
         let cutoff = TimeBudget::new(Duration::from_secs(3));
@@ -24,8 +24,14 @@ mod utils;
 mod width;
 mod subproblem_ranking;
 mod cutoff;
+mod ranking_check;
+mod total_order;
+mod activity;
 
 pub use utils::*;
 pub use width::*;
 pub use subproblem_ranking::*;
-pub use cutoff::*;
\ No newline at end of file
+pub use cutoff::*;
+pub(crate) use ranking_check::*;
+pub use total_order::*;
+pub use activity::*;
\ No newline at end of file
@@ -0,0 +1,113 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the guard which gives some context to the panics that
+//! occur while the nodes of a layer or of the fringe are being ordered. 
+//!
+//! A ranking which panics (because it unwraps a `partial_cmp` on a NaN key,
+//! for instance) would otherwise unwind from somewhere deep inside a sort or
+//! a binary heap, with no hint about the nodes being compared. When the 
+//! `ranking-check` feature is enabled, the comparisons are evaluated under
+//! `catch_unwind` and the panic is raised again with the depth of the nodes 
+//! and the dump of both states (see `StateRanking::dump_state`).
+
+use std::{any::Any, panic::{catch_unwind, AssertUnwindSafe}};
+
+/// True iff the compilation is performed with the ranking check
+pub(crate) const RANKING_CHECK: bool = cfg!(feature = "ranking-check");
+
+/// The context of a failed comparison: what was being compared and the 
+/// dump of both states (when the ranking provides one)
+pub(crate) type RankingContext = (String, Option<(String, String)>);
+
+/// Evaluates the given ordering operation. When the ranking check is enabled
+/// and that operation panics, the panic is raised again with a message built
+/// from the given context.
+pub(crate) fn guard_ranking<R>(eval: impl FnOnce() -> R, context: impl FnOnce() -> RankingContext) -> R {
+    if !RANKING_CHECK {
+        return eval();
+    }
+    match catch_unwind(AssertUnwindSafe(eval)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let (what, states) = context();
+            let cause = panic_message(payload.as_ref());
+            match states {
+                Some((left, right)) => 
+                    panic!("node ordering panicked while comparing {what}: {cause}\n  left : {left}\n  right: {right}"),
+                None => 
+                    panic!("node ordering panicked while comparing {what}: {cause}"),
+            }
+        }
+    }
+}
+
+/// Extracts the message of a panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.as_str()
+    } else {
+        "<no message>"
+    }
+}
+
+#[cfg(test)]
+mod test_ranking_check {
+    use std::panic::catch_unwind;
+
+    use super::*;
+
+    fn message_of(payload: Box<dyn Any + Send>) -> String {
+        panic_message(payload.as_ref()).to_string()
+    }
+
+    #[test]
+    fn the_result_of_a_successful_comparison_is_returned() {
+        let out = guard_ranking(|| 1.cmp(&2), || unreachable!());
+        assert_eq!(std::cmp::Ordering::Less, out);
+    }
+    #[test]
+    fn the_message_of_a_panic_payload_is_extracted() {
+        assert_eq!("static", message_of(Box::new("static")));
+        assert_eq!("owned", message_of(Box::new("owned".to_string())));
+        assert_eq!("<no message>", message_of(Box::new(42)));
+    }
+    #[cfg(feature = "ranking-check")]
+    #[test]
+    fn a_panicking_comparison_is_reported_with_its_context() {
+        let err = catch_unwind(|| guard_ranking(
+            || -> std::cmp::Ordering { panic!("NaN key") }, 
+            || ("nodes at depth 7".to_string(), Some(("a".to_string(), "b".to_string())))
+        )).unwrap_err();
+        assert_eq!(
+            "node ordering panicked while comparing nodes at depth 7: NaN key\n  left : a\n  right: b", 
+            message_of(err));
+    }
+    #[cfg(not(feature = "ranking-check"))]
+    #[test]
+    fn a_panicking_comparison_is_not_altered() {
+        let err = catch_unwind(|| guard_ranking(
+            || -> std::cmp::Ordering { panic!("NaN key") }, 
+            || unreachable!()
+        )).unwrap_err();
+        assert_eq!("NaN key", message_of(err));
+    }
+}
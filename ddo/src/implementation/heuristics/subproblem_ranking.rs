@@ -49,12 +49,12 @@ use crate::{StateRanking, SubProblemRanking, SubProblem};
 ///     }
 /// }
 /// 
-/// let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, depth: 0, path: vec![]};
-/// let b = SubProblem {state: Arc::new('b'), value:  2, ub: 100, depth: 0, path: vec![]};
-/// let c = SubProblem {state: Arc::new('c'), value: 24, ub: 150, depth: 0, path: vec![]};
-/// let d = SubProblem {state: Arc::new('d'), value: 13, ub:  60, depth: 0, path: vec![]};
-/// let e = SubProblem {state: Arc::new('e'), value: 65, ub: 700, depth: 0, path: vec![]};
-/// let f = SubProblem {state: Arc::new('f'), value: 19, ub: 100, depth: 0, path: vec![]};
+/// let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, depth: 0, path: vec![], priority_bonus: 0};
+/// let b = SubProblem {state: Arc::new('b'), value:  2, ub: 100, depth: 0, path: vec![], priority_bonus: 0};
+/// let c = SubProblem {state: Arc::new('c'), value: 24, ub: 150, depth: 0, path: vec![], priority_bonus: 0};
+/// let d = SubProblem {state: Arc::new('d'), value: 13, ub:  60, depth: 0, path: vec![], priority_bonus: 0};
+/// let e = SubProblem {state: Arc::new('e'), value: 65, ub: 700, depth: 0, path: vec![], priority_bonus: 0};
+/// let f = SubProblem {state: Arc::new('f'), value: 19, ub: 100, depth: 0, path: vec![], priority_bonus: 0};
 ///
 /// let ranking = MaxUB::new(&CharRanking);
 /// let mut priority_q = SimpleFringe::new(ranking);
@@ -72,8 +72,15 @@ use crate::{StateRanking, SubProblemRanking, SubProblem};
 /// assert_eq!('b', *priority_q.pop().unwrap().state); // because ub = 100 but value = 2
 /// assert_eq!('d', *priority_q.pop().unwrap().state); // because ub = 13 which is the worst
 /// ```
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug)]
 pub struct MaxUB<'a, O: StateRanking>(&'a O);
+// Not derived: a reference is copyable even when the ranking itself is not
+impl<O: StateRanking> Clone for MaxUB<'_, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<O: StateRanking> Copy for MaxUB<'_, O> {}
 impl <'a, O: StateRanking> MaxUB<'a, O> {
     /// Creates a new instance
     pub fn new(x: &'a O) -> Self {
@@ -88,6 +95,154 @@ impl<O: StateRanking> SubProblemRanking for MaxUB<'_, O> {
             .then_with(|| l.value.cmp(&r.value))
             .then_with(|| self.0.compare(&l.state, &r.state))
     }
+
+    fn dump_state(&self, state: &O::State) -> Option<String> {
+        self.0.dump_state(state)
+    }
+}
+
+/// The BestUbPerCost strategy is a variant of MaxUB which accounts for the 
+/// estimated cost of compiling the DDs of each subproblem. Its estimate is the
+/// number of free variables of the subproblem (the number of variables of the
+/// problem minus the depth of the subproblem) times the average width of the
+/// layers of its DDs. The subproblems are then popped by decreasing
+///
+///   `ub - penalty * estimated cost`
+///
+/// so that a subproblem which is much cheaper to fathom than another one 
+/// having a slightly better upper bound is explored first. The ties are 
+/// broken as in MaxUB.
+///
+/// With a zero penalty, this ranking is equivalent to MaxUB.
+///
+/// # Note
+/// The average width must be known when the ranking is created (e.g. the
+/// width of a `FixedWidth` heuristic or the average layer width observed on
+/// a previous run): the order of the nodes which sit in the fringe may not 
+/// change while they are there.
+///
+/// Because the nodes are not popped in descending upper bound order, the
+/// `SimpleFringe` and the `NoDupFringe` keep track of the upper bounds of the
+/// nodes they hold when they are given this ranking.
+#[derive(Debug)]
+pub struct BestUbPerCost<'a, O: StateRanking> {
+    /// The state ranking used to break the ties
+    ranking: &'a O,
+    /// The number of variables of the problem
+    nb_variables: usize,
+    /// The average width of the layers of the DDs
+    avg_width: usize,
+    /// The penalty incurred per unit of estimated compilation cost
+    penalty: f64,
+}
+// Not derived: a reference is copyable even when the ranking itself is not
+impl<O: StateRanking> Clone for BestUbPerCost<'_, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<O: StateRanking> Copy for BestUbPerCost<'_, O> {}
+impl <'a, O: StateRanking> BestUbPerCost<'a, O> {
+    /// Creates a new instance for a problem having `nb_variables` variables,
+    /// whose DDs have layers of `avg_width` nodes on average
+    pub fn new(ranking: &'a O, nb_variables: usize, avg_width: usize, penalty: f64) -> Self {
+        Self { ranking, nb_variables, avg_width, penalty }
+    }
+    /// Returns the estimated cost of compiling the DDs of the given subproblem
+    pub fn estimated_cost(&self, sub: &SubProblem<O::State>) -> usize {
+        self.nb_variables.saturating_sub(sub.depth).saturating_mul(self.avg_width)
+    }
+    /// Returns the score of the given subproblem: its upper bound minus the
+    /// penalty incurred for its estimated cost
+    fn score(&self, sub: &SubProblem<O::State>) -> f64 {
+        sub.ub as f64 - self.penalty * self.estimated_cost(sub) as f64
+    }
+}
+impl<O: StateRanking> SubProblemRanking for BestUbPerCost<'_, O> {
+    type State = O::State;
+
+    fn compare(&self, l: &SubProblem<O::State>, r: &SubProblem<O::State>) -> Ordering {
+        self.score(l).total_cmp(&self.score(r))
+            .then_with(|| l.ub.cmp(&r.ub))
+            .then_with(|| l.value.cmp(&r.value))
+            .then_with(|| self.ranking.compare(&l.state, &r.state))
+    }
+
+    fn orders_by_ub(&self) -> bool {
+        self.penalty == 0.0
+    }
+
+    fn dump_state(&self, state: &O::State) -> Option<String> {
+        self.ranking.dump_state(state)
+    }
+}
+
+/// The BonusFirst strategy wraps another subproblem ranking and pops the nodes
+/// carrying the largest priority bonus first. The ties are broken by the 
+/// wrapped ranking, which means that it behaves exactly as the wrapped ranking
+/// as long as no node carries a bonus.
+///
+/// A node gets a bonus when it is pushed with `push_boosted_subproblem`; and
+/// its descendants inherit it for as many generations as the bonus says (the
+/// bonus decreases by one at each generation). This way, a promising region 
+/// of the search space is explored coherently rather than being interleaved
+/// with unrelated nodes having a similar upper bound. This only reorders the
+/// nodes: none of them is ever pruned because of its bonus.
+///
+/// # Example
+/// ```
+/// # use std::sync::Arc;
+/// # use std::cmp::Ordering;
+/// # use ddo::*;
+/// #
+/// struct CharRanking;
+/// impl StateRanking for CharRanking {
+///     type State = char;
+///     fn compare(&self, a: &Self::State, b: &Self::State) -> Ordering {
+///         a.cmp(b)
+///     }
+/// }
+/// 
+/// let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, depth: 0, path: vec![], priority_bonus: 0};
+/// let b = SubProblem {state: Arc::new('b'), value:  2, ub: 100, depth: 0, path: vec![], priority_bonus: 1};
+/// let c = SubProblem {state: Arc::new('c'), value: 24, ub: 150, depth: 0, path: vec![], priority_bonus: 2};
+/// let d = SubProblem {state: Arc::new('d'), value: 13, ub:  60, depth: 0, path: vec![], priority_bonus: 1};
+///
+/// let ranking = BonusFirst::new(MaxUB::new(&CharRanking));
+/// let mut priority_q = SimpleFringe::new(ranking);
+/// priority_q.push(a);
+/// priority_q.push(b);
+/// priority_q.push(c);
+/// priority_q.push(d);
+///
+/// assert_eq!('c', *priority_q.pop().unwrap().state); // because its bonus is the largest
+/// assert_eq!('b', *priority_q.pop().unwrap().state); // same bonus as d, but a better ub
+/// assert_eq!('d', *priority_q.pop().unwrap().state); 
+/// assert_eq!('a', *priority_q.pop().unwrap().state); // because it has no bonus at all
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct BonusFirst<O: SubProblemRanking>(O);
+impl<O: SubProblemRanking> BonusFirst<O> {
+    /// Creates a new instance which breaks the ties with the given ranking
+    pub fn new(ranking: O) -> Self {
+        Self(ranking)
+    }
+}
+impl<O: SubProblemRanking> SubProblemRanking for BonusFirst<O> {
+    type State = O::State;
+
+    fn compare(&self, l: &SubProblem<O::State>, r: &SubProblem<O::State>) -> Ordering {
+        l.priority_bonus.cmp(&r.priority_bonus)
+            .then_with(|| self.0.compare(l, r))
+    }
+
+    fn orders_by_ub(&self) -> bool {
+        false
+    }
+
+    fn dump_state(&self, state: &O::State) -> Option<String> {
+        self.0.dump_state(state)
+    }
 }
 
 
@@ -110,16 +265,25 @@ mod test_maxub {
         fn compare(&self, a: &Self::State, b: &Self::State) -> Ordering {
             a.cmp(b)
         }
+        fn dump_state(&self, state: &Self::State) -> Option<String> {
+            Some(format!("{state:?}"))
+        }
+    }
+
+    #[test]
+    fn the_states_are_dumped_by_the_state_ranking() {
+        assert_eq!(Some("'a'".to_string()), MaxUB::new(&CharRanking).dump_state(&'a'));
+        assert_eq!(Some("'a'".to_string()), BonusFirst::new(MaxUB::new(&CharRanking)).dump_state(&'a'));
     }
 
     #[test]
     fn example() {
-        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0};
-        let b = SubProblem {state: Arc::new('b'), value:  2, ub: 100, path: vec![], depth: 0};
-        let c = SubProblem {state: Arc::new('c'), value: 24, ub: 150, path: vec![], depth: 0};
-        let d = SubProblem {state: Arc::new('d'), value: 13, ub:  60, path: vec![], depth: 0};
-        let e = SubProblem {state: Arc::new('e'), value: 65, ub: 700, path: vec![], depth: 0};
-        let f = SubProblem {state: Arc::new('f'), value: 19, ub: 100, path: vec![], depth: 0};
+        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0, priority_bonus: 0};
+        let b = SubProblem {state: Arc::new('b'), value:  2, ub: 100, path: vec![], depth: 0, priority_bonus: 0};
+        let c = SubProblem {state: Arc::new('c'), value: 24, ub: 150, path: vec![], depth: 0, priority_bonus: 0};
+        let d = SubProblem {state: Arc::new('d'), value: 13, ub:  60, path: vec![], depth: 0, priority_bonus: 0};
+        let e = SubProblem {state: Arc::new('e'), value: 65, ub: 700, path: vec![], depth: 0, priority_bonus: 0};
+        let f = SubProblem {state: Arc::new('f'), value: 19, ub: 100, path: vec![], depth: 0, priority_bonus: 0};
 
         let nodes = vec![a, b, c, d, e, f];
         let mut priority_q = BinaryHeap::from_vec_cmp(nodes, CompareSubProblem::new(MaxUB::new(&CharRanking)));
@@ -134,44 +298,191 @@ mod test_maxub {
 
     #[test]
     fn gt_because_ub() {
-        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0};
-        let b = SubProblem {state: Arc::new('b'), value: 42, ub: 100, path: vec![], depth: 0};
+        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0, priority_bonus: 0};
+        let b = SubProblem {state: Arc::new('b'), value: 42, ub: 100, path: vec![], depth: 0, priority_bonus: 0};
         let cmp = MaxUB::new(&CharRanking);
         assert_eq!(Ordering::Greater, cmp.compare(&a, &b));
     }
     #[test]
     fn gt_because_lplen() {
-        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0};
-        let b = SubProblem {state: Arc::new('b'), value:  2, ub: 300, path: vec![], depth: 0};
+        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0, priority_bonus: 0};
+        let b = SubProblem {state: Arc::new('b'), value:  2, ub: 300, path: vec![], depth: 0, priority_bonus: 0};
         let cmp = MaxUB::new(&CharRanking);
         assert_eq!(Ordering::Greater, cmp.compare(&a, &b));
     }
     #[test]
     fn lt_because_ub() {
-        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0};
-        let b = SubProblem {state: Arc::new('b'), value: 42, ub: 100, path: vec![], depth: 0};
+        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0, priority_bonus: 0};
+        let b = SubProblem {state: Arc::new('b'), value: 42, ub: 100, path: vec![], depth: 0, priority_bonus: 0};
         let cmp = MaxUB::new(&CharRanking);
         assert_eq!(Ordering::Less, cmp.compare(&b, &a));
     }
     #[test]
     fn lt_because_lplen() {
-        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0};
-        let b = SubProblem {state: Arc::new('b'), value:  2, ub: 300, path: vec![], depth: 0};
+        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0, priority_bonus: 0};
+        let b = SubProblem {state: Arc::new('b'), value:  2, ub: 300, path: vec![], depth: 0, priority_bonus: 0};
         let cmp = MaxUB::new(&CharRanking);
         assert_eq!(Ordering::Less, cmp.compare(&b, &a));
     }
     #[test]
     fn lt_because_state() {
-        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0};
-        let b = SubProblem {state: Arc::new('b'), value: 42, ub: 300, path: vec![], depth: 0};
+        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0, priority_bonus: 0};
+        let b = SubProblem {state: Arc::new('b'), value: 42, ub: 300, path: vec![], depth: 0, priority_bonus: 0};
         let cmp = MaxUB::new(&CharRanking);
         assert_eq!(Ordering::Less, cmp.compare(&a, &b));
     }
     #[test]
     fn eq_self() {
-        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0};
+        let a = SubProblem {state: Arc::new('a'), value: 42, ub: 300, path: vec![], depth: 0, priority_bonus: 0};
         let cmp = MaxUB::new(&CharRanking);
         assert_eq!(Ordering::Equal, cmp.compare(&a, &a));
     }
 }
 
+#[cfg(test)]
+mod test_best_ub_per_cost {
+    use std::cmp::Ordering;
+    use std::sync::Arc;
+
+    use crate::*;
+
+    /// A dummy state comparator for use in the tests
+    struct CharRanking;
+    impl StateRanking for CharRanking {
+        type State = char;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> Ordering {
+            a.cmp(b)
+        }
+    }
+
+    fn sub(state: char, ub: isize, depth: usize) -> SubProblem<char> {
+        SubProblem { state: Arc::new(state), value: 0, ub, path: vec![], depth, priority_bonus: 0 }
+    }
+
+    #[test]
+    fn the_estimated_cost_is_the_number_of_free_variables_times_the_width() {
+        let cmp = BestUbPerCost::new(&CharRanking, 10, 5, 1.0);
+        assert_eq!(50, cmp.estimated_cost(&sub('a', 0, 0)));
+        assert_eq!(15, cmp.estimated_cost(&sub('a', 0, 7)));
+        assert_eq!(0,  cmp.estimated_cost(&sub('a', 0, 12)));
+    }
+    #[test]
+    fn a_cheap_node_goes_first_when_the_ubs_are_close() {
+        let cmp = BestUbPerCost::new(&CharRanking, 10, 5, 1.0);
+        let deep = sub('a', 100, 9);    // score = 100 - 5  = 95
+        let shallow = sub('b', 110, 1); // score = 110 - 45 = 65
+        assert_eq!(Ordering::Greater, cmp.compare(&deep, &shallow));
+    }
+    #[test]
+    fn an_expensive_node_goes_first_when_its_ub_is_much_better() {
+        let cmp = BestUbPerCost::new(&CharRanking, 10, 5, 1.0);
+        let deep = sub('a', 100, 9);    // score = 100 - 5  = 95
+        let shallow = sub('b', 150, 1); // score = 150 - 45 = 105
+        assert_eq!(Ordering::Less, cmp.compare(&deep, &shallow));
+    }
+    #[test]
+    fn without_penalty_it_orders_the_nodes_like_max_ub() {
+        let cmp = BestUbPerCost::new(&CharRanking, 10, 5, 0.0);
+        let maxub = MaxUB::new(&CharRanking);
+        let nodes = [sub('a', 100, 9), sub('b', 110, 1), sub('c', 110, 3), sub('d', 90, 0)];
+        for a in nodes.iter() {
+            for b in nodes.iter() {
+                assert_eq!(maxub.compare(a, b), cmp.compare(a, b));
+            }
+        }
+        assert!(cmp.orders_by_ub());
+    }
+    #[test]
+    fn the_fringe_reports_the_best_ub_of_its_nodes() {
+        let mut fringe = SimpleFringe::new(BestUbPerCost::new(&CharRanking, 10, 5, 1.0));
+        assert!(!BestUbPerCost::new(&CharRanking, 10, 5, 1.0).orders_by_ub());
+        fringe.push(sub('a', 100, 9));
+        fringe.push(sub('b', 110, 1));
+        assert_eq!('a', *fringe.peek().unwrap().state);
+        assert_eq!(Some(110), fringe.best_ub());
+        fringe.pop();
+        assert_eq!(Some(110), fringe.best_ub());
+        fringe.pop();
+        assert_eq!(None, fringe.best_ub());
+    }
+    #[test]
+    fn the_no_dup_fringe_reports_the_best_ub_of_its_nodes() {
+        let mut fringe = NoDupFringe::new(BestUbPerCost::new(&CharRanking, 10, 5, 1.0));
+        fringe.push(sub('a', 100, 9));
+        fringe.push(sub('b', 110, 1));
+        fringe.push(sub('b', 120, 1));
+        assert_eq!('a', *fringe.peek().unwrap().state);
+        assert_eq!(Some(120), fringe.best_ub());
+        fringe.pop();
+        assert_eq!(Some(120), fringe.best_ub());
+        fringe.clear();
+        assert_eq!(None, fringe.best_ub());
+    }
+}
+
+#[cfg(test)]
+mod test_bonus_first {
+    use std::cmp::Ordering;
+    use std::sync::Arc;
+
+    use crate::*;
+
+    /// A dummy state comparator for use in the tests
+    struct CharRanking;
+    impl StateRanking for CharRanking {
+        type State = char;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> Ordering {
+            a.cmp(b)
+        }
+    }
+
+    fn sub(state: char, ub: isize, priority_bonus: u8) -> SubProblem<char> {
+        SubProblem { state: Arc::new(state), value: 0, ub, path: vec![], depth: 0, priority_bonus }
+    }
+
+    #[test]
+    fn the_largest_bonus_goes_first() {
+        let cmp = BonusFirst::new(MaxUB::new(&CharRanking));
+        assert_eq!(Ordering::Greater, cmp.compare(&sub('a', 10, 2), &sub('b', 100, 1)));
+        assert_eq!(Ordering::Less, cmp.compare(&sub('a', 100, 0), &sub('b', 10, 1)));
+    }
+    #[test]
+    fn without_bonus_it_orders_the_nodes_like_the_wrapped_ranking() {
+        let cmp = BonusFirst::new(MaxUB::new(&CharRanking));
+        let maxub = MaxUB::new(&CharRanking);
+        let nodes = [sub('a', 100, 0), sub('b', 110, 0), sub('c', 110, 0), sub('d', 90, 0)];
+        for a in nodes.iter() {
+            for b in nodes.iter() {
+                assert_eq!(maxub.compare(a, b), cmp.compare(a, b));
+            }
+        }
+    }
+    #[test]
+    fn the_ties_between_equal_bonuses_are_broken_by_the_wrapped_ranking() {
+        let cmp = BonusFirst::new(MaxUB::new(&CharRanking));
+        assert_eq!(Ordering::Greater, cmp.compare(&sub('a', 100, 1), &sub('b', 10, 1)));
+        assert_eq!(Ordering::Equal, cmp.compare(&sub('a', 100, 1), &sub('a', 100, 1)));
+    }
+    #[test]
+    fn the_fringe_reports_the_best_ub_of_its_nodes() {
+        let mut fringe = SimpleFringe::new(BonusFirst::new(MaxUB::new(&CharRanking)));
+        fringe.push(sub('a', 100, 1));
+        fringe.push(sub('b', 110, 0));
+        assert_eq!('a', *fringe.peek().unwrap().state);
+        assert_eq!(Some(110), fringe.best_ub());
+    }
+    #[test]
+    fn the_no_dup_fringe_keeps_the_largest_bonus() {
+        let mut fringe = NoDupFringe::new(BonusFirst::new(MaxUB::new(&CharRanking)));
+        fringe.push(sub('a', 100, 0));
+        fringe.push(sub('b', 110, 0));
+        fringe.push(sub('a', 90, 2));
+        assert_eq!(2, fringe.len());
+        let first = fringe.pop().unwrap();
+        assert_eq!('a', *first.state);
+        assert_eq!(2, first.priority_bonus);
+        assert_eq!(100, first.ub);
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides an adapter to rank the states with floating point keys.
+//! The `f64` type only implements `PartialOrd`, and the `partial_cmp(..).unwrap()`
+//! which is often used to compare such keys panics as soon as one of them is 
+//! NaN. The adapters below impose a total order on the keys instead, where 
+//! NaN is the worst possible value.
+
+use std::{cmp::Ordering, marker::PhantomData};
+
+use crate::StateRanking;
+
+/// A floating point key which is totally ordered: NaN compares less than any
+/// other value (hence a node with a NaN key is the first one to be dropped or
+/// merged) and all NaNs are equal. The other values are ordered as usual, so
+/// that `-0.0` and `0.0` are equal too.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// # use std::cmp::Ordering;
+/// assert_eq!(Ordering::Less,    NanWorst(f64::NAN).cmp(&NanWorst(f64::NEG_INFINITY)));
+/// assert_eq!(Ordering::Equal,   NanWorst(f64::NAN).cmp(&NanWorst(f64::NAN)));
+/// assert_eq!(Ordering::Greater, NanWorst(2.0).cmp(&NanWorst(1.0)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct NanWorst(pub f64);
+
+impl PartialEq for NanWorst {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for NanWorst {}
+impl PartialOrd for NanWorst {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for NanWorst {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.0.is_nan(), other.0.is_nan()) {
+            (true,  true ) => Ordering::Equal,
+            (true,  false) => Ordering::Less,
+            (false, true ) => Ordering::Greater,
+            (false, false) => self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal),
+        }
+    }
+}
+
+/// A state ranking which compares the states based on a floating point key.
+/// The keys are compared with `NanWorst`, hence this ranking never panics.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// # use std::cmp::Ordering;
+/// struct State { ratio: f64 }
+///
+/// let ranking = FloatKeyRanking::new(|s: &State| s.ratio);
+/// assert_eq!(Ordering::Greater, ranking.compare(&State{ratio: 0.5}, &State{ratio: f64::NAN}));
+/// ```
+pub struct FloatKeyRanking<S, F> {
+    key: F,
+    _phantom: PhantomData<fn(&S)>,
+}
+impl <S, F: Fn(&S) -> f64> FloatKeyRanking<S, F> {
+    /// Creates a ranking which orders the states by the given key
+    pub fn new(key: F) -> Self {
+        Self { key, _phantom: PhantomData }
+    }
+}
+impl <S, F: Fn(&S) -> f64> StateRanking for FloatKeyRanking<S, F> {
+    type State = S;
+
+    fn compare(&self, a: &S, b: &S) -> Ordering {
+        NanWorst((self.key)(a)).cmp(&NanWorst((self.key)(b)))
+    }
+}
+
+#[cfg(test)]
+mod test_total_order {
+    use std::cmp::Ordering;
+
+    use crate::*;
+
+    #[test]
+    fn nan_is_worse_than_any_other_value() {
+        for x in [f64::NEG_INFINITY, f64::MIN, -1.0, 0.0, 1.0, f64::MAX, f64::INFINITY] {
+            assert_eq!(Ordering::Less,    NanWorst(f64::NAN).cmp(&NanWorst(x)));
+            assert_eq!(Ordering::Greater, NanWorst(x).cmp(&NanWorst(f64::NAN)));
+        }
+    }
+    #[test]
+    fn all_nans_are_equal() {
+        assert_eq!(NanWorst(f64::NAN), NanWorst(-f64::NAN));
+    }
+    #[test]
+    fn the_other_values_keep_their_usual_order() {
+        assert!(NanWorst(-1.0) < NanWorst(0.0));
+        assert!(NanWorst(f64::INFINITY) > NanWorst(f64::MAX));
+        assert_eq!(NanWorst(-0.0), NanWorst(0.0));
+    }
+    #[test]
+    fn sorting_nan_keys_does_not_panic() {
+        let mut keys = [NanWorst(2.0), NanWorst(f64::NAN), NanWorst(-3.0), NanWorst(f64::NAN), NanWorst(1.0)];
+        keys.sort();
+        let sorted = keys.iter().map(|k| k.0).collect::<Vec<_>>();
+        assert!(sorted[0].is_nan() && sorted[1].is_nan());
+        assert_eq!(vec![-3.0, 1.0, 2.0], sorted[2..].to_vec());
+    }
+    #[test]
+    fn float_key_ranking_compares_the_keys() {
+        let ranking = FloatKeyRanking::new(|x: &f64| *x);
+        assert_eq!(Ordering::Greater, ranking.compare(&1.0, &0.5));
+        assert_eq!(Ordering::Less,    ranking.compare(&f64::NAN, &0.5));
+        assert_eq!(Ordering::Equal,   ranking.compare(&f64::NAN, &f64::NAN));
+    }
+}
@@ -23,14 +23,17 @@ use std::cmp::Ordering;
 
 use compare::Compare;
 
-use crate::{SubProblemRanking, SubProblem};
+use crate::{SubProblemRanking, SubProblem, guard_ranking};
 
 
 /// This is a thin wrapper to convert a SubProblemRanking into a `Compare` 
 /// object as is sometimes required (e.g. to configure the order in a binary heap)
 /// 
 /// This struct has no behavior of its own: it simply delegates to the 
-/// underlying implementation.
+/// underlying implementation. (When the `ranking-check` feature is enabled,
+/// it reports the sub-problems it was comparing if that implementation panics,
+/// along with the dump of their states when `SubProblemRanking::dump_state`
+/// provides one)
 /// 
 /// # Example
 /// ```
@@ -77,7 +80,10 @@ impl <X:SubProblemRanking> CompareSubProblem<X> {
 }
 impl <X:SubProblemRanking> Compare<SubProblem<X::State>> for CompareSubProblem<X> {
     fn compare(&self, l: &SubProblem<X::State>, r: &SubProblem<X::State>) -> Ordering {
-        self.0.compare(l, r)
+        guard_ranking(|| self.0.compare(l, r), || (
+            format!("the sub-problems at depth {} and {} of the fringe", l.depth, r.depth),
+            self.0.dump_state(l.state.as_ref()).zip(self.0.dump_state(r.state.as_ref()))
+        ))
     }
 }
 
@@ -108,24 +114,83 @@ mod test {
     fn when_a_is_less_than_b_comparesubproblem_returns_less() {
         let cmp = CompareSubProblem::new(CharRanking);
         assert_eq!(cmp.compare(
-            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 0}, 
-            &SubProblem{state: Arc::new('b'), value: 0, ub: isize::MAX, path: vec![], depth: 0}), 
+            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 0, priority_bonus: 0}, 
+            &SubProblem{state: Arc::new('b'), value: 0, ub: isize::MAX, path: vec![], depth: 0, priority_bonus: 0}), 
             Ordering::Less);
     }
     #[test]
     fn when_a_is_greater_than_b_comparesubproblem_returns_greater() {
         let cmp = CompareSubProblem::new(CharRanking);
         assert_eq!(cmp.compare(
-            &SubProblem{state: Arc::new('b'), value: 0, ub: isize::MAX, path: vec![], depth: 0}, 
-            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 0}), 
+            &SubProblem{state: Arc::new('b'), value: 0, ub: isize::MAX, path: vec![], depth: 0, priority_bonus: 0}, 
+            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 0, priority_bonus: 0}), 
             Ordering::Greater);
     }
     #[test]
     fn when_a_is_equal_to_b_comparesubproblem_returns_equal() {
         let cmp = CompareSubProblem::new(CharRanking);
         assert_eq!(cmp.compare(
-            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 0}, 
-            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 0}), 
+            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 0, priority_bonus: 0}, 
+            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 0, priority_bonus: 0}), 
             Ordering::Equal);
     }
-}
\ No newline at end of file
+
+    /// A ranking which cannot compare anything
+    struct PanickingRanking;
+    impl SubProblemRanking for PanickingRanking {
+        type State = char;
+
+        fn compare(&self, _: &SubProblem<char>, _: &SubProblem<char>) -> Ordering {
+            panic!("cannot rank these sub-problems")
+        }
+        fn dump_state(&self, state: &char) -> Option<String> {
+            Some(format!("{state:?}"))
+        }
+    }
+    /// A ranking which cannot compare anything and does not dump its states
+    #[cfg(feature = "ranking-check")]
+    struct MutePanickingRanking;
+    #[cfg(feature = "ranking-check")]
+    impl SubProblemRanking for MutePanickingRanking {
+        type State = char;
+
+        fn compare(&self, _: &SubProblem<char>, _: &SubProblem<char>) -> Ordering {
+            panic!("cannot rank these sub-problems")
+        }
+    }
+    #[cfg(feature = "ranking-check")]
+    #[test]
+    fn when_the_ranking_panics_comparesubproblem_reports_both_subproblems() {
+        let cmp = CompareSubProblem::new(PanickingRanking);
+        let err = std::panic::catch_unwind(|| cmp.compare(
+            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 3, priority_bonus: 0}, 
+            &SubProblem{state: Arc::new('b'), value: 0, ub: isize::MAX, path: vec![], depth: 4, priority_bonus: 0}))
+            .unwrap_err();
+        assert_eq!(Some(&
+            "node ordering panicked while comparing the sub-problems at depth 3 and 4 of the fringe: \
+            cannot rank these sub-problems\n  left : 'a'\n  right: 'b'".to_string()), 
+            err.downcast_ref::<String>());
+    }
+    #[cfg(feature = "ranking-check")]
+    #[test]
+    fn when_the_ranking_does_not_dump_its_states_comparesubproblem_only_reports_the_depths() {
+        let cmp = CompareSubProblem::new(MutePanickingRanking);
+        let err = std::panic::catch_unwind(|| cmp.compare(
+            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 3, priority_bonus: 0}, 
+            &SubProblem{state: Arc::new('b'), value: 0, ub: isize::MAX, path: vec![], depth: 4, priority_bonus: 0}))
+            .unwrap_err();
+        assert_eq!(Some(&
+            "node ordering panicked while comparing the sub-problems at depth 3 and 4 of the fringe: \
+            cannot rank these sub-problems".to_string()), 
+            err.downcast_ref::<String>());
+    }
+    #[cfg(not(feature = "ranking-check"))]
+    #[test]
+    #[should_panic(expected = "cannot rank these sub-problems")]
+    fn when_the_ranking_panics_comparesubproblem_propagates_the_panic() {
+        let cmp = CompareSubProblem::new(PanickingRanking);
+        cmp.compare(
+            &SubProblem{state: Arc::new('a'), value: 0, ub: isize::MAX, path: vec![], depth: 3, priority_bonus: 0}, 
+            &SubProblem{state: Arc::new('b'), value: 0, ub: isize::MAX, path: vec![], depth: 4, priority_bonus: 0});
+    }
+}
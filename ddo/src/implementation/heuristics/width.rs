@@ -36,7 +36,7 @@ use crate::{WidthHeuristic, SubProblem};
 /// let heuristic = FixedWidth(100); // assume a fixed width of 100
 /// 
 /// // assume the exsitence of whatever subroblem you like..
-/// let subproblem = SubProblem {state: Arc::new('a'), value: 42, ub: 100, depth: 0, path: vec![]};
+/// let subproblem = SubProblem {state: Arc::new('a'), value: 42, ub: 100, depth: 0, path: vec![], priority_bonus: 0};
 /// // still, the heuristic always return 100.
 /// assert_eq!(100, heuristic.max_width(&subproblem));
 /// ```
@@ -261,7 +261,8 @@ impl <X> WidthHeuristic<X> for FixedWidth {
 ///         Decision{variable: Variable(1), value: 1},
 ///         Decision{variable: Variable(3), value: 1},
 ///         Decision{variable: Variable(4), value: 1},
-///     ]
+///     ],
+/// #    priority_bonus: 0,
 /// };
 /// assert_eq!(2, heuristic.max_width(&subproblem));
 /// ```
@@ -500,7 +501,8 @@ impl <X> WidthHeuristic<X> for NbUnassignedWidth {
 ///         Decision{variable: Variable(1), value: 1},
 ///         Decision{variable: Variable(3), value: 1},
 ///         Decision{variable: Variable(4), value: 1},
-///     ]
+///     ],
+/// #    priority_bonus: 0,
 /// };
 /// assert_eq!(10, heuristic.max_width(&subproblem));
 /// ```
@@ -740,7 +742,8 @@ impl <S, X: WidthHeuristic<S>> WidthHeuristic<S> for Times<X> {
 ///         Decision{variable: Variable(1), value: 1},
 ///         Decision{variable: Variable(3), value: 1},
 ///         Decision{variable: Variable(4), value: 1},
-///     ]
+///     ],
+/// #    priority_bonus: 0,
 /// };
 /// assert_eq!(1, heuristic.max_width(&subproblem));
 /// ```
@@ -897,6 +900,7 @@ mod test_nbunassigned {
             ub   : 100,
             path : vec![Decision{variable: Variable(0), value: 4}],
             depth: 1,
+            priority_bonus: 0,
         };
         assert_eq!(4, heu.max_width(&sub));
     }
@@ -909,6 +913,7 @@ mod test_nbunassigned {
             ub   : 100,
             path : vec![], // no decision made, all vars are available
             depth: 0,
+            priority_bonus: 0,
         };
         assert_eq!(5, heu.max_width(&sub));
     }
@@ -929,6 +934,7 @@ mod test_nbunassigned {
                 Decision{variable: Variable(4), value: 4},
                 ],
             depth: 5,
+            priority_bonus: 0,
         };
         assert_eq!(0, heu.max_width(&sub));
     }
@@ -950,6 +956,7 @@ mod test_fixedwidth {
                 Decision{variable: Variable(0), value: 0},
                 ],
             depth: 1,
+            priority_bonus: 0,
         };
         assert_eq!(5, heu.max_width(&sub));
     }
@@ -962,6 +969,7 @@ mod test_fixedwidth {
             ub   : 100,
             path : vec![],
             depth: 0,
+            priority_bonus: 0,
         };
         assert_eq!(5, heu.max_width(&sub));
     }
@@ -981,6 +989,7 @@ mod test_fixedwidth {
                 Decision{variable: Variable(4), value: 4},
                 ],
             depth: 5,
+            priority_bonus: 0,
         };
         assert_eq!(5, heu.max_width(&sub));
     }
@@ -1007,6 +1016,7 @@ mod test_adapters {
                 Decision{variable: Variable(4), value: 4},
                 ],
             depth: 5,
+            priority_bonus: 0,
         };
         assert_eq!(10, Times( 2, heu).max_width(&sub));
         assert_eq!(15, Times( 3, heu).max_width(&sub));
@@ -1028,6 +1038,7 @@ mod test_adapters {
                 Decision{variable: Variable(4), value: 4},
                 ],
             depth: 5,
+            priority_bonus: 0,
         };
         assert_eq!( 2, DivBy( 2, FixedWidth(4)).max_width(&sub));
         assert_eq!( 3, DivBy( 3, FixedWidth(9)).max_width(&sub));
@@ -1049,6 +1060,7 @@ mod test_adapters {
                 Decision{variable: Variable(4), value: 4},
                 ],
             depth: 5,
+            priority_bonus: 0,
         };
         assert_eq!( 1, Times( 0, FixedWidth(10)).max_width(&sub));
         assert_eq!( 1, Times(10, FixedWidth( 0)).max_width(&sub));
@@ -1069,6 +1081,7 @@ mod test_adapters {
                 Decision{variable: Variable(4), value: 4},
                 ],
             depth: 5,
+            priority_bonus: 0,
         };
         DivBy( 0, FixedWidth(0)).max_width(&sub);
     }
@@ -5,12 +5,12 @@
 //! ``Decision Diagram-Based Branch-and-Bound with Caching
 //! for Dominance and Suboptimality Detection''.
 
-use std::{sync::Arc, hash::Hash, collections::hash_map::Entry, fmt::Debug};
+use std::{cell::Cell, sync::Arc, hash::Hash, collections::{HashMap, hash_map::Entry}, fmt::Debug, mem::size_of};
 
 use derive_builder::Builder;
 use fxhash::FxHashMap;
 
-use crate::{NodeFlags, Decision, CutsetType, CompilationInput, Completion, Reason, CompilationType, Problem, LAST_EXACT_LAYER, DecisionDiagram, SubProblem, FRONTIER, Solution, DominanceCheckResult};
+use crate::{merge_by_chunks, CutsetState, DEFAULT_MERGE_CHUNK_SIZE, CutoffPoll, NodeFlags, Decision, CutsetType, CompilationInput, Completion, Reason, CompilationType, Problem, ProblemCtx, LAST_EXACT_LAYER, ADAPTIVE, MddStats, WidthConflictPolicy, ExactnessPolicy, DecisionDiagram, SubProblem, FRONTIER, Solution, DominanceCheckResult, ProgressGuard, WidthProfile, RANKING_CHECK, guard_ranking, LayerMap, Fingerprint, arc_bytes};
 
 /// The identifier of a node: it indicates the position of the referenced node 
 /// in the ’nodes’ vector of the mdd structure.
@@ -40,11 +40,6 @@ struct Node<T> {
     /// The length of the longest path between the problem root and this
     /// specific node
     value_top: isize,
-    /// The length of the longest path between this node and the terminal node.
-    /// 
-    /// ### Note
-    /// This field is only ever populated after the MDD has been fully unrolled.
-    value_bot: isize,
     /// The identifier of the last edge on the longest path between the problem 
     /// root and this node if it exists.
     best: Option<EdgeId>,
@@ -53,12 +48,6 @@ struct Node<T> {
     inbound: EdgesListId,
     // The rough upper bound associated to this node
     rub: isize,
-    /// A threshold value to be stored in the cache that conditions the
-    /// re-exploration of other nodes with the same state.
-    /// 
-    /// ### Note
-    /// This field is only ever populated after the MDD has been fully unrolled.
-    theta: Option<isize>,
     /// A group of flag telling if the node is an exact node, if it is a relaxed
     /// node (helps to determine if the best path is an exact path) and if the
     /// node is reachable in a backwards traversal of the MDD starting at the
@@ -68,6 +57,45 @@ struct Node<T> {
     depth: usize,
 }
 
+/// The bounds of a node which are only ever needed once the node has been
+/// pruned or once the MDD has been fully unrolled. They are kept apart from
+/// the nodes (in the 'bounds' vector, indexed by node id) so that the nodes 
+/// stay small while the MDD is being compiled.
+#[derive(Debug, Clone, Copy)]
+struct Bounds {
+    /// The length of the longest path between this node and the terminal node.
+    value_bot: isize,
+    /// A threshold value to be stored in the cache that conditions the
+    /// re-exploration of other nodes with the same state.
+    theta: Option<isize>,
+}
+impl Default for Bounds {
+    fn default() -> Self {
+        Bounds { value_bot: isize::MIN, theta: None }
+    }
+}
+
+// Size of the structures that are allocated for each node and each edge of
+// the MDD, on a 64 bit target, with a `u64` state (the state itself lives in
+// the `Arc` which is shared with the layer map, the cache and the fringe).
+//
+// | structure      | size | layout                                          |
+// |----------------|------|-------------------------------------------------|
+// | Node<u64>      |   64 | state, value_top, rub, depth, inbound: 8 each   |
+// |                |      | best: 16 (id + tag), flags: 1 (+ 7 padding)     |
+// | Bounds         |   24 | value_bot: 8, theta: 16                         |
+// | Edge           |   40 | from, to: 8 each, decision: 16, cost: 8         |
+// | EdgesList      |   24 | tag: 8, head: 8, tail: 8                        |
+// | ArcInner<u64>  |   24 | strong and weak counts: 8 each, state: 8        |
+#[cfg(target_pointer_width = "64")]
+const _: () = {
+    use std::mem::size_of;
+    assert!(size_of::<Node<u64>>()  == 64);
+    assert!(size_of::<Bounds>()     == 24);
+    assert!(size_of::<Edge>()       == 40);
+    assert!(size_of::<EdgesList>()  == 24);
+};
+
 /// Materializes one edge a.k.a arc from the decision diagram. It logically 
 /// connects two nodes and annotates the link with a decision and a cost.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -98,6 +126,20 @@ struct Layer {
     to: usize,
 }
 
+/// The progress of the enumeration of the optimal solutions of an exact DD 
+/// (see `Mdd::next_optimal_solution`). It holds no reference to the DD so 
+/// that it can be stored alongside of it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OptimalPaths {
+    /// The optimal terminal nodes from which no path has been walked yet
+    terminals: Vec<NodeId>,
+    /// The nodes of the partial path being walked (from the terminal node up)
+    /// along with the list of their inbound edges which remain to be tried
+    frames: Vec<(NodeId, EdgesListId)>,
+    /// The decisions of the partial path being walked (from the deepest up)
+    decisions: Vec<Decision>,
+}
+
 /// The decision diagram in itself. This structure essentially keeps track
 /// of the nodes composing the diagram as well as the edges connecting these
 /// nodes in two vectors (enabling preallocation and good cache locality). 
@@ -123,6 +165,9 @@ where
     /// nodes from all layers in the DD. A nice property is that all nodes
     /// belonging to one same layer form a sequence in the ‘nodes‘ vector.
     nodes: Vec<Node<T>>,
+    /// The bounds of the nodes of the decision diagram (see `Bounds`). This
+    /// vector is only sized to match `nodes` when the diagram is finalized.
+    bounds: Vec<Bounds>,
     /// This vector stores the information about all edges connecting the nodes 
     /// of the decision diagram.
     edges: Vec<Edge>,
@@ -140,17 +185,29 @@ where
     /// The rationale being that two transitions to the same state in the same
     /// layer should lead to the same node. This indexation helps ensuring 
     /// the uniqueness constraint in amortized O(1).
-    next_l: FxHashMap<Arc<T>, NodeId>,
+    next_l: LayerMap<T, NodeId>,
     /// The depth of the layer currently being expanded
     curr_depth: usize,
 
     /// Keeps track of the decisions that have been taken to reach the root
     /// of this DD, starting from the problem root.
     path_to_root: Vec<Decision>,
+    /// The residual subproblem this DD was compiled from, without its path
+    /// (which is kept in `path_to_root`). See `take_residual`.
+    residual: Option<SubProblem<T>>,
     /// The identifier of the last exact layer (should this dd be inexact)
     lel: Option<LayerId>,
+    /// The type of cut-set computed for the last compilation. It only differs
+    /// from `CUTSET_TYPE` when that one is `ADAPTIVE`
+    cutset_type: CutsetType,
+    /// With an `ADAPTIVE` cut-set, the frontier cut-set is chosen whenever
+    /// the first inexact layer lies at least this deep (relative to the number
+    /// of layers of the DD). The last exact layer is chosen otherwise.
+    adaptive_threshold: f64,
     /// The cut-set of the decision diagram (only maintained for relaxed dd)
     cutset: Vec<NodeId>,
+    /// Whether the cut-set has been drained since the last compilation
+    cutset_state: CutsetState,
     /// The identifier of the best terminal node of the diagram (None when the
     /// problem compiled into this dd is infeasible)
     best_node: Option<NodeId>,
@@ -163,6 +220,42 @@ where
     /// A flag set to true when the longest r-t path of this decision diagram
     /// traverses no merged node (Exact Best Path Optimization aka EBPO).
     has_exact_best_path: bool,
+    /// The guard which makes sure the compilation terminates
+    progress: ProgressGuard,
+    /// The per-layer width multipliers of the problem being compiled
+    width_profile: WidthProfile,
+    /// The estimated size of the largest layer ever developed by this DD
+    peak_layer_bytes: usize,
+    /// The maximum number of states which are passed at once to the `merge`
+    /// of the relaxation (see `with_merge_chunk_size`)
+    merge_chunk_size: usize,
+    /// Polls the cutoff every so many node operations (see 
+    /// `with_poll_interval`)
+    poll: CutoffPoll,
+    /// Set when a layer had more nodes flagged by `StateRanking::must_keep`
+    /// than it could keep
+    keep_list_overflow: bool,
+    /// The number of layers which had more nodes flagged by 
+    /// `StateRanking::must_keep` than they could keep
+    width_conflicts: usize,
+    /// What to do when a layer has more nodes flagged by 
+    /// `StateRanking::must_keep` than it can keep
+    width_conflict: WidthConflictPolicy,
+    /// The rule which tells whether a node is exact (see `ExactnessPolicy`)
+    exactness: ExactnessPolicy,
+    /// Set once some nodes were pruned from a layer of the DD: the nodes of 
+    /// the layers below it have pruned siblings (see `child_exactness`)
+    pruned_layer: bool,
+    /// A scratch buffer holding the nodes of the layer being expanded. It is 
+    /// kept between compilations so as to reuse its allocation.
+    scratch: Vec<NodeId>,
+    /// The domain of the variable of the layer being expanded, when it does 
+    /// not depend on the state (see `Problem::domain_is_state_independent`).
+    /// It is kept between compilations so as to reuse its allocation.
+    domain: Vec<Decision>,
+    /// The scratch context which is passed on to the transition functions of
+    /// the problem. It is kept between compilations (see `ProblemCtx`).
+    ctx: ProblemCtx,
 }
 
 const NIL: EdgesListId = EdgesListId(0);
@@ -179,6 +272,12 @@ macro_rules! get {
     (mut edge     $id:expr, $dd:expr) => {&mut $dd.edges   [$id.0]};
     (    edgelist $id:expr, $dd:expr) => {&    $dd.edgelists[$id.0]};
     (mut edgelist $id:expr, $dd:expr) => {&mut $dd.edgelists[$id.0]};
+    (    bounds   $id:expr, $dd:expr) => {&    $dd.bounds  [$id.0]};
+    (mut bounds   $id:expr, $dd:expr) => {{
+        // the bounds are only allocated for the nodes that actually need them
+        if $dd.bounds.len() <= $id.0 { $dd.bounds.resize($id.0 + 1, Bounds::default()); }
+        &mut $dd.bounds[$id.0]
+    }};
     (    layer    $id:expr, $dd:expr) => {&    $dd.layers  [$id.0]};
     (mut layer    $id:expr, $dd:expr) => {&mut $dd.layers  [$id.0]};
 }
@@ -204,11 +303,11 @@ macro_rules! append_edge_to {
         $dd.edgelists.push(EdgesList::Cons { head: new_eid, tail: get!(node $edge.to, $dd).inbound });
         
         let parent = get!(node $edge.from, $dd);
-        let parent_exact = parent.flags.is_exact();
+        let exact_via_parent = $dd.exactness.child_exactness(parent.flags.is_exact(), false, $dd.pruned_layer);
         let value = parent.value_top.saturating_add($edge.cost);
         
         let node = get!(mut node $edge.to, $dd);
-        let exact = parent_exact & node.flags.is_exact();
+        let exact = exact_via_parent & node.flags.is_exact();
         node.flags.set_exact(exact);
         node.inbound = lst_id;
 
@@ -234,8 +333,14 @@ where
 {
     type State = T;
 
-    fn compile(&mut self, input: &CompilationInput<Self::State>) -> Result<Completion, Reason> {
-        self._compile(input)
+    fn compile(&mut self, input: &CompilationInput<Self::State>, residual: SubProblem<Self::State>) -> Result<Completion, Reason> {
+        self._compile(input, residual)
+    }
+
+    fn take_residual(&mut self) -> Option<SubProblem<T>> {
+        let mut residual = self.residual.take()?;
+        residual.path = std::mem::take(&mut self.path_to_root);
+        Some(residual)
     }
 
     fn is_exact(&self) -> bool {
@@ -250,6 +355,12 @@ where
         self._best_solution()
     }
 
+    fn for_each_best_decision(&self, f: &mut dyn FnMut(Decision)) {
+        if let Some(id) = self.best_node {
+            Self::_for_each_path_decision(id, &self.path_to_root, &self.nodes, &self.edges, f)
+        }
+    }
+
     fn best_exact_value(&self) -> Option<isize> {
         self._best_exact_value()
     }
@@ -258,10 +369,56 @@ where
         self._best_exact_solution()
     }
 
+    fn best_state(&self) -> Option<&Self::State> {
+        self.best_node.map(|id| get!(node id, self).state.as_ref())
+    }
+
+    fn best_exact_state(&self) -> Option<&Self::State> {
+        self.best_exact_node.map(|id| get!(node id, self).state.as_ref())
+    }
+
+    fn for_each_exact_solution(&self, func: &mut dyn FnMut(isize, Solution)) {
+        self._for_each_exact_solution(func)
+    }
+
+    fn cutset_len(&self) -> usize {
+        self._cutset_len()
+    }
+
+    fn for_each_cutset_node<F>(&self, func: F)
+    where
+        F: FnMut(SubProblem<Self::State>) {
+        self._for_each_cutset_node(func)
+    }
+
     fn drain_cutset<F>(&mut self, func: F)
     where
         F: FnMut(SubProblem<Self::State>) {
-        self._drain_cutset(func)
+        // The cutset is emptied before any callback is invoked. This way, a
+        // callback which panics cannot leave a half consumed cutset behind.
+        let mut subproblems = vec![];
+        self._for_each_cutset_node(|sub| subproblems.push(sub));
+        self.cutset.clear();
+        self.cutset_state = CutsetState::Consumed;
+        subproblems.into_iter().for_each(func);
+    }
+
+    fn cutset_state(&self) -> CutsetState {
+        self.cutset_state
+    }
+
+    fn peak_layer_bytes(&self) -> usize {
+        self.peak_layer_bytes
+    }
+
+    fn stats(&self) -> MddStats {
+        MddStats {
+            nb_layers: self.layers.len(),
+            first_inexact_layer: self._first_inexact_layer(),
+            cutset_type: self.cutset_type,
+            keep_list_overflow: self.keep_list_overflow,
+            width_conflicts: self.width_conflicts,
+        }
     }
 }
 
@@ -273,6 +430,7 @@ where
         Self {
             layers: vec![],
             nodes: vec![],
+            bounds: vec![],
             edges: vec![],
             edgelists: vec![],
             //
@@ -281,29 +439,213 @@ where
             curr_depth: 0,
             //
             path_to_root: vec![],
+            residual: None,
             lel: None,
+            cutset_type: CUTSET_TYPE,
+            adaptive_threshold: 0.5,
             cutset: vec![],
+            cutset_state: CutsetState::Fresh,
             best_node: None,
             best_exact_node: None,
             is_exact: true,
             has_exact_best_path: false,
+            progress: ProgressGuard::default(),
+            width_profile: WidthProfile::default(),
+            peak_layer_bytes: 0,
+            merge_chunk_size: DEFAULT_MERGE_CHUNK_SIZE,
+            poll: CutoffPoll::default(),
+            keep_list_overflow: false,
+            width_conflicts: 0,
+            width_conflict: WidthConflictPolicy::default(),
+            exactness: ExactnessPolicy::default(),
+            pruned_layer: false,
+            scratch: vec![],
+            domain: vec![],
+            ctx: ProblemCtx::default(),
+        }
+    }
+
+    /// Sets the maximum number of states which are passed at once to the 
+    /// `merge` of the relaxation (1024 by default). Larger groups of nodes are
+    /// merged by chunks, and the partial results are then merged together.
+    pub fn with_merge_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.merge_chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets the number of node operations performed between two polls of the
+    /// cutoff (1024 by default). The expansion of a node counts as one
+    /// operation plus one per branch; so does the deletion of a node when a
+    /// layer is trimmed. See the `poll` module for the worst case amount of 
+    /// work between two polls.
+    pub fn with_poll_interval(mut self, interval: usize) -> Self {
+        self.poll = CutoffPoll::new(interval);
+        self
+    }
+
+    /// Sets what to do when a layer has more nodes flagged by 
+    /// `StateRanking::must_keep` than its width lets it keep (by default, the
+    /// layer exceeds its width). See `WidthConflictPolicy`.
+    pub fn with_width_conflict_policy(mut self, policy: WidthConflictPolicy) -> Self {
+        self.width_conflict = policy;
+        self
+    }
+
+    /// Sets the rule which tells whether a node is exact (by default, the one
+    /// of `child_exactness`). See `ExactnessPolicy`.
+    pub fn with_exactness_policy(mut self, policy: ExactnessPolicy) -> Self {
+        self.exactness = policy;
+        self
+    }
+
+    /// Sets the relative depth of the first inexact layer from which an
+    /// `ADAPTIVE` DD chooses the frontier cut-set rather than the last exact
+    /// layer (0.5 by default). This has no effect on the other DDs.
+    pub fn with_adaptive_threshold(mut self, threshold: f64) -> Self {
+        self.adaptive_threshold = threshold;
+        self
+    }
+
+    /// Computes the max-marginals of the last compiled diagram: for each 
+    /// variable and each value, the value of the best solution (represented 
+    /// in the diagram) which assigns that value to the variable. This takes one
+    /// backward pass over the diagram (the forward pass was done during the 
+    /// compilation, it is the `value_top` of the nodes).
+    /// 
+    /// This returns None when the last compiled diagram is not exact.
+    pub(crate) fn max_marginals(&self) -> Option<Vec<HashMap<isize, isize>>> {
+        if !self.is_exact {
+            return None;
+        }
+        let mut marginals: Vec<HashMap<isize, isize>> = vec![];
+        let mut record = |decision: Decision, value: isize| {
+            let var = decision.variable.id();
+            if var >= marginals.len() {
+                marginals.resize_with(var + 1, HashMap::default);
+            }
+            let best = marginals[var].entry(decision.value).or_insert(value);
+            *best = value.max(*best);
+        };
+
+        let mut value_bot = vec![isize::MIN; self.nodes.len()];
+        for id in self.next_l.values() {
+            if !get!(node id, self).flags.is_deleted() {
+                value_bot[id.0] = 0;
+            }
+        }
+        for layer in self.layers.iter().rev() {
+            for id in (layer.from..layer.to).map(NodeId) {
+                let value = value_bot[id.0];
+                if value == isize::MIN {
+                    continue; // no solution goes through this node
+                }
+                foreach!(edge of id, self, |edge: Edge| {
+                    let using_edge = value.saturating_add(edge.cost);
+                    value_bot[edge.from.0] = value_bot[edge.from.0].max(using_edge);
+                    record(edge.decision, get!(node edge.from, self).value_top.saturating_add(using_edge));
+                });
+            }
+        }
+        // the decisions leading to the root belong to all the solutions
+        if let Some(best) = self.best_value() {
+            for decision in self.path_to_root.iter().copied() {
+                record(decision, best);
+            }
+        }
+        Some(marginals)
+    }
+    
+    /// Starts the enumeration of all the optimal solutions of the last 
+    /// compiled diagram (see `next_optimal_solution`). This returns None when
+    /// that diagram is not exact.
+    pub(crate) fn optimal_paths(&self) -> Option<OptimalPaths> {
+        if !self.is_exact {
+            return None;
+        }
+        let best = self.best_value();
+        let terminals = self.next_l.values()
+            .filter(|id| {
+                let node = get!(node id, self);
+                !node.flags.is_deleted() && Some(node.value_top) == best
+            })
+            .collect();
+        Some(OptimalPaths { terminals, frames: vec![], decisions: vec![] })
+    }
+
+    /// Returns the next optimal solution of the enumeration `paths`, or None
+    /// when all of them have been enumerated. The paths are walked depth first
+    /// from the optimal terminal nodes up to the root, following only the 
+    /// inbound edges which lie on a longest path: every walk which reaches 
+    /// the root is thus an optimal solution, and no walk is ever abandoned.
+    /// The decisions come in the same order as those of `best_solution`.
+    pub(crate) fn next_optimal_solution(&self, paths: &mut OptimalPaths) -> Option<Solution> {
+        loop {
+            let Some(top) = paths.frames.len().checked_sub(1) else {
+                let id = paths.terminals.pop()?;
+                if let Some(solution) = self._enter_optimal_path(paths, id) {
+                    return Some(solution);
+                }
+                continue;
+            };
+            let (id, list) = paths.frames[top];
+            match *get!(edgelist list, self) {
+                EdgesList::Nil => {
+                    paths.frames.pop();
+                    paths.decisions.pop();
+                }
+                EdgesList::Cons { head, tail } => {
+                    paths.frames[top].1 = tail;
+                    let edge = *get!(edge head, self);
+                    let value = get!(node edge.from, self).value_top.saturating_add(edge.cost);
+                    if value == get!(node id, self).value_top {
+                        paths.decisions.push(edge.decision);
+                        if let Some(solution) = self._enter_optimal_path(paths, edge.from) {
+                            return Some(solution);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Extends the partial path being walked with the given node. When that
+    /// node is the root, the path is complete: its solution is returned and
+    /// the walk backtracks right away.
+    fn _enter_optimal_path(&self, paths: &mut OptimalPaths, id: NodeId) -> Option<Solution> {
+        let inbound = get!(node id, self).inbound;
+        if let EdgesList::Nil = *get!(edgelist inbound, self) {
+            let solution = self.path_to_root.iter()
+                .chain(paths.decisions.iter())
+                .copied()
+                .collect();
+            paths.decisions.pop();
+            Some(solution)
+        } else {
+            paths.frames.push((id, inbound));
+            None
         }
     }
     
     fn _clear(&mut self) {
         self.layers.clear();
         self.nodes.clear();
+        self.bounds.clear();
         self.edges.clear();
         self.edgelists.clear();
         self.prev_l.clear();
         self.next_l.clear();
         self.path_to_root.clear();
+        self.residual = None;
         self.cutset.clear();
+        self.cutset_state = CutsetState::Fresh;
         self.lel = None;
         self.best_node = None;
         self.best_exact_node = None;
         self.is_exact = true;
         self.has_exact_best_path = false;
+        self.keep_list_overflow = false;
+        self.width_conflicts = 0;
+        self.pruned_layer = false;
     }
 
     fn _best_value(&self) -> Option<isize> {
@@ -322,6 +664,15 @@ where
         self.best_exact_node.map(|id| self._best_path(id))
     }
 
+    fn _for_each_exact_solution(&self, func: &mut dyn FnMut(isize, Solution)) {
+        for id in self.next_l.values() {
+            let node = get!(node id, self);
+            if node.flags.is_exact() || Some(id) == self.best_exact_node {
+                func(node.value_top, self._best_path(id))
+            }
+        }
+    }
+
     fn _best_path(&self, id: NodeId) -> Vec<Decision> {
         Self::_best_path_partial_borrow(id, &self.path_to_root, &self.nodes, &self.edges)
     }
@@ -332,99 +683,156 @@ where
         nodes: &[Node<T>],
         edges: &[Edge],
     ) -> Vec<Decision> {
-        let mut sol = root_pa.to_owned();
+        // the depth of the node is a good guess of the length of its path
+        let mut sol = Vec::with_capacity(root_pa.len().max(nodes[id.0].depth));
+        Self::_for_each_path_decision(id, root_pa, nodes, edges, |d| sol.push(d));
+        sol
+    }
+
+    /// Walks the best path from the given node up to the root without 
+    /// allocating. The decisions leading to the root come first, then those 
+    /// of the DD from the deepest one up.
+    fn _for_each_path_decision(
+        id: NodeId,
+        root_pa: &[Decision],
+        nodes: &[Node<T>],
+        edges: &[Edge],
+        mut f: impl FnMut(Decision),
+    ) {
+        root_pa.iter().copied().for_each(&mut f);
         let mut edge_id = nodes[id.0].best;
         while let Some(eid) = edge_id {
             let edge = edges[eid.0];
-            sol.push(edge.decision);
+            f(edge.decision);
             edge_id = nodes[edge.from.0].best;
         }
-        sol
     }
 
-    fn _compile(&mut self, input: &CompilationInput<T>) -> Result<Completion, Reason> {
+    fn _compile(&mut self, input: &CompilationInput<T>, residual: SubProblem<T>) -> Result<Completion, Reason> {
         self._clear();
-        self._initialize(input);
+        self._initialize(input, residual);
         
-        let mut curr_l = vec![];
-        while let Some(var) = input.problem.next_variable(self.curr_depth, &mut self.next_l.keys().map(|s| s.as_ref())) {
+        let mut curr_l = std::mem::take(&mut self.scratch);
+        curr_l.clear();
+        while let Some(var) = input.problem.next_variable(self.curr_depth, &mut self.next_l.states()) {
             // Did the cutoff kick in ?
-            if input.cutoff.must_stop() {
-                return Err(Reason::CutoffOccurred);
-            }
+            self.poll.poll(input.cutoff)?;
+            // Does the compilation still make progress ?
+            let sample = self.next_l.states().next();
+            self.progress.check(input.problem.nb_variables(), var, self.curr_depth, || sample.and_then(|s| input.problem.dump_state(s)))?;
             
-            if !self._move_to_next_layer(input, &mut curr_l) {
+            if !self._move_to_next_layer(input, &mut curr_l)? {
                 break;
             }
 
+            let mask = input.problem.filter_layer(var, &mut curr_l.iter().map(|id| self.nodes[id.0].state.as_ref()));
+            // when the domain does not depend on the state, it is only queried once
+            let mut domain = std::mem::take(&mut self.domain);
+            domain.clear();
+            let shared_domain = input.problem.domain_is_state_independent(var);
+            if let (true, Some(id)) = (shared_domain, curr_l.first()) {
+                input.problem.for_each_in_domain(var, self.nodes[id.0].state.as_ref(), &mut |decision: Decision| {
+                    if mask.allows(decision.value) {
+                        domain.push(decision);
+                    }
+                });
+            }
             for node_id in curr_l.iter() {
                 let state = self.nodes[node_id.0].state.clone();
                 let rub = input.relaxation.fast_upper_bound(state.as_ref());
                 self.nodes[node_id.0].rub = rub;
                 let ub = rub.saturating_add(self.nodes[node_id.0].value_top);
-                if ub > input.best_lb {
-                    input.problem.for_each_in_domain(var, state.as_ref(), &mut |decision| {
-                        self._branch_on(*node_id, decision, input.problem)
+                let mut branches = 0;
+                if ub > input.best_lb && shared_domain {
+                    for decision in domain.iter() {
+                        branches += 1;
+                        self._branch_on(*node_id, *decision, input.problem)
+                    }
+                } else if ub > input.best_lb {
+                    input.problem.for_each_in_domain(var, state.as_ref(), &mut |decision: Decision| {
+                        if mask.allows(decision.value) {
+                            branches += 1;
+                            self._branch_on(*node_id, decision, input.problem)
+                        }
                     })
                 }
+                self.poll.tick(input.cutoff, 1 + branches)?;
             }
+            self.domain = domain;
 
             self.curr_depth += 1;
         }
+        self.scratch = curr_l;
 
-        self._finalize(input);
+        self._record_layer_bytes(self.next_l.len());
+        self._finalize(input)?;
 
         Ok(Completion { 
             is_exact: self.is_exact(), 
-            best_value: self.best_node.map(|n| get!(node n, self).value_top) 
+            best_value: self.best_node.map(|n| get!(node n, self).value_top),
+            guaranteed_within: None,
+            stopped_by: None,
+            achieved_gap: None,
+            termination: None,
         })
     }
 
-    fn _initialize(&mut self, input: &CompilationInput<T>) {
-        self.path_to_root.extend_from_slice(&input.residual.path);
+    fn _initialize(&mut self, input: &CompilationInput<T>, residual: SubProblem<T>) {
+        self.progress.reset(input.problem.nb_variables(), &residual.path);
+        self.width_profile.reset(input.problem);
+        self.path_to_root = residual.path;
+        self.residual = Some(SubProblem { state: residual.state.clone(), path: vec![], ..residual });
         self.edgelists.push(EdgesList::Nil);
 
         let root_node_id = NodeId(0);
         
         let root_node = Node { 
-            state: input.residual.state.clone(), 
-            value_top: input.residual.value, 
-            value_bot: isize::MIN, 
+            state: residual.state.clone(), 
+            value_top: residual.value, 
             best: None, 
             inbound: NIL, 
             rub: isize::MAX, 
-            theta: None,
             flags: NodeFlags::new_exact(), 
-            depth: input.residual.depth,
+            depth: residual.depth,
         };
 
         self.nodes.push(root_node);
-        self.next_l.insert(input.residual.state.clone(), root_node_id);
+        self.next_l.insert(Fingerprint::of(residual.state.as_ref()), residual.state, root_node_id);
         self.edgelists.push(EdgesList::Nil);
-        self.curr_depth = input.residual.depth;
+        self.curr_depth = residual.depth;
     }
 
-    fn _finalize(&mut self, input: &CompilationInput<T>) {
+    fn _finalize(&mut self, input: &CompilationInput<T>) -> Result<(), Reason> {
         self._finalize_layers();
         self._find_best_node();
+        self.bounds.resize(self.nodes.len(), Bounds::default());
         self._finalize_exact(input);
         self._finalize_cutset(input);
-        self._compute_local_bounds(input);
-        self._compute_thresholds(input);
+        self._compute_local_bounds(input)?;
+        self._compute_thresholds(input)
     }
 
 
-    fn _drain_cutset<F>(&mut self, mut func: F)
+    fn _cutset_len(&self) -> usize {
+        if self.best_value().is_none() {
+            return 0;
+        }
+        self.cutset.iter()
+            .filter(|id| self.nodes[id.0].flags.is_marked())
+            .count()
+    }
+
+    fn _for_each_cutset_node<F>(&self, mut func: F)
     where
         F: FnMut(SubProblem<T>),
     {
         if let Some(best_value) = self.best_value() {
-            for id in self.cutset.drain(..) {
+            for id in self.cutset.iter().copied() {
                 let node = get!(node id, self);
 
                 if node.flags.is_marked() {
                     let rub  = node.value_top.saturating_add(node.rub);
-                    let locb = node.value_top.saturating_add(node.value_bot);
+                    let locb = node.value_top.saturating_add(get!(bounds id, self).value_bot);
                     let ub = rub.min(locb).min(best_value);
 
                     func(SubProblem {
@@ -438,6 +846,7 @@ where
                         ),
                         ub,
                         depth: node.depth,
+                        priority_bonus: 0,
                     })
                 }
             }
@@ -445,12 +854,12 @@ where
     }
 
     #[allow(clippy::redundant_closure_call)]
-    fn _compute_local_bounds(&mut self, input: &CompilationInput<T>) {
+    fn _compute_local_bounds(&mut self, input: &CompilationInput<T>) -> Result<(), Reason> {
         if self.lel.unwrap().0 < self.layers.len() && input.comp_type == CompilationType::Relaxed {
             // initialize last layer
             let Layer { from, to } = *get!(layer LayerId(self.layers.len()-1), self);
-            for node in &mut self.nodes[from..to] {
-                node.value_bot = 0;
+            for (node, bounds) in self.nodes[from..to].iter_mut().zip(self.bounds[from..to].iter_mut()) {
+                bounds.value_bot = 0;
                 node.flags.set_marked(true);
             }
 
@@ -458,24 +867,26 @@ where
             // note: cache requires that all nodes have an associated locb. not only those below cutset
             for Layer{from, to} in self.layers.iter().rev().copied() {
                 for id in from..to {
+                    self.poll.tick(input.cutoff, 1)?;
                     let id = NodeId(id);
                     let node = get!(node id, self);
-                    let value = node.value_bot;
+                    let value = get!(bounds id, self).value_bot;
                     if node.flags.is_marked() {
                         foreach!(edge of id, self, |edge: Edge| {
                             let using_edge = value.saturating_add(edge.cost);
-                            let parent = get!(mut node edge.from, self);
-                            parent.flags.set_marked(true);
+                            get!(mut node edge.from, self).flags.set_marked(true);
+                            let parent = get!(mut bounds edge.from, self);
                             parent.value_bot = parent.value_bot.max(using_edge);
                         });
                     }
                 }
             }
         }
+        Ok(())
     }
     
     #[allow(clippy::redundant_closure_call)]
-    fn _compute_thresholds(&mut self, input: &CompilationInput<T>) {
+    fn _compute_thresholds(&mut self, input: &CompilationInput<T>) -> Result<(), Reason> {
         if input.comp_type == CompilationType::Relaxed || self.is_exact {
             let mut best_known = input.best_lb;
 
@@ -484,16 +895,18 @@ where
                 best_known = best_known.max(best_exact_value);
 
                 for id in self.next_l.values() {
-                    if (CUTSET_TYPE == LAST_EXACT_LAYER && self.is_exact) || (CUTSET_TYPE == FRONTIER && self.nodes[id.0].flags.is_exact()) {
-                        self.nodes[id.0].theta = Some(best_known);
+                    if (self.cutset_type == LAST_EXACT_LAYER && self.is_exact) || (self.cutset_type == FRONTIER && self.nodes[id.0].flags.is_exact()) {
+                        self.bounds[id.0].theta = Some(best_known);
                     }
                 }
             }
 
             for Layer{from, to} in self.layers.iter().rev().copied() {
                 for id in from..to {
+                    self.poll.tick(input.cutoff, 1)?;
                     let id = NodeId(id);
-                    let node = get!(mut node id, self);
+                    let node = get!(node id, self);
+                    let bounds = get!(mut bounds id, self);
 
                     if node.flags.is_deleted() {
                         continue;
@@ -503,25 +916,25 @@ where
                     if !node.flags.is_pruned_by_cache() {
                         let tot_rub = node.value_top.saturating_add(node.rub);
                         if tot_rub <= best_known {
-                            node.theta = Some(best_known.saturating_sub(node.rub));
+                            bounds.theta = Some(best_known.saturating_sub(node.rub));
                         } else if node.flags.is_cutset() {
-                            let tot_locb = node.value_top.saturating_add(node.value_bot);
+                            let tot_locb = node.value_top.saturating_add(bounds.value_bot);
                             if tot_locb <= best_known {
-                                let theta = node.theta.unwrap_or(isize::MAX);
-                                node.theta = Some(theta.min(best_known.saturating_sub(node.value_bot)));
+                                let theta = bounds.theta.unwrap_or(isize::MAX);
+                                bounds.theta = Some(theta.min(best_known.saturating_sub(bounds.value_bot)));
                             } else {
-                                node.theta = Some(node.value_top);
+                                bounds.theta = Some(node.value_top);
                             }
-                        } else if node.flags.is_exact() && node.theta.is_none() { // large theta for dangling nodes
-                            node.theta = Some(isize::MAX);
+                        } else if node.flags.is_exact() && bounds.theta.is_none() { // large theta for dangling nodes
+                            bounds.theta = Some(isize::MAX);
                         }
 
-                        Self::_maybe_update_cache(node, input);
+                        Self::_maybe_update_cache(node, bounds.theta, input);
                     }
                     // only propagate if you have an actual threshold
-                    if let Some(my_theta) = node.theta {
+                    if let Some(my_theta) = bounds.theta {
                         foreach!(edge of id, self, |edge: Edge| {
-                            let parent = get!(mut node edge.from, self);
+                            let parent = get!(mut bounds edge.from, self);
                             let theta  = parent.theta.unwrap_or(isize::MAX); 
                             parent.theta = Some(theta.min(my_theta.saturating_sub(edge.cost)));
                         });
@@ -529,11 +942,12 @@ where
                 }
             }
         }
+        Ok(())
     }
 
-    fn _maybe_update_cache(node: &Node<T>, input: &CompilationInput<T>) {
+    fn _maybe_update_cache(node: &Node<T>, theta: Option<isize>, input: &CompilationInput<T>) {
         // A node can only be added to the cache if it belongs to the cutset or is above it
-        if let Some(theta) = node.theta {
+        if let Some(theta) = theta {
             if node.flags.is_above_cutset() {
                 input.cache.update_threshold(
                     node.state.clone(), 
@@ -548,8 +962,9 @@ where
         if self.lel.is_none() {
             self.lel = Some(LayerId(self.layers.len())); // all nodes of the DD are above cutset
         }
+        self.cutset_type = self._choose_cutset_type();
         if input.comp_type == CompilationType::Relaxed || self.is_exact {
-            match CUTSET_TYPE {
+            match self.cutset_type {
                 LAST_EXACT_LAYER => {
                     self._compute_last_exact_layer_cutset(self.lel.unwrap());
                 },
@@ -557,12 +972,33 @@ where
                     self._compute_frontier_cutset();
                 },
                 _ => {
-                    panic!("Only LAST_EXACT_LAYER and FRONTIER are supported so far")
+                    panic!("Only LAST_EXACT_LAYER, FRONTIER and ADAPTIVE are supported so far")
                 }
             }
         }
     }
 
+    /// Returns the index of the first layer that was restricted or relaxed,
+    /// or None when the DD is exact. 
+    fn _first_inexact_layer(&self) -> Option<usize> {
+        self.lel.map(|lel| lel.0 + 1).filter(|first| *first < self.layers.len())
+    }
+
+    /// Decides which cut-set is to be computed for the current compilation. 
+    /// When the relaxation kicks in early, the frontier is spread over many
+    /// shallow layers and most of the subproblems it yields overlap: the last
+    /// exact layer is then the better pick. When it only kicks in deep in the
+    /// DD, the frontier nodes root small subproblems which are worth keeping.
+    fn _choose_cutset_type(&self) -> CutsetType {
+        if CUTSET_TYPE != ADAPTIVE {
+            return CUTSET_TYPE;
+        }
+        match self._first_inexact_layer() {
+            Some(first) if (first as f64) >= self.adaptive_threshold * self.layers.len() as f64 => FRONTIER,
+            _ => LAST_EXACT_LAYER,
+        }
+    }
+
     fn _compute_last_exact_layer_cutset(&mut self, lel: LayerId) {
         if lel.0 < self.layers.len() {
             let Layer { from, to } = *get!(layer lel, self);
@@ -621,13 +1057,11 @@ where
         self.best_node = self
             .next_l
             .values()
-            .copied()
             .max_by_key(|id| get!(node id, self).value_top);
         self.best_exact_node = self
             .next_l
             .values()
             .filter(|id| get!(node id, self).flags.is_exact())
-            .copied()
             .max_by_key(|id| get!(node id, self).value_top);
     }
 
@@ -654,26 +1088,35 @@ where
         }
     }
 
-    fn _move_to_next_layer(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) -> bool {
+    /// Updates the estimated size of the largest layer developed so far
+    fn _record_layer_bytes(&mut self, nb_nodes: usize) {
+        let layer_bytes = nb_nodes * (size_of::<Node<T>>() + arc_bytes::<T>());
+        self.peak_layer_bytes = self.peak_layer_bytes.max(layer_bytes);
+    }
+    fn _move_to_next_layer(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) -> Result<bool, Reason> {
         self.prev_l.clear();
 
         for id in curr_l.drain(..) {
             self.prev_l.push(id);
         }
-        for (_, id) in self.next_l.drain() {
+        for id in self.next_l.drain_values() {
             curr_l.push(id);
         }
+        self._record_layer_bytes(curr_l.len());
 
         if curr_l.is_empty() {
             self.layers.push(Layer { from: 0, to: 0 });
-            false
+            Ok(false)
         } else {
+            let unpruned = curr_l.len();
             if !self.layers.is_empty() {
                 self._filter_with_cache(input, curr_l);
             }
             self._filter_with_dominance(input, curr_l);
+            self._collapse_feasibility_classes(input, curr_l);
+            self.pruned_layer |= curr_l.len() < unpruned;
 
-            self._squash_if_needed(input, curr_l);
+            self._squash_if_needed(input, curr_l)?;
             
             if self.layers.is_empty() {
                 self.layers.push(Layer { from: 0, to: self.nodes.len() });
@@ -682,7 +1125,7 @@ where
                 let layer = get!(layer id, self);
                 self.layers.push(Layer { from: layer.to, to: self.nodes.len() });
             }
-            true
+            Ok(true)
         }
     }
 
@@ -693,7 +1136,7 @@ where
             if node.flags.is_exact() {
                 let DominanceCheckResult { dominated, threshold } = input.dominance.is_dominated_or_insert(node.state.clone(), node.depth, node.value_top);
                 if dominated {
-                    node.theta = threshold;
+                    get!(mut bounds id, self).theta = threshold;
                     false
                 } else {
                     true
@@ -704,6 +1147,41 @@ where
         });
     }
     
+    fn _collapse_feasibility_classes(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
+        if input.comp_type == CompilationType::Exact {
+            return;
+        }
+        match input.problem.objective_depth() {
+            Some(depth) if self.curr_depth >= depth => {},
+            _ => return,
+        }
+
+        self._sort_layer(input, curr_l);
+
+        // past the objective depth, the best node of each class dominates all
+        // the other nodes of that class
+        let mut kept = FxHashMap::<u64, isize>::default();
+        curr_l.retain(|id| {
+            let node = get!(mut node id, self);
+            if !node.flags.is_exact() {
+                return true;
+            }
+            match input.problem.feasibility_class(node.state.as_ref()) {
+                None => true,
+                Some(class) => match kept.entry(class) {
+                    Entry::Vacant(e) => {
+                        e.insert(node.value_top);
+                        true
+                    },
+                    Entry::Occupied(e) => {
+                        get!(mut bounds id, self).theta = Some(*e.get()); // set theta for later propagation
+                        false
+                    }
+                }
+            }
+        });
+    }
+
     fn _filter_with_cache(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
         curr_l.retain(|id| {
             let node = get!(mut node id, self);
@@ -713,7 +1191,7 @@ where
                     true
                 } else {
                     node.flags.set_pruned_by_cache(true);
-                    node.theta = Some(threshold.value); // set theta for later propagation
+                    get!(mut bounds id, self).theta = Some(threshold.value); // set theta for later propagation
                     false
                 }
             } else {
@@ -729,26 +1207,25 @@ where
         problem: &dyn Problem<State = T>,
     ) {
         let state = get!(node from_id, self).state.as_ref();
-        let next_state = Arc::new(problem.transition(state, decision));
-        let cost = problem.transition_cost(state, next_state.as_ref(), decision);
+        let next_state = Arc::new(problem.transition_with_ctx(state, decision, &mut self.ctx));
+        let cost = problem.transition_cost_with_ctx(state, next_state.as_ref(), decision, &mut self.ctx);
 
-        match self.next_l.entry(next_state.clone()) {
-            Entry::Vacant(e) => {
+        let fingerprint = Fingerprint::of(next_state.as_ref());
+        match self.next_l.get(fingerprint, next_state.as_ref(), problem) {
+            None => {
                 let parent = get!(node from_id, self);
                 let node_id = NodeId(self.nodes.len());
                 let mut flags = NodeFlags::new_exact();
-                flags.set_exact(parent.flags.is_exact());
+                flags.set_exact(self.exactness.child_exactness(parent.flags.is_exact(), false, self.pruned_layer));
 
                 self.nodes.push(Node {
-                    state: next_state,
+                    state: next_state.clone(),
                     value_top: parent.value_top.saturating_add(cost),
-                    value_bot: isize::MIN,
                     //
                     best: None,
                     inbound: NIL,
                     //
                     rub: isize::MAX,
-                    theta: None,
                     flags,
                     depth: parent.depth + 1,
                 });
@@ -758,10 +1235,9 @@ where
                     decision,
                     cost,
                 });
-                e.insert(node_id);
+                self.next_l.insert(fingerprint, next_state, node_id);
             }
-            Entry::Occupied(e) => {
-                let node_id = *e.get();
+            Some(node_id) => {
                 append_edge_to!(self, Edge {
                     from: from_id,
                     to  : node_id,
@@ -773,22 +1249,28 @@ where
     }
 
 
-    fn _squash_if_needed(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
+    /// Returns the maximum width of the layer currently being developed (the
+    /// width of the heuristic scaled by the width profile of the problem)
+    fn _layer_width(&self, input: &CompilationInput<T>) -> usize {
+        self.width_profile.width(input.max_width, self.curr_depth)
+    }
+
+    fn _squash_if_needed(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) -> Result<(), Reason> {
+        let width = self._layer_width(input);
         match input.comp_type {
             CompilationType::Exact => { /* do nothing: you want to explore the complete DD */ }
             CompilationType::Restricted => {
-                if curr_l.len() > input.max_width {
-                    self._maybe_save_lel();
-                    self._restrict(input, curr_l)
+                if curr_l.len() > width {
+                    self._restrict(input, curr_l)?;
                 }
             },
             CompilationType::Relaxed => {
-                if curr_l.len() > input.max_width && self.layers.len() > 1 {
-                    self._maybe_save_lel();
-                    self._relax(input, curr_l)
+                if curr_l.len() > width && self.layers.len() > 1 {
+                    self._relax(input, curr_l)?;
                 }
             },
         }
+        Ok(())
     }
     fn _maybe_save_lel(&mut self) {
         if self.lel.is_none() {
@@ -796,33 +1278,101 @@ where
         }
     }
 
-    fn _restrict(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
-        curr_l.sort_unstable_by(|a, b| {
+    /// Sorts the nodes of the current layer from the most to the least 
+    /// promising one. When the ranking check is enabled, a panic of the state
+    /// ranking is reported along with the last pair of nodes it compared.
+    fn _sort_layer(&self, input: &CompilationInput<T>, curr_l: &mut [NodeId]) {
+        let last = Cell::new(None);
+        guard_ranking(|| curr_l.sort_unstable_by(|a, b| {
+            if RANKING_CHECK {
+                last.set(Some((*a, *b)));
+            }
             get!(node a, self).value_top
                 .cmp(&get!(node b, self).value_top)
                 .then_with(|| input.ranking.compare(get!(node a, self).state.as_ref(), get!(node b, self).state.as_ref()))
                 .reverse()
-        }); // reverse because greater means more likely to be kept
+        }), // reverse because greater means more likely to be kept
+        || {
+            let root = get!(node NodeId(0), self).depth;
+            match last.get() {
+                Some((a, b)) => (
+                    format!("nodes at depth {} of the subproblem rooted at depth {root}", get!(node a, self).depth),
+                    input.ranking.dump_state(get!(node a, self).state.as_ref())
+                        .zip(input.ranking.dump_state(get!(node b, self).state.as_ref()))
+                ),
+                None => (format!("nodes of the subproblem rooted at depth {root}"), None),
+            }
+        });
+    }
+
+    /// Moves the nodes which must be kept (see `StateRanking::must_keep`) 
+    /// ahead of the sorted layer, provided that they fit in the `slots` which
+    /// survive the trimming of the layer or that the width conflict policy 
+    /// lets the layer exceed its width. The relative order of the nodes is
+    /// preserved otherwise. It returns the number of nodes which survive the 
+    /// trimming of the layer.
+    fn _promote_kept_nodes(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>, slots: usize) -> Result<usize, Reason> {
+        let must_keep = |id: &NodeId| input.ranking.must_keep(get!(node id, self).state.as_ref());
+        let nb_kept = curr_l.iter().filter(|id| must_keep(id)).count();
+        if nb_kept == 0 {
+            return Ok(slots);
+        }
+        let conflict = nb_kept > slots;
+        if !conflict || self.width_conflict == WidthConflictPolicy::ExceedWidth {
+            let (mut kept, others): (Vec<NodeId>, Vec<NodeId>) = curr_l.iter().partition(|id| must_keep(id));
+            kept.extend(others);
+            *curr_l = kept;
+        }
+        if conflict {
+            self.keep_list_overflow = true;
+            self.width_conflicts += 1;
+            match self.width_conflict {
+                WidthConflictPolicy::ExceedWidth => {},
+                WidthConflictPolicy::MergeExact => return Ok(slots),
+                WidthConflictPolicy::Error => {
+                    let depth = get!(node curr_l[0], self).depth;
+                    return Err(Reason::WidthConflict { depth, flagged: nb_kept, slots });
+                },
+            }
+        }
+        Ok(slots.max(nb_kept))
+    }
+
+    fn _restrict(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) -> Result<(), Reason> {
+        let width = self._layer_width(input);
+        self._sort_layer(input, curr_l);
+        let width = self._promote_kept_nodes(input, curr_l, width)?;
+        // all the nodes are kept when they were all flagged (see `WidthConflictPolicy`)
+        if curr_l.len() <= width {
+            return Ok(());
+        }
+        self.pruned_layer = true;
+        self._maybe_save_lel();
 
-        for drop_id in curr_l.iter().skip(input.max_width).copied() {
+        for drop_id in curr_l.iter().skip(width).copied() {
             get!(mut node drop_id, self).flags.set_deleted(true);
+            self.poll.tick(input.cutoff, 1)?;
         }
 
-        curr_l.truncate(input.max_width);
+        curr_l.truncate(width);
+        Ok(())
     }
 
     #[allow(clippy::redundant_closure_call)]
-    fn _relax(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
-        curr_l.sort_unstable_by(|a, b| {
-            get!(node a, self).value_top
-                .cmp(&get!(node b, self).value_top)
-                .then_with(|| input.ranking.compare(get!(node a, self).state.as_ref(), get!(node b, self).state.as_ref()))
-                .reverse()
-        }); // reverse because greater means more likely to be kept
+    fn _relax(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) -> Result<(), Reason> {
+        let width = self._layer_width(input);
+        self._sort_layer(input, curr_l);
+        let slots = self._promote_kept_nodes(input, curr_l, width - 1)?;
 
         //--
-        let (keep, merge) = curr_l.split_at_mut(input.max_width - 1);
-        let merged = Arc::new(input.relaxation.merge(&mut merge.iter().map(|id| get!(node id, self).state.as_ref())));
+        let (keep, merge) = curr_l.split_at_mut(slots);
+        // a group of one node loses nothing: it stays as is (and exact)
+        if merge.len() < 2 {
+            return Ok(());
+        }
+        self._maybe_save_lel();
+        let states = merge.iter().map(|id| get!(node id, self).state.as_ref()).collect::<Vec<_>>();
+        let merged = Arc::new(merge_by_chunks(input.relaxation, input.cutoff, self.merge_chunk_size, &states)?);
 
         let recycled = keep.iter().find(|id| get!(node *id, self).state.eq(&merged)).copied();
 
@@ -831,12 +1381,10 @@ where
             self.nodes.push(Node {
                 state: merged.clone(),
                 value_top: isize::MIN,
-                value_bot: isize::MIN,
                 best: None,    // yet
                 inbound: NIL,  // yet
                 //
                 rub: isize::MAX,
-                theta: None,
                 flags: NodeFlags::new_relaxed(),
                 depth: get!(node merge[0], self).depth,
             });
@@ -848,7 +1396,9 @@ where
         for drop_id in merge {
             get!(mut node drop_id, self).flags.set_deleted(true);
 
+            let mut rerouted = 0;
             foreach!(edge of drop_id, self, |edge: Edge| {
+                rerouted += 1;
                 let src   = get!(node edge.from, self).state.as_ref();
                 let dst   = get!(node edge.to,   self).state.as_ref();
                 let rcost = input.relaxation.relax(src, dst, merged.as_ref(), edge.decision, edge.cost);
@@ -860,16 +1410,18 @@ where
                     cost: rcost
                 });
             });
+            self.poll.tick(input.cutoff, 1 + rerouted)?;
         }
 
         if recycled.is_some() {
-            curr_l.truncate(input.max_width);
-            let saved_id = curr_l[input.max_width - 1];
+            curr_l.truncate(slots + 1);
+            let saved_id = curr_l[slots];
             self.nodes[saved_id.0].flags.set_deleted(false);
         } else {
-            curr_l.truncate(input.max_width - 1);
+            curr_l.truncate(slots);
             curr_l.push(merged_id);
         }
+        Ok(())
     }
 }
 
@@ -1016,7 +1568,7 @@ where T: Debug + Eq + PartialEq + Hash + Clone {
         let color = Self::node_color(node, merged);
         let peripheries = Self::node_peripheries(node);
         let group = self.node_group(node);
-        let label = Self::node_label(node, state, config);
+        let label = Self::node_label(node, &self.bounds.get(id).copied().unwrap_or_default(), state, config);
 
         format!("shape={shape},style=filled,color={color},peripheries={peripheries},group=\"{group}\",label=\"{label}\"")
     }
@@ -1058,20 +1610,20 @@ where T: Debug + Eq + PartialEq + Hash + Clone {
         }
     }
     /// Creates text label to place inside of the node when displaying it
-    fn node_label(node: &Node<T>, state: &T, config: &VizConfig) -> String {
+    fn node_label(node: &Node<T>, bounds: &Bounds, state: &T, config: &VizConfig) -> String {
         let mut out = format!("{state:?}");
 
         if config.show_value {
             out.push_str(&format!("\\nval: {}", node.value_top));
         }
         if config.show_locb {
-        out.push_str(&format!("\\nlocb: {}", Self::extreme(node.value_bot)));
+        out.push_str(&format!("\\nlocb: {}", Self::extreme(bounds.value_bot)));
         }
         if config.show_rub {
             out.push_str(&format!("\\nrub: {}", Self::extreme(node.rub)));
         }
         if config.show_threshold {
-            out.push_str(&format!("\\ntheta: {}", Self::extreme(node.theta.unwrap_or(isize::MAX))));
+            out.push_str(&format!("\\ntheta: {}", Self::extreme(bounds.theta.unwrap_or(isize::MAX))));
         }
 
         out
@@ -1093,16 +1645,21 @@ where T: Debug + Eq + PartialEq + Hash + Clone {
 
 #[cfg(test)]
 mod test_default_mdd {
+    use std::cell::{Cell, RefCell};
     use std::cmp::Ordering;
     use std::sync::Arc;
+    use std::time::{Duration, Instant};
 
     use fxhash::FxHashMap;
 
-    use crate::{Variable, DecisionDiagram, SubProblem, CompilationInput, Problem, Decision, Relaxation, StateRanking, NoCutoff, CompilationType, Cutoff, Reason, DecisionCallback, EmptyCache, SimpleCache, Cache, LAST_EXACT_LAYER, Mdd, FRONTIER, VizConfigBuilder, Threshold, EmptyDominanceChecker};
+    use crate::{Variable, CutsetConsumed, CutsetState, DecisionDiagram, Solver, SubProblem, CompilationInput, Problem, Decision, Relaxation, StateRanking, NoCutoff, CompilationType, Cutoff, Reason, DecisionCallback, EmptyCache, SimpleCache, Cache, LAST_EXACT_LAYER, Mdd, ProblemCtx, FRONTIER, ADAPTIVE, MddStats, VizConfigBuilder, Threshold, EmptyDominanceChecker, ValueMask, WidthConflictPolicy, ExactnessPolicy, SequentialSolver, SimpleFringe, MaxUB, FixedWidth};
+
+    use super::NodeId;
 
     type DefaultMDD<State>    = DefaultMDDLEL<State>;
     type DefaultMDDLEL<State> = Mdd<State, {LAST_EXACT_LAYER}>;
     type DefaultMDDFC<State>  = Mdd<State, {FRONTIER}>;
+    type DefaultMDDAdaptive<State> = Mdd<State, {ADAPTIVE}>;
 
     #[test]
     fn by_default_the_mdd_type_is_exact() {
@@ -1115,6 +1672,14 @@ mod test_default_mdd {
     fn root_remembers_the_pa_from_the_fringe_node() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 1, value: 42}), 
+            value: 42, 
+            path:  vec![Decision{variable: Variable(0), value: 42}], 
+            ub:    isize::MAX,
+            depth: 1,
+            priority_bonus: 0,
+        };
         let mut input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1123,35 +1688,69 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  3,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 1, value: 42}), 
-                value: 42, 
-                path:  vec![Decision{variable: Variable(0), value: 42}], 
-                ub:    isize::MAX,
-                depth: 1,
-            },
             cache: &cache,
             dominance: &dominance,
         };
 
         let mut mdd = DefaultMDD::new();
-        assert!(mdd.compile(&input).is_ok());
+        assert!(mdd.compile(&input, residual.clone()).is_ok());
         assert_eq!(mdd.path_to_root, vec![Decision{variable: Variable(0), value: 42}]);
 
         input.comp_type = CompilationType::Relaxed;
-        assert!(mdd.compile(&input).is_ok());
+        assert!(mdd.compile(&input, residual.clone()).is_ok());
         assert_eq!(mdd.path_to_root, vec![Decision{variable: Variable(0), value: 42}]);
 
         input.comp_type = CompilationType::Restricted;
-        assert!(mdd.compile(&input).is_ok());
+        assert!(mdd.compile(&input, residual).is_ok());
         assert_eq!(mdd.path_to_root, vec![Decision{variable: Variable(0), value: 42}]);
     }
     
+    #[test]
+    fn compiling_moves_the_residual_into_the_root_layer() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 1, value: 42}), 
+            value: 42, 
+            path:  vec![Decision{variable: Variable(0), value: 42}], 
+            ub:    isize::MAX,
+            depth: 1,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  3,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let state = residual.state.clone();
+        let path  = residual.path.as_ptr();
+
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
+        // neither the path nor the state have been copied
+        assert_eq!(path, mdd.path_to_root.as_ptr());
+        assert!(Arc::ptr_eq(&state, &mdd.nodes[0].state));
+    }
+
     // In an exact setup, the dummy problem would be 3*3*3 = 9 large at the bottom level
     #[test]
     fn exact_completely_unrolls_the_mdd_no_matter_its_width() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1160,19 +1759,12 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
 
-        assert!(mdd.compile(&input).is_ok());
+        assert!(mdd.compile(&input, residual).is_ok());
         assert!(mdd.best_solution().is_some());
         assert_eq!(mdd.best_value(), Some(6));
         assert_eq!(mdd.best_solution().unwrap(),
@@ -1184,10 +1776,86 @@ mod test_default_mdd {
         );
     }
 
+    // The widest layer of the exact dummy mdd is the terminal one: it holds the 7 values 0..=6
+    #[test]
+    fn the_peak_layer_estimate_accounts_for_each_node_of_the_widest_layer() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert_eq!(0, mdd.peak_layer_bytes());
+
+        assert!(mdd.compile(&input, residual).is_ok());
+        let per_node = std::mem::size_of::<super::Node<DummyState>>() + crate::arc_bytes::<DummyState>();
+        assert_eq!(7 * per_node, mdd.peak_layer_bytes());
+    }
+
+    #[test]
+    fn each_exact_terminal_node_yields_a_solution() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        let mut values = vec![];
+        mdd.for_each_exact_solution(&mut |value, solution| {
+            assert_eq!(3, solution.len());
+            assert_eq!(value, solution.iter().map(|d| d.value).sum::<isize>());
+            values.push(value);
+        });
+        values.sort_unstable();
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 6], values);
+    }
+
     #[test]
     fn restricted_drops_the_less_interesting_nodes() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1196,19 +1864,12 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
 
-        assert!(mdd.compile(&input).is_ok());
+        assert!(mdd.compile(&input, residual).is_ok());
         assert!(mdd.best_solution().is_some());
         assert_eq!(mdd.best_value().unwrap(), 6);
         assert_eq!(mdd.best_solution().unwrap(),
@@ -1224,6 +1885,14 @@ mod test_default_mdd {
     fn exact_no_cutoff_completion_must_be_coherent_with_outcome() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1232,18 +1901,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
 
         assert!(result.is_ok());
         let completion = result.unwrap();
@@ -1254,6 +1916,14 @@ mod test_default_mdd {
     fn restricted_no_cutoff_completion_must_be_coherent_with_outcome_() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1262,18 +1932,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         
         assert!(result.is_ok());
         let completion = result.unwrap();
@@ -1284,6 +1947,14 @@ mod test_default_mdd {
     fn relaxed_no_cutoff_completion_must_be_coherent_with_outcome() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1292,18 +1963,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         
         assert!(result.is_ok());
         let completion = result.unwrap();
@@ -1320,6 +1984,14 @@ mod test_default_mdd {
     fn exact_fails_with_cutoff_when_cutoff_occurs() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1328,18 +2000,11 @@ mod test_default_mdd {
             cutoff:     &CutoffAlways,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_err());
         assert_eq!(Some(Reason::CutoffOccurred), result.err());
     }
@@ -1348,6 +2013,14 @@ mod test_default_mdd {
     fn restricted_fails_with_cutoff_when_cutoff_occurs() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1356,18 +2029,11 @@ mod test_default_mdd {
             cutoff:     &CutoffAlways,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_err());
         assert_eq!(Some(Reason::CutoffOccurred), result.err());
     }
@@ -1375,6 +2041,14 @@ mod test_default_mdd {
     fn relaxed_fails_with_cutoff_when_cutoff_occurs() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1383,26 +2057,183 @@ mod test_default_mdd {
             cutoff:     &CutoffAlways,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_err());
         assert_eq!(Some(Reason::CutoffOccurred), result.err());
     }
 
+    /// A buggy problem whose next_variable never returns None and keeps
+    /// branching on the very same variable
+    #[derive(Copy, Clone)]
+    struct SelfLoopingProblem;
+    impl Problem for SelfLoopingProblem {
+        type State = DummyState;
+
+        fn nb_variables(&self)  -> usize { 3 }
+        fn initial_value(&self) -> isize { 0 }
+        fn initial_state(&self) -> Self::State {
+            DummyState { value: 0, depth: 0 }
+        }
+        fn transition(&self, state: &Self::State, decision: crate::Decision) -> Self::State {
+            DummyState { value: state.value + decision.value, depth: 1 + state.depth }
+        }
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, decision: crate::Decision) -> isize {
+            decision.value
+        }
+        fn next_variable(&self, _: usize, _: &mut dyn Iterator<Item = &Self::State>)
+            -> Option<crate::Variable> {
+            Some(Variable(0))
+        }
+        fn for_each_in_domain(&self, var: crate::Variable, _: &Self::State, f: &mut dyn DecisionCallback) {
+            f.apply(Decision {variable: var, value: 1})
+        }
+        fn dump_state(&self, state: &Self::State) -> Option<String> {
+            Some(format!("{state:?}"))
+        }
+    }
+    fn compile_self_looping(comp_type: CompilationType) -> Result<crate::Completion, Reason> {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type,
+            problem:    &SelfLoopingProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  10,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        mdd.compile(&input, residual)
+    }
+    #[cfg(not(feature = "progress-check"))]
+    #[test]
+    fn a_self_looping_problem_fails_when_the_layer_limit_is_exceeded() {
+        for comp_type in [CompilationType::Exact, CompilationType::Restricted, CompilationType::Relaxed] {
+            let result = compile_self_looping(comp_type);
+            assert_eq!(Some(Reason::LayerLimitExceeded { variable: Variable(0), depth: 3 }), result.err());
+        }
+    }
+    #[cfg(feature = "progress-check")]
+    #[test]
+    fn a_self_looping_problem_fails_the_progress_check() {
+        for comp_type in [CompilationType::Exact, CompilationType::Restricted, CompilationType::Relaxed] {
+            let result = compile_self_looping(comp_type);
+            assert_eq!(Some(Reason::NoProgress { 
+                    variable: Variable(0), 
+                    depth: 1, 
+                    state: Some("DummyState { value: 1, depth: 1 }".to_string()) 
+                }), 
+                result.err());
+        }
+    }
+
+    /// The same problem as `DummyProblem`, except that all decisions are free.
+    /// Hence, all the nodes of a layer have the same value and must be ranked.
+    #[derive(Copy, Clone)]
+    struct FreeProblem;
+    impl Problem for FreeProblem {
+        type State = DummyState;
+
+        fn nb_variables(&self)  -> usize { DummyProblem.nb_variables() }
+        fn initial_value(&self) -> isize { 0 }
+        fn initial_state(&self) -> Self::State { DummyProblem.initial_state() }
+        fn transition(&self, state: &Self::State, decision: crate::Decision) -> Self::State {
+            DummyProblem.transition(state, decision)
+        }
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, _: crate::Decision) -> isize {
+            0
+        }
+        fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+            -> Option<crate::Variable> {
+            DummyProblem.next_variable(depth, next_layer)
+        }
+        fn for_each_in_domain(&self, var: crate::Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            DummyProblem.for_each_in_domain(var, state, f)
+        }
+    }
+    /// A ranking which cannot compare anything
+    struct PanickingRanking;
+    impl StateRanking for PanickingRanking {
+        type State = DummyState;
+
+        fn compare(&self, _: &Self::State, _: &Self::State) -> Ordering {
+            panic!("cannot rank these states")
+        }
+        fn dump_state(&self, state: &Self::State) -> Option<String> {
+            Some(format!("{state:?}"))
+        }
+    }
+    fn compile_with_panicking_ranking(comp_type: CompilationType) -> Result<crate::Completion, Reason> {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type,
+            problem:    &FreeProblem,
+            relaxation: &DummyRelax,
+            ranking:    &PanickingRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        mdd.compile(&input, residual)
+    }
+    #[cfg(not(feature = "ranking-check"))]
+    #[test]
+    #[should_panic(expected = "cannot rank these states")]
+    fn a_panicking_ranking_propagates_its_panic() {
+        let _ = compile_with_panicking_ranking(CompilationType::Restricted);
+    }
+    #[cfg(feature = "ranking-check")]
+    #[test]
+    fn a_panicking_ranking_is_reported_with_the_nodes_it_compared() {
+        for comp_type in [CompilationType::Restricted, CompilationType::Relaxed] {
+            let err = std::panic::catch_unwind(|| compile_with_panicking_ranking(comp_type)).unwrap_err();
+            let msg = err.downcast_ref::<String>().unwrap();
+            assert!(msg.starts_with("node ordering panicked while comparing nodes at depth "), "{msg}");
+            assert!(msg.contains(" of the subproblem rooted at depth 0: cannot rank these states\n"), "{msg}");
+            assert!(msg.contains("\n  left : DummyState { "), "{msg}");
+            assert!(msg.contains("\n  right: DummyState { "), "{msg}");
+        }
+    }
+
     #[test]
     fn relaxed_merges_the_less_interesting_nodes() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1411,18 +2242,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
 
         assert!(result.is_ok());
         assert!(mdd.best_solution().is_some());
@@ -1440,6 +2264,14 @@ mod test_default_mdd {
     fn relaxed_populates_the_cutset_and_will_not_squash_first_layer() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1448,18 +2280,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         let mut cutset = vec![];
@@ -1468,67 +2293,110 @@ mod test_default_mdd {
     }
 
     #[test]
-    fn an_exact_mdd_must_be_exact() {
+    fn a_panicking_callback_leaves_no_half_drained_cutset() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
-            comp_type: crate::CompilationType::Exact,
+            comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
             relaxation: &DummyRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        
-        assert!(mdd.is_exact())
+        assert!(mdd.compile(&input, residual.clone()).is_ok());
+        let mut expected = vec![];
+        mdd.for_each_cutset_node(|n| expected.push(n));
+
+        let mut seen = 0;
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| 
+            mdd.drain_cutset(|_| {
+                seen += 1;
+                if seen == 2 {
+                    panic!("a bug in the bookkeeping of the caller");
+                }
+            })
+        ));
+        assert!(outcome.is_err());
+        assert_eq!(0, mdd.cutset_len());
+        let mut leftovers = vec![];
+        mdd.drain_cutset(|n| leftovers.push(n));
+        assert!(leftovers.is_empty());
+
+        // the dd can be compiled again and yields the very same cutset
+        assert!(mdd.compile(&input, residual).is_ok());
+        let mut drained = vec![];
+        mdd.drain_cutset(|n| drained.push(n));
+        assert_eq!(expected, drained);
     }
 
     #[test]
-    fn a_relaxed_mdd_is_exact_as_long_as_no_merge_occurs() {
+    fn the_cutset_can_be_inspected_before_it_is_drained() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
             relaxation: &DummyRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  10,
+            max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        
-        assert!(mdd.is_exact())
+        assert!(mdd.compile(&input, residual).is_ok());
+        assert_eq!(3, mdd.cutset_len());
+
+        let mut first = vec![];
+        mdd.for_each_cutset_node(|n| first.push(n));
+        let mut second = vec![];
+        mdd.for_each_cutset_node(|n| second.push(n));
+        let mut drained = vec![];
+        mdd.drain_cutset(|n| drained.push(n));
+
+        assert_eq!(3, first.len());
+        assert_eq!(first, second);
+        assert_eq!(first, drained);
+        assert_eq!(0, mdd.cutset_len());
     }
 
-    #[test]
-    fn a_relaxed_mdd_is_not_exact_when_a_merge_occurred() {
+    /// Drains the cut-set of a relaxed DD of type `D` several times and checks
+    /// that each of its nodes is delivered exactly once
+    fn check_the_cutset_is_delivered_once<D>() 
+    where D: DecisionDiagram<State = DummyState> + Default
+    {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1537,418 +2405,569 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
-        let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        
-        assert!(!mdd.is_exact())
+        let mut mdd = D::default();
+        assert!(mdd.compile(&input, residual.clone()).is_ok());
+        assert_eq!(CutsetState::Fresh, mdd.cutset_state());
+
+        let mut inspected = vec![];
+        assert_eq!(Ok(()), mdd.try_for_each_cutset_node(|n| inspected.push(n)));
+        assert!(!inspected.is_empty());
+        assert_eq!(CutsetState::Fresh, mdd.cutset_state());
+
+        let mut drained = vec![];
+        mdd.drain_cutset(|n| drained.push(n));
+        mdd.drain_cutset(|n| drained.push(n));
+        mdd.for_each_cutset_node(|n| drained.push(n));
+        assert_eq!(inspected, drained);
+        assert_eq!(CutsetState::Consumed, mdd.cutset_state());
+        assert_eq!(0, mdd.cutset_len());
+        assert_eq!(Err(CutsetConsumed), mdd.try_for_each_cutset_node(|n| drained.push(n)));
+        assert_eq!(inspected, drained);
+
+        // compiling the dd again yields a fresh cutset
+        assert!(mdd.compile(&input, residual).is_ok());
+        assert_eq!(CutsetState::Fresh, mdd.cutset_state());
+        let mut again = vec![];
+        mdd.drain_cutset(|n| again.push(n));
+        assert_eq!(inspected, again);
     }
     #[test]
-    fn a_restricted_mdd_is_exact_as_long_as_no_restriction_occurs() {
+    fn the_last_exact_layer_cutset_is_delivered_once() {
+        check_the_cutset_is_delivered_once::<DefaultMDDLEL<DummyState>>();
+    }
+    #[test]
+    fn the_frontier_cutset_is_delivered_once() {
+        check_the_cutset_is_delivered_once::<DefaultMDDFC<DummyState>>();
+    }
+    #[test]
+    fn the_adaptive_cutset_is_delivered_once() {
+        check_the_cutset_is_delivered_once::<Mdd<DummyState, {ADAPTIVE}>>();
+    }
+
+    #[test]
+    fn an_exact_mdd_must_be_exact() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
-            comp_type: crate::CompilationType::Restricted,
+            comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
             relaxation: &DummyRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  10,
+            max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         assert!(mdd.is_exact())
     }
+
     #[test]
-    fn a_restricted_mdd_is_not_exact_when_a_restriction_occurred() {
+    fn merging_by_chunks_yields_the_same_bound_when_the_merge_is_associative() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
-            relaxation: &DummyRelax,
+            relaxation: &DummyMaxRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  1,
+            max_width:  2,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        
-        assert!(!mdd.is_exact())
+        assert!(mdd.compile(&input, residual.clone()).is_ok());
+        let at_once = mdd.best_value();
+
+        let mut mdd = DefaultMDD::new().with_merge_chunk_size(2);
+        assert!(mdd.compile(&input, residual).is_ok());
+        let by_chunks = mdd.best_value();
+
+        assert!(mdd.stats().first_inexact_layer.is_some());
+        assert_eq!(at_once, by_chunks);
+        assert!(by_chunks.unwrap() >= 6);
     }
+
     #[test]
-    fn when_the_problem_is_infeasible_there_is_no_solution() {
+    fn the_best_state_is_reached_by_replaying_the_best_solution() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
-            problem:    &DummyInfeasibleProblem,
+            problem:    &DummyProblem,
             relaxation: &DummyRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
             max_width:  usize::MAX,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        assert!(mdd.best_solution().is_none())
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        let replayed = mdd.best_solution().unwrap().iter()
+            .fold(DummyProblem.initial_state(), |state, decision| DummyProblem.transition(&state, *decision));
+        assert_eq!(Some(&replayed), mdd.best_state());
+        assert_eq!(Some(&replayed), mdd.best_exact_state());
     }
+
     #[test]
-    fn when_the_problem_is_infeasible_there_is_no_best_value() {
+    fn the_best_decisions_are_streamed_in_the_order_of_the_best_solution() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![Decision{variable: Variable(10), value: 4}, Decision{variable: Variable(11), value: 2}], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
-            comp_type: crate::CompilationType::Exact,
-            problem:    &DummyInfeasibleProblem,
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
             relaxation: &DummyRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  usize::MAX,
+            max_width:  2,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        assert!(mdd.best_value().is_none())
+        let mut streamed = vec![];
+        mdd.for_each_best_decision(&mut |d| streamed.push(d));
+        assert!(streamed.is_empty());
+
+        assert!(mdd.compile(&input, residual).is_ok());
+        mdd.for_each_best_decision(&mut |d| streamed.push(d));
+        assert_eq!(mdd.best_solution().unwrap(), streamed);
+        assert_eq!(&[Decision{variable: Variable(10), value: 4}, Decision{variable: Variable(11), value: 2}], &streamed[..2]);
     }
+
     #[test]
-    fn exact_skips_node_with_an_ub_less_than_best_known_lb() {
+    fn the_nodes_which_must_be_kept_survive_the_restriction() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
-            comp_type: crate::CompilationType::Exact,
+            comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
             relaxation: &DummyRelax,
-            ranking:    &DummyRanking,
+            ranking:    &KeepAtMost(0),
             cutoff:     &NoCutoff,
-            max_width:  usize::MAX,
-            best_lb:    1000,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+            max_width:  2,
+            best_lb:    isize::MIN,
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        assert!(mdd.best_solution().is_none())
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        // the second layer keeps the nodes with value 0 and 4 (instead of 3 and 4)
+        let mut values = vec![];
+        mdd.for_each_exact_solution(&mut |value, _| values.push(value));
+        values.sort_unstable();
+        assert_eq!(vec![0, 1, 2, 4, 5, 6], values);
+        assert!(!mdd.stats().keep_list_overflow);
     }
+
     #[test]
-    fn relaxed_skips_node_with_an_ub_less_than_best_known_lb() {
+    fn the_nodes_which_must_be_kept_are_not_merged() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
-            relaxation: &DummyRelax,
-            ranking:    &DummyRanking,
+            relaxation: &DummyMaxRelax,
+            ranking:    &KeepAtMost(0),
             cutoff:     &NoCutoff,
-            max_width:  usize::MAX,
-            best_lb:    1000,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+            max_width:  2,
+            best_lb:    isize::MIN,
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        assert!(mdd.best_solution().is_none())
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        // the only exact node of the second layer is the one with value 0
+        assert_eq!(Some(2), mdd.best_exact_value());
+        assert_eq!(Some(6), mdd.best_value());
+        assert!(!mdd.stats().keep_list_overflow);
     }
+
     #[test]
-    fn restricted_skips_node_with_an_ub_less_than_best_known_lb() {
+    fn the_layer_is_trimmed_as_usual_when_too_many_nodes_must_be_kept() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
             relaxation: &DummyRelax,
-            ranking:    &DummyRanking,
+            ranking:    &KeepAtMost(2),
             cutoff:     &NoCutoff,
-            max_width:  usize::MAX,
-            best_lb:    1000,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+            max_width:  2,
+            best_lb:    isize::MIN,
             cache: &cache,
             dominance: &dominance,
         };
-        let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        assert!(mdd.best_solution().is_none())
+        let mut mdd = DefaultMDD::new().with_width_conflict_policy(WidthConflictPolicy::MergeExact);
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        // the three nodes of the first layer must be kept but only two fit: the
+        // nodes with value 1 and 2 are kept as usual. Both fit in the second layer.
+        let mut values = vec![];
+        mdd.for_each_exact_solution(&mut |value, _| values.push(value));
+        values.sort_unstable();
+        assert_eq!(vec![1, 2, 3, 4], values);
+        assert!(mdd.stats().keep_list_overflow);
+        assert!(mdd.stats().width_conflicts > 0);
     }
+
     #[test]
-    fn exact_skips_nodes_with_a_value_less_than_known_threshold() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&DummyProblem);
-        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 0}), 1, 0, true);
-        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 1}), 1, 1, true);
-        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 2}), 1, 2, true);
+    fn the_merged_node_takes_no_slot_of_the_nodes_which_must_be_kept() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
-            comp_type: crate::CompilationType::Exact,
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyMaxRelax,
+            ranking:    &KeepAtMost(1),
+            cutoff:     &NoCutoff,
+            max_width:  2,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new().with_width_conflict_policy(WidthConflictPolicy::MergeExact);
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        // two nodes must be kept, but one slot is left once the merged node is accounted for
+        assert!(mdd.stats().keep_list_overflow);
+        assert_eq!(Some(6), mdd.best_exact_value());
+    }
+
+    #[test]
+    fn by_default_the_flagged_nodes_are_kept_even_when_they_exceed_the_width() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
             relaxation: &DummyRelax,
-            ranking:    &DummyRanking,
+            ranking:    &KeepAtMost(2),
             cutoff:     &NoCutoff,
-            max_width:  usize::MAX,
+            max_width:  2,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        assert!(mdd.best_solution().is_none())
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        // the three flagged nodes of the first and second layers are kept 
+        // (the nodes with a value of 0, 1 and 2) while the nodes with a value 
+        // of 3 and 4 are dropped from the second layer.
+        let mut values = vec![];
+        mdd.for_each_exact_solution(&mut |value, _| values.push(value));
+        values.sort_unstable();
+        assert_eq!(vec![0, 1, 2, 3, 4], values);
+        assert!(mdd.stats().keep_list_overflow);
+        assert_eq!(2, mdd.stats().width_conflicts);
     }
+
     #[test]
-    fn relaxed_skips_nodes_with_a_value_less_than_known_threshold() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&DummyProblem);
-        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 0}), 1, 0, true);
-        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 1}), 1, 1, true);
-        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 2}), 1, 2, true);
+    fn a_relaxed_layer_exceeding_the_width_merges_the_nodes_which_are_not_flagged() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
-            relaxation: &DummyRelax,
-            ranking:    &DummyRanking,
+            relaxation: &DummyMaxRelax,
+            ranking:    &KeepAtMost(1),
             cutoff:     &NoCutoff,
-            max_width:  usize::MAX,
+            max_width:  2,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        assert!(mdd.best_solution().is_none())
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        // the flagged nodes of the second layer (0 and 1) are kept while all 
+        // the others are merged. The first layer is the last exact one.
+        assert!(!mdd.is_exact());
+        assert_eq!(Some(6), mdd.best_value());
+        assert!(mdd.stats().keep_list_overflow);
+        assert_eq!(1, mdd.stats().width_conflicts);
+        let mut cutset = vec![];
+        mdd.for_each_cutset_node(|n| cutset.push((n.depth, n.state.value)));
+        cutset.sort_unstable();
+        assert_eq!(vec![(1, 0), (1, 1), (1, 2)], cutset);
     }
+
     #[test]
-    fn restricted_skips_nodes_with_a_value_less_than_known_threshold() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&DummyProblem);
-        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 0}), 1, 0, true);
-        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 1}), 1, 1, true);
-        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 2}), 1, 2, true);
+    fn the_width_conflict_fails_the_compilation_when_asked_to() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let mut input = CompilationInput {
+            comp_type: crate::CompilationType::Restricted,
+            problem:    &DummyProblem,
+            relaxation: &DummyMaxRelax,
+            ranking:    &KeepAtMost(2),
+            cutoff:     &NoCutoff,
+            max_width:  2,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new().with_width_conflict_policy(WidthConflictPolicy::Error);
+        let outcome = mdd.compile(&input, residual.clone());
+        assert_eq!(Some(Reason::WidthConflict { depth: 1, flagged: 3, slots: 2 }), outcome.err());
+
+        // when relaxed, the first layer is left untouched and one slot of the
+        // second layer is taken by the merged node
+        input.comp_type = crate::CompilationType::Relaxed;
+        let outcome = mdd.compile(&input, residual);
+        assert_eq!(Some(Reason::WidthConflict { depth: 2, flagged: 3, slots: 1 }), outcome.err());
+    }
+
+    #[test]
+    fn the_optimum_is_found_whatever_the_width_conflict_policy() {
+        for policy in [WidthConflictPolicy::ExceedWidth, WidthConflictPolicy::MergeExact, WidthConflictPolicy::Error] {
+            let ranking = KeepAtMost(2);
+            let width = FixedWidth(2);
+            let cutoff = NoCutoff;
+            let dominance = EmptyDominanceChecker::default();
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = SequentialSolver::<DummyState, DefaultMDD<DummyState>, EmptyCache<DummyState>>::custom(
+                &DummyProblem, &DummyMaxRelax, &ranking, &width, &dominance, &cutoff, &mut fringe)
+                .with_mdd(DefaultMDD::new().with_width_conflict_policy(policy));
+
+            let completion = solver.maximize();
+            if policy == WidthConflictPolicy::Error {
+                assert!(!completion.is_exact);
+                assert!(matches!(completion.stopped_by, Some(Reason::WidthConflict { .. })));
+            } else {
+                assert!(completion.is_exact);
+                assert_eq!(Some(6), completion.best_value);
+            }
+        }
+    }
+
+    #[test]
+    fn with_the_strict_exactness_policy_the_nodes_below_a_restricted_layer_are_inexact() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
             relaxation: &DummyRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  usize::MAX,
+            max_width:  2,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
-        let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-        assert!(mdd.best_solution().is_none())
+        let mut standard = DefaultMDD::new();
+        assert!(standard.compile(&input, residual.clone()).is_ok());
+        assert_eq!(Some(6), standard.best_value());
+        assert_eq!(Some(6), standard.best_exact_value());
+
+        let mut strict = DefaultMDD::new().with_exactness_policy(ExactnessPolicy::PrunedLayersInexact);
+        assert!(strict.compile(&input, residual).is_ok());
+        assert_eq!(Some(6), strict.best_value());
+        assert_eq!(None, strict.best_exact_value());
+        assert_eq!(standard.stats(), strict.stats());
     }
 
     #[test]
-    fn restricted_mdd_computes_thresholds_when_exact() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&DummyProblem);
+    fn the_optimum_is_found_whatever_the_exactness_policy() {
+        for policy in [ExactnessPolicy::Standard, ExactnessPolicy::PrunedLayersInexact] {
+            let ranking = DummyRanking;
+            let width = FixedWidth(2);
+            let cutoff = NoCutoff;
+            let dominance = EmptyDominanceChecker::default();
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = SequentialSolver::<DummyState, Mdd<DummyState, {FRONTIER}>, EmptyCache<DummyState>>::custom(
+                &DummyProblem, &DummyMaxRelax, &ranking, &width, &dominance, &cutoff, &mut fringe)
+                .with_mdd(Mdd::new().with_exactness_policy(policy));
+
+            let completion = solver.maximize();
+            assert!(completion.is_exact);
+            assert_eq!(Some(6), completion.best_value);
+        }
+    }
+
+    #[test]
+    fn a_relaxed_mdd_is_exact_as_long_as_no_merge_occurs() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
-            comp_type: crate::CompilationType::Restricted,
+            comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
             relaxation: &DummyRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
             max_width:  10,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
-        assert!(mdd.is_exact());
-
-        let expected = vec![
-            (DummyState{depth: 0, value: 0}, Some(Threshold {value: 0, explored: true})),
-            (DummyState{depth: 1, value: 0}, Some(Threshold {value: 2, explored: true})),
-            (DummyState{depth: 1, value: 1}, Some(Threshold {value: 2, explored: true})),
-            (DummyState{depth: 1, value: 2}, Some(Threshold {value: 2, explored: true})),
-            (DummyState{depth: 2, value: 0}, Some(Threshold {value: 4, explored: true})),
-            (DummyState{depth: 2, value: 1}, Some(Threshold {value: 4, explored: true})),
-            (DummyState{depth: 2, value: 2}, Some(Threshold {value: 4, explored: true})),
-            (DummyState{depth: 2, value: 3}, Some(Threshold {value: 4, explored: true})),
-            (DummyState{depth: 2, value: 4}, Some(Threshold {value: 4, explored: true})),
-            (DummyState{depth: 3, value: 0}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 1}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 2}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 3}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 4}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 5}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 6}, Some(Threshold {value: 6, explored: true})),
-        ];
-
-        for (state, threshold) in expected.iter().copied() {
-            assert_eq!(threshold, cache.get_threshold(&state, state.depth));
-        }
+        assert!(mdd.is_exact())
     }
 
     #[test]
-    fn relaxed_mdd_computes_thresholds_when_exact() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&DummyProblem);
+    fn a_relaxed_mdd_is_not_exact_when_a_merge_occurred() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
             relaxation: &DummyRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  10,
+            max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
-        assert!(mdd.is_exact());
-
-        let expected = vec![
-            (DummyState{depth: 0, value: 0}, Some(Threshold {value: 0, explored: true})),
-            (DummyState{depth: 1, value: 0}, Some(Threshold {value: 2, explored: true})),
-            (DummyState{depth: 1, value: 1}, Some(Threshold {value: 2, explored: true})),
-            (DummyState{depth: 1, value: 2}, Some(Threshold {value: 2, explored: true})),
-            (DummyState{depth: 2, value: 0}, Some(Threshold {value: 4, explored: true})),
-            (DummyState{depth: 2, value: 1}, Some(Threshold {value: 4, explored: true})),
-            (DummyState{depth: 2, value: 2}, Some(Threshold {value: 4, explored: true})),
-            (DummyState{depth: 2, value: 3}, Some(Threshold {value: 4, explored: true})),
-            (DummyState{depth: 2, value: 4}, Some(Threshold {value: 4, explored: true})),
-            (DummyState{depth: 3, value: 0}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 1}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 2}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 3}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 4}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 5}, Some(Threshold {value: 6, explored: true})),
-            (DummyState{depth: 3, value: 6}, Some(Threshold {value: 6, explored: true})),
-        ];
-
-        for (state, threshold) in expected.iter().copied() {
-            assert_eq!(threshold, cache.get_threshold(&state, state.depth));
-        }
+        assert!(!mdd.is_exact())
     }
-
     #[test]
-    fn restricted_mdd_computes_thresholds_when_all_pruned() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&DummyProblem);
+    fn a_restricted_mdd_is_exact_as_long_as_no_restriction_occurs() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1956,84 +2975,482 @@ mod test_default_mdd {
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
             max_width:  10,
-            best_lb:    15,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+            best_lb:    isize::MIN,
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
-        assert!(mdd.is_exact());
-
-        let expected = vec![
-            (DummyState{depth: 0, value: 0}, Some(Threshold {value: 1, explored: true})),
-            (DummyState{depth: 1, value: 0}, Some(Threshold {value: 3, explored: true})),
-            (DummyState{depth: 1, value: 1}, Some(Threshold {value: 3, explored: true})),
-            (DummyState{depth: 1, value: 2}, Some(Threshold {value: 3, explored: true})),
-            (DummyState{depth: 2, value: 0}, Some(Threshold {value: 5, explored: true})),
-            (DummyState{depth: 2, value: 1}, Some(Threshold {value: 5, explored: true})),
-            (DummyState{depth: 2, value: 2}, Some(Threshold {value: 5, explored: true})),
-            (DummyState{depth: 2, value: 3}, Some(Threshold {value: 5, explored: true})),
-            (DummyState{depth: 2, value: 4}, Some(Threshold {value: 5, explored: true})),
-            (DummyState{depth: 3, value: 0}, None),
-            (DummyState{depth: 3, value: 1}, None),
-            (DummyState{depth: 3, value: 2}, None),
-            (DummyState{depth: 3, value: 3}, None),
-            (DummyState{depth: 3, value: 4}, None),
-            (DummyState{depth: 3, value: 5}, None),
-            (DummyState{depth: 3, value: 6}, None),
-        ];
-
-        for (state, threshold) in expected.iter().copied() {
-            assert_eq!(threshold, cache.get_threshold(&state, state.depth));
-        }
+        assert!(mdd.is_exact())
     }
-
     #[test]
-    fn relaxed_mdd_computes_thresholds_when_all_pruned() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&DummyProblem);
+    fn a_restricted_mdd_is_not_exact_when_a_restriction_occurred() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
             relaxation: &DummyRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  10,
-            best_lb:    15,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+            max_width:  1,
+            best_lb:    isize::MIN,
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
-        assert!(mdd.is_exact());
-
-        let expected = vec![
-            (DummyState{depth: 0, value: 0}, Some(Threshold {value: 1, explored: true})),
-            (DummyState{depth: 1, value: 0}, Some(Threshold {value: 3, explored: true})),
-            (DummyState{depth: 1, value: 1}, Some(Threshold {value: 3, explored: true})),
-            (DummyState{depth: 1, value: 2}, Some(Threshold {value: 3, explored: true})),
-            (DummyState{depth: 2, value: 0}, Some(Threshold {value: 5, explored: true})),
-            (DummyState{depth: 2, value: 1}, Some(Threshold {value: 5, explored: true})),
-            (DummyState{depth: 2, value: 2}, Some(Threshold {value: 5, explored: true})),
+        assert!(!mdd.is_exact())
+    }
+    #[test]
+    fn when_the_problem_is_infeasible_there_is_no_solution() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyInfeasibleProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        assert!(mdd.best_solution().is_none())
+    }
+    #[test]
+    fn when_the_problem_is_infeasible_there_is_no_best_value() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyInfeasibleProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        assert!(mdd.best_value().is_none())
+    }
+    #[test]
+    fn exact_skips_node_with_an_ub_less_than_best_known_lb() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    1000,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        assert!(mdd.best_solution().is_none())
+    }
+    #[test]
+    fn relaxed_skips_node_with_an_ub_less_than_best_known_lb() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    1000,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        assert!(mdd.best_solution().is_none())
+    }
+    #[test]
+    fn restricted_skips_node_with_an_ub_less_than_best_known_lb() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Restricted,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    1000,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        assert!(mdd.best_solution().is_none())
+    }
+    #[test]
+    fn exact_skips_nodes_with_a_value_less_than_known_threshold() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&DummyProblem);
+        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 0}), 1, 0, true);
+        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 1}), 1, 1, true);
+        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 2}), 1, 2, true);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        assert!(mdd.best_solution().is_none())
+    }
+    #[test]
+    fn relaxed_skips_nodes_with_a_value_less_than_known_threshold() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&DummyProblem);
+        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 0}), 1, 0, true);
+        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 1}), 1, 1, true);
+        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 2}), 1, 2, true);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        assert!(mdd.best_solution().is_none())
+    }
+    #[test]
+    fn restricted_skips_nodes_with_a_value_less_than_known_threshold() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&DummyProblem);
+        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 0}), 1, 0, true);
+        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 1}), 1, 1, true);
+        cache.update_threshold(Arc::new(DummyState{depth: 1, value: 2}), 1, 2, true);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Restricted,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        assert!(mdd.best_solution().is_none())
+    }
+
+    #[test]
+    fn restricted_mdd_computes_thresholds_when_exact() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&DummyProblem);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Restricted,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  10,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        
+        assert!(mdd.is_exact());
+
+        let expected = vec![
+            (DummyState{depth: 0, value: 0}, Some(Threshold {value: 0, explored: true})),
+            (DummyState{depth: 1, value: 0}, Some(Threshold {value: 2, explored: true})),
+            (DummyState{depth: 1, value: 1}, Some(Threshold {value: 2, explored: true})),
+            (DummyState{depth: 1, value: 2}, Some(Threshold {value: 2, explored: true})),
+            (DummyState{depth: 2, value: 0}, Some(Threshold {value: 4, explored: true})),
+            (DummyState{depth: 2, value: 1}, Some(Threshold {value: 4, explored: true})),
+            (DummyState{depth: 2, value: 2}, Some(Threshold {value: 4, explored: true})),
+            (DummyState{depth: 2, value: 3}, Some(Threshold {value: 4, explored: true})),
+            (DummyState{depth: 2, value: 4}, Some(Threshold {value: 4, explored: true})),
+            (DummyState{depth: 3, value: 0}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 1}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 2}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 3}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 4}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 5}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 6}, Some(Threshold {value: 6, explored: true})),
+        ];
+
+        for (state, threshold) in expected.iter().copied() {
+            assert_eq!(threshold, cache.get_threshold(&state, state.depth));
+        }
+    }
+
+    #[test]
+    fn relaxed_mdd_computes_thresholds_when_exact() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&DummyProblem);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  10,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        
+        assert!(mdd.is_exact());
+
+        let expected = vec![
+            (DummyState{depth: 0, value: 0}, Some(Threshold {value: 0, explored: true})),
+            (DummyState{depth: 1, value: 0}, Some(Threshold {value: 2, explored: true})),
+            (DummyState{depth: 1, value: 1}, Some(Threshold {value: 2, explored: true})),
+            (DummyState{depth: 1, value: 2}, Some(Threshold {value: 2, explored: true})),
+            (DummyState{depth: 2, value: 0}, Some(Threshold {value: 4, explored: true})),
+            (DummyState{depth: 2, value: 1}, Some(Threshold {value: 4, explored: true})),
+            (DummyState{depth: 2, value: 2}, Some(Threshold {value: 4, explored: true})),
+            (DummyState{depth: 2, value: 3}, Some(Threshold {value: 4, explored: true})),
+            (DummyState{depth: 2, value: 4}, Some(Threshold {value: 4, explored: true})),
+            (DummyState{depth: 3, value: 0}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 1}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 2}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 3}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 4}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 5}, Some(Threshold {value: 6, explored: true})),
+            (DummyState{depth: 3, value: 6}, Some(Threshold {value: 6, explored: true})),
+        ];
+
+        for (state, threshold) in expected.iter().copied() {
+            assert_eq!(threshold, cache.get_threshold(&state, state.depth));
+        }
+    }
+
+    #[test]
+    fn restricted_mdd_computes_thresholds_when_all_pruned() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&DummyProblem);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Restricted,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  10,
+            best_lb:    15,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        
+        assert!(mdd.is_exact());
+
+        let expected = vec![
+            (DummyState{depth: 0, value: 0}, Some(Threshold {value: 1, explored: true})),
+            (DummyState{depth: 1, value: 0}, Some(Threshold {value: 3, explored: true})),
+            (DummyState{depth: 1, value: 1}, Some(Threshold {value: 3, explored: true})),
+            (DummyState{depth: 1, value: 2}, Some(Threshold {value: 3, explored: true})),
+            (DummyState{depth: 2, value: 0}, Some(Threshold {value: 5, explored: true})),
+            (DummyState{depth: 2, value: 1}, Some(Threshold {value: 5, explored: true})),
+            (DummyState{depth: 2, value: 2}, Some(Threshold {value: 5, explored: true})),
+            (DummyState{depth: 2, value: 3}, Some(Threshold {value: 5, explored: true})),
+            (DummyState{depth: 2, value: 4}, Some(Threshold {value: 5, explored: true})),
+            (DummyState{depth: 3, value: 0}, None),
+            (DummyState{depth: 3, value: 1}, None),
+            (DummyState{depth: 3, value: 2}, None),
+            (DummyState{depth: 3, value: 3}, None),
+            (DummyState{depth: 3, value: 4}, None),
+            (DummyState{depth: 3, value: 5}, None),
+            (DummyState{depth: 3, value: 6}, None),
+        ];
+
+        for (state, threshold) in expected.iter().copied() {
+            assert_eq!(threshold, cache.get_threshold(&state, state.depth));
+        }
+    }
+
+    #[test]
+    fn relaxed_mdd_computes_thresholds_when_all_pruned() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&DummyProblem);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  10,
+            best_lb:    15,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+        
+        assert!(mdd.is_exact());
+
+        let expected = vec![
+            (DummyState{depth: 0, value: 0}, Some(Threshold {value: 1, explored: true})),
+            (DummyState{depth: 1, value: 0}, Some(Threshold {value: 3, explored: true})),
+            (DummyState{depth: 1, value: 1}, Some(Threshold {value: 3, explored: true})),
+            (DummyState{depth: 1, value: 2}, Some(Threshold {value: 3, explored: true})),
+            (DummyState{depth: 2, value: 0}, Some(Threshold {value: 5, explored: true})),
+            (DummyState{depth: 2, value: 1}, Some(Threshold {value: 5, explored: true})),
+            (DummyState{depth: 2, value: 2}, Some(Threshold {value: 5, explored: true})),
             (DummyState{depth: 2, value: 3}, Some(Threshold {value: 5, explored: true})),
             (DummyState{depth: 2, value: 4}, Some(Threshold {value: 5, explored: true})),
             (DummyState{depth: 3, value: 0}, None),
@@ -2092,9 +3509,9 @@ mod test_default_mdd {
                 'M' => Some(Variable(2)),
                 'e' => Some(Variable(2)),
                 'f' => Some(Variable(2)),
-                'g' => Some(Variable(0)),
-                'h' => Some(Variable(0)),
-                'i' => Some(Variable(0)),
+                'g' => Some(Variable(3)),
+                'h' => Some(Variable(3)),
+                'i' => Some(Variable(3)),
                 _   => None,
             }
         }
@@ -2182,6 +3599,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&LocBoundsAndThresholdsExamplePb);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &LocBoundsAndThresholdsExamplePb,
@@ -2190,18 +3615,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  3,
             best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDDLEL::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
 
         assert!(!mdd.is_exact());
@@ -2233,16 +3651,215 @@ mod test_default_mdd {
         assert_eq!(10, threshold.value);
         assert!(!threshold.explored);
 
-        threshold = cache.get_threshold(&'b', 1).unwrap();
-        assert_eq!(7, threshold.value);
-        assert!(!threshold.explored);
+        threshold = cache.get_threshold(&'b', 1).unwrap();
+        assert_eq!(7, threshold.value);
+        assert!(!threshold.explored);
+    }
+
+    #[test]
+    fn relaxed_computes_local_bounds_and_thresholds_2() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&LocBoundsAndThresholdsExamplePb);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &LocBoundsAndThresholdsExamplePb,
+            relaxation: &LocBoundsAndThresholdsExampleRelax,
+            ranking:    &CmpChar,
+            cutoff:     &NoCutoff,
+            max_width:  3,
+            best_lb:    0,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDDFC::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+
+        assert!(!mdd.is_exact());
+        assert_eq!(Some(16), mdd.best_value());
+
+        let mut v = FxHashMap::<char, isize>::default();
+        mdd.drain_cutset(|n| {v.insert(*n.state, n.ub);});
+
+        assert_eq!(16, v[&'a']);
+        assert_eq!(14, v[&'b']);
+        assert_eq!(13, v[&'h']);
+        assert_eq!(14, v[&'i']);
+        assert_eq!(4, v.len());
+
+        assert!(cache.get_threshold(&'r', 0).is_some());
+        assert!(cache.get_threshold(&'a', 1).is_some());
+        assert!(cache.get_threshold(&'b', 1).is_some());
+        assert!(cache.get_threshold(&'M', 2).is_none());
+        assert!(cache.get_threshold(&'e', 2).is_some());
+        assert!(cache.get_threshold(&'f', 2).is_some());
+        assert!(cache.get_threshold(&'g', 3).is_none());
+        assert!(cache.get_threshold(&'h', 3).is_some());
+        assert!(cache.get_threshold(&'i', 3).is_some());
+        assert!(cache.get_threshold(&'t', 4).is_none());
+
+        let mut threshold = cache.get_threshold(&'r', 0).unwrap();
+        assert_eq!(0, threshold.value);
+        assert!(threshold.explored);
+
+        threshold = cache.get_threshold(&'a', 1).unwrap();
+        assert_eq!(10, threshold.value);
+        assert!(!threshold.explored);
+
+        threshold = cache.get_threshold(&'b', 1).unwrap();
+        assert_eq!(7, threshold.value);
+        assert!(!threshold.explored);
+
+        threshold = cache.get_threshold(&'e', 2).unwrap();
+        assert_eq!(13, threshold.value);
+        assert!(threshold.explored);
+
+        threshold = cache.get_threshold(&'f', 2).unwrap();
+        assert_eq!(12, threshold.value);
+        assert!(threshold.explored);
+
+        threshold = cache.get_threshold(&'h', 3).unwrap();
+        assert_eq!(13, threshold.value);
+        assert!(!threshold.explored);
+
+        threshold = cache.get_threshold(&'i', 3).unwrap();
+        assert_eq!(14, threshold.value);
+        assert!(!threshold.explored);
+    }
+
+    #[test]
+    fn adaptive_chooses_the_last_exact_layer_when_the_relaxation_kicks_in_early() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&LocBoundsAndThresholdsExamplePb);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &LocBoundsAndThresholdsExamplePb,
+            relaxation: &LocBoundsAndThresholdsExampleRelax,
+            ranking:    &CmpChar,
+            cutoff:     &NoCutoff,
+            max_width:  3,
+            best_lb:    0,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        // the first inexact layer lies at 2/5 of the diagram
+        let mut mdd = DefaultMDDAdaptive::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+
+        assert_eq!(MddStats { nb_layers: 5, first_inexact_layer: Some(2), cutset_type: LAST_EXACT_LAYER, keep_list_overflow: false, width_conflicts: 0 }, mdd.stats());
+
+        let mut v = FxHashMap::<char, isize>::default();
+        mdd.drain_cutset(|n| {v.insert(*n.state, n.ub);});
+
+        assert_eq!(16, v[&'a']);
+        assert_eq!(14, v[&'b']);
+        assert_eq!(2, v.len());
+    }
+
+    #[test]
+    fn adaptive_chooses_the_frontier_when_the_relaxation_kicks_in_late() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&LocBoundsAndThresholdsExamplePb);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &LocBoundsAndThresholdsExamplePb,
+            relaxation: &LocBoundsAndThresholdsExampleRelax,
+            ranking:    &CmpChar,
+            cutoff:     &NoCutoff,
+            max_width:  3,
+            best_lb:    0,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        // the first inexact layer lies at 2/5 of the diagram
+        let mut mdd = DefaultMDDAdaptive::new().with_adaptive_threshold(0.4);
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+
+        assert_eq!(MddStats { nb_layers: 5, first_inexact_layer: Some(2), cutset_type: FRONTIER, keep_list_overflow: false, width_conflicts: 0 }, mdd.stats());
+
+        let mut v = FxHashMap::<char, isize>::default();
+        mdd.drain_cutset(|n| {v.insert(*n.state, n.ub);});
+
+        assert_eq!(16, v[&'a']);
+        assert_eq!(14, v[&'b']);
+        assert_eq!(13, v[&'h']);
+        assert_eq!(14, v[&'i']);
+        assert_eq!(4, v.len());
+    }
+
+    #[test]
+    fn the_stats_of_an_exact_mdd_have_no_inexact_layer() {
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &LocBoundsAndThresholdsExamplePb,
+            relaxation: &LocBoundsAndThresholdsExampleRelax,
+            ranking:    &CmpChar,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    0,
+            cache: &EmptyCache::new(),
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDDAdaptive::new();
+        let result = mdd.compile(&input, residual);
+        assert!(result.is_ok());
+
+        assert!(mdd.is_exact());
+        assert_eq!(None, mdd.stats().first_inexact_layer);
+        assert_eq!(LAST_EXACT_LAYER, mdd.stats().cutset_type);
     }
 
     #[test]
-    fn relaxed_computes_local_bounds_and_thresholds_2() {
+    fn relaxed_computes_local_bounds_and_thresholds_with_pruning() {
         let mut cache = SimpleCache::default();
         cache.initialize(&LocBoundsAndThresholdsExamplePb);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &LocBoundsAndThresholdsExamplePb,
@@ -2250,19 +3867,12 @@ mod test_default_mdd {
             ranking:    &CmpChar,
             cutoff:     &NoCutoff,
             max_width:  3,
-            best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+            best_lb:    15,
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDDFC::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
 
         assert!(!mdd.is_exact());
@@ -2273,9 +3883,7 @@ mod test_default_mdd {
 
         assert_eq!(16, v[&'a']);
         assert_eq!(14, v[&'b']);
-        assert_eq!(13, v[&'h']);
-        assert_eq!(14, v[&'i']);
-        assert_eq!(4, v.len());
+        assert_eq!(2, v.len());
 
         assert!(cache.get_threshold(&'r', 0).is_some());
         assert!(cache.get_threshold(&'a', 1).is_some());
@@ -2297,253 +3905,461 @@ mod test_default_mdd {
         assert!(!threshold.explored);
 
         threshold = cache.get_threshold(&'b', 1).unwrap();
-        assert_eq!(7, threshold.value);
+        assert_eq!(8, threshold.value);
         assert!(!threshold.explored);
 
         threshold = cache.get_threshold(&'e', 2).unwrap();
-        assert_eq!(13, threshold.value);
+        assert_eq!(15, threshold.value);
         assert!(threshold.explored);
 
         threshold = cache.get_threshold(&'f', 2).unwrap();
-        assert_eq!(12, threshold.value);
+        assert_eq!(13, threshold.value);
         assert!(threshold.explored);
 
         threshold = cache.get_threshold(&'h', 3).unwrap();
-        assert_eq!(13, threshold.value);
-        assert!(!threshold.explored);
+        assert_eq!(15, threshold.value);
+        assert!(threshold.explored);
 
         threshold = cache.get_threshold(&'i', 3).unwrap();
-        assert_eq!(14, threshold.value);
-        assert!(!threshold.explored);
+        assert_eq!(15, threshold.value);
+        assert!(threshold.explored);
     }
 
     #[test]
-    fn relaxed_computes_local_bounds_and_thresholds_with_pruning() {
+    fn test_default_visualisation() {
         let mut cache = SimpleCache::default();
         cache.initialize(&LocBoundsAndThresholdsExamplePb);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
-            comp_type: crate::CompilationType::Relaxed,
+            comp_type:  crate::CompilationType::Relaxed,
             problem:    &LocBoundsAndThresholdsExamplePb,
             relaxation: &LocBoundsAndThresholdsExampleRelax,
             ranking:    &CmpChar,
             cutoff:     &NoCutoff,
             max_width:  3,
-            best_lb:    15,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+            best_lb:    0,
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDDFC::new();
-        let result = mdd.compile(&input);
-        assert!(result.is_ok());
-
-        assert!(!mdd.is_exact());
-        assert_eq!(Some(16), mdd.best_value());
+        let _ = mdd.compile(&input, residual);
+        
+        let dot = include_str!("../../../../resources/visualisation_tests/default_viz.dot");
+        let config = VizConfigBuilder::default().build().unwrap();            
+        let s = mdd.as_graphviz(&config); 
+        //println!("{}", s)
+        assert_eq!(strip_format(dot), strip_format(&s));
+    }
 
-        let mut v = FxHashMap::<char, isize>::default();
-        mdd.drain_cutset(|n| {v.insert(*n.state, n.ub);});
+    #[test]
+    fn test_terse_visualisation() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&LocBoundsAndThresholdsExamplePb);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type:  crate::CompilationType::Relaxed,
+            problem:    &LocBoundsAndThresholdsExamplePb,
+            relaxation: &LocBoundsAndThresholdsExampleRelax,
+            ranking:    &CmpChar,
+            cutoff:     &NoCutoff,
+            max_width:  3,
+            best_lb:    0,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDDFC::new();
+        let _ = mdd.compile(&input, residual);
+        
+        let dot = include_str!("../../../../resources/visualisation_tests/terse_viz.dot");
+        let config = VizConfigBuilder::default()
+            .show_value(false)
+            .show_deleted(false)
+            .show_rub(false)
+            .show_locb(false)
+            .show_threshold(false)
+            .build().unwrap();           
+        let s = mdd.as_graphviz(&config); 
+        assert_eq!(strip_format(dot), strip_format(&s));
+    }
 
-        assert_eq!(16, v[&'a']);
-        assert_eq!(14, v[&'b']);
-        assert_eq!(2, v.len());
+    #[test]
+    fn test_show_deleted_viz() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&LocBoundsAndThresholdsExamplePb);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type:  crate::CompilationType::Relaxed,
+            problem:    &LocBoundsAndThresholdsExamplePb,
+            relaxation: &LocBoundsAndThresholdsExampleRelax,
+            ranking:    &CmpChar,
+            cutoff:     &NoCutoff,
+            max_width:  3,
+            best_lb:    0,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDDFC::new();
+        let _ = mdd.compile(&input, residual);
+        
+        let dot = include_str!("../../../../resources/visualisation_tests/deleted_viz.dot");
+        let config = VizConfigBuilder::default()
+            .show_value(false)
+            .show_deleted(true)
+            .show_rub(false)
+            .show_locb(false)
+            .show_threshold(false)
+            .build().unwrap();         
+        let s = mdd.as_graphviz(&config); 
+        assert_eq!(strip_format(dot), strip_format(&s));
+    }
 
-        assert!(cache.get_threshold(&'r', 0).is_some());
-        assert!(cache.get_threshold(&'a', 1).is_some());
-        assert!(cache.get_threshold(&'b', 1).is_some());
-        assert!(cache.get_threshold(&'M', 2).is_none());
-        assert!(cache.get_threshold(&'e', 2).is_some());
-        assert!(cache.get_threshold(&'f', 2).is_some());
-        assert!(cache.get_threshold(&'g', 3).is_none());
-        assert!(cache.get_threshold(&'h', 3).is_some());
-        assert!(cache.get_threshold(&'i', 3).is_some());
-        assert!(cache.get_threshold(&'t', 4).is_none());
+    #[test]
+    fn test_show_group_merged() {
+        let mut cache = SimpleCache::default();
+        cache.initialize(&LocBoundsAndThresholdsExamplePb);
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type:  crate::CompilationType::Relaxed,
+            problem:    &LocBoundsAndThresholdsExamplePb,
+            relaxation: &LocBoundsAndThresholdsExampleRelax,
+            ranking:    &CmpChar,
+            cutoff:     &NoCutoff,
+            max_width:  3,
+            best_lb:    0,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDDFC::new();
+        let _ = mdd.compile(&input, residual);
+        
+        let dot = include_str!("../../../../resources/visualisation_tests/clusters_viz.dot");
+        let config = VizConfigBuilder::default()
+            .show_value(false)
+            .show_deleted(true)
+            .show_rub(false)
+            .show_locb(false)
+            .show_threshold(false)
+            .group_merged(true)
+            .build().unwrap();
+        let s = mdd.as_graphviz(&config);
+        assert_eq!(strip_format(dot), strip_format(&s));
+    }
 
-        let mut threshold = cache.get_threshold(&'r', 0).unwrap();
-        assert_eq!(0, threshold.value);
-        assert!(threshold.explored);
+    fn strip_format(s: &str) -> String {
+        s.lines().map(|l| l.trim()).collect()
+    }
 
-        threshold = cache.get_threshold(&'a', 1).unwrap();
-        assert_eq!(10, threshold.value);
-        assert!(!threshold.explored);
+    /// Compiles the exact DD of `DummyProblem` and returns the ids of the
+    /// nodes at the given depth
+    fn exact_layer(mdd: &mut DefaultMDD<DummyState>, depth: usize) -> Vec<NodeId> {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        assert!(mdd.compile(&input, residual).is_ok());
+        (0..mdd.nodes.len()).map(NodeId).filter(|id| mdd.nodes[id.0].depth == depth).collect()
+    }
 
-        threshold = cache.get_threshold(&'b', 1).unwrap();
-        assert_eq!(8, threshold.value);
-        assert!(!threshold.explored);
+    #[test]
+    fn a_group_of_one_node_is_not_merged_and_stays_exact() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let relax = CountingRelax::default();
+        let mut mdd = DefaultMDD::new();
+        let mut layer = exact_layer(&mut mdd, 2);
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &relax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  layer.len(),
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let nb_nodes = mdd.nodes.len();
+        assert!(mdd._relax(&input, &mut layer).is_ok());
+
+        assert!(relax.groups.borrow().is_empty());
+        assert_eq!(5, layer.len());
+        assert_eq!(nb_nodes, mdd.nodes.len());
+        for id in layer {
+            let flags = mdd.nodes[id.0].flags;
+            assert!(flags.is_exact());
+            assert!(!flags.is_relaxed());
+            assert!(!flags.is_deleted());
+        }
+    }
+    #[test]
+    fn a_group_of_two_nodes_is_merged_into_one_inexact_node() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let relax = CountingRelax::default();
+        let mut mdd = DefaultMDD::new();
+        let mut layer = exact_layer(&mut mdd, 2);
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &relax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  layer.len() - 1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        assert!(mdd._relax(&input, &mut layer).is_ok());
+
+        assert_eq!(vec![2], *relax.groups.borrow());
+        assert_eq!(4, layer.len());
+        let (merged, kept) = layer.split_last().unwrap();
+        assert!(kept.iter().all(|id| mdd.nodes[id.0].flags.is_exact()));
+        assert!(mdd.nodes[merged.0].flags.is_relaxed());
+        assert!(!mdd.nodes[merged.0].flags.is_exact());
+    }
+    #[test]
+    fn a_large_group_is_merged_by_chunks_of_at_least_two_nodes() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let relax = CountingRelax::default();
+        let mut mdd = DefaultMDD::new().with_merge_chunk_size(3);
+        let mut layer = exact_layer(&mut mdd, 3);
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &relax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        assert!(mdd._relax(&input, &mut layer).is_ok());
 
-        threshold = cache.get_threshold(&'e', 2).unwrap();
-        assert_eq!(15, threshold.value);
-        assert!(threshold.explored);
+        // 7 nodes: two chunks of 3, the last node is carried over
+        assert_eq!(vec![3, 3, 3], *relax.groups.borrow());
+        assert_eq!(1, layer.len());
+        assert!(mdd.nodes[layer[0].0].flags.is_relaxed());
+        assert_eq!(DummyState { value: 6, depth: 3 }, *mdd.nodes[layer[0].0].state);
+    }
 
-        threshold = cache.get_threshold(&'f', 2).unwrap();
-        assert_eq!(13, threshold.value);
-        assert!(threshold.explored);
+    /// Counts the nodes at the given depth which survived the trimming of
+    /// their layer
+    fn surviving_nodes(mdd: &DefaultMDD<DummyState>, depth: usize) -> usize {
+        mdd.nodes.iter().filter(|n| n.depth == depth && !n.flags.is_deleted()).count()
+    }
 
-        threshold = cache.get_threshold(&'h', 3).unwrap();
-        assert_eq!(15, threshold.value);
-        assert!(threshold.explored);
+    #[test]
+    fn restricted_respects_the_width_profile_of_the_problem() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let problem = DummyProfiledProblem(vec![1, 2, 1]);
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Restricted,
+            problem:    &problem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
 
-        threshold = cache.get_threshold(&'i', 3).unwrap();
-        assert_eq!(15, threshold.value);
-        assert!(threshold.explored);
+        assert_eq!(1, surviving_nodes(&mdd, 0));
+        assert_eq!(2, surviving_nodes(&mdd, 1));
+        assert_eq!(1, surviving_nodes(&mdd, 2));
+        assert_eq!(Some(6), mdd.best_value());
     }
-
     #[test]
-    fn test_default_visualisation() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&LocBoundsAndThresholdsExamplePb);
+    fn relaxed_respects_the_width_profile_of_the_problem() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let relax = CountingRelax::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let problem = DummyProfiledProblem(vec![1, 1, 3]);
         let input = CompilationInput {
-            comp_type:  crate::CompilationType::Relaxed,
-            problem:    &LocBoundsAndThresholdsExamplePb,
-            relaxation: &LocBoundsAndThresholdsExampleRelax,
-            ranking:    &CmpChar,
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &problem,
+            relaxation: &relax,
+            ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  3,
-            best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+            max_width:  1,
+            best_lb:    isize::MIN,
             cache: &cache,
             dominance: &dominance,
         };
-        let mut mdd = DefaultMDDFC::new();
-        let _ = mdd.compile(&input);
-        
-        let dot = include_str!("../../../../resources/visualisation_tests/default_viz.dot");
-        let config = VizConfigBuilder::default().build().unwrap();            
-        let s = mdd.as_graphviz(&config); 
-        //println!("{}", s)
-        assert_eq!(strip_format(dot), strip_format(&s));
-    }
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
 
+        // the 5 nodes of the third layer: two are kept and three are merged
+        assert_eq!(vec![3], *relax.groups.borrow());
+        assert_eq!(3, surviving_nodes(&mdd, 1));
+        assert_eq!(3, surviving_nodes(&mdd, 2));
+    }
     #[test]
-    fn test_terse_visualisation() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&LocBoundsAndThresholdsExamplePb);
+    fn the_problem_ctx_is_owned_by_the_dd_and_kept_between_compilations() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
-            comp_type:  crate::CompilationType::Relaxed,
-            problem:    &LocBoundsAndThresholdsExamplePb,
-            relaxation: &LocBoundsAndThresholdsExampleRelax,
-            ranking:    &CmpChar,
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyScratchProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  3,
-            best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
             cache: &cache,
             dominance: &dominance,
         };
-        let mut mdd = DefaultMDDFC::new();
-        let _ = mdd.compile(&input);
-        
-        let dot = include_str!("../../../../resources/visualisation_tests/terse_viz.dot");
-        let config = VizConfigBuilder::default()
-            .show_value(false)
-            .show_deleted(false)
-            .show_rub(false)
-            .show_locb(false)
-            .show_threshold(false)
-            .build().unwrap();           
-        let s = mdd.as_graphviz(&config); 
-        assert_eq!(strip_format(dot), strip_format(&s));
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual.clone()).is_ok());
+        // 1 + 3 + 5 nodes are expanded, each of which has 3 branches
+        assert_eq!(27, *mdd.ctx.get::<usize>());
+        assert_eq!(Some(6), mdd.best_value());
+
+        assert!(mdd.compile(&input, residual).is_ok());
+        assert_eq!(54, *mdd.ctx.get::<usize>());
     }
 
     #[test]
-    fn test_show_deleted_viz() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&LocBoundsAndThresholdsExamplePb);
+    fn the_values_filtered_out_of_a_layer_are_skipped_by_all_its_nodes() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
-            comp_type:  crate::CompilationType::Relaxed,
-            problem:    &LocBoundsAndThresholdsExamplePb,
-            relaxation: &LocBoundsAndThresholdsExampleRelax,
-            ranking:    &CmpChar,
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyFilteredProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  3,
-            best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
             cache: &cache,
             dominance: &dominance,
         };
-        let mut mdd = DefaultMDDFC::new();
-        let _ = mdd.compile(&input);
-        
-        let dot = include_str!("../../../../resources/visualisation_tests/deleted_viz.dot");
-        let config = VizConfigBuilder::default()
-            .show_value(false)
-            .show_deleted(true)
-            .show_rub(false)
-            .show_locb(false)
-            .show_threshold(false)
-            .build().unwrap();         
-        let s = mdd.as_graphviz(&config); 
-        assert_eq!(strip_format(dot), strip_format(&s));
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
+        // the value 2 is skipped on the first two layers only
+        assert!(mdd.is_exact());
+        assert_eq!(Some(4), mdd.best_value());
     }
-
     #[test]
-    fn test_show_group_merged() {
-        let mut cache = SimpleCache::default();
-        cache.initialize(&LocBoundsAndThresholdsExamplePb);
+    fn a_state_independent_domain_is_queried_once_per_layer() {
+        let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
-        let input = CompilationInput {
-            comp_type:  crate::CompilationType::Relaxed,
-            problem:    &LocBoundsAndThresholdsExamplePb,
-            relaxation: &LocBoundsAndThresholdsExampleRelax,
-            ranking:    &CmpChar,
-            cutoff:     &NoCutoff,
-            max_width:  3,
-            best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
+        let compile = |independent: bool| {
+            let problem = DummyCountingProblem { independent, queries: Cell::new(0) };
+            let residual = SubProblem { 
+                state: Arc::new(DummyState{depth: 0, value: 0}), 
                 value: 0, 
                 path:  vec![], 
                 ub:    isize::MAX,
                 depth: 0,
-            },
-            cache: &cache,
-            dominance: &dominance,
+                priority_bonus: 0,
+            };
+            let input = CompilationInput {
+                comp_type: crate::CompilationType::Exact,
+                problem:    &problem,
+                relaxation: &DummyRelax,
+                ranking:    &DummyRanking,
+                cutoff:     &NoCutoff,
+                max_width:  usize::MAX,
+                best_lb:    isize::MIN,
+                cache: &cache,
+                dominance: &dominance,
+            };
+            let mut mdd = DefaultMDD::new();
+            assert!(mdd.compile(&input, residual).is_ok());
+            assert!(mdd.is_exact());
+            (mdd.best_value(), problem.queries.get())
         };
-        let mut mdd = DefaultMDDFC::new();
-        let _ = mdd.compile(&input);
-        
-        let dot = include_str!("../../../../resources/visualisation_tests/clusters_viz.dot");
-        let config = VizConfigBuilder::default()
-            .show_value(false)
-            .show_deleted(true)
-            .show_rub(false)
-            .show_locb(false)
-            .show_threshold(false)
-            .group_merged(true)
-            .build().unwrap();
-        let s = mdd.as_graphviz(&config);
-        assert_eq!(strip_format(dot), strip_format(&s));
-    }
-
-    fn strip_format(s: &str) -> String {
-        s.lines().map(|l| l.trim()).collect()
+        // the layers comprise 1, 3 and 5 nodes
+        assert_eq!((Some(6), 9), compile(false));
+        assert_eq!((Some(6), 3), compile(true));
     }
 
     #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
@@ -2593,6 +4409,123 @@ mod test_default_mdd {
         }
     }
 
+    /// Behaves like `DummyProblem` but scales the width of its layers with
+    /// the given width profile
+    struct DummyProfiledProblem(Vec<usize>);
+    impl Problem for DummyProfiledProblem {
+        type State = DummyState;
+
+        fn nb_variables(&self)  -> usize { DummyProblem.nb_variables() }
+        fn initial_value(&self) -> isize { DummyProblem.initial_value() }
+        fn initial_state(&self) -> Self::State { DummyProblem.initial_state() }
+        fn transition(&self, state: &Self::State, decision: crate::Decision) -> Self::State {
+            DummyProblem.transition(state, decision)
+        }
+        fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: crate::Decision) -> isize {
+            DummyProblem.transition_cost(source, dest, decision)
+        }
+        fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+            -> Option<crate::Variable> {
+            DummyProblem.next_variable(depth, next_layer)
+        }
+        fn for_each_in_domain(&self, var: crate::Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            DummyProblem.for_each_in_domain(var, state, f)
+        }
+        fn width_profile(&self) -> Option<Vec<usize>> {
+            Some(self.0.clone())
+        }
+    }
+
+    /// Behaves like `DummyProblem` but skips the value 2 for all the nodes of
+    /// a layer unless one of them has a value greater than 1
+    struct DummyFilteredProblem;
+    impl Problem for DummyFilteredProblem {
+        type State = DummyState;
+
+        fn nb_variables(&self)  -> usize { DummyProblem.nb_variables() }
+        fn initial_value(&self) -> isize { DummyProblem.initial_value() }
+        fn initial_state(&self) -> Self::State { DummyProblem.initial_state() }
+        fn transition(&self, state: &Self::State, decision: crate::Decision) -> Self::State {
+            DummyProblem.transition(state, decision)
+        }
+        fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: crate::Decision) -> isize {
+            DummyProblem.transition_cost(source, dest, decision)
+        }
+        fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+            -> Option<crate::Variable> {
+            DummyProblem.next_variable(depth, next_layer)
+        }
+        fn for_each_in_domain(&self, var: crate::Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            DummyProblem.for_each_in_domain(var, state, f)
+        }
+        fn filter_layer(&self, _: crate::Variable, states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+            let mut mask = ValueMask::all();
+            if states.map(|s| s.value).max().is_none_or(|v| v <= 1) {
+                mask.skip(2);
+            }
+            mask
+        }
+    }
+
+    /// Behaves like `DummyProblem` but counts the queries of the domains, 
+    /// which may be told to be state independent
+    struct DummyCountingProblem {
+        independent: bool,
+        queries: Cell<usize>,
+    }
+    impl Problem for DummyCountingProblem {
+        type State = DummyState;
+
+        fn nb_variables(&self)  -> usize { DummyProblem.nb_variables() }
+        fn initial_value(&self) -> isize { DummyProblem.initial_value() }
+        fn initial_state(&self) -> Self::State { DummyProblem.initial_state() }
+        fn transition(&self, state: &Self::State, decision: crate::Decision) -> Self::State {
+            DummyProblem.transition(state, decision)
+        }
+        fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: crate::Decision) -> isize {
+            DummyProblem.transition_cost(source, dest, decision)
+        }
+        fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+            -> Option<crate::Variable> {
+            DummyProblem.next_variable(depth, next_layer)
+        }
+        fn for_each_in_domain(&self, var: crate::Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            self.queries.set(self.queries.get() + 1);
+            DummyProblem.for_each_in_domain(var, state, f)
+        }
+        fn domain_is_state_independent(&self, _: crate::Variable) -> bool {
+            self.independent
+        }
+    }
+
+    /// Behaves like `DummyProblem` but counts the transitions it makes in the
+    /// scratch context of the DD
+    struct DummyScratchProblem;
+    impl Problem for DummyScratchProblem {
+        type State = DummyState;
+
+        fn nb_variables(&self)  -> usize { DummyProblem.nb_variables() }
+        fn initial_value(&self) -> isize { DummyProblem.initial_value() }
+        fn initial_state(&self) -> Self::State { DummyProblem.initial_state() }
+        fn transition(&self, state: &Self::State, decision: crate::Decision) -> Self::State {
+            DummyProblem.transition(state, decision)
+        }
+        fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: crate::Decision) -> isize {
+            DummyProblem.transition_cost(source, dest, decision)
+        }
+        fn transition_with_ctx(&self, state: &Self::State, decision: crate::Decision, ctx: &mut ProblemCtx) -> Self::State {
+            *ctx.get::<usize>() += 1;
+            DummyProblem.transition(state, decision)
+        }
+        fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+            -> Option<crate::Variable> {
+            DummyProblem.next_variable(depth, next_layer)
+        }
+        fn for_each_in_domain(&self, var: crate::Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            DummyProblem.for_each_in_domain(var, state, f)
+        }
+    }
+
     #[derive(Clone,Copy)]
     struct DummyInfeasibleProblem;
     impl Problem for DummyInfeasibleProblem {
@@ -2653,6 +4586,41 @@ mod test_default_mdd {
         }
     }
 
+    #[derive(Copy, Clone)]
+    struct DummyMaxRelax;
+    impl Relaxation for DummyMaxRelax {
+        type State = DummyState;
+
+        fn merge(&self, s: &mut dyn Iterator<Item=&Self::State>) -> Self::State {
+            s.copied().max_by_key(|s| s.value).unwrap()
+        }
+        fn relax(&self, _: &Self::State, _: &Self::State, _: &Self::State, _: Decision, cost: isize) -> isize {
+            cost
+        }
+        fn fast_upper_bound(&self, state: &Self::State) -> isize {
+            (DummyProblem.nb_variables() - state.depth) as isize * 10
+        }
+    }
+
+    /// Merges the states like `DummyMaxRelax` and records the number of
+    /// states given to each call to merge
+    #[derive(Default)]
+    struct CountingRelax {
+        groups: std::cell::RefCell<Vec<usize>>,
+    }
+    impl Relaxation for CountingRelax {
+        type State = DummyState;
+
+        fn merge(&self, s: &mut dyn Iterator<Item=&Self::State>) -> Self::State {
+            let states = s.copied().collect::<Vec<_>>();
+            self.groups.borrow_mut().push(states.len());
+            states.into_iter().max_by_key(|s| s.value).unwrap()
+        }
+        fn relax(&self, _: &Self::State, _: &Self::State, _: &Self::State, _: Decision, cost: isize) -> isize {
+            cost
+        }
+    }
+
     #[derive(Copy, Clone)]
     struct DummyRanking;
     impl StateRanking for DummyRanking {
@@ -2662,4 +4630,115 @@ mod test_default_mdd {
             a.value.cmp(&b.value).reverse()
         }
     }
-}
\ No newline at end of file
+
+    /// Ranks the states like `DummyRanking` but flags the nodes whose value
+    /// is at most the given one as nodes that must be kept
+    #[derive(Copy, Clone)]
+    struct KeepAtMost(isize);
+    impl StateRanking for KeepAtMost {
+        type State = DummyState;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> Ordering {
+            a.value.cmp(&b.value).reverse()
+        }
+        fn must_keep(&self, state: &Self::State) -> bool {
+            state.value <= self.0
+        }
+    }
+
+    /// A problem whose layers grow up to 10_000 nodes of 10 branches each. It
+    /// counts the transitions it computes.
+    struct Wide {
+        transitions: Cell<usize>,
+    }
+    impl Problem for Wide {
+        type State = usize;
+
+        fn nb_variables(&self) -> usize { 20 }
+        fn initial_state(&self) -> usize { 0 }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, state: &usize, d: Decision) -> usize {
+            self.transitions.set(self.transitions.get() + 1);
+            (state * 10 + d.value as usize) % 10_000
+        }
+        fn transition_cost(&self, _: &usize, _: &usize, d: Decision) -> isize {
+            d.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+            Some(Variable(depth)).filter(|_| depth < 20)
+        }
+        fn for_each_in_domain(&self, var: Variable, _: &usize, f: &mut dyn DecisionCallback) {
+            for value in 0..10 {
+                f.apply(Decision { variable: var, value });
+            }
+        }
+    }
+    struct WideRelax;
+    impl Relaxation for WideRelax {
+        type State = usize;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &usize>) -> usize {
+            states.copied().max().unwrap()
+        }
+        fn relax(&self, _: &usize, _: &usize, _: &usize, _: Decision, cost: isize) -> isize {
+            cost
+        }
+    }
+    struct WideRanking;
+    impl StateRanking for WideRanking {
+        type State = usize;
+
+        fn compare(&self, a: &usize, b: &usize) -> Ordering {
+            a.cmp(b)
+        }
+    }
+    /// Never kicks in, but records when it is polled and how many transitions
+    /// had been computed at that time
+    struct PollLog<'a> {
+        transitions: &'a Cell<usize>,
+        polls: RefCell<Vec<(Instant, usize)>>,
+    }
+    impl Cutoff for PollLog<'_> {
+        fn must_stop(&self) -> bool {
+            self.polls.borrow_mut().push((Instant::now(), self.transitions.get()));
+            false
+        }
+    }
+
+    #[test]
+    fn the_cutoff_is_polled_at_bounded_intervals() {
+        const INTERVAL: usize = 100;
+        let problem = Wide { transitions: Cell::new(0) };
+        let cutoff = PollLog { transitions: &problem.transitions, polls: RefCell::new(vec![]) };
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let input = CompilationInput {
+            comp_type:  CompilationType::Relaxed,
+            problem:    &problem,
+            relaxation: &WideRelax,
+            ranking:    &WideRanking,
+            cutoff:     &cutoff,
+            max_width:  1_000,
+            best_lb:    isize::MIN,
+            cache:      &cache,
+            dominance:  &dominance,
+        };
+        let root = SubProblem { state: Arc::new(0), value: 0, path: vec![], ub: isize::MAX, depth: 0, priority_bonus: 0 };
+
+        let mut mdd = DefaultMDD::new().with_poll_interval(INTERVAL);
+        let start = Instant::now();
+        assert!(mdd.compile(&input, root).is_ok());
+        let total = problem.transitions.get();
+        assert!(total > 100_000);
+
+        let polls = cutoff.polls.into_inner();
+        assert!(polls.len() >= total / (INTERVAL + 10));
+        // at most one interval, plus the domain of the last node expanded
+        let mut last = (start, 0);
+        for (at, transitions) in polls {
+            assert!(transitions - last.1 <= INTERVAL + 10);
+            assert!(at - last.0 < Duration::from_micros(500) * INTERVAL as u32);
+            last = (at, transitions);
+        }
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the map which is used to deduplicate the nodes of 
+//! a layer while a decision diagram is being compiled. 
+//! 
+//! Unlike a plain hashmap indexed on the states, this map is indexed on the
+//! fingerprint (hash) of the states. This way, the (possibly expensive) 
+//! equality test between two states is only ever performed when both states 
+//! have the same 64 bit fingerprint and when the problem could not tell them
+//! apart with its `quick_neq` hook.
+
+use std::{hash::{Hash, Hasher, BuildHasherDefault}, sync::Arc, collections::{HashMap, hash_map::{Entry, Values}}, slice::Iter};
+
+use fxhash::FxHasher;
+
+use crate::Problem;
+
+/// The 64 bit hash of a state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Fingerprint(u64);
+impl Fingerprint {
+    /// Computes the fingerprint of the given state
+    pub fn of<T: Hash>(state: &T) -> Self {
+        let mut hasher = FxHasher::default();
+        state.hash(&mut hasher);
+        Fingerprint(hasher.finish())
+    }
+}
+
+/// A hasher which simply passes the fingerprints through: they already are
+/// the hash of a state.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct PassThrough(u64);
+impl Hasher for PassThrough {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, _: &[u8]) {
+        unreachable!("only fingerprints are hashed with this hasher")
+    }
+    fn write_u64(&mut self, fp: u64) {
+        self.0 = fp;
+    }
+}
+
+/// A map associating the states of a layer with some value (typically the
+/// identifier of the node having that state).
+#[derive(Debug, Clone)]
+pub(crate) struct LayerMap<T, V> {
+    /// The entries of the map indexed on the fingerprint of their state
+    entries: HashMap<Fingerprint, (Arc<T>, V), BuildHasherDefault<PassThrough>>,
+    /// The (rare) entries whose fingerprint collides with the fingerprint of
+    /// a different state which is already stored in `entries`
+    collisions: Vec<(Fingerprint, Arc<T>, V)>,
+}
+
+impl <T, V> Default for LayerMap<T, V> {
+    fn default() -> Self {
+        Self { entries: Default::default(), collisions: vec![] }
+    }
+}
+
+impl <T: Eq, V: Copy> LayerMap<T, V> {
+    /// Returns true iff the map contains no state
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Returns the number of states in the map
+    pub fn len(&self) -> usize {
+        self.entries.len() + self.collisions.len()
+    }
+    /// Removes all entries from the map
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.collisions.clear();
+    }
+    /// Returns the value associated with the given state (if any)
+    pub fn get(&self, fp: Fingerprint, state: &T, problem: &dyn Problem<State = T>) -> Option<V> {
+        let same = |other: &T| !problem.quick_neq(other, state) && other == state;
+
+        match self.entries.get(&fp) {
+            None => None,
+            Some((s, v)) if same(s) => Some(*v),
+            Some(_) => self.collisions.iter()
+                .find(|(f, s, _)| *f == fp && same(s))
+                .map(|(_, _, v)| *v),
+        }
+    }
+    /// Associates the given value with the given state which is assumed not
+    /// to belong to the map yet
+    pub fn insert(&mut self, fp: Fingerprint, state: Arc<T>, value: V) {
+        match self.entries.entry(fp) {
+            Entry::Occupied(_) => self.collisions.push((fp, state, value)),
+            Entry::Vacant(e)   => { e.insert((state, value)); },
+        }
+    }
+    /// Removes the given state from the map
+    pub fn remove(&mut self, fp: Fingerprint, state: &T, problem: &dyn Problem<State = T>) {
+        let same = |other: &T| !problem.quick_neq(other, state) && other == state;
+
+        if let Some((s, _)) = self.entries.get(&fp) {
+            if same(s) {
+                self.entries.remove(&fp);
+                // promote the first colliding entry (if any)
+                if let Some(pos) = self.collisions.iter().position(|(f, _, _)| *f == fp) {
+                    let (f, s, v) = self.collisions.swap_remove(pos);
+                    self.entries.insert(f, (s, v));
+                }
+            } else if let Some(pos) = self.collisions.iter().position(|(f, s, _)| *f == fp && same(s)) {
+                self.collisions.swap_remove(pos);
+            }
+        }
+    }
+    /// Iterates over all the states of the map
+    pub fn states(&self) -> States<'_, T, V> {
+        States { entries: self.entries.values(), collisions: self.collisions.iter() }
+    }
+    /// Iterates over all the values of the map
+    pub fn values(&self) -> impl Iterator<Item = V> + '_ {
+        self.entries.values().map(|(_, v)| *v)
+            .chain(self.collisions.iter().map(|(_, _, v)| *v))
+    }
+    /// Empties the map and iterates over the values it used to contain
+    pub fn drain_values(&mut self) -> impl Iterator<Item = V> + '_ {
+        self.entries.drain().map(|(_, (_, v))| v)
+            .chain(self.collisions.drain(..).map(|(_, _, v)| v))
+    }
+}
+
+/// An iterator over the states of a layer map. 
+/// 
+/// # Note
+/// This is a named type rather than an `impl Iterator` so that the borrow 
+/// checker knows it needs no drop glue: this is what lets the compilation 
+/// loop pass it to `next_variable` in the condition of a `while let`.
+pub(crate) struct States<'a, T, V> {
+    entries: Values<'a, Fingerprint, (Arc<T>, V)>,
+    collisions: Iter<'a, (Fingerprint, Arc<T>, V)>,
+}
+impl <'a, T, V> Iterator for States<'a, T, V> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(s, _)| s.as_ref())
+            .or_else(|| self.collisions.next().map(|(_, s, _)| s.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod test_layer_map {
+    use std::{sync::Arc, cell::Cell, hash::{Hash, Hasher}};
+
+    use crate::*;
+    use super::{Fingerprint, LayerMap};
+
+    thread_local! {
+        static EQ_CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// A state whose (supposedly expensive) equality test is monitored
+    #[derive(Debug, Clone)]
+    struct Big { checksum: u8, data: Vec<u8> }
+    impl PartialEq for Big {
+        fn eq(&self, other: &Self) -> bool {
+            EQ_CALLS.with(|c| c.set(c.get() + 1));
+            self.data == other.data
+        }
+    }
+    impl Eq for Big {}
+    impl Hash for Big {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.data.hash(state);
+        }
+    }
+    fn big(data: Vec<u8>) -> Big {
+        Big { checksum: data.iter().fold(0, |a, b| a.wrapping_add(*b)), data }
+    }
+
+    struct BigProblem { quick: bool }
+    impl Problem for BigProblem {
+        type State = Big;
+        fn nb_variables(&self) -> usize { 0 }
+        fn initial_state(&self) -> Self::State { big(vec![]) }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, state: &Self::State, _: Decision) -> Self::State { state.clone() }
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, _: Decision) -> isize { 0 }
+        fn next_variable(&self, _: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> { None }
+        fn for_each_in_domain(&self, _: Variable, _: &Self::State, _: &mut dyn DecisionCallback) {}
+        fn quick_neq(&self, a: &Self::State, b: &Self::State) -> bool {
+            self.quick && a.checksum != b.checksum
+        }
+    }
+
+    fn eq_calls() -> usize {
+        EQ_CALLS.with(|c| c.replace(0))
+    }
+
+    #[test]
+    fn get_finds_the_inserted_states_only() {
+        let pb = BigProblem { quick: false };
+        let mut map = LayerMap::default();
+        let a = big(vec![1, 2, 3]);
+        let b = big(vec![3, 2, 1]);
+        map.insert(Fingerprint::of(&a), Arc::new(a.clone()), 1);
+
+        assert_eq!(Some(1), map.get(Fingerprint::of(&a), &a, &pb));
+        assert_eq!(None,    map.get(Fingerprint::of(&b), &b, &pb));
+    }
+    #[test]
+    fn colliding_fingerprints_are_told_apart() {
+        let pb = BigProblem { quick: false };
+        let mut map = LayerMap::default();
+        let a = big(vec![1, 2, 3]);
+        let b = big(vec![3, 2, 1]);
+        let c = big(vec![4, 5, 6]);
+        map.insert(Fingerprint(0), Arc::new(a.clone()), 1);
+        map.insert(Fingerprint(0), Arc::new(b.clone()), 2);
+
+        assert_eq!(Some(1), map.get(Fingerprint(0), &a, &pb));
+        assert_eq!(Some(2), map.get(Fingerprint(0), &b, &pb));
+        assert_eq!(None,    map.get(Fingerprint(0), &c, &pb));
+
+        let mut values = map.values().collect::<Vec<_>>();
+        values.sort_unstable();
+        assert_eq!(vec![1, 2], values);
+        assert_eq!(2, map.states().count());
+    }
+    #[test]
+    fn remove_promotes_a_colliding_entry() {
+        let pb = BigProblem { quick: false };
+        let mut map = LayerMap::default();
+        let a = big(vec![1, 2, 3]);
+        let b = big(vec![3, 2, 1]);
+        let fp = Fingerprint::of(&a);
+        map.insert(fp, Arc::new(a.clone()), 1);
+        map.insert(fp, Arc::new(b.clone()), 2);
+
+        map.remove(fp, &a, &pb);
+        assert_eq!(None,    map.get(fp, &a, &pb));
+        assert_eq!(Some(2), map.get(fp, &b, &pb));
+        
+        map.remove(fp, &b, &pb);
+        assert!(map.is_empty());
+    }
+    #[test]
+    fn quick_neq_spares_the_equality_test() {
+        let mut map = LayerMap::default();
+        let a = big(vec![1, 2, 3]);
+        let b = big(vec![3, 2, 2]);
+        map.insert(Fingerprint(0), Arc::new(a.clone()), 1);
+        eq_calls();
+
+        let slow = BigProblem { quick: false };
+        assert_eq!(None, map.get(Fingerprint(0), &b, &slow));
+        assert_eq!(1, eq_calls());
+
+        let quick = BigProblem { quick: true };
+        assert_eq!(None, map.get(Fingerprint(0), &b, &quick));
+        assert_eq!(0, eq_calls());
+        assert_eq!(Some(1), map.get(Fingerprint(0), &a, &quick));
+        assert_eq!(1, eq_calls());
+    }
+    #[test]
+    fn drain_values_empties_the_map() {
+        let pb = BigProblem { quick: false };
+        let mut map = LayerMap::default();
+        let a = big(vec![1, 2, 3]);
+        map.insert(Fingerprint::of(&a), Arc::new(a.clone()), 1);
+        map.insert(Fingerprint::of(&a), Arc::new(big(vec![7])), 2);
+
+        let mut values = map.drain_values().collect::<Vec<_>>();
+        values.sort_unstable();
+        assert_eq!(vec![1, 2], values);
+        assert!(map.is_empty());
+        assert_eq!(None, map.get(Fingerprint::of(&a), &a, &pb));
+    }
+}
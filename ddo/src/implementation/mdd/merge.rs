@@ -0,0 +1,185 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the merge of the nodes of a relaxed layer by bounded
+//! groups. Instead of handing a whole (possibly huge) group of states to the
+//! relaxation at once, the states are merged by chunks of at most `chunk_size`
+//! states. The partial results are then merged the same way until only one 
+//! state remains. This bounds the time elapsed between two polls of the cutoff.
+
+use crate::{Relaxation, Cutoff, Reason};
+
+/// The default maximum number of states passed to one call to `merge`
+pub const DEFAULT_MERGE_CHUNK_SIZE: usize = 1024;
+
+/// Merges the given `states` in a tree whose nodes have at most `chunk_size`
+/// children. The cutoff is polled before each call to `merge`, and an error
+/// is returned as soon as it kicks in.
+///
+/// A state which would end up alone in its chunk is carried over as is
+/// rather than being merged on its own: `merge` is never called with fewer
+/// than two states.
+///
+/// # Note
+/// The `states` must not be empty
+pub(crate) fn merge_by_chunks<T: Clone>(
+    relaxation: &dyn Relaxation<State = T>,
+    cutoff: &dyn Cutoff,
+    chunk_size: usize,
+    states: &[&T],
+) -> Result<T, Reason> {
+    let chunk_size = chunk_size.max(2);
+    let mut partials = Vec::with_capacity(states.len().div_ceil(chunk_size));
+    for chunk in states.chunks(chunk_size) {
+        if let [single] = chunk {
+            partials.push((*single).clone());
+            continue;
+        }
+        if cutoff.must_stop() {
+            return Err(Reason::CutoffOccurred);
+        }
+        partials.push(relaxation.merge(&mut chunk.iter().copied()));
+    }
+    while partials.len() > 1 {
+        let carried = if partials.len() % chunk_size == 1 { partials.pop() } else { None };
+        let mut merged = Vec::with_capacity(partials.len().div_ceil(chunk_size) + 1);
+        for chunk in partials.chunks(chunk_size) {
+            if cutoff.must_stop() {
+                return Err(Reason::CutoffOccurred);
+            }
+            merged.push(relaxation.merge(&mut chunk.iter()));
+        }
+        merged.extend(carried);
+        partials = merged;
+    }
+    Ok(partials.pop().expect("cannot merge an empty set of states"))
+}
+
+#[cfg(test)]
+mod test_merge_by_chunks {
+    use std::cell::Cell;
+
+    use crate::{Relaxation, Decision, Cutoff, NoCutoff, Reason};
+
+    use super::merge_by_chunks;
+
+    /// Merges the states by component-wise max, and counts the calls to merge
+    #[derive(Default)]
+    struct MaxRelax {
+        calls: Cell<usize>,
+        largest: Cell<usize>,
+        smallest: Cell<Option<usize>>,
+    }
+    impl Relaxation for MaxRelax {
+        type State = [isize; 2];
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            let mut count = 0;
+            let merged = states.fold([isize::MIN; 2], |acc, s| {
+                count += 1;
+                [acc[0].max(s[0]), acc[1].max(s[1])]
+            });
+            self.calls.set(self.calls.get() + 1);
+            self.largest.set(self.largest.get().max(count));
+            self.smallest.set(Some(self.smallest.get().map_or(count, |c| c.min(count))));
+            merged
+        }
+        fn relax(&self, _: &Self::State, _: &Self::State, _: &Self::State, _: Decision, cost: isize) -> isize {
+            cost
+        }
+    }
+
+    /// Kicks in after the given number of polls
+    struct StopAfter(Cell<usize>);
+    impl Cutoff for StopAfter {
+        fn must_stop(&self) -> bool {
+            let left = self.0.get();
+            self.0.set(left.saturating_sub(1));
+            left == 0
+        }
+    }
+
+    fn states(n: isize) -> Vec<[isize; 2]> {
+        (0..n).map(|i| [(i * 7) % 101, (i * 13) % 97]).collect()
+    }
+
+    #[test]
+    fn the_result_is_the_same_as_a_single_merge_when_the_merge_is_associative() {
+        let states = states(1000);
+        let refs = states.iter().collect::<Vec<_>>();
+
+        let relax = MaxRelax::default();
+        let expected = relax.merge(&mut refs.iter().copied());
+        for chunk_size in [2, 3, 10, 999, 1000, 5000] {
+            assert_eq!(Ok(expected), merge_by_chunks(&relax, &NoCutoff, chunk_size, &refs));
+        }
+    }
+    #[test]
+    fn no_call_to_merge_exceeds_the_chunk_size() {
+        let states = states(1000);
+        let refs = states.iter().collect::<Vec<_>>();
+
+        let relax = MaxRelax::default();
+        merge_by_chunks(&relax, &NoCutoff, 10, &refs).unwrap();
+        assert_eq!(10, relax.largest.get());
+        assert_eq!(100 + 10 + 1, relax.calls.get());
+    }
+    #[test]
+    fn a_group_no_larger_than_the_chunk_size_is_merged_at_once() {
+        let states = states(10);
+        let refs = states.iter().collect::<Vec<_>>();
+
+        let relax = MaxRelax::default();
+        merge_by_chunks(&relax, &NoCutoff, 10, &refs).unwrap();
+        assert_eq!(1, relax.calls.get());
+    }
+    #[test]
+    fn the_cutoff_is_polled_between_two_chunks() {
+        let states = states(1000);
+        let refs = states.iter().collect::<Vec<_>>();
+
+        let relax = MaxRelax::default();
+        let result = merge_by_chunks(&relax, &StopAfter(Cell::new(3)), 10, &refs);
+        assert_eq!(Err(Reason::CutoffOccurred), result);
+        assert_eq!(3, relax.calls.get());
+    }
+    #[test]
+    fn merge_is_never_called_with_a_single_state() {
+        for n in [2, 3, 11, 21, 101, 1001] {
+            let states = states(n);
+            let refs = states.iter().collect::<Vec<_>>();
+            let expected = MaxRelax::default().merge(&mut refs.iter().copied());
+
+            for chunk_size in [2, 3, 10] {
+                let relax = MaxRelax::default();
+                assert_eq!(Ok(expected), merge_by_chunks(&relax, &NoCutoff, chunk_size, &refs));
+                assert!(relax.smallest.get().unwrap() >= 2);
+            }
+        }
+    }
+    #[test]
+    fn a_single_state_is_returned_as_is() {
+        let states = states(1);
+        let refs = states.iter().collect::<Vec<_>>();
+
+        let relax = MaxRelax::default();
+        assert_eq!(Ok(states[0]), merge_by_chunks(&relax, &NoCutoff, 10, &refs));
+        assert_eq!(0, relax.calls.get());
+    }
+}
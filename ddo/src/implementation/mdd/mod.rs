@@ -29,12 +29,26 @@
 mod node_flags;
 mod clean;
 mod pooled;
+mod progress;
+mod layer_map;
+mod queries;
+mod merge;
+mod poll;
+mod projected;
+mod width_profile;
 
 pub use node_flags::*;
 pub use clean::*;
 pub use pooled::*;
+pub use queries::*;
+pub use merge::*;
+pub use poll::*;
+pub use projected::*;
+pub(crate) use progress::*;
+pub(crate) use layer_map::*;
+pub(crate) use width_profile::*;
 
-use crate::{LAST_EXACT_LAYER, FRONTIER};
+use crate::{LAST_EXACT_LAYER, FRONTIER, ADAPTIVE};
 
 /// By default, the mdd implementation which you will want to use is the vector based
 /// implementation. In most cases, it is faster than everything else I have tried.
@@ -53,4 +67,9 @@ pub type DefaultMDDLEL<T> = Mdd<T, LAST_EXACT_LAYER>;
 /// So having a alias calling it the "default" DD implementation seems to make sense.
 /// 
 /// This is the variant implementation that produces a frontier cut-set when asked
-pub type DefaultMDDFC<T> = Mdd<T, FRONTIER>;
\ No newline at end of file
+pub type DefaultMDDFC<T> = Mdd<T, FRONTIER>;
+
+/// This is the variant implementation that chooses between a last exact layer
+/// and a frontier cut-set upon each compilation, depending on how deep the 
+/// relaxation kicked in (see `Mdd::with_adaptive_threshold`)
+pub type DefaultMDDAdaptive<T> = Mdd<T, ADAPTIVE>;
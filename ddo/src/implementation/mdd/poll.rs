@@ -0,0 +1,156 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the polling of the cutoff while a decision diagram is
+//! being compiled. Rather than checking the cutoff once per layer (a single
+//! layer may take seconds to develop), the DDs count the *node operations* 
+//! they perform and poll the cutoff every `interval` operations.
+//!
+//! # Worst case work between two polls
+//! With an interval of `K` operations, the work carried out between two polls
+//! of the cutoff by `Mdd` and `Pooled` is at most:
+//!
+//! * layer expansion: `K` operations, where the expansion of a node counts as
+//!   one operation plus one per branch. At worst, the last node expanded
+//!   before a poll adds the size of its domain to the `K` operations.
+//! * trimming (restriction and relaxation): `K` deleted nodes plus, with a
+//!   relaxation, the inbound edges of the last node which was redirected to
+//!   the merged node.
+//! * merging: one call to `Relaxation::merge` with at most `merge_chunk_size`
+//!   states (see `merge_by_chunks`).
+//! * bottom-up passes (local bounds and thresholds): `K` nodes plus the 
+//!   inbound edges of the last one of them.
+//!
+//! The work done once per layer is not interrupted: filtering the layer with
+//! the cache and the dominance checker, and sorting it before it is trimmed.
+//! It is bounded by `O(n log n)` where `n` is the number of nodes generated 
+//! in that layer. The same goes for the reconstruction of a solution (one
+//! step per variable) and the enumeration of the cut-set (one subproblem per 
+//! node of the cut-set). The `ProjectedMdd` only polls the cutoff once per 
+//! layer.
+
+use crate::{Cutoff, Reason};
+
+/// The default number of node operations between two polls of the cutoff
+pub const DEFAULT_POLL_INTERVAL: usize = 1024;
+
+/// Counts the node operations performed since the last poll of the cutoff
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CutoffPoll {
+    /// The number of node operations between two polls of the cutoff
+    interval: usize,
+    /// The number of node operations performed since the last poll
+    pending: usize,
+}
+impl Default for CutoffPoll {
+    fn default() -> Self {
+        Self::new(DEFAULT_POLL_INTERVAL)
+    }
+}
+
+impl CutoffPoll {
+    /// Creates a counter which polls the cutoff every `interval` operations
+    /// (an interval of zero is understood as one)
+    pub fn new(interval: usize) -> Self {
+        Self { interval: interval.max(1), pending: 0 }
+    }
+
+    /// Polls the cutoff right away and returns an error if it kicked in
+    pub fn poll(&mut self, cutoff: &dyn Cutoff) -> Result<(), Reason> {
+        self.pending = 0;
+        if cutoff.must_stop() {
+            Err(Reason::CutoffOccurred)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Records that `ops` node operations were performed, and polls the 
+    /// cutoff when the interval has elapsed
+    pub fn tick(&mut self, cutoff: &dyn Cutoff, ops: usize) -> Result<(), Reason> {
+        self.pending = self.pending.saturating_add(ops);
+        if self.pending >= self.interval {
+            self.poll(cutoff)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_cutoff_poll {
+    use std::cell::Cell;
+
+    use crate::{Cutoff, Reason};
+
+    use super::CutoffPoll;
+
+    /// Counts the polls and kicks in from the given poll onwards
+    struct CountPolls { polls: Cell<usize>, stop_at: usize }
+    impl Cutoff for CountPolls {
+        fn must_stop(&self) -> bool {
+            self.polls.set(self.polls.get() + 1);
+            self.polls.get() >= self.stop_at
+        }
+    }
+
+    #[test]
+    fn the_cutoff_is_polled_once_per_interval() {
+        let cutoff = CountPolls { polls: Cell::new(0), stop_at: usize::MAX };
+        let mut poll = CutoffPoll::new(10);
+        for _ in 0..35 {
+            poll.tick(&cutoff, 1).unwrap();
+        }
+        assert_eq!(3, cutoff.polls.get());
+    }
+    #[test]
+    fn a_large_tick_polls_right_away() {
+        let cutoff = CountPolls { polls: Cell::new(0), stop_at: usize::MAX };
+        let mut poll = CutoffPoll::new(10);
+        poll.tick(&cutoff, 25).unwrap();
+        assert_eq!(1, cutoff.polls.get());
+        poll.tick(&cutoff, 9).unwrap();
+        assert_eq!(1, cutoff.polls.get());
+    }
+    #[test]
+    fn an_explicit_poll_restarts_the_interval() {
+        let cutoff = CountPolls { polls: Cell::new(0), stop_at: usize::MAX };
+        let mut poll = CutoffPoll::new(10);
+        poll.tick(&cutoff, 9).unwrap();
+        poll.poll(&cutoff).unwrap();
+        poll.tick(&cutoff, 9).unwrap();
+        assert_eq!(1, cutoff.polls.get());
+    }
+    #[test]
+    fn a_zero_interval_polls_upon_each_operation() {
+        let cutoff = CountPolls { polls: Cell::new(0), stop_at: usize::MAX };
+        let mut poll = CutoffPoll::new(0);
+        poll.tick(&cutoff, 1).unwrap();
+        poll.tick(&cutoff, 1).unwrap();
+        assert_eq!(2, cutoff.polls.get());
+    }
+    #[test]
+    fn an_error_is_returned_when_the_cutoff_kicks_in() {
+        let cutoff = CountPolls { polls: Cell::new(0), stop_at: 2 };
+        let mut poll = CutoffPoll::new(2);
+        assert_eq!(Ok(()), poll.tick(&cutoff, 2));
+        assert_eq!(Ok(()), poll.tick(&cutoff, 1));
+        assert_eq!(Err(Reason::CutoffOccurred), poll.tick(&cutoff, 1));
+    }
+}
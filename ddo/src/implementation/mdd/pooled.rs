@@ -5,11 +5,11 @@
 //! ``Decision Diagram-Based Branch-and-Bound with Caching
 //! for Dominance and Suboptimality Detection''.
 
-use std::{sync::Arc, hash::Hash, collections::{hash_map::Entry, BTreeMap}, fmt::Debug};
+use std::{cell::Cell, sync::Arc, hash::Hash, collections::{hash_map::Entry, BTreeMap}, fmt::Debug, mem::size_of};
 
 use fxhash::FxHashMap;
 
-use crate::{NodeFlags, Decision, CompilationInput, Completion, Reason, CompilationType, Problem, DecisionDiagram, SubProblem, Solution, VizConfig, Variable, DominanceCheckResult};
+use crate::{merge_by_chunks, CutsetState, MddStats, WidthConflictPolicy, ExactnessPolicy, DEFAULT_MERGE_CHUNK_SIZE, CutoffPoll, NodeFlags, Decision, CompilationInput, Completion, Reason, CompilationType, Problem, ProblemCtx, DecisionDiagram, SubProblem, Solution, VizConfig, Variable, DominanceCheckResult, ProgressGuard, WidthProfile, RANKING_CHECK, guard_ranking, LayerMap, Fingerprint, arc_bytes};
 
 /// The identifier of a node: it indicates the position of the referenced node 
 /// in the ’nodes’ vector of the mdd structure.
@@ -39,11 +39,6 @@ struct Node<T> {
     /// The length of the longest path between the problem root and this
     /// specific node
     value_top: isize,
-    /// The length of the longest path between this node and the terminal node.
-    /// 
-    /// ### Note
-    /// This field is only ever populated after the MDD has been fully unrolled.
-    value_bot: isize,
     /// The identifier of the last edge on the longest path between the problem 
     /// root and this node if it exists.
     best: Option<EdgeId>,
@@ -52,12 +47,6 @@ struct Node<T> {
     inbound: EdgesListId,
     // The rough upper bound associated to this node
     rub: isize,
-    /// A threshold value to be stored in the cache that conditions the
-    /// re-exploration of other nodes with the same state.
-    /// 
-    /// ### Note
-    /// This field is only ever populated after the MDD has been fully unrolled.
-    theta: Option<isize>,
     /// A group of flag telling if the node is an exact node, if it is a relaxed
     /// node (helps to determine if the best path is an exact path) and if the
     /// node is reachable in a backwards traversal of the MDD starting at the
@@ -71,6 +60,35 @@ struct Node<T> {
     depth: usize,
 }
 
+/// The bounds of a node which are only ever needed once the node has been
+/// pruned or once the MDD has been fully unrolled. They are kept apart from
+/// the nodes (in the 'bounds' vector, indexed by node id) so that the nodes 
+/// stay small while the MDD is being compiled.
+#[derive(Debug, Clone, Copy)]
+struct Bounds {
+    /// The length of the longest path between this node and the terminal node.
+    value_bot: isize,
+    /// A threshold value to be stored in the cache that conditions the
+    /// re-exploration of other nodes with the same state.
+    theta: Option<isize>,
+}
+impl Default for Bounds {
+    fn default() -> Self {
+        Bounds { value_bot: isize::MIN, theta: None }
+    }
+}
+
+// The layout of these structures is the same as in the clean mdd (see the
+// size table in `clean.rs`), these asserts keep both versions in sync.
+#[cfg(target_pointer_width = "64")]
+const _: () = {
+    use std::mem::size_of;
+    assert!(size_of::<Node<u64>>()  == 64);
+    assert!(size_of::<Bounds>()     == 24);
+    assert!(size_of::<Edge>()       == 40);
+    assert!(size_of::<EdgesList>()  == 24);
+};
+
 /// Materializes one edge a.k.a arc from the decision diagram. It logically 
 /// connects two nodes and annotates the link with a decision and a cost.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -125,6 +143,9 @@ where
     /// nodes from all layers in the DD. A nice property is that all nodes
     /// belonging to one same layer form a sequence in the ‘nodes‘ vector.
     nodes: Vec<Node<T>>,
+    /// The bounds of the nodes of the decision diagram (see `Bounds`). This
+    /// vector is only sized to match `nodes` when the diagram is finalized.
+    bounds: Vec<Bounds>,
     /// This vector stores the information about all edges connecting the nodes 
     /// of the decision diagram.
     edges: Vec<Edge>,
@@ -139,13 +160,18 @@ where
     /// The rationale being that two transitions to the same state in the same
     /// layer should lead to the same node. This indexation helps ensuring 
     /// the uniqueness constraint in amortized O(1).
-    pool: FxHashMap<Arc<T>, NodeId>,
+    pool: LayerMap<T, NodeId>,
 
     /// Keeps track of the decisions that have been taken to reach the root
     /// of this DD, starting from the problem root.
     path_to_root: Vec<Decision>,
+    /// The residual subproblem this DD was compiled from, without its path
+    /// (which is kept in `path_to_root`). See `take_residual`.
+    residual: Option<SubProblem<T>>,
     /// The cutset of the decision diagram (only maintained for relaxed dd)
     cutset: Vec<NodeId>,
+    /// Whether the cut-set has been drained since the last compilation
+    cutset_state: CutsetState,
     /// The identifier of the best terminal node of the diagram (None when the
     /// problem compiled into this dd is infeasible)
     best_node: Option<NodeId>,
@@ -158,6 +184,44 @@ where
     /// A flag set to true when the longest r-t path of this decision diagram
     /// traverses no merged node (Exact Best Path Optimization aka EBPO).
     has_exact_best_path: bool,
+    /// The guard which makes sure the compilation terminates
+    progress: ProgressGuard,
+    /// The per-layer width multipliers of the problem being compiled
+    width_profile: WidthProfile,
+    /// The estimated size of the largest layer ever developed by this DD
+    peak_layer_bytes: usize,
+    /// The maximum number of states which are passed at once to the `merge`
+    /// of the relaxation (see `with_merge_chunk_size`)
+    merge_chunk_size: usize,
+    /// Polls the cutoff every so many node operations (see 
+    /// `with_poll_interval`)
+    poll: CutoffPoll,
+    /// Set when a layer had more nodes flagged by `StateRanking::must_keep`
+    /// than it could keep
+    keep_list_overflow: bool,
+    /// The number of layers which had more nodes flagged by 
+    /// `StateRanking::must_keep` than they could keep
+    width_conflicts: usize,
+    /// What to do when a layer has more nodes flagged by 
+    /// `StateRanking::must_keep` than it can keep
+    width_conflict: WidthConflictPolicy,
+    /// The rule which tells whether a node is exact (see `ExactnessPolicy`)
+    exactness: ExactnessPolicy,
+    /// Set once some nodes were pruned from a layer of the DD: the nodes of 
+    /// the layers below it have pruned siblings (see `child_exactness`)
+    pruned_layer: bool,
+    /// Scratch buffers holding the nodes of the layer being expanded and the
+    /// states leaving the pool. They are kept between compilations so as to
+    /// reuse their allocations.
+    to_expand: Vec<NodeId>,
+    to_remove: Vec<Arc<T>>,
+    /// The domain of the variable of the layer being expanded, when it does 
+    /// not depend on the state (see `Problem::domain_is_state_independent`).
+    /// It is kept between compilations so as to reuse its allocation.
+    domain: Vec<Decision>,
+    /// The scratch context which is passed on to the transition functions of
+    /// the problem. It is kept between compilations (see `ProblemCtx`).
+    ctx: ProblemCtx,
 }
 
 const NIL: EdgesListId = EdgesListId(0);
@@ -174,6 +238,12 @@ macro_rules! get {
     (mut edge     $id:expr, $dd:expr) => {&mut $dd.edges   [$id.0]};
     (    edgelist $id:expr, $dd:expr) => {&    $dd.edgelists[$id.0]};
     (mut edgelist $id:expr, $dd:expr) => {&mut $dd.edgelists[$id.0]};
+    (    bounds   $id:expr, $dd:expr) => {&    $dd.bounds  [$id.0]};
+    (mut bounds   $id:expr, $dd:expr) => {{
+        // the bounds are only allocated for the nodes that actually need them
+        if $dd.bounds.len() <= $id.0 { $dd.bounds.resize($id.0 + 1, Bounds::default()); }
+        &mut $dd.bounds[$id.0]
+    }};
 }
 
 /// This macro performs an action for each edge of a given node in the dd
@@ -197,11 +267,11 @@ macro_rules! append_edge_to {
         $dd.edgelists.push(EdgesList::Cons { head: new_eid, tail: get!(node $edge.to, $dd).inbound });
         
         let parent = get!(node $edge.from, $dd);
-        let parent_exact = parent.flags.is_exact();
+        let exact_via_parent = $dd.exactness.child_exactness(parent.flags.is_exact(), false, $dd.pruned_layer);
         let value = parent.value_top.saturating_add($edge.cost);
         
         let node = get!(mut node $edge.to, $dd);
-        let exact = parent_exact & node.flags.is_exact();
+        let exact = exact_via_parent & node.flags.is_exact();
         node.flags.set_exact(exact);
         node.inbound = lst_id;
 
@@ -227,8 +297,14 @@ where
 {
     type State = T;
 
-    fn compile(&mut self, input: &CompilationInput<Self::State>) -> Result<Completion, Reason> {
-        self._compile(input)
+    fn compile(&mut self, input: &CompilationInput<Self::State>, residual: SubProblem<Self::State>) -> Result<Completion, Reason> {
+        self._compile(input, residual)
+    }
+
+    fn take_residual(&mut self) -> Option<SubProblem<T>> {
+        let mut residual = self.residual.take()?;
+        residual.path = std::mem::take(&mut self.path_to_root);
+        Some(residual)
     }
 
     fn is_exact(&self) -> bool {
@@ -243,6 +319,12 @@ where
         self._best_solution()
     }
 
+    fn for_each_best_decision(&self, f: &mut dyn FnMut(Decision)) {
+        if let Some(id) = self.best_node {
+            Self::_for_each_path_decision(id, &self.path_to_root, &self.nodes, &self.edges, f)
+        }
+    }
+
     fn best_exact_value(&self) -> Option<isize> {
         self._best_exact_value()
     }
@@ -251,10 +333,54 @@ where
         self._best_exact_solution()
     }
 
+    fn best_state(&self) -> Option<&Self::State> {
+        self.best_node.map(|id| get!(node id, self).state.as_ref())
+    }
+
+    fn best_exact_state(&self) -> Option<&Self::State> {
+        self.best_exact_node.map(|id| get!(node id, self).state.as_ref())
+    }
+
+    fn for_each_exact_solution(&self, func: &mut dyn FnMut(isize, Solution)) {
+        self._for_each_exact_solution(func)
+    }
+
+    fn cutset_len(&self) -> usize {
+        self._cutset_len()
+    }
+
+    fn for_each_cutset_node<F>(&self, func: F)
+    where
+        F: FnMut(SubProblem<Self::State>) {
+        self._for_each_cutset_node(func)
+    }
+
     fn drain_cutset<F>(&mut self, func: F)
     where
         F: FnMut(SubProblem<Self::State>) {
-        self._drain_cutset(func)
+        // The cutset is emptied before any callback is invoked. This way, a
+        // callback which panics cannot leave a half consumed cutset behind.
+        let mut subproblems = vec![];
+        self._for_each_cutset_node(|sub| subproblems.push(sub));
+        self.cutset.clear();
+        self.cutset_state = CutsetState::Consumed;
+        subproblems.into_iter().for_each(func);
+    }
+
+    fn cutset_state(&self) -> CutsetState {
+        self.cutset_state
+    }
+
+    fn peak_layer_bytes(&self) -> usize {
+        self.peak_layer_bytes
+    }
+
+    fn stats(&self) -> MddStats {
+        MddStats {
+            keep_list_overflow: self.keep_list_overflow,
+            width_conflicts: self.width_conflicts,
+            ..MddStats::default()
+        }
     }
 }
 
@@ -266,6 +392,7 @@ where
         Self {
             layers: Default::default(),
             nodes: vec![],
+            bounds: vec![],
             edges: vec![],
             edgelists: vec![],
             //
@@ -273,26 +400,81 @@ where
             pool: Default::default(),
             //
             path_to_root: vec![],
+            residual: None,
             cutset: vec![],
+            cutset_state: CutsetState::Fresh,
             best_node: None,
             best_exact_node: None,
             is_exact: true,
             has_exact_best_path: false,
+            progress: ProgressGuard::default(),
+            width_profile: WidthProfile::default(),
+            peak_layer_bytes: 0,
+            merge_chunk_size: DEFAULT_MERGE_CHUNK_SIZE,
+            poll: CutoffPoll::default(),
+            keep_list_overflow: false,
+            width_conflicts: 0,
+            width_conflict: WidthConflictPolicy::default(),
+            exactness: ExactnessPolicy::default(),
+            pruned_layer: false,
+            to_expand: vec![],
+            to_remove: vec![],
+            domain: vec![],
+            ctx: ProblemCtx::default(),
         }
     }
+
+    /// Sets the maximum number of states which are passed at once to the 
+    /// `merge` of the relaxation (1024 by default). Larger groups of nodes are
+    /// merged by chunks, and the partial results are then merged together.
+    pub fn with_merge_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.merge_chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets the number of node operations performed between two polls of the
+    /// cutoff (1024 by default). The expansion of a node counts as one
+    /// operation plus one per branch; so does the deletion of a node when a
+    /// layer is trimmed. See the `poll` module for the worst case amount of 
+    /// work between two polls.
+    pub fn with_poll_interval(mut self, interval: usize) -> Self {
+        self.poll = CutoffPoll::new(interval);
+        self
+    }
+
+    /// Sets what to do when a layer has more nodes flagged by 
+    /// `StateRanking::must_keep` than its width lets it keep (by default, the
+    /// layer exceeds its width). See `WidthConflictPolicy`.
+    pub fn with_width_conflict_policy(mut self, policy: WidthConflictPolicy) -> Self {
+        self.width_conflict = policy;
+        self
+    }
+
+    /// Sets the rule which tells whether a node is exact (by default, the one
+    /// of `child_exactness`). See `ExactnessPolicy`.
+    pub fn with_exactness_policy(mut self, policy: ExactnessPolicy) -> Self {
+        self.exactness = policy;
+        self
+    }
     
     fn _clear(&mut self) {
         self.layers.clear();
         self.nodes.clear();
+        self.bounds.clear();
         self.edges.clear();
         self.edgelists.clear();
         self.pool.clear();
         self.path_to_root.clear();
+        self.residual = None;
         self.cutset.clear();
+        self.cutset_state = CutsetState::Fresh;
         self.best_node = None;
         self.best_exact_node = None;
         self.is_exact = true;
         self.has_exact_best_path = false;
+        self.keep_list_overflow = false;
+        self.width_conflicts = 0;
+        self.pruned_layer = false;
     }
 
     fn _best_value(&self) -> Option<isize> {
@@ -311,6 +493,15 @@ where
         self.best_exact_node.map(|id| self._best_path(id))
     }
 
+    fn _for_each_exact_solution(&self, func: &mut dyn FnMut(isize, Solution)) {
+        for id in self.pool.values() {
+            let node = get!(node id, self);
+            if node.flags.is_exact() || Some(id) == self.best_exact_node {
+                func(node.value_top, self._best_path(id))
+            }
+        }
+    }
+
     fn _best_path(&self, id: NodeId) -> Vec<Decision> {
         Self::_best_path_partial_borrow(id, &self.path_to_root, &self.nodes, &self.edges)
     }
@@ -321,100 +512,157 @@ where
         nodes: &[Node<T>],
         edges: &[Edge],
     ) -> Vec<Decision> {
-        let mut sol = root_pa.to_owned();
+        // the depth of the node is a good guess of the length of its path
+        let mut sol = Vec::with_capacity(root_pa.len().max(nodes[id.0].depth));
+        Self::_for_each_path_decision(id, root_pa, nodes, edges, |d| sol.push(d));
+        sol
+    }
+
+    /// Walks the best path from the given node up to the root without 
+    /// allocating. The decisions leading to the root come first, then those 
+    /// of the DD from the deepest one up.
+    fn _for_each_path_decision(
+        id: NodeId,
+        root_pa: &[Decision],
+        nodes: &[Node<T>],
+        edges: &[Edge],
+        mut f: impl FnMut(Decision),
+    ) {
+        root_pa.iter().copied().for_each(&mut f);
         let mut edge_id = nodes[id.0].best;
         while let Some(eid) = edge_id {
             let edge = edges[eid.0];
-            sol.push(edge.decision);
+            f(edge.decision);
             edge_id = nodes[edge.from.0].best;
         }
-        sol
     }
 
-    fn _compile(&mut self, input: &CompilationInput<T>) -> Result<Completion, Reason> {
+    fn _compile(&mut self, input: &CompilationInput<T>, residual: SubProblem<T>) -> Result<Completion, Reason> {
         self._clear();
-        self._initialize(input);
+        self._initialize(input, residual);
 
-        while let Some(var) = input.problem.next_variable(self.curr_l.0, &mut self.pool.keys().map(|s| s.as_ref())) {
+        while let Some(var) = input.problem.next_variable(self.curr_l.0, &mut self.pool.states()) {
             // Did the cutoff kick in ?
-            if input.cutoff.must_stop() {
-                return Err(Reason::CutoffOccurred);
-            }
+            self.poll.poll(input.cutoff)?;
+            // Does the compilation still make progress ?
+            let sample = self.pool.states().next();
+            self.progress.check(input.problem.nb_variables(), var, self.curr_l.0, || sample.and_then(|s| input.problem.dump_state(s)))?;
 
             if self.pool.is_empty() {
                 break;
             }
 
-            let curr_l = self._move_to_next_layer(input, var);
-
+            let mut curr_l = std::mem::take(&mut self.to_expand);
+            self._move_to_next_layer(input, var, &mut curr_l)?;
+
+            let mask = input.problem.filter_layer(var, &mut curr_l.iter().map(|id| self.nodes[id.0].state.as_ref()));
+            // when the domain does not depend on the state, it is only queried once
+            let mut domain = std::mem::take(&mut self.domain);
+            domain.clear();
+            let shared_domain = input.problem.domain_is_state_independent(var);
+            if let (true, Some(id)) = (shared_domain, curr_l.first()) {
+                input.problem.for_each_in_domain(var, self.nodes[id.0].state.as_ref(), &mut |decision: Decision| {
+                    if mask.allows(decision.value) {
+                        domain.push(decision);
+                    }
+                });
+            }
             for node_id in curr_l.iter() {
                 let state = self.nodes[node_id.0].state.clone();
                 let rub = input.relaxation.fast_upper_bound(state.as_ref());
                 self.nodes[node_id.0].rub = rub;
                 let ub = rub.saturating_add(self.nodes[node_id.0].value_top);
-                if ub > input.best_lb {
-                    input.problem.for_each_in_domain(var, state.as_ref(), &mut |decision| {
-                        self._branch_on(*node_id, decision, input.problem)
+                let mut branches = 0;
+                if ub > input.best_lb && shared_domain {
+                    for decision in domain.iter() {
+                        branches += 1;
+                        self._branch_on(*node_id, *decision, input.problem)
+                    }
+                } else if ub > input.best_lb {
+                    input.problem.for_each_in_domain(var, state.as_ref(), &mut |decision: Decision| {
+                        if mask.allows(decision.value) {
+                            branches += 1;
+                            self._branch_on(*node_id, decision, input.problem)
+                        }
                     })
                 }
+                self.poll.tick(input.cutoff, 1 + branches)?;
             }
+            self.domain = domain;
 
+            self.to_expand = curr_l;
             self.curr_l.0 += 1;
         }
 
-        self._finalize(input);
+        self._record_layer_bytes(self.pool.len());
+        self._finalize(input)?;
 
         Ok(Completion { 
             is_exact: self.is_exact(), 
-            best_value: self.best_node.map(|n| get!(node n, self).value_top) 
+            best_value: self.best_node.map(|n| get!(node n, self).value_top),
+            guaranteed_within: None,
+            stopped_by: None,
+            achieved_gap: None,
+            termination: None,
         })
     }
 
-    fn _initialize(&mut self, input: &CompilationInput<T>) {
-        self.path_to_root.extend_from_slice(&input.residual.path);
+    fn _initialize(&mut self, input: &CompilationInput<T>, residual: SubProblem<T>) {
+        self.progress.reset(input.problem.nb_variables(), &residual.path);
+        self.width_profile.reset(input.problem);
+        self.path_to_root = residual.path;
+        self.residual = Some(SubProblem { state: residual.state.clone(), path: vec![], ..residual });
         self.edgelists.push(EdgesList::Nil);
 
         let root_node_id = NodeId(0);
         
         let root_node = Node { 
-            state: input.residual.state.clone(), 
-            value_top: input.residual.value, 
-            value_bot: isize::MIN, 
+            state: residual.state.clone(), 
+            value_top: residual.value, 
             best: None, 
             inbound: NIL, 
             rub: isize::MAX, 
-            theta: None,
             flags: NodeFlags::new_exact(), 
-            depth: input.residual.depth,
+            depth: residual.depth,
         };
 
         self.nodes.push(root_node);
-        self.pool.insert(input.residual.state.clone(), root_node_id);
+        self.pool.insert(Fingerprint::of(residual.state.as_ref()), residual.state, root_node_id);
         self.edgelists.push(EdgesList::Nil);
-        self.curr_l = LayerId(input.residual.depth);
+        self.curr_l = LayerId(residual.depth);
     }
 
-    fn _finalize(&mut self, input: &CompilationInput<T>) {
+    fn _finalize(&mut self, input: &CompilationInput<T>) -> Result<(), Reason> {
         self._finalize_layers();
         self._find_best_node();
+        self.bounds.resize(self.nodes.len(), Bounds::default());
         self._finalize_exact(input);
         self._compute_frontier_cutset(input);
-        self._compute_local_bounds(input);
-        self._compute_thresholds(input);
+        self._compute_local_bounds(input)?;
+        self._compute_thresholds(input)
     }
 
 
-    fn _drain_cutset<F>(&mut self, mut func: F)
+    fn _cutset_len(&self) -> usize {
+        if self.best_value().is_none() {
+            return 0;
+        }
+        self.cutset.iter()
+            .filter(|id| self.nodes[id.0].flags.is_marked())
+            .count()
+    }
+
+    fn _for_each_cutset_node<F>(&self, mut func: F)
     where
         F: FnMut(SubProblem<T>),
     {
         if let Some(best_value) = self.best_value() {
-            for id in self.cutset.drain(..) {
+            for id in self.cutset.iter().copied() {
                 let node = get!(node id, self);
 
                 if node.flags.is_marked() {
                     let rub  = node.value_top.saturating_add(node.rub);
-                    let locb = node.value_top.saturating_add(node.value_bot);
+                    let locb = node.value_top.saturating_add(get!(bounds id, self).value_bot);
                     let ub = rub.min(locb).min(best_value);
 
                     func(SubProblem {
@@ -428,43 +676,45 @@ where
                         ),
                         ub,
                         depth: node.depth,
+                        priority_bonus: 0,
                     })
                 }
             }
         }
     }
     #[allow(clippy::redundant_closure_call)]
-    fn _compute_local_bounds(&mut self, input: &CompilationInput<T>) {
+    fn _compute_local_bounds(&mut self, input: &CompilationInput<T>) -> Result<(), Reason> {
         if !self.cutset.is_empty() && input.comp_type == CompilationType::Relaxed {
             // initialize last layer
             let (_, Layer { nodes }) = self.layers.last_key_value().unwrap();
             for id in nodes.iter() {
-                let node = get!(mut node id, self);
-                node.value_bot = 0;
-                node.flags.set_marked(true);
+                get!(mut bounds id, self).value_bot = 0;
+                get!(mut node id, self).flags.set_marked(true);
             }
 
             // traverse bottom-up
             // note: cache requires that all nodes have an associated locb. not only those below cutset
             for Layer { nodes } in self.layers.values().rev() {
                 for id in nodes.iter() {
+                    self.poll.tick(input.cutoff, 1)?;
                     let node = get!(node id, self);
-                    let value = node.value_bot;
+                    let value = get!(bounds id, self).value_bot;
                     if node.flags.is_marked() {
                         foreach!(edge of id, self, |edge: Edge| {
                             let using_edge = value.saturating_add(edge.cost);
-                            let parent = get!(mut node edge.from, self);
-                            parent.flags.set_marked(true);
+                            get!(mut node edge.from, self).flags.set_marked(true);
+                            let parent = get!(mut bounds edge.from, self);
                             parent.value_bot = parent.value_bot.max(using_edge);
                         });
                     }
                 }
             }
         }
+        Ok(())
     }
 
     #[allow(clippy::redundant_closure_call)]
-    fn _compute_thresholds(&mut self, input: &CompilationInput<T>) {
+    fn _compute_thresholds(&mut self, input: &CompilationInput<T>) -> Result<(), Reason> {
         if input.comp_type == CompilationType::Relaxed || self.is_exact {
             let mut best_known = input.best_lb;
 
@@ -474,7 +724,7 @@ where
 
                 for id in self.pool.values() {
                     if self.nodes[id.0].flags.is_exact() {
-                        self.nodes[id.0].theta = Some(best_known);
+                        self.bounds[id.0].theta = Some(best_known);
                     }
                 }
             }
@@ -482,7 +732,9 @@ where
 
             for Layer { nodes } in self.layers.values().rev() {
                 for id in nodes.iter() {
-                    let node = get!(mut node id, self);
+                    self.poll.tick(input.cutoff, 1)?;
+                    let node = get!(node id, self);
+                    let bounds = get!(mut bounds id, self);
 
                     if node.flags.is_deleted() {
                         continue;
@@ -492,25 +744,25 @@ where
                     if !node.flags.is_pruned_by_cache() {
                         let tot_rub = node.value_top.saturating_add(node.rub);
                         if tot_rub <= best_known {
-                            node.theta = Some(best_known.saturating_sub(node.rub));
+                            bounds.theta = Some(best_known.saturating_sub(node.rub));
                         } else if node.flags.is_cutset() {
-                            let tot_locb = node.value_top.saturating_add(node.value_bot);
+                            let tot_locb = node.value_top.saturating_add(bounds.value_bot);
                             if tot_locb <= best_known {
-                                let theta = node.theta.unwrap_or(isize::MAX);
-                                node.theta = Some(theta.min(best_known.saturating_sub(node.value_bot)));
+                                let theta = bounds.theta.unwrap_or(isize::MAX);
+                                bounds.theta = Some(theta.min(best_known.saturating_sub(bounds.value_bot)));
                             } else {
-                                node.theta = Some(node.value_top);
+                                bounds.theta = Some(node.value_top);
                             }
-                        } else if node.flags.is_exact() && node.theta.is_none() { // large theta for dangling nodes
-                            node.theta = Some(isize::MAX);
+                        } else if node.flags.is_exact() && bounds.theta.is_none() { // large theta for dangling nodes
+                            bounds.theta = Some(isize::MAX);
                         }
 
-                        Self::_maybe_update_cache(node, input);
+                        Self::_maybe_update_cache(node, bounds.theta, input);
                     }
                     // only propagate if you have an actual threshold
-                    if let Some(my_theta) = node.theta {
+                    if let Some(my_theta) = bounds.theta {
                         foreach!(edge of id, self, |edge: Edge| {
-                            let parent = get!(mut node edge.from, self);
+                            let parent = get!(mut bounds edge.from, self);
                             let theta  = parent.theta.unwrap_or(isize::MAX); 
                             parent.theta = Some(theta.min(my_theta.saturating_sub(edge.cost)));
                         });
@@ -518,11 +770,12 @@ where
                 }
             }
         }
+        Ok(())
     }
 
-    fn _maybe_update_cache(node: &Node<T>, input: &CompilationInput<T>) {
+    fn _maybe_update_cache(node: &Node<T>, theta: Option<isize>, input: &CompilationInput<T>) {
         // A node can only be added to the cache if it belongs to the cutset or is above it
-        if let Some(theta) = node.theta {
+        if let Some(theta) = theta {
             if node.flags.is_above_cutset() {
                 input.cache.update_threshold(
                     node.state.clone(), 
@@ -561,7 +814,7 @@ where
 
     fn _finalize_layers(&mut self) {
         let mut last_l = vec![];
-        self.pool.values().copied().for_each(|id| {
+        self.pool.values().for_each(|id| {
             let node = get!(mut node id, self);
             last_l.push(id);
             node.depth = self.curr_l.0;
@@ -573,13 +826,11 @@ where
         self.best_node = self
             .pool
             .values()
-            .copied()
             .max_by_key(|id| get!(node id, self).value_top);
         self.best_exact_node = self
             .pool
             .values()
             .filter(|id| get!(node id, self).flags.is_exact())
-            .copied()
             .max_by_key(|id| get!(node id, self).value_top);
     }
 
@@ -605,31 +856,43 @@ where
         }
     }
 
-    fn _move_to_next_layer(&mut self, input: &CompilationInput<T>, var: Variable) -> Vec<NodeId> {
-        let mut curr_l: Vec<NodeId> = self.pool.values().copied().collect();
-        let mut to_remove = vec![];
+    /// Updates the estimated size of the largest layer developed so far
+    fn _record_layer_bytes(&mut self, nb_nodes: usize) {
+        let layer_bytes = nb_nodes * (size_of::<Node<T>>() + arc_bytes::<T>());
+        self.peak_layer_bytes = self.peak_layer_bytes.max(layer_bytes);
+    }
+    /// Moves the nodes impacted by `var` out of the pool into a new layer and
+    /// fills `to_expand` with those nodes of the layer that must be expanded
+    fn _move_to_next_layer(&mut self, input: &CompilationInput<T>, var: Variable, to_expand: &mut Vec<NodeId>) -> Result<(), Reason> {
+        let mut curr_l: Vec<NodeId> = self.pool.values().collect();
+        self._record_layer_bytes(curr_l.len());
         curr_l.retain(|node_id| {
             let node = get!(mut node node_id, self);
             let state = node.state.as_ref();
             if input.problem.is_impacted_by(var, state) {
                 node.depth = self.curr_l.0;
-                to_remove.push(node.state.clone());
+                self.to_remove.push(node.state.clone());
                 true
             } else {
                 false
             }
         });
 
-        to_remove.drain(..).for_each(|s| { self.pool.remove(s.as_ref()); });
+        self.to_remove.drain(..).for_each(|s| { self.pool.remove(Fingerprint::of(s.as_ref()), s.as_ref(), input.problem); });
         
-        let mut to_expand = curr_l.clone(); // need to preserve layer to remember nodes pruned by cache
+        // need to preserve layer to remember nodes pruned by cache
+        to_expand.clear();
+        to_expand.extend_from_slice(&curr_l);
+        let unpruned = to_expand.len();
         if !self.layers.is_empty() {
-            self._filter_with_cache(input, &mut to_expand);
+            self._filter_with_cache(input, to_expand);
         }
-        self._filter_with_dominance(input, &mut to_expand);
+        self._filter_with_dominance(input, to_expand);
+        self._collapse_feasibility_classes(input, to_expand);
+        self.pruned_layer |= to_expand.len() < unpruned;
 
         let len = self.nodes.len(); // but need to add the potential merged node
-        self._squash_if_needed(input, &mut to_expand);
+        self._squash_if_needed(input, to_expand)?;
         if self.nodes.len() > len {
             curr_l.push(NodeId(len));
         }
@@ -637,8 +900,7 @@ where
         if !curr_l.is_empty() {
             self.layers.insert(self.curr_l.0, Layer { nodes: curr_l });
         }
-
-        to_expand
+        Ok(())
     }
 
     fn _filter_with_dominance(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
@@ -648,7 +910,7 @@ where
             if node.flags.is_exact() {
                 let DominanceCheckResult { dominated, threshold } = input.dominance.is_dominated_or_insert(node.state.clone(), node.depth, node.value_top);
                 if dominated {
-                    node.theta = threshold; // set theta for later propagation
+                    get!(mut bounds id, self).theta = threshold; // set theta for later propagation
                     false
                 } else {
                     true
@@ -659,6 +921,41 @@ where
         });
     }
 
+    fn _collapse_feasibility_classes(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
+        if input.comp_type == CompilationType::Exact {
+            return;
+        }
+        match input.problem.objective_depth() {
+            Some(depth) if self.curr_l.0 >= depth => {},
+            _ => return,
+        }
+
+        self._sort_layer(input, curr_l);
+
+        // past the objective depth, the best node of each class dominates all
+        // the other nodes of that class
+        let mut kept = FxHashMap::<u64, isize>::default();
+        curr_l.retain(|id| {
+            let node = get!(mut node id, self);
+            if !node.flags.is_exact() {
+                return true;
+            }
+            match input.problem.feasibility_class(node.state.as_ref()) {
+                None => true,
+                Some(class) => match kept.entry(class) {
+                    Entry::Vacant(e) => {
+                        e.insert(node.value_top);
+                        true
+                    },
+                    Entry::Occupied(e) => {
+                        get!(mut bounds id, self).theta = Some(*e.get()); // set theta for later propagation
+                        false
+                    }
+                }
+            }
+        });
+    }
+
     fn _filter_with_cache(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
         curr_l.retain(|id| {
             let node = get!(mut node id, self);
@@ -668,7 +965,7 @@ where
                     true
                 } else {
                     node.flags.set_pruned_by_cache(true);
-                    node.theta = Some(threshold.value); // set theta for later propagation
+                    get!(mut bounds id, self).theta = Some(threshold.value); // set theta for later propagation
                     false
                 }
             } else {
@@ -684,26 +981,25 @@ where
         problem: &dyn Problem<State = T>,
     ) {
         let state = get!(node from_id, self).state.as_ref();
-        let next_state = Arc::new(problem.transition(state, decision));
-        let cost = problem.transition_cost(state, next_state.as_ref(), decision);
+        let next_state = Arc::new(problem.transition_with_ctx(state, decision, &mut self.ctx));
+        let cost = problem.transition_cost_with_ctx(state, next_state.as_ref(), decision, &mut self.ctx);
 
-        match self.pool.entry(next_state.clone()) {
-            Entry::Vacant(e) => {
+        let fingerprint = Fingerprint::of(next_state.as_ref());
+        match self.pool.get(fingerprint, next_state.as_ref(), problem) {
+            None => {
                 let parent = get!(node from_id, self);
                 let node_id = NodeId(self.nodes.len());
                 let mut flags = NodeFlags::new_exact();
-                flags.set_exact(parent.flags.is_exact());
+                flags.set_exact(self.exactness.child_exactness(parent.flags.is_exact(), false, self.pruned_layer));
 
                 self.nodes.push(Node {
-                    state: next_state,
+                    state: next_state.clone(),
                     value_top: parent.value_top.saturating_add(cost),
-                    value_bot: isize::MIN,
                     //
                     best: None,
                     inbound: NIL,
                     //
                     rub: isize::MAX,
-                    theta: None,
                     flags,
                     depth: parent.depth + 1, // value will be updated when expanded
                 });
@@ -713,10 +1009,9 @@ where
                     decision,
                     cost,
                 });
-                e.insert(node_id);
+                self.pool.insert(fingerprint, next_state, node_id);
             }
-            Entry::Occupied(e) => {
-                let node_id = *e.get();
+            Some(node_id) => {
                 append_edge_to!(self, Edge {
                     from: from_id,
                     to  : node_id,
@@ -728,51 +1023,125 @@ where
     }
 
 
-    fn _squash_if_needed(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
+    /// Returns the maximum width of the layer currently being developed (the
+    /// width of the heuristic scaled by the width profile of the problem)
+    fn _layer_width(&self, input: &CompilationInput<T>) -> usize {
+        self.width_profile.width(input.max_width, self.curr_l.0)
+    }
+
+    fn _squash_if_needed(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) -> Result<(), Reason> {
+        let width = self._layer_width(input);
         match input.comp_type {
             CompilationType::Exact => { /* do nothing: you want to explore the complete DD */ }
             CompilationType::Restricted => {
-                if curr_l.len() > input.max_width {
-                    self._restrict(input, curr_l)
+                if curr_l.len() > width {
+                    self._restrict(input, curr_l)?;
                 }
             },
             CompilationType::Relaxed => {
-                if curr_l.len() > input.max_width && self.layers.len() >= 2 {
-                    self._relax(input, curr_l)
+                if curr_l.len() > width && self.layers.len() >= 2 {
+                    self._relax(input, curr_l)?;
                 }
             },
         }
+        Ok(())
     }
 
-    fn _restrict(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
-        self.is_exact = false;
-        curr_l.sort_unstable_by(|a, b| {
+    /// Sorts the nodes of the current layer from the most to the least 
+    /// promising one. When the ranking check is enabled, a panic of the state
+    /// ranking is reported along with the last pair of nodes it compared.
+    fn _sort_layer(&self, input: &CompilationInput<T>, curr_l: &mut [NodeId]) {
+        let last = Cell::new(None);
+        guard_ranking(|| curr_l.sort_unstable_by(|a, b| {
+            if RANKING_CHECK {
+                last.set(Some((*a, *b)));
+            }
             get!(node a, self).value_top
                 .cmp(&get!(node b, self).value_top)
                 .then_with(|| input.ranking.compare(get!(node a, self).state.as_ref(), get!(node b, self).state.as_ref()))
                 .reverse()
-        }); // reverse because greater means more likely to be kept
+        }), // reverse because greater means more likely to be kept
+        || {
+            let root = get!(node NodeId(0), self).depth;
+            match last.get() {
+                Some((a, b)) => (
+                    format!("nodes at depth {} of the subproblem rooted at depth {root}", get!(node a, self).depth),
+                    input.ranking.dump_state(get!(node a, self).state.as_ref())
+                        .zip(input.ranking.dump_state(get!(node b, self).state.as_ref()))
+                ),
+                None => (format!("nodes of the subproblem rooted at depth {root}"), None),
+            }
+        });
+    }
+
+    /// Moves the nodes which must be kept (see `StateRanking::must_keep`) 
+    /// ahead of the sorted layer, provided that they fit in the `slots` which
+    /// survive the trimming of the layer or that the width conflict policy 
+    /// lets the layer exceed its width. The relative order of the nodes is
+    /// preserved otherwise. It returns the number of nodes which survive the 
+    /// trimming of the layer.
+    fn _promote_kept_nodes(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>, slots: usize) -> Result<usize, Reason> {
+        let must_keep = |id: &NodeId| input.ranking.must_keep(get!(node id, self).state.as_ref());
+        let nb_kept = curr_l.iter().filter(|id| must_keep(id)).count();
+        if nb_kept == 0 {
+            return Ok(slots);
+        }
+        let conflict = nb_kept > slots;
+        if !conflict || self.width_conflict == WidthConflictPolicy::ExceedWidth {
+            let (mut kept, others): (Vec<NodeId>, Vec<NodeId>) = curr_l.iter().partition(|id| must_keep(id));
+            kept.extend(others);
+            *curr_l = kept;
+        }
+        if conflict {
+            self.keep_list_overflow = true;
+            self.width_conflicts += 1;
+            match self.width_conflict {
+                WidthConflictPolicy::ExceedWidth => {},
+                WidthConflictPolicy::MergeExact => return Ok(slots),
+                WidthConflictPolicy::Error => {
+                    let depth = get!(node curr_l[0], self).depth;
+                    return Err(Reason::WidthConflict { depth, flagged: nb_kept, slots });
+                },
+            }
+        }
+        Ok(slots.max(nb_kept))
+    }
+
+    fn _restrict(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) -> Result<(), Reason> {
+        let width = self._layer_width(input);
+        self._sort_layer(input, curr_l);
+        let width = self._promote_kept_nodes(input, curr_l, width)?;
+        // all the nodes are kept when they were all flagged (see `WidthConflictPolicy`)
+        if curr_l.len() <= width {
+            return Ok(());
+        }
+        self.pruned_layer = true;
+        self.is_exact = false;
 
-        for drop_id in curr_l.iter().skip(input.max_width).copied() {
+        for drop_id in curr_l.iter().skip(width).copied() {
             get!(mut node drop_id, self).flags.set_deleted(true);
+            self.poll.tick(input.cutoff, 1)?;
         }
 
-        curr_l.truncate(input.max_width);
+        curr_l.truncate(width);
+        Ok(())
     }
 
     #[allow(clippy::redundant_closure_call)]
-    fn _relax(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) {
-        self.is_exact = false;
-        curr_l.sort_unstable_by(|a, b| {
-            get!(node a, self).value_top
-                .cmp(&get!(node b, self).value_top)
-                .then_with(|| input.ranking.compare(get!(node a, self).state.as_ref(), get!(node b, self).state.as_ref()))
-                .reverse()
-        }); // reverse because greater means more likely to be kept
+    fn _relax(&mut self, input: &CompilationInput<T>, curr_l: &mut Vec<NodeId>) -> Result<(), Reason> {
+        let width = self._layer_width(input);
+        self._sort_layer(input, curr_l);
+        let slots = self._promote_kept_nodes(input, curr_l, width - 1)?;
 
         //--
-        let (keep, merge) = curr_l.split_at_mut(input.max_width - 1);
-        let merged = Arc::new(input.relaxation.merge(&mut merge.iter().map(|id| get!(node id, self).state.as_ref())));
+        let (keep, merge) = curr_l.split_at_mut(slots);
+        // a group of one node loses nothing: it stays as is (and exact)
+        if merge.len() < 2 {
+            return Ok(());
+        }
+        self.is_exact = false;
+        let states = merge.iter().map(|id| get!(node id, self).state.as_ref()).collect::<Vec<_>>();
+        let merged = Arc::new(merge_by_chunks(input.relaxation, input.cutoff, self.merge_chunk_size, &states)?);
 
         let recycled = keep.iter().find(|id| get!(node *id, self).state.eq(&merged)).copied();
 
@@ -781,12 +1150,10 @@ where
             self.nodes.push(Node {
                 state: merged.clone(),
                 value_top: isize::MIN,
-                value_bot: isize::MIN,
                 best: None,    // yet
                 inbound: NIL,  // yet
                 //
                 rub: isize::MAX,
-                theta: None,
                 flags: NodeFlags::new_relaxed(),
                 depth: get!(node merge[0], self).depth,
             });
@@ -798,7 +1165,9 @@ where
         for drop_id in merge {
             get!(mut node drop_id, self).flags.set_deleted(true);
 
+            let mut rerouted = 0;
             foreach!(edge of drop_id, self, |edge: Edge| {
+                rerouted += 1;
                 let src   = get!(node edge.from, self).state.as_ref();
                 let dst   = get!(node edge.to,   self).state.as_ref();
                 let rcost = input.relaxation.relax(src, dst, merged.as_ref(), edge.decision, edge.cost);
@@ -810,16 +1179,18 @@ where
                     cost: rcost
                 });
             });
+            self.poll.tick(input.cutoff, 1 + rerouted)?;
         }
 
         if recycled.is_some() {
-            curr_l.truncate(input.max_width);
-            let saved_id = curr_l[input.max_width - 1];
+            curr_l.truncate(slots + 1);
+            let saved_id = curr_l[slots];
             self.nodes[saved_id.0].flags.set_deleted(false);
         } else {
-            curr_l.truncate(input.max_width - 1);
+            curr_l.truncate(slots);
             curr_l.push(merged_id);
         }
+        Ok(())
     }
 }
 
@@ -934,7 +1305,7 @@ where T: Debug + Eq + PartialEq + Hash + Clone {
         let color = Self::node_color(node, merged);
         let peripheries = Self::node_peripheries(node);
         let group = self.node_group(node);
-        let label = Self::node_label(node, state, config);
+        let label = Self::node_label(node, &self.bounds.get(id).copied().unwrap_or_default(), state, config);
 
         format!("shape={shape},style=filled,color={color},peripheries={peripheries},group=\"{group}\",label=\"{label}\"")
     }
@@ -976,20 +1347,20 @@ where T: Debug + Eq + PartialEq + Hash + Clone {
         }
     }
     /// Creates text label to place inside of the node when displaying it
-    fn node_label(node: &Node<T>, state: &T, config: &VizConfig) -> String {
+    fn node_label(node: &Node<T>, bounds: &Bounds, state: &T, config: &VizConfig) -> String {
         let mut out = format!("{state:?}");
 
         if config.show_value {
             out.push_str(&format!("\\nval: {}", node.value_top));
         }
         if config.show_locb {
-        out.push_str(&format!("\\nlocb: {}", Self::extreme(node.value_bot)));
+        out.push_str(&format!("\\nlocb: {}", Self::extreme(bounds.value_bot)));
         }
         if config.show_rub {
             out.push_str(&format!("\\nrub: {}", Self::extreme(node.rub)));
         }
         if config.show_threshold {
-            out.push_str(&format!("\\ntheta: {}", Self::extreme(node.theta.unwrap_or(isize::MAX))));
+            out.push_str(&format!("\\ntheta: {}", Self::extreme(bounds.theta.unwrap_or(isize::MAX))));
         }
 
         out
@@ -1016,7 +1387,9 @@ mod test_default_mdd {
 
     use fxhash::FxHashMap;
 
-    use crate::{Variable, DecisionDiagram, SubProblem, CompilationInput, Problem, Decision, Relaxation, StateRanking, NoCutoff, CompilationType, Cutoff, Reason, DecisionCallback, EmptyCache, SimpleCache, Cache, Pooled, VizConfigBuilder, Threshold, EmptyDominanceChecker};
+    use crate::{Variable, CutsetConsumed, CutsetState, DecisionDiagram, SubProblem, CompilationInput, Problem, Decision, Relaxation, StateRanking, NoCutoff, CompilationType, Cutoff, Reason, DecisionCallback, EmptyCache, SimpleCache, Cache, Pooled, VizConfigBuilder, Threshold, EmptyDominanceChecker, ValueMask};
+
+    use super::NodeId;
 
     type DefaultMDD<State>    = Pooled<State>;
 
@@ -1031,6 +1404,14 @@ mod test_default_mdd {
     fn root_remembers_the_pa_from_the_fringe_node() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 1, value: 42}), 
+            value: 42, 
+            path:  vec![Decision{variable: Variable(0), value: 42}], 
+            ub:    isize::MAX,
+            depth: 1,
+            priority_bonus: 0,
+        };
         let mut input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1039,35 +1420,69 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  3,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 1, value: 42}), 
-                value: 42, 
-                path:  vec![Decision{variable: Variable(0), value: 42}], 
-                ub:    isize::MAX,
-                depth: 1,
-            },
             cache: &cache,
             dominance: &dominance,
         };
 
         let mut mdd = DefaultMDD::new();
-        assert!(mdd.compile(&input).is_ok());
+        assert!(mdd.compile(&input, residual.clone()).is_ok());
         assert_eq!(mdd.path_to_root, vec![Decision{variable: Variable(0), value: 42}]);
 
         input.comp_type = CompilationType::Relaxed;
-        assert!(mdd.compile(&input).is_ok());
+        assert!(mdd.compile(&input, residual.clone()).is_ok());
         assert_eq!(mdd.path_to_root, vec![Decision{variable: Variable(0), value: 42}]);
 
         input.comp_type = CompilationType::Restricted;
-        assert!(mdd.compile(&input).is_ok());
+        assert!(mdd.compile(&input, residual).is_ok());
         assert_eq!(mdd.path_to_root, vec![Decision{variable: Variable(0), value: 42}]);
     }
     
+    #[test]
+    fn compiling_moves_the_residual_into_the_root_layer() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 1, value: 42}), 
+            value: 42, 
+            path:  vec![Decision{variable: Variable(0), value: 42}], 
+            ub:    isize::MAX,
+            depth: 1,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  3,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let state = residual.state.clone();
+        let path  = residual.path.as_ptr();
+
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
+        // neither the path nor the state have been copied
+        assert_eq!(path, mdd.path_to_root.as_ptr());
+        assert!(Arc::ptr_eq(&state, &mdd.nodes[0].state));
+    }
+
     // In an exact setup, the dummy problem would be 3*3*3 = 9 large at the bottom level
     #[test]
     fn exact_completely_unrolls_the_mdd_no_matter_its_width() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1076,19 +1491,12 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
 
-        assert!(mdd.compile(&input).is_ok());
+        assert!(mdd.compile(&input, residual).is_ok());
         assert!(mdd.best_solution().is_some());
         assert_eq!(mdd.best_value(), Some(6));
         assert_eq!(mdd.best_solution().unwrap(),
@@ -1100,10 +1508,86 @@ mod test_default_mdd {
         );
     }
 
+    // The widest layer of the exact dummy mdd is the terminal one: it holds the 7 values 0..=6
+    #[test]
+    fn the_peak_layer_estimate_accounts_for_each_node_of_the_widest_layer() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert_eq!(0, mdd.peak_layer_bytes());
+
+        assert!(mdd.compile(&input, residual).is_ok());
+        let per_node = std::mem::size_of::<super::Node<DummyState>>() + crate::arc_bytes::<DummyState>();
+        assert_eq!(7 * per_node, mdd.peak_layer_bytes());
+    }
+
+    #[test]
+    fn each_exact_terminal_node_yields_a_solution() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        let mut values = vec![];
+        mdd.for_each_exact_solution(&mut |value, solution| {
+            assert_eq!(3, solution.len());
+            assert_eq!(value, solution.iter().map(|d| d.value).sum::<isize>());
+            values.push(value);
+        });
+        values.sort_unstable();
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 6], values);
+    }
+
     #[test]
     fn restricted_drops_the_less_interesting_nodes() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1112,19 +1596,12 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
 
-        assert!(mdd.compile(&input).is_ok());
+        assert!(mdd.compile(&input, residual).is_ok());
         assert!(mdd.best_solution().is_some());
         assert_eq!(mdd.best_value().unwrap(), 6);
         assert_eq!(mdd.best_solution().unwrap(),
@@ -1140,6 +1617,14 @@ mod test_default_mdd {
     fn exact_no_cutoff_completion_must_be_coherent_with_outcome() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1148,18 +1633,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
 
         assert!(result.is_ok());
         let completion = result.unwrap();
@@ -1170,6 +1648,14 @@ mod test_default_mdd {
     fn restricted_no_cutoff_completion_must_be_coherent_with_outcome_() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1178,18 +1664,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         
         assert!(result.is_ok());
         let completion = result.unwrap();
@@ -1200,6 +1679,14 @@ mod test_default_mdd {
     fn relaxed_no_cutoff_completion_must_be_coherent_with_outcome() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1208,18 +1695,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         
         assert!(result.is_ok());
         let completion = result.unwrap();
@@ -1236,6 +1716,14 @@ mod test_default_mdd {
     fn exact_fails_with_cutoff_when_cutoff_occurs() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1244,18 +1732,11 @@ mod test_default_mdd {
             cutoff:     &CutoffAlways,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_err());
         assert_eq!(Some(Reason::CutoffOccurred), result.err());
     }
@@ -1264,6 +1745,14 @@ mod test_default_mdd {
     fn restricted_fails_with_cutoff_when_cutoff_occurs() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1272,18 +1761,11 @@ mod test_default_mdd {
             cutoff:     &CutoffAlways,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_err());
         assert_eq!(Some(Reason::CutoffOccurred), result.err());
     }
@@ -1291,6 +1773,14 @@ mod test_default_mdd {
     fn relaxed_fails_with_cutoff_when_cutoff_occurs() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1299,83 +1789,234 @@ mod test_default_mdd {
             cutoff:     &CutoffAlways,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_err());
         assert_eq!(Some(Reason::CutoffOccurred), result.err());
     }
 
-    #[test]
-    fn relaxed_merges_the_less_interesting_nodes() {
+    /// A buggy problem whose next_variable never returns None and keeps
+    /// branching on the very same variable
+    #[derive(Copy, Clone)]
+    struct SelfLoopingProblem;
+    impl Problem for SelfLoopingProblem {
+        type State = DummyState;
+
+        fn nb_variables(&self)  -> usize { 3 }
+        fn initial_value(&self) -> isize { 0 }
+        fn initial_state(&self) -> Self::State {
+            DummyState { value: 0, depth: 0 }
+        }
+        fn transition(&self, state: &Self::State, decision: crate::Decision) -> Self::State {
+            DummyState { value: state.value + decision.value, depth: 1 + state.depth }
+        }
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, decision: crate::Decision) -> isize {
+            decision.value
+        }
+        fn next_variable(&self, _: usize, _: &mut dyn Iterator<Item = &Self::State>)
+            -> Option<crate::Variable> {
+            Some(Variable(0))
+        }
+        fn for_each_in_domain(&self, var: crate::Variable, _: &Self::State, f: &mut dyn DecisionCallback) {
+            f.apply(Decision {variable: var, value: 1})
+        }
+        fn dump_state(&self, state: &Self::State) -> Option<String> {
+            Some(format!("{state:?}"))
+        }
+    }
+    fn compile_self_looping(comp_type: CompilationType) -> Result<crate::Completion, Reason> {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
-            comp_type: crate::CompilationType::Relaxed,
-            problem:    &DummyProblem,
+            comp_type,
+            problem:    &SelfLoopingProblem,
             relaxation: &DummyRelax,
             ranking:    &DummyRanking,
             cutoff:     &NoCutoff,
-            max_width:  1,
+            max_width:  10,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
-
-        assert!(result.is_ok());
-        assert!(mdd.best_solution().is_some());
-        assert_eq!(mdd.best_value().unwrap(), 24);
-        assert_eq!(mdd.best_solution().unwrap(),
-                   vec![
-                       Decision{variable: Variable(2), value: 2},
-                       Decision{variable: Variable(1), value: 0}, // that's a relaxed edge
-                       Decision{variable: Variable(0), value: 2},
-                   ]
-        );
+        mdd.compile(&input, residual)
     }
-
+    #[cfg(not(feature = "progress-check"))]
     #[test]
-    fn relaxed_populates_the_cutset_and_will_not_squash_first_layer() {
-        let cache = EmptyCache::new();
-        let dominance = EmptyDominanceChecker::default();
-        let input = CompilationInput {
-            comp_type: crate::CompilationType::Relaxed,
-            problem:    &DummyProblem,
-            relaxation: &DummyRelax,
-            ranking:    &DummyRanking,
-            cutoff:     &NoCutoff,
-            max_width:  1,
-            best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
+    fn a_self_looping_problem_fails_when_the_layer_limit_is_exceeded() {
+        for comp_type in [CompilationType::Exact, CompilationType::Restricted, CompilationType::Relaxed] {
+            let result = compile_self_looping(comp_type);
+            assert_eq!(Some(Reason::LayerLimitExceeded { variable: Variable(0), depth: 3 }), result.err());
+        }
+    }
+    #[cfg(feature = "progress-check")]
+    #[test]
+    fn a_self_looping_problem_fails_the_progress_check() {
+        for comp_type in [CompilationType::Exact, CompilationType::Restricted, CompilationType::Relaxed] {
+            let result = compile_self_looping(comp_type);
+            assert_eq!(Some(Reason::NoProgress { 
+                    variable: Variable(0), 
+                    depth: 1, 
+                    state: Some("DummyState { value: 1, depth: 1 }".to_string()) 
+                }), 
+                result.err());
+        }
+    }
+
+    /// The same problem as `DummyProblem`, except that all decisions are free.
+    /// Hence, all the nodes of a layer have the same value and must be ranked.
+    #[derive(Copy, Clone)]
+    struct FreeProblem;
+    impl Problem for FreeProblem {
+        type State = DummyState;
+
+        fn nb_variables(&self)  -> usize { DummyProblem.nb_variables() }
+        fn initial_value(&self) -> isize { 0 }
+        fn initial_state(&self) -> Self::State { DummyProblem.initial_state() }
+        fn transition(&self, state: &Self::State, decision: crate::Decision) -> Self::State {
+            DummyProblem.transition(state, decision)
+        }
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, _: crate::Decision) -> isize {
+            0
+        }
+        fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+            -> Option<crate::Variable> {
+            DummyProblem.next_variable(depth, next_layer)
+        }
+        fn for_each_in_domain(&self, var: crate::Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            DummyProblem.for_each_in_domain(var, state, f)
+        }
+    }
+    /// A ranking which cannot compare anything
+    struct PanickingRanking;
+    impl StateRanking for PanickingRanking {
+        type State = DummyState;
+
+        fn compare(&self, _: &Self::State, _: &Self::State) -> Ordering {
+            panic!("cannot rank these states")
+        }
+        fn dump_state(&self, state: &Self::State) -> Option<String> {
+            Some(format!("{state:?}"))
+        }
+    }
+    fn compile_with_panicking_ranking(comp_type: CompilationType) -> Result<crate::Completion, Reason> {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type,
+            problem:    &FreeProblem,
+            relaxation: &DummyRelax,
+            ranking:    &PanickingRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        mdd.compile(&input, residual)
+    }
+    #[cfg(not(feature = "ranking-check"))]
+    #[test]
+    #[should_panic(expected = "cannot rank these states")]
+    fn a_panicking_ranking_propagates_its_panic() {
+        let _ = compile_with_panicking_ranking(CompilationType::Restricted);
+    }
+    #[cfg(feature = "ranking-check")]
+    #[test]
+    fn a_panicking_ranking_is_reported_with_the_nodes_it_compared() {
+        for comp_type in [CompilationType::Restricted, CompilationType::Relaxed] {
+            let err = std::panic::catch_unwind(|| compile_with_panicking_ranking(comp_type)).unwrap_err();
+            let msg = err.downcast_ref::<String>().unwrap();
+            assert!(msg.starts_with("node ordering panicked while comparing nodes at depth "), "{msg}");
+            assert!(msg.contains(" of the subproblem rooted at depth 0: cannot rank these states\n"), "{msg}");
+            assert!(msg.contains("\n  left : DummyState { "), "{msg}");
+            assert!(msg.contains("\n  right: DummyState { "), "{msg}");
+        }
+    }
+
+    #[test]
+    fn relaxed_merges_the_less_interesting_nodes() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
+
+        assert!(result.is_ok());
+        assert!(mdd.best_solution().is_some());
+        assert_eq!(mdd.best_value().unwrap(), 24);
+        assert_eq!(mdd.best_solution().unwrap(),
+                   vec![
+                       Decision{variable: Variable(2), value: 2},
+                       Decision{variable: Variable(1), value: 0}, // that's a relaxed edge
+                       Decision{variable: Variable(0), value: 2},
+                   ]
+        );
+    }
+
+    #[test]
+    fn relaxed_populates_the_cutset_and_will_not_squash_first_layer() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         let mut cutset = vec![];
@@ -1383,10 +2024,152 @@ mod test_default_mdd {
         assert_eq!(cutset.len(), 3); // L1 was not squashed even though it was 3 wide
     }
 
+    #[test]
+    fn a_panicking_callback_leaves_no_half_drained_cutset() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual.clone()).is_ok());
+        let mut expected = vec![];
+        mdd.for_each_cutset_node(|n| expected.push(n));
+
+        let mut seen = 0;
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| 
+            mdd.drain_cutset(|_| {
+                seen += 1;
+                if seen == 2 {
+                    panic!("a bug in the bookkeeping of the caller");
+                }
+            })
+        ));
+        assert!(outcome.is_err());
+        assert_eq!(0, mdd.cutset_len());
+        let mut leftovers = vec![];
+        mdd.drain_cutset(|n| leftovers.push(n));
+        assert!(leftovers.is_empty());
+
+        // the dd can be compiled again and yields the very same cutset
+        assert!(mdd.compile(&input, residual).is_ok());
+        let mut drained = vec![];
+        mdd.drain_cutset(|n| drained.push(n));
+        assert_eq!(expected, drained);
+    }
+
+    #[test]
+    fn the_cutset_can_be_inspected_before_it_is_drained() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
+        assert_eq!(3, mdd.cutset_len());
+
+        let mut first = vec![];
+        mdd.for_each_cutset_node(|n| first.push(n));
+        let mut second = vec![];
+        mdd.for_each_cutset_node(|n| second.push(n));
+        let mut drained = vec![];
+        mdd.drain_cutset(|n| drained.push(n));
+
+        assert_eq!(3, first.len());
+        assert_eq!(first, second);
+        assert_eq!(first, drained);
+        assert_eq!(0, mdd.cutset_len());
+    }
+
+    #[test]
+    fn the_cutset_is_delivered_once() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual.clone()).is_ok());
+        assert_eq!(CutsetState::Fresh, mdd.cutset_state());
+
+        let mut drained = vec![];
+        mdd.drain_cutset(|n| drained.push(n));
+        mdd.drain_cutset(|n| drained.push(n));
+        mdd.for_each_cutset_node(|n| drained.push(n));
+        assert_eq!(3, drained.len());
+        assert_eq!(CutsetState::Consumed, mdd.cutset_state());
+        assert_eq!(Err(CutsetConsumed), mdd.try_for_each_cutset_node(|n| drained.push(n)));
+        assert_eq!(3, drained.len());
+
+        // compiling the dd again yields a fresh cutset
+        assert!(mdd.compile(&input, residual).is_ok());
+        assert_eq!(CutsetState::Fresh, mdd.cutset_state());
+        let mut again = vec![];
+        assert_eq!(Ok(()), mdd.try_for_each_cutset_node(|n| again.push(n)));
+        assert_eq!(drained, again);
+    }
+
     #[test]
     fn an_exact_mdd_must_be_exact() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1395,18 +2178,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         assert!(mdd.is_exact())
@@ -1416,6 +2192,14 @@ mod test_default_mdd {
     fn a_relaxed_mdd_is_exact_as_long_as_no_merge_occurs() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1424,18 +2208,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  10,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         assert!(mdd.is_exact())
@@ -1445,6 +2222,14 @@ mod test_default_mdd {
     fn a_relaxed_mdd_is_not_exact_when_a_merge_occurred() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1453,18 +2238,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         assert!(!mdd.is_exact())
@@ -1473,6 +2251,14 @@ mod test_default_mdd {
     fn a_restricted_mdd_is_exact_as_long_as_no_restriction_occurs() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1481,18 +2267,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  10,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         assert!(mdd.is_exact())
@@ -1501,6 +2280,14 @@ mod test_default_mdd {
     fn a_restricted_mdd_is_not_exact_when_a_restriction_occurred() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1509,18 +2296,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  1,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         assert!(!mdd.is_exact())
@@ -1529,6 +2309,14 @@ mod test_default_mdd {
     fn when_the_problem_is_infeasible_there_is_no_solution() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyInfeasibleProblem,
@@ -1537,18 +2325,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  usize::MAX,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         assert!(mdd.best_solution().is_none())
     }
@@ -1556,6 +2337,14 @@ mod test_default_mdd {
     fn when_the_problem_is_infeasible_there_is_no_best_value() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyInfeasibleProblem,
@@ -1564,18 +2353,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  usize::MAX,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         assert!(mdd.best_value().is_none())
     }
@@ -1583,6 +2365,14 @@ mod test_default_mdd {
     fn exact_skips_node_with_an_ub_less_than_best_known_lb() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1591,18 +2381,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  usize::MAX,
             best_lb:    1000,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         assert!(mdd.best_solution().is_none())
     }
@@ -1610,6 +2393,14 @@ mod test_default_mdd {
     fn relaxed_skips_node_with_an_ub_less_than_best_known_lb() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1618,18 +2409,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  usize::MAX,
             best_lb:    1000,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         assert!(mdd.best_solution().is_none())
     }
@@ -1637,6 +2421,14 @@ mod test_default_mdd {
     fn restricted_skips_node_with_an_ub_less_than_best_known_lb() {
         let cache = EmptyCache::new();
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1645,18 +2437,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  usize::MAX,
             best_lb:    1000,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         assert!(mdd.best_solution().is_none())
     }
@@ -1668,6 +2453,14 @@ mod test_default_mdd {
         cache.update_threshold(Arc::new(DummyState{depth: 1, value: 1}), 1, 1, true);
         cache.update_threshold(Arc::new(DummyState{depth: 1, value: 2}), 1, 2, true);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Exact,
             problem:    &DummyProblem,
@@ -1676,18 +2469,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  usize::MAX,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         assert!(mdd.best_solution().is_none())
     }
@@ -1699,6 +2485,14 @@ mod test_default_mdd {
         cache.update_threshold(Arc::new(DummyState{depth: 1, value: 1}), 1, 1, true);
         cache.update_threshold(Arc::new(DummyState{depth: 1, value: 2}), 1, 2, true);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1707,18 +2501,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  usize::MAX,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         assert!(mdd.best_solution().is_none())
     }
@@ -1730,6 +2517,14 @@ mod test_default_mdd {
         cache.update_threshold(Arc::new(DummyState{depth: 1, value: 1}), 1, 1, true);
         cache.update_threshold(Arc::new(DummyState{depth: 1, value: 2}), 1, 2, true);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1738,18 +2533,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  usize::MAX,
             best_lb:    isize::MIN,
-            residual: &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         assert!(mdd.best_solution().is_none())
     }
@@ -1759,6 +2547,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&DummyProblem);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1767,18 +2563,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  10,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         assert!(mdd.is_exact());
@@ -1812,6 +2601,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&DummyProblem);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1820,18 +2617,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  10,
             best_lb:    isize::MIN,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         assert!(mdd.is_exact());
@@ -1865,6 +2655,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&DummyProblem);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Restricted,
             problem:    &DummyProblem,
@@ -1873,18 +2671,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  10,
             best_lb:    15,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         assert!(mdd.is_exact());
@@ -1918,6 +2709,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&DummyProblem);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &DummyProblem,
@@ -1926,18 +2725,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  10,
             best_lb:    15,
-            residual:  &SubProblem { 
-                state: Arc::new(DummyState{depth: 0, value: 0}), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
         
         assert!(mdd.is_exact());
@@ -2008,9 +2800,9 @@ mod test_default_mdd {
                 'M' => Some(Variable(2)),
                 'e' => Some(Variable(2)),
                 'f' => Some(Variable(2)),
-                'g' => Some(Variable(0)),
-                'h' => Some(Variable(0)),
-                'i' => Some(Variable(0)),
+                'g' => Some(Variable(3)),
+                'h' => Some(Variable(3)),
+                'i' => Some(Variable(3)),
                 _   => None,
             }
         }
@@ -2098,6 +2890,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&LocBoundsAndThresholdsExamplePb);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &LocBoundsAndThresholdsExamplePb,
@@ -2106,18 +2906,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  3,
             best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
 
         assert!(!mdd.is_exact());
@@ -2177,6 +2970,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&LocBoundsAndThresholdsExamplePb);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type: crate::CompilationType::Relaxed,
             problem:    &LocBoundsAndThresholdsExamplePb,
@@ -2185,18 +2986,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  3,
             best_lb:    15,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let result = mdd.compile(&input);
+        let result = mdd.compile(&input, residual);
         assert!(result.is_ok());
 
         assert!(!mdd.is_exact());
@@ -2254,6 +3048,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&LocBoundsAndThresholdsExamplePb);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type:  crate::CompilationType::Relaxed,
             problem:    &LocBoundsAndThresholdsExamplePb,
@@ -2262,18 +3064,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  3,
             best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let _ = mdd.compile(&input);
+        let _ = mdd.compile(&input, residual);
         
         let dot = include_str!("../../../../resources/visualisation_tests/default_viz.dot");
         let config = VizConfigBuilder::default().build().unwrap();            
@@ -2287,6 +3082,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&LocBoundsAndThresholdsExamplePb);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type:  crate::CompilationType::Relaxed,
             problem:    &LocBoundsAndThresholdsExamplePb,
@@ -2295,18 +3098,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  3,
             best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let _ = mdd.compile(&input);
+        let _ = mdd.compile(&input, residual);
         
         let dot = include_str!("../../../../resources/visualisation_tests/terse_viz.dot");
         let config = VizConfigBuilder::default()
@@ -2325,6 +3121,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&LocBoundsAndThresholdsExamplePb);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type:  crate::CompilationType::Relaxed,
             problem:    &LocBoundsAndThresholdsExamplePb,
@@ -2333,18 +3137,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  3,
             best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let _ = mdd.compile(&input);
+        let _ = mdd.compile(&input, residual);
         
         let dot = include_str!("../../../../resources/visualisation_tests/deleted_viz.dot");
         let config = VizConfigBuilder::default()
@@ -2363,6 +3160,14 @@ mod test_default_mdd {
         let mut cache = SimpleCache::default();
         cache.initialize(&LocBoundsAndThresholdsExamplePb);
         let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new('r'), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
         let input = CompilationInput {
             comp_type:  crate::CompilationType::Relaxed,
             problem:    &LocBoundsAndThresholdsExamplePb,
@@ -2371,18 +3176,11 @@ mod test_default_mdd {
             cutoff:     &NoCutoff,
             max_width:  3,
             best_lb:    0,
-            residual: &SubProblem { 
-                state: Arc::new('r'), 
-                value: 0, 
-                path:  vec![], 
-                ub:    isize::MAX,
-                depth: 0,
-            },
             cache: &cache,
             dominance: &dominance,
         };
         let mut mdd = DefaultMDD::new();
-        let _ = mdd.compile(&input);
+        let _ = mdd.compile(&input, residual);
         
         let dot = include_str!("../../../../resources/visualisation_tests/clusters_viz.dot");
         let config = VizConfigBuilder::default()
@@ -2401,6 +3199,221 @@ mod test_default_mdd {
         s.lines().map(|l| l.trim()).collect()
     }
 
+    /// Compiles the exact DD of `DummyProblem` and returns the ids of the
+    /// nodes at the given depth
+    fn exact_layer(mdd: &mut DefaultMDD<DummyState>, depth: usize) -> Vec<NodeId> {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        assert!(mdd.compile(&input, residual).is_ok());
+        (0..mdd.nodes.len()).map(NodeId).filter(|id| mdd.nodes[id.0].depth == depth).collect()
+    }
+
+    #[test]
+    fn a_group_of_one_node_is_not_merged_and_stays_exact() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let relax = CountingRelax::default();
+        let mut mdd = DefaultMDD::new();
+        let mut layer = exact_layer(&mut mdd, 2);
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &relax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  layer.len(),
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let nb_nodes = mdd.nodes.len();
+        assert!(mdd._relax(&input, &mut layer).is_ok());
+
+        assert!(relax.groups.borrow().is_empty());
+        assert_eq!(5, layer.len());
+        assert_eq!(nb_nodes, mdd.nodes.len());
+        for id in layer {
+            let flags = mdd.nodes[id.0].flags;
+            assert!(flags.is_exact());
+            assert!(!flags.is_relaxed());
+            assert!(!flags.is_deleted());
+        }
+    }
+    #[test]
+    fn a_group_of_two_nodes_is_merged_into_one_inexact_node() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let relax = CountingRelax::default();
+        let mut mdd = DefaultMDD::new();
+        let mut layer = exact_layer(&mut mdd, 2);
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &relax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  layer.len() - 1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        assert!(mdd._relax(&input, &mut layer).is_ok());
+
+        assert_eq!(vec![2], *relax.groups.borrow());
+        assert_eq!(4, layer.len());
+        let (merged, kept) = layer.split_last().unwrap();
+        assert!(kept.iter().all(|id| mdd.nodes[id.0].flags.is_exact()));
+        assert!(mdd.nodes[merged.0].flags.is_relaxed());
+        assert!(!mdd.nodes[merged.0].flags.is_exact());
+    }
+    #[test]
+    fn a_large_group_is_merged_by_chunks_of_at_least_two_nodes() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let relax = CountingRelax::default();
+        let mut mdd = DefaultMDD::new().with_merge_chunk_size(3);
+        let mut layer = exact_layer(&mut mdd, 3);
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &DummyProblem,
+            relaxation: &relax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        assert!(mdd._relax(&input, &mut layer).is_ok());
+
+        // 7 nodes: two chunks of 3, the last node is carried over
+        assert_eq!(vec![3, 3, 3], *relax.groups.borrow());
+        assert_eq!(1, layer.len());
+        assert!(mdd.nodes[layer[0].0].flags.is_relaxed());
+        assert_eq!(DummyState { value: 6, depth: 3 }, *mdd.nodes[layer[0].0].state);
+    }
+
+    /// Counts the nodes at the given depth which survived the trimming of
+    /// their layer
+    fn surviving_nodes(mdd: &DefaultMDD<DummyState>, depth: usize) -> usize {
+        mdd.nodes.iter().filter(|n| n.depth == depth && !n.flags.is_deleted()).count()
+    }
+
+    #[test]
+    fn restricted_respects_the_width_profile_of_the_problem() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let problem = DummyProfiledProblem(vec![1, 2, 1]);
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Restricted,
+            problem:    &problem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        assert_eq!(1, surviving_nodes(&mdd, 0));
+        assert_eq!(2, surviving_nodes(&mdd, 1));
+        assert_eq!(1, surviving_nodes(&mdd, 2));
+        assert_eq!(Some(6), mdd.best_value());
+    }
+    #[test]
+    fn relaxed_respects_the_width_profile_of_the_problem() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let relax = CountingRelax::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let problem = DummyProfiledProblem(vec![1, 1, 3]);
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Relaxed,
+            problem:    &problem,
+            relaxation: &relax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  1,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
+
+        // the 5 nodes of the third layer: two are kept and three are merged
+        assert_eq!(vec![3], *relax.groups.borrow());
+        assert_eq!(3, surviving_nodes(&mdd, 1));
+        assert_eq!(3, surviving_nodes(&mdd, 2));
+    }
+
+    #[test]
+    fn the_values_filtered_out_of_a_layer_are_skipped_by_all_its_nodes() {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let residual = SubProblem { 
+            state: Arc::new(DummyState{depth: 0, value: 0}), 
+            value: 0, 
+            path:  vec![], 
+            ub:    isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let input = CompilationInput {
+            comp_type: crate::CompilationType::Exact,
+            problem:    &DummyFilteredProblem,
+            relaxation: &DummyRelax,
+            ranking:    &DummyRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        assert!(mdd.compile(&input, residual).is_ok());
+        // the value 2 is skipped on the first two layers only
+        assert!(mdd.is_exact());
+        assert_eq!(Some(4), mdd.best_value());
+    }
+
     #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
     struct DummyState {
         value: isize,
@@ -2448,6 +3461,64 @@ mod test_default_mdd {
         }
     }
 
+    /// Behaves like `DummyProblem` but scales the width of its layers with
+    /// the given width profile
+    struct DummyProfiledProblem(Vec<usize>);
+    impl Problem for DummyProfiledProblem {
+        type State = DummyState;
+
+        fn nb_variables(&self)  -> usize { DummyProblem.nb_variables() }
+        fn initial_value(&self) -> isize { DummyProblem.initial_value() }
+        fn initial_state(&self) -> Self::State { DummyProblem.initial_state() }
+        fn transition(&self, state: &Self::State, decision: crate::Decision) -> Self::State {
+            DummyProblem.transition(state, decision)
+        }
+        fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: crate::Decision) -> isize {
+            DummyProblem.transition_cost(source, dest, decision)
+        }
+        fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+            -> Option<crate::Variable> {
+            DummyProblem.next_variable(depth, next_layer)
+        }
+        fn for_each_in_domain(&self, var: crate::Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            DummyProblem.for_each_in_domain(var, state, f)
+        }
+        fn width_profile(&self) -> Option<Vec<usize>> {
+            Some(self.0.clone())
+        }
+    }
+
+    /// Behaves like `DummyProblem` but skips the value 2 for all the nodes of
+    /// a layer unless one of them has a value greater than 1
+    struct DummyFilteredProblem;
+    impl Problem for DummyFilteredProblem {
+        type State = DummyState;
+
+        fn nb_variables(&self)  -> usize { DummyProblem.nb_variables() }
+        fn initial_value(&self) -> isize { DummyProblem.initial_value() }
+        fn initial_state(&self) -> Self::State { DummyProblem.initial_state() }
+        fn transition(&self, state: &Self::State, decision: crate::Decision) -> Self::State {
+            DummyProblem.transition(state, decision)
+        }
+        fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: crate::Decision) -> isize {
+            DummyProblem.transition_cost(source, dest, decision)
+        }
+        fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+            -> Option<crate::Variable> {
+            DummyProblem.next_variable(depth, next_layer)
+        }
+        fn for_each_in_domain(&self, var: crate::Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            DummyProblem.for_each_in_domain(var, state, f)
+        }
+        fn filter_layer(&self, _: crate::Variable, states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+            let mut mask = ValueMask::all();
+            if states.map(|s| s.value).max().is_none_or(|v| v <= 1) {
+                mask.skip(2);
+            }
+            mask
+        }
+    }
+
     #[derive(Clone,Copy)]
     struct DummyInfeasibleProblem;
     impl Problem for DummyInfeasibleProblem {
@@ -2508,6 +3579,25 @@ mod test_default_mdd {
         }
     }
 
+    /// Merges the states like `DummyMaxRelax` and records the number of
+    /// states given to each call to merge
+    #[derive(Default)]
+    struct CountingRelax {
+        groups: std::cell::RefCell<Vec<usize>>,
+    }
+    impl Relaxation for CountingRelax {
+        type State = DummyState;
+
+        fn merge(&self, s: &mut dyn Iterator<Item=&Self::State>) -> Self::State {
+            let states = s.copied().collect::<Vec<_>>();
+            self.groups.borrow_mut().push(states.len());
+            states.into_iter().max_by_key(|s| s.value).unwrap()
+        }
+        fn relax(&self, _: &Self::State, _: &Self::State, _: &Self::State, _: Decision, cost: isize) -> isize {
+            cost
+        }
+    }
+
     #[derive(Copy, Clone)]
     struct DummyRanking;
     impl StateRanking for DummyRanking {
@@ -0,0 +1,120 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the guard which makes sure that the compilation of a
+//! decision diagram always terminates. 
+//! 
+//! In all builds, the guard refuses to develop more than one layer per 
+//! variable of the problem (`Reason::LayerLimitExceeded`). When the 
+//! `progress-check` feature is enabled, it additionally asserts that the set 
+//! of free variables strictly decreases from one layer to the next and tells
+//! the dump of one of the offending states when it does not 
+//! (`Reason::NoProgress`, see `Problem::dump_state`).
+
+use crate::{Decision, Reason, Variable};
+
+/// True iff the compilation is performed with the full progress check
+const PROGRESS_CHECK: bool = cfg!(feature = "progress-check");
+
+/// The guard that is consulted by the decision diagrams before they develop
+/// a new layer.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProgressGuard {
+    /// The variables that have been assigned on the path to the current layer
+    /// (only maintained when the progress check is enabled)
+    assigned: Vec<bool>,
+}
+
+impl ProgressGuard {
+    /// Prepares the guard for the compilation of a residual problem whose root
+    /// is reached by the given path
+    pub fn reset(&mut self, nb_vars: usize, path: &[Decision]) {
+        if PROGRESS_CHECK {
+            self.assigned.clear();
+            self.assigned.resize(nb_vars, false);
+            for d in path {
+                self.mark(d.variable);
+            }
+        }
+    }
+
+    /// Verifies that it is safe to develop the layer at the given depth by 
+    /// branching on `variable`. The `dump` of one state of the layer is only
+    /// evaluated when the progress check fails.
+    pub fn check(
+        &mut self, 
+        nb_vars : usize, 
+        variable: Variable, 
+        depth   : usize, 
+        dump    : impl FnOnce() -> Option<String>
+    ) -> Result<(), Reason> {
+        if PROGRESS_CHECK {
+            let repeated = self.assigned.get(variable.id()).copied().unwrap_or(false);
+            if repeated || depth >= nb_vars {
+                let state = dump();
+                return Err(Reason::NoProgress { variable, depth, state });
+            }
+            self.mark(variable);
+        } else if depth >= nb_vars {
+            return Err(Reason::LayerLimitExceeded { variable, depth });
+        }
+        Ok(())
+    }
+
+    fn mark(&mut self, variable: Variable) {
+        let id = variable.id();
+        if id >= self.assigned.len() {
+            self.assigned.resize(id + 1, false);
+        }
+        self.assigned[id] = true;
+    }
+}
+
+#[cfg(test)]
+mod test_progress_guard {
+    use crate::{Decision, Reason, Variable};
+
+    use super::ProgressGuard;
+
+    #[test]
+    fn the_dump_is_not_evaluated_while_the_compilation_makes_progress() {
+        let mut guard = ProgressGuard::default();
+        guard.reset(3, &[Decision { variable: Variable(0), value: 1 }]);
+        assert_eq!(Ok(()), guard.check(3, Variable(1), 1, || unreachable!()));
+        assert_eq!(Ok(()), guard.check(3, Variable(2), 2, || unreachable!()));
+    }
+    #[cfg(feature = "progress-check")]
+    #[test]
+    fn a_repeated_variable_is_reported_without_a_dump_when_there_is_none() {
+        let mut guard = ProgressGuard::default();
+        guard.reset(3, &[Decision { variable: Variable(0), value: 1 }]);
+        assert_eq!(
+            Err(Reason::NoProgress { variable: Variable(0), depth: 1, state: None }), 
+            guard.check(3, Variable(0), 1, || None));
+    }
+    #[cfg(not(feature = "progress-check"))]
+    #[test]
+    fn the_layer_limit_is_enforced_without_a_dump() {
+        let mut guard = ProgressGuard::default();
+        guard.reset(3, &[]);
+        assert_eq!(
+            Err(Reason::LayerLimitExceeded { variable: Variable(0), depth: 3 }), 
+            guard.check(3, Variable(0), 3, || unreachable!()));
+    }
+}
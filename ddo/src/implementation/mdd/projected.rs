@@ -0,0 +1,924 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides a decision diagram which compiles its relaxed DDs in
+//! the abstract state space of a `Projection`. The restricted DDs (and the
+//! exact layers of the relaxed ones) are compiled with the concrete states.
+
+use std::{hash::Hash, sync::Arc, collections::hash_map::Entry};
+
+use fxhash::FxHashMap;
+
+use crate::{child_exactness, CompilationInput, CutsetState, CompilationType, Completion, Decision, DecisionDiagram, DefaultMDDLEL, MddStats, ProblemCtx, Projection, Reason, Solution, SubProblem, Variable, WidthProfile, LAST_EXACT_LAYER};
+
+/// A node of the exact (concrete) part of a relaxed DD
+struct ConcreteNode<T> {
+    state: Arc<T>,
+    value: isize,
+    /// The index of the best parent in the previous layer
+    parent: usize,
+    /// The decision labeling the arc from the best parent (None for the root)
+    decision: Option<Decision>,
+}
+
+/// An arc between two nodes of the abstract part of a relaxed DD
+#[derive(Debug, Clone, Copy)]
+struct AbstractArc {
+    from: usize,
+    decision: Decision,
+    cost: isize,
+}
+
+/// A node of the abstract part of a relaxed DD
+struct AbstractNode<A> {
+    state: A,
+    value: isize,
+    /// The index of the best parent in the previous layer. For the nodes of
+    /// the first abstract layer, this is a node of the last exact layer.
+    parent: usize,
+    /// The decision labeling the arc from the best parent (None in the first
+    /// abstract layer)
+    decision: Option<Decision>,
+    arcs: Vec<AbstractArc>,
+    /// The length of the longest path from this node to the terminal layer
+    /// (isize::MIN when there is no such path)
+    locb: isize,
+}
+
+/// This decision diagram compiles its relaxed DDs with a `Projection`. As
+/// long as the layers of a relaxed DD are exact, they are compiled with the
+/// concrete states of the problem. As soon as a layer grows too large, the
+/// last exact layer is projected onto the abstract state space and the rest
+/// of the DD is compiled with the abstract model. The nodes of the last exact
+/// layer are the cut-set of the DD (and it is a last exact layer cut-set).
+///
+/// The restricted DDs are compiled by the inner DD `D`. And so are the relaxed
+/// DDs when no projection was given (which is the case of the default value).
+///
+/// # Note
+/// The cache and the dominance checker are not consulted when the relaxed DD
+/// is compiled with a projection.
+pub struct ProjectedMdd<'a, T, A, D = DefaultMDDLEL<T>>
+where D: DecisionDiagram<State = T>
+{
+    projection: Option<&'a dyn Projection<State = T, Abstract = A>>,
+    inner: D,
+    /// Set when the last DD was a relaxed DD compiled with the projection
+    projected: bool,
+
+    path_to_root: Vec<Decision>,
+    /// The residual subproblem this DD was compiled from, without its path
+    /// (which is kept in `path_to_root`). See `take_residual`.
+    residual: Option<SubProblem<T>>,
+    /// The depth of the root of the DD
+    root_depth: usize,
+    /// The per-layer width multipliers of the problem being compiled
+    width_profile: WidthProfile,
+    concrete: Vec<Vec<ConcreteNode<T>>>,
+    abstracts: Vec<Vec<AbstractNode<A>>>,
+    /// The index of the abstract node of the first abstract layer onto which
+    /// each node of the last exact layer is projected
+    owner: Vec<usize>,
+    is_exact: bool,
+    /// The index of the best terminal node (in the terminal layer)
+    best_node: Option<usize>,
+    cutset: Vec<SubProblem<T>>,
+    /// Whether the cut-set has been drained since the last compilation
+    cutset_state: CutsetState,
+    /// The scratch context which is passed on to the transition functions of
+    /// the problem when the concrete layers are compiled (see `ProblemCtx`)
+    ctx: ProblemCtx,
+}
+
+impl<'a, T, A, D> ProjectedMdd<'a, T, A, D>
+where
+    T: Eq + Hash + Clone,
+    A: Eq + Hash + Clone,
+    D: DecisionDiagram<State = T> + Default,
+{
+    /// Creates a DD whose relaxed DDs are compiled with the given projection
+    pub fn new(projection: &'a dyn Projection<State = T, Abstract = A>) -> Self {
+        Self { projection: Some(projection), ..Self::default() }
+    }
+
+    fn _clear(&mut self) {
+        self.path_to_root.clear();
+        self.residual = None;
+        self.concrete.clear();
+        self.abstracts.clear();
+        self.owner.clear();
+        self.is_exact = true;
+        self.best_node = None;
+        self.cutset.clear();
+        self.cutset_state = CutsetState::Fresh;
+    }
+
+    fn _compile(
+        &mut self,
+        projection: &dyn Projection<State = T, Abstract = A>,
+        input: &CompilationInput<T>,
+        residual: SubProblem<T>
+    ) -> Result<Completion, Reason> {
+        self._clear();
+        self.path_to_root = residual.path;
+        self.residual = Some(SubProblem { state: residual.state.clone(), path: vec![], ..residual });
+        self.root_depth = residual.depth;
+        self.width_profile.reset(input.problem);
+        self.concrete.push(vec![ConcreteNode { state: residual.state, value: residual.value, parent: 0, decision: None }]);
+
+        // 1. EXACT LAYERS (concrete)
+        let mut depth = residual.depth;
+        let var = loop {
+            let curr = self.concrete.last().unwrap();
+            let Some(var) = input.problem.next_variable(depth, &mut curr.iter().map(|n| n.state.as_ref())) else {
+                self.best_node = Self::_argmax(curr.iter().map(|n| n.value));
+                return Ok(Completion::new(true, self.best_value()));
+            };
+            if input.cutoff.must_stop() {
+                return Err(Reason::CutoffOccurred);
+            }
+            let next = Self::_branch_concrete(input, curr, var, &mut self.ctx);
+            if next.len() > self.width_profile.width(input.max_width, depth + 1) {
+                break var;
+            }
+            self.concrete.push(next);
+            depth += 1;
+        };
+
+        // 2. RELAXED LAYERS (abstract)
+        // the abstract layers result from merging the projected states
+        self.is_exact = child_exactness(self.is_exact, true, false);
+        self._project_last_exact_layer(projection);
+        let mut var = Some(var);
+        while let Some(v) = var {
+            if input.cutoff.must_stop() {
+                return Err(Reason::CutoffOccurred);
+            }
+            let curr = self.abstracts.last().unwrap();
+            let mut next = Self::_branch_abstract(projection, input, curr, v);
+            let width = self.width_profile.width(input.max_width, depth + 1);
+            if next.len() > width {
+                next = Self::_merge(projection, width, next, curr);
+            }
+            depth += 1;
+            var = projection.next_variable(depth, &mut next.iter().map(|n| &n.state));
+            self.abstracts.push(next);
+        }
+
+        self.best_node = Self::_argmax(self.abstracts.last().unwrap().iter().map(|n| n.value));
+        self._compute_local_bounds();
+        self._compute_cutset(input);
+        Ok(Completion::new(false, self.best_value()))
+    }
+
+    fn _argmax(values: impl Iterator<Item = isize>) -> Option<usize> {
+        values.enumerate().max_by_key(|(_, v)| *v).map(|(i, _)| i)
+    }
+
+    fn _branch_concrete(input: &CompilationInput<T>, curr: &[ConcreteNode<T>], var: Variable, ctx: &mut ProblemCtx) -> Vec<ConcreteNode<T>> {
+        let mut next = vec![];
+        let mut index = FxHashMap::<Arc<T>, usize>::default();
+        let mask = input.problem.filter_layer(var, &mut curr.iter().map(|n| n.state.as_ref()));
+        for (parent, node) in curr.iter().enumerate() {
+            let rub = input.relaxation.fast_upper_bound(node.state.as_ref());
+            if rub.saturating_add(node.value) <= input.best_lb {
+                continue;
+            }
+            input.problem.for_each_in_domain(var, node.state.as_ref(), &mut |decision: Decision| {
+                if !mask.allows(decision.value) {
+                    return;
+                }
+                let state = Arc::new(input.problem.transition_with_ctx(node.state.as_ref(), decision, ctx));
+                let cost = input.problem.transition_cost_with_ctx(node.state.as_ref(), state.as_ref(), decision, ctx);
+                let value = node.value.saturating_add(cost);
+                match index.entry(state.clone()) {
+                    Entry::Occupied(e) => {
+                        let n: &mut ConcreteNode<T> = &mut next[*e.get()];
+                        if value > n.value {
+                            n.value = value;
+                            n.parent = parent;
+                            n.decision = Some(decision);
+                        }
+                    },
+                    Entry::Vacant(e) => {
+                        e.insert(next.len());
+                        next.push(ConcreteNode { state, value, parent, decision: Some(decision) });
+                    },
+                }
+            });
+        }
+        next
+    }
+
+    fn _project_last_exact_layer(&mut self, projection: &dyn Projection<State = T, Abstract = A>) {
+        let mut layer: Vec<AbstractNode<A>> = vec![];
+        let mut index = FxHashMap::<A, usize>::default();
+        for (i, node) in self.concrete.last().unwrap().iter().enumerate() {
+            let state = projection.project(node.state.as_ref());
+            let id = *index.entry(state.clone()).or_insert_with(|| {
+                layer.push(AbstractNode { state, value: isize::MIN, parent: i, decision: None, arcs: vec![], locb: isize::MIN });
+                layer.len() - 1
+            });
+            if node.value > layer[id].value {
+                layer[id].value = node.value;
+                layer[id].parent = i;
+            }
+            self.owner.push(id);
+        }
+        self.abstracts.push(layer);
+    }
+
+    fn _branch_abstract(
+        projection: &dyn Projection<State = T, Abstract = A>,
+        input: &CompilationInput<T>,
+        curr: &[AbstractNode<A>],
+        var: Variable
+    ) -> Vec<AbstractNode<A>> {
+        let mut next: Vec<AbstractNode<A>> = vec![];
+        let mut index = FxHashMap::<A, usize>::default();
+        for (from, node) in curr.iter().enumerate() {
+            let rub = projection.fast_upper_bound(&node.state);
+            if rub.saturating_add(node.value) <= input.best_lb {
+                continue;
+            }
+            projection.for_each_in_domain(var, &node.state, &mut |decision| {
+                let state = projection.transition_abstract(&node.state, decision);
+                let cost = projection.transition_cost_abstract(&node.state, &state, decision);
+                let id = *index.entry(state.clone()).or_insert_with(|| {
+                    next.push(AbstractNode { state, value: isize::MIN, parent: from, decision: None, arcs: vec![], locb: isize::MIN });
+                    next.len() - 1
+                });
+                Self::_add_arc(&mut next[id], node.value, AbstractArc { from, decision, cost });
+            });
+        }
+        next
+    }
+
+    fn _add_arc(node: &mut AbstractNode<A>, parent_value: isize, arc: AbstractArc) {
+        let value = parent_value.saturating_add(arc.cost);
+        if value > node.value {
+            node.value = value;
+            node.parent = arc.from;
+            node.decision = Some(arc.decision);
+        }
+        node.arcs.push(arc);
+    }
+
+    /// Keeps the `width - 1` most promising nodes of the layer and merges all
+    /// the others (unless there is only one of them)
+    fn _merge(
+        projection: &dyn Projection<State = T, Abstract = A>,
+        width: usize,
+        mut layer: Vec<AbstractNode<A>>,
+        prev: &[AbstractNode<A>]
+    ) -> Vec<AbstractNode<A>> {
+        if layer.len() < width.saturating_sub(1) + 2 {
+            return layer; // a group of one node is left untouched
+        }
+        // reverse because greater means more likely to be kept
+        layer.sort_unstable_by(|a, b| a.value.cmp(&b.value)
+            .then_with(|| projection.compare(&a.state, &b.state))
+            .reverse());
+
+        let merged = layer.split_off(width.saturating_sub(1));
+        let states = merged.iter().map(|n| n.state.clone()).collect::<Vec<_>>();
+        let state = projection.merge(&states);
+
+        let target = layer.iter().position(|n| n.state == state).unwrap_or_else(|| {
+            layer.push(AbstractNode { state, value: isize::MIN, parent: 0, decision: None, arcs: vec![], locb: isize::MIN });
+            layer.len() - 1
+        });
+        for node in merged {
+            for arc in node.arcs {
+                let src = &prev[arc.from];
+                let cost = projection.relax(&src.state, &node.state, &layer[target].state, arc.decision, arc.cost);
+                Self::_add_arc(&mut layer[target], src.value, AbstractArc { cost, ..arc });
+            }
+        }
+        layer
+    }
+
+    fn _compute_local_bounds(&mut self) {
+        self.abstracts.last_mut().unwrap().iter_mut().for_each(|n| n.locb = 0);
+        for k in (1..self.abstracts.len()).rev() {
+            let (above, below) = self.abstracts.split_at_mut(k);
+            let parents = above.last_mut().unwrap();
+            for node in below[0].iter().filter(|n| n.locb != isize::MIN) {
+                for arc in node.arcs.iter() {
+                    let parent = &mut parents[arc.from];
+                    parent.locb = parent.locb.max(node.locb.saturating_add(arc.cost));
+                }
+            }
+        }
+    }
+
+    fn _compute_cutset(&mut self, input: &CompilationInput<T>) {
+        let Some(best_value) = self.best_value() else { return };
+        let lel = self.concrete.len() - 1;
+        for (i, node) in self.concrete[lel].iter().enumerate() {
+            let locb = self.abstracts[0][self.owner[i]].locb;
+            if locb == isize::MIN {
+                continue;
+            }
+            let rub = node.value.saturating_add(input.relaxation.fast_upper_bound(node.state.as_ref()));
+            let ub = node.value.saturating_add(locb).min(rub).min(best_value);
+            self.cutset.push(SubProblem {
+                state: node.state.clone(),
+                value: node.value,
+                path: self._concrete_path(lel, i),
+                ub,
+                depth: self.root_depth + lel,
+                priority_bonus: 0,
+            });
+        }
+    }
+
+    /// Returns the path from the problem root to the given concrete node
+    fn _concrete_path(&self, mut layer: usize, mut id: usize) -> Vec<Decision> {
+        let mut path = vec![];
+        while let Some(decision) = self.concrete[layer][id].decision {
+            path.push(decision);
+            id = self.concrete[layer][id].parent;
+            layer -= 1;
+        }
+        path.extend(self.path_to_root.iter().rev().copied());
+        path.reverse();
+        path
+    }
+
+    fn _best_solution(&self) -> Option<Solution> {
+        let best = self.best_node?;
+        if self.is_exact {
+            return Some(self._concrete_path(self.concrete.len() - 1, best));
+        }
+        let mut path = vec![];
+        let mut id = best;
+        for layer in self.abstracts.iter().rev() {
+            let node = &layer[id];
+            path.extend(node.decision);
+            id = node.parent;
+        }
+        // id is now a node of the last exact layer
+        path.reverse();
+        let mut solution = self._concrete_path(self.concrete.len() - 1, id);
+        solution.append(&mut path);
+        Some(solution)
+    }
+
+    fn _terminal_value(&self, id: usize) -> isize {
+        if self.is_exact {
+            self.concrete.last().unwrap()[id].value
+        } else {
+            self.abstracts.last().unwrap()[id].value
+        }
+    }
+}
+
+impl<T, A, D> Default for ProjectedMdd<'_, T, A, D>
+where D: DecisionDiagram<State = T> + Default
+{
+    fn default() -> Self {
+        Self {
+            projection: None,
+            inner: D::default(),
+            projected: false,
+            path_to_root: vec![],
+            residual: None,
+            root_depth: 0,
+            width_profile: WidthProfile::default(),
+            concrete: vec![],
+            abstracts: vec![],
+            owner: vec![],
+            is_exact: true,
+            best_node: None,
+            cutset: vec![],
+            cutset_state: CutsetState::Fresh,
+            ctx: ProblemCtx::default(),
+        }
+    }
+}
+
+impl<T, A, D> DecisionDiagram for ProjectedMdd<'_, T, A, D>
+where
+    T: Eq + Hash + Clone,
+    A: Eq + Hash + Clone,
+    D: DecisionDiagram<State = T> + Default,
+{
+    type State = T;
+
+    fn compile(&mut self, input: &CompilationInput<T>, residual: SubProblem<T>) -> Result<Completion, Reason> {
+        match (input.comp_type, self.projection) {
+            (CompilationType::Relaxed, Some(projection)) => {
+                self.projected = true;
+                self._compile(projection, input, residual)
+            },
+            _ => {
+                self.projected = false;
+                self.inner.compile(input, residual)
+            }
+        }
+    }
+
+    fn take_residual(&mut self) -> Option<SubProblem<T>> {
+        if self.projected {
+            let mut residual = self.residual.take()?;
+            residual.path = std::mem::take(&mut self.path_to_root);
+            Some(residual)
+        } else {
+            self.inner.take_residual()
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        if self.projected { self.is_exact } else { self.inner.is_exact() }
+    }
+
+    fn best_value(&self) -> Option<isize> {
+        if self.projected {
+            self.best_node.map(|id| self._terminal_value(id))
+        } else {
+            self.inner.best_value()
+        }
+    }
+
+    fn best_solution(&self) -> Option<Solution> {
+        if self.projected { self._best_solution() } else { self.inner.best_solution() }
+    }
+
+    fn for_each_best_decision(&self, f: &mut dyn FnMut(Decision)) {
+        if self.projected {
+            self._best_solution().into_iter().flatten().for_each(f)
+        } else {
+            self.inner.for_each_best_decision(f)
+        }
+    }
+
+    fn best_exact_value(&self) -> Option<isize> {
+        if self.projected {
+            self.best_value().filter(|_| self.is_exact)
+        } else {
+            self.inner.best_exact_value()
+        }
+    }
+
+    fn best_exact_solution(&self) -> Option<Solution> {
+        if self.projected {
+            self._best_solution().filter(|_| self.is_exact)
+        } else {
+            self.inner.best_exact_solution()
+        }
+    }
+
+    fn best_state(&self) -> Option<&T> {
+        if self.projected { self.best_exact_state() } else { self.inner.best_state() }
+    }
+
+    fn best_exact_state(&self) -> Option<&T> {
+        if self.projected {
+            self.best_node.filter(|_| self.is_exact).map(|id| self.concrete.last().unwrap()[id].state.as_ref())
+        } else {
+            self.inner.best_exact_state()
+        }
+    }
+
+    fn cutset_len(&self) -> usize {
+        if self.projected { self.cutset.len() } else { self.inner.cutset_len() }
+    }
+
+    fn for_each_cutset_node<F>(&self, mut func: F)
+    where F: FnMut(SubProblem<T>)
+    {
+        if self.projected {
+            self.cutset.iter().cloned().for_each(&mut func)
+        } else {
+            self.inner.for_each_cutset_node(func)
+        }
+    }
+
+    fn drain_cutset<F>(&mut self, mut func: F)
+    where F: FnMut(SubProblem<T>)
+    {
+        if self.projected {
+            self.cutset_state = CutsetState::Consumed;
+            self.cutset.drain(..).for_each(&mut func)
+        } else {
+            self.inner.drain_cutset(func)
+        }
+    }
+
+    fn cutset_state(&self) -> CutsetState {
+        if self.projected { self.cutset_state } else { self.inner.cutset_state() }
+    }
+
+    fn peak_layer_bytes(&self) -> usize {
+        self.inner.peak_layer_bytes()
+    }
+
+    fn stats(&self) -> MddStats {
+        if self.projected {
+            MddStats {
+                nb_layers: self.concrete.len() + self.abstracts.len(),
+                first_inexact_layer: Some(self.concrete.len()).filter(|_| !self.is_exact),
+                cutset_type: LAST_EXACT_LAYER,
+                keep_list_overflow: false,
+                width_conflicts: 0,
+            }
+        } else {
+            self.inner.stats()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_projected_mdd {
+    use std::{cmp::Ordering, sync::Arc};
+
+    use crate::{CompilationInput, CompilationType, CutsetConsumed, CutsetState, Decision, DecisionCallback, DecisionDiagram, DefaultMDDLEL, EmptyCache, EmptyDominanceChecker, FixedWidth, MaxUB, NoCutoff, Problem, ProjectedMdd, Projection, Relaxation, SequentialSolver, SimpleFringe, Solver, StateRanking, SubProblem, Variable, Completion};
+
+    /// A knapsack whose states remember which items were taken. This makes
+    /// the states large, although the objective does not depend on it.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct KsState {
+        capacity: usize,
+        taken: Vec<bool>,
+    }
+    /// The abstract knapsack state: only the remaining capacity matters
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct KsAbstract {
+        depth: usize,
+        capacity: usize,
+    }
+
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<isize>,
+        weight: Vec<usize>,
+        profile: Option<Vec<usize>>,
+    }
+    impl Knapsack {
+        /// Generates a pseudo random instance with n items
+        fn generate(n: usize, seed: usize) -> Self {
+            let mut x = seed;
+            let mut next = || { x = (x * 1_103_515_245 + 12_345) % 2_147_483_648; x };
+            let profit = (0..n).map(|_| (next() % 90 + 10) as isize).collect::<Vec<_>>();
+            let weight = (0..n).map(|_| next() % 40 + 5).collect::<Vec<_>>();
+            let capacity = weight.iter().sum::<usize>() / 2;
+            Knapsack { capacity, profit, weight, profile: None }
+        }
+        fn remaining_profit(&self, depth: usize) -> isize {
+            self.profit[depth..].iter().sum()
+        }
+    }
+    impl Problem for Knapsack {
+        type State = KsState;
+
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> KsState {
+            KsState { capacity: self.capacity, taken: vec![] }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &KsState, decision: Decision) -> KsState {
+            let mut taken = state.taken.clone();
+            taken.push(decision.value == 1);
+            let used = if decision.value == 1 { self.weight[decision.variable.id()] } else { 0 };
+            KsState { capacity: state.capacity - used, taken }
+        }
+        fn transition_cost(&self, _: &KsState, _: &KsState, decision: Decision) -> isize {
+            self.profit[decision.variable.id()] * decision.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &KsState>) -> Option<Variable> {
+            Some(Variable(depth)).filter(|_| depth < self.nb_variables())
+        }
+        fn for_each_in_domain(&self, var: Variable, state: &KsState, f: &mut dyn DecisionCallback) {
+            if state.capacity >= self.weight[var.id()] {
+                f.apply(Decision { variable: var, value: 1 });
+            }
+            f.apply(Decision { variable: var, value: 0 });
+        }
+        fn width_profile(&self) -> Option<Vec<usize>> {
+            self.profile.clone()
+        }
+    }
+    struct KsRelax<'a>(&'a Knapsack);
+    impl Relaxation for KsRelax<'_> {
+        type State = KsState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &KsState>) -> KsState {
+            let mut states = states.peekable();
+            let depth = states.peek().map(|s| s.taken.len()).unwrap_or_default();
+            let capacity = states.map(|s| s.capacity).max().unwrap_or_default();
+            KsState { capacity, taken: vec![false; depth] }
+        }
+        fn relax(&self, _: &KsState, _: &KsState, _: &KsState, _: Decision, cost: isize) -> isize {
+            cost
+        }
+        fn fast_upper_bound(&self, state: &KsState) -> isize {
+            self.0.remaining_profit(state.taken.len())
+        }
+    }
+    struct KsRanking;
+    impl StateRanking for KsRanking {
+        type State = KsState;
+
+        fn compare(&self, a: &KsState, b: &KsState) -> Ordering {
+            a.capacity.cmp(&b.capacity)
+        }
+    }
+    struct KsProjection<'a>(&'a Knapsack);
+    impl Projection for KsProjection<'_> {
+        type State = KsState;
+        type Abstract = KsAbstract;
+
+        fn project(&self, state: &KsState) -> KsAbstract {
+            KsAbstract { depth: state.taken.len(), capacity: state.capacity }
+        }
+        fn merge(&self, states: &[KsAbstract]) -> KsAbstract {
+            *states.iter().max_by_key(|s| s.capacity).unwrap()
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &KsAbstract>) -> Option<Variable> {
+            self.0.next_variable(depth, &mut std::iter::empty())
+        }
+        fn for_each_in_domain(&self, var: Variable, state: &KsAbstract, f: &mut dyn DecisionCallback) {
+            if state.capacity >= self.0.weight[var.id()] {
+                f.apply(Decision { variable: var, value: 1 });
+            }
+            f.apply(Decision { variable: var, value: 0 });
+        }
+        fn transition_abstract(&self, state: &KsAbstract, decision: Decision) -> KsAbstract {
+            let used = if decision.value == 1 { self.0.weight[decision.variable.id()] } else { 0 };
+            KsAbstract { depth: state.depth + 1, capacity: state.capacity - used }
+        }
+        fn transition_cost_abstract(&self, _: &KsAbstract, _: &KsAbstract, decision: Decision) -> isize {
+            self.0.profit[decision.variable.id()] * decision.value
+        }
+        fn compare(&self, a: &KsAbstract, b: &KsAbstract) -> Ordering {
+            a.capacity.cmp(&b.capacity)
+        }
+        fn fast_upper_bound(&self, state: &KsAbstract) -> isize {
+            self.0.remaining_profit(state.depth)
+        }
+    }
+
+    fn root(problem: &Knapsack) -> SubProblem<KsState> {
+        SubProblem {
+            state: Arc::new(problem.initial_state()),
+            value: 0,
+            path: vec![],
+            ub: isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        }
+    }
+    fn compile<D: DecisionDiagram<State = KsState>>(mdd: &mut D, problem: &Knapsack, comp_type: CompilationType, width: usize) -> Completion {
+        let relax = KsRelax(problem);
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let input = CompilationInput {
+            comp_type,
+            problem,
+            relaxation: &relax,
+            ranking: &KsRanking,
+            cutoff: &NoCutoff,
+            max_width: width,
+            best_lb: isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        mdd.compile(&input, root(problem)).unwrap()
+    }
+    fn optimum(problem: &Knapsack) -> isize {
+        let mut mdd = DefaultMDDLEL::new();
+        compile(&mut mdd, problem, CompilationType::Exact, usize::MAX).best_value.unwrap()
+    }
+    fn replay(problem: &Knapsack, path: &[Decision]) -> (KsState, isize) {
+        let mut state = problem.initial_state();
+        let mut value = problem.initial_value();
+        for d in path {
+            let next = problem.transition(&state, *d);
+            value += problem.transition_cost(&state, &next, *d);
+            state = next;
+        }
+        (state, value)
+    }
+
+    #[test]
+    fn without_projection_it_behaves_as_the_inner_dd() {
+        let problem = Knapsack::generate(15, 1);
+        let mut projected = ProjectedMdd::<KsState, KsAbstract>::default();
+        let mut plain = DefaultMDDLEL::new();
+        let a = compile(&mut projected, &problem, CompilationType::Relaxed, 5);
+        let b = compile(&mut plain, &problem, CompilationType::Relaxed, 5);
+
+        assert_eq!(a.best_value, b.best_value);
+        assert_eq!(a.is_exact, b.is_exact);
+        assert_eq!(projected.cutset_len(), plain.cutset_len());
+        assert_eq!(projected.stats(), plain.stats());
+    }
+    #[test]
+    fn a_group_of_one_abstract_node_is_not_merged() {
+        let problem = Knapsack::generate(5, 0);
+        let projection = KsProjection(&problem);
+        let node = |capacity| super::AbstractNode {
+            state: KsAbstract { depth: 1, capacity },
+            value: 0, parent: 0, decision: None, arcs: vec![], locb: isize::MIN
+        };
+        let layer = || vec![node(1), node(2), node(3)];
+
+        let untouched = ProjectedMdd::<KsState, KsAbstract>::_merge(&projection, 3, layer(), &[]);
+        let capacities = untouched.iter().map(|n| n.state.capacity).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3], capacities);
+
+        let merged = ProjectedMdd::<KsState, KsAbstract>::_merge(&projection, 2, layer(), &[]);
+        let capacities = merged.iter().map(|n| n.state.capacity).collect::<Vec<_>>();
+        assert_eq!(vec![3, 2], capacities);
+    }
+    #[test]
+    fn the_width_profile_caps_the_concrete_and_the_abstract_layers() {
+        let mut problem = Knapsack::generate(12, 3);
+        problem.profile = Some(vec![1, 1, 1, 4, 4, 4]);
+        let cap = |depth: usize| 2 * problem.profile.as_ref().unwrap().get(depth).copied().unwrap_or(1);
+
+        let projection = KsProjection(&problem);
+        let mut projected = ProjectedMdd::<_, _>::new(&projection);
+        compile(&mut projected, &problem, CompilationType::Relaxed, 2);
+
+        for (depth, layer) in projected.concrete.iter().enumerate() {
+            assert!(layer.len() <= cap(depth));
+        }
+        // the first abstract layer is the projection of the last exact one
+        let first = projected.concrete.len() - 1;
+        for (i, layer) in projected.abstracts.iter().enumerate() {
+            assert!(layer.len() <= cap(first + i));
+        }
+        assert!(projected.abstracts.iter().any(|layer| layer.len() > 2));
+    }
+    #[test]
+    fn a_projected_relaxation_yields_an_upper_bound() {
+        for seed in 0..10 {
+            let problem = Knapsack::generate(15, seed);
+            let projection = KsProjection(&problem);
+            let optimum = optimum(&problem);
+            for width in [1, 2, 5, 10] {
+                let mut projected = ProjectedMdd::<_, _>::new(&projection);
+                let mut plain = DefaultMDDLEL::new();
+                let a = compile(&mut projected, &problem, CompilationType::Relaxed, width);
+                let b = compile(&mut plain, &problem, CompilationType::Relaxed, width);
+                assert!(a.best_value.unwrap() >= optimum);
+                assert!(b.best_value.unwrap() >= optimum);
+            }
+        }
+    }
+    #[test]
+    fn the_abstract_space_may_yield_tighter_bounds_than_the_concrete_one() {
+        // the concrete states of this knapsack never coincide. Hence, there are
+        // many more nodes to merge in the concrete space than in the abstract one
+        let mut tighter = 0;
+        for seed in 0..10 {
+            let problem = Knapsack::generate(15, seed);
+            let projection = KsProjection(&problem);
+            let mut projected = ProjectedMdd::<_, _>::new(&projection);
+            let mut plain = DefaultMDDLEL::new();
+            let a = compile(&mut projected, &problem, CompilationType::Relaxed, 10);
+            let b = compile(&mut plain, &problem, CompilationType::Relaxed, 10);
+            if a.best_value < b.best_value {
+                tighter += 1;
+            }
+        }
+        assert!(tighter > 0);
+    }
+    #[test]
+    fn the_cutset_is_made_of_concrete_states_of_the_last_exact_layer() {
+        let problem = Knapsack::generate(15, 3);
+        let projection = KsProjection(&problem);
+        let mut mdd = ProjectedMdd::<_, _>::new(&projection);
+        let Completion { is_exact, best_value, .. } = compile(&mut mdd, &problem, CompilationType::Relaxed, 5);
+        assert!(!is_exact);
+
+        let depth = mdd.stats().first_inexact_layer.unwrap() - 1;
+        let mut count = 0;
+        mdd.drain_cutset(|node| {
+            let (state, value) = replay(&problem, &node.path);
+            assert_eq!(depth, node.depth);
+            assert_eq!(state, *node.state);
+            assert_eq!(value, node.value);
+            assert!(node.ub <= best_value.unwrap());
+            count += 1;
+        });
+        assert!(count > 0);
+        assert_eq!(0, mdd.cutset_len());
+    }
+    #[test]
+    fn the_cutset_is_delivered_once() {
+        let problem = Knapsack::generate(15, 3);
+        let projection = KsProjection(&problem);
+        let mut mdd = ProjectedMdd::<_, _>::new(&projection);
+        compile(&mut mdd, &problem, CompilationType::Relaxed, 5);
+        assert_eq!(CutsetState::Fresh, mdd.cutset_state());
+
+        let mut inspected = vec![];
+        mdd.for_each_cutset_node(|n| inspected.push(n));
+        let mut drained = vec![];
+        mdd.drain_cutset(|n| drained.push(n));
+        mdd.drain_cutset(|n| drained.push(n));
+        mdd.for_each_cutset_node(|n| drained.push(n));
+        assert!(!inspected.is_empty());
+        assert_eq!(inspected, drained);
+        assert_eq!(CutsetState::Consumed, mdd.cutset_state());
+        assert_eq!(Err(CutsetConsumed), mdd.try_for_each_cutset_node(|_| unreachable!()));
+
+        // restricted dds are compiled by the inner dd, which has a cutset 
+        // state of its own
+        compile(&mut mdd, &problem, CompilationType::Restricted, 5);
+        assert_eq!(CutsetState::Fresh, mdd.cutset_state());
+        compile(&mut mdd, &problem, CompilationType::Relaxed, 5);
+        assert_eq!(CutsetState::Fresh, mdd.cutset_state());
+    }
+    #[test]
+    fn a_wide_enough_relaxation_is_exact_and_concrete() {
+        let problem = Knapsack::generate(10, 4);
+        let projection = KsProjection(&problem);
+        let mut mdd = ProjectedMdd::<_, _>::new(&projection);
+        let Completion { is_exact, best_value, .. } = compile(&mut mdd, &problem, CompilationType::Relaxed, usize::MAX);
+
+        assert!(is_exact);
+        assert_eq!(Some(optimum(&problem)), best_value);
+        assert_eq!(best_value, mdd.best_exact_value());
+        let solution = mdd.best_exact_solution().unwrap();
+        let (state, value) = replay(&problem, &solution);
+        assert_eq!(best_value, Some(value));
+        assert_eq!(Some(&state), mdd.best_exact_state());
+        assert_eq!(0, mdd.cutset_len());
+    }
+    #[test]
+    fn the_best_solution_of_a_projected_relaxation_assigns_all_variables() {
+        let problem = Knapsack::generate(15, 5);
+        let projection = KsProjection(&problem);
+        let mut mdd = ProjectedMdd::<_, _>::new(&projection);
+        compile(&mut mdd, &problem, CompilationType::Relaxed, 3);
+
+        let solution = mdd.best_solution().unwrap();
+        assert_eq!(problem.nb_variables(), solution.len());
+        assert!(solution.iter().enumerate().all(|(i, d)| d.variable.id() == i));
+        assert_eq!(None, mdd.best_exact_value());
+        assert_eq!(None, mdd.best_exact_solution());
+    }
+    #[test]
+    fn restricted_dds_are_compiled_by_the_inner_dd() {
+        let problem = Knapsack::generate(15, 6);
+        let projection = KsProjection(&problem);
+        let mut projected = ProjectedMdd::<_, _>::new(&projection);
+        let mut plain = DefaultMDDLEL::new();
+        let a = compile(&mut projected, &problem, CompilationType::Restricted, 5);
+        let b = compile(&mut plain, &problem, CompilationType::Restricted, 5);
+
+        assert_eq!(a.best_value, b.best_value);
+        assert_eq!(projected.best_solution(), plain.best_solution());
+    }
+    #[test]
+    fn the_solver_finds_the_same_optimum_with_and_without_projection() {
+        for seed in 0..5 {
+            let problem = Knapsack::generate(20, seed);
+            let relax = KsRelax(&problem);
+            let ranking = KsRanking;
+            let width = FixedWidth(5);
+            let dominance = EmptyDominanceChecker::default();
+            let cutoff = NoCutoff;
+            let projection = KsProjection(&problem);
+
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = SequentialSolver::<_, ProjectedMdd<_, _>>::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe)
+                .with_mdd(ProjectedMdd::new(&projection));
+            let projected = solver.maximize();
+            let solution = solver.best_solution().unwrap();
+
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = SequentialSolver::<_, DefaultMDDLEL<_>>::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+            let plain = solver.maximize();
+
+            assert!(projected.is_exact);
+            assert_eq!(plain.best_value, projected.best_value);
+            assert_eq!(Some(optimum(&problem)), projected.best_value);
+            assert_eq!(projected.best_value, Some(replay(&problem, &solution).1));
+        }
+    }
+}
@@ -0,0 +1,372 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the decision-support queries which can be answered 
+//! from an exact decision diagram once it has been compiled. Because an exact
+//! diagram represents all the solutions of a (sub-)problem, a single backward
+//! pass over it suffices to tell the best objective value reachable with any
+//! given variable fixed to any given value.
+
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{CutsetType, DecisionDiagram, Mdd, Variable};
+
+/// The answers to the queries about the solutions of an exact decision diagram.
+/// It is computed once from a compiled `Mdd` and holds no reference to it, so 
+/// the diagram can be reused for another compilation afterwards.
+///
+/// # Note
+/// The nodes which are pruned by the cache, the dominance checker or the 
+/// best known lower bound are not part of the diagram. If you need the 
+/// marginals of *all* the solutions, compile the diagram with an `EmptyCache`,
+/// an `EmptyDominanceChecker` and a `best_lb` of `isize::MIN`.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// # use std::cmp::Ordering;
+/// #
+/// /// Pick any number of items among 3 without exceeding a capacity of 5
+/// struct Knapsack;
+/// impl Problem for Knapsack {
+///     type State = usize; // the remaining capacity
+///     fn nb_variables(&self) -> usize { 3 }
+///     fn initial_state(&self) -> usize { 5 }
+///     fn initial_value(&self) -> isize { 0 }
+///     fn transition(&self, capacity: &usize, d: Decision) -> usize {
+///         capacity - d.value as usize * [2, 3, 4][d.variable.id()]
+///     }
+///     fn transition_cost(&self, _: &usize, _: &usize, d: Decision) -> isize {
+///         d.value * [3, 4, 6][d.variable.id()]
+///     }
+///     fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+///         (depth < 3).then_some(Variable(depth))
+///     }
+///     fn for_each_in_domain(&self, var: Variable, capacity: &usize, f: &mut dyn DecisionCallback) {
+///         f.apply(Decision { variable: var, value: 0 });
+///         if *capacity >= [2, 3, 4][var.id()] {
+///             f.apply(Decision { variable: var, value: 1 });
+///         }
+///     }
+/// }
+/// # struct KnapsackRelax;
+/// # impl Relaxation for KnapsackRelax {
+/// #     type State = usize;
+/// #     fn merge(&self, states: &mut dyn Iterator<Item = &usize>) -> usize { *states.max().unwrap() }
+/// #     fn relax(&self, _: &usize, _: &usize, _: &usize, _: Decision, cost: isize) -> isize { cost }
+/// # }
+/// # struct KnapsackRanking;
+/// # impl StateRanking for KnapsackRanking {
+/// #     type State = usize;
+/// #     fn compare(&self, a: &usize, b: &usize) -> Ordering { a.cmp(b) }
+/// # }
+/// let cache = EmptyCache::new();
+/// let dominance = EmptyDominanceChecker::default();
+/// let input = CompilationInput {
+///     comp_type:  CompilationType::Exact,
+///     problem:    &Knapsack,
+///     relaxation: &KnapsackRelax,
+///     ranking:    &KnapsackRanking,
+///     cutoff:     &NoCutoff,
+///     max_width:  usize::MAX,
+///     best_lb:    isize::MIN,
+///     cache:      &cache,
+///     dominance:  &dominance,
+/// };
+/// let root = SubProblem { 
+///     state: std::sync::Arc::new(5), value: 0, path: vec![], ub: isize::MAX, depth: 0,
+///     priority_bonus: 0,
+/// };
+/// let mut mdd = DefaultMDD::new();
+/// mdd.compile(&input, root).unwrap();
+///
+/// let queries = ExactDdQueries::new(&mdd).unwrap();
+/// assert_eq!(Some(7), queries.best_value());
+/// assert_eq!(Some(7), queries.conditional_best(Variable(1), 1));
+/// // taking the last item leaves no room for the other ones
+/// assert_eq!(Some(6), queries.conditional_best(Variable(2), 1));
+/// assert_eq!(Some(6), queries.conditional_best(Variable(0), 0));
+/// // a value which is never taken has no conditional optimum
+/// assert_eq!(None,    queries.conditional_best(Variable(2), 2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExactDdQueries {
+    /// The value of the best solution of the diagram (if any)
+    best_value: Option<isize>,
+    /// For each variable and each value, the value of the best solution that
+    /// assigns that value to the variable
+    marginals: Vec<HashMap<isize, isize>>,
+}
+
+impl ExactDdQueries {
+    /// Answers the queries about the last diagram compiled by `mdd`. This 
+    /// returns None when that diagram is not exact (because it was restricted
+    /// or relaxed): such a diagram does not represent the exact set of 
+    /// solutions of the problem.
+    pub fn new<T, const CUTSET_TYPE: CutsetType>(mdd: &Mdd<T, CUTSET_TYPE>) -> Option<Self> 
+    where T: Eq + PartialEq + Hash + Clone
+    {
+        let marginals = mdd.max_marginals()?;
+        Some(Self { best_value: mdd.best_value(), marginals })
+    }
+    /// Returns the value of the best solution of the diagram
+    pub fn best_value(&self) -> Option<isize> {
+        self.best_value
+    }
+    /// Returns the value of the best solution which assigns `value` to `var`.
+    /// This returns None when no solution of the diagram assigns that value 
+    /// to the variable.
+    pub fn conditional_best(&self, var: Variable, value: isize) -> Option<isize> {
+        self.marginals.get(var.id())
+            .and_then(|values| values.get(&value))
+            .copied()
+    }
+    /// Returns the max-marginals of the diagram: for each variable (indexed by
+    /// its id), the map from each value that can be assigned to the variable 
+    /// to the value of the best solution that assigns it.
+    pub fn max_marginals(&self) -> Vec<HashMap<isize, isize>> {
+        self.marginals.clone()
+    }
+}
+
+#[cfg(test)]
+mod test_queries {
+    use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+    use crate::*;
+
+    /// A small knapsack instance: the state is the remaining capacity
+    struct Knapsack {
+        capacity: usize,
+        weight  : Vec<usize>,
+        profit  : Vec<isize>,
+    }
+    impl Problem for Knapsack {
+        type State = usize;
+
+        fn nb_variables(&self)  -> usize { self.weight.len() }
+        fn initial_state(&self) -> usize { self.capacity }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, capacity: &usize, d: Decision) -> usize {
+            capacity - d.value as usize * self.weight[d.variable.id()]
+        }
+        fn transition_cost(&self, _: &usize, _: &usize, d: Decision) -> isize {
+            d.value * self.profit[d.variable.id()]
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+            (depth < self.nb_variables()).then_some(Variable(depth))
+        }
+        fn for_each_in_domain(&self, var: Variable, capacity: &usize, f: &mut dyn DecisionCallback) {
+            f.apply(Decision { variable: var, value: 0 });
+            if *capacity >= self.weight[var.id()] {
+                f.apply(Decision { variable: var, value: 1 });
+            }
+        }
+    }
+    struct KnapsackRelax;
+    impl Relaxation for KnapsackRelax {
+        type State = usize;
+        fn merge(&self, states: &mut dyn Iterator<Item = &usize>) -> usize { 
+            *states.max().unwrap() 
+        }
+        fn relax(&self, _: &usize, _: &usize, _: &usize, _: Decision, cost: isize) -> isize { 
+            cost 
+        }
+    }
+    struct KnapsackRanking;
+    impl StateRanking for KnapsackRanking {
+        type State = usize;
+        fn compare(&self, a: &usize, b: &usize) -> Ordering { 
+            a.cmp(b) 
+        }
+    }
+
+    fn compile(pb: &Knapsack, comp_type: CompilationType, max_width: usize, root: SubProblem<usize>) -> DefaultMDD<usize> {
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let input = CompilationInput {
+            comp_type,
+            problem:    pb,
+            relaxation: &KnapsackRelax,
+            ranking:    &KnapsackRanking,
+            cutoff:     &NoCutoff,
+            max_width,
+            best_lb:    isize::MIN,
+            cache:      &cache,
+            dominance:  &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        mdd.compile(&input, root).unwrap();
+        mdd
+    }
+    fn root_of(pb: &Knapsack) -> SubProblem<usize> {
+        SubProblem { state: Arc::new(pb.capacity), value: 0, path: vec![], ub: isize::MAX, depth: 0, priority_bonus: 0 }
+    }
+
+    /// Enumerates all the feasible solutions to compute the max-marginals
+    fn brute_force(pb: &Knapsack) -> Vec<HashMap<isize, isize>> {
+        let n = pb.nb_variables();
+        let mut marginals = vec![HashMap::new(); n];
+        for x in 0..(1_usize << n) {
+            let weight = (0..n).filter(|i| x & (1 << i) != 0).map(|i| pb.weight[i]).sum::<usize>();
+            if weight > pb.capacity {
+                continue;
+            }
+            let profit = (0..n).filter(|i| x & (1 << i) != 0).map(|i| pb.profit[i]).sum::<isize>();
+            for (i, marginal) in marginals.iter_mut().enumerate() {
+                let value = ((x >> i) & 1) as isize;
+                let best = marginal.entry(value).or_insert(profit);
+                *best = profit.max(*best);
+            }
+        }
+        marginals
+    }
+
+    /// A deterministic pseudo random instance generator (xorshift)
+    fn random_instance(seed: &mut u64, n: usize) -> Knapsack {
+        let mut next = |modulo: u64| {
+            *seed ^= *seed << 13;
+            *seed ^= *seed >> 7;
+            *seed ^= *seed << 17;
+            *seed % modulo
+        };
+        let weight = (0..n).map(|_| 1 + next(10) as usize).collect::<Vec<_>>();
+        let profit = (0..n).map(|_| 1 + next(20) as isize).collect::<Vec<_>>();
+        let capacity = weight.iter().sum::<usize>() / 2;
+        Knapsack { capacity, weight, profit }
+    }
+
+    #[test]
+    fn max_marginals_match_a_brute_force_enumeration() {
+        let mut seed = 0x2545F4914F6CDD1D;
+        for n in 1..=10 {
+            for _ in 0..5 {
+                let pb = random_instance(&mut seed, n);
+                let mdd = compile(&pb, CompilationType::Exact, usize::MAX, root_of(&pb));
+                let queries = ExactDdQueries::new(&mdd).unwrap();
+                assert_eq!(brute_force(&pb), queries.max_marginals());
+            }
+        }
+    }
+    #[test]
+    fn conditional_best_matches_a_brute_force_enumeration() {
+        let mut seed = 0x9E3779B97F4A7C15;
+        for _ in 0..20 {
+            let pb = random_instance(&mut seed, 8);
+            let mdd = compile(&pb, CompilationType::Exact, usize::MAX, root_of(&pb));
+            let queries = ExactDdQueries::new(&mdd).unwrap();
+            let expected = brute_force(&pb);
+            for (var, values) in expected.iter().enumerate() {
+                for value in [0, 1, 2] {
+                    assert_eq!(values.get(&value).copied(), queries.conditional_best(Variable(var), value));
+                }
+            }
+            assert_eq!(mdd.best_value(), queries.best_value());
+            let best = expected[0].values().max().copied();
+            assert_eq!(best, queries.best_value());
+        }
+    }
+    #[test]
+    fn the_best_value_is_reached_by_one_value_of_each_variable() {
+        let mut seed = 42;
+        let pb = random_instance(&mut seed, 9);
+        let mdd = compile(&pb, CompilationType::Exact, usize::MAX, root_of(&pb));
+        let queries = ExactDdQueries::new(&mdd).unwrap();
+        for values in queries.max_marginals() {
+            assert_eq!(queries.best_value(), values.values().max().copied());
+        }
+    }
+    #[test]
+    fn no_query_can_be_answered_from_an_inexact_diagram() {
+        let mut seed = 7;
+        let pb = random_instance(&mut seed, 8);
+        let restricted = compile(&pb, CompilationType::Restricted, 2, root_of(&pb));
+        assert!(!restricted.is_exact());
+        assert_eq!(None, ExactDdQueries::new(&restricted));
+
+        let relaxed = compile(&pb, CompilationType::Relaxed, 2, root_of(&pb));
+        assert!(!relaxed.is_exact());
+        assert_eq!(None, ExactDdQueries::new(&relaxed));
+    }
+    #[test]
+    fn a_narrow_diagram_which_needs_no_restriction_is_exact() {
+        let pb = Knapsack { capacity: 0, weight: vec![1, 1, 1], profit: vec![1, 2, 3] };
+        let mdd = compile(&pb, CompilationType::Restricted, 1, root_of(&pb));
+        let queries = ExactDdQueries::new(&mdd).unwrap();
+        assert_eq!(Some(0), queries.best_value());
+        assert_eq!(Some(0), queries.conditional_best(Variable(1), 0));
+        assert_eq!(None,    queries.conditional_best(Variable(1), 1));
+    }
+    #[test]
+    fn the_decisions_leading_to_the_root_belong_to_all_solutions() {
+        let pb = Knapsack { capacity: 5, weight: vec![2, 3, 4], profit: vec![3, 4, 6] };
+        // the subproblem where the first item was taken
+        let root = SubProblem { 
+            state: Arc::new(3), 
+            value: 3, 
+            path : vec![Decision { variable: Variable(0), value: 1 }], 
+            ub   : isize::MAX, 
+            depth: 1,
+            priority_bonus: 0,
+        };
+        let mdd = compile(&pb, CompilationType::Exact, usize::MAX, root);
+        let queries = ExactDdQueries::new(&mdd).unwrap();
+        assert_eq!(Some(7), queries.best_value());
+        assert_eq!(Some(7), queries.conditional_best(Variable(0), 1));
+        assert_eq!(None,    queries.conditional_best(Variable(0), 0));
+        assert_eq!(Some(7), queries.conditional_best(Variable(1), 1));
+        assert_eq!(Some(3), queries.conditional_best(Variable(1), 0));
+        assert_eq!(None,    queries.conditional_best(Variable(2), 1));
+    }
+    #[test]
+    fn an_infeasible_diagram_has_no_conditional_optimum() {
+        struct Infeasible;
+        impl Problem for Infeasible {
+            type State = usize;
+            fn nb_variables(&self)  -> usize { 2 }
+            fn initial_state(&self) -> usize { 0 }
+            fn initial_value(&self) -> isize { 0 }
+            fn transition(&self, s: &usize, _: Decision) -> usize { *s }
+            fn transition_cost(&self, _: &usize, _: &usize, _: Decision) -> isize { 0 }
+            fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+                (depth < 2).then_some(Variable(depth))
+            }
+            fn for_each_in_domain(&self, _: Variable, _: &usize, _: &mut dyn DecisionCallback) {}
+        }
+        let cache = EmptyCache::new();
+        let dominance = EmptyDominanceChecker::default();
+        let input = CompilationInput {
+            comp_type:  CompilationType::Exact,
+            problem:    &Infeasible,
+            relaxation: &KnapsackRelax,
+            ranking:    &KnapsackRanking,
+            cutoff:     &NoCutoff,
+            max_width:  usize::MAX,
+            best_lb:    isize::MIN,
+            cache:      &cache,
+            dominance:  &dominance,
+        };
+        let mut mdd = DefaultMDD::new();
+        mdd.compile(&input, SubProblem { state: Arc::new(0), value: 0, path: vec![], ub: isize::MAX, depth: 0, priority_bonus: 0 }).unwrap();
+        let queries = ExactDdQueries::new(&mdd).unwrap();
+        assert_eq!(None, queries.best_value());
+        assert_eq!(None, queries.conditional_best(Variable(0), 0));
+        assert!(queries.max_marginals().is_empty());
+    }
+}
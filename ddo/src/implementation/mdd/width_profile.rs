@@ -0,0 +1,80 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the per-layer width which is enforced by the decision
+//! diagrams when the problem provides a width profile (see
+//! `Problem::width_profile`).
+
+use crate::Problem;
+
+/// The width multipliers of the problem being compiled. It is fetched from
+/// the problem once per compilation.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WidthProfile {
+    /// The multiplier of each depth (empty when the problem has no profile)
+    multipliers: Vec<usize>,
+}
+
+impl WidthProfile {
+    /// Fetches the width profile of the given problem
+    pub fn reset<T>(&mut self, problem: &dyn Problem<State = T>) {
+        self.multipliers = problem.width_profile().unwrap_or_default();
+    }
+
+    /// Returns the maximum width of the layer at the given (absolute) depth.
+    /// The layers which are not covered by the profile keep the `max_width`
+    /// and a multiplier of zero is understood as one.
+    pub fn width(&self, max_width: usize, depth: usize) -> usize {
+        match self.multipliers.get(depth) {
+            Some(m) => max_width.saturating_mul((*m).max(1)),
+            None => max_width,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_width_profile {
+    use super::WidthProfile;
+
+    #[test]
+    fn without_profile_all_layers_have_the_same_width() {
+        let profile = WidthProfile::default();
+        assert_eq!(10, profile.width(10, 0));
+        assert_eq!(10, profile.width(10, 5));
+    }
+    #[test]
+    fn the_profile_multiplies_the_width_of_the_layers_it_covers() {
+        let profile = WidthProfile { multipliers: vec![1, 1, 4, 4, 1] };
+        assert_eq!(10, profile.width(10, 1));
+        assert_eq!(40, profile.width(10, 2));
+        assert_eq!(40, profile.width(10, 3));
+        assert_eq!(10, profile.width(10, 4));
+        assert_eq!(10, profile.width(10, 5));
+    }
+    #[test]
+    fn a_zero_multiplier_leaves_the_width_untouched() {
+        let profile = WidthProfile { multipliers: vec![0] };
+        assert_eq!(10, profile.width(10, 0));
+    }
+    #[test]
+    fn the_multiplied_width_saturates() {
+        let profile = WidthProfile { multipliers: vec![3] };
+        assert_eq!(usize::MAX, profile.width(usize::MAX, 0));
+    }
+}
@@ -26,10 +26,22 @@ mod mdd;
 mod cache;
 mod dominance;
 mod solver;
+mod preprocess;
+mod codec;
+mod export;
+mod reuse;
+mod objective;
+mod relaxation;
 
 pub use heuristics::*;
 pub use fringe::*;
 pub use mdd::*;
 pub use cache::*;
 pub use dominance::*;
-pub use solver::*;
\ No newline at end of file
+pub use solver::*;
+pub use preprocess::*;
+pub use codec::*;
+pub use export::*;
+pub use reuse::*;
+pub use objective::*;
+pub use relaxation::*;
\ No newline at end of file
@@ -0,0 +1,344 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the `Minimize` wrapper which turns a minimization 
+//! model into the maximization model the solvers expect.
+
+use crate::{Decision, DecisionCallback, ObjectiveSense, Problem, ProblemCtx, Relaxation, ValueMask, Variable};
+
+/// This wrapper lets you model a minimization problem as such, without 
+/// negating its costs by hand. Wrap both the problem and its relaxation in a
+/// `Minimize` and call `Solver::minimize` rather than `maximize`: the solver 
+/// then maximizes the opposite of the objective, and reports its best value 
+/// in the original sense.
+///
+/// The wrapped problem has the same states, variables and domains as the 
+/// original one; only its initial value and transition costs are negated. 
+/// The wrapped relaxation relaxes the costs in the original sense, and its
+/// `fast_upper_bound` must return a *lower* bound on the cost of the best 
+/// completion of a state (or isize::MAX, the default, when it cannot tell).
+///
+/// # Note
+/// The heuristics which only look at the states (e.g. the `StateRanking`) 
+/// are used as they are. The values which are handed to the dominance checker
+/// and the cache are in the sense of the solver though: a smaller cost is a
+/// larger value.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// # struct Cost;
+/// # impl Problem for Cost {
+/// #     type State = usize;
+/// #     fn nb_variables(&self) -> usize { 2 }
+/// #     fn initial_state(&self) -> usize { 0 }
+/// #     fn initial_value(&self) -> isize { 0 }
+/// #     fn transition(&self, s: &usize, _: Decision) -> usize { s + 1 }
+/// #     fn transition_cost(&self, _: &usize, _: &usize, d: Decision) -> isize { 3 + d.value }
+/// #     fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+/// #         if depth < 2 { Some(Variable(depth)) } else { None }
+/// #     }
+/// #     fn for_each_in_domain(&self, variable: Variable, _: &usize, f: &mut dyn DecisionCallback) {
+/// #         f.apply(Decision{variable, value: 0});
+/// #         f.apply(Decision{variable, value: 1});
+/// #     }
+/// # }
+/// # struct CostRelax;
+/// # impl Relaxation for CostRelax {
+/// #     type State = usize;
+/// #     fn merge(&self, states: &mut dyn Iterator<Item = &usize>) -> usize { states.max().copied().unwrap() }
+/// #     fn relax(&self, _: &usize, _: &usize, _: &usize, _: Decision, cost: isize) -> isize { cost }
+/// # }
+/// # struct CostRanking;
+/// # impl StateRanking for CostRanking {
+/// #     type State = usize;
+/// #     fn compare(&self, a: &usize, b: &usize) -> std::cmp::Ordering { a.cmp(b) }
+/// # }
+/// let problem = Minimize(Cost);
+/// let relaxation = Minimize(CostRelax);
+/// let ranking = CostRanking;
+/// let width = FixedWidth(10);
+/// let dominance = EmptyDominanceChecker::default();
+/// let cutoff = NoCutoff;
+/// let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+/// let mut solver = DefaultSolver::new(&problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+///
+/// let completion = solver.minimize();
+/// assert_eq!(Some(6), completion.best_value);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Minimize<T>(pub T);
+
+impl<T> Minimize<T> {
+    /// Returns the wrapped problem (or relaxation)
+    pub fn inner(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<P: Problem> Problem for Minimize<P> {
+    type State = P::State;
+
+    fn nb_variables(&self) -> usize {
+        self.0.nb_variables()
+    }
+    fn initial_state(&self) -> Self::State {
+        self.0.initial_state()
+    }
+    fn initial_value(&self) -> isize {
+        ObjectiveSense::Minimize.value(self.0.initial_value())
+    }
+    fn transition(&self, state: &Self::State, decision: Decision) -> Self::State {
+        self.0.transition(state, decision)
+    }
+    fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: Decision) -> isize {
+        ObjectiveSense::Minimize.value(self.0.transition_cost(source, dest, decision))
+    }
+    fn transition_with_ctx(&self, state: &Self::State, decision: Decision, ctx: &mut ProblemCtx) -> Self::State {
+        self.0.transition_with_ctx(state, decision, ctx)
+    }
+    fn transition_cost_with_ctx(&self, source: &Self::State, dest: &Self::State, decision: Decision, ctx: &mut ProblemCtx) -> isize {
+        ObjectiveSense::Minimize.value(self.0.transition_cost_with_ctx(source, dest, decision, ctx))
+    }
+    fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+        -> Option<Variable> {
+        self.0.next_variable(depth, next_layer)
+    }
+    fn for_each_in_domain(&self, var: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+        self.0.for_each_in_domain(var, state, f)
+    }
+    fn is_impacted_by(&self, var: Variable, state: &Self::State) -> bool {
+        self.0.is_impacted_by(var, state)
+    }
+    fn objective_depth(&self) -> Option<usize> {
+        self.0.objective_depth()
+    }
+    fn feasibility_class(&self, state: &Self::State) -> Option<u64> {
+        self.0.feasibility_class(state)
+    }
+    fn quick_neq(&self, a: &Self::State, b: &Self::State) -> bool {
+        self.0.quick_neq(a, b)
+    }
+    fn width_profile(&self) -> Option<Vec<usize>> {
+        self.0.width_profile()
+    }
+    fn filter_layer(&self, var: Variable, states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+        self.0.filter_layer(var, states)
+    }
+    fn domain_is_state_independent(&self, var: Variable) -> bool {
+        self.0.domain_is_state_independent(var)
+    }
+    fn dump_state(&self, state: &Self::State) -> Option<String> {
+        self.0.dump_state(state)
+    }
+}
+
+impl<R: Relaxation> Relaxation for Minimize<R> {
+    type State = R::State;
+
+    fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+        self.0.merge(states)
+    }
+    fn relax(
+        &self,
+        source: &Self::State,
+        dest: &Self::State,
+        new: &Self::State,
+        decision: Decision,
+        cost: isize,
+    ) -> isize {
+        let sense = ObjectiveSense::Minimize;
+        sense.value(self.0.relax(source, dest, new, decision, sense.value(cost)))
+    }
+    fn fast_upper_bound(&self, state: &Self::State) -> isize {
+        match self.0.fast_upper_bound(state) {
+            isize::MAX => isize::MAX,
+            lower_bound => ObjectiveSense::Minimize.value(lower_bound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_minimize {
+    use crate::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct KnapsackState {
+        depth: usize,
+        capacity: usize
+    }
+    /// A knapsack whose profits are multiplied by `sign`: with a sign of -1,
+    /// minimizing the cost is the same as maximizing the profit.
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<usize>,
+        weight: Vec<usize>,
+        sign: isize,
+    }
+
+    const TAKE_IT: isize = 1;
+    const LEAVE_IT_OUT: isize = 0;
+
+    impl Problem for Knapsack {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> Self::State {
+            KnapsackState{ depth: 0, capacity: self.capacity }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = *state;
+            ret.depth  += 1;
+            if dec.value == TAKE_IT { 
+                ret.capacity -= self.weight[dec.variable.id()] 
+            }
+            ret
+        }
+        fn transition_cost(&self, _state: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            self.sign * self.profit[dec.variable.id()] as isize * dec.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            let n = self.nb_variables();
+            if depth < n {
+                Some(Variable(depth))
+            } else {
+                None
+            }
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback)
+        {
+            if state.capacity >= self.weight[variable.id()] {
+                f.apply(Decision { variable, value: TAKE_IT });
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            } else {
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            }
+        }
+    }
+    /// Its fast upper bound is the sign times the sum of the remaining profits
+    /// (which is a lower bound on the cost when the sign is -1)
+    struct KPRelax<'a>{pb: &'a Knapsack}
+    impl Relaxation for KPRelax<'_> {
+        type State = KnapsackState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            states.max_by_key(|node| node.capacity).copied().unwrap()
+        }
+        fn relax(&self, _source: &Self::State, _dest: &Self::State, _merged: &Self::State, _decision: Decision, cost: isize) -> isize {
+            cost
+        }
+        fn fast_upper_bound(&self, state: &Self::State) -> isize {
+            self.pb.sign * self.pb.profit[state.depth..].iter().sum::<usize>() as isize
+        }
+    }
+    struct KPRanking;
+    impl StateRanking for KPRanking {
+        type State = KnapsackState;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.capacity.cmp(&b.capacity)
+        }
+    }
+
+    fn knapsack(sign: isize) -> Knapsack {
+        Knapsack {
+            capacity: 50,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4],
+            sign,
+        }
+    }
+    /// The best objective value, found by enumerating all the assignments
+    fn brute_force(pb: &Knapsack, sense: ObjectiveSense) -> isize {
+        let n = pb.nb_variables();
+        (0..1_usize << n)
+            .filter(|set| (0..n).filter(|i| set & (1 << i) != 0).map(|i| pb.weight[i]).sum::<usize>() <= pb.capacity)
+            .map(|set| (0..n).filter(|i| set & (1 << i) != 0).map(|i| pb.sign * pb.profit[i] as isize).sum::<isize>())
+            .map(|value| sense.value(value))
+            .max()
+            .map(|value| sense.value(value))
+            .unwrap()
+    }
+
+    fn maximize(pb: Knapsack) -> Completion {
+        let relax = KPRelax {pb: &pb};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(3);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(&pb, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        solver.maximize()
+    }
+    fn minimize(pb: Knapsack) -> Completion {
+        let problem = Minimize(pb);
+        let relax = Minimize(KPRelax {pb: problem.inner()});
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(3);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        solver.minimize()
+    }
+
+    #[test]
+    fn the_same_model_yields_opposite_values_in_both_senses() {
+        let max = maximize(knapsack(1));
+        let min = minimize(knapsack(-1));
+        assert!(max.is_exact);
+        assert!(min.is_exact);
+        assert_eq!(Some(brute_force(&knapsack(1), ObjectiveSense::Maximize)), max.best_value);
+        assert_eq!(Some(brute_force(&knapsack(-1), ObjectiveSense::Minimize)), min.best_value);
+        assert_eq!(max.best_value.map(|v| -v), min.best_value);
+    }
+    #[test]
+    fn the_costs_and_bounds_are_negated() {
+        let pb = Minimize(knapsack(-1));
+        let relax = Minimize(KPRelax {pb: pb.inner()});
+        let state = pb.initial_state();
+        let decision = Decision { variable: Variable(0), value: TAKE_IT };
+        let next = pb.transition(&state, decision);
+
+        assert_eq!(10, pb.transition_cost(&state, &next, decision));
+        assert_eq!(0, pb.initial_value());
+        assert_eq!(7, relax.relax(&state, &next, &next, decision, 7));
+        assert_eq!(228, relax.fast_upper_bound(&state));
+    }
+    #[test]
+    fn a_relaxation_which_cannot_tell_yields_no_bound() {
+        struct NoBound;
+        impl Relaxation for NoBound {
+            type State = KnapsackState;
+            fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+                *states.next().unwrap()
+            }
+            fn relax(&self, _: &Self::State, _: &Self::State, _: &Self::State, _: Decision, cost: isize) -> isize {
+                cost
+            }
+        }
+        let state = knapsack(-1).initial_state();
+        assert_eq!(isize::MAX, Minimize(NoBound).fast_upper_bound(&state));
+    }
+}
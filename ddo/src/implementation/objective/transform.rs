@@ -0,0 +1,422 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the `Transformed` wrapper which compiles the DDs of a
+//! problem on exactly transformed costs (see `CostTransform`), along with the
+//! `Scale` transformation.
+
+use crate::{CostTransform, Decision, DecisionCallback, Problem, ProblemCtx, Relaxation, ValueMask, Variable};
+
+/// Multiplies all costs by a constant (positive) factor. The values are 
+/// mapped back with a floor division: the low-order digits which do not 
+/// make a multiple of the factor are free for a secondary objective.
+///
+/// # Panics
+/// When a scaled cost overflows (the transformation would not be exact)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scale(pub isize);
+
+impl CostTransform for Scale {
+    fn forward(&self, cost: isize) -> isize {
+        cost.checked_mul(self.0).expect("the scaled cost overflows")
+    }
+    fn backward(&self, value: isize) -> isize {
+        value.div_euclid(self.0)
+    }
+}
+
+/// This wrapper compiles the DDs of a problem on the costs transformed by a 
+/// `CostTransform`. Wrap both the problem and its relaxation with the same 
+/// transformation: the initial value, the costs of the arcs and the fast 
+/// upper bounds are mapped with `forward`, and the solver then maximizes the
+/// transformed objective. Its values and bounds are converted back with 
+/// `backward` (see `Completion::in_original_scale`).
+///
+/// # Note
+/// The relaxation sees the costs on the original scale: the low-order digits
+/// of a transformed cost (i.e. those which do not come from an original cost)
+/// are set aside while the cost is relaxed, and added back afterwards.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// # struct Cost;
+/// # impl Problem for Cost {
+/// #     type State = usize;
+/// #     fn nb_variables(&self) -> usize { 2 }
+/// #     fn initial_state(&self) -> usize { 0 }
+/// #     fn initial_value(&self) -> isize { 0 }
+/// #     fn transition(&self, s: &usize, _: Decision) -> usize { s + 1 }
+/// #     fn transition_cost(&self, _: &usize, _: &usize, d: Decision) -> isize { 3 + d.value }
+/// #     fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+/// #         if depth < 2 { Some(Variable(depth)) } else { None }
+/// #     }
+/// #     fn for_each_in_domain(&self, variable: Variable, _: &usize, f: &mut dyn DecisionCallback) {
+/// #         f.apply(Decision{variable, value: 0});
+/// #         f.apply(Decision{variable, value: 1});
+/// #     }
+/// # }
+/// # struct CostRelax;
+/// # impl Relaxation for CostRelax {
+/// #     type State = usize;
+/// #     fn merge(&self, states: &mut dyn Iterator<Item = &usize>) -> usize { states.max().copied().unwrap() }
+/// #     fn relax(&self, _: &usize, _: &usize, _: &usize, _: Decision, cost: isize) -> isize { cost }
+/// # }
+/// # struct CostRanking;
+/// # impl StateRanking for CostRanking {
+/// #     type State = usize;
+/// #     fn compare(&self, a: &usize, b: &usize) -> std::cmp::Ordering { a.cmp(b) }
+/// # }
+/// let problem = Transformed::new(Cost, Scale(1000));
+/// let relaxation = Transformed::new(CostRelax, Scale(1000));
+/// let ranking = CostRanking;
+/// let width = FixedWidth(10);
+/// let dominance = EmptyDominanceChecker::default();
+/// let cutoff = NoCutoff;
+/// let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+/// let mut solver = DefaultSolver::new(&problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+///
+/// let completion = solver.maximize();
+/// assert_eq!(Some(8000), completion.best_value);
+/// assert_eq!(Some(8), completion.in_original_scale(problem.transform()).best_value);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Transformed<X, T> {
+    inner: X,
+    transform: T,
+}
+
+impl<X, T: CostTransform> Transformed<X, T> {
+    /// Wraps the given problem (or relaxation) so that its costs are mapped
+    /// with the given transformation
+    pub fn new(inner: X, transform: T) -> Self {
+        Self { inner, transform }
+    }
+    /// Returns the wrapped problem (or relaxation)
+    pub fn inner(&self) -> &X {
+        &self.inner
+    }
+    /// Returns the transformation of the costs
+    pub fn transform(&self) -> &T {
+        &self.transform
+    }
+}
+
+impl<P: Problem, T: CostTransform> Problem for Transformed<P, T> {
+    type State = P::State;
+
+    fn nb_variables(&self) -> usize {
+        self.inner.nb_variables()
+    }
+    fn initial_state(&self) -> Self::State {
+        self.inner.initial_state()
+    }
+    fn initial_value(&self) -> isize {
+        self.transform.forward(self.inner.initial_value())
+    }
+    fn transition(&self, state: &Self::State, decision: Decision) -> Self::State {
+        self.inner.transition(state, decision)
+    }
+    fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: Decision) -> isize {
+        self.transform.forward(self.inner.transition_cost(source, dest, decision))
+    }
+    fn transition_with_ctx(&self, state: &Self::State, decision: Decision, ctx: &mut ProblemCtx) -> Self::State {
+        self.inner.transition_with_ctx(state, decision, ctx)
+    }
+    fn transition_cost_with_ctx(&self, source: &Self::State, dest: &Self::State, decision: Decision, ctx: &mut ProblemCtx) -> isize {
+        self.transform.forward(self.inner.transition_cost_with_ctx(source, dest, decision, ctx))
+    }
+    fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>)
+        -> Option<Variable> {
+        self.inner.next_variable(depth, next_layer)
+    }
+    fn for_each_in_domain(&self, var: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+        self.inner.for_each_in_domain(var, state, f)
+    }
+    fn is_impacted_by(&self, var: Variable, state: &Self::State) -> bool {
+        self.inner.is_impacted_by(var, state)
+    }
+    fn objective_depth(&self) -> Option<usize> {
+        self.inner.objective_depth()
+    }
+    fn feasibility_class(&self, state: &Self::State) -> Option<u64> {
+        self.inner.feasibility_class(state)
+    }
+    fn quick_neq(&self, a: &Self::State, b: &Self::State) -> bool {
+        self.inner.quick_neq(a, b)
+    }
+    fn width_profile(&self) -> Option<Vec<usize>> {
+        self.inner.width_profile()
+    }
+    fn filter_layer(&self, var: Variable, states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+        self.inner.filter_layer(var, states)
+    }
+    fn domain_is_state_independent(&self, var: Variable) -> bool {
+        self.inner.domain_is_state_independent(var)
+    }
+    fn dump_state(&self, state: &Self::State) -> Option<String> {
+        self.inner.dump_state(state)
+    }
+}
+
+impl<R: Relaxation, T: CostTransform> Relaxation for Transformed<R, T> {
+    type State = R::State;
+
+    fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+        self.inner.merge(states)
+    }
+    fn relax(
+        &self,
+        source: &Self::State,
+        dest: &Self::State,
+        new: &Self::State,
+        decision: Decision,
+        cost: isize,
+    ) -> isize {
+        let original = self.transform.backward(cost);
+        let residual = cost - self.transform.forward(original);
+        self.transform.forward(self.inner.relax(source, dest, new, decision, original)) + residual
+    }
+    fn fast_upper_bound(&self, state: &Self::State) -> isize {
+        match self.inner.fast_upper_bound(state) {
+            isize::MAX => isize::MAX,
+            bound => self.transform.forward(bound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_transform {
+    use crate::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct KnapsackState {
+        depth: usize,
+        capacity: usize
+    }
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<usize>,
+        weight: Vec<usize>,
+    }
+
+    const TAKE_IT: isize = 1;
+    const LEAVE_IT_OUT: isize = 0;
+
+    impl Problem for Knapsack {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> Self::State {
+            KnapsackState{ depth: 0, capacity: self.capacity }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = *state;
+            ret.depth  += 1;
+            if dec.value == TAKE_IT { 
+                ret.capacity -= self.weight[dec.variable.id()] 
+            }
+            ret
+        }
+        fn transition_cost(&self, _state: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            self.profit[dec.variable.id()] as isize * dec.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            let n = self.nb_variables();
+            if depth < n {
+                Some(Variable(depth))
+            } else {
+                None
+            }
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback)
+        {
+            if state.capacity >= self.weight[variable.id()] {
+                f.apply(Decision { variable, value: TAKE_IT });
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            } else {
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            }
+        }
+    }
+    struct KPRelax<'a>{pb: &'a Knapsack}
+    impl Relaxation for KPRelax<'_> {
+        type State = KnapsackState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            states.max_by_key(|node| node.capacity).copied().unwrap()
+        }
+        fn relax(&self, _source: &Self::State, _dest: &Self::State, _merged: &Self::State, _decision: Decision, cost: isize) -> isize {
+            cost
+        }
+        fn fast_upper_bound(&self, state: &Self::State) -> isize {
+            self.pb.profit[state.depth..].iter().sum::<usize>() as isize
+        }
+    }
+    struct KPRanking;
+    impl StateRanking for KPRanking {
+        type State = KnapsackState;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.capacity.cmp(&b.capacity)
+        }
+    }
+
+    /// Adds a secondary objective to the (scaled) knapsack: among the best 
+    /// sacks, the one with the fewest items is preferred. Each item which is
+    /// left out is worth one, in the low-order digits of the scaled costs.
+    struct FewestItems<X>(X);
+    impl<P: Problem<State = KnapsackState>> Problem for FewestItems<P> {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.0.nb_variables()
+        }
+        fn initial_state(&self) -> Self::State {
+            self.0.initial_state()
+        }
+        fn initial_value(&self) -> isize {
+            self.0.initial_value()
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            self.0.transition(state, dec)
+        }
+        fn transition_cost(&self, source: &Self::State, dest: &Self::State, dec: Decision) -> isize {
+            self.0.transition_cost(source, dest, dec) + (dec.value == LEAVE_IT_OUT) as isize
+        }
+        fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            self.0.next_variable(depth, next_layer)
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            self.0.for_each_in_domain(variable, state, f)
+        }
+    }
+    impl<R: Relaxation<State = KnapsackState>> Relaxation for FewestItems<R> {
+        type State = KnapsackState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            self.0.merge(states)
+        }
+        fn relax(&self, source: &Self::State, dest: &Self::State, merged: &Self::State, decision: Decision, cost: isize) -> isize {
+            self.0.relax(source, dest, merged, decision, cost)
+        }
+        fn fast_upper_bound(&self, state: &Self::State) -> isize {
+            self.0.fast_upper_bound(state).saturating_add((N - state.depth) as isize)
+        }
+    }
+
+    const N: usize = 10;
+    const FACTOR: isize = 100;
+
+    fn knapsack() -> Knapsack {
+        Knapsack {
+            capacity: 50,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4],
+        }
+    }
+    /// The best (profit, items left out) pair in lexicographic order, found
+    /// by enumerating all the assignments
+    fn brute_force(pb: &Knapsack) -> (isize, isize) {
+        (0..1_usize << N)
+            .filter(|set| (0..N).filter(|i| set & (1 << i) != 0).map(|i| pb.weight[i]).sum::<usize>() <= pb.capacity)
+            .map(|set| {
+                let profit = (0..N).filter(|i| set & (1 << i) != 0).map(|i| pb.profit[i] as isize).sum::<isize>();
+                (profit, N as isize - set.count_ones() as isize)
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn scaling_is_exact_and_leaves_the_low_order_digits_free() {
+        let scale = Scale(FACTOR);
+        assert_eq!(1200, scale.forward(12));
+        assert_eq!(-1200, scale.forward(-12));
+        assert_eq!(12, scale.backward(1200));
+        assert_eq!(12, scale.backward(1299));
+        assert_eq!(-12, scale.backward(-1200));
+        assert_eq!(-12, scale.backward(-1199));
+        assert_eq!(isize::MIN, scale.backward_bound(isize::MIN));
+        assert_eq!(isize::MAX, scale.backward_bound(isize::MAX));
+    }
+    #[test]
+    #[should_panic]
+    fn scaling_panics_rather_than_overflowing() {
+        Scale(FACTOR).forward(isize::MAX / 2);
+    }
+    #[test]
+    fn the_costs_and_bounds_are_transformed() {
+        let pb = Transformed::new(knapsack(), Scale(FACTOR));
+        let relax = Transformed::new(KPRelax {pb: pb.inner()}, Scale(FACTOR));
+        let state = pb.initial_state();
+        let decision = Decision { variable: Variable(0), value: TAKE_IT };
+        let next = pb.transition(&state, decision);
+
+        assert_eq!(1000, pb.transition_cost(&state, &next, decision));
+        assert_eq!(0, pb.initial_value());
+        assert_eq!(22800, relax.fast_upper_bound(&state));
+        // the low-order digits are preserved by the relaxation
+        assert_eq!(707, relax.relax(&state, &next, &next, decision, 707));
+    }
+    #[test]
+    fn lexicographic_optimization_by_scaling_matches_the_enumeration() {
+        let pb = knapsack();
+        let (best_profit, left_out) = brute_force(&pb);
+
+        let problem = FewestItems(Transformed::new(knapsack(), Scale(FACTOR)));
+        let relax = FewestItems(Transformed::new(KPRelax {pb: &pb}, Scale(FACTOR)));
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(3);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        let completion = solver.maximize();
+
+        assert!(completion.is_exact);
+        assert_eq!(Some(best_profit * FACTOR + left_out), completion.best_value);
+        assert_eq!(Some(best_profit), completion.in_original_scale(&Scale(FACTOR)).best_value);
+    }
+    #[test]
+    fn the_primary_objective_is_the_same_as_without_transformation() {
+        let pb = knapsack();
+        let relax = KPRelax {pb: &pb};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(3);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(&pb, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        let plain = solver.maximize();
+
+        let problem = Transformed::new(knapsack(), Scale(FACTOR));
+        let relax = Transformed::new(KPRelax {pb: &pb}, Scale(FACTOR));
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        let scaled = solver.maximize().in_original_scale(problem.transform());
+
+        assert_eq!(Some(brute_force(&pb).0), plain.best_value);
+        assert_eq!(plain.best_value, scaled.best_value);
+    }
+}
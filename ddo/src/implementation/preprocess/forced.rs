@@ -0,0 +1,111 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use crate::*;
+
+/// This lifter is meant to be used with the most common kind of reductions:
+/// those which remove some variables from the problem because their value is
+/// forced (or can be decided greedily without loss of optimality). 
+///
+/// When lifting a solution, each variable of the reduced problem is renamed
+/// to the original variable it stands for, and the forced decisions are 
+/// inserted in the solution. The decisions of the lifted solution are sorted
+/// by increasing variable id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForcedDecisionsLifter {
+    /// This vector tells what original variable corresponds to each variable
+    /// of the reduced problem (`mapping[i]` is the original name of variable i)
+    mapping: Vec<Variable>,
+    /// The decisions that have been fixed by the preprocessing
+    forced: Vec<Decision>,
+}
+
+impl ForcedDecisionsLifter {
+    /// Creates a new lifter where `mapping[i]` denotes the original variable
+    /// corresponding to the i-th variable of the reduced problem, and where 
+    /// `forced` are the decisions which were fixed during the preprocessing.
+    pub fn new(mapping: Vec<Variable>, forced: Vec<Decision>) -> Self {
+        Self { mapping, forced }
+    }
+    /// Returns the decisions that have been fixed by the preprocessing
+    pub fn forced(&self) -> &[Decision] {
+        &self.forced
+    }
+    /// Returns the original variable which corresponds to the given variable
+    /// of the reduced problem
+    pub fn original(&self, reduced: Variable) -> Variable {
+        self.mapping[reduced.id()]
+    }
+}
+
+impl SolutionLifter for ForcedDecisionsLifter {
+    fn lift(&self, reduced: &[Decision]) -> Solution {
+        let mut solution = Vec::with_capacity(reduced.len() + self.forced.len());
+        solution.extend(reduced.iter().map(|d| Decision{variable: self.original(d.variable), value: d.value}));
+        solution.extend_from_slice(&self.forced);
+        solution.sort_unstable_by_key(|d| d.variable.id());
+        solution
+    }
+}
+
+#[cfg(test)]
+mod test_forced_lifter {
+    use crate::*;
+
+    #[test]
+    fn lift_renames_the_variables_of_the_reduced_problem() {
+        let lifter = ForcedDecisionsLifter::new(vec![Variable(1), Variable(3)], vec![]);
+        let lifted = lifter.lift(&[
+            Decision{variable: Variable(1), value: 7},
+            Decision{variable: Variable(0), value: 5},
+        ]);
+        assert_eq!(lifted, vec![
+            Decision{variable: Variable(1), value: 5},
+            Decision{variable: Variable(3), value: 7},
+        ]);
+    }
+    #[test]
+    fn lift_inserts_the_forced_decisions() {
+        let lifter = ForcedDecisionsLifter::new(
+            vec![Variable(1), Variable(3)], 
+            vec![Decision{variable: Variable(2), value: 0}, Decision{variable: Variable(0), value: 1}]);
+        let lifted = lifter.lift(&[
+            Decision{variable: Variable(0), value: 5},
+            Decision{variable: Variable(1), value: 7},
+        ]);
+        assert_eq!(lifted, vec![
+            Decision{variable: Variable(0), value: 1},
+            Decision{variable: Variable(1), value: 5},
+            Decision{variable: Variable(2), value: 0},
+            Decision{variable: Variable(3), value: 7},
+        ]);
+    }
+    #[test]
+    fn lift_yields_the_forced_decisions_when_nothing_remains() {
+        let forced = vec![Decision{variable: Variable(0), value: 1}, Decision{variable: Variable(1), value: 0}];
+        let lifter = ForcedDecisionsLifter::new(vec![], forced.clone());
+        assert_eq!(forced, lifter.lift(&[]));
+    }
+    #[test]
+    fn original_returns_the_name_of_the_variable_in_the_original_problem() {
+        let lifter = ForcedDecisionsLifter::new(vec![Variable(4), Variable(2)], vec![]);
+        assert_eq!(Variable(4), lifter.original(Variable(0)));
+        assert_eq!(Variable(2), lifter.original(Variable(1)));
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use crate::*;
+
+/// This is the trivial preprocessing which leaves the problem untouched.
+/// It is mostly useful when you want to be able to switch the preprocessing
+/// of an instance on and off without changing the rest of your code.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoPreprocessing;
+
+impl <P: Problem + Clone> Preprocess<P> for NoPreprocessing {
+    type Reduced = P;
+    type Lifter  = IdentityLifter;
+
+    fn preprocess(&self, problem: &P) -> (P, IdentityLifter) {
+        (problem.clone(), IdentityLifter)
+    }
+}
+
+/// The lifter which is used when the problem was not reduced at all: it
+/// simply returns a copy of the solution it was given.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IdentityLifter;
+
+impl SolutionLifter for IdentityLifter {
+    fn lift(&self, reduced: &[Decision]) -> Solution {
+        reduced.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod test_identity {
+    use crate::*;
+
+    #[test]
+    fn identity_lifter_returns_the_very_same_solution() {
+        let solution = vec![
+            Decision{variable: Variable(1), value: 4},
+            Decision{variable: Variable(0), value: 2},
+        ];
+        assert_eq!(solution, IdentityLifter.lift(&solution));
+    }
+}
@@ -0,0 +1,405 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides an analysis which spots the variables that a model
+//! does not use: the variables whose decisions never change the state and 
+//! never cost anything (they are typically left over from some preprocessing
+//! of the data). Such variables are harmless, but each of them adds a layer
+//! to every DD that is compiled.
+//!
+//! The analysis probes the states reached by random dives from the root of 
+//! the problem. At each state, it tries all the values of the domain of the
+//! variable to branch on. A variable is reported as unused when every decision
+//! that was sampled about it left the state unchanged and cost zero. This is a
+//! sampling based analysis: it can only tell that a variable *looks* unused.
+//!
+//! When the variables are always branched on in the same order, the unused
+//! variables can be skipped altogether (see `VariableAnalysis::skip_unused`).
+
+use crate::{Decision, DecisionCallback, ForcedDecisionsLifter, Problem, ProblemCtx, ValueMask, Variable};
+use crate::implementation::solver::seed_of_run;
+
+/// The outcome of `analyze_variables`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableAnalysis {
+    /// The number of random dives which were performed
+    pub samples: usize,
+    /// The variables which look unused (sorted by id), each along with one of
+    /// the values that was sampled from its domain
+    pub unused: Vec<Decision>,
+    /// The order in which the variables were branched on, if all the dives 
+    /// agreed on it. It is None when the order seems to depend on the states
+    /// (or when no dive reached the end of the problem).
+    pub order: Option<Vec<Variable>>,
+}
+
+impl VariableAnalysis {
+    /// Returns true iff the given variable looks unused
+    pub fn is_unused(&self, var: Variable) -> bool {
+        self.unused.iter().any(|d| d.variable == var)
+    }
+    /// Wraps the given problem so that its unused variables are skipped, and
+    /// returns it along with the lifter that puts the skipped decisions back
+    /// into the solutions of the wrapped problem. This returns None when the
+    /// variables were not branched on in a fixed order.
+    ///
+    /// # Note
+    /// Skipping is only safe if the variables are really unused and if the 
+    /// variable ordering of the problem does not depend on the states. This
+    /// analysis can only tell that it looked so on the sampled states.
+    pub fn skip_unused<P: Problem>(&self, problem: P) -> Option<(SkipVariables<P>, ForcedDecisionsLifter)> {
+        let order = self.order.as_ref()?;
+        let (depths, order): (Vec<usize>, Vec<Variable>) = order.iter().copied()
+            .enumerate()
+            .filter(|(_, var)| !self.is_unused(*var))
+            .unzip();
+        let mapping = (0..problem.nb_variables()).map(Variable).collect();
+        let lifter = ForcedDecisionsLifter::new(mapping, self.unused.clone());
+        Some((SkipVariables { problem, order, depths }, lifter))
+    }
+}
+
+/// Probes the states reached by `samples` random dives from the root of the 
+/// problem and reports the variables which look unused. The same `seed` always
+/// yields the same analysis.
+pub fn analyze_variables<State: PartialEq>(
+    problem: &dyn Problem<State = State>,
+    samples: usize,
+    seed: u64,
+) -> VariableAnalysis {
+    let mut probes = vec![Probe::default(); problem.nb_variables()];
+    let mut complete: Option<Vec<Variable>> = None;
+    let mut partial = vec![];
+    let mut agree = true;
+    for i in 0..samples {
+        let (order, done) = dive(problem, &mut probes, seed_of_run(seed, i));
+        if !done {
+            partial.push(order);
+        } else if let Some(reference) = complete.as_ref() {
+            agree &= *reference == order;
+        } else {
+            complete = Some(order);
+        }
+    }
+    let order = complete.filter(|reference| agree && partial.iter().all(|p| reference.starts_with(p)));
+    let unused = probes.iter().enumerate()
+        .filter(|(_, probe)| !probe.used)
+        .filter_map(|(id, probe)| probe.value.map(|value| Decision { variable: Variable(id), value }))
+        .collect();
+    VariableAnalysis { samples, unused, order }
+}
+
+/// What has been learned about a variable so far
+#[derive(Debug, Clone, Copy, Default)]
+struct Probe {
+    /// One of the values of the decisions which did nothing
+    value: Option<isize>,
+    /// Set when some decision changed the state or had a cost, or when the 
+    /// domain of the variable was empty
+    used: bool,
+}
+
+/// Performs one random dive from the root of the problem, probing all the 
+/// decisions it meets. It returns the variables it branched on and whether 
+/// it reached the end of the problem.
+fn dive<State: PartialEq>(
+    problem: &dyn Problem<State = State>,
+    probes: &mut [Probe],
+    mut rng: u64,
+) -> (Vec<Variable>, bool) {
+    let mut state = problem.initial_state();
+    let mut order = vec![];
+    let mut decisions = vec![];
+    let mut depth = 0;
+    while let Some(var) = problem.next_variable(depth, &mut std::iter::once(&state)) {
+        order.push(var);
+        decisions.clear();
+        problem.for_each_in_domain(var, &state, &mut |d: Decision| decisions.push(d));
+
+        let probe = &mut probes[var.id()];
+        if decisions.is_empty() {
+            probe.used = true;
+            return (order, false);
+        }
+        for d in decisions.iter().copied() {
+            let next = problem.transition(&state, d);
+            if next == state && problem.transition_cost(&state, &next, d) == 0 {
+                probe.value.get_or_insert(d.value);
+            } else {
+                probe.used = true;
+            }
+        }
+
+        rng = seed_of_run(rng, depth);
+        let pick = decisions[(rng % decisions.len() as u64) as usize];
+        state = problem.transition(&state, pick);
+        depth += 1;
+    }
+    (order, true)
+}
+
+/// This problem wraps another one and skips some of its variables (see 
+/// `VariableAnalysis::skip_unused`). The remaining variables are branched on
+/// in a fixed order and they keep their names. The solutions of this problem
+/// must be lifted to get complete solutions of the wrapped problem.
+#[derive(Debug, Clone)]
+pub struct SkipVariables<P> {
+    /// The wrapped problem
+    problem: P,
+    /// The variables which are branched on, in that order
+    order: Vec<Variable>,
+    /// The depth of each of these variables in the wrapped problem
+    depths: Vec<usize>,
+}
+
+impl<P> SkipVariables<P> {
+    /// Returns the wrapped problem
+    pub fn inner(&self) -> &P {
+        &self.problem
+    }
+    /// Returns the variables which are branched on, in that order
+    pub fn order(&self) -> &[Variable] {
+        &self.order
+    }
+    /// Returns the number of layers of a DD compiled for the wrapped problem
+    /// which fall before the given depth of the wrapped problem
+    fn depth_of(&self, depth: usize) -> usize {
+        self.depths.partition_point(|d| *d < depth)
+    }
+}
+
+impl<P: Problem> Problem for SkipVariables<P> {
+    type State = P::State;
+
+    fn nb_variables(&self) -> usize {
+        self.problem.nb_variables()
+    }
+    fn initial_state(&self) -> Self::State {
+        self.problem.initial_state()
+    }
+    fn initial_value(&self) -> isize {
+        self.problem.initial_value()
+    }
+    fn transition(&self, state: &Self::State, decision: Decision) -> Self::State {
+        self.problem.transition(state, decision)
+    }
+    fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: Decision) -> isize {
+        self.problem.transition_cost(source, dest, decision)
+    }
+    fn transition_with_ctx(&self, state: &Self::State, decision: Decision, ctx: &mut ProblemCtx) -> Self::State {
+        self.problem.transition_with_ctx(state, decision, ctx)
+    }
+    fn transition_cost_with_ctx(&self, source: &Self::State, dest: &Self::State, decision: Decision, ctx: &mut ProblemCtx) -> isize {
+        self.problem.transition_cost_with_ctx(source, dest, decision, ctx)
+    }
+    fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+        self.order.get(depth).copied()
+    }
+    fn for_each_in_domain(&self, var: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+        self.problem.for_each_in_domain(var, state, f)
+    }
+    fn is_impacted_by(&self, var: Variable, state: &Self::State) -> bool {
+        self.problem.is_impacted_by(var, state)
+    }
+    fn objective_depth(&self) -> Option<usize> {
+        self.problem.objective_depth().map(|depth| self.depth_of(depth))
+    }
+    fn feasibility_class(&self, state: &Self::State) -> Option<u64> {
+        self.problem.feasibility_class(state)
+    }
+    fn quick_neq(&self, a: &Self::State, b: &Self::State) -> bool {
+        self.problem.quick_neq(a, b)
+    }
+    fn width_profile(&self) -> Option<Vec<usize>> {
+        self.problem.width_profile()
+            .map(|profile| self.depths.iter().map_while(|d| profile.get(*d).copied()).collect())
+    }
+    fn filter_layer(&self, var: Variable, states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+        self.problem.filter_layer(var, states)
+    }
+    fn domain_is_state_independent(&self, var: Variable) -> bool {
+        self.problem.domain_is_state_independent(var)
+    }
+    fn dump_state(&self, state: &Self::State) -> Option<String> {
+        self.problem.dump_state(state)
+    }
+}
+
+#[cfg(test)]
+mod test_unused_variables {
+    use crate::*;
+
+    /// A knapsack whose state is the remaining capacity. The items which 
+    /// neither weigh nor earn anything are deliberately dead variables.
+    struct Items {
+        capacity: usize,
+        items: Vec<(usize, isize)>,
+    }
+    impl Problem for Items {
+        type State = usize;
+
+        fn nb_variables(&self) -> usize { self.items.len() }
+        fn initial_state(&self) -> usize { self.capacity }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, state: &usize, d: Decision) -> usize {
+            if d.value == 1 { state - self.items[d.variable.id()].0 } else { *state }
+        }
+        fn transition_cost(&self, _: &usize, _: &usize, d: Decision) -> isize {
+            if d.value == 1 { self.items[d.variable.id()].1 } else { 0 }
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+            Some(Variable(depth)).filter(|_| depth < self.items.len())
+        }
+        fn for_each_in_domain(&self, var: Variable, state: &usize, f: &mut dyn DecisionCallback) {
+            f.apply(Decision { variable: var, value: 0 });
+            if self.items[var.id()].0 <= *state {
+                f.apply(Decision { variable: var, value: 1 });
+            }
+        }
+    }
+    fn instance() -> Items {
+        Items { capacity: 5, items: vec![(2, 3), (0, 0), (3, 4), (0, 0), (4, 5)] }
+    }
+    /// Exhaustively solves the problem and returns the best value and solution
+    fn optimum(problem: &dyn Problem<State = usize>) -> (isize, Vec<Decision>) {
+        fn explore(problem: &dyn Problem<State = usize>, state: usize, depth: usize) -> (isize, Vec<Decision>) {
+            let Some(var) = problem.next_variable(depth, &mut std::iter::once(&state)) else {
+                return (0, vec![]);
+            };
+            let mut best = (isize::MIN, vec![]);
+            problem.for_each_in_domain(var, &state, &mut |d: Decision| {
+                let next = problem.transition(&state, d);
+                let cost = problem.transition_cost(&state, &next, d);
+                let (value, mut path) = explore(problem, next, depth + 1);
+                if cost + value > best.0 {
+                    path.push(d);
+                    best = (cost + value, path);
+                }
+            });
+            best
+        }
+        let (value, mut path) = explore(problem, problem.initial_state(), 0);
+        path.sort_unstable_by_key(|d| d.variable.id());
+        (problem.initial_value() + value, path)
+    }
+
+    #[test]
+    fn the_dead_variables_are_detected() {
+        let problem = instance();
+        let analysis = analyze_variables(&problem, 100, 42);
+        assert_eq!(100, analysis.samples);
+        assert_eq!(vec![
+            Decision { variable: Variable(1), value: 0 },
+            Decision { variable: Variable(3), value: 0 },
+        ], analysis.unused);
+        assert!(analysis.is_unused(Variable(1)));
+        assert!(!analysis.is_unused(Variable(2)));
+        assert_eq!(Some((0..5).map(Variable).collect()), analysis.order);
+    }
+    #[test]
+    fn the_same_seed_yields_the_same_analysis() {
+        let problem = instance();
+        assert_eq!(analyze_variables(&problem, 10, 7), analyze_variables(&problem, 10, 7));
+    }
+    #[test]
+    fn without_samples_nothing_is_reported() {
+        let problem = instance();
+        let analysis = analyze_variables(&problem, 0, 42);
+        assert!(analysis.unused.is_empty());
+        assert_eq!(None, analysis.order);
+        assert!(analysis.skip_unused(problem).is_none());
+    }
+    #[test]
+    fn skipping_the_dead_variables_shrinks_the_depth_but_not_the_optimum() {
+        let problem = instance();
+        let (value, solution) = optimum(&problem);
+        assert_eq!(7, value);
+
+        let analysis = analyze_variables(&problem, 100, 42);
+        let (skipped, lifter) = analysis.skip_unused(instance()).unwrap();
+        assert_eq!(&[Variable(0), Variable(2), Variable(4)], skipped.order());
+        assert_eq!(5, skipped.nb_variables());
+
+        let (reduced_value, reduced) = optimum(&skipped);
+        assert_eq!(value, reduced_value);
+        assert_eq!(3, reduced.len());
+        assert_eq!(solution, lifter.lift(&reduced));
+    }
+    #[test]
+    fn a_variable_whose_domain_may_be_empty_is_used() {
+        /// Variable 1 does nothing, but it is infeasible after taking x0 = 1
+        struct Blocking;
+        impl Problem for Blocking {
+            type State = bool;
+
+            fn nb_variables(&self) -> usize { 2 }
+            fn initial_state(&self) -> bool { false }
+            fn initial_value(&self) -> isize { 0 }
+            fn transition(&self, state: &bool, d: Decision) -> bool { 
+                *state || (d.variable.id() == 0 && d.value == 1) 
+            }
+            fn transition_cost(&self, _: &bool, _: &bool, d: Decision) -> isize { d.value }
+            fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &bool>) -> Option<Variable> {
+                Some(Variable(depth)).filter(|_| depth < 2)
+            }
+            fn for_each_in_domain(&self, var: Variable, state: &bool, f: &mut dyn DecisionCallback) {
+                if var.id() == 0 {
+                    f.apply(Decision { variable: var, value: 0 });
+                    f.apply(Decision { variable: var, value: 1 });
+                } else if !state {
+                    f.apply(Decision { variable: var, value: 0 });
+                }
+            }
+        }
+        let analysis = analyze_variables(&Blocking, 20, 42);
+        assert!(analysis.unused.is_empty());
+        assert_eq!(Some(vec![Variable(0), Variable(1)]), analysis.order);
+    }
+    #[test]
+    fn the_order_is_unknown_when_it_depends_on_the_states() {
+        /// The second variable depends on the first decision
+        struct Dynamic;
+        impl Problem for Dynamic {
+            type State = isize;
+
+            fn nb_variables(&self) -> usize { 3 }
+            fn initial_state(&self) -> isize { -1 }
+            fn initial_value(&self) -> isize { 0 }
+            fn transition(&self, state: &isize, d: Decision) -> isize { 
+                if *state < 0 { d.value } else { 2 + *state }
+            }
+            fn transition_cost(&self, _: &isize, _: &isize, d: Decision) -> isize { d.value }
+            fn next_variable(&self, _: usize, states: &mut dyn Iterator<Item = &isize>) -> Option<Variable> {
+                match states.next().copied() {
+                    Some(-1) => Some(Variable(0)),
+                    Some(s) if s < 2 => Some(Variable(1 + s as usize)),
+                    _ => None,
+                }
+            }
+            fn for_each_in_domain(&self, var: Variable, _: &isize, f: &mut dyn DecisionCallback) {
+                f.apply(Decision { variable: var, value: 0 });
+                f.apply(Decision { variable: var, value: 1 });
+            }
+        }
+        let analysis = analyze_variables(&Dynamic, 50, 42);
+        assert_eq!(None, analysis.order);
+        assert!(analysis.skip_unused(Dynamic).is_none());
+    }
+}
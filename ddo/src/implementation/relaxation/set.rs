@@ -0,0 +1,289 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the `SetRelaxation`: the relaxation of the problems
+//! whose states are sets (viewed as bitsets through `StateAsBitset`). It
+//! merges the states with a union or with an intersection and leaves the 
+//! costs of the arcs untouched, so that the value of a merged node is the 
+//! longest of the paths reaching the nodes it stands for.
+
+use crate::{Decision, Relaxation, StateAsBitset};
+
+/// The rough upper bound of a state
+type UpperBound<S> = Box<dyn Fn(&S) -> isize + Send + Sync>;
+
+/// The operator a `SetRelaxation` merges the states with. Which of them is
+/// admissible depends on what the elements of the sets stand for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetMerge {
+    /// The merged state holds the elements of any of the merged states. This
+    /// suits the "still available" semantics, where a larger set never makes
+    /// the value of a state worse: e.g. the vertices which may still be added
+    /// to an independent set (MISP) or the items which may still be packed.
+    Union,
+    /// The merged state only holds the elements of all the merged states. This
+    /// suits the "must still do" semantics, where a smaller set never makes 
+    /// the value of a state worse: e.g. the cities which must still be visited
+    /// (TSPTW) or the elements which must still be covered (set cover).
+    Intersection,
+}
+
+/// A relaxation which merges states that are sets. It is generic over the 
+/// view of these states as bitsets (see `StateAsBitset`), which makes it a
+/// one-liner for the models whose states are sets.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// /// The states are sets of at most 64 elements
+/// struct Bits;
+/// impl StateAsBitset for Bits {
+///     type State = u64;
+///     type Word  = u64;
+///
+///     fn bits<'a>(&self, state: &'a u64) -> &'a [u64] { std::slice::from_ref(state) }
+///     fn rebuild(&self, words: Vec<u64>) -> u64 { words.first().copied().unwrap_or(0) }
+/// }
+/// 
+/// let relaxation = SetRelaxation::union(Bits).with_fast_upper_bound(|s| s.count_ones() as isize);
+/// assert_eq!(0b1110, relaxation.merge(&mut [0b0110, 0b1010].iter()));
+/// assert_eq!(3, relaxation.fast_upper_bound(&0b1110));
+/// ```
+pub struct SetRelaxation<V: StateAsBitset> {
+    /// How the states are viewed as bitsets
+    view: V,
+    /// How the states are merged
+    operator: SetMerge,
+    /// The rough upper bound of a state (when there is one)
+    upper_bound: Option<UpperBound<V::State>>,
+}
+
+impl<V: StateAsBitset> SetRelaxation<V> {
+    /// Creates a relaxation which merges the states with the given operator
+    pub fn new(view: V, operator: SetMerge) -> Self {
+        Self { view, operator, upper_bound: None }
+    }
+    /// Creates a relaxation which merges the states with a union
+    pub fn union(view: V) -> Self {
+        Self::new(view, SetMerge::Union)
+    }
+    /// Creates a relaxation which merges the states with an intersection
+    pub fn intersection(view: V) -> Self {
+        Self::new(view, SetMerge::Intersection)
+    }
+    /// Sets the rough upper bound of the states (see 
+    /// `Relaxation::fast_upper_bound`). There is none by default.
+    pub fn with_fast_upper_bound<F>(mut self, bound: F) -> Self
+    where F: Fn(&V::State) -> isize + Send + Sync + 'static
+    {
+        self.upper_bound = Some(Box::new(bound));
+        self
+    }
+    /// Returns the operator this relaxation merges the states with
+    pub fn operator(&self) -> SetMerge {
+        self.operator
+    }
+}
+
+impl<V: StateAsBitset> Relaxation for SetRelaxation<V> {
+    type State = V::State;
+
+    fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+        let mut words = states.next()
+            .map(|s| self.view.bits(s).to_vec())
+            .unwrap_or_default();
+        for state in states {
+            let bits = self.view.bits(state);
+            match self.operator {
+                SetMerge::Union => {
+                    for (i, w) in bits.iter().copied().enumerate() {
+                        match words.get_mut(i) {
+                            Some(x) => *x = *x | w,
+                            None    => words.push(w),
+                        }
+                    }
+                },
+                SetMerge::Intersection => {
+                    words.truncate(bits.len());
+                    for (x, w) in words.iter_mut().zip(bits.iter().copied()) {
+                        *x = *x & w;
+                    }
+                },
+            }
+        }
+        self.view.rebuild(words)
+    }
+
+    fn relax(&self, _: &Self::State, _: &Self::State, _: &Self::State, _: Decision, cost: isize) -> isize {
+        cost
+    }
+
+    fn fast_upper_bound(&self, state: &Self::State) -> isize {
+        self.upper_bound.as_ref().map_or(isize::MAX, |bound| bound(state))
+    }
+}
+
+#[cfg(test)]
+mod test_set_relaxation {
+    use std::{cmp::Ordering, sync::Arc};
+
+    use crate::*;
+
+    /// Views vectors of words as bitsets
+    struct Words;
+    impl StateAsBitset for Words {
+        type State = Vec<u64>;
+        type Word  = u64;
+
+        fn bits<'a>(&self, state: &'a Vec<u64>) -> &'a [u64] { state }
+        fn rebuild(&self, words: Vec<u64>) -> Vec<u64> { words }
+    }
+
+    #[test]
+    fn union_holds_the_elements_of_any_merged_state() {
+        let relax = SetRelaxation::union(Words);
+        let states = [vec![0b0011], vec![0b0110, 0b1], vec![0b1000]];
+        assert_eq!(vec![0b1111, 0b1], relax.merge(&mut states.iter()));
+        assert_eq!(SetMerge::Union, relax.operator());
+    }
+    #[test]
+    fn intersection_holds_the_elements_of_all_merged_states() {
+        let relax = SetRelaxation::intersection(Words);
+        let states = [vec![0b0111, 0b1], vec![0b0110, 0b1], vec![0b1110]];
+        assert_eq!(vec![0b0110], relax.merge(&mut states.iter()));
+        assert_eq!(SetMerge::Intersection, relax.operator());
+    }
+    #[test]
+    fn the_costs_are_left_untouched() {
+        let relax = SetRelaxation::union(Words);
+        let d = Decision { variable: Variable(0), value: 1 };
+        assert_eq!(-7, relax.relax(&vec![1], &vec![2], &vec![3], d, -7));
+    }
+    #[test]
+    fn the_rough_upper_bound_is_the_given_one() {
+        let relax = SetRelaxation::union(Words);
+        assert_eq!(isize::MAX, relax.fast_upper_bound(&vec![0b101]));
+        let relax = relax.with_fast_upper_bound(|s| s.iter().map(|w| w.count_ones() as isize).sum());
+        assert_eq!(2, relax.fast_upper_bound(&vec![0b101]));
+    }
+
+    /// A problem with "must still do" semantics: the state is the set of the
+    /// tasks which are still pending. A pending task must be done (which costs
+    /// its penalty); any other may be volunteered for (which earns a bonus but
+    /// makes some later task pending).
+    struct Obligations {
+        penalty: Vec<isize>,
+        bonus  : Vec<isize>,
+        burden : Vec<u64>,
+    }
+    impl Problem for Obligations {
+        type State = Vec<u64>;
+
+        fn nb_variables(&self)  -> usize { self.penalty.len() }
+        fn initial_state(&self) -> Vec<u64> { vec![0b1] }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, pending: &Vec<u64>, d: Decision) -> Vec<u64> {
+            let i = d.variable.id();
+            if pending[0] & (1 << i) != 0 {
+                vec![pending[0] & !(1 << i)]
+            } else if d.value == 1 {
+                vec![pending[0] | self.burden[i]]
+            } else {
+                pending.clone()
+            }
+        }
+        fn transition_cost(&self, pending: &Vec<u64>, _: &Vec<u64>, d: Decision) -> isize {
+            let i = d.variable.id();
+            if pending[0] & (1 << i) != 0 {
+                -self.penalty[i]
+            } else {
+                d.value * self.bonus[i]
+            }
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Vec<u64>>) -> Option<Variable> {
+            (depth < self.nb_variables()).then_some(Variable(depth))
+        }
+        fn for_each_in_domain(&self, variable: Variable, pending: &Vec<u64>, f: &mut dyn DecisionCallback) {
+            f.apply(Decision { variable, value: 1 });
+            if pending[0] & (1 << variable.id()) == 0 {
+                f.apply(Decision { variable, value: 0 });
+            }
+        }
+    }
+    struct FewerPending;
+    impl StateRanking for FewerPending {
+        type State = Vec<u64>;
+        fn compare(&self, a: &Vec<u64>, b: &Vec<u64>) -> Ordering {
+            b[0].count_ones().cmp(&a[0].count_ones()).then_with(|| a.cmp(b))
+        }
+    }
+
+    /// Returns the best value of the DD compiled for the problem 
+    fn compile(pb: &Obligations, relax: &dyn Relaxation<State = Vec<u64>>, comp_type: CompilationType, max_width: usize) -> Option<isize> {
+        let cache = EmptyCache::default();
+        let dominance = EmptyDominanceChecker::default();
+        let input = CompilationInput {
+            comp_type,
+            problem: pb,
+            relaxation: relax,
+            ranking: &FewerPending,
+            cutoff: &NoCutoff,
+            max_width,
+            best_lb: isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let root = SubProblem {
+            state: Arc::new(pb.initial_state()),
+            value: pb.initial_value(),
+            path: vec![],
+            ub: isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let mut mdd = DefaultMDDLEL::default();
+        mdd.compile(&input, root).unwrap();
+        mdd.best_value()
+    }
+
+    #[test]
+    fn intersection_is_admissible_for_the_must_still_do_semantics() {
+        let mut rng = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = |m: u64| {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            rng % m
+        };
+        let relax = SetRelaxation::intersection(Words);
+        for _ in 0..50 {
+            let n = 4 + next(8) as usize;
+            let pb = Obligations {
+                penalty: (0..n).map(|_| next(10) as isize).collect(),
+                bonus  : (0..n).map(|_| next(10) as isize).collect(),
+                burden : (0..n).map(|i| (i + 1..n).filter(|_| next(3) == 0).fold(0, |b, j| b | 1 << j)).collect(),
+            };
+            let exact = compile(&pb, &relax, CompilationType::Exact, usize::MAX).unwrap();
+            for width in 1..=3 {
+                let relaxed = compile(&pb, &relax, CompilationType::Relaxed, width).unwrap();
+                assert!(relaxed >= exact, "width {width}: {relaxed} < {exact}");
+            }
+        }
+    }
+}
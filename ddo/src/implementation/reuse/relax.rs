@@ -0,0 +1,311 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the relaxation which reuses the bounds recorded for
+//! the other instances of the model in a `SharedBounds` store.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{BoundTransfer, Decision, Relaxation, SharedBounds};
+
+/// This relaxation wraps the relaxation of the problem and tightens its rough
+/// upper bound (see `Relaxation::fast_upper_bound`) with the bounds of the 
+/// `store`, as corrected by the `rule` of the instance being solved. Unless
+/// told otherwise, it records its own rough upper bounds in the store so that
+/// the instances which are solved afterwards may benefit from them.
+///
+/// # Note
+/// To solve a batch of instances, create one `SharedBounds` store and wrap
+/// the relaxation of each instance in a `ReuseRelaxation` referring to it.
+pub struct ReuseRelaxation<'a, R, T: ?Sized> {
+    /// The relaxation of the problem
+    inner: R,
+    /// The rule telling how the bounds of the other instances are corrected
+    rule: &'a T,
+    /// The bounds shared by all the instances
+    store: &'a SharedBounds,
+    /// Set when the rough upper bounds of this instance are recorded
+    record: bool,
+    /// The number of times a reused bound beat that of the inner relaxation
+    tightened: AtomicUsize,
+}
+impl<'a, R, T: ?Sized> ReuseRelaxation<'a, R, T> {
+    /// Wraps the relaxation `inner` of the instance described by `rule`
+    pub fn new(inner: R, rule: &'a T, store: &'a SharedBounds) -> Self {
+        Self { inner, rule, store, record: true, tightened: AtomicUsize::new(0) }
+    }
+    /// Tells whether the rough upper bounds of this instance are recorded in
+    /// the store (they are by default)
+    pub fn with_recording(mut self, record: bool) -> Self {
+        self.record = record;
+        self
+    }
+    /// Returns the relaxation of the problem
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+    /// Returns the number of times a bound from the store was tighter than 
+    /// the one of the inner relaxation
+    pub fn tightened(&self) -> usize {
+        self.tightened.load(Ordering::Relaxed)
+    }
+}
+
+impl<R, T> Relaxation for ReuseRelaxation<'_, R, T>
+where
+    R: Relaxation,
+    T: BoundTransfer<State = R::State> + ?Sized,
+{
+    type State = R::State;
+
+    fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+        self.inner.merge(states)
+    }
+    fn relax(
+        &self,
+        source: &Self::State,
+        dest: &Self::State,
+        new: &Self::State,
+        decision: Decision,
+        cost: isize,
+    ) -> isize {
+        self.inner.relax(source, dest, new, decision, cost)
+    }
+    fn fast_upper_bound(&self, state: &Self::State) -> isize {
+        let own = self.inner.fast_upper_bound(state);
+        if self.record && own < isize::MAX {
+            self.store.record(self.rule, state, own);
+        }
+        match self.store.lookup(self.rule, state) {
+            Some(reused) if reused < own => {
+                self.tightened.fetch_add(1, Ordering::Relaxed);
+                reused
+            },
+            _ => own,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_reuse_relaxation {
+    use crate::*;
+
+    fn knapsack(bonus: usize) -> Knapsack {
+        Knapsack {
+            capacity: 30,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4],
+            bonus,
+        }
+    }
+    /// Computes the longest path from the given state by brute force
+    fn value_to_go(pb: &Knapsack, state: KnapsackState) -> isize {
+        if state.depth == pb.nb_variables() {
+            return 0;
+        }
+        let next = KnapsackState { depth: state.depth + 1, capacity: state.capacity };
+        let leave = value_to_go(pb, next);
+        if pb.weight[state.depth] <= state.capacity {
+            let take = KnapsackState { capacity: state.capacity - pb.weight[state.depth], ..next };
+            leave.max((pb.profit[state.depth] + pb.bonus) as isize + value_to_go(pb, take))
+        } else {
+            leave
+        }
+    }
+    /// Picks some states pseudo-randomly
+    fn sample(pb: &Knapsack, count: usize) -> Vec<KnapsackState> {
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        (0..count).map(|_| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let depth = (seed >> 33) as usize % (pb.nb_variables() + 1);
+            let capacity = (seed >> 17) as usize % (pb.capacity + 1);
+            KnapsackState { depth, capacity }
+        }).collect()
+    }
+
+    #[test]
+    fn transferred_bounds_remain_admissible() {
+        let instances = [0, 2, 5, 1].map(knapsack);
+        let store = SharedBounds::new();
+        // the exact values are the tightest bounds that can ever be recorded
+        for pb in instances.iter() {
+            for state in sample(pb, 200) {
+                store.record(&BonusTransfer { pb }, &state, value_to_go(pb, state));
+            }
+        }
+        for pb in instances.iter() {
+            for state in sample(pb, 200) {
+                let bound = store.lookup(&BonusTransfer { pb }, &state).unwrap();
+                assert!(bound >= value_to_go(pb, state));
+            }
+        }
+    }
+    #[test]
+    fn the_relaxation_is_tightened_by_the_bounds_of_the_other_instances() {
+        let store = SharedBounds::new();
+        let first = knapsack(0);
+        for depth in 0..=first.nb_variables() {
+            for capacity in 0..=first.capacity {
+                let state = KnapsackState { depth, capacity };
+                store.record(&BonusTransfer { pb: &first }, &state, value_to_go(&first, state));
+            }
+        }
+
+        let problem = knapsack(1);
+        let rule = BonusTransfer { pb: &problem };
+        let relax = ReuseRelaxation::new(KPRelax { pb: &problem }, &rule, &store);
+        let ranking = KPRanking;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let cutoff = NoCutoff;
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+        let completion = solver.maximize();
+        assert!(completion.is_exact);
+        assert_eq!(Some(value_to_go(&problem, problem.initial_state())), completion.best_value);
+        assert!(relax.tightened() > 0);
+    }
+    #[test]
+    fn the_bounds_of_the_instance_are_only_recorded_when_asked_to() {
+        let store = SharedBounds::new();
+        let problem = knapsack(0);
+        let rule = BonusTransfer { pb: &problem };
+        let state = problem.initial_state();
+
+        let relax = ReuseRelaxation::new(KPRelax { pb: &problem }, &rule, &store).with_recording(false);
+        relax.fast_upper_bound(&state);
+        assert!(store.is_empty());
+
+        let relax = ReuseRelaxation::new(KPRelax { pb: &problem }, &rule, &store);
+        let bound = relax.fast_upper_bound(&state);
+        assert_eq!(Some(bound), store.lookup(&rule, &state));
+        assert_eq!(0, relax.tightened());
+    }
+
+    /// Each item taken earns the bonus of the instance on top of its profit.
+    /// Hence, moving to an instance with a larger bonus may increase the value
+    /// of the longest path by the difference of the bonuses for each of the 
+    /// remaining items; while moving to a smaller bonus cannot increase it.
+    struct BonusTransfer<'a> {
+        pb: &'a Knapsack,
+    }
+    impl BoundTransfer for BonusTransfer<'_> {
+        type State = KnapsackState;
+
+        fn fingerprint(&self) -> u64 {
+            self.pb.bonus as u64
+        }
+        fn state_key(&self, state: &Self::State) -> u64 {
+            ((state.depth as u64) << 32) | state.capacity as u64
+        }
+        fn delta(&self, _: &Self::State) -> isize {
+            self.pb.bonus as isize
+        }
+        fn transfer(&self, state: &Self::State, _: u64, bound: isize, delta: isize) -> Option<isize> {
+            let remaining = (self.pb.nb_variables() - state.depth) as isize;
+            Some(bound + (self.pb.bonus as isize - delta).max(0) * remaining)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct KnapsackState {
+        depth: usize,
+        capacity: usize
+    }
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<usize>,
+        weight: Vec<usize>,
+        bonus: usize,
+    }
+
+    const TAKE_IT: isize = 1;
+    const LEAVE_IT_OUT: isize = 0;
+
+    impl Problem for Knapsack {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> Self::State {
+            KnapsackState{ depth: 0, capacity: self.capacity }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = *state;
+            ret.depth  += 1;
+            if dec.value == TAKE_IT { 
+                ret.capacity -= self.weight[dec.variable.id()] 
+            }
+            ret
+        }
+        fn transition_cost(&self, _state: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            (self.profit[dec.variable.id()] + self.bonus) as isize * dec.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            let n = self.nb_variables();
+            if depth < n {
+                Some(Variable(depth))
+            } else {
+                None
+            }
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback)
+        {
+            if state.capacity >= self.weight[variable.id()] {
+                f.apply(Decision { variable, value: TAKE_IT });
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            } else {
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            }
+        }
+    }
+    struct KPRelax<'a>{pb: &'a Knapsack}
+    impl Relaxation for KPRelax<'_> {
+        type State = KnapsackState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            states.max_by_key(|node| node.capacity).copied().unwrap()
+        }
+        fn relax(&self, _source: &Self::State, _dest: &Self::State, _merged: &Self::State, _decision: Decision, cost: isize) -> isize {
+            cost
+        }
+        fn fast_upper_bound(&self, state: &Self::State) -> isize {
+            let mut tot = 0;
+            for var in state.depth..self.pb.nb_variables() {
+                if self.pb.weight[var] <= state.capacity {
+                    tot += self.pb.profit[var] + self.pb.bonus;
+                }
+            }
+            tot as isize
+        }
+    }
+    struct KPRanking;
+    impl StateRanking for KPRanking {
+        type State = KnapsackState;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.capacity.cmp(&b.capacity)
+        }
+    }
+}
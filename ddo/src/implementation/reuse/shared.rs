@@ -0,0 +1,178 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the store of the bounds which are shared across the
+//! resolution of several instances of one same model.
+
+use dashmap::DashMap;
+
+use crate::BoundTransfer;
+
+/// A bound which was recorded for some state of one instance 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoredBound {
+    /// The fingerprint of the instance the bound was derived for
+    pub fingerprint: u64,
+    /// The upper bound on the longest path from the state in that instance
+    pub bound: isize,
+    /// The delta bound of the state in that instance (see `BoundTransfer::delta`)
+    pub delta: isize,
+}
+
+/// The store of the bounds shared by the resolutions of several instances of
+/// the same model. The bounds are keyed by the canonical key of their state
+/// and by the fingerprint of the instance they were derived for; only the 
+/// tightest bound is kept for any such pair.
+///
+/// The store is meant to outlive the solvers: it is shared (by reference) 
+/// among all the resolutions of a batch, possibly running in parallel.
+#[derive(Debug, Default)]
+pub struct SharedBounds {
+    bounds: DashMap<u64, Vec<StoredBound>, fxhash::FxBuildHasher>,
+}
+impl SharedBounds {
+    /// Creates an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Records that `bound` is an upper bound on the longest path from `state`
+    /// in the instance described by `rule`
+    pub fn record<T: BoundTransfer + ?Sized>(&self, rule: &T, state: &T::State, bound: isize) {
+        let fingerprint = rule.fingerprint();
+        let delta = rule.delta(state);
+        let mut bounds = self.bounds.entry(rule.state_key(state)).or_default();
+        if let Some(stored) = bounds.iter_mut().find(|b| b.fingerprint == fingerprint) {
+            if bound < stored.bound {
+                *stored = StoredBound { fingerprint, bound, delta };
+            }
+        } else {
+            bounds.push(StoredBound { fingerprint, bound, delta });
+        }
+    }
+    /// Returns the tightest upper bound on the longest path from `state` in 
+    /// the instance described by `rule` that can be derived from the recorded
+    /// bounds. The bound recorded for that very instance is used as is, the
+    /// others are corrected by `rule` (see `BoundTransfer::transfer`).
+    pub fn lookup<T: BoundTransfer + ?Sized>(&self, rule: &T, state: &T::State) -> Option<isize> {
+        let fingerprint = rule.fingerprint();
+        let bounds = self.bounds.get(&rule.state_key(state))?;
+        bounds.iter()
+            .filter_map(|b| if b.fingerprint == fingerprint {
+                Some(b.bound)
+            } else {
+                rule.transfer(state, b.fingerprint, b.bound, b.delta)
+            })
+            .min()
+    }
+    /// Returns the bounds recorded for the state having the given key
+    pub fn recorded(&self, state_key: u64) -> Vec<StoredBound> {
+        self.bounds.get(&state_key).map(|b| b.clone()).unwrap_or_default()
+    }
+    /// Forgets about all the bounds recorded for the given instance
+    pub fn forget(&self, fingerprint: u64) {
+        self.bounds.iter_mut().for_each(|mut b| b.retain(|s| s.fingerprint != fingerprint));
+        self.bounds.retain(|_, b| !b.is_empty());
+    }
+    /// Returns the number of bounds in the store
+    pub fn len(&self) -> usize {
+        self.bounds.iter().map(|b| b.len()).sum()
+    }
+    /// Returns true iff no bound has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+    /// Forgets about all the recorded bounds
+    pub fn clear(&self) {
+        self.bounds.clear();
+    }
+}
+
+#[cfg(test)]
+mod test_shared_bounds {
+    use crate::*;
+
+    /// The states are plain numbers and the instances add an offset to all
+    /// the bounds: a bound of the instance `from` is shifted by the offset of
+    /// this instance minus that of `from` (recorded as the delta bound)
+    struct Offset(isize);
+    impl BoundTransfer for Offset {
+        type State = u64;
+
+        fn fingerprint(&self) -> u64 { self.0 as u64 }
+        fn state_key(&self, state: &u64) -> u64 { *state }
+        fn delta(&self, _: &u64) -> isize { self.0 }
+        fn transfer(&self, _: &u64, _: u64, bound: isize, delta: isize) -> Option<isize> {
+            Some(bound + self.0 - delta)
+        }
+    }
+    /// Never lets a bound be transferred
+    struct Isolated(u64);
+    impl BoundTransfer for Isolated {
+        type State = u64;
+
+        fn fingerprint(&self) -> u64 { self.0 }
+        fn state_key(&self, state: &u64) -> u64 { *state }
+        fn transfer(&self, _: &u64, _: u64, _: isize, _: isize) -> Option<isize> { None }
+    }
+
+    #[test]
+    fn only_the_tightest_bound_is_kept_for_a_state_of_an_instance() {
+        let store = SharedBounds::new();
+        store.record(&Offset(0), &7, 10);
+        store.record(&Offset(0), &7, 12);
+        store.record(&Offset(0), &7, 8);
+        assert_eq!(1, store.len());
+        assert_eq!(Some(8), store.lookup(&Offset(0), &7));
+        assert_eq!(vec![StoredBound { fingerprint: 0, bound: 8, delta: 0 }], store.recorded(7));
+    }
+    #[test]
+    fn the_bounds_of_the_other_instances_are_corrected_by_the_rule() {
+        let store = SharedBounds::new();
+        store.record(&Offset(0), &7, 10);
+        store.record(&Offset(5), &7, 20);
+        assert_eq!(2, store.len());
+        // 10 + 3 - 0 beats 20 + 3 - 5
+        assert_eq!(Some(13), store.lookup(&Offset(3), &7));
+        // its own bound (20) is looser than that of the other instance (10 + 5 - 0)
+        assert_eq!(Some(15), store.lookup(&Offset(5), &7));
+        assert_eq!(None, store.lookup(&Offset(3), &8));
+    }
+    #[test]
+    fn a_bound_which_cannot_be_transferred_is_ignored() {
+        let store = SharedBounds::new();
+        store.record(&Isolated(1), &7, 10);
+        assert_eq!(Some(10), store.lookup(&Isolated(1), &7));
+        assert_eq!(None, store.lookup(&Isolated(2), &7));
+    }
+    #[test]
+    fn forgetting_an_instance_drops_its_bounds_only() {
+        let store = SharedBounds::new();
+        store.record(&Offset(0), &7, 10);
+        store.record(&Offset(0), &8, 10);
+        store.record(&Offset(5), &7, 20);
+        store.forget(0);
+        assert_eq!(1, store.len());
+        assert_eq!(Some(20), store.lookup(&Offset(5), &7));
+        assert_eq!(None, store.lookup(&Offset(5), &8));
+
+        store.clear();
+        assert!(store.is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides a handle which cancels the search of a solver from 
+//! another thread, e.g. a signal handler or a button of a user interface (see
+//! `abort_handle`).
+
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+
+use crate::Reason;
+
+/// A token which asks a solver to stop its search. Once it is set, the solver
+/// stops at the next safe point (between two nodes, or between two layers of
+/// the DD being compiled) and `maximize` returns the best solution found so
+/// far with `stopped_by == Some(Reason::Aborted)`.
+///
+/// The handle is cheap to clone: all the clones share the same flag. Setting
+/// it merely stores an atomic boolean, hence it may be done from a signal
+/// handler. The flag remains set until the solver is `reset`: a solver which
+/// is aborted before `maximize` is called returns at once.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// let handle = AbortHandle::default();
+/// let button = handle.clone();
+/// assert!(!handle.is_aborted());
+/// button.abort();
+/// assert!(handle.is_aborted());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AbortHandle {
+    aborted: Arc<AtomicBool>,
+}
+impl AbortHandle {
+    /// Asks the solver to stop its search
+    pub fn abort(&self) {
+        self.aborted.store(true, Ordering::Release);
+    }
+    /// Returns true iff the solver was asked to stop its search
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::Acquire)
+    }
+    /// Forgets that the solver was asked to stop
+    pub(crate) fn clear(&self) {
+        self.aborted.store(false, Ordering::Release);
+    }
+    /// Tells the abort apart from the other cutoffs: a compilation which was
+    /// stopped once the handle was set was stopped because of it
+    pub(crate) fn explain(&self, reason: Reason) -> Reason {
+        if reason == Reason::CutoffOccurred && self.is_aborted() {
+            Reason::Aborted
+        } else {
+            reason
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_abort {
+    use crate::*;
+
+    #[test]
+    fn all_the_clones_share_the_same_flag() {
+        let handle = AbortHandle::default();
+        let other = handle.clone();
+        other.abort();
+        assert!(handle.is_aborted());
+        handle.clear();
+        assert!(!other.is_aborted());
+    }
+    #[test]
+    fn only_the_cutoffs_are_explained_by_the_abort() {
+        let handle = AbortHandle::default();
+        assert_eq!(Reason::CutoffOccurred, handle.explain(Reason::CutoffOccurred));
+        handle.abort();
+        assert_eq!(Reason::Aborted, handle.explain(Reason::CutoffOccurred));
+        assert_eq!(Reason::NodeLimit, handle.explain(Reason::NodeLimit));
+    }
+}
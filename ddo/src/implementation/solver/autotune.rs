@@ -0,0 +1,532 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides a solver which auto-tunes its configuration. It is
+//! given a few candidate solvers (typically the same problem solved with
+//! different widths or heuristics) and lets each of them explore a fixed
+//! number of nodes from the root. The candidate which made the most progress
+//! is then used to solve the problem for good, starting from the best solution
+//! found by any candidate.
+
+use std::{cmp::Ordering, time::{Duration, Instant}};
+
+use crate::{Completion, Solution, Solver, Statistics};
+
+/// The default number of nodes each candidate may explore while tuning
+pub const DEFAULT_TUNING_BUDGET: usize = 1_000;
+
+/// The progress made by one candidate while tuning
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TuningRecord {
+    /// The number of nodes explored by the candidate
+    pub explored: usize,
+    /// The best lower bound found by the candidate
+    pub best_lb: isize,
+    /// The best upper bound derived by the candidate
+    pub best_ub: isize,
+    /// The optimality gap reached by the candidate (see `Solver::gap`)
+    pub gap: f32,
+    /// Set when the candidate solved the problem to optimality within its budget
+    pub is_exact: bool,
+}
+impl TuningRecord {
+    /// Compares the progress made by two candidates: the smallest gap wins,
+    /// and the best incumbent breaks ties. Greater is better.
+    fn compare(&self, other: &Self) -> Ordering {
+        other.gap.total_cmp(&self.gap)
+            .then_with(|| self.best_lb.cmp(&other.best_lb))
+    }
+}
+
+/// This solver picks the best of its candidate solvers on a prefix of the
+/// search, and commits to it for the remainder of the resolution.
+///
+/// # Note
+/// All the candidates must be solvers of the same problem. Each candidate is
+/// reset before it is tuned, and the winner is reset once again before it
+/// solves the problem with the best solution found while tuning as a primal.
+pub struct AutoTuneSolver<'a> {
+    candidates: Vec<Box<dyn Solver + 'a>>,
+    /// The number of nodes each candidate may explore while tuning
+    tuning_budget: usize,
+    /// The node budget of the winner (see `Solver::set_node_budget`)
+    node_budget: Option<usize>,
+    /// The time `maximize` may run, tuning included (see `Solver::set_time_limit`)
+    time_limit: Option<Duration>,
+    /// The best known solution (either set with `set_primal` or found while tuning)
+    primal: Option<(isize, Solution)>,
+    /// The bound set with `set_primal_bound` (if any)
+    primal_bound: Option<isize>,
+    /// The progress made by each candidate while tuning
+    records: Vec<TuningRecord>,
+    /// The index of the candidate that was picked
+    winner: Option<usize>,
+    /// Set when the winner was run past the tuning phase
+    committed: bool,
+}
+
+impl<'a> AutoTuneSolver<'a> {
+    /// Creates an auto-tuning solver picking one of the given `candidates`
+    ///
+    /// # Panics
+    /// If no candidate is given
+    pub fn new(candidates: Vec<Box<dyn Solver + 'a>>) -> Self {
+        assert!(!candidates.is_empty(), "auto-tuning requires at least one candidate");
+        Self {
+            candidates,
+            tuning_budget: DEFAULT_TUNING_BUDGET,
+            node_budget: None,
+            time_limit: None,
+            primal: None,
+            primal_bound: None,
+            records: vec![],
+            winner: None,
+            committed: false,
+        }
+    }
+    /// Sets the number of nodes each candidate may explore while tuning
+    pub fn with_tuning_budget(mut self, nodes: usize) -> Self {
+        self.tuning_budget = nodes;
+        self
+    }
+    /// Returns the progress made by each candidate while tuning
+    pub fn tuning_records(&self) -> &[TuningRecord] {
+        &self.records
+    }
+    /// Returns the index of the candidate that was picked (None until the
+    /// problem has been solved)
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    fn offer(&mut self, value: Option<isize>, solution: Option<Solution>) {
+        if let (Some(value), Some(solution)) = (value, solution) {
+            if self.primal.as_ref().is_none_or(|(best, _)| value > *best) {
+                self.primal = Some((value, solution));
+            }
+        }
+    }
+
+    /// Returns the time that is left to `maximize` since it was started
+    fn remaining(&self, start: Instant) -> Option<Duration> {
+        self.time_limit.map(|limit| limit.saturating_sub(start.elapsed()))
+    }
+
+    /// Lets each candidate explore the tuning budget. It returns the index
+    /// of the winner and its completion when it proved optimality.
+    fn tune(&mut self, start: Instant) -> (usize, Option<Completion>) {
+        self.records.clear();
+        for i in 0..self.candidates.len() {
+            let remaining = self.remaining(start);
+            let candidate = &mut self.candidates[i];
+            candidate.reset();
+            candidate.set_node_budget(Some(self.tuning_budget));
+            candidate.set_time_limit(remaining);
+            if let Some(bound) = self.primal_bound {
+                candidate.set_primal_bound(bound);
+            }
+            if let Some((value, solution)) = self.primal.as_ref() {
+                candidate.set_primal(*value, solution.clone());
+            }
+            let completion = candidate.maximize();
+            self.records.push(TuningRecord {
+                explored: candidate.explored(),
+                best_lb: candidate.best_lower_bound(),
+                best_ub: candidate.best_upper_bound(),
+                gap: candidate.gap(),
+                is_exact: completion.is_exact,
+            });
+            let (value, solution) = (candidate.best_value(), candidate.best_solution());
+            self.offer(value, solution);
+
+            if completion.is_exact {
+                return (i, Some(completion));
+            }
+        }
+        let winner = self.records.iter().enumerate()
+            .max_by(|(i, a), (j, b)| a.compare(b).then_with(|| j.cmp(i)))
+            .map(|(i, _)| i)
+            .unwrap();
+        (winner, None)
+    }
+
+    fn winner_ref(&self) -> Option<&(dyn Solver + 'a)> {
+        self.winner.map(|i| self.candidates[i].as_ref())
+    }
+}
+
+impl Solver for AutoTuneSolver<'_> {
+    fn maximize(&mut self) -> Completion {
+        let start = Instant::now();
+        let (winner, completion) = self.tune(start);
+        self.winner = Some(winner);
+        self.committed = completion.is_none();
+        if let Some(completion) = completion {
+            return completion;
+        }
+
+        let remaining = self.remaining(start);
+        let candidate = &mut self.candidates[winner];
+        candidate.reset();
+        candidate.set_node_budget(self.node_budget);
+        candidate.set_time_limit(remaining);
+        if let Some(bound) = self.primal_bound {
+            candidate.set_primal_bound(bound);
+        }
+        if let Some((value, solution)) = self.primal.as_ref() {
+            candidate.set_primal(*value, solution.clone());
+        }
+        candidate.maximize()
+    }
+
+    fn best_value(&self) -> Option<isize> {
+        match self.winner_ref() {
+            Some(winner) => winner.best_value(),
+            None => self.primal.as_ref().map(|(value, _)| *value),
+        }
+    }
+
+    fn best_solution(&self) -> Option<Solution> {
+        match self.winner_ref() {
+            Some(winner) => winner.best_solution(),
+            None => self.primal.as_ref().map(|(_, solution)| solution.clone()),
+        }
+    }
+
+    fn best_lower_bound(&self) -> isize {
+        self.best_value().unwrap_or(isize::MIN)
+    }
+
+    /// The bounds derived by the candidates while tuning remain valid, hence
+    /// the tightest of them is reported when it beats that of the winner
+    fn best_upper_bound(&self) -> isize {
+        let tuned = self.records.iter().map(|r| r.best_ub).min().unwrap_or(isize::MAX);
+        let winner = self.winner_ref().map(|w| w.best_upper_bound()).unwrap_or(isize::MAX);
+        tuned.min(winner)
+    }
+
+    fn set_primal(&mut self, value: isize, solution: Solution) {
+        self.offer(Some(value), Some(solution.clone()));
+        if let Some(winner) = self.winner {
+            self.candidates[winner].set_primal(value, solution);
+        }
+    }
+
+    fn set_primal_bound(&mut self, bound: isize) {
+        if self.primal_bound.is_none_or(|b| bound > b) {
+            self.primal_bound = Some(bound);
+        }
+        if self.primal.as_ref().is_some_and(|(value, _)| *value <= bound) {
+            self.primal = None;
+        }
+        if let Some(winner) = self.winner {
+            self.candidates[winner].set_primal_bound(bound);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.candidates.iter_mut().for_each(|c| c.reset());
+        self.primal = None;
+        self.primal_bound = None;
+        self.records.clear();
+        self.winner = None;
+        self.committed = false;
+    }
+
+    /// Limits the number of nodes the winner may explore once tuning is over
+    fn set_node_budget(&mut self, budget: Option<usize>) {
+        self.node_budget = budget;
+    }
+
+    /// Limits the time of the whole resolution: each candidate is given the
+    /// time that is left when its turn comes
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+    }
+
+    /// Returns the number of nodes explored by all the candidates while
+    /// tuning, plus those explored by the winner afterwards
+    fn explored(&self) -> usize {
+        let tuning = self.records.iter().map(|r| r.explored).sum::<usize>();
+        let committed = self.winner_ref().filter(|_| self.committed).map(|w| w.explored()).unwrap_or(0);
+        tuning + committed
+    }
+
+    /// Returns the statistics of the winner. Those of the other candidates
+    /// (which only searched while tuning) are left out.
+    fn statistics(&self) -> Option<Statistics> {
+        self.winner_ref().and_then(|w| w.statistics())
+    }
+}
+
+#[cfg(test)]
+mod test_autotune {
+    use crate::*;
+
+    fn knapsack() -> Knapsack {
+        Knapsack {
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        }
+    }
+    /// Computes the optimum of the given knapsack instance by brute force
+    fn optimum(problem: &Knapsack) -> isize {
+        let n = problem.nb_variables();
+        (0..1_usize << n)
+            .filter(|set| (0..n).filter(|i| set & (1 << i) != 0).map(|i| problem.weight[i]).sum::<usize>() <= problem.capacity)
+            .map(|set| (0..n).filter(|i| set & (1 << i) != 0).map(|i| problem.profit[i]).sum::<usize>() as isize)
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    #[should_panic]
+    fn at_least_one_candidate_is_required() {
+        AutoTuneSolver::new(vec![]);
+    }
+
+    #[test]
+    fn the_result_is_optimal_whichever_candidate_wins() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let widths = [FixedWidth(1), FixedWidth(2), FixedWidth(5)];
+        let expected = optimum(&problem);
+
+        // each candidate in turn is the only one that can win
+        for width in widths.iter() {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let candidate = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, width, &dominance, &cutoff, &mut fringe);
+            let mut solver = AutoTuneSolver::new(vec![Box::new(candidate)]).with_tuning_budget(2);
+
+            let completion = solver.maximize();
+            assert!(completion.is_exact);
+            assert_eq!(Some(expected), completion.best_value);
+            assert_eq!(Some(expected), solver.best_value());
+            assert_eq!(Some(0), solver.winner());
+        }
+    }
+
+    #[test]
+    fn the_time_limit_covers_the_tuning_and_the_resolution() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(1);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let candidate = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        let mut solver = AutoTuneSolver::new(vec![Box::new(candidate)]).with_tuning_budget(2);
+
+        solver.set_time_limit(Some(std::time::Duration::ZERO));
+        let completion = solver.maximize();
+        assert!(!completion.is_exact);
+        assert_eq!(Some(Reason::Timeout), completion.stopped_by);
+        assert!(solver.tuning_records().iter().all(|r| r.explored == 0));
+    }
+
+    #[test]
+    fn the_winner_is_the_candidate_which_made_the_most_progress() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let (narrow, wide) = (FixedWidth(1), FixedWidth(4));
+        let mut f1 = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut f2 = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut f3 = SimpleFringe::new(MaxUB::new(&ranking));
+        let candidates: Vec<Box<dyn Solver>> = vec![
+            Box::new(SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &narrow, &dominance, &cutoff, &mut f1)),
+            Box::new(SeqNoCachingSolverFc::custom(&problem, &relax, &ranking, &wide, &dominance, &cutoff, &mut f2)),
+            Box::new(ParNoCachingSolverLel::custom(&problem, &relax, &ranking, &wide, &dominance, &cutoff, &mut f3, 2)),
+        ];
+        let mut solver = AutoTuneSolver::new(candidates).with_tuning_budget(3);
+
+        let completion = solver.maximize();
+        assert!(completion.is_exact);
+        assert_eq!(Some(optimum(&problem)), completion.best_value);
+        assert_eq!(completion.best_value, solver.best_value());
+        assert_eq!(solver.best_value(), Some(solver.best_upper_bound()));
+
+        let records = solver.tuning_records();
+        assert_eq!(3, records.len());
+        assert!(records.iter().all(|r| !r.is_exact && r.explored <= 3 + 2));
+
+        let winner = solver.winner().unwrap();
+        assert!(records.iter().all(|r| r.gap >= records[winner].gap));
+        assert!(solver.explored() > records.iter().map(|r| r.explored).sum::<usize>());
+    }
+
+    #[test]
+    fn tuning_stops_as_soon_as_a_candidate_proves_optimality() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(5);
+        let mut f1 = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut f2 = SimpleFringe::new(MaxUB::new(&ranking));
+        let candidates: Vec<Box<dyn Solver>> = vec![
+            Box::new(SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut f1)),
+            Box::new(SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut f2)),
+        ];
+        let mut solver = AutoTuneSolver::new(candidates).with_tuning_budget(usize::MAX);
+
+        let completion = solver.maximize();
+        assert!(completion.is_exact);
+        assert_eq!(Some(optimum(&problem)), completion.best_value);
+        assert_eq!(Some(0), solver.winner());
+        assert_eq!(1, solver.tuning_records().len());
+        assert_eq!(solver.tuning_records()[0].explored, solver.explored());
+    }
+
+    #[test]
+    fn the_incumbent_found_while_tuning_is_carried_over() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(1);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let candidate = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        // the winner is not allowed to explore anything past the tuning phase
+        let mut solver = AutoTuneSolver::new(vec![Box::new(candidate)]).with_tuning_budget(2);
+        solver.set_node_budget(Some(0));
+
+        let completion = solver.maximize();
+        assert!(!completion.is_exact);
+        assert_eq!(Some(solver.tuning_records()[0].best_lb), completion.best_value);
+        assert!(solver.best_solution().is_some());
+    }
+
+    #[test]
+    fn set_primal_and_reset() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(2);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let candidate = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        let mut solver = AutoTuneSolver::new(vec![Box::new(candidate)]);
+
+        solver.set_primal(10, vec![]);
+        assert_eq!(Some(10), solver.best_value());
+        assert_eq!(10, solver.best_lower_bound());
+
+        solver.maximize();
+        solver.reset();
+        assert_eq!(None, solver.best_value());
+        assert_eq!(None, solver.winner());
+        assert_eq!(isize::MAX, solver.best_upper_bound());
+        assert_eq!(0, solver.explored());
+        assert!(solver.tuning_records().is_empty());
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct KnapsackState {
+        depth: usize,
+        capacity: usize
+    }
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<usize>,
+        weight: Vec<usize>,
+    }
+
+    const TAKE_IT: isize = 1;
+    const LEAVE_IT_OUT: isize = 0;
+
+    impl Problem for Knapsack {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> Self::State {
+            KnapsackState{ depth: 0, capacity: self.capacity }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = *state;
+            ret.depth  += 1;
+            if dec.value == TAKE_IT { 
+                ret.capacity -= self.weight[dec.variable.id()] 
+            }
+            ret
+        }
+        fn transition_cost(&self, _state: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            self.profit[dec.variable.id()] as isize * dec.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            let n = self.nb_variables();
+            if depth < n {
+                Some(Variable(depth))
+            } else {
+                None
+            }
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback)
+        {
+            if state.capacity >= self.weight[variable.id()] {
+                f.apply(Decision { variable, value: TAKE_IT });
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            } else {
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            }
+        }
+    }
+    struct KPRelax<'a>{pb: &'a Knapsack}
+    impl Relaxation for KPRelax<'_> {
+        type State = KnapsackState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            states.max_by_key(|node| node.capacity).copied().unwrap()
+        }
+        fn relax(&self, _source: &Self::State, _dest: &Self::State, _merged: &Self::State, _decision: Decision, cost: isize) -> isize {
+            cost
+        }
+        fn fast_upper_bound(&self, state: &Self::State) -> isize {
+            let mut tot = 0;
+            for var in state.depth..self.pb.nb_variables() {
+                if self.pb.weight[var] <= state.capacity {
+                    tot += self.pb.profit[var];
+                }
+            }
+            tot as isize
+        }
+    }
+    struct KPRanking;
+    impl StateRanking for KPRanking {
+        type State = KnapsackState;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.capacity.cmp(&b.capacity)
+        }
+    }
+}
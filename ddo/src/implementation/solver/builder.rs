@@ -0,0 +1,500 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides a builder which assembles a solver from the parts you
+//! care about and picks a sensible default for all the others. It spares you
+//! the long list of arguments (and of generic parameters) of the constructors
+//! of the solvers.
+
+use std::{hash::Hash, time::Duration};
+
+use crate::{Problem, Relaxation, StateRanking, WidthHeuristic, DominanceChecker, Cutoff, Fringe, Solver, NbUnassignedWidth, EmptyDominanceChecker, NoCutoff, SimpleFringe, StackFringe, FringeOrder, MaxUB, ParNoCachingSolverLel, ParCachingSolverLel, Preprocess, SolutionLifter, Completion, Solution, Statistics};
+
+/// This builder assembles a (parallel) solver for a given problem. Only the 
+/// relaxation and the state ranking are mandatory; the other parts default to
+///
+/// * `NbUnassignedWidth` for the maximum width of the DDs,
+/// * `EmptyDominanceChecker` for the dominance checker,
+/// * `NoCutoff` for the cutoff,
+/// * a `SimpleFringe` ordered by `MaxUB` for the fringe (best-first order),
+/// * as many threads as there are hardware threads, and no cache.
+///
+/// The problem may also be reduced before it is solved (see 
+/// `with_preprocessing`).
+///
+/// The builder owns the parts it was given, and the solver it builds borrows
+/// them. Hence the builder must outlive the solver.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # struct State(usize);
+/// # struct Binary;
+/// # impl Problem for Binary {
+/// #     type State = State;
+/// #     fn nb_variables(&self) -> usize { 3 }
+/// #     fn initial_state(&self) -> State { State(0) }
+/// #     fn initial_value(&self) -> isize { 0 }
+/// #     fn transition(&self, s: &State, _: Decision) -> State { State(s.0 + 1) }
+/// #     fn transition_cost(&self, _: &State, _: &State, d: Decision) -> isize { d.value }
+/// #     fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &State>) -> Option<Variable> {
+/// #         (depth < 3).then_some(Variable(depth))
+/// #     }
+/// #     fn for_each_in_domain(&self, var: Variable, _: &State, f: &mut dyn DecisionCallback) {
+/// #         f.apply(Decision { variable: var, value: 0 });
+/// #         f.apply(Decision { variable: var, value: 1 });
+/// #     }
+/// # }
+/// # struct Relax;
+/// # impl Relaxation for Relax {
+/// #     type State = State;
+/// #     fn merge(&self, s: &mut dyn Iterator<Item = &State>) -> State { s.next().unwrap().clone() }
+/// #     fn relax(&self, _: &State, _: &State, _: &State, _: Decision, cost: isize) -> isize { cost }
+/// # }
+/// # struct Ranking;
+/// # impl StateRanking for Ranking {
+/// #     type State = State;
+/// #     fn compare(&self, a: &State, b: &State) -> std::cmp::Ordering { a.0.cmp(&b.0) }
+/// # }
+/// # let (problem, relaxation, ranking) = (Binary, Relax, Ranking);
+/// let mut builder = SolverBuilder::for_problem(&problem)
+///     .with_relaxation(&relaxation)
+///     .with_ranking(&ranking)
+///     .with_width(FixedWidth(100))
+///     .with_threads(2);
+/// let mut solver = builder.build();
+/// assert_eq!(Some(3), solver.maximize().best_value);
+/// ```
+pub struct SolverBuilder<'a, State, P> {
+    problem: &'a P,
+    /// The problem which is solved in place of `problem` when it has been
+    /// preprocessed
+    reduced: Option<Box<dyn Problem<State = State> + Send + Sync + 'a>>,
+    /// The lifter which maps the solutions of the reduced problem back
+    lifter: Option<Box<dyn SolutionLifter + 'a>>,
+    relaxation: Option<&'a (dyn Relaxation<State = State> + Send + Sync)>,
+    ranking: Option<&'a (dyn StateRanking<State = State> + Send + Sync)>,
+    /// The width heuristic, or None to use `NbUnassignedWidth`
+    width: Option<Box<dyn WidthHeuristic<State> + Send + Sync + 'a>>,
+    dominance: Box<dyn DominanceChecker<State = State> + Send + Sync + 'a>,
+    cutoff: Box<dyn Cutoff + Send + Sync + 'a>,
+    fringe: Option<Box<dyn Fringe<State = State> + Send + Sync + 'a>>,
+    /// The best-first fringe which is used when no fringe is given
+    best_first: Option<Box<dyn Fringe<State = State> + Send + Sync + 'a>>,
+    /// The depth-first fringe which is used when no fringe is given
+    depth_first: StackFringe<State>,
+    order: FringeOrder,
+    threads: usize,
+    caching: bool,
+}
+impl<'a, State, P> SolverBuilder<'a, State, P>
+where State: Eq + Hash + Clone + Send + Sync + 'a,
+      P: Problem<State = State> + Send + Sync + 'a,
+{
+    /// Starts assembling a solver for the given problem
+    pub fn for_problem(problem: &'a P) -> Self {
+        Self {
+            problem,
+            reduced: None,
+            lifter: None,
+            relaxation: None,
+            ranking: None,
+            width: None,
+            dominance: Box::<EmptyDominanceChecker<State>>::default(),
+            cutoff: Box::new(NoCutoff),
+            fringe: None,
+            best_first: None,
+            depth_first: StackFringe::new(),
+            order: FringeOrder::BestFirst,
+            threads: num_cpus::get(),
+            caching: false,
+        }
+    }
+    /// Sets the relaxation of the problem (mandatory)
+    pub fn with_relaxation(mut self, relaxation: &'a (dyn Relaxation<State = State> + Send + Sync)) -> Self {
+        self.relaxation = Some(relaxation);
+        self
+    }
+    /// Sets the ranking of the states (mandatory). Unless a fringe is given,
+    /// the best-first order sorts the subproblems by `MaxUB` with this ranking.
+    pub fn with_ranking<O>(mut self, ranking: &'a O) -> Self
+    where O: StateRanking<State = State> + Send + Sync
+    {
+        self.ranking = Some(ranking);
+        self.best_first = Some(Box::new(SimpleFringe::new(MaxUB::new(ranking))));
+        self
+    }
+    /// Sets the heuristic which gives the maximum width of the DDs
+    pub fn with_width<W>(mut self, width: W) -> Self
+    where W: WidthHeuristic<State> + Send + Sync + 'a
+    {
+        self.width = Some(Box::new(width));
+        self
+    }
+    /// Sets the dominance checker
+    pub fn with_dominance<D>(mut self, dominance: D) -> Self
+    where D: DominanceChecker<State = State> + Send + Sync + 'a
+    {
+        self.dominance = Box::new(dominance);
+        self
+    }
+    /// Sets the cutoff which tells when the search must stop
+    pub fn with_cutoff<C>(mut self, cutoff: C) -> Self
+    where C: Cutoff + Send + Sync + 'a
+    {
+        self.cutoff = Box::new(cutoff);
+        self
+    }
+    /// Sets the order in which the subproblems are explored when no fringe is
+    /// given (best-first by default). The depth-first order uses a 
+    /// `StackFringe`, which bounds the memory of the search at the expense 
+    /// of the number of explored nodes. Either order yields the same optimum.
+    pub fn with_fringe_order(mut self, order: FringeOrder) -> Self {
+        self.order = order;
+        self
+    }
+    /// Sets the fringe which orders the subproblems to explore. This fringe
+    /// takes precedence over the fringe order.
+    pub fn with_fringe<F>(mut self, fringe: F) -> Self
+    where F: Fringe<State = State> + Send + Sync + 'a
+    {
+        self.fringe = Some(Box::new(fringe));
+        self
+    }
+    /// Sets the number of threads of the solver
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+    /// Tells whether the solver should use a cache to avoid exploring the 
+    /// same nodes several times
+    pub fn with_caching(mut self, caching: bool) -> Self {
+        self.caching = caching;
+        self
+    }
+    /// Reduces the problem with the given preprocessing: the solver then 
+    /// solves the reduced problem, and its `best_solution` is lifted back to
+    /// a solution of the original problem (see `SolutionLifter`).
+    ///
+    /// # Note
+    /// The relaxation, the ranking and the other parts of the builder are 
+    /// used with the reduced problem. Hence they must remain valid for that
+    /// problem (for instance, a relaxation which looks the variables up in 
+    /// the original problem must not be used with a preprocessing that 
+    /// renames them). Likewise, the solutions given to `set_primal` are
+    /// solutions of the reduced problem.
+    pub fn with_preprocessing<R>(mut self, preprocess: R) -> Self
+    where R: Preprocess<P>,
+          R::Reduced: Problem<State = State> + Send + Sync + 'a,
+          R::Lifter: 'a,
+    {
+        let (reduced, lifter) = preprocess.preprocess(self.problem);
+        self.reduced = Some(Box::new(reduced));
+        self.lifter = Some(Box::new(lifter));
+        self
+    }
+    /// Builds a solver out of the parts of this builder. 
+    ///
+    /// # Panics
+    /// When the relaxation or the ranking has not been set.
+    pub fn build(&mut self) -> Box<dyn Solver + '_> {
+        let relaxation = self.relaxation.expect("the relaxation of the problem must be set");
+        let ranking = self.ranking.expect("the ranking of the states must be set");
+        let problem: &(dyn Problem<State = State> + Send + Sync) = match self.reduced.as_deref() {
+            Some(reduced) => reduced,
+            None          => self.problem,
+        };
+        let width = &**self.width.get_or_insert_with(|| Box::new(NbUnassignedWidth(problem.nb_variables())));
+        let fringe: &mut (dyn Fringe<State = State> + Send + Sync + 'a) = match (self.fringe.as_deref_mut(), self.order) {
+            (Some(fringe), _)               => fringe,
+            (None, FringeOrder::BestFirst)  => self.best_first.as_deref_mut().expect("the fringe is set along with the ranking"),
+            (None, FringeOrder::DepthFirst) => &mut self.depth_first,
+        };
+        let solver: Box<dyn Solver + '_> = if self.caching {
+            Box::new(ParCachingSolverLel::custom(problem, relaxation, ranking, 
+                width, &*self.dominance, &*self.cutoff, fringe, self.threads))
+        } else {
+            Box::new(ParNoCachingSolverLel::custom(problem, relaxation, ranking, 
+                width, &*self.dominance, &*self.cutoff, fringe, self.threads))
+        };
+        match self.lifter.as_deref() {
+            Some(lifter) => Box::new(LiftedSolver { solver, lifter }),
+            None         => solver,
+        }
+    }
+}
+
+/// The solver built for a preprocessed problem: it lifts the best solution 
+/// of the reduced problem back to a solution of the original problem.
+struct LiftedSolver<'b> {
+    solver: Box<dyn Solver + 'b>,
+    lifter: &'b dyn SolutionLifter,
+}
+impl Solver for LiftedSolver<'_> {
+    fn maximize(&mut self) -> Completion {
+        self.solver.maximize()
+    }
+    fn best_value(&self) -> Option<isize> {
+        self.solver.best_value()
+    }
+    fn best_solution(&self) -> Option<Solution> {
+        self.solver.best_solution().map(|solution| self.lifter.lift(&solution))
+    }
+    fn best_lower_bound(&self) -> isize {
+        self.solver.best_lower_bound()
+    }
+    fn best_upper_bound(&self) -> isize {
+        self.solver.best_upper_bound()
+    }
+    fn set_primal(&mut self, value: isize, solution: Solution) {
+        self.solver.set_primal(value, solution)
+    }
+    fn set_primal_bound(&mut self, bound: isize) {
+        self.solver.set_primal_bound(bound)
+    }
+    fn reset(&mut self) {
+        self.solver.reset()
+    }
+    fn set_node_budget(&mut self, budget: Option<usize>) {
+        self.solver.set_node_budget(budget)
+    }
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.solver.set_time_limit(limit)
+    }
+    fn explored(&self) -> usize {
+        self.solver.explored()
+    }
+    fn statistics(&self) -> Option<Statistics> {
+        self.solver.statistics()
+    }
+}
+
+#[cfg(test)]
+mod test_builder {
+    use crate::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct KnapsackState {
+        depth: usize,
+        capacity: usize
+    }
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<usize>,
+        weight: Vec<usize>,
+    }
+
+    const TAKE_IT: isize = 1;
+    const LEAVE_IT_OUT: isize = 0;
+
+    impl Problem for Knapsack {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> Self::State {
+            KnapsackState{ depth: 0, capacity: self.capacity }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = *state;
+            ret.depth  += 1;
+            if dec.value == TAKE_IT { 
+                ret.capacity -= self.weight[dec.variable.id()] 
+            }
+            ret
+        }
+        fn transition_cost(&self, _state: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            self.profit[dec.variable.id()] as isize * dec.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            let n = self.nb_variables();
+            if depth < n {
+                Some(Variable(depth))
+            } else {
+                None
+            }
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback)
+        {
+            if state.capacity >= self.weight[variable.id()] {
+                f.apply(Decision { variable, value: TAKE_IT });
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            } else {
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            }
+        }
+    }
+    struct KPRelax<'a>{pb: &'a Knapsack}
+    impl Relaxation for KPRelax<'_> {
+        type State = KnapsackState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            states.max_by_key(|node| node.capacity).copied().unwrap()
+        }
+        fn relax(&self, _source: &Self::State, _dest: &Self::State, _merged: &Self::State, _decision: Decision, cost: isize) -> isize {
+            cost
+        }
+        fn fast_upper_bound(&self, state: &Self::State) -> isize {
+            let mut tot = 0;
+            for var in state.depth..self.pb.nb_variables() {
+                if self.pb.weight[var] <= state.capacity {
+                    tot += self.pb.profit[var];
+                }
+            }
+            tot as isize
+        }
+    }
+    struct KPRanking;
+    impl StateRanking for KPRanking {
+        type State = KnapsackState;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.capacity.cmp(&b.capacity)
+        }
+    }
+
+    fn knapsack() -> Knapsack {
+        Knapsack {
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        }
+    }
+
+    #[test]
+    fn the_defaults_yield_a_solver_which_finds_the_optimum() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let mut builder = SolverBuilder::for_problem(&problem)
+            .with_relaxation(&relax)
+            .with_ranking(&KPRanking);
+        let mut solver = builder.build();
+        let completion = solver.maximize();
+        assert!(completion.is_exact);
+        assert_eq!(Some(134), completion.best_value);
+    }
+    #[test]
+    fn each_part_can_be_replaced_on_its_own() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let mut builder = SolverBuilder::for_problem(&problem)
+            .with_relaxation(&relax)
+            .with_ranking(&ranking)
+            .with_width(FixedWidth(2))
+            .with_fringe(NoDupFringe::new(MaxUB::new(&ranking)))
+            .with_threads(2)
+            .with_caching(true);
+        let mut solver = builder.build();
+        assert_eq!(Some(134), solver.maximize().best_value);
+    }
+    #[test]
+    fn the_cutoff_of_the_builder_is_used_by_the_solver() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let mut builder = SolverBuilder::for_problem(&problem)
+            .with_relaxation(&relax)
+            .with_ranking(&KPRanking)
+            .with_width(FixedWidth(1))
+            .with_cutoff(CutoffFn::new(|| true));
+        let mut solver = builder.build();
+        let completion = solver.maximize();
+        assert!(!completion.is_exact);
+        assert_eq!(Some(Reason::CutoffOccurred), completion.stopped_by);
+    }
+    #[test]
+    fn both_fringe_orders_yield_the_same_optimum() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        for order in [FringeOrder::BestFirst, FringeOrder::DepthFirst] {
+            let mut builder = SolverBuilder::for_problem(&problem)
+                .with_relaxation(&relax)
+                .with_ranking(&KPRanking)
+                .with_width(FixedWidth(2))
+                .with_fringe_order(order)
+                .with_threads(2);
+            let mut solver = builder.build();
+            let completion = solver.maximize();
+            assert!(completion.is_exact);
+            assert_eq!(Some(134), completion.best_value);
+            assert_eq!(134, solver.best_upper_bound());
+        }
+    }
+    /// Removes the items which are heavier than the capacity of the sack and
+    /// forces them to be left out
+    struct DropHeavyItems;
+    impl Preprocess<Knapsack> for DropHeavyItems {
+        type Reduced = Knapsack;
+        type Lifter = ForcedDecisionsLifter;
+
+        fn preprocess(&self, problem: &Knapsack) -> (Knapsack, ForcedDecisionsLifter) {
+            let mut reduced = Knapsack { capacity: problem.capacity, profit: vec![], weight: vec![] };
+            let mut mapping = vec![];
+            let mut forced = vec![];
+            for (i, (profit, weight)) in problem.profit.iter().zip(problem.weight.iter()).enumerate() {
+                if *weight > problem.capacity {
+                    forced.push(Decision { variable: Variable(i), value: LEAVE_IT_OUT });
+                } else {
+                    mapping.push(Variable(i));
+                    reduced.profit.push(*profit);
+                    reduced.weight.push(*weight);
+                }
+            }
+            (reduced, ForcedDecisionsLifter::new(mapping, forced))
+        }
+    }
+    #[test]
+    fn the_best_solution_of_a_preprocessed_problem_is_lifted() {
+        let mut problem = knapsack();
+        // the heavy items come last so that the relaxation of the original 
+        // problem remains valid for the reduced one
+        problem.profit.extend([100, 90]);
+        problem.weight.extend([ 61, 75]);
+        let relax = KPRelax {pb: &problem};
+        let mut builder = SolverBuilder::for_problem(&problem)
+            .with_relaxation(&relax)
+            .with_ranking(&KPRanking)
+            .with_width(FixedWidth(2))
+            .with_threads(2)
+            .with_preprocessing(DropHeavyItems);
+        let mut solver = builder.build();
+        let completion = solver.maximize();
+        assert!(completion.is_exact);
+        assert_eq!(Some(134), completion.best_value);
+
+        let solution = solver.best_solution().unwrap();
+        assert_eq!(problem.nb_variables(), solution.len());
+        assert!(solution.iter().enumerate().all(|(i, d)| d.variable == Variable(i)));
+        assert_eq!(LEAVE_IT_OUT, solution[12].value);
+        assert_eq!(LEAVE_IT_OUT, solution[13].value);
+        let profit = solution.iter().map(|d| problem.profit[d.variable.id()] as isize * d.value).sum::<isize>();
+        assert_eq!(134, profit);
+    }
+    #[test]
+    #[should_panic]
+    fn the_relaxation_is_mandatory() {
+        let problem = knapsack();
+        let _ = SolverBuilder::for_problem(&problem).with_ranking(&KPRanking).build();
+    }
+}
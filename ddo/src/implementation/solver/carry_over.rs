@@ -0,0 +1,67 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the bookkeeping of the adaptive heuristics of the 
+//! solvers (see `with_carry_over`). The outcome of each compilation is handed
+//! over to these heuristics right after the incumbent has been updated.
+
+use crate::{CarryOver, CompilationOutcome, CompilationType, DecisionDiagram};
+
+/// The subproblem a DD has been compiled for, as far as the adaptive 
+/// heuristics are concerned
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CompiledNode {
+    /// The depth of the subproblem
+    pub depth: usize,
+    /// The number of decisions leading to the root of the DD
+    pub path_len: usize,
+    /// The upper bound of the subproblem before the compilation
+    pub ub: isize,
+}
+
+/// Hands the outcome of the compilation which was just performed with `mdd`
+/// over to the adaptive heuristic (if any)
+pub(crate) fn notify_carry_over<D: DecisionDiagram>(
+    carry_over: Option<&dyn CarryOver>,
+    mdd: &D,
+    comp_type: CompilationType,
+    node: CompiledNode,
+    is_exact: bool,
+) {
+    if let Some(carry_over) = carry_over {
+        let mut skipped = 0;
+        let mut best_path = vec![];
+        mdd.for_each_best_decision(&mut |decision| {
+            if skipped < node.path_len {
+                skipped += 1;
+            } else {
+                best_path.push(decision);
+            }
+        });
+        carry_over.carry_over(&CompilationOutcome {
+            comp_type,
+            depth: node.depth,
+            node_ub: node.ub,
+            best_value: mdd.best_value(),
+            is_exact,
+            best_path: &best_path,
+        });
+    }
+}
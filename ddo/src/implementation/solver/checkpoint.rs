@@ -0,0 +1,109 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the format of the checkpoints of the sequential 
+//! solver (feature `checkpoint`, see `SequentialSolver::checkpoint`). A 
+//! checkpoint holds the nodes which were left open when a search was stopped,
+//! its incumbent and its counters: enough to resume the search in another
+//! process. The states of the problem must implement `Serialize` and 
+//! `Deserialize` for that purpose.
+
+use std::io::{self, Read, Write};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::Decision;
+
+/// The first bytes of each checkpoint (the trailing digit is the version of
+/// the format)
+const MAGIC: &[u8; 8] = b"ddo-ckp1";
+
+/// An open node, as it is saved in a checkpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SavedNode<State> {
+    pub state: State,
+    pub lp_len: isize,
+    pub ub: isize,
+    pub path: Vec<Decision>,
+}
+
+/// The content of a checkpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Checkpoint<State> {
+    /// The nodes which remain to be explored
+    pub nodes: Vec<SavedNode<State>>,
+    /// The value of the incumbent (or the primal bound)
+    pub best_lb: isize,
+    /// The incumbent, if any
+    pub best_sol: Option<Vec<Decision>>,
+    /// The number of nodes which were explored
+    pub explored: usize,
+}
+impl<State: Serialize> Checkpoint<State> {
+    /// Writes the checkpoint to `out`
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        bincode::serialize_into(&mut *out, self).map_err(io::Error::other)?;
+        out.flush()
+    }
+}
+impl<State: DeserializeOwned> Checkpoint<State> {
+    /// Reads a checkpoint back from `input`. It fails with an `InvalidData`
+    /// error when `input` does not start with a checkpoint.
+    pub fn read(input: &mut impl Read) -> io::Result<Self> {
+        let mut magic = [0_u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "this is not a checkpoint of ddo"));
+        }
+        bincode::deserialize_from(input).map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod test_checkpoint {
+    use std::io;
+
+    use crate::{Decision, Variable};
+
+    use super::{Checkpoint, SavedNode};
+
+    #[test]
+    fn a_checkpoint_reads_back_as_it_was_written() {
+        let take = Decision { variable: Variable(0), value: 1 };
+        let checkpoint = Checkpoint {
+            nodes: vec![SavedNode { state: 42_usize, lp_len: 10, ub: 30, path: vec![take] }],
+            best_lb: 12,
+            best_sol: Some(vec![take]),
+            explored: 7,
+        };
+        let mut bytes = vec![];
+        checkpoint.write(&mut bytes).unwrap();
+
+        assert_eq!(checkpoint, Checkpoint::read(&mut bytes.as_slice()).unwrap());
+    }
+    #[test]
+    fn a_file_which_is_not_a_checkpoint_is_rejected() {
+        let bytes = b"definitely not a checkpoint";
+        let error = Checkpoint::<usize>::read(&mut bytes.as_slice()).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the closing attempt which may conclude a heuristic
+//! run: a time-boxed branch and bound which is warm started with the best 
+//! solution found by the heuristic. This way, the easy instances end up being
+//! proved optimal rather than merely "best found".
+
+use std::time::Duration;
+
+use crate::{Completion, Reason, Solution, Solver};
+
+/// The outcome of a closing attempt (see `finish_exact`)
+#[derive(Debug, Clone)]
+pub struct ClosingAttempt {
+    /// The completion of the branch and bound
+    pub completion: Completion,
+    /// The value of the incumbent which warm started the branch and bound
+    pub incumbent: Option<isize>,
+}
+impl ClosingAttempt {
+    /// Returns true iff the branch and bound proved the optimality of its
+    /// best solution
+    pub fn proved_optimality(&self) -> bool {
+        self.completion.is_exact
+    }
+    /// Returns true iff the branch and bound found a better solution than 
+    /// the incumbent
+    pub fn improved(&self) -> bool {
+        self.completion.best_value > self.incumbent
+    }
+    /// Returns true iff the branch and bound was stopped because its budget
+    /// was exhausted
+    pub fn ran_out_of_budget(&self) -> bool {
+        self.completion.stopped_by == Some(Reason::Timeout)
+    }
+}
+
+/// Spends at most `budget` on a branch and bound (carried out by `solver`) 
+/// which is warm started with the `incumbent` of a heuristic run. The solver
+/// is reset beforehand, and its time limit is lifted afterwards.
+///
+/// # Note
+/// The solver is used as is: the cutoff, node budget and fringe it was 
+/// created with remain in effect during the closing attempt.
+pub fn finish_exact(solver: &mut dyn Solver, incumbent: Option<(isize, Solution)>, budget: Duration) -> ClosingAttempt {
+    solver.reset();
+    let value = incumbent.as_ref().map(|(value, _)| *value);
+    if let Some((value, solution)) = incumbent {
+        solver.set_primal(value, solution);
+    }
+    solver.set_time_limit(Some(budget));
+    let completion = solver.maximize();
+    solver.set_time_limit(None);
+    ClosingAttempt { completion, incumbent: value }
+}
+
+#[cfg(test)]
+mod test_closing {
+    use std::time::Duration;
+
+    use crate::*;
+
+    fn knapsack() -> Knapsack {
+        Knapsack {
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        }
+    }
+    /// Takes the items in order for as long as they fit
+    fn greedy(problem: &Knapsack) -> (isize, Solution) {
+        let mut capacity = problem.capacity;
+        let mut value = 0;
+        let solution = (0..problem.nb_variables()).map(|i| {
+            let take = problem.weight[i] <= capacity;
+            if take {
+                capacity -= problem.weight[i];
+                value += problem.profit[i] as isize;
+            }
+            Decision { variable: Variable(i), value: isize::from(take) }
+        }).collect();
+        (value, solution)
+    }
+
+    #[test]
+    fn the_optimality_of_a_small_instance_is_proved_within_a_tiny_budget() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(2);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+        let incumbent = greedy(&problem);
+        assert_eq!(117, incumbent.0);
+        let attempt = finish_exact(&mut solver, Some(incumbent), Duration::from_secs(1));
+        assert!(attempt.proved_optimality());
+        assert!(attempt.improved());
+        assert!(!attempt.ran_out_of_budget());
+        assert_eq!(Some(117), attempt.incumbent);
+        assert_eq!(Some(134), attempt.completion.best_value);
+    }
+
+    #[test]
+    fn an_optimal_incumbent_is_proved_without_being_improved() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(2);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+        let optimum = finish_exact(&mut solver, None, Duration::from_secs(1));
+        assert!(optimum.proved_optimality());
+        let solution = solver.best_solution().unwrap();
+
+        let attempt = finish_exact(&mut solver, Some((134, solution)), Duration::from_secs(1));
+        assert!(attempt.proved_optimality());
+        assert!(!attempt.improved());
+        assert_eq!(Some(134), attempt.completion.best_value);
+    }
+
+    #[test]
+    fn the_incumbent_is_kept_when_the_budget_runs_out() {
+        let problem = knapsack();
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let dominance = EmptyDominanceChecker::default();
+        let width = FixedWidth(2);
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+
+        let attempt = finish_exact(&mut solver, Some(greedy(&problem)), Duration::ZERO);
+        assert!(!attempt.proved_optimality());
+        assert!(!attempt.improved());
+        assert!(attempt.ran_out_of_budget());
+        assert_eq!(Some(117), attempt.completion.best_value);
+
+        // the time limit is lifted afterwards
+        solver.reset();
+        assert!(solver.maximize().is_exact);
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct KnapsackState {
+        depth: usize,
+        capacity: usize
+    }
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<usize>,
+        weight: Vec<usize>,
+    }
+
+    const TAKE_IT: isize = 1;
+    const LEAVE_IT_OUT: isize = 0;
+
+    impl Problem for Knapsack {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> Self::State {
+            KnapsackState{ depth: 0, capacity: self.capacity }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = *state;
+            ret.depth  += 1;
+            if dec.value == TAKE_IT { 
+                ret.capacity -= self.weight[dec.variable.id()] 
+            }
+            ret
+        }
+        fn transition_cost(&self, _state: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            self.profit[dec.variable.id()] as isize * dec.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            let n = self.nb_variables();
+            if depth < n {
+                Some(Variable(depth))
+            } else {
+                None
+            }
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback)
+        {
+            if state.capacity >= self.weight[variable.id()] {
+                f.apply(Decision { variable, value: TAKE_IT });
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            } else {
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            }
+        }
+    }
+    struct KPRelax<'a>{pb: &'a Knapsack}
+    impl Relaxation for KPRelax<'_> {
+        type State = KnapsackState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            states.max_by_key(|node| node.capacity).copied().unwrap()
+        }
+        fn relax(&self, _source: &Self::State, _dest: &Self::State, _merged: &Self::State, _decision: Decision, cost: isize) -> isize {
+            cost
+        }
+        fn fast_upper_bound(&self, state: &Self::State) -> isize {
+            let mut tot = 0;
+            for var in state.depth..self.pb.nb_variables() {
+                if self.pb.weight[var] <= state.capacity {
+                    tot += self.pb.profit[var];
+                }
+            }
+            tot as isize
+        }
+    }
+    struct KPRanking;
+    impl StateRanking for KPRanking {
+        type State = KnapsackState;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.capacity.cmp(&b.capacity)
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the bookkeeping of the time limit of the solvers (see
+//! `set_time_limit`). The limit is checked between two nodes of the branch and
+//! bound, and it is folded into the cutoff of the compilations so that a single
+//! huge DD cannot blow past it. The same goes for the abort handle.
+
+use std::time::{Duration, Instant};
+
+use crate::{AbortHandle, Cutoff, Reason};
+
+/// The time limit of a solver and the deadline it implies for the current 
+/// run of `maximize`
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct TimeLimit {
+    /// How long `maximize` may run (None when there is no limit)
+    pub limit: Option<Duration>,
+    /// When the current run of `maximize` must stop
+    pub deadline: Option<Instant>,
+    /// When the current run of `maximize` started
+    pub started: Option<Instant>,
+}
+impl TimeLimit {
+    /// Starts the clock of a new run of `maximize`
+    pub fn start(&mut self) {
+        let now = Instant::now();
+        self.started = Some(now);
+        self.deadline = self.limit.and_then(|limit| now.checked_add(limit));
+    }
+    /// Returns the time spent since the current run of `maximize` started
+    pub fn elapsed(&self) -> Duration {
+        self.started.map(|started| started.elapsed()).unwrap_or_default()
+    }
+    /// Returns true iff the deadline has passed
+    pub fn is_over(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+    /// Tells the timeout apart from the other cutoffs: a compilation which 
+    /// was stopped once the deadline had passed was stopped because of it
+    /// (unless the user's cutoff kicked in as well).
+    pub fn explain(&self, reason: Reason, cutoff: &dyn Cutoff) -> Reason {
+        if reason == Reason::CutoffOccurred && self.is_over() && !cutoff.must_stop() {
+            Reason::Timeout
+        } else {
+            reason
+        }
+    }
+}
+
+/// The cutoff of the compilations: it stops when the user's cutoff says so,
+/// when the deadline has passed or when the solver was aborted
+pub(crate) struct Deadline<'a> {
+    /// The cutoff of the user
+    pub cutoff: &'a dyn Cutoff,
+    /// The time limit of the solver
+    pub limit: TimeLimit,
+    /// The abort handle of the solver
+    pub abort: AbortHandle,
+}
+impl Cutoff for Deadline<'_> {
+    fn must_stop(&self) -> bool {
+        self.cutoff.must_stop() || self.limit.is_over() || self.abort.is_aborted()
+    }
+}
+
+#[cfg(test)]
+mod test_deadline {
+    use crate::NoCutoff;
+
+    use super::*;
+
+    struct Always;
+    impl Cutoff for Always {
+        fn must_stop(&self) -> bool { true }
+    }
+
+    #[test]
+    fn without_a_limit_the_deadline_never_passes() {
+        let mut limit = TimeLimit::default();
+        limit.start();
+        assert!(!limit.is_over());
+        assert!(!Deadline { cutoff: &NoCutoff, limit, abort: AbortHandle::default() }.must_stop());
+        assert!(Deadline { cutoff: &Always, limit, abort: AbortHandle::default() }.must_stop());
+    }
+    #[test]
+    fn with_a_zero_limit_the_deadline_is_already_over() {
+        let mut limit = TimeLimit { limit: Some(Duration::ZERO), ..TimeLimit::default() };
+        assert!(!limit.is_over());
+        limit.start();
+        assert!(limit.is_over());
+        assert!(Deadline { cutoff: &NoCutoff, limit, abort: AbortHandle::default() }.must_stop());
+    }
+    #[test]
+    fn an_aborted_solver_stops_its_compilations() {
+        let mut limit = TimeLimit::default();
+        limit.start();
+        let abort = AbortHandle::default();
+        let deadline = Deadline { cutoff: &NoCutoff, limit, abort: abort.clone() };
+        assert!(!deadline.must_stop());
+        abort.abort();
+        assert!(deadline.must_stop());
+    }
+    #[test]
+    fn the_elapsed_time_is_measured_from_the_start() {
+        let mut limit = TimeLimit::default();
+        assert_eq!(Duration::ZERO, limit.elapsed());
+        limit.start();
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(limit.elapsed() >= Duration::from_millis(10));
+    }
+    #[test]
+    fn a_cutoff_is_explained_by_the_deadline_only_when_the_user_cutoff_did_not_kick_in() {
+        let mut limit = TimeLimit { limit: Some(Duration::ZERO), ..TimeLimit::default() };
+        assert_eq!(Reason::CutoffOccurred, limit.explain(Reason::CutoffOccurred, &NoCutoff));
+        limit.start();
+        assert_eq!(Reason::Timeout, limit.explain(Reason::CutoffOccurred, &NoCutoff));
+        assert_eq!(Reason::CutoffOccurred, limit.explain(Reason::CutoffOccurred, &Always));
+    }
+}
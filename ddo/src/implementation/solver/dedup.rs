@@ -0,0 +1,184 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the filter which keeps the solvers from feeding the
+//! same solution to the pool of diverse solutions over and over again (e.g.
+//! when the search is restarted, the same solutions are found once again).
+//!
+//! The filter remembers the fingerprint of the last `capacity` solutions it
+//! has seen. The fingerprint of a solution does not depend on the order of
+//! its decisions: two solutions making the same decisions in a different 
+//! order are thus understood as duplicates.
+
+use std::{collections::VecDeque, hash::{Hash, Hasher}};
+
+use fxhash::{FxHashSet, FxHasher};
+
+use crate::Decision;
+
+/// Returns the canonical form of the given solution: its decisions, sorted 
+/// by variable. The canonical forms of two solutions are also used to break 
+/// the ties between solutions of equal value.
+pub(crate) fn canonical_key(solution: &[Decision]) -> Vec<(usize, isize)> {
+    let mut key = solution.iter().map(|d| (d.variable.id(), d.value)).collect::<Vec<_>>();
+    key.sort_unstable();
+    key
+}
+
+/// Scrambles the bits of a hash (this is the finalizer of splitmix64). The
+/// hashes of the decisions are summed; without this, fxhash is linear enough 
+/// for different sets of decisions to sum up to the same value.
+fn mix(hash: u64) -> u64 {
+    let mut z = hash;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// The fingerprints of the (at most `capacity`) solutions which have been
+/// seen most recently
+#[derive(Debug, Clone)]
+pub(crate) struct SeenSolutions {
+    /// The maximum number of fingerprints which are remembered
+    capacity: usize,
+    /// The fingerprints which are remembered
+    seen: FxHashSet<u64>,
+    /// The fingerprints which are remembered, from the oldest to the newest
+    order: VecDeque<u64>,
+    /// The number of duplicate solutions which were suppressed
+    suppressed: usize,
+}
+
+impl SeenSolutions {
+    /// Creates a filter which remembers the last `capacity` solutions
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, seen: FxHashSet::default(), order: VecDeque::new(), suppressed: 0 }
+    }
+    /// Returns a fresh filter having the same capacity as this one
+    pub fn renewed(&self) -> Self {
+        Self::new(self.capacity)
+    }
+    /// Returns the number of duplicate solutions which were suppressed
+    pub fn suppressed(&self) -> usize {
+        self.suppressed
+    }
+    /// Returns true iff the given solution was not seen recently. When it was,
+    /// the solution is counted as a suppressed duplicate. Otherwise, it is 
+    /// remembered (and the oldest solution is forgotten if need be).
+    pub fn is_new(&mut self, solution: &[Decision]) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+        let fingerprint = Self::fingerprint(solution.iter().copied());
+        if self.seen.contains(&fingerprint) {
+            self.suppressed += 1;
+            return false;
+        }
+        if self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(fingerprint);
+        self.order.push_back(fingerprint);
+        true
+    }
+    /// Returns the fingerprint of the solution made of the given decisions. 
+    /// The decisions are hashed one at a time and their (mixed) hashes are 
+    /// summed, which makes the fingerprint independent of their order without having
+    /// to collect and sort them first.
+    fn fingerprint(decisions: impl Iterator<Item = Decision>) -> u64 {
+        let mut count = 0_u64;
+        let mut sum = 0_u64;
+        for d in decisions {
+            let mut hasher = FxHasher::default();
+            (d.variable.id(), d.value).hash(&mut hasher);
+            sum = sum.wrapping_add(mix(hasher.finish()));
+            count += 1;
+        }
+        let mut hasher = FxHasher::default();
+        (count, sum).hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod test_seen_solutions {
+    use crate::{Decision, Variable, Solution};
+    use super::*;
+
+    fn sol(values: &[isize]) -> Solution {
+        values.iter().enumerate().map(|(i, v)| Decision { variable: Variable(i), value: *v }).collect()
+    }
+
+    #[test]
+    fn a_solution_is_only_new_once() {
+        let mut seen = SeenSolutions::new(4);
+        assert!(seen.is_new(&sol(&[0, 1, 0])));
+        assert!(!seen.is_new(&sol(&[0, 1, 0])));
+        assert!(!seen.is_new(&sol(&[0, 1, 0])));
+        assert!(seen.is_new(&sol(&[1, 1, 0])));
+        assert_eq!(2, seen.suppressed());
+    }
+    #[test]
+    fn the_order_of_the_decisions_does_not_matter() {
+        let mut seen = SeenSolutions::new(4);
+        let mut reversed = sol(&[0, 1, 2]);
+        reversed.reverse();
+        assert!(seen.is_new(&sol(&[0, 1, 2])));
+        assert!(!seen.is_new(&reversed));
+    }
+    #[test]
+    fn the_fingerprint_ignores_the_order_of_the_decisions() {
+        let solution = sol(&[3, 1, 4, 1, 5]);
+        let mut shuffled = solution.clone();
+        shuffled.swap(0, 4);
+        shuffled.swap(1, 3);
+        assert_eq!(SeenSolutions::fingerprint(solution.iter().copied()), SeenSolutions::fingerprint(shuffled.iter().copied()));
+        assert_ne!(SeenSolutions::fingerprint(solution.iter().copied()), SeenSolutions::fingerprint(solution[1..].iter().copied()));
+        assert_ne!(SeenSolutions::fingerprint(sol(&[0, 1]).into_iter()), SeenSolutions::fingerprint(sol(&[1, 0]).into_iter()));
+    }
+    #[test]
+    fn the_oldest_solutions_are_forgotten_when_the_capacity_is_reached() {
+        let mut seen = SeenSolutions::new(2);
+        assert!(seen.is_new(&sol(&[0])));
+        assert!(seen.is_new(&sol(&[1])));
+        assert!(seen.is_new(&sol(&[2])));
+        assert!(seen.is_new(&sol(&[0])));
+        assert!(!seen.is_new(&sol(&[2])));
+    }
+    #[test]
+    fn a_zero_capacity_filters_nothing() {
+        let mut seen = SeenSolutions::new(0);
+        assert!(seen.is_new(&sol(&[0])));
+        assert!(seen.is_new(&sol(&[0])));
+        assert_eq!(0, seen.suppressed());
+    }
+    #[test]
+    fn a_renewed_filter_forgets_everything_but_its_capacity() {
+        let mut seen = SeenSolutions::new(1);
+        assert!(seen.is_new(&sol(&[0])));
+        assert!(!seen.is_new(&sol(&[0])));
+        let mut renewed = seen.renewed();
+        assert_eq!(0, renewed.suppressed());
+        assert!(renewed.is_new(&sol(&[0])));
+        assert!(renewed.is_new(&sol(&[1])));
+        assert!(!renewed.is_new(&sol(&[1])));
+    }
+}
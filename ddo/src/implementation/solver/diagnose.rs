@@ -0,0 +1,597 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides a health check of a user model (see `diagnose`). It 
+//! is meant to be run on a new model before trusting its answers: an 
+//! inconsistent model (transitions and costs which disagree, a domain or a 
+//! filter which misses values, a merge which is not admissible) would 
+//! otherwise only show up as wrong answers. The checks are performed as 
+//! follows.
+//!
+//! 1. Random dives (see `sample_solutions`) are replayed: each transition,
+//!    transition cost and domain must be the same when it is computed twice,
+//!    and the total cost of the dive must match the value of its solution
+//!    (see `evaluate`).
+//! 2. Tiny sub-instances, made of the first k variables of the problem only,
+//!    are solved twice: with an exact DD and by exhaustive enumeration. Both
+//!    must agree on the optimum.
+//! 3. Random groups of states reached at the same depth are merged. The
+//!    merged state must be admissible, which is checked against the best 
+//!    completions of the group members whenever these can be enumerated.
+//!
+//! Each failure is reported with the seed which reproduces it.
+
+use std::{fmt, hash::Hash, iter, marker::PhantomData, sync::Arc};
+
+use fxhash::FxHashSet;
+
+use crate::{evaluate, sample_solutions, seed_of_run, CompilationInput, CompilationType, Decision, DecisionCallback, DecisionDiagram, DefaultMDDLEL, EmptyCache, EmptyDominanceChecker, NoCutoff, Problem, ProblemCtx, Relaxation, StateRanking, SubProblem, ValueMask, Variable};
+
+/// The largest number of variables of the sub-instances which are solved by
+/// exhaustive enumeration, and of the completions which are enumerated to 
+/// check the merges
+const MAX_ENUMERATED_VARIABLES: usize = 10;
+/// The number of transitions an enumeration may perform before it gives up
+const ENUMERATION_BUDGET: usize = 1 << 16;
+
+/// A symptom of an inconsistent model (see `diagnose`). Each symptom comes 
+/// with the seed of the check which revealed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosisFailure {
+    /// Enumerating the domain of the variable twice in the state reached by 
+    /// the dive `sample_solutions(problem, 1, seed)` yielded different values
+    NonDeterministicDomain { seed: u64, depth: usize, variable: Variable },
+    /// Making the same decision twice from the state reached by the dive 
+    /// `sample_solutions(problem, 1, seed)` yielded different states
+    NonDeterministicTransition { seed: u64, depth: usize, variable: Variable },
+    /// Computing the cost of the same transition twice along the dive 
+    /// `sample_solutions(problem, 1, seed)` yielded different costs
+    NonDeterministicCost { seed: u64, depth: usize, variable: Variable, first: isize, second: isize },
+    /// The value of the dive `sample_solutions(problem, 1, seed)` does not 
+    /// match the value of its solution when it is evaluated (None when the
+    /// solution could not be evaluated at all)
+    InconsistentTotal { seed: u64, sampled: isize, replayed: Option<isize> },
+    /// The exact DD of the sub-instance made of the first `k` variables does
+    /// not agree with the exhaustive enumeration of that sub-instance (None
+    /// stands for an infeasible sub-instance)
+    ExactMismatch { seed: u64, k: usize, exact: Option<isize>, enumerated: Option<isize> },
+    /// Merging the states reached at the given depth by the dives of 
+    /// `sample_solutions(problem, n, seed)` yielded a state whose (relaxed)
+    /// value underestimates the one of a merged state. The bound is None 
+    /// when the merged state has no feasible completion at all.
+    InadmissibleMerge { seed: u64, depth: usize, bound: Option<isize>, exact: isize },
+}
+impl fmt::Display for DiagnosisFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let opt = |v: &Option<isize>| v.map(|v| v.to_string()).unwrap_or_else(|| "infeasible".to_string());
+        match self {
+            DiagnosisFailure::NonDeterministicDomain { seed, depth, variable } =>
+                write!(f, "seed {seed}: the domain of variable {} changes from one call to the next (depth {depth})", variable.id()),
+            DiagnosisFailure::NonDeterministicTransition { seed, depth, variable } =>
+                write!(f, "seed {seed}: the transition on variable {} changes from one call to the next (depth {depth})", variable.id()),
+            DiagnosisFailure::NonDeterministicCost { seed, depth, variable, first, second } =>
+                write!(f, "seed {seed}: the cost of the transition on variable {} is {first}, then {second} (depth {depth})", variable.id()),
+            DiagnosisFailure::InconsistentTotal { seed, sampled, replayed } =>
+                write!(f, "seed {seed}: a dive worth {sampled} is evaluated to {}", opt(replayed)),
+            DiagnosisFailure::ExactMismatch { seed, k, exact, enumerated } =>
+                write!(f, "seed {seed}: on the first {k} variables, the exact dd finds {} but the enumeration finds {}", opt(exact), opt(enumerated)),
+            DiagnosisFailure::InadmissibleMerge { seed, depth, bound, exact } =>
+                write!(f, "seed {seed}: a merge at depth {depth} bounds a value of {exact} by {}", opt(bound)),
+        }
+    }
+}
+
+/// The outcome of `diagnose`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosisReport {
+    /// The number of random dives which were replayed
+    pub dives: usize,
+    /// The number of sub-instances which were solved by enumeration
+    pub sub_instances: usize,
+    /// The number of merges whose admissibility was checked
+    pub merges: usize,
+    /// The symptoms of inconsistency, in the order they were found
+    pub failures: Vec<DiagnosisFailure>,
+}
+impl DiagnosisReport {
+    /// Returns true iff no check failed
+    pub fn is_healthy(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+impl fmt::Display for DiagnosisReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} dives, {} sub-instances and {} merges checked: ", 
+            self.dives, self.sub_instances, self.merges)?;
+        if self.is_healthy() {
+            write!(f, "no failure")
+        } else {
+            write!(f, "{} failures", self.failures.len())?;
+            for failure in self.failures.iter() {
+                write!(f, "\n  {failure}")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Runs the self-diagnostics of the given model (see the module doc). Each 
+/// kind of check is attempted `samples` times; the checks whose enumeration
+/// would be too large are skipped. The same `seed` always yields the same
+/// report.
+pub fn diagnose<State>(
+    problem: &dyn Problem<State = State>,
+    relaxation: &dyn Relaxation<State = State>,
+    samples: usize,
+    seed: u64,
+) -> DiagnosisReport
+where State: Eq + Hash + Clone
+{
+    let mut report = DiagnosisReport::default();
+    let seeds = || (0..samples).map(move |i| seed_of_run(seed, i));
+
+    for seed in seeds() {
+        match check_dive(problem, seed) {
+            Checked::Skipped      => {},
+            Checked::Passed       => report.dives += 1,
+            Checked::Failed(fail) => { report.dives += 1; report.failures.push(fail) },
+        }
+    }
+
+    let nb_vars = problem.nb_variables().min(MAX_ENUMERATED_VARIABLES);
+    let mut sizes = FxHashSet::default();
+    for seed in seeds().filter(|_| nb_vars > 0) {
+        let k = 1 + (seed % nb_vars as u64) as usize;
+        if !sizes.insert(k) {
+            continue;
+        }
+        match check_sub_instance(problem, relaxation, seed, k) {
+            Checked::Skipped      => {},
+            Checked::Passed       => report.sub_instances += 1,
+            Checked::Failed(fail) => { report.sub_instances += 1; report.failures.push(fail) },
+        }
+    }
+
+    for seed in seeds() {
+        match check_merge(problem, relaxation, seed) {
+            Checked::Skipped      => {},
+            Checked::Passed       => report.merges += 1,
+            Checked::Failed(fail) => { report.merges += 1; report.failures.push(fail) },
+        }
+    }
+
+    report
+}
+
+/// The outcome of one check
+enum Checked {
+    /// The check could not be carried out (e.g. the dive reached a dead end)
+    Skipped,
+    Passed,
+    Failed(DiagnosisFailure),
+}
+
+/// Replays the dive of the given seed, computing each domain, transition and
+/// transition cost twice
+fn check_dive<State>(problem: &dyn Problem<State = State>, seed: u64) -> Checked
+where State: Eq
+{
+    let Some(Ok(sample)) = sample_solutions(problem, 1, seed).pop() else { 
+        return Checked::Skipped;
+    };
+
+    let mut state = problem.initial_state();
+    for (depth, decision) in sample.solution.iter().copied().enumerate() {
+        let variable = decision.variable;
+        if domain(problem, &state, variable) != domain(problem, &state, variable) {
+            return Checked::Failed(DiagnosisFailure::NonDeterministicDomain { seed, depth, variable });
+        }
+        let next = problem.transition(&state, decision);
+        if next != problem.transition(&state, decision) {
+            return Checked::Failed(DiagnosisFailure::NonDeterministicTransition { seed, depth, variable });
+        }
+        let first  = problem.transition_cost(&state, &next, decision);
+        let second = problem.transition_cost(&state, &next, decision);
+        if first != second {
+            return Checked::Failed(DiagnosisFailure::NonDeterministicCost { seed, depth, variable, first, second });
+        }
+        state = next;
+    }
+
+    match evaluate(problem, &sample.solution) {
+        Ok(value) if value == sample.value => Checked::Passed,
+        replayed => Checked::Failed(DiagnosisFailure::InconsistentTotal { 
+            seed, sampled: sample.value, replayed: replayed.ok() }),
+    }
+}
+
+/// Solves the sub-instance made of the first `k` variables with an exact DD
+/// and by enumeration
+fn check_sub_instance<State>(
+    problem: &dyn Problem<State = State>,
+    relaxation: &dyn Relaxation<State = State>,
+    seed: u64,
+    k: usize,
+) -> Checked
+where State: Eq + Hash + Clone
+{
+    let sub = FirstVariables { problem, k };
+    let mut budget = ENUMERATION_BUDGET;
+    let Ok(best) = best_completion(&sub, &sub.initial_state(), 0, &mut budget) else {
+        return Checked::Skipped;
+    };
+    let enumerated = best.map(|v| sub.initial_value() + v);
+
+    let ranking = AnyOrder(PhantomData);
+    let cache = EmptyCache::default();
+    let dominance = EmptyDominanceChecker::default();
+    let input = CompilationInput {
+        comp_type: CompilationType::Exact,
+        problem: &sub,
+        relaxation,
+        ranking: &ranking,
+        cutoff: &NoCutoff,
+        max_width: usize::MAX,
+        best_lb: isize::MIN,
+        cache: &cache,
+        dominance: &dominance,
+    };
+    let root = SubProblem {
+        state: Arc::new(sub.initial_state()),
+        value: sub.initial_value(),
+        path: vec![],
+        ub: isize::MAX,
+        depth: 0,
+        priority_bonus: 0,
+    };
+    let mut mdd = DefaultMDDLEL::default();
+    if mdd.compile(&input, root).is_err() {
+        return Checked::Skipped;
+    }
+    let exact = mdd.best_value();
+
+    if exact == enumerated {
+        Checked::Passed
+    } else {
+        Checked::Failed(DiagnosisFailure::ExactMismatch { seed, k, exact, enumerated })
+    }
+}
+
+/// Merges the states reached at some depth by a few dives, and checks that
+/// the merged state is admissible for each of them. That is, for each dive
+/// going from `parent` to `state` with a transition of the given `cost`, 
+/// `relax(parent, state, merged, decision, cost) + v*(merged)` may not be 
+/// smaller than `cost + v*(state)` where `v*` is the best completion of a 
+/// state.
+fn check_merge<State>(
+    problem: &dyn Problem<State = State>,
+    relaxation: &dyn Relaxation<State = State>,
+    seed: u64,
+) -> Checked
+where State: Eq
+{
+    let nb_vars = problem.nb_variables();
+    if nb_vars < 2 {
+        return Checked::Skipped;
+    }
+    let remaining = 1 + (seed % (nb_vars - 1).min(MAX_ENUMERATED_VARIABLES) as u64) as usize;
+    let depth = nb_vars - remaining;
+    let group = 2 + ((seed >> 32) % 3) as usize;
+
+    // the arcs which lead to the states being merged
+    let mut arcs = vec![];
+    for sample in sample_solutions(problem, group, seed).into_iter().flatten() {
+        if sample.solution.len() < depth {
+            continue;
+        }
+        let mut parent = problem.initial_state();
+        for decision in sample.solution[..depth - 1].iter().copied() {
+            parent = problem.transition(&parent, decision);
+        }
+        let decision = sample.solution[depth - 1];
+        let state = problem.transition(&parent, decision);
+        let cost = problem.transition_cost(&parent, &state, decision);
+        arcs.push((parent, decision, state, cost));
+    }
+    let mut states: Vec<&State> = vec![];
+    for (_, _, state, _) in arcs.iter() {
+        if !states.contains(&state) {
+            states.push(state);
+        }
+    }
+    if states.len() < 2 {
+        return Checked::Skipped;
+    }
+    let merged = relaxation.merge(&mut states.iter().copied());
+
+    let mut budget = ENUMERATION_BUDGET;
+    let Ok(merged_value) = best_completion(problem, &merged, depth, &mut budget) else {
+        return Checked::Skipped;
+    };
+    for (parent, decision, state, cost) in arcs.iter() {
+        let Ok(value) = best_completion(problem, state, depth, &mut budget) else {
+            return Checked::Skipped;
+        };
+        let Some(value) = value else { continue };
+        let exact = cost + value;
+        let bound = merged_value.map(|v| relaxation.relax(parent, state, &merged, *decision, *cost) + v);
+        if bound.is_none_or(|bound| bound < exact) {
+            return Checked::Failed(DiagnosisFailure::InadmissibleMerge { seed, depth, bound, exact });
+        }
+    }
+    Checked::Passed
+}
+
+/// Tells that an enumeration exhausted its budget
+struct Exhausted;
+
+/// Returns the value of the best completion of the given state (None when it
+/// has no feasible completion) by enumerating all of them
+fn best_completion<P>(problem: &P, state: &P::State, depth: usize, budget: &mut usize) -> Result<Option<isize>, Exhausted>
+    where P: Problem + ?Sized
+{
+    let Some(variable) = problem.next_variable(depth, &mut iter::once(state)) else {
+        return Ok(Some(0));
+    };
+    let mask = problem.filter_layer(variable, &mut iter::once(state));
+    let mut decisions = vec![];
+    problem.for_each_in_domain(variable, state, &mut |d: Decision| {
+        if mask.allows(d.value) {
+            decisions.push(d);
+        }
+    });
+
+    let mut best = None;
+    for decision in decisions {
+        if *budget == 0 {
+            return Err(Exhausted);
+        }
+        *budget -= 1;
+        let next = problem.transition(state, decision);
+        let cost = problem.transition_cost(state, &next, decision);
+        if let Some(value) = best_completion(problem, &next, depth + 1, budget)? {
+            best = best.max(Some(cost + value));
+        }
+    }
+    Ok(best)
+}
+
+/// Returns the values in the domain of the given variable
+fn domain<State>(problem: &dyn Problem<State = State>, state: &State, variable: Variable) -> Vec<isize> {
+    let mut values = vec![];
+    problem.for_each_in_domain(variable, state, &mut |d: Decision| values.push(d.value));
+    values
+}
+
+/// The sub-instance made of the first `k` variables of a problem. The 
+/// objective depth and the feasibility classes of the problem are not 
+/// forwarded: they need not hold once the problem is truncated.
+struct FirstVariables<'a, State> {
+    problem: &'a dyn Problem<State = State>,
+    k: usize,
+}
+impl<State> Problem for FirstVariables<'_, State> {
+    type State = State;
+
+    fn nb_variables(&self) -> usize {
+        self.k
+    }
+    fn initial_state(&self) -> Self::State {
+        self.problem.initial_state()
+    }
+    fn initial_value(&self) -> isize {
+        self.problem.initial_value()
+    }
+    fn transition(&self, state: &Self::State, decision: Decision) -> Self::State {
+        self.problem.transition(state, decision)
+    }
+    fn transition_cost(&self, source: &Self::State, dest: &Self::State, decision: Decision) -> isize {
+        self.problem.transition_cost(source, dest, decision)
+    }
+    fn transition_with_ctx(&self, state: &Self::State, decision: Decision, ctx: &mut ProblemCtx) -> Self::State {
+        self.problem.transition_with_ctx(state, decision, ctx)
+    }
+    fn transition_cost_with_ctx(&self, source: &Self::State, dest: &Self::State, decision: Decision, ctx: &mut ProblemCtx) -> isize {
+        self.problem.transition_cost_with_ctx(source, dest, decision, ctx)
+    }
+    fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+        if depth < self.k {
+            self.problem.next_variable(depth, next_layer)
+        } else {
+            None
+        }
+    }
+    fn for_each_in_domain(&self, var: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+        self.problem.for_each_in_domain(var, state, f)
+    }
+    fn is_impacted_by(&self, var: Variable, state: &Self::State) -> bool {
+        self.problem.is_impacted_by(var, state)
+    }
+    fn quick_neq(&self, a: &Self::State, b: &Self::State) -> bool {
+        self.problem.quick_neq(a, b)
+    }
+    fn filter_layer(&self, var: Variable, states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+        self.problem.filter_layer(var, states)
+    }
+    fn domain_is_state_independent(&self, var: Variable) -> bool {
+        self.problem.domain_is_state_independent(var)
+    }
+    fn dump_state(&self, state: &Self::State) -> Option<String> {
+        self.problem.dump_state(state)
+    }
+}
+
+/// A ranking which never prefers any state: an exact DD does not need one
+struct AnyOrder<State>(PhantomData<State>);
+impl<State> StateRanking for AnyOrder<State> {
+    type State = State;
+
+    fn compare(&self, _: &Self::State, _: &Self::State) -> std::cmp::Ordering {
+        std::cmp::Ordering::Equal
+    }
+}
+
+#[cfg(test)]
+mod test_diagnose {
+    use std::cell::Cell;
+
+    use crate::*;
+
+    const TAKE_IT: isize = 1;
+    const LEAVE_IT_OUT: isize = 0;
+
+    /// The ways in which the knapsack model may be broken
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Fault {
+        Sound,
+        /// Every other transition cost is off by one
+        FlakyCost,
+        /// Taking an item is forbidden as soon as the layer has several states
+        GreedyFilter,
+        /// The merge keeps the smallest capacity rather than the largest one
+        TightMerge,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct KnapsackState {
+        depth: usize,
+        capacity: usize,
+    }
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<usize>,
+        weight: Vec<usize>,
+        fault: Fault,
+        calls: Cell<usize>,
+    }
+    impl Knapsack {
+        fn new(fault: Fault) -> Self {
+            Knapsack {
+                capacity: 30,
+                profit  : vec![10, 24, 31, 12, 40, 22, 17, 29],
+                weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13],
+                fault,
+                calls: Cell::new(0),
+            }
+        }
+    }
+    impl Problem for Knapsack {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> Self::State {
+            KnapsackState { depth: 0, capacity: self.capacity }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = *state;
+            ret.depth += 1;
+            if dec.value == TAKE_IT {
+                ret.capacity -= self.weight[dec.variable.id()]
+            }
+            ret
+        }
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            let cost = self.profit[dec.variable.id()] as isize * dec.value;
+            if self.fault == Fault::FlakyCost {
+                self.calls.set(self.calls.get() + 1);
+                cost + (self.calls.get() % 2) as isize
+            } else {
+                cost
+            }
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            (depth < self.nb_variables()).then_some(Variable(depth))
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            if state.capacity >= self.weight[variable.id()] {
+                f.apply(Decision { variable, value: TAKE_IT });
+            }
+            f.apply(Decision { variable, value: LEAVE_IT_OUT });
+        }
+        fn filter_layer(&self, _: Variable, states: &mut dyn Iterator<Item = &Self::State>) -> ValueMask {
+            let mut mask = ValueMask::all();
+            if self.fault == Fault::GreedyFilter && states.count() > 1 {
+                mask.skip(TAKE_IT);
+            }
+            mask
+        }
+    }
+    struct KPRelax<'a> {
+        pb: &'a Knapsack,
+    }
+    impl Relaxation for KPRelax<'_> {
+        type State = KnapsackState;
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            if self.pb.fault == Fault::TightMerge {
+                *states.min_by_key(|s| s.capacity).unwrap()
+            } else {
+                *states.max_by_key(|s| s.capacity).unwrap()
+            }
+        }
+        fn relax(&self, _: &Self::State, _: &Self::State, _: &Self::State, _: Decision, cost: isize) -> isize {
+            cost
+        }
+    }
+
+    #[test]
+    fn a_consistent_model_is_healthy() {
+        let pb = Knapsack::new(Fault::Sound);
+        let report = diagnose(&pb, &KPRelax { pb: &pb }, 50, 0);
+
+        assert!(report.is_healthy(), "{report}");
+        assert_eq!(50, report.dives);
+        assert!(report.sub_instances > 0);
+        assert!(report.merges > 0);
+    }
+    #[test]
+    fn a_flaky_cost_is_caught_by_the_dives() {
+        let pb = Knapsack::new(Fault::FlakyCost);
+        let report = diagnose(&pb, &KPRelax { pb: &pb }, 50, 0);
+
+        assert!(matches!(report.failures.first(), Some(DiagnosisFailure::NonDeterministicCost { depth: 0, .. })));
+    }
+    #[test]
+    fn a_filter_which_misses_values_is_caught_by_the_sub_instances() {
+        let pb = Knapsack::new(Fault::GreedyFilter);
+        let report = diagnose(&pb, &KPRelax { pb: &pb }, 50, 0);
+
+        assert!(!report.is_healthy());
+        assert!(report.failures.iter().all(|f| matches!(f, DiagnosisFailure::ExactMismatch { .. })));
+        // the dd drops the solutions which take an item past the first one
+        assert!(report.failures.iter().all(|f| match f {
+            DiagnosisFailure::ExactMismatch { k, exact, enumerated, .. } => *k > 1 && exact < enumerated,
+            _ => false,
+        }));
+    }
+    #[test]
+    fn an_inadmissible_merge_is_caught_with_its_seed() {
+        let pb = Knapsack::new(Fault::TightMerge);
+        let report = diagnose(&pb, &KPRelax { pb: &pb }, 100, 7);
+
+        assert!(!report.is_healthy());
+        assert!(report.failures.iter().all(|f| matches!(f, DiagnosisFailure::InadmissibleMerge { .. })));
+        // the same seed yields the same report
+        assert_eq!(report, diagnose(&pb, &KPRelax { pb: &pb }, 100, 7));
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//! This module provides the *diversity* mode which can optionally be used by
+//! the solvers. Besides the best solution, the solver then maintains a small
+//! pool of good solutions which are pairwise different from one another: any 
+//! two of them assign a different value to at least `d` variables.
+//!
+//! # Note
+//! The pool is fed with the best exact solution of each decision diagram that
+//! is compiled during the search. The pruning of the search still only relies
+//! on the value of the best solution: optimality is thus unaffected, but the
+//! other solutions of the pool can only come from those parts of the search
+//! space which were not pruned.
+
+use std::cmp::{Ordering, Reverse};
+
+use crate::Solution;
+
+/// This structure configures the pool of diverse solutions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Diversity {
+    /// The maximum number of solutions in the pool
+    pub k: usize,
+    /// The minimum number of variables to which any two solutions of the pool
+    /// assign a different value
+    pub d: usize,
+}
+
+/// One of the solutions from the pool of diverse solutions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiverseSolution {
+    /// The objective value of the solution
+    pub value: isize,
+    /// The decisions of the solution (sorted by variable)
+    pub solution: Solution,
+}
+
+/// The pool of (at most `k`) diverse solutions. It maintains the invariant
+/// that the Hamming distance between any two solutions of the pool is at 
+/// least `d`.
+pub(crate) struct DiversityPool {
+    pub(crate) policy: Diversity,
+    solutions: Vec<DiverseSolution>,
+}
+impl DiversityPool {
+    pub(crate) fn new(policy: Diversity) -> Self {
+        Self { policy, solutions: vec![] }
+    }
+    /// Returns the solutions of the pool, by decreasing value
+    pub(crate) fn solutions(&self) -> Vec<DiverseSolution> {
+        let mut solutions = self.solutions.clone();
+        solutions.sort_by_key(|s| Reverse(s.value));
+        solutions
+    }
+    /// Offers a candidate solution to the pool. When the candidate is at least 
+    /// `d` decisions away from all the solutions of the pool, it is either 
+    /// added to the pool or it replaces the worst solution (if it is better). 
+    /// Otherwise, it replaces the solutions it is too close to (the closest 
+    /// one, typically), provided that it is strictly better than all of them.
+    ///
+    /// This method returns true iff the candidate was added to the pool.
+    pub(crate) fn offer(&mut self, value: isize, mut solution: Solution) -> bool {
+        if self.policy.k == 0 {
+            return false;
+        }
+        solution.sort_unstable_by_key(|d| d.variable.id());
+        let d = self.policy.d;
+        let too_close = self.solutions.iter()
+            .map(|s| hamming_distance(&s.solution, &solution) < d)
+            .collect::<Vec<_>>();
+
+        if too_close.iter().any(|c| *c) {
+            let dominated = self.solutions.iter().zip(too_close.iter())
+                .filter(|(_, close)| **close)
+                .all(|(s, _)| s.value < value);
+            if !dominated {
+                return false;
+            }
+            let mut close = too_close.into_iter();
+            self.solutions.retain(|_| !close.next().unwrap_or(false));
+        } else if self.solutions.len() >= self.policy.k {
+            let worst = (0..self.solutions.len())
+                .min_by_key(|i| self.solutions[*i].value)
+                .filter(|i| self.solutions[*i].value < value);
+            match worst {
+                Some(i) => { self.solutions.swap_remove(i); },
+                None    => return false,
+            }
+        }
+        self.solutions.push(DiverseSolution { value, solution });
+        true
+    }
+}
+
+/// Returns the number of variables to which the two (sorted) solutions assign 
+/// a different value. A variable which is only assigned in one of the two 
+/// solutions counts as a difference.
+pub(crate) fn hamming_distance(a: &Solution, b: &Solution) -> usize {
+    let (mut i, mut j, mut distance) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].variable.cmp(&b[j].variable) {
+            Ordering::Less    => { distance += 1; i += 1; },
+            Ordering::Greater => { distance += 1; j += 1; },
+            Ordering::Equal   => {
+                if a[i].value != b[j].value {
+                    distance += 1;
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    distance + (a.len() - i) + (b.len() - j)
+}
+
+#[cfg(test)]
+mod test_diversity {
+    use crate::{Decision, Variable, Solution};
+    use super::*;
+
+    fn sol(values: &[isize]) -> Solution {
+        values.iter().enumerate().map(|(i, v)| Decision { variable: Variable(i), value: *v }).collect()
+    }
+
+    #[test]
+    fn hamming_distance_counts_the_differing_assignments() {
+        assert_eq!(0, hamming_distance(&sol(&[0, 1, 0]), &sol(&[0, 1, 0])));
+        assert_eq!(2, hamming_distance(&sol(&[0, 1, 0]), &sol(&[1, 1, 1])));
+        assert_eq!(1, hamming_distance(&sol(&[0, 1, 0]), &sol(&[0, 1])));
+        assert_eq!(3, hamming_distance(&sol(&[]), &sol(&[0, 1, 0])));
+    }
+    #[test]
+    fn candidates_are_sorted_by_variable() {
+        let mut pool = DiversityPool::new(Diversity { k: 2, d: 1 });
+        let mut reversed = sol(&[1, 2, 3]);
+        reversed.reverse();
+        assert!(pool.offer(5, reversed));
+        assert_eq!(sol(&[1, 2, 3]), pool.solutions()[0].solution);
+    }
+    #[test]
+    fn distant_candidates_fill_the_pool_then_replace_the_worst() {
+        let mut pool = DiversityPool::new(Diversity { k: 2, d: 2 });
+        assert!(pool.offer(5, sol(&[0, 0, 0])));
+        assert!(pool.offer(3, sol(&[1, 1, 0])));
+        // the pool is full and the candidate is no better than the worst one
+        assert!(!pool.offer(3, sol(&[0, 1, 1])));
+        assert!(pool.offer(4, sol(&[0, 1, 1])));
+        assert_eq!(vec![5, 4], pool.solutions().iter().map(|s| s.value).collect::<Vec<_>>());
+    }
+    #[test]
+    fn close_candidates_only_replace_the_closest_solution_when_strictly_better() {
+        let mut pool = DiversityPool::new(Diversity { k: 3, d: 2 });
+        assert!(pool.offer(5, sol(&[0, 0, 0])));
+        assert!(!pool.offer(5, sol(&[0, 0, 1])));
+        assert!(!pool.offer(4, sol(&[0, 0, 1])));
+        assert!(pool.offer(6, sol(&[0, 0, 1])));
+        assert_eq!(vec![DiverseSolution { value: 6, solution: sol(&[0, 0, 1]) }], pool.solutions());
+    }
+    #[test]
+    fn a_candidate_close_to_several_solutions_must_beat_all_of_them() {
+        let mut pool = DiversityPool::new(Diversity { k: 3, d: 2 });
+        assert!(pool.offer(5, sol(&[0, 0, 0, 0])));
+        assert!(pool.offer(7, sol(&[1, 1, 0, 0])));
+        // at distance 1 of both solutions
+        assert!(!pool.offer(6, sol(&[1, 0, 0, 0])));
+        assert!(pool.offer(8, sol(&[1, 0, 0, 0])));
+        assert_eq!(vec![DiverseSolution { value: 8, solution: sol(&[1, 0, 0, 0]) }], pool.solutions());
+    }
+    #[test]
+    fn an_empty_pool_accepts_nothing() {
+        let mut pool = DiversityPool::new(Diversity { k: 0, d: 2 });
+        assert!(!pool.offer(5, sol(&[0, 0, 0])));
+        assert!(pool.solutions().is_empty());
+    }
+}
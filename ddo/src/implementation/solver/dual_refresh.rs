@@ -0,0 +1,447 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the *dual refresh* policy which can optionally be 
+//! used by the solvers. Without it, the best upper bound only tightens as the
+//! fringe drains; which can be slow. When the dual refresh is enabled, the 
+//! solver periodically recompiles a relaxed DD rooted in the initial state 
+//! of the problem, albeit with a larger maximum width. Because that DD is
+//! wider, it often yields a much tighter bound than the one derived at the
+//! beginning of the search.
+
+use std::{sync::Arc, time::{Duration, Instant}};
+
+use crate::*;
+
+/// This structure configures when and how the solver should refresh its dual
+/// bound (best upper bound) by recompiling a wider relaxation of the root 
+/// problem.
+///
+/// The refresh is triggered each time the number of explored nodes has grown
+/// by a factor `explored_factor` since the previous refresh (that is, after 
+/// 10, 100, 1000, ... explored nodes with the default settings). Optionally,
+/// it can also be triggered whenever `interval` has elapsed since the previous
+/// refresh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DualRefresh {
+    /// The width of the root relaxation is the one returned by the width 
+    /// heuristic for the root node, multiplied by this factor.
+    pub width_factor: usize,
+    /// A refresh is triggered when the number of explored nodes has been
+    /// multiplied by this factor since the last refresh.
+    pub explored_factor: usize,
+    /// If set, a refresh is also triggered when that much time has elapsed
+    /// since the last refresh.
+    pub interval: Option<Duration>,
+    /// The maximum amount of time that can be spent compiling one wider root
+    /// relaxation. Whenever that budget is exhausted, the refresh is abandoned.
+    pub budget: Duration,
+}
+impl Default for DualRefresh {
+    fn default() -> Self {
+        Self {
+            width_factor: 4,
+            explored_factor: 10,
+            interval: None,
+            budget: Duration::from_secs(10),
+        }
+    }
+}
+
+/// This records the outcome of one refresh of the dual bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DualRefreshRecord {
+    /// The number of nodes that had been explored when the refresh occurred
+    pub explored: usize,
+    /// The time it took to compile the wider root relaxation
+    pub elapsed: Duration,
+    /// The best upper bound before the refresh
+    pub ub_before: isize,
+    /// The best upper bound after the refresh (or None when the refresh was
+    /// abandoned because its time budget was exhausted)
+    pub ub_after: Option<isize>,
+}
+
+/// The bookkeeping that is required to know when the next refresh is due and
+/// to keep track of the refreshes which already happened.
+pub(crate) struct DualRefreshSchedule {
+    pub(crate) policy: DualRefresh,
+    /// The number of explored nodes that will trigger the next refresh
+    next_explored: usize,
+    /// The moment when the last refresh occurred
+    last: Instant,
+    /// The outcome of all the refreshes which already occurred
+    pub(crate) records: Vec<DualRefreshRecord>,
+}
+impl DualRefreshSchedule {
+    pub(crate) fn new(policy: DualRefresh) -> Self {
+        Self {
+            policy,
+            next_explored: policy.explored_factor.max(1),
+            last: Instant::now(),
+            records: vec![],
+        }
+    }
+    /// Returns true iff a refresh must occur now. When that is the case, the 
+    /// next refresh is scheduled.
+    pub(crate) fn is_due(&mut self, explored: usize) -> bool {
+        let by_nodes = explored >= self.next_explored;
+        let by_time  = self.policy.interval.map(|i| self.last.elapsed() >= i).unwrap_or(false);
+        if by_nodes || by_time {
+            let factor = self.policy.explored_factor.max(2);
+            while self.next_explored <= explored {
+                self.next_explored = self.next_explored.saturating_mul(factor);
+            }
+            self.last = Instant::now();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A cutoff which stops the compilation of the root relaxation either when
+/// the solver cutoff says so, or when the refresh budget is exhausted.
+struct RefreshCutoff<'a> {
+    outer: &'a dyn Cutoff,
+    deadline: Instant,
+}
+impl Cutoff for RefreshCutoff<'_> {
+    fn must_stop(&self) -> bool {
+        self.outer.must_stop() || Instant::now() >= self.deadline
+    }
+}
+
+/// Compiles a relaxed DD rooted in the initial state of the problem and 
+/// returns the upper bound it yields (None when the budget got exhausted).
+///
+/// # Note
+/// The compilation neither uses the cache nor the dominance checker of the 
+/// solver: these hold information about the subproblems of the ongoing search
+/// which would make the bound invalid for the root problem.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn compile_root_relaxation<State, D>(
+    mdd: &mut D,
+    problem: &dyn Problem<State = State>,
+    relaxation: &dyn Relaxation<State = State>,
+    ranking: &dyn StateRanking<State = State>,
+    width_heu: &dyn WidthHeuristic<State>,
+    cutoff: &dyn Cutoff,
+    policy: &DualRefresh,
+    best_lb: isize,
+) -> Option<isize> 
+where D: DecisionDiagram<State = State>
+{
+    let root = SubProblem {
+        state: Arc::new(problem.initial_state()),
+        value: problem.initial_value(),
+        path: vec![],
+        ub: isize::MAX,
+        depth: 0,
+        priority_bonus: 0,
+    };
+    let cache = EmptyCache::default();
+    let dominance = EmptyDominanceChecker::default();
+    let cutoff = RefreshCutoff { outer: cutoff, deadline: Instant::now() + policy.budget };
+    let compilation = CompilationInput {
+        comp_type: CompilationType::Relaxed,
+        max_width: width_heu.max_width(&root).saturating_mul(policy.width_factor.max(1)),
+        problem,
+        relaxation,
+        ranking,
+        cutoff: &cutoff,
+        best_lb,
+        cache: &cache,
+        dominance: &dominance,
+    };
+
+    mdd.compile(&compilation, root).ok()?;
+    Some(mdd.best_value().unwrap_or(best_lb).max(best_lb))
+}
+
+/// Tightens the upper bound of all the nodes in the fringe given that no 
+/// solution can be better than `ub` and drops those which can no longer 
+/// improve on `best_lb`. The `open_by_layer` counters are updated accordingly.
+pub(crate) fn sweep_fringe<State>(
+    fringe: &mut dyn Fringe<State = State>, 
+    open_by_layer: &mut [usize],
+    ub: isize,
+    best_lb: isize,
+) {
+    let mut kept = Vec::with_capacity(fringe.len());
+    while let Some(mut node) = fringe.pop() {
+        node.ub = node.ub.min(ub);
+        if node.ub > best_lb {
+            kept.push(node);
+        } else {
+            open_by_layer[node.depth] -= 1;
+        }
+    }
+    for node in kept {
+        fringe.push(node);
+    }
+}
+
+#[cfg(test)]
+mod test_dual_refresh {
+    use std::{sync::Arc, time::Duration};
+
+    use crate::*;
+    use super::{DualRefreshSchedule, sweep_fringe};
+
+    #[test]
+    fn by_default_refreshes_are_due_after_each_tenfold_increase_of_explored_nodes() {
+        let mut schedule = DualRefreshSchedule::new(DualRefresh::default());
+        assert!(!schedule.is_due(0));
+        assert!(!schedule.is_due(9));
+        assert!(schedule.is_due(10));
+        assert!(!schedule.is_due(11));
+        assert!(!schedule.is_due(99));
+        assert!(schedule.is_due(100));
+        assert!(schedule.is_due(1000));
+    }
+    #[test]
+    fn a_jump_in_explored_nodes_only_triggers_one_refresh() {
+        let mut schedule = DualRefreshSchedule::new(DualRefresh::default());
+        assert!(schedule.is_due(5000));
+        assert!(!schedule.is_due(5001));
+        assert!(!schedule.is_due(9999));
+        assert!(schedule.is_due(10000));
+    }
+    #[test]
+    fn refreshes_can_be_triggered_by_time() {
+        let policy = DualRefresh { interval: Some(Duration::ZERO), explored_factor: 1000, ..DualRefresh::default() };
+        let mut schedule = DualRefreshSchedule::new(policy);
+        assert!(schedule.is_due(0));
+        assert!(schedule.is_due(1));
+    }
+    #[test]
+    fn sweeping_the_fringe_tightens_the_bounds_and_drops_useless_nodes() {
+        let mut fringe = SimpleFringe::new(MaxUB::new(&UsizeRanking));
+        let mut open_by_layer = vec![0, 0, 0];
+        for (state, ub, depth) in [(1, 100, 1), (2, 50, 2), (3, 20, 2)] {
+            fringe.push(SubProblem { state: Arc::new(state), value: 0, path: vec![], ub, depth, priority_bonus: 0 });
+            open_by_layer[depth] += 1;
+        }
+        
+        sweep_fringe(&mut fringe, &mut open_by_layer, 60, 30);
+        assert_eq!(2, fringe.len());
+        assert_eq!(vec![0, 1, 1], open_by_layer);
+        assert_eq!(60, fringe.pop().unwrap().ub);
+        assert_eq!(50, fringe.pop().unwrap().ub);
+    }
+
+    #[test]
+    fn a_search_driven_step_by_step_never_loosens_its_refreshed_dual_bound() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let policy = DualRefresh { explored_factor: 2, ..DualRefresh::default() };
+
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        let expected = solver.maximize();
+        drop(solver);
+
+        // the cache relies on the count of open nodes per layer, which the 
+        // sweeps must keep up to date
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqCachingSolverLel::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe)
+            .with_dual_refresh(policy);
+        let mut best_ub = isize::MAX;
+        while solver.step() == StepResult::Explored {
+            assert!(solver.best_upper_bound() <= best_ub);
+            assert!(solver.best_lower_bound() <= solver.best_upper_bound());
+            best_ub = solver.best_upper_bound();
+        }
+        let actual = solver.finish();
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert_eq!(expected.best_value, Some(best_ub));
+        assert!(!solver.dual_refreshes().is_empty());
+        assert_eq!(0, solver.open_nodes());
+    }
+    #[cfg(feature = "checkpoint")]
+    #[test]
+    fn a_resumed_search_refreshes_its_dual_bound_without_ever_loosening_it() {
+        use crate::implementation::solver::checkpoint::Checkpoint;
+        type SeqSolver<'a, T> = SequentialSolver<'a, T, DefaultMDDLEL<T>, EmptyCache<T>>;
+
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let policy = DualRefresh { explored_factor: 2, ..DualRefresh::default() };
+
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        let expected = solver.maximize();
+        drop(solver);
+
+        let mut checkpoint = vec![];
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let solver = SeqSolver::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe);
+        solver.checkpoint(&mut checkpoint).unwrap();
+        drop(solver);
+
+        // the search is preempted every third node, and its dual bound is 
+        // refreshed as soon as it is resumed
+        let (mut best_ub, mut resumptions, mut refreshes) = (isize::MAX, 0, 0);
+        let actual = loop {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = SeqSolver::custom(&problem, &relax, &ranking, &width, &dominance, &cutoff, &mut fringe)
+                .with_checkpointing()
+                .with_dual_refresh(policy);
+            solver.resume(&mut checkpoint.as_slice()).unwrap();
+            solver.set_node_budget(Some(solver.explored() + 3));
+            while solver.step() == StepResult::Explored {
+                assert!(solver.best_upper_bound() <= best_ub);
+                best_ub = solver.best_upper_bound();
+            }
+            let completion = solver.finish();
+            resumptions += 1;
+            refreshes += solver.dual_refreshes().len();
+            for refresh in solver.dual_refreshes() {
+                assert!(refresh.ub_after.unwrap() <= refresh.ub_before);
+            }
+            if completion.is_exact {
+                break completion;
+            }
+            checkpoint.clear();
+            solver.checkpoint(&mut checkpoint).unwrap();
+            drop(solver);
+
+            // the nodes which were kept by the sweeps comply with the bound 
+            let saved = Checkpoint::<KnapsackState>::read(&mut checkpoint.as_slice()).unwrap();
+            assert!(!saved.nodes.is_empty());
+            assert!(saved.nodes.iter().all(|node| node.ub <= best_ub));
+        };
+
+        assert!(resumptions > 1);
+        assert!(refreshes > 0);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert_eq!(expected.best_value, Some(best_ub));
+    }
+
+    struct UsizeRanking;
+    impl StateRanking for UsizeRanking {
+        type State = usize;
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.cmp(b)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    struct KnapsackState {
+        depth: usize,
+        capacity: usize
+    }
+    struct Knapsack {
+        capacity: usize,
+        profit: Vec<usize>,
+        weight: Vec<usize>,
+    }
+
+    const TAKE_IT: isize = 1;
+    const LEAVE_IT_OUT: isize = 0;
+
+    impl Problem for Knapsack {
+        type State = KnapsackState;
+        fn nb_variables(&self) -> usize {
+            self.profit.len()
+        }
+        fn initial_state(&self) -> Self::State {
+            KnapsackState{ depth: 0, capacity: self.capacity }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = *state;
+            ret.depth  += 1;
+            if dec.value == TAKE_IT { 
+                ret.capacity -= self.weight[dec.variable.id()] 
+            }
+            ret
+        }
+        fn transition_cost(&self, _state: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            self.profit[dec.variable.id()] as isize * dec.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            let n = self.nb_variables();
+            if depth < n {
+                Some(Variable(depth))
+            } else {
+                None
+            }
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback)
+        {
+            if state.capacity >= self.weight[variable.id()] {
+                f.apply(Decision { variable, value: TAKE_IT });
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            } else {
+                f.apply(Decision { variable, value: LEAVE_IT_OUT });
+            }
+        }
+    }
+    struct KPRelax<'a>{pb: &'a Knapsack}
+    impl Relaxation for KPRelax<'_> {
+        type State = KnapsackState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            states.max_by_key(|node| node.capacity).copied().unwrap()
+        }
+        fn relax(&self, _source: &Self::State, _dest: &Self::State, _merged: &Self::State, _decision: Decision, cost: isize) -> isize {
+            cost
+        }
+        fn fast_upper_bound(&self, state: &Self::State) -> isize {
+            let mut tot = 0;
+            for var in state.depth..self.pb.nb_variables() {
+                if self.pb.weight[var] <= state.capacity {
+                    tot += self.pb.profit[var];
+                }
+            }
+            tot as isize
+        }
+    }
+    struct KPRanking;
+    impl StateRanking for KPRanking {
+        type State = KnapsackState;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.capacity.cmp(&b.capacity)
+        }
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the enumeration of *all* the optimal solutions of a
+//! problem (see `enumerate_optimal`). Unlike the solvers, which stop at the
+//! first optimal solution they can prove, this compiles the exact DD of the 
+//! whole problem and walks each of its longest root-to-terminal paths. 
+//!
+//! # Caveat
+//! The exact DD of a problem may be exponentially large: this is meant for 
+//! instances that are small enough to be compiled exactly in one go. The 
+//! solutions themselves, however, are produced one at a time so that a huge
+//! number of optima is never held in memory.
+
+use std::{hash::Hash, sync::Arc};
+
+use crate::{CompilationInput, CompilationType, DecisionDiagram, DefaultMDDLEL, EmptyCache, EmptyDominanceChecker, NoCutoff, OptimalPaths, Problem, Reason, Relaxation, Solution, StateRanking, SubProblem};
+
+/// The optimal solutions of a problem (see `enumerate_optimal`). They are 
+/// walked lazily out of the exact DD this iterator owns.
+pub struct OptimalSolutions<State> 
+where State: Eq + Hash + Clone
+{
+    /// The exact DD of the problem
+    mdd: DefaultMDDLEL<State>,
+    /// How far the enumeration has gone
+    paths: OptimalPaths,
+}
+
+impl<State> OptimalSolutions<State> 
+where State: Eq + Hash + Clone
+{
+    /// Returns the optimal value of the problem, or None when the problem is
+    /// infeasible (in which case there is no solution to enumerate).
+    pub fn best_value(&self) -> Option<isize> {
+        self.mdd.best_value()
+    }
+}
+
+impl<State> Iterator for OptimalSolutions<State> 
+where State: Eq + Hash + Clone
+{
+    type Item = Solution;
+
+    fn next(&mut self) -> Option<Solution> {
+        self.mdd.next_optimal_solution(&mut self.paths)
+    }
+}
+
+/// Enumerates all the optimal solutions of the given problem: the solutions 
+/// whose value is the optimum. Each of them is yielded exactly once. 
+///
+/// The exact DD of the problem is compiled before this returns; it is then
+/// walked as the solutions are pulled from the iterator. The relaxation and 
+/// the ranking are required by the compilation but they play no role in an
+/// exact DD. This fails when the compilation of that DD fails.
+///
+/// # Note
+/// Two solutions only differ by the decisions they are made of: their order
+/// within a solution is the one of `DecisionDiagram::best_solution`.
+pub fn enumerate_optimal<State>(
+    problem: &dyn Problem<State = State>,
+    relaxation: &dyn Relaxation<State = State>,
+    ranking: &dyn StateRanking<State = State>,
+) -> Result<OptimalSolutions<State>, Reason>
+where State: Eq + Hash + Clone
+{
+    let cache = EmptyCache::default();
+    let dominance = EmptyDominanceChecker::default();
+    let input = CompilationInput {
+        comp_type: CompilationType::Exact,
+        problem,
+        relaxation,
+        ranking,
+        cutoff: &NoCutoff,
+        max_width: usize::MAX,
+        best_lb: isize::MIN,
+        cache: &cache,
+        dominance: &dominance,
+    };
+    let root = SubProblem {
+        state: Arc::new(problem.initial_state()),
+        value: problem.initial_value(),
+        path: vec![],
+        ub: isize::MAX,
+        depth: 0,
+        priority_bonus: 0,
+    };
+    let mut mdd = DefaultMDDLEL::default();
+    mdd.compile(&input, root)?;
+    let paths = mdd.optimal_paths().unwrap_or_default();
+    Ok(OptimalSolutions { mdd, paths })
+}
+
+#[cfg(test)]
+mod test_enumerate {
+    use std::cmp::Ordering;
+
+    use crate::*;
+
+    /// A small knapsack instance: the state is the remaining capacity
+    struct Knapsack {
+        capacity: usize,
+        weight  : Vec<usize>,
+        profit  : Vec<isize>,
+    }
+    impl Problem for Knapsack {
+        type State = usize;
+
+        fn nb_variables(&self)  -> usize { self.weight.len() }
+        fn initial_state(&self) -> usize { self.capacity }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, capacity: &usize, d: Decision) -> usize {
+            capacity - d.value as usize * self.weight[d.variable.id()]
+        }
+        fn transition_cost(&self, _: &usize, _: &usize, d: Decision) -> isize {
+            d.value * self.profit[d.variable.id()]
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+            (depth < self.nb_variables()).then_some(Variable(depth))
+        }
+        fn for_each_in_domain(&self, var: Variable, capacity: &usize, f: &mut dyn DecisionCallback) {
+            f.apply(Decision { variable: var, value: 0 });
+            if *capacity >= self.weight[var.id()] {
+                f.apply(Decision { variable: var, value: 1 });
+            }
+        }
+    }
+    struct KnapsackRelax;
+    impl Relaxation for KnapsackRelax {
+        type State = usize;
+        fn merge(&self, states: &mut dyn Iterator<Item = &usize>) -> usize { 
+            *states.max().unwrap() 
+        }
+        fn relax(&self, _: &usize, _: &usize, _: &usize, _: Decision, cost: isize) -> isize { 
+            cost 
+        }
+    }
+    struct KnapsackRanking;
+    impl StateRanking for KnapsackRanking {
+        type State = usize;
+        fn compare(&self, a: &usize, b: &usize) -> Ordering { 
+            a.cmp(b) 
+        }
+    }
+
+    /// The items taken by a solution, as a bitset
+    fn items_of(solution: &Solution) -> usize {
+        solution.iter()
+            .filter(|d| d.value == 1)
+            .fold(0, |set, d| set | (1 << d.variable.id()))
+    }
+    /// Enumerates all the feasible solutions to find the optimal ones
+    fn brute_force(pb: &Knapsack) -> Vec<usize> {
+        let n = pb.nb_variables();
+        let feasible = (0..(1_usize << n))
+            .filter(|x| (0..n).filter(|i| x & (1 << i) != 0).map(|i| pb.weight[i]).sum::<usize>() <= pb.capacity)
+            .map(|x| (x, (0..n).filter(|i| x & (1 << i) != 0).map(|i| pb.profit[i]).sum::<isize>()))
+            .collect::<Vec<_>>();
+        let best = feasible.iter().map(|(_, v)| *v).max().unwrap();
+        feasible.into_iter().filter(|(_, v)| *v == best).map(|(x, _)| x).collect()
+    }
+
+    #[test]
+    fn both_symmetric_optima_are_enumerated() {
+        // only one of the two identical items fits in the knapsack
+        let pb = Knapsack { capacity: 1, weight: vec![1, 1], profit: vec![5, 5] };
+        let optima = enumerate_optimal(&pb, &KnapsackRelax, &KnapsackRanking).unwrap();
+        assert_eq!(Some(5), optima.best_value());
+
+        let mut solutions = optima.collect::<Vec<_>>();
+        solutions.iter_mut().for_each(|s| s.sort_unstable_by_key(|d| d.variable.id()));
+        solutions.sort_unstable_by_key(items_of);
+        assert_eq!(vec![
+            vec![Decision { variable: Variable(0), value: 1 }, Decision { variable: Variable(1), value: 0 }],
+            vec![Decision { variable: Variable(0), value: 0 }, Decision { variable: Variable(1), value: 1 }],
+        ], solutions);
+    }
+    #[test]
+    fn the_optima_match_a_brute_force_enumeration() {
+        // with unit weights and profits, any subset of 3 items out of 7 is optimal
+        let pb = Knapsack { capacity: 3, weight: vec![1; 7], profit: vec![1; 7] };
+        let mut optima = enumerate_optimal(&pb, &KnapsackRelax, &KnapsackRanking).unwrap()
+            .inspect(|s| assert_eq!(Some(3), evaluate(&pb, s).ok()))
+            .map(|s| items_of(&s))
+            .collect::<Vec<_>>();
+        optima.sort_unstable();
+        assert_eq!(35, optima.len());
+        assert_eq!(brute_force(&pb), optima);
+
+        let pb = Knapsack { capacity: 6, weight: vec![2, 3, 1, 4, 3, 2], profit: vec![4, 5, 2, 7, 5, 3] };
+        let mut optima = enumerate_optimal(&pb, &KnapsackRelax, &KnapsackRanking).unwrap()
+            .map(|s| items_of(&s))
+            .collect::<Vec<_>>();
+        optima.sort_unstable();
+        assert_eq!(brute_force(&pb), optima);
+    }
+    #[test]
+    fn the_optima_are_produced_lazily() {
+        let pb = Knapsack { capacity: 10, weight: vec![1; 20], profit: vec![1; 20] };
+        let mut optima = enumerate_optimal(&pb, &KnapsackRelax, &KnapsackRanking).unwrap();
+        // there are C(20, 10) = 184 756 optima, only the first two are walked
+        let first  = optima.next().unwrap();
+        let second = optima.next().unwrap();
+        assert_ne!(items_of(&first), items_of(&second));
+        assert_eq!(10, first.iter().filter(|d| d.value == 1).count());
+    }
+    #[test]
+    fn an_infeasible_problem_has_no_optimal_solution() {
+        struct Infeasible;
+        impl Problem for Infeasible {
+            type State = usize;
+            fn nb_variables(&self)  -> usize { 2 }
+            fn initial_state(&self) -> usize { 0 }
+            fn initial_value(&self) -> isize { 0 }
+            fn transition(&self, s: &usize, _: Decision) -> usize { *s }
+            fn transition_cost(&self, _: &usize, _: &usize, _: Decision) -> isize { 0 }
+            fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+                (depth < 2).then_some(Variable(depth))
+            }
+            fn for_each_in_domain(&self, _: Variable, _: &usize, _: &mut dyn DecisionCallback) {}
+        }
+        let mut optima = enumerate_optimal(&Infeasible, &KnapsackRelax, &KnapsackRanking).unwrap();
+        assert_eq!(None, optima.best_value());
+        assert_eq!(None, optima.next());
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides a sampling based estimate of the size of the search 
+//! tree of a problem (Knuth, 1975). Each sample is a random dive from the root
+//! of the problem: at each node, the dive picks one of the children uniformly 
+//! at random and multiplies the branching factors it has met so far. The sum
+//! of these products over the whole dive is an unbiased estimate of the number
+//! of nodes in the tree. Averaging the estimates of many dives gives a rough 
+//! idea of the effort that lies ahead before committing hours to the search.
+//!
+//! # Note
+//! The estimated tree is the one of the decisions made on the variables in the
+//! order imposed by `Problem::next_variable`, where the children which cannot
+//! improve on a known lower bound (as told by `Relaxation::fast_upper_bound`)
+//! are pruned. It is not the tree of the subproblems explored by the solver:
+//! the latter is at most as large, since each of its nodes compiles a whole 
+//! DD.
+
+use crate::{Decision, Problem, Relaxation};
+
+use super::restart::seed_of_run;
+
+/// This configures how the random dives of `estimate_tree_size` prune the
+/// search tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EstimateConfig {
+    /// The children whose value plus fast upper bound does not exceed this
+    /// lower bound are pruned
+    pub best_lb: isize,
+}
+impl Default for EstimateConfig {
+    fn default() -> Self {
+        Self { best_lb: isize::MIN }
+    }
+}
+
+/// The outcome of `estimate_tree_size`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EstimateReport {
+    /// The number of random dives which were performed
+    pub samples: usize,
+    /// The estimated number of nodes in the tree (mean over all dives)
+    pub mean: f64,
+    /// The sample variance of the estimates of the dives
+    pub variance: f64,
+}
+impl EstimateReport {
+    /// Returns the standard error of the estimated number of nodes
+    pub fn std_error(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            (self.variance / self.samples as f64).sqrt()
+        }
+    }
+}
+
+/// Estimates the number of nodes in the search tree of the given problem with
+/// `samples` random dives from its root. The same `seed` always yields the same
+/// estimate.
+pub fn estimate_tree_size<State>(
+    problem: &dyn Problem<State = State>,
+    relaxation: &dyn Relaxation<State = State>,
+    config: &EstimateConfig,
+    samples: usize,
+    seed: u64,
+) -> EstimateReport {
+    let estimates = (0..samples)
+        .map(|i| dive(problem, relaxation, config, seed_of_run(seed, i)))
+        .collect::<Vec<_>>();
+
+    let n = samples as f64;
+    let mean = if samples == 0 { 0.0 } else { estimates.iter().sum::<f64>() / n };
+    let variance = if samples < 2 {
+        0.0
+    } else {
+        estimates.iter().map(|e| (e - mean) * (e - mean)).sum::<f64>() / (n - 1.0)
+    };
+    EstimateReport { samples, mean, variance }
+}
+
+/// Performs one random dive from the root of the problem and returns the 
+/// estimated number of nodes in the tree
+fn dive<State>(
+    problem: &dyn Problem<State = State>,
+    relaxation: &dyn Relaxation<State = State>,
+    config: &EstimateConfig,
+    mut rng: u64,
+) -> f64 {
+    let mut state = problem.initial_state();
+    let mut value = problem.initial_value();
+    let mut weight = 1.0;
+    let mut estimate = 1.0;
+    let mut children = vec![];
+    let mut depth = 0;
+    while let Some(var) = problem.next_variable(depth, &mut std::iter::once(&state)) {
+        children.clear();
+        problem.for_each_in_domain(var, &state, &mut |d: Decision| {
+            let next = problem.transition(&state, d);
+            let next_value = value.saturating_add(problem.transition_cost(&state, &next, d));
+            if next_value.saturating_add(relaxation.fast_upper_bound(&next)) > config.best_lb {
+                children.push((next, next_value));
+            }
+        });
+        if children.is_empty() {
+            break;
+        }
+        weight *= children.len() as f64;
+        estimate += weight;
+
+        rng = seed_of_run(rng, depth);
+        let pick = (rng % children.len() as u64) as usize;
+        (state, value) = children.swap_remove(pick);
+        depth += 1;
+    }
+    estimate
+}
+
+/// The bookkeeping of the solvers which estimate the size of the search tree
+/// when the search starts (see `with_tree_estimate`)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TreeEstimate {
+    /// The number of random dives
+    pub samples: usize,
+    /// The seed of the dives
+    pub seed: u64,
+    /// The estimate computed when the search started
+    pub report: Option<EstimateReport>,
+}
+impl TreeEstimate {
+    pub fn new(samples: usize, seed: u64) -> Self {
+        Self { samples, seed, report: None }
+    }
+    /// Estimates the size of the search tree unless it was already done
+    pub fn run<State>(
+        &mut self,
+        problem: &dyn Problem<State = State>,
+        relaxation: &dyn Relaxation<State = State>,
+        best_lb: isize,
+    ) {
+        if self.report.is_none() {
+            let config = EstimateConfig { best_lb };
+            self.report = Some(estimate_tree_size(problem, relaxation, &config, self.samples, self.seed));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_estimate {
+    use crate::{DecisionCallback, Variable};
+
+    use super::*;
+
+    /// A complete tree of depth `n` where each node has `k` children. The
+    /// state is the depth of the node and each decision is worth its value.
+    struct Uniform {
+        n: usize,
+        k: isize,
+    }
+    impl Problem for Uniform {
+        type State = usize;
+
+        fn nb_variables(&self) -> usize { self.n }
+        fn initial_state(&self) -> usize { 0 }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, state: &usize, _: Decision) -> usize { state + 1 }
+        fn transition_cost(&self, _: &usize, _: &usize, d: Decision) -> isize { d.value }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+            Some(Variable(depth)).filter(|_| depth < self.n)
+        }
+        fn for_each_in_domain(&self, var: Variable, _: &usize, f: &mut dyn DecisionCallback) {
+            (0..self.k).for_each(|value| f.apply(Decision { variable: var, value }));
+        }
+    }
+    /// Its fast upper bound is exact when `exact` is set
+    struct UniformRelax<'a> {
+        pb: &'a Uniform,
+        exact: bool,
+    }
+    impl Relaxation for UniformRelax<'_> {
+        type State = usize;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &usize>) -> usize { states.copied().min().unwrap_or_default() }
+        fn relax(&self, _: &usize, _: &usize, _: &usize, _: Decision, cost: isize) -> isize { cost }
+        fn fast_upper_bound(&self, state: &usize) -> isize {
+            if self.exact {
+                (self.pb.n - state) as isize * (self.pb.k - 1)
+            } else {
+                isize::MAX
+            }
+        }
+    }
+
+    /// The first decision tells whether the subsequent nodes have one or 
+    /// three children. The tree has 1 + 2 + (1 + 1) + (3 + 9) = 17 nodes.
+    struct Skewed;
+    impl Problem for Skewed {
+        type State = (usize, isize);
+
+        fn nb_variables(&self) -> usize { 3 }
+        fn initial_state(&self) -> (usize, isize) { (0, 0) }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, state: &(usize, isize), d: Decision) -> (usize, isize) {
+            if state.0 == 0 { (1, d.value) } else { (state.0 + 1, state.1) }
+        }
+        fn transition_cost(&self, _: &(usize, isize), _: &(usize, isize), _: Decision) -> isize { 0 }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &(usize, isize)>) -> Option<Variable> {
+            Some(Variable(depth)).filter(|_| depth < 3)
+        }
+        fn for_each_in_domain(&self, var: Variable, state: &(usize, isize), f: &mut dyn DecisionCallback) {
+            let k = if state.0 == 0 { 2 } else { 1 + 2 * state.1 };
+            (0..k).for_each(|value| f.apply(Decision { variable: var, value }));
+        }
+    }
+    struct NoBound;
+    impl Relaxation for NoBound {
+        type State = (usize, isize);
+
+        fn merge(&self, _: &mut dyn Iterator<Item = &(usize, isize)>) -> (usize, isize) { (0, 0) }
+        fn relax(&self, _: &(usize, isize), _: &(usize, isize), _: &(usize, isize), _: Decision, cost: isize) -> isize { cost }
+    }
+
+    #[test]
+    fn the_estimate_is_exact_on_a_uniform_complete_tree() {
+        let problem = Uniform { n: 4, k: 3 };
+        let relax = UniformRelax { pb: &problem, exact: false };
+        let report = estimate_tree_size(&problem, &relax, &EstimateConfig::default(), 10, 42);
+
+        assert_eq!(10, report.samples);
+        assert_eq!(121.0, report.mean);
+        assert_eq!(0.0, report.variance);
+        assert_eq!(0.0, report.std_error());
+    }
+    #[test]
+    fn the_children_which_cannot_improve_on_the_lower_bound_are_pruned() {
+        let problem = Uniform { n: 2, k: 2 };
+        let relax = UniformRelax { pb: &problem, exact: true };
+        let report = estimate_tree_size(&problem, &relax, &EstimateConfig { best_lb: 1 }, 10, 42);
+        // only the path which takes the value 1 twice improves on the bound
+        assert_eq!(3.0, report.mean);
+    }
+    #[test]
+    fn the_estimate_of_an_irregular_tree_is_unbiased() {
+        let report = estimate_tree_size(&Skewed, &NoBound, &EstimateConfig::default(), 4000, 7);
+        assert!((report.mean - 17.0).abs() < 1.0);
+        assert!(report.variance > 0.0);
+    }
+    #[test]
+    fn the_same_seed_yields_the_same_estimate() {
+        let a = estimate_tree_size(&Skewed, &NoBound, &EstimateConfig::default(), 50, 7);
+        let b = estimate_tree_size(&Skewed, &NoBound, &EstimateConfig::default(), 50, 7);
+        assert_eq!(a, b);
+    }
+    #[test]
+    fn without_sample_there_is_no_estimate() {
+        let report = estimate_tree_size(&Skewed, &NoBound, &EstimateConfig::default(), 0, 7);
+        assert_eq!(EstimateReport { samples: 0, mean: 0.0, variance: 0.0 }, report);
+    }
+    #[test]
+    fn the_solvers_estimate_the_tree_only_once() {
+        let problem = Uniform { n: 2, k: 2 };
+        let relax = UniformRelax { pb: &problem, exact: true };
+        let mut estimate = TreeEstimate::new(10, 42);
+        estimate.run(&problem, &relax, 1);
+        estimate.run(&problem, &relax, isize::MIN);
+        assert_eq!(Some(3.0), estimate.report.map(|r| r.mean));
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the snapshots of the composition of the fringe which
+//! can optionally be taken by the sequential solver. They tell how the open
+//! nodes are spread, in terms of upper bounds and depths, as the search goes
+//! on; which helps understand why an instance is hard (e.g. a fringe which 
+//! keeps growing with nodes whose bounds are all close to the best one).
+//!
+//! A snapshot is taken every so many explored nodes (see `FringeSampling`).
+//! The number of snapshots is bounded: when there are too many of them, every
+//! other snapshot is dropped and the sampling period is doubled. This way,
+//! the snapshots always span the whole search.
+
+use crate::{Fringe, SubProblem};
+
+/// This configures the snapshots of the composition of the fringe
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FringeSampling {
+    /// A snapshot is taken every `every` explored nodes (at first)
+    pub every: usize,
+    /// The number of buckets of the histogram of the upper bounds
+    pub ub_buckets: usize,
+    /// The nodes whose upper bound lies within this relative distance of the
+    /// best upper bound are counted (0.05 means within 5%)
+    pub near_best: f64,
+    /// The maximum number of snapshots which are kept
+    pub max_snapshots: usize,
+}
+impl Default for FringeSampling {
+    fn default() -> Self {
+        Self { every: 1000, ub_buckets: 10, near_best: 0.05, max_snapshots: 1000 }
+    }
+}
+
+/// The composition of the fringe at some point of the search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FringeSnapshot {
+    /// The number of nodes which had been explored when the snapshot was taken
+    pub explored: usize,
+    /// The best lower bound at that time
+    pub best_lb: isize,
+    /// The best upper bound at that time
+    pub best_ub: isize,
+    /// The number of nodes which were visited (see `Fringe::inspect`)
+    pub nb_nodes: usize,
+    /// The smallest and the largest upper bounds of the visited nodes (None 
+    /// when no node was visited)
+    pub ub_range: Option<(isize, isize)>,
+    /// The number of nodes whose upper bound falls in each bucket. The range 
+    /// of the upper bounds is split in buckets of equal width, the first one 
+    /// starting at the smallest upper bound.
+    pub ub_histogram: Vec<usize>,
+    /// The number of nodes at each depth (from zero to the deepest node)
+    pub depth_histogram: Vec<usize>,
+    /// The number of nodes whose upper bound lies within `near_best` of the
+    /// best upper bound (see `FringeSampling`)
+    pub near_best: usize,
+}
+impl FringeSnapshot {
+    /// The header of the CSV lines produced by `to_csv`
+    pub const CSV_HEADER: &'static str = "explored,best_lb,best_ub,nb_nodes,near_best,ub_min,ub_max,ub_histogram,depth_histogram";
+
+    /// Takes a snapshot of the given fringe
+    pub fn of<T>(fringe: &dyn Fringe<State = T>, explored: usize, best_lb: isize, best_ub: isize, sampling: &FringeSampling) -> Self {
+        let mut ubs = vec![];
+        let mut depth_histogram = vec![];
+        fringe.inspect(&mut |node: &SubProblem<T>| {
+            ubs.push(node.ub);
+            if depth_histogram.len() <= node.depth {
+                depth_histogram.resize(node.depth + 1, 0);
+            }
+            depth_histogram[node.depth] += 1;
+        });
+
+        let ub_range = ubs.iter().copied().min().zip(ubs.iter().copied().max());
+        let mut ub_histogram = vec![0; sampling.ub_buckets];
+        if let (Some((min, max)), false) = (ub_range, ub_histogram.is_empty()) {
+            for ub in ubs.iter().copied() {
+                ub_histogram[bucket_of(ub, min, max, sampling.ub_buckets)] += 1;
+            }
+        }
+        let near_best = ubs.iter().filter(|ub| is_near(**ub, best_ub, sampling.near_best)).count();
+
+        Self { explored, best_lb, best_ub, nb_nodes: ubs.len(), ub_range, ub_histogram, depth_histogram, near_best }
+    }
+    /// Returns the snapshot as a line of CSV (without its header). The counts
+    /// of the histograms are separated by semicolons, and the bounds of the
+    /// range of upper bounds are left empty when the fringe is empty.
+    pub fn to_csv(&self) -> String {
+        let counts = |h: &[usize]| h.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(";");
+        let (min, max) = self.ub_range
+            .map(|(min, max)| (min.to_string(), max.to_string()))
+            .unwrap_or_default();
+        format!("{},{},{},{},{},{},{},{},{}",
+            self.explored,
+            self.best_lb,
+            self.best_ub,
+            self.nb_nodes,
+            self.near_best,
+            min,
+            max,
+            counts(&self.ub_histogram),
+            counts(&self.depth_histogram))
+    }
+}
+
+/// Returns the index of the bucket in which the given `ub` falls when the 
+/// range `[min, max]` is split in `nb_buckets` buckets of equal width
+fn bucket_of(ub: isize, min: isize, max: isize, nb_buckets: usize) -> usize {
+    let width = max as i128 - min as i128 + 1;
+    ((ub as i128 - min as i128) * nb_buckets as i128 / width) as usize
+}
+/// Returns true iff the given `ub` lies within the relative distance `near`
+/// of the `best_ub`
+fn is_near(ub: isize, best_ub: isize, near: f64) -> bool {
+    let distance = best_ub as i128 - ub as i128;
+    distance as f64 <= near * best_ub.unsigned_abs() as f64
+}
+
+/// Takes the snapshots of the fringe on behalf of a solver
+#[derive(Debug, Clone)]
+pub(crate) struct FringeSampler {
+    pub(crate) sampling: FringeSampling,
+    /// The current sampling period
+    every: usize,
+    /// The snapshots which have been taken so far
+    snapshots: Vec<FringeSnapshot>,
+}
+impl FringeSampler {
+    pub(crate) fn new(sampling: FringeSampling) -> Self {
+        Self { sampling, every: sampling.every, snapshots: vec![] }
+    }
+    /// Returns the snapshots which have been taken so far
+    pub(crate) fn snapshots(&self) -> &[FringeSnapshot] {
+        &self.snapshots
+    }
+    /// Returns true iff a snapshot must be taken after `explored` nodes
+    pub(crate) fn is_due(&self, explored: usize) -> bool {
+        self.every > 0 && self.sampling.max_snapshots > 0 && explored.is_multiple_of(self.every)
+    }
+    /// Remembers the given snapshot. When there are too many snapshots, the
+    /// sampling period doubles and the snapshots which were not taken at a 
+    /// multiple of the new period are dropped.
+    pub(crate) fn record(&mut self, snapshot: FringeSnapshot) {
+        self.snapshots.push(snapshot);
+        while self.snapshots.len() > self.sampling.max_snapshots {
+            self.every = self.every.saturating_mul(2);
+            let every = self.every;
+            self.snapshots.retain(|s| s.explored % every == 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_fringe_snapshot {
+    use std::cmp::Ordering;
+    use std::sync::Arc;
+
+    use crate::*;
+    use super::FringeSampler;
+
+    /// A dummy state comparator for use in the tests
+    struct CharRanking;
+    impl StateRanking for CharRanking {
+        type State = char;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> Ordering {
+            a.cmp(b)
+        }
+    }
+
+    fn sub(state: char, ub: isize, depth: usize) -> SubProblem<char> {
+        SubProblem { state: Arc::new(state), value: 0, ub, path: vec![], depth, priority_bonus: 0 }
+    }
+    fn synthetic_fringe() -> SimpleFringe<MaxUB<'static, CharRanking>> {
+        let mut fringe = SimpleFringe::new(MaxUB::new(&CharRanking));
+        fringe.push(sub('a', 100, 1));
+        fringe.push(sub('b',  96, 1));
+        fringe.push(sub('c',  90, 2));
+        fringe.push(sub('d',  55, 2));
+        fringe.push(sub('e',  10, 4));
+        fringe.push(sub('f',  19, 4));
+        fringe
+    }
+
+    #[test]
+    fn the_histograms_count_each_node_once() {
+        let fringe = synthetic_fringe();
+        let sampling = FringeSampling { ub_buckets: 5, near_best: 0.05, ..Default::default() };
+        let snapshot = FringeSnapshot::of(&fringe, 42, 30, 100, &sampling);
+
+        assert_eq!(42, snapshot.explored);
+        assert_eq!(6, snapshot.nb_nodes);
+        assert_eq!(Some((10, 100)), snapshot.ub_range);
+        // the range [10, 100] is split in 5 buckets of width 91/5
+        assert_eq!(vec![2, 0, 1, 0, 3], snapshot.ub_histogram);
+        assert_eq!(vec![0, 2, 2, 0, 2], snapshot.depth_histogram);
+        // 100 and 96 lie within 5% of 100
+        assert_eq!(2, snapshot.near_best);
+        assert_eq!(6, snapshot.ub_histogram.iter().sum::<usize>());
+        assert_eq!(6, snapshot.depth_histogram.iter().sum::<usize>());
+    }
+    #[test]
+    fn the_no_dup_fringe_can_be_inspected_as_well() {
+        let mut fringe = NoDupFringe::new(MaxUB::new(&CharRanking));
+        fringe.push(sub('a', 100, 1));
+        fringe.push(sub('b',  96, 1));
+        fringe.push(sub('a',  90, 1));
+        let snapshot = FringeSnapshot::of(&fringe, 0, 0, 100, &FringeSampling::default());
+        assert_eq!(2, snapshot.nb_nodes);
+        assert_eq!(vec![0, 2], snapshot.depth_histogram);
+    }
+    #[test]
+    fn a_single_upper_bound_falls_in_the_first_bucket() {
+        let mut fringe = SimpleFringe::new(MaxUB::new(&CharRanking));
+        fringe.push(sub('a', isize::MAX, 0));
+        fringe.push(sub('b', isize::MAX, 0));
+        let snapshot = FringeSnapshot::of(&fringe, 0, isize::MIN, isize::MAX, &FringeSampling::default());
+        assert_eq!(2, snapshot.ub_histogram[0]);
+        assert_eq!(2, snapshot.near_best);
+    }
+    #[test]
+    fn an_empty_fringe_yields_empty_histograms() {
+        let fringe = SimpleFringe::new(MaxUB::new(&CharRanking));
+        let snapshot = FringeSnapshot::of(&fringe, 0, 0, 0, &FringeSampling::default());
+        assert_eq!(0, snapshot.nb_nodes);
+        assert_eq!(None, snapshot.ub_range);
+        assert!(snapshot.ub_histogram.iter().all(|c| *c == 0));
+        assert!(snapshot.depth_histogram.is_empty());
+        assert_eq!("0,0,0,0,0,,,0;0;0;0;0;0;0;0;0;0,", snapshot.to_csv());
+    }
+    #[test]
+    fn the_csv_line_has_one_field_per_column_of_the_header() {
+        let fringe = synthetic_fringe();
+        let sampling = FringeSampling { ub_buckets: 5, ..Default::default() };
+        let line = FringeSnapshot::of(&fringe, 42, 30, 100, &sampling).to_csv();
+        assert_eq!(FringeSnapshot::CSV_HEADER.split(',').count(), line.split(',').count());
+        assert_eq!("42,30,100,6,2,10,100,2;0;1;0;3,0;2;2;0;2", line);
+    }
+    #[test]
+    fn the_number_of_snapshots_is_bounded() {
+        let fringe = synthetic_fringe();
+        let sampling = FringeSampling { every: 10, max_snapshots: 4, ..Default::default() };
+        let mut sampler = FringeSampler::new(sampling);
+        for explored in 1..=100 {
+            if sampler.is_due(explored) {
+                sampler.record(FringeSnapshot::of(&fringe, explored, 0, 100, &sampling));
+            }
+        }
+        let explored = sampler.snapshots().iter().map(|s| s.explored).collect::<Vec<_>>();
+        assert_eq!(vec![40, 80], explored);
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the bookkeeping of the gap tolerance of the solvers
+//! (see `with_gap_tolerance`). The gap is checked between two nodes of the
+//! branch and bound, and the search stops as soon as it drops below the 
+//! tolerance. The target of the solvers (see `with_target`) is checked in 
+//! the same way.
+
+use crate::Reason;
+
+/// The relative gap below which a solver may stop its search
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct GapTolerance(pub f64);
+impl GapTolerance {
+    /// Returns the gap between the given bounds iff it is below the tolerance.
+    /// A zero tolerance is never reached: the search then runs until it 
+    /// proves optimality.
+    pub fn is_reached(&self, best_lb: isize, best_ub: isize) -> Option<f64> {
+        let gap = relative_gap(best_lb, best_ub);
+        Some(gap).filter(|gap| *gap < self.0)
+    }
+}
+
+/// Tells whether the search aiming at `target` may stop given its current 
+/// bounds: either because it found a solution reaching the target, or 
+/// because it proved that no solution can reach it.
+pub(crate) fn target_reason(target: isize, best_lb: isize, best_ub: isize) -> Option<Reason> {
+    if best_lb >= target {
+        Some(Reason::TargetReached)
+    } else if best_ub < target {
+        Some(Reason::TargetUnreachable)
+    } else {
+        None
+    }
+}
+
+/// Computes the relative gap `(ub - lb) / |ub|` between the given bounds. 
+/// The denominator is taken to be the largest magnitude of the two bounds so
+/// that the gap remains meaningful (and finite) when the upper bound is zero
+/// or negative. The gap is infinite as long as either bound is unknown.
+pub(crate) fn relative_gap(best_lb: isize, best_ub: isize) -> f64 {
+    if best_lb == isize::MIN || best_ub == isize::MAX {
+        f64::INFINITY
+    } else if best_ub <= best_lb {
+        0.0
+    } else {
+        let ub = best_ub as f64;
+        let lb = best_lb as f64;
+        (ub - lb) / ub.abs().max(lb.abs())
+    }
+}
+
+#[cfg(test)]
+mod test_gap {
+    use super::*;
+
+    #[test]
+    fn the_gap_is_infinite_while_a_bound_is_unknown() {
+        assert_eq!(f64::INFINITY, relative_gap(isize::MIN, 10));
+        assert_eq!(f64::INFINITY, relative_gap(10, isize::MAX));
+        assert_eq!(f64::INFINITY, relative_gap(isize::MIN, isize::MAX));
+    }
+    #[test]
+    fn the_gap_is_zero_once_the_bounds_meet() {
+        assert_eq!(0.0, relative_gap(10, 10));
+        assert_eq!(0.0, relative_gap(0, 0));
+        assert_eq!(0.0, relative_gap(-5, -5));
+    }
+    #[test]
+    fn the_gap_is_relative_to_the_upper_bound() {
+        assert_eq!(0.5, relative_gap(50, 100));
+        assert_eq!(0.01, relative_gap(99, 100));
+    }
+    #[test]
+    fn the_gap_remains_finite_when_the_upper_bound_is_zero_or_negative() {
+        assert_eq!(1.0, relative_gap(-10, 0));
+        assert_eq!(0.5, relative_gap(-10, -5));
+        assert_eq!(2.0, relative_gap(-10, 10));
+        assert!(relative_gap(-10, -5).is_finite());
+    }
+    #[test]
+    fn a_zero_tolerance_is_never_reached() {
+        assert_eq!(None, GapTolerance(0.0).is_reached(10, 10));
+        assert_eq!(None, GapTolerance(0.0).is_reached(9, 10));
+        assert_eq!(None, GapTolerance::default().is_reached(9, 10));
+    }
+    #[test]
+    fn the_tolerance_is_reached_when_the_gap_drops_below_it() {
+        assert_eq!(None, GapTolerance(0.05).is_reached(isize::MIN, 100));
+        assert_eq!(None, GapTolerance(0.05).is_reached(90, 100));
+        assert_eq!(Some(0.01), GapTolerance(0.05).is_reached(99, 100));
+    }
+    #[test]
+    fn the_target_is_decided_once_a_bound_crosses_it() {
+        assert_eq!(None, target_reason(50, isize::MIN, isize::MAX));
+        assert_eq!(None, target_reason(50, 49, 50));
+        assert_eq!(Some(Reason::TargetReached), target_reason(50, 50, 60));
+        assert_eq!(Some(Reason::TargetUnreachable), target_reason(50, 40, 49));
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides a handle on the incumbent of a solver which can be 
+//! read by any thread while the solver runs (see `with_shared_incumbent`).
+
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::{Decision, Solution};
+
+/// A snapshot of the incumbent: its value along with its decisions, sorted 
+/// by variable
+pub type IncumbentSnapshot = Arc<(isize, Solution)>;
+
+/// A handle on the best solution found so far by a solver. The solver updates
+/// it each time its incumbent improves while the other threads (e.g. a user
+/// interface) read it whenever they see fit, without stopping the search.
+///
+/// The handle is cheap to clone: all the clones share the same incumbent. 
+/// Reading it yields a snapshot which is consistent (its value is the value
+/// of its decisions) and which is never worse than the snapshots read before.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// let incumbent = SharedIncumbent::default();
+/// assert_eq!(None, incumbent.value());
+/// let reader = incumbent.clone();
+/// assert!(reader.snapshot().is_none());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SharedIncumbent {
+    /// The current snapshot (None until a solution has been found)
+    current: Arc<RwLock<Option<IncumbentSnapshot>>>,
+}
+impl SharedIncumbent {
+    /// Returns the latest snapshot of the incumbent (None when no solution
+    /// has been found yet)
+    pub fn snapshot(&self) -> Option<IncumbentSnapshot> {
+        self.current.read().clone()
+    }
+    /// Returns the value of the incumbent (None when no solution has been 
+    /// found yet)
+    pub fn value(&self) -> Option<isize> {
+        self.current.read().as_ref().map(|snapshot| snapshot.0)
+    }
+    /// Replaces the incumbent with the given solution iff it is strictly 
+    /// better. The solution is sorted before it is published.
+    pub(crate) fn offer(&self, value: isize, solution: &[Decision]) {
+        if self.value().is_some_and(|current| current >= value) {
+            return;
+        }
+        let mut solution = solution.to_vec();
+        solution.sort_unstable_by_key(|d| d.variable.0);
+        let snapshot = Arc::new((value, solution));
+
+        let mut current = self.current.write();
+        if current.as_ref().is_none_or(|c| c.0 < value) {
+            *current = Some(snapshot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_incumbent {
+    use crate::*;
+
+    fn decision(var: usize, value: isize) -> Decision {
+        Decision { variable: Variable(var), value }
+    }
+
+    #[test]
+    fn only_a_better_solution_replaces_the_incumbent() {
+        let incumbent = SharedIncumbent::default();
+        let reader = incumbent.clone();
+        assert!(reader.snapshot().is_none());
+
+        incumbent.offer(10, &[decision(1, 1), decision(0, 0)]);
+        assert_eq!(Some(10), reader.value());
+        assert_eq!(vec![decision(0, 0), decision(1, 1)], reader.snapshot().unwrap().1);
+
+        incumbent.offer(10, &[decision(0, 1), decision(1, 0)]);
+        incumbent.offer(5,  &[decision(0, 0), decision(1, 0)]);
+        assert_eq!(vec![decision(0, 0), decision(1, 1)], reader.snapshot().unwrap().1);
+
+        incumbent.offer(12, &[decision(0, 1), decision(1, 1)]);
+        assert_eq!(Some(12), reader.value());
+    }
+    #[test]
+    fn a_snapshot_outlives_the_updates() {
+        let incumbent = SharedIncumbent::default();
+        incumbent.offer(1, &[decision(0, 1)]);
+        let old = incumbent.snapshot().unwrap();
+        incumbent.offer(2, &[decision(0, 2)]);
+        assert_eq!(1, old.0);
+        assert_eq!(2, incumbent.snapshot().unwrap().0);
+    }
+}
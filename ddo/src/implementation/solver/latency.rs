@@ -0,0 +1,279 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the latency histograms of the compilations performed
+//! by the sequential solver. Average timings hide the handful of pathological
+//! compilations which dominate the wall time; the histograms do not. Each
+//! duration falls in a fixed log-scale bucket: bucket 0 holds the zero 
+//! durations and bucket $k > 0$ holds the durations of $[2^{k-1}, 2^k)$ 
+//! nanoseconds.
+//!
+//! Optionally, the solver can also remember the compilations which took much
+//! longer than usual (see `LatencyWatch`) so that you can study them.
+
+use std::time::Duration;
+
+use crate::{CompilationType, Decision};
+
+/// The number of buckets of a latency histogram
+pub const NB_LATENCY_BUCKETS: usize = 65;
+
+/// A fixed-bucket log-scale histogram of durations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// The number of durations which fell in each bucket
+    buckets: [u64; NB_LATENCY_BUCKETS],
+    /// The number of recorded durations
+    count: u64,
+    /// The longest recorded duration
+    max: Duration,
+}
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self { buckets: [0; NB_LATENCY_BUCKETS], count: 0, max: Duration::ZERO }
+    }
+}
+impl LatencyHistogram {
+    /// Records the given duration
+    pub fn record(&mut self, elapsed: Duration) {
+        self.buckets[Self::bucket_of(elapsed)] += 1;
+        self.count += 1;
+        self.max = self.max.max(elapsed);
+    }
+    /// Returns the number of recorded durations
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+    /// Returns the number of durations which fell in each bucket
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+    /// Returns the longest recorded duration (None when nothing was recorded)
+    pub fn max(&self) -> Option<Duration> {
+        Some(self.max).filter(|_| self.count > 0)
+    }
+    /// Returns the median duration
+    pub fn p50(&self) -> Option<Duration> {
+        self.percentile(0.50)
+    }
+    /// Returns the 95th percentile of the durations
+    pub fn p95(&self) -> Option<Duration> {
+        self.percentile(0.95)
+    }
+    /// Returns the 99th percentile of the durations
+    pub fn p99(&self) -> Option<Duration> {
+        self.percentile(0.99)
+    }
+    /// Returns an upper bound on the given quantile `q` (between 0 and 1) of 
+    /// the recorded durations: the upper bound of the bucket it falls in, or
+    /// the longest duration when that one is smaller. It returns None when 
+    /// nothing was recorded.
+    pub fn percentile(&self, q: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Some(Self::upper_bound(bucket).min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// Returns the index of the bucket the given duration falls in
+    pub(crate) fn bucket_of(elapsed: Duration) -> usize {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        (u64::BITS - nanos.leading_zeros()) as usize
+    }
+    /// Returns the longest duration which falls in the given bucket
+    pub(crate) fn upper_bound(bucket: usize) -> Duration {
+        match bucket {
+            0 => Duration::ZERO,
+            64 => Duration::from_nanos(u64::MAX),
+            k => Duration::from_nanos((1_u64 << k) - 1),
+        }
+    }
+}
+
+/// The latency histograms of the restricted and relaxed compilations
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompilationLatencies {
+    /// The durations of the restricted compilations
+    pub restricted: LatencyHistogram,
+    /// The durations of the relaxed compilations
+    pub relaxed: LatencyHistogram,
+}
+
+/// This configures which compilations are deemed pathological: those which
+/// take more than `factor` times the running 95th percentile of the 
+/// compilations of the same type. No compilation is deemed pathological 
+/// before `warmup` compilations of its type have been recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyWatch {
+    /// How many times slower than the running p95 a compilation must be
+    pub factor: u32,
+    /// The number of compilations which must be recorded beforehand
+    pub warmup: u64,
+}
+impl Default for LatencyWatch {
+    fn default() -> Self {
+        Self { factor: 10, warmup: 100 }
+    }
+}
+
+/// A compilation which took much longer than usual (see `LatencyWatch`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowCompilation {
+    /// The rank of the subproblem in the exploration order (the first one is #1)
+    pub node: usize,
+    /// The depth of the subproblem
+    pub depth: usize,
+    /// The decisions leading to the subproblem
+    pub path: Vec<Decision>,
+    /// The type of the compilation (restricted or relaxed)
+    pub comp_type: CompilationType,
+    /// The time it took to compile the DD
+    pub elapsed: Duration,
+    /// The running 95th percentile when the compilation occurred
+    pub p95: Duration,
+}
+
+/// The bookkeeping of the compilation latencies
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LatencyTracker {
+    pub histograms: CompilationLatencies,
+    pub watch: Option<LatencyWatch>,
+    pub slow: Vec<SlowCompilation>,
+}
+impl LatencyTracker {
+    /// Returns true iff the slow compilations are being watched
+    pub fn watching(&self) -> bool {
+        self.watch.is_some()
+    }
+    /// Records the duration of a compilation of the subproblem having the 
+    /// given rank and depth. The `path` is only called when the compilation 
+    /// turns out to be pathological.
+    pub fn record(&mut self, comp_type: CompilationType, elapsed: Duration, node: usize, depth: usize, path: impl FnOnce() -> Vec<Decision>) {
+        let histogram = match comp_type {
+            CompilationType::Restricted => &mut self.histograms.restricted,
+            _ => &mut self.histograms.relaxed,
+        };
+        if let Some(watch) = self.watch {
+            if histogram.count() >= watch.warmup.max(1) {
+                let p95 = histogram.p95().unwrap_or_default();
+                if elapsed > p95.saturating_mul(watch.factor) {
+                    self.slow.push(SlowCompilation { node, depth, path: path(), comp_type, elapsed, p95 });
+                }
+            }
+        }
+        histogram.record(elapsed);
+    }
+    /// Forgets all about the previous compilations
+    pub fn clear(&mut self) {
+        self.histograms = CompilationLatencies::default();
+        self.slow.clear();
+    }
+}
+
+#[cfg(test)]
+mod test_latency {
+    use super::*;
+
+    #[test]
+    fn the_buckets_double_in_size() {
+        assert_eq!(0, LatencyHistogram::bucket_of(Duration::ZERO));
+        assert_eq!(1, LatencyHistogram::bucket_of(Duration::from_nanos(1)));
+        assert_eq!(2, LatencyHistogram::bucket_of(Duration::from_nanos(2)));
+        assert_eq!(2, LatencyHistogram::bucket_of(Duration::from_nanos(3)));
+        assert_eq!(3, LatencyHistogram::bucket_of(Duration::from_nanos(4)));
+        assert_eq!(10, LatencyHistogram::bucket_of(Duration::from_nanos(1023)));
+        assert_eq!(11, LatencyHistogram::bucket_of(Duration::from_nanos(1024)));
+        assert_eq!(64, LatencyHistogram::bucket_of(Duration::from_nanos(u64::MAX)));
+        assert_eq!(64, LatencyHistogram::bucket_of(Duration::MAX));
+    }
+    #[test]
+    fn each_bucket_ends_right_before_the_next_one_starts() {
+        for bucket in 0..NB_LATENCY_BUCKETS {
+            let upper = LatencyHistogram::upper_bound(bucket);
+            assert_eq!(bucket, LatencyHistogram::bucket_of(upper));
+            if bucket < NB_LATENCY_BUCKETS - 1 {
+                assert_eq!(bucket + 1, LatencyHistogram::bucket_of(upper + Duration::from_nanos(1)));
+            }
+        }
+    }
+    #[test]
+    fn an_empty_histogram_has_no_percentile() {
+        let histogram = LatencyHistogram::default();
+        assert_eq!(0, histogram.count());
+        assert_eq!(None, histogram.p50());
+        assert_eq!(None, histogram.max());
+    }
+    #[test]
+    fn the_percentiles_are_bounded_by_their_bucket_and_by_the_max() {
+        let mut histogram = LatencyHistogram::default();
+        for _ in 0..98 {
+            histogram.record(Duration::from_nanos(100)); // bucket 7: [64, 127]
+        }
+        histogram.record(Duration::from_nanos(1_000));    // bucket 10: [512, 1023]
+        histogram.record(Duration::from_nanos(5_000));
+        assert_eq!(100, histogram.count());
+        assert_eq!(98, histogram.buckets()[7]);
+        assert_eq!(Some(Duration::from_nanos(127)), histogram.p50());
+        assert_eq!(Some(Duration::from_nanos(127)), histogram.p95());
+        assert_eq!(Some(Duration::from_nanos(1023)), histogram.p99());
+        assert_eq!(Some(Duration::from_nanos(5_000)), histogram.percentile(1.0));
+        assert_eq!(Some(Duration::from_nanos(5_000)), histogram.max());
+    }
+    #[test]
+    fn the_slow_compilations_are_only_watched_after_the_warmup() {
+        let mut tracker = LatencyTracker { 
+            watch: Some(LatencyWatch { factor: 10, warmup: 3 }), 
+            ..Default::default() 
+        };
+        let path = || vec![Decision { variable: crate::Variable(0), value: 1 }];
+        tracker.record(CompilationType::Restricted, Duration::from_nanos(100), 1, 0, path);
+        tracker.record(CompilationType::Restricted, Duration::from_nanos(100), 2, 1, path);
+        tracker.record(CompilationType::Relaxed, Duration::from_micros(100), 2, 1, path);
+        tracker.record(CompilationType::Restricted, Duration::from_nanos(100), 3, 1, path);
+        assert!(tracker.slow.is_empty());
+
+        // p95 = 100ns (the max): 2000ns is more than ten times that
+        tracker.record(CompilationType::Restricted, Duration::from_nanos(2000), 4, 2, path);
+        assert_eq!(1, tracker.slow.len());
+        assert_eq!(4, tracker.slow[0].node);
+        assert_eq!(2, tracker.slow[0].depth);
+        assert_eq!(path(), tracker.slow[0].path);
+        assert_eq!(CompilationType::Restricted, tracker.slow[0].comp_type);
+        assert_eq!(Duration::from_nanos(100), tracker.slow[0].p95);
+
+        // p95 = 2000ns: 15000ns is less than ten times that
+        tracker.record(CompilationType::Restricted, Duration::from_nanos(15_000), 5, 3, path);
+        assert_eq!(1, tracker.slow.len());
+
+        assert_eq!(5, tracker.histograms.restricted.count());
+        assert_eq!(1, tracker.histograms.relaxed.count());
+        tracker.clear();
+        assert!(tracker.slow.is_empty());
+        assert_eq!(0, tracker.histograms.restricted.count());
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides a handle on the best bounds of a solver which can be
+//! read by any thread while the solver runs (see `bounds_handle`).
+
+use std::sync::{atomic::{AtomicIsize, Ordering}, Arc};
+
+use super::gap::relative_gap;
+
+/// A handle on the best lower and upper bounds of a solver. The solver keeps
+/// them up to date while it runs, and the other threads (e.g. a dashboard) may
+/// poll them whenever they see fit, without stopping the search.
+///
+/// The handle is cheap to clone: all the clones share the same bounds. Each
+/// bound is read atomically, but the two bounds are not read together: a 
+/// reader may see a lower bound which is slightly more recent than the upper
+/// bound (or vice versa).
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// let bounds = BoundsHandle::default();
+/// assert_eq!(isize::MIN, bounds.best_lb());
+/// assert_eq!(isize::MAX, bounds.best_ub());
+/// assert_eq!(f64::INFINITY, bounds.gap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BoundsHandle {
+    /// The best lower bound (isize::MIN until a solution is found)
+    lb: Arc<AtomicIsize>,
+    /// The best upper bound (isize::MAX until a bound is derived)
+    ub: Arc<AtomicIsize>,
+}
+impl Default for BoundsHandle {
+    fn default() -> Self {
+        Self { 
+            lb: Arc::new(AtomicIsize::new(isize::MIN)), 
+            ub: Arc::new(AtomicIsize::new(isize::MAX)),
+        }
+    }
+}
+impl BoundsHandle {
+    /// Returns the best lower bound of the solver
+    pub fn best_lb(&self) -> isize {
+        self.lb.load(Ordering::Acquire)
+    }
+    /// Returns the best upper bound of the solver
+    pub fn best_ub(&self) -> isize {
+        self.ub.load(Ordering::Acquire)
+    }
+    /// Returns the relative gap between the best bounds of the solver. It is 
+    /// infinite as long as either bound is unknown, and zero once they meet.
+    pub fn gap(&self) -> f64 {
+        let lb = self.best_lb();
+        let ub = self.best_ub();
+        relative_gap(lb, ub)
+    }
+    /// Publishes the current bounds of the solver
+    pub(crate) fn publish(&self, best_lb: isize, best_ub: isize) {
+        self.lb.store(best_lb, Ordering::Release);
+        self.ub.store(best_ub, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod test_live_bounds {
+    use crate::*;
+
+    #[test]
+    fn all_the_clones_see_the_published_bounds() {
+        let bounds = BoundsHandle::default();
+        let reader = bounds.clone();
+        bounds.publish(80, 100);
+        assert_eq!(80, reader.best_lb());
+        assert_eq!(100, reader.best_ub());
+        assert!((reader.gap() - 0.2).abs() < 1e-9);
+    }
+    #[test]
+    fn the_gap_is_zero_once_the_bounds_meet() {
+        let bounds = BoundsHandle::default();
+        bounds.publish(100, 100);
+        assert_eq!(0.0, bounds.gap());
+    }
+    #[test]
+    fn the_gap_is_infinite_while_a_bound_is_unknown() {
+        let bounds = BoundsHandle::default();
+        bounds.publish(isize::MIN, 100);
+        assert_eq!(f64::INFINITY, bounds.gap());
+        bounds.publish(10, isize::MAX);
+        assert_eq!(f64::INFINITY, bounds.gap());
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the *manual branching* at the root of the problem.
+//! Rather than letting the solver start from the root, one may split the root
+//! problem into subproblems of one's own (e.g. by branching on a strategic
+//! variable) and have the solver explore each of them. The subproblems are
+//! pushed onto the fringe when the search starts (and upon each restart).
+
+use std::sync::Arc;
+
+use crate::{Decision, Problem, SubProblem};
+
+/// A subproblem which was pushed by the user. Unlike a `SubProblem`, it owns
+/// its state: this way, it can be sent to another thread whenever the state
+/// can be sent.
+#[derive(Debug, Clone)]
+struct Pushed<State> {
+    state: State,
+    value: isize,
+    ub: isize,
+    path: Vec<Decision>,
+    bonus: u8,
+}
+
+/// The subproblems which were pushed by the user before the search starts
+#[derive(Debug, Clone)]
+pub(crate) struct ManualRoots<State> {
+    /// When set, the root of the problem is not pushed onto the fringe: only
+    /// the subproblems that were pushed by the user are explored
+    pub manual: bool,
+    /// The subproblems that were pushed by the user
+    subproblems: Vec<Pushed<State>>,
+}
+impl<State> Default for ManualRoots<State> {
+    fn default() -> Self {
+        Self { manual: false, subproblems: vec![] }
+    }
+}
+
+impl<State: Eq + Clone> ManualRoots<State> {
+    /// Validates the given subproblem and remembers it. Its children (and 
+    /// their descendants) inherit a priority bonus for `bonus` generations.
+    ///
+    /// # Panics
+    /// This panics if the path decides on some variable more than once, or
+    /// on a variable the problem does not have. When the debug assertions are
+    /// enabled, it also panics if replaying the path from the root does not
+    /// yield the given `state` and `lp_len`.
+    pub fn push(
+        &mut self,
+        problem: &dyn Problem<State = State>,
+        state: State,
+        lp_len: isize,
+        ub: isize,
+        path: Vec<Decision>,
+        bonus: u8,
+    ) {
+        let nb_vars = problem.nb_variables();
+        let mut decided = vec![false; nb_vars];
+        for d in path.iter() {
+            let id = d.variable.id();
+            assert!(id < nb_vars, "the path of a subproblem decides on an unknown variable");
+            assert!(!decided[id], "the path of a subproblem decides on a variable more than once");
+            decided[id] = true;
+        }
+        debug_assert!(Self::replays_to(problem, &path, &state, lp_len),
+            "replaying the path of a subproblem does not yield its state and value");
+
+        self.subproblems.push(Pushed { state, value: lp_len, ub, path, bonus });
+    }
+
+    /// Forgets all the subproblems that were pushed by the user
+    pub fn clear(&mut self) {
+        self.subproblems.clear();
+    }
+
+    /// Returns true iff applying the decisions of the `path` from the root
+    /// of the problem yields the given `state` and `value`
+    fn replays_to(problem: &dyn Problem<State = State>, path: &[Decision], state: &State, value: isize) -> bool {
+        let mut current = problem.initial_state();
+        let mut lp_len = problem.initial_value();
+        for d in path.iter().copied() {
+            let next = problem.transition(&current, d);
+            lp_len = lp_len.saturating_add(problem.transition_cost(&current, &next, d));
+            current = next;
+        }
+        current == *state && lp_len == value
+    }
+
+    /// Returns the subproblems which must be pushed onto the fringe when the
+    /// search starts: the `root` (unless the branching is manual) and all the
+    /// subproblems that were pushed by the user. None of them may have an
+    /// upper bound greater than `ub`.
+    pub fn roots(&self, root: SubProblem<State>, ub: isize) -> Vec<SubProblem<State>> {
+        let root = Some(root).filter(|_| !self.manual);
+        let pushed = self.subproblems.iter().map(|p| SubProblem {
+            state: Arc::new(p.state.clone()),
+            value: p.value,
+            path: p.path.clone(),
+            ub: p.ub,
+            depth: p.path.len(),
+            priority_bonus: p.bonus,
+        });
+        root.into_iter()
+            .chain(pushed)
+            .map(|mut sub| { sub.ub = sub.ub.min(ub); sub })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_manual_roots {
+    use std::sync::Arc;
+
+    use crate::{Decision, DecisionCallback, Problem, SubProblem, Variable};
+
+    use super::ManualRoots;
+
+    /// Each variable adds its value to the state
+    struct Sum;
+    impl Problem for Sum {
+        type State = isize;
+
+        fn nb_variables(&self) -> usize { 2 }
+        fn initial_state(&self) -> isize { 0 }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, state: &isize, d: Decision) -> isize { state + d.value }
+        fn transition_cost(&self, _: &isize, _: &isize, d: Decision) -> isize { d.value }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &isize>) -> Option<Variable> {
+            Some(Variable(depth)).filter(|_| depth < 2)
+        }
+        fn for_each_in_domain(&self, var: Variable, _: &isize, f: &mut dyn DecisionCallback) {
+            f.apply(Decision { variable: var, value: 0 });
+            f.apply(Decision { variable: var, value: 1 });
+        }
+    }
+    fn root() -> SubProblem<isize> {
+        SubProblem { state: Arc::new(0), value: 0, path: vec![], ub: isize::MAX, depth: 0, priority_bonus: 0 }
+    }
+    fn decide(var: usize, value: isize) -> Decision {
+        Decision { variable: Variable(var), value }
+    }
+
+    #[test]
+    fn by_default_only_the_root_is_pushed() {
+        let roots = ManualRoots::default();
+        assert_eq!(vec![root()], roots.roots(root(), isize::MAX));
+    }
+    #[test]
+    fn the_pushed_subproblems_replace_the_root_when_the_branching_is_manual() {
+        let mut roots = ManualRoots { manual: true, ..Default::default() };
+        roots.push(&Sum, 1, 1, 10, vec![decide(0, 1)], 0);
+        roots.push(&Sum, 0, 0, 5, vec![decide(0, 0)], 0);
+
+        let pushed = roots.roots(root(), 8);
+        assert_eq!(2, pushed.len());
+        assert_eq!(1, pushed[0].depth);
+        assert_eq!(8, pushed[0].ub);
+        assert_eq!(5, pushed[1].ub);
+    }
+    #[test]
+    fn the_pushed_subproblems_carry_their_priority_bonus() {
+        let mut roots = ManualRoots::default();
+        roots.push(&Sum, 1, 1, 10, vec![decide(0, 1)], 3);
+
+        let pushed = roots.roots(root(), isize::MAX);
+        assert_eq!(0, pushed[0].priority_bonus);
+        assert_eq!(3, pushed[1].priority_bonus);
+    }
+    #[test]
+    #[should_panic]
+    fn a_path_cannot_decide_twice_on_the_same_variable() {
+        let mut roots = ManualRoots::default();
+        roots.push(&Sum, 2, 2, 10, vec![decide(0, 1), decide(0, 1)], 0);
+    }
+    #[test]
+    #[should_panic]
+    fn a_path_cannot_decide_on_an_unknown_variable() {
+        let mut roots = ManualRoots::default();
+        roots.push(&Sum, 1, 1, 10, vec![decide(5, 1)], 0);
+    }
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn a_path_must_replay_to_the_state_of_the_subproblem() {
+        let mut roots = ManualRoots::default();
+        roots.push(&Sum, 0, 1, 10, vec![decide(0, 1)], 0);
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides a lightweight accounting of the memory used by the 
+//! solvers. It tells how large the main data structures (fringe, decision 
+//! diagrams, cache and dominance checker) grew during the search. 
+//! 
+//! # Note
+//! All the figures are *estimates* computed from the number of items stored
+//! in each data structure and the (shallow) size of these items. In particular,
+//! the heap data owned by the states (e.g. the content of a `Vec`), the paths
+//! of the subproblems and the overhead of the hash tables are not accounted 
+//! for. The actual resident set size of the process can optionally be sampled
+//! by enabling the `rss` feature (on Linux only).
+
+use std::mem::size_of;
+
+use crate::SubProblem;
+
+/// Returns the estimated size of an `Arc<T>` allocation (that is, the two 
+/// reference counters and the payload).
+pub fn arc_bytes<T>() -> usize {
+    2 * size_of::<usize>() + size_of::<T>()
+}
+/// Returns the estimated size of one subproblem with state `T` (its path
+/// excluded).
+pub fn subproblem_bytes<T>() -> usize {
+    size_of::<SubProblem<T>>() + arc_bytes::<T>()
+}
+
+/// This structure summarizes how much memory was used by the solver. Each 
+/// field holds the peak estimate for one data structure, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MemoryStats {
+    /// The peak size of the fringe
+    pub fringe_bytes: usize,
+    /// The peak size of the largest layer compiled by each worker, summed
+    /// over all the workers
+    pub mdd_bytes: usize,
+    /// The peak size of the cache
+    pub cache_bytes: usize,
+    /// The peak size of the dominance checker
+    pub dominance_bytes: usize,
+    /// The peak resident set size sampled whenever the best solution improved
+    /// and at the end of the search. This is only ever available when the 
+    /// `rss` feature is enabled.
+    pub rss_bytes: Option<usize>,
+}
+impl MemoryStats {
+    /// Returns the estimated peak memory usage of the solver. Because the
+    /// peaks of the various data structures need not occur at the same time,
+    /// this is an upper bound of the actual peak.
+    pub fn peak_bytes(&self) -> usize {
+        self.fringe_bytes + self.mdd_bytes + self.cache_bytes + self.dominance_bytes
+    }
+    /// Records the current size of the fringe
+    pub(crate) fn record_fringe(&mut self, bytes: usize) {
+        self.fringe_bytes = self.fringe_bytes.max(bytes);
+    }
+    /// Records the current size of the cache and of the dominance checker
+    /// and samples the resident set size (when available)
+    pub(crate) fn record_progress(&mut self, cache: usize, dominance: usize) {
+        self.cache_bytes = self.cache_bytes.max(cache);
+        self.dominance_bytes = self.dominance_bytes.max(dominance);
+        if let Some(rss) = resident_set_size() {
+            self.rss_bytes = Some(self.rss_bytes.unwrap_or(0).max(rss));
+        }
+    }
+}
+
+/// Returns the resident set size of the current process in bytes (if known)
+#[cfg(all(feature = "rss", target_os = "linux"))]
+pub fn resident_set_size() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb = line.split_whitespace().nth(1)?.parse::<usize>().ok()?;
+    Some(kb * 1024)
+}
+/// Returns the resident set size of the current process in bytes (if known)
+#[cfg(not(all(feature = "rss", target_os = "linux")))]
+pub fn resident_set_size() -> Option<usize> {
+    None
+}
+
+#[cfg(test)]
+mod test_memory {
+    use std::{sync::Arc, mem::size_of};
+
+    use crate::*;
+
+    #[test]
+    fn arc_bytes_accounts_for_the_counters() {
+        assert_eq!(2 * size_of::<usize>() + 8, arc_bytes::<u64>());
+        assert_eq!(2 * size_of::<usize>() + 24, arc_bytes::<Vec<u8>>());
+    }
+    #[test]
+    fn peak_is_the_sum_of_all_components() {
+        let stats = MemoryStats { fringe_bytes: 1, mdd_bytes: 20, cache_bytes: 300, dominance_bytes: 4000, rss_bytes: Some(1) };
+        assert_eq!(4321, stats.peak_bytes());
+    }
+    #[test]
+    fn recording_keeps_the_peak() {
+        let mut stats = MemoryStats::default();
+        stats.record_fringe(10);
+        stats.record_fringe(5);
+        stats.record_progress(3, 7);
+        stats.record_progress(2, 9);
+        assert_eq!(10, stats.fringe_bytes);
+        assert_eq!(3,  stats.cache_bytes);
+        assert_eq!(9,  stats.dominance_bytes);
+        assert_eq!(22, stats.peak_bytes());
+    }
+    #[test]
+    fn the_fringe_accounts_for_each_of_its_nodes() {
+        let mut fringe = SimpleFringe::new(MaxUB::new(&CharRanking));
+        for (i, c) in ['a', 'b', 'c', 'd', 'e'].into_iter().enumerate() {
+            fringe.push(SubProblem { state: Arc::new(c), value: i as isize, ub: 10, path: vec![], depth: 0, priority_bonus: 0 });
+        }
+        assert_eq!(5 * subproblem_bytes::<char>(), fringe.estimated_bytes());
+        fringe.pop();
+        assert_eq!(4 * subproblem_bytes::<char>(), fringe.estimated_bytes());
+    }
+    #[cfg(all(feature = "rss", target_os = "linux"))]
+    #[test]
+    fn the_resident_set_size_is_sampled() {
+        assert!(resident_set_size().is_some_and(|rss| rss > 0));
+    }
+
+    struct CharRanking;
+    impl StateRanking for CharRanking {
+        type State = char;
+        fn compare(&self, a: &char, b: &char) -> std::cmp::Ordering {
+            a.cmp(b)
+        }
+    }
+}
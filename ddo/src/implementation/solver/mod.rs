@@ -20,10 +20,56 @@
 //! This module provide the solver implementation.
 mod parallel;
 mod sequential;
+mod dual_refresh;
+mod restart;
+mod presolve;
+mod memory;
+mod diversity;
+mod dedup;
+mod solution_pool;
+mod autotune;
+mod manual;
+mod estimate;
+mod verify;
+mod latency;
+mod fringe_snapshot;
+mod tree_tracker;
+mod deadline;
+mod gap;
+mod closing;
+mod carry_over;
+mod incumbent;
+mod builder;
+mod profile;
+mod live_bounds;
+mod abort;
+mod diagnose;
+mod enumerate;
+#[cfg(feature = "checkpoint")]
+mod checkpoint;
 pub use parallel::*;
 pub use sequential::*;
+pub use dual_refresh::*;
+pub use restart::*;
+pub use presolve::*;
+pub use memory::*;
+pub use diversity::*;
+pub use autotune::*;
+pub use estimate::*;
+pub use latency::*;
+pub use fringe_snapshot::*;
+pub use closing::*;
+pub use incumbent::*;
+pub use builder::*;
+pub use profile::*;
+pub use live_bounds::*;
+pub use abort::*;
+pub use diagnose::*;
+pub use enumerate::*;
 
-use crate::{DefaultMDDLEL, EmptyCache, SimpleCache, DefaultMDDFC, Pooled};
+pub(crate) use restart::seed_of_run;
+
+use crate::{DefaultMDDLEL, EmptyCache, SimpleCache, DefaultMDDFC, DefaultMDDAdaptive, Pooled};
 
 /// A type alias to emphasize that this is the solver that should be used by default.
 pub type DefaultSolver<'a, State>        = ParNoCachingSolverLel<'a, State>;
@@ -31,17 +77,21 @@ pub type DefaultCachingSolver<'a, State> = ParCachingSolverFc<'a, State>;
 
 pub type ParNoCachingSolverLel<'a, State>   = ParallelSolver<'a, State, DefaultMDDLEL<State>, EmptyCache<State>>;
 pub type ParNoCachingSolverFc<'a, State>    = ParallelSolver<'a, State, DefaultMDDFC<State>,  EmptyCache<State>>;
+pub type ParNoCachingSolverAdaptive<'a, State>= ParallelSolver<'a, State, DefaultMDDAdaptive<State>, EmptyCache<State>>;
 pub type ParNoCachingSolverPooled<'a, State>= ParallelSolver<'a, State, Pooled<State>,        EmptyCache<State>>;
 
 pub type ParCachingSolverLel<'a, State>   = ParallelSolver<'a, State, DefaultMDDLEL<State>, SimpleCache<State>>;
 pub type ParCachingSolverFc<'a, State>    = ParallelSolver<'a, State, DefaultMDDFC<State>,  SimpleCache<State>>;
+pub type ParCachingSolverAdaptive<'a, State>= ParallelSolver<'a, State, DefaultMDDAdaptive<State>, SimpleCache<State>>;
 pub type ParCachingSolverPooled<'a, State>= ParallelSolver<'a, State, Pooled<State>,        SimpleCache<State>>;
 
 
 pub type SeqNoCachingSolverLel<'a, State>   = SequentialSolver<'a, State, DefaultMDDLEL<State>, EmptyCache<State>>;
 pub type SeqNoCachingSolverFc<'a, State>    = SequentialSolver<'a, State, DefaultMDDFC<State>,  EmptyCache<State>>;
+pub type SeqNoCachingSolverAdaptive<'a, State>= SequentialSolver<'a, State, DefaultMDDAdaptive<State>, EmptyCache<State>>;
 pub type SeqNoCachingSolverPooled<'a, State>= SequentialSolver<'a, State, Pooled<State>,        EmptyCache<State>>;
 
 pub type SeqCachingSolverLel<'a, State>   = SequentialSolver<'a, State, DefaultMDDLEL<State>, SimpleCache<State>>;
 pub type SeqCachingSolverFc<'a, State>    = SequentialSolver<'a, State, DefaultMDDFC<State>,  SimpleCache<State>>;
+pub type SeqCachingSolverAdaptive<'a, State>= SequentialSolver<'a, State, DefaultMDDAdaptive<State>, SimpleCache<State>>;
 pub type SeqCachingSolverPooled<'a, State>= SequentialSolver<'a, State, Pooled<State>,        SimpleCache<State>>;
\ No newline at end of file
@@ -22,11 +22,23 @@
 //! By default, it uses as many threads as the number of hardware threads
 //! available on the machine.
 use std::clone::Clone;
-use std::{marker::PhantomData, sync::Arc, hash::Hash};
+use std::{marker::PhantomData, sync::Arc, hash::Hash, time::{Duration, Instant}};
 
 use parking_lot::{Condvar, Mutex};
 
-use crate::{Fringe, Decision, Problem, Relaxation, StateRanking, WidthHeuristic, Cutoff, SubProblem, DecisionDiagram, CompilationInput, CompilationType, Solver, Solution, Completion, Reason, Cache, DominanceChecker};
+use crate::{Fringe, Decision, Problem, Relaxation, StateRanking, WidthHeuristic, Cutoff, SubProblem, DecisionDiagram, CompilationInput, CompilationType, Solver, Solution, Completion, Reason, Termination, Cache, DominanceChecker, DualRefresh, DualRefreshRecord, Restarts, RestartRecord, PresolveConfig, PresolveRecord, Statistics, Reseed, CarryOver, IncumbentListener, Progress, ProgressEvent, SharedIncumbent, BoundsHandle, AbortHandle, MemoryStats, Diversity, DiverseSolution, EstimateReport, WarmStartError};
+use crate::eval::check_warm_start;
+
+use super::dual_refresh::{DualRefreshSchedule, compile_root_relaxation, sweep_fringe};
+use super::restart::RestartTracker;
+use super::presolve::compile_root_restriction;
+use super::diversity::DiversityPool;
+use super::dedup::{SeenSolutions, canonical_key};
+use super::manual::ManualRoots;
+use super::estimate::TreeEstimate;
+use super::deadline::{Deadline, TimeLimit};
+use super::gap::{relative_gap, target_reason, GapTolerance};
+use super::carry_over::{notify_carry_over, CompiledNode};
 
 /// The shared data that may only be manipulated within critical sections
 struct Critical<'a, State> {
@@ -62,23 +74,90 @@ struct Critical<'a, State> {
     first_active_layer: usize,
     /// This is the value of the best known lower bound.
     best_lb: isize,
-    /// This is the value of the best known lower bound.
-    /// *WARNING* This one only gets set when the interrupt condition is satisfied
+    /// This is the value of the best known upper bound. It is refreshed 
+    /// whenever a node is popped from the fringe or done being processed.
     best_ub: isize,
     /// If set, this keeps the info about the best solution so far.
     best_sol: Option<Vec<Decision>>,
+    /// The state of the terminal node reached by the best solution so far
+    /// (None when that solution was set with `set_primal`)
+    best_state: Option<State>,
     /// This vector is used to store the upper bound on the node which is
     /// currently processed by each thread.
     ///
     /// # Note
     /// When a thread is idle (or more generally when it is done with processing
-    /// it node), it should place the value i32::min_value() in its corresponding
+    /// it node), it should place the value isize::MIN in its corresponding
     /// cell.
     upper_bounds: Vec<isize>,
     /// If we decide not to go through a complete proof of optimality, this is
     /// the reason why we took that decision.
     abort_proof: Option<Reason>,
+    /// If set, this tells when the solver should recompile a wider relaxation of
+    /// the root problem in order to tighten its best upper bound.
+    dual_refresh: Option<DualRefreshSchedule>,
+    /// If set, this tells when the solver should restart its search
+    restarts: Option<RestartTracker>,
+    /// The estimated memory usage of the solver (all workers included)
+    memory: MemoryStats,
+    /// The statistics of the search (see `Statistics`). The workers keep 
+    /// their own compilation counters and add them up when they are done.
+    stats: Statistics,
+    /// If set, the pool of diverse solutions maintained by the solver
+    diversity: Option<DiversityPool>,
+    /// If set, the solutions which were recently offered to the pool of 
+    /// diverse solutions are not offered again
+    dedup: Option<SeenSolutions>,
+    /// The number of relaxed DDs that have been compiled once again with a
+    /// wider width
+    recompiled: usize,
+    /// The number of cut-set nodes whose upper bound was tightened by the 
+    /// best value of the relaxed DD they were taken from
+    clamped: usize,
+    /// The number of nodes which were pushed onto the fringe with a priority
+    /// bonus
+    boosted: usize,
+    /// The upper bound derived from the first compilation of the root problem
+    root_bound: Option<isize>,
+    /// The value of the first solution found by the search
+    first_incumbent: Option<isize>,
+    /// The subproblems pushed with `push_subproblem` (see `with_manual_root`)
+    roots: ManualRoots<State>,
+    /// If set, the size of the search tree is estimated when the search starts
+    tree_estimate: Option<TreeEstimate>,
+    /// Who gets told about each new best solution (see `with_incumbent_listener`)
+    incumbent_listener: Option<&'a mut (dyn IncumbentListener + Send)>,
+    /// Who gets told about the progress of the search (see `with_progress`)
+    progress: Option<&'a mut (dyn Progress + Send)>,
+}
+impl<State> Critical<'_, State> {
+    /// Commits the given solution as the new incumbent when it is strictly
+    /// better than the current one. Solutions of equal value are ordered by
+    /// their canonical decision vector (the decisions sorted by variable,
+    /// compared lexicographically), the smallest one being preferred. This way,
+    /// the incumbent which sticks does not depend on the order in which the
+    /// workers happen to find their solutions.
+    ///
+    /// It returns true iff the value of the incumbent has improved.
+    fn commit_incumbent(&mut self, value: isize, solution: Solution, state: Option<State>) -> bool {
+        let improved = value > self.best_lb;
+        let replace = improved || (value == self.best_lb && self.best_sol.as_ref()
+            .is_none_or(|incumbent| canonical_key(&solution) < canonical_key(incumbent)));
+        if replace {
+            self.best_lb = value;
+            self.best_sol = Some(solution);
+            self.best_state = state;
+        }
+        improved
+    }
+    /// Tells the progress listener (if any) about the given event
+    fn report(&mut self, event: ProgressEvent) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.report(&event);
+        }
+    }
 }
+
 /// The state which is shared among the many running threads: it provides an
 /// access to the critical data (protected by a mutex) as well as a monitor
 /// (condvar) to park threads in case of node-starvation.
@@ -102,6 +181,38 @@ struct Shared<'a, State, C> where
     /// Data structure containing info about past compilations used to prune the search
     cache: C,
     dominance: &'a (dyn DominanceChecker<State = State> + Send + Sync),
+    /// The randomized heuristics which must be re-seeded upon restart
+    reseed: Option<&'a (dyn Reseed + Send + Sync)>,
+    /// If set, this tells how to presolve the problem before the search
+    presolve: Option<PresolveConfig>,
+    /// The outcome of the presolve (if it was run)
+    presolve_record: Option<PresolveRecord>,
+    /// The adaptive heuristic which learns from each compilation
+    carry_over: Option<&'a (dyn CarryOver + Send + Sync)>,
+    /// If set, a relaxed DD whose cut-set comprises more than this many nodes
+    /// is compiled once again with twice the width before being enqueued
+    wider_recompile: Option<usize>,
+    /// The subproblems whose upper bound does not exceed the best lower bound
+    /// by more than this slack are pruned
+    absolute_slack: isize,
+    /// The handle on the incumbent which other threads may read (see 
+    /// `with_shared_incumbent`)
+    shared_incumbent: Option<SharedIncumbent>,
+    /// The handle on the best bounds which other threads may read (see 
+    /// `bounds_handle`)
+    bounds: BoundsHandle,
+    /// The flag which other threads may set to stop the search (see 
+    /// `abort_handle`)
+    abort: AbortHandle,
+    /// If set, the search stops once this many nodes have been explored
+    node_budget: Option<usize>,
+    /// The wall-clock time `maximize` may run (see `Solver::set_time_limit`)
+    time_limit: TimeLimit,
+    /// The search stops once the relative gap drops below this tolerance
+    gap_tolerance: GapTolerance,
+    /// If set, the search stops once it is known whether a solution reaches
+    /// this value (see `with_target`)
+    target: Option<isize>,
 
     /// This is the shared state data which can only be accessed within critical
     /// sections. Therefore, it is protected by a mutex which prevents concurrent
@@ -124,6 +235,50 @@ enum WorkLoad<T> {
     WorkItem { node: SubProblem<T> },
 }
 
+/// The outcome of the processing of a node, as far as the critical data is
+/// concerned. A deterministic search (see `with_deterministic`) gathers it 
+/// while the nodes of a round are processed, and only applies it once the 
+/// whole round is over.
+struct Deferred<State> {
+    /// The best lower bound when the round started, raised by the solutions
+    /// found while processing the node
+    best_lb: isize,
+    /// Whether the exact solutions must be offered to the pool of diverse
+    /// solutions
+    diversity: bool,
+    /// The exact solutions to offer to the pool of diverse solutions
+    diverse: Vec<(isize, Solution)>,
+    /// The best solution of each DD that might become the incumbent
+    incumbents: Vec<(isize, Solution, Option<State>)>,
+    /// The bound derived from the first compilation of a root problem
+    root_bound: Option<isize>,
+    /// The nodes of the cut-set of the relaxed DD
+    cutset: Vec<SubProblem<State>>,
+    /// The upper bound of the node, the best value of its relaxed DD and the
+    /// priority bonus of its cut-set nodes (None when there is no cut-set)
+    cutset_bounds: Option<(isize, isize, u8)>,
+}
+impl<State: Clone> Deferred<State> {
+    fn new(best_lb: isize, diversity: bool) -> Self {
+        Self { best_lb, diversity, diverse: vec![], incumbents: vec![], root_bound: None, cutset: vec![], cutset_bounds: None }
+    }
+    /// Keeps the solutions of the given DD which might improve the incumbent
+    /// (see `maybe_update_best`)
+    fn collect<D: DecisionDiagram<State = State>>(&mut self, mdd: &D) {
+        if self.diversity {
+            let diverse = &mut self.diverse;
+            mdd.for_each_exact_solution(&mut |value, solution| diverse.push((value, solution)));
+        }
+        let dd_best_value = mdd.best_exact_value().unwrap_or(isize::MIN);
+        if dd_best_value < self.best_lb {
+            return;
+        }
+        let Some(solution) = mdd.best_exact_solution() else { return };
+        self.best_lb = dd_best_value;
+        self.incumbents.push((dd_best_value, solution, mdd.best_exact_state().cloned()));
+    }
+}
+
 
 /// This is the structure implementing a multi-threaded MDD solver.
 ///
@@ -294,6 +449,9 @@ where D: DecisionDiagram<State = State> + Default,
     /// will be spawned to solve the problem. By default, this number amounts
     /// to the number of hardware threads available on the machine.
     nb_threads: usize,
+    /// When set, the nodes are processed in rounds so that the search is 
+    /// reproducible (see `with_deterministic`)
+    deterministic: bool,
     /// This is just a marker that allows us to remember the exact type of the
     /// mdds to be instantiated.
     _phantom: PhantomData<D>, 
@@ -336,13 +494,27 @@ where
                 cutoff,
                 cache: C::default(),
                 dominance,
+                reseed: None,
+                presolve: None,
+                presolve_record: None,
+                carry_over: None,
+                wider_recompile: None,
+                absolute_slack: 0,
+                shared_incumbent: None,
+                bounds: BoundsHandle::default(),
+                abort: AbortHandle::default(),
+                node_budget: None,
+                time_limit: TimeLimit::default(),
+                gap_tolerance: GapTolerance::default(),
+                target: None,
                 //
                 monitor: Condvar::new(),
                 critical: Mutex::new(Critical {
                     best_sol: None,
+                    best_state: None,
                     best_lb: isize::MIN,
                     best_ub: isize::MAX,
-                    upper_bounds: vec![isize::MAX; nb_threads],
+                    upper_bounds: vec![isize::MIN; nb_threads],
                     fringe,
                     ongoing: 0,
                     explored: 0,
@@ -350,9 +522,25 @@ where
                     ongoing_by_layer: vec![0; problem.nb_variables() + 1],
                     first_active_layer: 0,
                     abort_proof: None,
+                    dual_refresh: None,
+                    restarts: None,
+                    memory: MemoryStats::default(),
+                    stats: Statistics::default(),
+                    diversity: None,
+                    dedup: None,
+                    recompiled: 0,
+                    clamped: 0,
+                    boosted: 0,
+                    root_bound: None,
+                    first_incumbent: None,
+                    roots: ManualRoots::default(),
+                    tree_estimate: None,
+                    incumbent_listener: None,
+                    progress: None,
                 }),
             },
             nb_threads,
+            deterministic: false,
             _phantom: Default::default(),
         }
     }
@@ -361,26 +549,363 @@ where
         self.nb_threads = nb_threads;
         self
     }
+    /// Makes the search reproducible: two runs on the same problem with the
+    /// same number of threads explore the same nodes in the same order, 
+    /// report the same events (see `with_progress`), gather the same 
+    /// statistics (the timings aside) and end up with the same solution. To
+    /// that end, the nodes are processed in rounds of `nb_threads` nodes. All
+    /// the nodes of a round are popped before any of them is processed, they
+    /// are compiled with the best lower bound known when the round starts, and
+    /// their outcomes (the new incumbents and the cut-sets) are applied in the
+    /// order in which they were popped once the whole round is over.
+    ///
+    /// # Performance
+    /// Each round ends with a barrier: the workers which are done with their
+    /// node wait for the slowest one before they can start the next round,
+    /// and the nodes of a round cannot prune anything with the solutions that
+    /// are found by the other nodes of the same round. Expect the search to be
+    /// noticeably slower than the default one, all the more so as the time it
+    /// takes to process a node varies.
+    ///
+    /// # Note
+    /// A run remains subject to its time limit, to its cutoff and to its abort
+    /// handle. Besides, it is only reproducible when the randomized heuristics
+    /// are seeded the same way (see `with_reseed`), and when the cache, the 
+    /// dominance checker and the adaptive heuristics (see `with_carry_over`)
+    /// do not depend on the order in which concurrent compilations consult
+    /// them (e.g. `EmptyCache` and `EmptyDominanceChecker`).
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+    /// Enables the periodic refresh of the dual bound (see `DualRefresh`).
+    /// 
+    /// # Note
+    /// The refresh is carried out by one of the worker threads while the 
+    /// others keep on processing the nodes from the fringe.
+    pub fn with_dual_refresh(mut self, policy: DualRefresh) -> Self {
+        self.shared.critical.get_mut().dual_refresh = Some(DualRefreshSchedule::new(policy));
+        self
+    }
+    /// Returns the outcome of all the dual bound refreshes that have occurred
+    pub fn dual_refreshes(&self) -> Vec<DualRefreshRecord> {
+        self.shared.critical.lock().dual_refresh.as_ref().map(|s| s.records.clone()).unwrap_or_default()
+    }
+    /// Enables the restarts of the search (see `Restarts`).
+    ///
+    /// # Note
+    /// When a restart is due, the threads stop picking up new nodes until all
+    /// ongoing nodes have been processed. This way, the randomized heuristics
+    /// are never re-seeded while a decision diagram is being compiled.
+    pub fn with_restarts(mut self, policy: Restarts) -> Self {
+        self.shared.critical.get_mut().restarts = Some(RestartTracker::new(policy));
+        self
+    }
+    /// Enables the presolve phase which seeds the search with the best of a 
+    /// few restricted compilations of the root problem (see `PresolveConfig`).
+    /// The presolve is carried out by one single thread.
+    pub fn with_presolve(mut self, config: PresolveConfig) -> Self {
+        self.shared.presolve = Some(config);
+        self
+    }
+    /// Returns the outcome of the presolve phase (None until it has been run)
+    pub fn presolve_record(&self) -> Option<PresolveRecord> {
+        self.shared.presolve_record
+    }
+    /// Sets the randomized heuristic which is re-seeded before each run of 
+    /// the search when restarts are enabled (and before each iteration of 
+    /// the presolve)
+    pub fn with_reseed(mut self, reseed: &'a (dyn Reseed + Send + Sync)) -> Self {
+        self.shared.reseed = Some(reseed);
+        self
+    }
+    /// Tells the given listener about each new best solution found while 
+    /// searching, be it by a restricted or by a relaxed DD. The listener is
+    /// called by the worker which found the solution, one call at a time.
+    pub fn with_incumbent_listener(mut self, listener: &'a mut (dyn IncumbentListener + Send)) -> Self {
+        self.shared.critical.get_mut().incumbent_listener = Some(listener);
+        self
+    }
+    /// Tells the given listener about the progress of the search: each node
+    /// which is explored, each new best solution and the end of the search 
+    /// (see `ProgressEvent`). By default, the progress is not reported.
+    pub fn with_progress(mut self, progress: &'a mut (dyn Progress + Send)) -> Self {
+        self.shared.critical.get_mut().progress = Some(progress);
+        self
+    }
+    /// Publishes each new incumbent to the given handle, which any thread may
+    /// read while the search is running
+    pub fn with_shared_incumbent(mut self, incumbent: &SharedIncumbent) -> Self {
+        self.shared.shared_incumbent = Some(incumbent.clone());
+        self
+    }
+    /// Returns a handle on the best bounds of the solver, which any thread 
+    /// may poll while the search is running. The handle remains valid across
+    /// the calls to `maximize` and `reset`.
+    pub fn bounds_handle(&self) -> BoundsHandle {
+        self.shared.bounds.clone()
+    }
+    /// Returns a handle which any thread may use to stop the search (see 
+    /// `AbortHandle`). The handle remains valid across the calls to 
+    /// `maximize` and `reset`.
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.shared.abort.clone()
+    }
+    /// Sets the adaptive heuristic which is told about the outcome of each
+    /// compilation (see `CarryOver` for the order of the calls). The calls
+    /// made by the different threads may interleave.
+    pub fn with_carry_over(mut self, carry_over: &'a (dyn CarryOver + Send + Sync)) -> Self {
+        self.shared.carry_over = Some(carry_over);
+        self
+    }
+    /// Returns the progress made during each run of the search that was 
+    /// interrupted by a restart
+    pub fn restarts(&self) -> Vec<RestartRecord> {
+        self.shared.critical.lock().restarts.as_ref().map(|r| r.records.clone()).unwrap_or_default()
+    }
+    /// Returns the progress made during each run of the search, the ongoing 
+    /// (or last) run included. The nodes explored by all these runs add up 
+    /// to `explored`. This is empty unless the restarts have been enabled.
+    pub fn runs(&self) -> Vec<RestartRecord> {
+        let critical = self.shared.critical.lock();
+        critical.restarts.as_ref()
+            .map(|r| r.runs(critical.explored, critical.best_lb, critical.best_ub))
+            .unwrap_or_default()
+    }
+    /// Enables the pool of diverse solutions (see `Diversity`)
+    pub fn with_diversity(mut self, policy: Diversity) -> Self {
+        self.shared.critical.get_mut().diversity = Some(DiversityPool::new(policy));
+        self
+    }
+    /// Returns the solutions of the pool of diverse solutions by decreasing
+    /// value. This is empty unless the diversity mode has been enabled.
+    pub fn diverse_solutions(&self) -> Vec<DiverseSolution> {
+        self.shared.critical.lock().diversity.as_ref().map(|p| p.solutions()).unwrap_or_default()
+    }
+    /// Keeps the solver from offering the same solution to the pool of diverse
+    /// solutions more than once. The solver remembers the last `capacity` 
+    /// solutions it has offered to the pool; the duplicates of these are 
+    /// suppressed (see `suppressed_duplicates`).
+    pub fn with_solution_dedup(mut self, capacity: usize) -> Self {
+        self.shared.critical.get_mut().dedup = Some(SeenSolutions::new(capacity));
+        self
+    }
+    /// Returns the number of duplicate solutions which were not offered to the
+    /// pool of diverse solutions (see `with_solution_dedup`)
+    pub fn suppressed_duplicates(&self) -> usize {
+        self.shared.critical.lock().dedup.as_ref().map(|d| d.suppressed()).unwrap_or_default()
+    }
+    /// Whenever the exact cut-set of a relaxed DD comprises more than 
+    /// `threshold` nodes, the relaxed DD is compiled once again with twice
+    /// the maximum width before its cut-set is enqueued. Only the cut-set of
+    /// the wider DD ends up in the fringe.
+    pub fn with_wider_recompile(mut self, threshold: usize) -> Self {
+        self.shared.wider_recompile = Some(threshold);
+        self
+    }
+    /// Prunes every subproblem whose upper bound does not exceed the best
+    /// known lower bound by more than `slack`. This trades the proof of 
+    /// optimality for speed: the value of the solution which is eventually
+    /// returned lies within `slack` of the optimum (see `Completion`).
+    pub fn with_absolute_slack(mut self, slack: isize) -> Self {
+        self.shared.absolute_slack = slack;
+        self
+    }
+    /// Stops the search once `budget` nodes have been explored. The nodes 
+    /// being processed when the budget runs out are processed to their end so
+    /// that the incumbent remains consistent (see `Solver::set_node_budget`).
+    pub fn with_max_explored(mut self, budget: usize) -> Self {
+        self.shared.node_budget = Some(budget);
+        self
+    }
+    /// Stops the search as soon as the relative gap between the best upper
+    /// and lower bounds drops below `tolerance` (0.01 means within 1% of the 
+    /// optimum). The completion then tells so with `stopped_by` set to 
+    /// `Reason::GapReached` and reports the `achieved_gap`. A zero tolerance
+    /// (the default) lets the search run until it proves optimality.
+    pub fn with_gap_tolerance(mut self, tolerance: f64) -> Self {
+        self.shared.gap_tolerance = GapTolerance(tolerance);
+        self
+    }
+    /// Stops the search as soon as it finds a solution whose value is at 
+    /// least `target`; the completion then tells so with `stopped_by` set to
+    /// `Reason::TargetReached`, and the best solution is the one that reached
+    /// the target. Conversely, the search stops with `Reason::TargetUnreachable`
+    /// as soon as the relaxed DDs prove that no solution can reach the target.
+    /// When the search is carried out to its end without reaching the target,
+    /// the best value is exact but `stopped_by` is `Reason::TargetUnreachable`
+    /// as well.
+    pub fn with_target(mut self, target: isize) -> Self {
+        self.shared.target = Some(target);
+        self
+    }
+    /// Returns the number of relaxed DDs that have been compiled once again
+    /// because their cut-set was too large (see `with_wider_recompile`)
+    pub fn wider_recompilations(&self) -> usize {
+        self.shared.critical.lock().recompiled
+    }
+    /// Returns the number of cut-set nodes whose upper bound was tightened by
+    /// the best value of the relaxed DD they came from. This is zero unless 
+    /// the DD derives looser bounds for its cut-set than its own best value.
+    pub fn clamped_cutset_ubs(&self) -> usize {
+        self.shared.critical.lock().clamped
+    }
+    /// Returns the number of nodes which were pushed onto the fringe with a
+    /// priority bonus (see `push_boosted_subproblem`)
+    pub fn boosted_nodes(&self) -> usize {
+        self.shared.critical.lock().boosted
+    }
+    /// Starts the search with an empty fringe rather than with the root of 
+    /// the problem. Only the subproblems that are pushed with `push_subproblem`
+    /// are explored, which lets you split the root of the problem yourself
+    /// (e.g. by branching on some strategic variable).
+    ///
+    /// # Important
+    /// The search only proves optimality if the pushed subproblems cover all
+    /// the solutions of the problem.
+    pub fn with_manual_root(mut self) -> Self {
+        self.shared.critical.get_mut().roots.manual = true;
+        self
+    }
+    /// Pushes a subproblem which is explored as soon as the search starts (and
+    /// upon each restart). Its `state` is reached from the root of the problem 
+    /// by applying the decisions of `path`; which yields a value of `lp_len`.
+    /// The `ub` is an upper bound on the objective reachable in the subproblem.
+    /// The pushed subproblems are forgotten when the solver is `reset`.
+    ///
+    /// # Panics
+    /// This panics if the path decides on some variable more than once, or
+    /// on a variable the problem does not have. When the debug assertions are
+    /// enabled, it also panics if replaying the `path` does not yield the
+    /// given `state` and `lp_len`.
+    pub fn push_subproblem(&mut self, state: State, lp_len: isize, ub: isize, path: Vec<Decision>) {
+        let problem = self.shared.problem;
+        self.shared.critical.get_mut().roots.push(problem, state, lp_len, ub, path, 0);
+    }
+    /// Pushes a subproblem just like `push_subproblem` does, except that the
+    /// subproblem carries a priority bonus which its descendants inherit for
+    /// `bonus` generations (the bonus decreases by one at each generation).
+    /// The bonus only matters when the fringe is ordered by a `BonusFirst`
+    /// ranking, which explores the nodes carrying the largest bonus first.
+    pub fn push_boosted_subproblem(&mut self, state: State, lp_len: isize, ub: isize, path: Vec<Decision>, bonus: u8) {
+        let problem = self.shared.problem;
+        self.shared.critical.get_mut().roots.push(problem, state, lp_len, ub, path, bonus);
+    }
+    /// Returns the upper bound that was derived from the first compilation
+    /// of the root problem (that is, the value of its relaxed DD or its optimum
+    /// when the restricted DD is exact). It returns None as long as the root
+    /// has not been processed.
+    pub fn root_bound(&self) -> Option<isize> {
+        self.shared.critical.lock().root_bound
+    }
+    /// Returns the value of the first solution that was found by the search,
+    /// if any. (A solution set with `set_primal` is not found by the search)
+    pub fn first_incumbent(&self) -> Option<isize> {
+        self.shared.critical.lock().first_incumbent
+    }
+    /// Returns a copy of the terminal state which is reached by the best
+    /// solution found so far. This is None when no solution was found, or when
+    /// the best solution is the primal that was set with `set_primal`.
+    pub fn best_state(&self) -> Option<State> {
+        self.shared.critical.lock().best_state.clone()
+    }
+    /// Estimates the size of the search tree with `samples` random dives
+    /// (see `estimate_tree_size`) when the search starts. The dives prune the
+    /// nodes which cannot improve on the primal set with `set_primal`, if any.
+    pub fn with_tree_estimate(mut self, samples: usize, seed: u64) -> Self {
+        self.shared.critical.get_mut().tree_estimate = Some(TreeEstimate::new(samples, seed));
+        self
+    }
+    /// Returns the estimated size of the search tree (see `with_tree_estimate`).
+    /// It returns None as long as the search has not started.
+    pub fn tree_estimate(&self) -> Option<EstimateReport> {
+        self.shared.critical.lock().tree_estimate.and_then(|e| e.report)
+    }
+    /// Returns the estimated memory usage of the solver (see `MemoryStats`).
+    /// The figures of the decision diagrams are summed over all workers.
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.shared.critical.lock().memory
+    }
 
     /// This method initializes the problem resolution. Put more simply, this
-    /// method posts the root node of the mdd onto the fringe so that a thread
-    /// can pick it up and the processing can be bootstrapped.
+    /// method posts the root node of the mdd (or the subproblems pushed with
+    /// `push_subproblem`) onto the fringe so that a thread can pick it up and
+    /// the processing can be bootstrapped.
     fn initialize(&mut self) {
-        let root = self.root_node();
+        let root = Self::root_node(&self.shared);
         self.shared.cache.initialize(self.shared.problem);
+        self.shared.time_limit.start();
+        self.run_presolve(&root);
         let mut critical = self.shared.critical.lock();
-        critical.fringe.push(root);
-        critical.open_by_layer[0] += 1;
+        if let Some(restarts) = critical.restarts.as_mut() {
+            restarts.start_clock();
+        }
+        let best_lb = critical.best_lb;
+        if let Some(estimate) = critical.tree_estimate.as_mut() {
+            estimate.run(self.shared.problem, self.shared.relaxation, best_lb);
+        }
+        if let (Some(restarts), Some(reseed)) = (critical.restarts.as_ref(), self.shared.reseed) {
+            reseed.reseed(restarts.seed());
+        }
+        Self::push_roots(&mut critical, root, isize::MAX);
+        let fringe_bytes = critical.fringe.estimated_bytes();
+        critical.memory.record_fringe(fringe_bytes);
     }
 
-    fn root_node(&self) -> SubProblem<State> {
+    /// Compiles the restricted DDs of the presolve phase (if it is enabled)
+    /// and records how much they improved the best lower bound
+    fn run_presolve(&mut self, root: &SubProblem<State>) {
+        let Some(config) = self.shared.presolve.clone() else { return };
         let shared = &self.shared;
+        let start = Instant::now();
+        let lb_before = Self::best_lb(shared);
+        let default_width = shared.width_heu.max_width(root);
+        let cutoff = Deadline { cutoff: shared.cutoff, limit: shared.time_limit, abort: shared.abort.clone() };
+        let mut record = PresolveRecord { iterations: 0, improving: 0, lb_before, lb_after: lb_before, elapsed: Duration::ZERO };
+        let mut mdd = D::default();
+        for i in 0..config.iterations {
+            if let Some(reseed) = shared.reseed {
+                reseed.reseed(config.seed(i));
+            }
+            let best_lb = Self::best_lb(shared);
+            let outcome = compile_root_restriction(&mut mdd, shared.problem, shared.relaxation, 
+                shared.ranking, &cutoff, config.width(i, default_width), best_lb);
+            let Ok(is_exact) = outcome else { break };
+            Self::maybe_update_best(&mdd, shared);
+            record.iterations += 1;
+            record.improving += usize::from(Self::best_lb(shared) > best_lb);
+            if is_exact {
+                break;
+            }
+        }
+        record.lb_after = Self::best_lb(shared);
+        record.elapsed = start.elapsed();
+        self.shared.presolve_record = Some(record);
+    }
+
+    /// Pushes the given `root` (unless the branching is manual) along with 
+    /// the subproblems pushed with `push_subproblem` onto the fringe
+    fn push_roots(critical: &mut Critical<'a, State>, root: SubProblem<State>, ub: isize) {
+        for sub in critical.roots.roots(root, ub) {
+            let depth = sub.depth;
+            critical.boosted += usize::from(sub.priority_bonus > 0);
+            let before = critical.fringe.len();
+            critical.fringe.push(sub);
+            critical.open_by_layer[depth] += critical.fringe.len() - before;
+        }
+        critical.stats.peak_fringe = critical.stats.peak_fringe.max(critical.fringe.len());
+        let open_by_layer = &mut critical.open_by_layer;
+        critical.fringe.shed_excess(&mut |depth| open_by_layer[depth] -= 1);
+    }
+
+    fn root_node(shared: &Shared<'a, State, C>) -> SubProblem<State> {
         SubProblem {
             state: Arc::new(shared.problem.initial_state()),
             value: shared.problem.initial_value(),
             path: vec![],
             ub: isize::MAX,
             depth: 0,
+            priority_bonus: 0,
         }
     }
 
@@ -388,49 +913,85 @@ where
     /// best lower bound from the critical data. Then it expands a restricted
     /// and possibly a relaxed mdd rooted in `node`. If that is necessary,
     /// it stores cut-set nodes onto the fringe for further parallel processing.
+    ///
+    /// When `deferred` is set, the outcome of the processing is kept there 
+    /// instead of being applied to the critical data right away (see 
+    /// `with_deterministic`).
     fn process_one_node(
         mdd: &mut D,
         shared: &Shared<'a, State, C>,
+        stats: &mut Statistics,
         node: SubProblem<State>,
+        mut deferred: Option<&mut Deferred<State>>,
     ) -> Result<(), Reason> {
         // 1. RESTRICTION
         let node_ub = node.ub;
-        let best_lb = Self::best_lb(shared);
+        let bonus = node.priority_bonus.saturating_sub(1);
+        let best_lb = Self::current_lb(shared, deferred.as_deref());
 
-        if node_ub <= best_lb {
+        if node_ub <= best_lb.saturating_add(shared.absolute_slack) {
+            stats.pruned_by_bound += 1;
             return Ok(());
         }
 
         let width = shared.width_heu.max_width(&node);
+        let cutoff = Deadline { cutoff: shared.cutoff, limit: shared.time_limit, abort: shared.abort.clone() };
         let mut compilation = CompilationInput {
             comp_type: CompilationType::Restricted,
             max_width: width,
             problem: shared.problem,
             relaxation: shared.relaxation,
             ranking: shared.ranking,
-            cutoff: shared.cutoff,
-            residual: &node,
+            cutoff: &cutoff,
             //
             best_lb,
             cache: &shared.cache,
             dominance: shared.dominance,
         };
 
-        let Completion{is_exact, ..} = mdd.compile(&compilation)?;
-        Self::maybe_update_best(mdd, shared);
+        let is_root = node.depth == 0;
+        let compiled = CompiledNode { depth: node.depth, path_len: node.path.len(), ub: node_ub };
+        let carry_over = shared.carry_over.map(|c| c as &dyn CarryOver);
+        let start = Instant::now();
+        let Completion{is_exact, ..} = mdd.compile(&compilation, node)?;
+        stats.record_compilation(CompilationType::Restricted, start.elapsed());
+        Self::offer_best(mdd, shared, deferred.as_deref_mut());
+        notify_carry_over(carry_over, mdd, CompilationType::Restricted, compiled, is_exact);
         if is_exact {
+            Self::maybe_record_root_bound(mdd, shared, is_root, best_lb, deferred);
             return Ok(());
         }
 
         // 2. RELAXATION
-        let best_lb = Self::best_lb(shared);
+        let best_lb = Self::current_lb(shared, deferred.as_deref());
         compilation.comp_type = CompilationType::Relaxed;
         compilation.best_lb = best_lb;
 
-        let Completion{is_exact, ..} = mdd.compile(&compilation)?;
-        Self::maybe_update_best(mdd, shared);
+        // the restricted dd gives the node back instead of it being cloned
+        let node = mdd.take_residual().expect("the restricted dd was compiled from the node");
+        let start = Instant::now();
+        let Completion{mut is_exact, ..} = mdd.compile(&compilation, node)?;
+        stats.record_compilation(CompilationType::Relaxed, start.elapsed());
+        Self::offer_best(mdd, shared, deferred.as_deref_mut());
+        Self::maybe_record_root_bound(mdd, shared, is_root, compilation.best_lb, deferred.as_deref_mut());
+        notify_carry_over(carry_over, mdd, CompilationType::Relaxed, compiled, is_exact);
+
+        // 3. WIDER RELAXATION (when the cut-set is too large)
+        if let (false, Some(threshold)) = (is_exact, shared.wider_recompile) {
+            if mdd.cutset_len() > threshold {
+                let node = mdd.take_residual().expect("the relaxed dd was compiled from the node");
+                compilation.max_width = width.saturating_mul(2);
+                compilation.best_lb = Self::current_lb(shared, deferred.as_deref());
+                let start = Instant::now();
+                is_exact = mdd.compile(&compilation, node)?.is_exact;
+                stats.record_compilation(CompilationType::Relaxed, start.elapsed());
+                shared.critical.lock().recompiled += 1;
+                Self::offer_best(mdd, shared, deferred.as_deref_mut());
+                notify_carry_over(carry_over, mdd, CompilationType::Relaxed, compiled, is_exact);
+            }
+        }
         if !is_exact {
-            Self::enqueue_cutset(mdd, shared, node_ub);
+            Self::enqueue_cutset(mdd, shared, node_ub, bonus, deferred);
         }
 
         Ok(())
@@ -439,55 +1000,243 @@ where
     fn best_lb(shared: &Shared<'a, State, C>) -> isize {
         shared.critical.lock().best_lb
     }
+    /// Returns the best lower bound which the processing of a node relies on:
+    /// the one of the critical data, unless the outcome of the processing is
+    /// `deferred` until the end of its round
+    fn current_lb(shared: &Shared<'a, State, C>, deferred: Option<&Deferred<State>>) -> isize {
+        deferred.map_or_else(|| Self::best_lb(shared), |deferred| deferred.best_lb)
+    }
+    /// Either updates the best known solution with the one of the current 
+    /// `mdd`, or keeps that solution until the end of the round (see 
+    /// `with_deterministic`)
+    fn offer_best(mdd: &D, shared: &Shared<'a, State, C>, deferred: Option<&mut Deferred<State>>) {
+        match deferred {
+            Some(deferred) => deferred.collect(mdd),
+            None => Self::maybe_update_best(mdd, shared),
+        }
+    }
 
     /// This private method updates the shared best known node and lower bound in
     /// case the best value of the current `mdd` expansion improves the current
     /// bounds.
     fn maybe_update_best(mdd: &D, shared: &Shared<'a, State, C>) {
-        let mut shared = shared.critical.lock();
+        let mut guard = shared.critical.lock();
+        let critical = &mut *guard;
+        if let Some(pool) = critical.diversity.as_mut() {
+            let dedup = &mut critical.dedup;
+            mdd.for_each_exact_solution(&mut |value, solution| {
+                if dedup.as_mut().is_none_or(|d| d.is_new(&solution)) {
+                    pool.offer(value, solution);
+                }
+            });
+        }
         let dd_best_value = mdd.best_exact_value().unwrap_or(isize::MIN);
-        if dd_best_value > shared.best_lb {
-            shared.best_lb = dd_best_value;
-            shared.best_sol = mdd.best_exact_solution();
+        if dd_best_value < critical.best_lb {
+            return;
+        }
+        let Some(solution) = mdd.best_exact_solution() else { return };
+        Self::install_incumbent(shared, critical, dd_best_value, solution, mdd.best_exact_state().cloned());
+    }
+    /// Commits the given solution as the new incumbent (see 
+    /// `Critical::commit_incumbent`). When it improves the best lower bound, 
+    /// the listeners are told about it and the fringe gets pruned.
+    fn install_incumbent(shared: &Shared<'a, State, C>, critical: &mut Critical<'a, State>, dd_best_value: isize, solution: Solution, state: Option<State>) {
+        if critical.commit_incumbent(dd_best_value, solution, state) {
+            critical.first_incumbent.get_or_insert(dd_best_value);
+            if let (Some(listener), Some(solution)) = (critical.incumbent_listener.as_mut(), critical.best_sol.as_ref()) {
+                let mut solution = solution.clone();
+                solution.sort_unstable_by_key(|d| d.variable.0);
+                listener.on_new_best(dd_best_value, &solution);
+            }
+            if let (Some(incumbent), Some(solution)) = (shared.shared_incumbent.as_ref(), critical.best_sol.as_ref()) {
+                incumbent.offer(dd_best_value, solution);
+            }
+            critical.report(ProgressEvent::NewIncumbent { value: dd_best_value });
+            Self::record_memory(shared, critical);
+            let open_by_layer = &mut critical.open_by_layer;
+            let pruned = &mut critical.stats.pruned_by_bound;
+            critical.fringe.prune(dd_best_value.saturating_add(shared.absolute_slack), &mut |depth| {
+                open_by_layer[depth] -= 1;
+                *pruned += 1;
+            });
+        }
+    }
+    /// Remembers the bound derived from the DD that was just compiled if it
+    /// is the first DD compiled for the root problem. Nothing better than the
+    /// `best_lb` used for that compilation needs to be represented by the DD.
+    fn maybe_record_root_bound(mdd: &D, shared: &Shared<'a, State, C>, is_root: bool, best_lb: isize, deferred: Option<&mut Deferred<State>>) {
+        if is_root {
+            let bound = mdd.best_value().unwrap_or(isize::MIN).max(best_lb);
+            match deferred {
+                Some(deferred) => { deferred.root_bound.get_or_insert(bound); }
+                None => { shared.critical.lock().root_bound.get_or_insert(bound); }
+            }
         }
     }
+    /// Samples the memory used by the cache, the dominance checker and (when
+    /// available) the whole process
+    fn record_memory(shared: &Shared<'a, State, C>, critical: &mut Critical<'a, State>) {
+        critical.memory.record_progress(shared.cache.estimated_bytes(), shared.dominance.estimated_bytes());
+    }
     /// If necessary, tightens the bound of nodes in the cut-set of `mdd` and
-    /// then add the relevant nodes to the shared fringe.
-    fn enqueue_cutset(mdd: &mut D, shared: &Shared<'a, State, C>, ub: isize) {
+    /// then add the relevant nodes to the shared fringe (unless they are 
+    /// `deferred` until the end of the round). These nodes carry the given 
+    /// priority `bonus`.
+    fn enqueue_cutset(mdd: &mut D, shared: &Shared<'a, State, C>, ub: isize, bonus: u8, deferred: Option<&mut Deferred<State>>) {
+        // the relaxed dd is a relaxation of the whole subproblem: its best 
+        // value bounds the value of all its cut-set nodes
+        let dd_ub = mdd.best_value().unwrap_or(isize::MIN);
+        if let Some(deferred) = deferred {
+            deferred.cutset_bounds = Some((ub, dd_ub, bonus));
+            mdd.drain_cutset(|cutset_node| deferred.cutset.push(cutset_node));
+            return;
+        }
         let mut critical = shared.critical.lock();
-        let best_lb = critical.best_lb;
-        mdd.drain_cutset(|mut cutset_node| {
-            cutset_node.ub = ub.min(cutset_node.ub);
-            if cutset_node.ub > best_lb {
-                let depth = cutset_node.depth;
-                let before = critical.fringe.len();
-                critical.fringe.push(cutset_node);
-                let after = critical.fringe.len();
-                critical.open_by_layer[depth] += after - before;
-            }
-        });
+        mdd.drain_cutset(|cutset_node| Self::push_cutset_node(shared, &mut critical, cutset_node, ub, dd_ub, bonus));
+        Self::cutset_pushed(&mut critical);
+    }
+    /// Tightens the bound of the given cut-set node (see `enqueue_cutset`) 
+    /// with the best value of the relaxed DD and with the rough upper bound
+    /// of its state, then pushes it onto the fringe unless it is pruned
+    fn push_cutset_node(shared: &Shared<'a, State, C>, critical: &mut Critical<'a, State>, mut cutset_node: SubProblem<State>, ub: isize, dd_ub: isize, bonus: u8) {
+        critical.stats.cutset_nodes += 1;
+        let inherited = ub.min(cutset_node.ub);
+        cutset_node.ub = inherited.min(dd_ub);
+        cutset_node.priority_bonus = bonus;
+        critical.clamped += usize::from(cutset_node.ub < inherited);
+        // the rough upper bound may be tighter than what the dd derived
+        let rough_ub = cutset_node.value.saturating_add(shared.relaxation.fast_upper_bound(cutset_node.state.as_ref()));
+        cutset_node.ub = cutset_node.ub.min(rough_ub);
+        if cutset_node.ub > critical.best_lb.saturating_add(shared.absolute_slack) {
+            let depth = cutset_node.depth;
+            critical.boosted += usize::from(bonus > 0);
+            let before = critical.fringe.len();
+            critical.fringe.push(cutset_node);
+            let after = critical.fringe.len();
+            critical.open_by_layer[depth] += after - before;
+        } else {
+            critical.stats.pruned_by_bound += 1;
+        }
+    }
+    /// Records the size of the fringe once a whole cut-set was pushed, and
+    /// lets the fringe shed the nodes which do not fit
+    fn cutset_pushed(critical: &mut Critical<'a, State>) {
+        critical.stats.peak_fringe = critical.stats.peak_fringe.max(critical.fringe.len());
+        let open_by_layer = &mut critical.open_by_layer;
+        critical.fringe.shed_excess(&mut |depth| open_by_layer[depth] -= 1);
+        let fringe_bytes = critical.fringe.estimated_bytes();
+        critical.memory.record_fringe(fringe_bytes);
     }
     /// Acknowledges that a thread finished processing its node.
     fn notify_node_finished(shared: &Shared<'a, State, C>, thread_id: usize, depth: usize) {
         let mut critical = shared.critical.lock();
         critical.ongoing -= 1;
-        critical.upper_bounds[thread_id] = isize::MAX;
+        critical.upper_bounds[thread_id] = isize::MIN;
         critical.ongoing_by_layer[depth] -= 1;
+        Self::refresh_best_ub(shared, &mut critical);
+        shared.bounds.publish(critical.best_lb, critical.best_ub);
+        let event = ProgressEvent::NodeExplored { 
+            explored: critical.explored, 
+            lb: critical.best_lb, 
+            ub: critical.best_ub, 
+            fringe_len: critical.fringe.len(),
+        };
+        critical.report(event);
         shared.monitor.notify_all();
     }
 
-    fn abort_search(shared: &Shared<'a, State, C>, reason: Reason, current_ub: isize) {
+    /// If a refresh of the dual bound is due, this method compiles a wider 
+    /// relaxation of the root problem and uses it to tighten the best upper 
+    /// bound as well as the upper bound of all the nodes in the fringe.
+    fn maybe_refresh_dual(mdd: &mut D, shared: &Shared<'a, State, C>) {
+        let (policy, best_lb, ub_before, explored) = {
+            let mut critical = shared.critical.lock();
+            let explored = critical.explored;
+            let best_lb  = critical.best_lb;
+            let best_ub  = critical.best_ub;
+            let aborted  = critical.abort_proof.is_some();
+            let Some(schedule) = critical.dual_refresh.as_mut() else { return };
+            if aborted || !schedule.is_due(explored) {
+                return;
+            }
+            (schedule.policy, best_lb, best_ub, explored)
+        };
+
+        let start = Instant::now();
+        let root_ub = compile_root_relaxation(mdd, shared.problem, shared.relaxation, 
+            shared.ranking, shared.width_heu, shared.cutoff, &policy, best_lb);
+
         let mut critical = shared.critical.lock();
-        critical.abort_proof = Some(reason);
-        if critical.best_ub == isize::MAX {
-            critical.best_ub = current_ub;
+        if let Some(ub) = root_ub {
+            if critical.abort_proof.is_none() {
+                let critical = &mut *critical;
+                critical.best_ub = critical.best_ub.min(ub);
+                sweep_fringe(critical.fringe, &mut critical.open_by_layer, critical.best_ub, critical.best_lb.saturating_add(shared.absolute_slack));
+            }
+        }
+        let ub_after = root_ub.map(|_| critical.best_ub);
+        if let Some(schedule) = critical.dual_refresh.as_mut() {
+            schedule.records.push(DualRefreshRecord { 
+                explored, 
+                elapsed: start.elapsed(), 
+                ub_before, 
+                ub_after,
+            });
+        }
+        shared.monitor.notify_all();
+    }
+
+    /// Interrupts the current run of the search and starts a new one from the
+    /// root of the problem. The best solution and bounds are retained; and so 
+    /// are the cache and the dominance checker if the policy says so.
+    ///
+    /// # Note
+    /// This method must only be called when no node is being processed.
+    fn restart(shared: &Shared<'a, State, C>, critical: &mut Critical<'a, State>) {
+        let (explored, best_lb, best_ub) = (critical.explored, critical.best_lb, critical.best_ub);
+        let Some(restarts) = critical.restarts.as_mut() else { return };
+        let seed = restarts.restart(explored, best_lb, best_ub);
+        let keep_cache = restarts.policy.keep_cache;
+
+        critical.fringe.clear();
+        critical.open_by_layer.iter_mut().for_each(|o| *o = 0);
+        critical.first_active_layer = 0;
+        if keep_cache {
+            shared.cache.forget_explored();
         } else {
-            critical.best_ub = current_ub.max(critical.best_ub);
+            shared.cache.clear();
+            (0..=shared.problem.nb_variables()).for_each(|d| shared.dominance.clear_layer(d));
         }
+        if let Some(reseed) = shared.reseed {
+            reseed.reseed(seed);
+        }
+
+        let root = Self::root_node(shared);
+        Self::push_roots(critical, root, best_ub);
+    }
+
+    fn abort_search(shared: &Shared<'a, State, C>, reason: Reason) {
+        let mut critical = shared.critical.lock();
+        Self::refresh_best_ub(shared, &mut critical);
+        critical.abort_proof = Some(reason);
         critical.fringe.clear();
         shared.cache.clear();
     }
 
+    /// Tightens the best known upper bound based on the nodes that are still
+    /// open: the ones sitting in the fringe (see `Fringe::best_ub`) and the ones currently being processed by some thread.
+    /// Once the search has been aborted, the bound is frozen since the fringe
+    /// no longer holds all the open nodes.
+    fn refresh_best_ub(shared: &Shared<'a, State, C>, critical: &mut Critical<'a, State>) {
+        if critical.abort_proof.is_some() {
+            return;
+        }
+        let fringe_ub  = critical.fringe.best_ub().max(critical.fringe.shed().best_ub).unwrap_or(isize::MIN);
+        let ongoing_ub = critical.upper_bounds.iter().copied().max().unwrap_or(isize::MIN);
+        let open_ub    = fringe_ub.max(ongoing_ub).max(critical.best_lb.saturating_add(shared.absolute_slack));
+        critical.best_ub = critical.best_ub.min(open_ub);
+    }
+
     /// Consults the shared state to fetch a workload. Depending on the current
     /// state, the workload can either be:
     ///
@@ -510,7 +1259,11 @@ where
 
         // Are we done ?
         if critical.ongoing == 0 && critical.fringe.is_empty() {
-            critical.best_ub = critical.best_lb;
+            // an aborted search has an empty fringe without being complete
+            if critical.abort_proof.is_none() {
+                let shed_ub = critical.fringe.shed().best_ub.unwrap_or(isize::MIN);
+                critical.best_ub = critical.best_ub.min(critical.best_lb.saturating_add(shared.absolute_slack).max(shed_ub));
+            }
             return WorkLoad::Complete;
         }
 
@@ -519,6 +1272,50 @@ where
             return WorkLoad::Aborted;
         }
 
+        // Is the node budget exhausted ?
+        if shared.node_budget.is_some_and(|budget| critical.explored >= budget) {
+            Self::refresh_best_ub(shared, &mut critical);
+            critical.abort_proof = Some(Reason::NodeLimit);
+            critical.fringe.clear();
+            shared.cache.clear();
+            shared.monitor.notify_all();
+            return WorkLoad::Aborted;
+        }
+
+        // Was the search aborted ? Is the time up ? Did the cutoff kick in ? 
+        // Is the solution good enough ? Do we know whether the target can be
+        // reached ?
+        let reason = if shared.abort.is_aborted() {
+            Some(Reason::Aborted)
+        } else if shared.time_limit.is_over() {
+            Some(Reason::Timeout)
+        } else if shared.cutoff.must_stop() {
+            Some(Reason::CutoffOccurred)
+        } else {
+            Self::refresh_best_ub(shared, &mut critical);
+            shared.gap_tolerance.is_reached(critical.best_lb, critical.best_ub)
+                .map(|_| Reason::GapReached)
+                .or_else(|| shared.target.and_then(|target| 
+                    target_reason(target, critical.best_lb, critical.best_ub)))
+        };
+        if let Some(reason) = reason {
+            Self::refresh_best_ub(shared, &mut critical);
+            critical.abort_proof = Some(reason);
+            critical.fringe.clear();
+            shared.cache.clear();
+            shared.monitor.notify_all();
+            return WorkLoad::Aborted;
+        }
+
+        // Should we start over ? => Wait for the ongoing nodes to be processed
+        if critical.restarts.as_ref().is_some_and(|r| r.is_due(critical.explored)) {
+            if critical.ongoing > 0 {
+                shared.monitor.wait(&mut critical);
+                return WorkLoad::Starvation;
+            }
+            Self::restart(shared, &mut critical);
+        }
+
         // Nothing to do yet ? => Wait for someone to post jobs
         if critical.fringe.is_empty() {
             shared.monitor.wait(&mut critical);
@@ -528,7 +1325,7 @@ where
         let mut nn = critical.fringe.pop().unwrap();
         loop {
             // Nothing relevant ? =>  Wait for someone to post jobs
-            if nn.ub <= critical.best_lb {
+            if nn.ub <= critical.best_lb.saturating_add(shared.absolute_slack) {
                 critical.fringe.clear();
                 critical.open_by_layer.iter_mut().for_each(|o| *o = 0);
                 return WorkLoad::Starvation;
@@ -554,56 +1351,225 @@ where
         critical.upper_bounds[thread_id] = nn.ub;
         critical.open_by_layer[nn.depth] -= 1;
         critical.ongoing_by_layer[nn.depth] += 1;
+        Self::refresh_best_ub(shared, &mut critical);
 
         WorkLoad::WorkItem { node: nn }
     }
 
+    /// Pops the nodes of the next round of a deterministic search (see 
+    /// `with_deterministic`): at most `size` nodes which are all popped before
+    /// any of them gets processed. The round is empty once the search is over.
+    fn get_round(shared: &Shared<'a, State, C>, size: usize) -> Vec<SubProblem<State>> {
+        let mut round = vec![];
+        while round.len() < size {
+            // the nodes of the round are not processed yet: there is no point
+            // in waiting for them
+            if !round.is_empty() {
+                let critical = shared.critical.lock();
+                let restart_due = critical.restarts.as_ref().is_some_and(|r| r.is_due(critical.explored));
+                if critical.fringe.is_empty() || restart_due {
+                    break;
+                }
+            }
+            match Self::get_workload(shared, round.len()) {
+                WorkLoad::WorkItem { node } => round.push(node),
+                WorkLoad::Starvation if round.is_empty() => continue,
+                _ => break,
+            }
+        }
+        round
+    }
+    /// Applies the outcome of the processing of a node which was deferred 
+    /// until the end of its round (see `with_deterministic`)
+    fn apply_deferred(shared: &Shared<'a, State, C>, deferred: Deferred<State>) {
+        let mut guard = shared.critical.lock();
+        let critical = &mut *guard;
+        if let Some(pool) = critical.diversity.as_mut() {
+            let dedup = &mut critical.dedup;
+            for (value, solution) in deferred.diverse {
+                if dedup.as_mut().is_none_or(|d| d.is_new(&solution)) {
+                    pool.offer(value, solution);
+                }
+            }
+        }
+        for (value, solution, state) in deferred.incumbents {
+            Self::install_incumbent(shared, critical, value, solution, state);
+        }
+        if let Some(bound) = deferred.root_bound {
+            critical.root_bound.get_or_insert(bound);
+        }
+        if let Some((ub, dd_ub, bonus)) = deferred.cutset_bounds {
+            for cutset_node in deferred.cutset {
+                Self::push_cutset_node(shared, critical, cutset_node, ub, dd_ub, bonus);
+            }
+            Self::cutset_pushed(critical);
+        }
+    }
 }
 
-impl<'a, State, D, C> Solver for ParallelSolver<'a, State, D, C>
+impl<'a, State, D, C> ParallelSolver<'a, State, D, C>
 where
-    State: Eq + PartialEq + Hash + Clone,
+    State: Eq + Hash + Clone + Send + Sync,
     D: DecisionDiagram<State = State> + Default,
     C: Cache<State = State> + Send + Sync + Default,
 {
-    /// Applies the branch and bound algorithm proposed by Bergman et al. to
-    /// solve the problem to optimality. To do so, it spawns `nb_threads` workers
-    /// (long running threads); each of which will continually get a workload
-    /// and process it until the problem is solved.
-    fn maximize(&mut self) -> Completion {
-        self.initialize();
-
+    /// Warm starts the search with a solution that is known beforehand. The
+    /// solution is replayed through the problem (see `evaluate`) and it is 
+    /// only recorded as the primal (see `Solver::set_primal`) when it is 
+    /// feasible and worth `value`. The first compilations are then pruned 
+    /// against that value, and the solution is returned when the search does
+    /// not find any better one.
+    pub fn set_initial_solution(&mut self, solution: Solution, value: isize) -> Result<(), WarmStartError<State>> {
+        check_warm_start(self.shared.problem, &solution, value)?;
+        self.set_primal(value, solution);
+        Ok(())
+    }
+    /// Carries out the search with `nb_threads` workers (long running 
+    /// threads), each of which continually gets a workload and processes it
+    /// until the problem is solved. This returns the memory used by the DDs of
+    /// the workers along with the statistics of their compilations.
+    fn run_workers(&self) -> (usize, Statistics) {
         std::thread::scope(|s| {
-            for i in 0..self.nb_threads {
+            let workers = (0..self.nb_threads).map(|i| {
                 let shared = &self.shared;
                 s.spawn(move || {
                     let mut mdd = D::default();
+                    let mut stats = Statistics::default();
                     loop {
                         match Self::get_workload(shared, i) {
                             WorkLoad::Complete => break,
                             WorkLoad::Aborted => break, // this one cannot occur
                             WorkLoad::Starvation => continue,
                             WorkLoad::WorkItem { node } => {
-                                let ub = node.ub;
                                 let depth = node.depth;
-                                let outcome = Self::process_one_node(&mut mdd, shared, node);
+                                let outcome = Self::process_one_node(&mut mdd, shared, &mut stats, node, None);
                                 if let Err(reason) = outcome {
-                                    Self::abort_search(shared, reason, ub);
+                                    let reason = shared.time_limit.explain(shared.abort.explain(reason), shared.cutoff);
+                                    Self::abort_search(shared, reason);
                                     Self::notify_node_finished(shared, i, depth); 
                                     break;
                                 } else {
                                     Self::notify_node_finished(shared, i, depth);
+                                    Self::maybe_refresh_dual(&mut mdd, shared);
                                 }
                             }
                         }
                     }
-                });
+                    (mdd.peak_layer_bytes(), stats)
+                })
+            }).collect::<Vec<_>>();
+            workers.into_iter()
+                .map(|w| w.join().unwrap_or_default())
+                .fold((0, Statistics::default()), |(bytes, mut total), (mdd_bytes, stats)| {
+                    total.merge(&stats);
+                    (bytes + mdd_bytes, total)
+                })
+        })
+    }
+    /// Carries out a deterministic search (see `with_deterministic`): the 
+    /// nodes of each round are processed in parallel, after what the outcomes
+    /// of their processing are applied in the order in which they were popped.
+    /// This returns the memory used by the DDs of the workers along with the
+    /// statistics of their compilations.
+    fn run_rounds(&self) -> (usize, Statistics) {
+        let shared = &self.shared;
+        let mut workers = vec![(0, Statistics::default()); self.nb_threads];
+        let mut mdd = D::default();
+        loop {
+            let round = Self::get_round(shared, self.nb_threads);
+            if round.is_empty() {
+                break;
             }
-        });
+            let (best_lb, diversity) = {
+                let critical = shared.critical.lock();
+                (critical.best_lb, critical.diversity.is_some())
+            };
+            let outcomes = std::thread::scope(|s| {
+                let handles = round.into_iter().zip(workers.iter_mut()).map(|(node, (mdd_bytes, stats))| {
+                    s.spawn(move || {
+                        let mut mdd = D::default();
+                        let mut deferred = Deferred::new(best_lb, diversity);
+                        let depth = node.depth;
+                        let outcome = Self::process_one_node(&mut mdd, shared, stats, node, Some(&mut deferred));
+                        *mdd_bytes = (*mdd_bytes).max(mdd.peak_layer_bytes());
+                        (depth, outcome, deferred)
+                    })
+                }).collect::<Vec<_>>();
+                handles.into_iter()
+                    .map(|h| h.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+                    .collect::<Vec<_>>()
+            });
+            for (i, (depth, outcome, deferred)) in outcomes.into_iter().enumerate() {
+                Self::apply_deferred(shared, deferred);
+                if let Err(reason) = outcome {
+                    let reason = shared.time_limit.explain(shared.abort.explain(reason), shared.cutoff);
+                    Self::abort_search(shared, reason);
+                }
+                Self::notify_node_finished(shared, i, depth);
+            }
+            Self::maybe_refresh_dual(&mut mdd, shared);
+        }
+        workers.into_iter().fold((0, Statistics::default()), |(bytes, mut total), (mdd_bytes, stats)| {
+            total.merge(&stats);
+            (bytes + mdd_bytes, total)
+        })
+    }
+}
+
+impl<'a, State, D, C> Solver for ParallelSolver<'a, State, D, C>
+where
+    State: Eq + PartialEq + Hash + Clone + Send + Sync,
+    D: DecisionDiagram<State = State> + Default,
+    C: Cache<State = State> + Send + Sync + Default,
+{
+    /// Applies the branch and bound algorithm proposed by Bergman et al. to
+    /// solve the problem to optimality. To do so, it spawns `nb_threads` workers
+    /// (long running threads); each of which will continually get a workload
+    /// and process it until the problem is solved. A deterministic search 
+    /// processes the nodes in rounds instead (see `with_deterministic`).
+    fn maximize(&mut self) -> Completion {
+        self.initialize();
+
+        let (mdd_bytes, stats) = if self.deterministic {
+            self.run_rounds()
+        } else {
+            self.run_workers()
+        };
 
         let mut critical = self.shared.critical.lock();
+        critical.memory.mdd_bytes = critical.memory.mdd_bytes.max(mdd_bytes);
+        critical.stats.merge(&stats);
+        critical.stats.elapsed = self.shared.time_limit.elapsed();
+        Self::record_memory(&self.shared, &mut critical);
+        self.shared.bounds.publish(critical.best_lb, critical.best_ub);
         if let Some(sol) = critical.best_sol.as_mut() { sol.sort_unstable_by_key(|d| d.variable.0) }
-        Completion { is_exact: critical.abort_proof.is_none(), best_value: critical.best_sol.as_ref().map(|_| critical.best_lb) }
+        let event = ProgressEvent::Finished { 
+            value: critical.best_sol.as_ref().map(|_| critical.best_lb), 
+            explored: critical.explored,
+        };
+        critical.report(event);
+        let guaranteed_within = Some(self.shared.absolute_slack).filter(|slack| *slack != 0 && critical.abort_proof.is_none());
+        let shed = critical.fringe.shed();
+        // the shed nodes which could have improved the best solution were never explored
+        let lossy = shed.best_ub.is_some_and(|ub| ub > critical.best_lb);
+        // a complete search which did not reach the target proved it unreachable
+        let stopped_by = critical.abort_proof.clone().or_else(|| 
+            self.shared.target.filter(|target| critical.best_lb < *target).map(|_| Reason::TargetUnreachable));
+        Completion { 
+            is_exact: critical.abort_proof.is_none() && guaranteed_within.is_none() && !lossy, 
+            best_value: critical.best_sol.as_ref().map(|_| critical.best_lb),
+            guaranteed_within,
+            stopped_by: stopped_by.clone(),
+            achieved_gap: Some(relative_gap(critical.best_lb, critical.best_ub))
+                .filter(|_| critical.abort_proof == Some(Reason::GapReached)),
+            termination: Some(Termination {
+                reason: stopped_by,
+                elapsed: critical.stats.elapsed,
+                explored: critical.explored,
+                gap: relative_gap(critical.best_lb, critical.best_ub),
+                shed: shed.count,
+            }),
+        }
     }
 
     /// Returns the best solution that has been identified for this problem.
@@ -628,16 +1594,82 @@ where
     }
     /// Sets a primal (best known value and solution) of the problem.
     fn set_primal(&mut self, value: isize, solution: Solution) {
-        let mut critical = self.shared.critical.lock();
-        if value > critical.best_lb {
-            critical.best_sol = Some(solution);
-            critical.best_lb  = value;
+        let mut guard = self.shared.critical.lock();
+        let critical = &mut *guard;
+        if let Some(pool) = critical.diversity.as_mut() {
+            if critical.dedup.as_mut().is_none_or(|d| d.is_new(&solution)) {
+                pool.offer(value, solution.clone());
+            }
+        }
+        if let Some(incumbent) = self.shared.shared_incumbent.as_ref() {
+            incumbent.offer(value, &solution);
         }
+        critical.commit_incumbent(value, solution, None);
+        self.shared.bounds.publish(critical.best_lb, critical.best_ub);
+    }
+    /// Only looks for solutions better than `bound` (see `Solver::set_primal_bound`)
+    fn set_primal_bound(&mut self, bound: isize) {
+        let critical = self.shared.critical.get_mut();
+        if bound > critical.best_lb {
+            critical.best_sol = None;
+            critical.best_state = None;
+            critical.best_lb = bound;
+            self.shared.bounds.publish(critical.best_lb, critical.best_ub);
+        }
+    }
+    /// Forgets all about the previous resolutions (see `Solver::reset`)
+    fn reset(&mut self) {
+        let nb_vars = self.shared.problem.nb_variables();
+        self.shared.cache.clear();
+        (0..=nb_vars).for_each(|d| self.shared.dominance.clear_layer(d));
+        self.shared.abort.clear();
+        self.shared.presolve_record = None;
+        
+        let critical = self.shared.critical.get_mut();
+        critical.fringe.clear();
+        critical.ongoing = 0;
+        critical.explored = 0;
+        critical.open_by_layer = vec![0; nb_vars + 1];
+        critical.ongoing_by_layer = vec![0; nb_vars + 1];
+        critical.first_active_layer = 0;
+        critical.best_lb = isize::MIN;
+        critical.best_ub = isize::MAX;
+        self.shared.bounds.publish(critical.best_lb, critical.best_ub);
+        critical.best_sol = None;
+        critical.best_state = None;
+        critical.upper_bounds.iter_mut().for_each(|ub| *ub = isize::MIN);
+        critical.abort_proof = None;
+        //
+        critical.dual_refresh = critical.dual_refresh.as_ref().map(|s| DualRefreshSchedule::new(s.policy));
+        critical.restarts = critical.restarts.as_ref().map(|r| RestartTracker::new(r.policy));
+        critical.diversity = critical.diversity.as_ref().map(|p| DiversityPool::new(p.policy));
+        critical.dedup = critical.dedup.as_ref().map(|d| d.renewed());
+        critical.memory = MemoryStats::default();
+        critical.stats = Statistics::default();
+        critical.recompiled = 0;
+        critical.clamped = 0;
+        critical.boosted = 0;
+        critical.root_bound = None;
+        critical.first_incumbent = None;
+        critical.roots.clear();
+        critical.tree_estimate = critical.tree_estimate.map(|e| TreeEstimate::new(e.samples, e.seed));
+    }
+    /// Limits the number of nodes that may be explored (see `Solver::set_node_budget`)
+    fn set_node_budget(&mut self, budget: Option<usize>) {
+        self.shared.node_budget = budget;
+    }
+    /// Limits the time `maximize` may run (see `Solver::set_time_limit`)
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.shared.time_limit.limit = limit;
     }
     /// Returns the number of nodes that have been explored so far.
     fn explored(&self) -> usize {
         self.shared.critical.lock().explored
     }
+    fn statistics(&self) -> Option<Statistics> {
+        let critical = self.shared.critical.lock();
+        Some(Statistics { explored: critical.explored, ..critical.stats })
+    }
 }
 
 
@@ -653,6 +1685,8 @@ where
 
 #[cfg(test)]
 mod test_solver {
+    use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+
     use crate::*;
     
     type DdLel<'a, T> = ParallelSolver<'a, T, DefaultMDDLEL<T>, EmptyCache<T>>;
@@ -1150,7 +2184,7 @@ mod test_solver {
     }
 
     #[test]
-    fn set_primal_overwrites_best_value_and_sol_if_it_improves() {
+    fn reset_forgets_about_the_previous_resolution() {
         let problem = Knapsack {
             capacity: 50,
             profit  : vec![60, 100, 120],
@@ -1170,47 +2204,42 @@ mod test_solver {
             &dominance,
             &cutoff,
             &mut fringe,
-            1,
+            2,
         );
 
-        let d1  = Decision{variable: Variable(0), value: 10};
-        let sol = vec![d1];
-
-        solver.set_primal(10, sol.clone());
-        assert!(solver.best_solution().is_some());
-        assert_eq!(10, solver.best_lower_bound());
-
-        // in this case, it wont update because there is no improvement
-        solver.set_primal(5, sol.clone());
-        assert!(solver.best_solution().is_some());
-        assert_eq!(10, solver.best_lower_bound());
-
-        // but here, it will update as it improves the best known sol
-        solver.set_primal(10000, sol);
-        assert!(solver.best_solution().is_some());
-        assert_eq!(10000, solver.best_lower_bound());
-
-        // it wont do much as the primal is better than the actual feasible solution
-        let maximized = solver.maximize();
-        assert!(maximized.is_exact);
-        assert_eq!(maximized.best_value, Some(10000));
-        assert!(solver.best_solution().is_some());
+        let first = solver.maximize();
+        let solution = solver.best_solution();
+        solver.set_primal(10000, vec![]);
+
+        solver.reset();
+        assert_eq!(None, solver.best_value());
+        assert_eq!(None, solver.best_solution());
+        assert_eq!(None, solver.best_state());
+        assert_eq!(None, solver.root_bound());
+        assert_eq!(isize::MIN, solver.best_lower_bound());
+        assert_eq!(isize::MAX, solver.best_upper_bound());
+        assert_eq!(0, solver.explored());
+
+        // solving the problem once again yields the very same outcome
+        let second = solver.maximize();
+        assert_eq!(first.is_exact, second.is_exact);
+        assert_eq!(first.best_value, second.best_value);
+        assert_eq!(solution, solver.best_solution());
     }
-
     #[test]
-    fn when_no_solution_is_found_the_gap_is_one() {
+    fn the_search_stops_when_the_node_budget_is_exhausted() {
         let problem = Knapsack {
-            capacity: 50,
-            profit  : vec![60, 100, 120],
-            weight  : vec![10,  20,  30]
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
         };
         let relax = KPRelax {pb: &problem};
         let ranking = KPRanking;
         let cutoff = NoCutoff;
-        let width = NbUnassignedWidth(problem.nb_variables());
+        let width = FixedWidth(1);
         let dominance = EmptyDominanceChecker::default();
         let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
-        let solver = DdLel::custom(
+        let mut solver = DdLel::custom(
             &problem,
             &relax,
             &ranking,
@@ -1218,22 +2247,293 @@ mod test_solver {
             &dominance,
             &cutoff,
             &mut fringe,
-            1,
+            4,
         );
 
-        assert_eq!(1.0, solver.gap());
+        solver.set_node_budget(Some(3));
+        let partial = solver.maximize();
+        assert!(!partial.is_exact);
+        assert_eq!(Some(Reason::NodeLimit), partial.stopped_by);
+        assert!(solver.explored() <= 3 + 4);
+        assert!(solver.best_upper_bound() > solver.best_lower_bound());
+
+        // lifting the budget lets the solver reach the optimum
+        solver.reset();
+        solver.set_node_budget(None);
+        let complete = solver.maximize();
+        assert!(complete.is_exact);
+        assert_eq!(Some(134), complete.best_value);
+        assert!(solver.explored() > 3);
     }
     #[test]
-    fn when_optimum_solution_is_found_the_gap_is_zero() {
+    fn the_search_stops_when_the_time_is_up() {
         let problem = Knapsack {
-            capacity: 50,
-            profit  : vec![60, 100, 120],
-            weight  : vec![10,  20,  30]
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
         };
         let relax = KPRelax {pb: &problem};
         let ranking = KPRanking;
         let cutoff = NoCutoff;
-        let width = NbUnassignedWidth(problem.nb_variables());
+        let width = FixedWidth(1);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            4,
+        );
+
+        solver.set_time_limit(Some(std::time::Duration::ZERO));
+        let partial = solver.maximize();
+        assert!(!partial.is_exact);
+        assert_eq!(Some(Reason::Timeout), partial.stopped_by);
+
+        // lifting the limit lets the solver reach the optimum
+        solver.reset();
+        solver.set_time_limit(None);
+        let complete = solver.maximize();
+        assert!(complete.is_exact);
+        assert_eq!(None, complete.stopped_by);
+        assert_eq!(Some(134), complete.best_value);
+    }
+    #[test]
+    fn the_same_solution_is_returned_whenever_there_are_ties() {
+        // lots of items are interchangeable: there are many optimal solutions
+        let problem = Knapsack {
+            capacity: 40,
+            profit  : vec![6, 6, 6, 6, 8, 8, 8, 8, 6, 6, 6, 6, 8, 8, 8, 8],
+            weight  : vec![3, 3, 3, 3, 4, 4, 4, 4, 3, 3, 3, 3, 4, 4, 4, 4],
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+
+        let mut solutions = vec![];
+        for _ in 0..50 {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = DdLel::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+                4,
+            );
+            let completion = solver.maximize();
+            assert!(completion.is_exact);
+            assert_eq!(Some(80), completion.best_value);
+            solutions.push(solver.best_solution().unwrap());
+        }
+        assert!(solutions.iter().all(|s| *s == solutions[0]));
+    }
+    #[test]
+    fn set_primal_breaks_ties_on_the_canonical_solution() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        let take = |v: usize, value: isize| Decision { variable: Variable(v), value };
+        let larger  = vec![take(1, 1), take(0, 1)];
+        let smaller = vec![take(0, 0), take(1, 1)];
+
+        solver.set_primal(10, larger.clone());
+        solver.set_primal(10, smaller.clone());
+        assert_eq!(Some(smaller.clone()), solver.best_solution());
+        // the order in which the ties are offered does not matter
+        solver.set_primal(10, larger);
+        assert_eq!(Some(smaller), solver.best_solution());
+        assert_eq!(Some(10), solver.best_value());
+    }
+    #[test]
+    fn manually_splitting_the_root_preserves_the_optimum() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120, 30, 45, 80],
+            weight  : vec![10,  20,  30,  5, 15, 25]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            4,
+        );
+        let expected = solver.maximize();
+
+        // branch on the first item: either it is taken or it is left out
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            4,
+        ).with_manual_root();
+        let take  = Decision { variable: Variable(0), value: TAKE_IT };
+        let leave = Decision { variable: Variable(0), value: LEAVE_IT_OUT };
+        solver.push_subproblem(KnapsackState { depth: 1, capacity: 40 }, 60, isize::MAX, vec![take]);
+        solver.push_subproblem(KnapsackState { depth: 1, capacity: 50 },  0, isize::MAX, vec![leave]);
+        let actual = solver.maximize();
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert_eq!(problem.nb_variables(), solver.best_solution().unwrap().len());
+    }
+    #[test]
+    fn a_priority_bonus_reorders_the_search_without_changing_the_optimum() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120, 30, 45, 80],
+            weight  : vec![10,  20,  30,  5, 15, 25]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            4,
+        );
+        let expected = solver.maximize();
+        assert_eq!(0, solver.boosted_nodes());
+
+        let mut fringe = SimpleFringe::new(BonusFirst::new(MaxUB::new(&ranking)));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            4,
+        ).with_manual_root();
+        let take  = Decision { variable: Variable(0), value: TAKE_IT };
+        let leave = Decision { variable: Variable(0), value: LEAVE_IT_OUT };
+        solver.push_subproblem(KnapsackState { depth: 1, capacity: 40 }, 60, isize::MAX, vec![take]);
+        solver.push_boosted_subproblem(KnapsackState { depth: 1, capacity: 50 },  0, isize::MAX, vec![leave], 2);
+        let actual = solver.maximize();
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert!(solver.boosted_nodes() > 0);
+    }
+    #[test]
+    fn with_a_manual_root_and_no_subproblem_nothing_is_explored() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            4,
+        ).with_manual_root();
+
+        let outcome = solver.maximize();
+        assert_eq!(0, solver.explored());
+        assert_eq!(None, outcome.best_value);
+    }
+    #[test]
+    #[should_panic]
+    fn pushing_a_subproblem_which_decides_twice_on_a_variable_panics() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            4,
+        ).with_manual_root();
+
+        let leave = Decision { variable: Variable(0), value: LEAVE_IT_OUT };
+        solver.push_subproblem(KnapsackState { depth: 2, capacity: 50 }, 0, isize::MAX, vec![leave, leave]);
+    }
+    #[test]
+    fn set_primal_overwrites_best_value_and_sol_if_it_improves() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
         let dominance = EmptyDominanceChecker::default();
         let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
         let mut solver = DdLel::custom(
@@ -1247,13 +2547,1290 @@ mod test_solver {
             1,
         );
 
-        let Completion{is_exact, best_value} = solver.maximize();
-        assert!(is_exact);
-        assert!(best_value.is_some());
-        assert_eq!(0.0, solver.gap());
+        let d1  = Decision{variable: Variable(0), value: 10};
+        let sol = vec![d1];
+
+        solver.set_primal(10, sol.clone());
+        assert!(solver.best_solution().is_some());
+        assert_eq!(10, solver.best_lower_bound());
+
+        // in this case, it wont update because there is no improvement
+        solver.set_primal(5, sol.clone());
+        assert!(solver.best_solution().is_some());
+        assert_eq!(10, solver.best_lower_bound());
+
+        // but here, it will update as it improves the best known sol
+        solver.set_primal(10000, sol);
+        assert!(solver.best_solution().is_some());
+        assert_eq!(10000, solver.best_lower_bound());
+
+        // it wont do much as the primal is better than the actual feasible solution
+        let maximized = solver.maximize();
+        assert!(maximized.is_exact);
+        assert_eq!(maximized.best_value, Some(10000));
+        assert!(solver.best_solution().is_some());
+    }
+
+    #[test]
+    fn an_initial_solution_is_returned_when_nothing_beats_it() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut cold = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            1,
+        );
+
+        cold.maximize();
+        let optimal = vec![
+            Decision{variable: Variable(0), value: LEAVE_IT_OUT},
+            Decision{variable: Variable(1), value: TAKE_IT},
+            Decision{variable: Variable(2), value: TAKE_IT},
+        ];
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            1,
+        );
+
+        assert!(solver.set_initial_solution(optimal.clone(), 220).is_ok());
+        let warm = solver.maximize();
+        assert!(warm.is_exact);
+        assert_eq!(Some(220), warm.best_value);
+        assert_eq!(Some(optimal), solver.best_solution());
+        assert!(solver.explored() <= cold.explored());
+    }
+    #[test]
+    fn an_initial_solution_is_only_trusted_when_it_is_worth_its_value() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            1,
+        );
+
+        let optimal = vec![
+            Decision{variable: Variable(0), value: LEAVE_IT_OUT},
+            Decision{variable: Variable(1), value: TAKE_IT},
+            Decision{variable: Variable(2), value: TAKE_IT},
+        ];
+        assert_eq!(
+            Err(WarmStartError::WrongValue { announced: 300, actual: 220 }), 
+            solver.set_initial_solution(optimal, 300));
+        let overweight = vec![
+            Decision{variable: Variable(0), value: TAKE_IT},
+            Decision{variable: Variable(1), value: TAKE_IT},
+            Decision{variable: Variable(2), value: TAKE_IT},
+        ];
+        assert!(matches!(
+            solver.set_initial_solution(overweight, 280), 
+            Err(WarmStartError::Invalid(EvalError::Infeasible{depth: 2, ..}))));
+        assert_eq!(None, solver.best_value());
+        assert_eq!(isize::MIN, solver.best_lower_bound());
+    }
+    #[test]
+    fn a_primal_bound_prunes_the_search_without_providing_a_solution() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            1,
+        );
+
+        solver.set_primal_bound(220);
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert_eq!(None, outcome.best_value);
+        assert_eq!(None, solver.best_solution());
+
+        solver.reset();
+        solver.set_primal_bound(219);
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert_eq!(Some(220), outcome.best_value);
+    }
+
+    #[test]
+    fn when_no_solution_is_found_the_gap_is_one() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            1,
+        );
+
+        assert_eq!(1.0, solver.gap());
+    }
+    #[test]
+    fn when_optimum_solution_is_found_the_gap_is_zero() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            1,
+        );
+
+        let Completion{is_exact, best_value, ..} = solver.maximize();
+        assert!(is_exact);
+        assert!(best_value.is_some());
+        assert_eq!(0.0, solver.gap());
+    }
+    #[test]
+    fn the_gap_never_increases_during_the_search() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            1,
+        );
+
+        // drive the search step by step from a single worker (thread 0)
+        solver.initialize();
+        let mut mdd = DefaultMDDLEL::default();
+        let mut ub  = solver.best_upper_bound();
+        let mut gap = solver.gap();
+        loop {
+            match DdLel::get_workload(&solver.shared, 0) {
+                super::WorkLoad::Complete    => break,
+                super::WorkLoad::Aborted     => unreachable!(),
+                super::WorkLoad::Starvation  => continue,
+                super::WorkLoad::WorkItem { node } => {
+                    let depth = node.depth;
+                    DdLel::process_one_node(&mut mdd, &solver.shared, &mut Statistics::default(), node, None).unwrap();
+                    DdLel::notify_node_finished(&solver.shared, 0, depth);
+                }
+            }
+            assert!(solver.best_upper_bound() <= ub);
+            assert!(solver.gap() <= gap);
+            ub  = solver.best_upper_bound();
+            gap = solver.gap();
+        }
+        assert!(solver.explored() > 1);
+        assert_eq!(solver.best_lower_bound(), solver.best_upper_bound());
+        assert_eq!(0.0, solver.gap());
+    }
+
+    #[test]
+    fn dual_refresh_preserves_the_optimum_and_never_loosens_the_bound() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        let expected = solver.maximize();
+        assert!(solver.dual_refreshes().is_empty());
+
+        let policy = DualRefresh { explored_factor: 2, ..DualRefresh::default() };
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        ).with_dual_refresh(policy);
+        let actual = solver.maximize();
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert_eq!(solver.best_lower_bound(), solver.best_upper_bound());
+        assert!(!solver.dual_refreshes().is_empty());
+        for refresh in solver.dual_refreshes().iter() {
+            assert!(refresh.ub_after.unwrap() <= refresh.ub_before);
+        }
+    }
+
+    #[test]
+    fn memory_stats_account_for_each_data_structure() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdFc::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        assert_eq!(MemoryStats::default(), solver.memory_stats());
+        solver.maximize();
+
+        let stats = solver.memory_stats();
+        let node_bytes = subproblem_bytes::<KnapsackState>();
+        assert!(stats.fringe_bytes >= node_bytes);
+        assert_eq!(0, stats.fringe_bytes % node_bytes);
+        assert!(stats.mdd_bytes > 0);
+        assert!(stats.cache_bytes > 0);
+        assert_eq!(0, stats.dominance_bytes);
+        assert_eq!(stats.fringe_bytes + stats.mdd_bytes + stats.cache_bytes, stats.peak_bytes());
+        assert_eq!(cfg!(all(feature = "rss", target_os = "linux")), stats.rss_bytes.is_some());
+    }
+
+    #[cfg(feature = "ooc")]
+    #[test]
+    fn the_out_of_core_fringe_reaches_the_same_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdFc::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        let expected = solver.maximize();
+
+        let config = OocConfig { working_set: 2, compact_every: 1, ..OocConfig::default() };
+        let mut fringe = OocFringe::new(MaxUB::new(&ranking), config).unwrap();
+        let mut solver = DdFc::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        let actual = solver.maximize();
+        drop(solver);
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert!(fringe.stats().spilled_nodes > 0);
+        assert!(fringe.stats().reloaded_nodes > 0);
+    }
+
+    /// A fringe which remembers the path of each node that was pushed onto it
+    struct RecordingFringe<'a> {
+        fringe: SimpleFringe<MaxUB<'a, KPRanking>>,
+        pushed: Vec<Vec<Decision>>,
+    }
+    impl Fringe for RecordingFringe<'_> {
+        type State = KnapsackState;
+        fn push(&mut self, node: SubProblem<KnapsackState>) {
+            self.pushed.push(node.path.clone());
+            self.fringe.push(node)
+        }
+        fn pop(&mut self) -> Option<SubProblem<KnapsackState>> {
+            self.fringe.pop()
+        }
+        fn peek(&self) -> Option<&SubProblem<KnapsackState>> {
+            self.fringe.peek()
+        }
+        fn clear(&mut self) {
+            self.fringe.clear()
+        }
+        fn len(&self) -> usize {
+            self.fringe.len()
+        }
+    }
+
+    #[test]
+    fn recompiling_wider_relaxations_enqueues_each_node_once() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        let expected = solver.maximize();
+
+        let mut fringe = RecordingFringe { fringe: SimpleFringe::new(MaxUB::new(&ranking)), pushed: vec![] };
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        ).with_wider_recompile(0);
+        let actual = solver.maximize();
+        assert!(solver.wider_recompilations() > 0);
+        drop(solver);
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        let mut paths = fringe.pushed.clone();
+        paths.sort_unstable();
+        paths.dedup();
+        assert_eq!(fringe.pushed.len(), paths.len());
+    }
+
+    #[test]
+    fn the_root_bound_and_the_first_incumbent_bracket_the_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        assert_eq!(None, solver.root_bound());
+        assert_eq!(None, solver.first_incumbent());
+
+        let best = solver.maximize().best_value.unwrap();
+        let root_bound = solver.root_bound().unwrap();
+        let first_incumbent = solver.first_incumbent().unwrap();
+        assert!(root_bound > best);
+        assert!(first_incumbent <= best);
+    }
+    #[test]
+    fn the_root_bound_is_the_optimum_when_the_root_is_solved_exactly() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10, 20, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        solver.maximize();
+        assert_eq!(Some(220), solver.root_bound());
+        assert_eq!(Some(220), solver.first_incumbent());
+    }
+
+    #[test]
+    fn with_an_absolute_slack_the_best_value_is_within_that_slack_of_the_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let solve = |slack: isize| {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = DdLel::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+                2,
+            ).with_absolute_slack(slack);
+            let completion = solver.maximize();
+            assert!(solver.best_upper_bound() >= completion.best_value.unwrap());
+            completion
+        };
+
+        let exact = solve(0);
+        assert!(exact.is_exact);
+        assert_eq!(None, exact.guaranteed_within);
+        let optimum = exact.best_value.unwrap();
+        assert_eq!(Some(optimum), solve(0).best_value);
+
+        for slack in [1, 5, 10, 25, 50, 100] {
+            let approx = solve(slack);
+            assert!(!approx.is_exact);
+            assert_eq!(Some(slack), approx.guaranteed_within);
+            assert!(approx.best_value.unwrap() >= optimum - slack);
+        }
+    }
+    #[test]
+    fn with_a_loose_gap_tolerance_the_search_stops_before_proving_optimality() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let solve = |tolerance: f64| {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = DdLel::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+                2,
+            ).with_gap_tolerance(tolerance);
+            let completion = solver.maximize();
+            assert!(solver.best_upper_bound() >= completion.best_value.unwrap());
+            completion
+        };
+
+        let exact = solve(0.0);
+        assert!(exact.is_exact);
+        assert_eq!(None, exact.stopped_by);
+        assert_eq!(None, exact.achieved_gap);
+        let optimum = exact.best_value.unwrap();
+
+        let approx = solve(0.99);
+        assert!(!approx.is_exact);
+        assert_eq!(Some(Reason::GapReached), approx.stopped_by);
+        let gap = approx.achieved_gap.unwrap();
+        assert!(gap > 0.0 && gap < 0.99);
+        assert!(approx.best_value.unwrap() as f64 >= (1.0 - gap) * optimum as f64);
+    }
+    #[test]
+    fn with_a_target_the_search_stops_once_it_knows_whether_it_is_reachable() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let solve = |target: isize| {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = DdLel::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+                2,
+            ).with_target(target);
+            let completion = solver.maximize();
+            (completion, solver.best_upper_bound(), solver.best_solution())
+        };
+
+        // the optimum is 380
+        let (reached, _, solution) = solve(300);
+        assert!(!reached.is_exact);
+        assert_eq!(Some(Reason::TargetReached), reached.stopped_by);
+        let value = reached.best_value.unwrap();
+        assert!(value >= 300);
+        let profit = solution.unwrap().iter()
+            .map(|d| problem.profit[d.variable.id()] as isize * d.value)
+            .sum::<isize>();
+        assert_eq!(value, profit);
+
+        let (unreachable, best_ub, _) = solve(400);
+        assert_eq!(Some(Reason::TargetUnreachable), unreachable.stopped_by);
+        assert!(best_ub < 400);
+        assert!(unreachable.best_value.unwrap() < 400);
+    }
+    #[test]
+    fn the_termination_tells_why_and_when_the_search_ended() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let solve = |cutoff: &(dyn Cutoff + Send + Sync), budget: Option<usize>| {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = DdLel::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                cutoff,
+                &mut fringe,
+                2,
+            );
+            solver.set_node_budget(budget);
+            let completion = solver.maximize();
+            let termination = completion.termination.clone().unwrap();
+            assert_eq!(completion.stopped_by, termination.reason);
+            assert_eq!(solver.explored(), termination.explored);
+            termination
+        };
+
+        let complete = solve(&NoCutoff, None);
+        assert_eq!(None, complete.reason);
+        assert_eq!(0.0, complete.gap);
+
+        let nodes = solve(&NoCutoff, Some(1));
+        assert_eq!(Some(Reason::NodeLimit), nodes.reason);
+
+        let cutoff = solve(&CutoffFn::new(|| true), None);
+        assert_eq!(Some(Reason::CutoffOccurred), cutoff.reason);
+    }
+    #[test]
+    fn the_incumbent_listener_is_told_about_each_new_best_solution() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut found = vec![];
+        let mut listener = |value: isize, solution: &[Decision]| found.push((value, solution.to_vec()));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            4,
+        ).with_incumbent_listener(&mut listener);
+
+        let Completion{best_value, ..} = solver.maximize();
+        drop(solver);
+
+        assert!(found.windows(2).all(|w| w[0].0 < w[1].0));
+        for (value, solution) in found.iter() {
+            let profit = solution.iter().map(|d| problem.profit[d.variable.id()] as isize * d.value).sum::<isize>();
+            assert_eq!(*value, profit);
+        }
+        assert_eq!(best_value, found.last().map(|(value, _)| *value));
+    }
+    #[test]
+    fn the_shared_incumbent_can_be_read_while_the_solver_runs() {
+        let problem = Knapsack {
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(1);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let incumbent = SharedIncumbent::default();
+        let done = AtomicBool::new(false);
+
+        let seen = std::thread::scope(|s| {
+            let reader = incumbent.clone();
+            let done = &done;
+            let handle = s.spawn(move || {
+                let mut seen: Vec<IncumbentSnapshot> = vec![];
+                while !done.load(AtomicOrdering::Acquire) {
+                    if let Some(snapshot) = reader.snapshot() {
+                        if seen.last().is_none_or(|last| last.0 != snapshot.0) {
+                            seen.push(snapshot);
+                        }
+                    }
+                    std::thread::yield_now();
+                }
+                seen
+            });
+
+            let mut solver = DdLel::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+            4,
+            ).with_shared_incumbent(&incumbent);
+            let Completion{best_value, ..} = solver.maximize();
+            assert_eq!(Some(134), best_value);
+            done.store(true, AtomicOrdering::Release);
+            handle.join().unwrap()
+        });
+
+        assert!(seen.windows(2).all(|w| w[0].0 < w[1].0));
+        for snapshot in seen.iter() {
+            let (value, solution) = &**snapshot;
+            let profit = solution.iter().map(|d| problem.profit[d.variable.id()] as isize * d.value).sum::<isize>();
+            assert_eq!(*value, profit);
+        }
+        assert_eq!(Some(134), incumbent.value());
+    }
+    #[test]
+    fn the_live_bounds_can_be_polled_while_the_solver_runs() {
+        let problem = Knapsack {
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(1);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let done = AtomicBool::new(false);
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        let bounds = solver.bounds_handle();
+        assert_eq!(f64::INFINITY, bounds.gap());
+
+        let gaps = std::thread::scope(|s| {
+            let reader = bounds.clone();
+            let done = &done;
+            let handle = s.spawn(move || {
+                let mut gaps = vec![];
+                while !done.load(AtomicOrdering::Acquire) {
+                    let gap = reader.gap();
+                    if gaps.last() != Some(&gap) {
+                        gaps.push(gap);
+                    }
+                    std::thread::yield_now();
+                }
+                gaps
+            });
+
+            let Completion{best_value, ..} = solver.maximize();
+            assert_eq!(Some(134), best_value);
+            done.store(true, AtomicOrdering::Release);
+            handle.join().unwrap()
+        });
+
+        assert!(gaps.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(134, bounds.best_lb());
+        assert_eq!(134, bounds.best_ub());
+        assert_eq!(0.0, bounds.gap());
+    }
+    #[test]
+    fn an_aborted_solver_returns_promptly_with_its_best_incumbent() {
+        /// A knapsack whose transitions are slow enough for the solver to be
+        /// aborted long before it is done
+        struct Sluggish<'a>(&'a Knapsack);
+        impl Problem for Sluggish<'_> {
+            type State = KnapsackState;
+            fn nb_variables(&self) -> usize {
+                self.0.nb_variables()
+            }
+            fn initial_state(&self) -> Self::State {
+                self.0.initial_state()
+            }
+            fn initial_value(&self) -> isize {
+                self.0.initial_value()
+            }
+            fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                self.0.transition(state, dec)
+            }
+            fn transition_cost(&self, state: &Self::State, next: &Self::State, dec: Decision) -> isize {
+                self.0.transition_cost(state, next, dec)
+            }
+            fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+                self.0.next_variable(depth, next_layer)
+            }
+            fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+                self.0.for_each_in_domain(variable, state, f)
+            }
+        }
+
+        let knapsack = Knapsack {
+            capacity: 200,
+            profit  : (0..40).map(|i| 10 + (i * 7) % 23).collect(),
+            weight  : (0..40).map(|i|  5 + (i * 11) % 17).collect(),
+        };
+        let problem = Sluggish(&knapsack);
+        let relax = KPRelax {pb: &knapsack};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(10);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        let abort = solver.abort_handle();
+
+        let (completion, elapsed) = std::thread::scope(|s| {
+            s.spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                abort.abort();
+            });
+            let start = std::time::Instant::now();
+            let completion = solver.maximize();
+            (completion, start.elapsed())
+        });
+
+        assert!(!completion.is_exact);
+        assert_eq!(Some(Reason::Aborted), completion.stopped_by);
+        assert!(elapsed < std::time::Duration::from_secs(1));
+        assert_eq!(completion.best_value, solver.best_value());
+        
+        // the abort is forgotten once the solver is reset
+        solver.reset();
+        assert!(!solver.abort_handle().is_aborted());
+    }
+    #[test]
+    fn the_progress_of_the_search_is_reported_to_the_listener() {
+        struct Record(Vec<ProgressEvent>);
+        impl Progress for Record {
+            fn report(&mut self, event: &ProgressEvent) {
+                self.0.push(*event);
+            }
+        }
+
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut record = Record(vec![]);
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            4,
+        ).with_progress(&mut record);
+
+        let Completion{best_value, ..} = solver.maximize();
+        let explored = solver.explored();
+        drop(solver);
+
+        let events = record.0;
+        assert_eq!(Some(&ProgressEvent::Finished { value: best_value, explored }), events.last());
+        let nodes = events.iter().filter(|e| matches!(e, ProgressEvent::NodeExplored { .. })).count();
+        assert_eq!(explored, nodes);
+    }
+    #[test]
+    fn a_deterministic_search_is_reproducible() {
+        struct Record(Vec<ProgressEvent>);
+        impl Progress for Record {
+            fn report(&mut self, event: &ProgressEvent) {
+                self.0.push(*event);
+            }
+        }
+
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let solve = || {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut record = Record(vec![]);
+            let mut solver = DdLel::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+                4,
+            ).with_deterministic(true).with_progress(&mut record);
+
+            let completion = solver.maximize();
+            let solution = solver.best_solution();
+            let stats = solver.statistics().unwrap();
+            drop(solver);
+            (completion, solution, stats, record.0)
+        };
+
+        let (first, first_solution, first_stats, first_events) = solve();
+        let (second, second_solution, second_stats, second_events) = solve();
+        assert!(first.is_exact);
+        assert_eq!(Some(380), first.best_value);
+        assert_eq!(first.best_value, second.best_value);
+        assert_eq!(first_solution, second_solution);
+        assert_eq!(first_events, second_events);
+        let counters = |stats: Statistics| (stats.explored, stats.peak_fringe, stats.restricted, stats.relaxed, stats.cutset_nodes, stats.pruned_by_bound);
+        assert_eq!(counters(first_stats), counters(second_stats));
+    }
+    #[test]
+    fn the_best_state_is_reached_by_replaying_the_best_solution() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        assert_eq!(None, solver.best_state());
+
+        solver.maximize();
+        let replayed = solver.best_solution().unwrap().iter()
+            .fold(problem.initial_state(), |state, decision| problem.transition(&state, *decision));
+        assert_eq!(Some(replayed), solver.best_state());
+    }
+    #[test]
+    fn the_best_state_is_forgotten_when_a_primal_is_set() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10, 20, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        solver.maximize();
+        assert!(solver.best_state().is_some());
+
+        solver.set_primal(1000, vec![]);
+        assert_eq!(None, solver.best_state());
+    }
+
+    #[test]
+    fn diverse_solutions_are_pairwise_distant() {
+        // the profit of each item equals its weight: many subsets nearly fill the knapsack
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19],
+            weight  : vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdFc::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        ).with_diversity(Diversity { k: 5, d: 4 });
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert_eq!(Some(50), outcome.best_value);
+
+        let pool = solver.diverse_solutions();
+        assert!(pool.len() > 1 && pool.len() <= 5);
+        assert_eq!(outcome.best_value, Some(pool[0].value));
+        for s in pool.iter() {
+            let chosen = s.solution.iter().filter(|d| d.value == 1).map(|d| d.variable.id());
+            assert_eq!(s.value, chosen.map(|i| problem.profit[i] as isize).sum::<isize>());
+        }
+        for (i, a) in pool.iter().enumerate() {
+            for b in pool[i+1..].iter() {
+                let distance = a.solution.iter().zip(b.solution.iter()).filter(|(x, y)| x != y).count();
+                assert!(distance >= 4);
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_solutions_are_suppressed_before_they_reach_the_pool() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdFc::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        ).with_diversity(Diversity { k: 5, d: 0 })
+         .with_solution_dedup(16);
+        let take = |v: usize, value: isize| Decision { variable: Variable(v), value };
+        let solution = vec![take(1, 1), take(2, 1)];
+        let shuffled = vec![take(2, 1), take(1, 1)];
+
+        solver.set_primal(220, solution.clone());
+        solver.set_primal(220, shuffled);
+        solver.set_primal(220, solution);
+        assert_eq!(1, solver.diverse_solutions().len());
+        assert_eq!(2, solver.suppressed_duplicates());
+    }
+
+    #[test]
+    fn restarts_preserve_the_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdFc::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        );
+        let expected = solver.maximize();
+        assert!(solver.restarts().is_empty());
+
+        let seeds = SeedLog::default();
+        let policy = Restarts { 
+            schedule: RestartSchedule::Fixed { nodes: 2 }, 
+            max_restarts: Some(3), 
+            ..Restarts::default() 
+        };
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdFc::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        ).with_restarts(policy).with_reseed(&seeds);
+        let actual = solver.maximize();
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert_eq!(solver.best_lower_bound(), solver.best_upper_bound());
+        let records = solver.restarts();
+        assert!(!records.is_empty());
+        assert_eq!(records.len() + 1, seeds.0.lock().len());
+        for (record, seed) in records.iter().zip(seeds.0.lock().iter()) {
+            assert_eq!(record.seed, *seed);
+            assert!(record.explored >= 2);
+        }
+        // the ongoing run completes the restarts
+        let runs = solver.runs();
+        assert_eq!(records.len() + 1, runs.len());
+        assert_eq!(solver.explored(), runs.iter().map(|r| r.explored).sum::<usize>());
+    }
+
+    #[test]
+    fn the_statistics_add_up_the_work_of_all_threads() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            4,
+        );
+        let outcome = solver.maximize();
+        assert_eq!(Some(380), outcome.best_value);
+
+        let stats = solver.statistics().unwrap();
+        assert_eq!(solver.explored(), stats.explored);
+        assert!(stats.restricted >= 1 && stats.restricted <= stats.explored);
+        assert!(stats.relaxed <= stats.restricted);
+        assert!(stats.cutset_nodes >= stats.explored - 1);
+        assert!(stats.peak_fringe >= 1);
+
+        solver.reset();
+        assert_eq!(Some(Statistics::default()), solver.statistics());
+    }
+    #[test]
+    fn the_presolve_reseeds_each_iteration_and_preserves_the_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let seeds = SeedLog::default();
+        let config = PresolveConfig { iterations: 2, width_schedule: vec![1, 2], seed: 11 };
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = DdFc::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+            2,
+        ).with_presolve(config.clone()).with_reseed(&seeds);
+        let outcome = solver.maximize();
+
+        assert!(outcome.is_exact);
+        assert_eq!(Some(380), outcome.best_value);
+        let record = solver.presolve_record().unwrap();
+        assert_eq!(2, record.iterations);
+        assert_eq!(isize::MIN, record.lb_before);
+        assert!(record.lb_after <= 380);
+        assert_eq!(vec![config.seed(0), config.seed(1)], *seeds.0.lock());
+    }
+
+    #[derive(Default)]
+    struct SeedLog(parking_lot::Mutex<Vec<u64>>);
+    impl Reseed for SeedLog {
+        fn reseed(&self, seed: u64) {
+            self.0.lock().push(seed);
+        }
     }
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "ooc", derive(serde::Serialize, serde::Deserialize))]
     struct KnapsackState {
         depth: usize,
         capacity: usize
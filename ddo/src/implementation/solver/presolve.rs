@@ -0,0 +1,196 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the *presolve* phase which can optionally be run by
+//! the solvers before the branch-and-bound starts. During that phase, the 
+//! solver repeatedly compiles a restricted DD rooted in the initial state of
+//! the problem with a growing maximum width (and, when the solver was given
+//! a randomized heuristic, a different seed each time). The best solution 
+//! found along the way becomes the incumbent of the search, so that the 
+//! branch-and-bound prunes more nodes from the very beginning.
+//!
+//! The outcome of the presolve (see `PresolveRecord`) tells how much it 
+//! improved the best lower bound, which helps tuning it.
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use crate::*;
+
+use super::restart::seed_of_run;
+
+/// This structure configures the presolve phase of the solver.
+///
+/// # Note
+/// The variable ordering is only randomized when the solver was given a 
+/// randomized heuristic (see `with_reseed`): that heuristic is re-seeded 
+/// before each iteration with a seed derived from `seed`. Otherwise, the 
+/// iterations only differ by their width.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresolveConfig {
+    /// The number of restricted DDs which are compiled
+    pub iterations: usize,
+    /// The maximum width of the DD compiled at each iteration. The iterations
+    /// past the end of the schedule reuse its last width; and when the 
+    /// schedule is empty, the width heuristic of the solver decides.
+    pub width_schedule: Vec<usize>,
+    /// The master seed from which the seed of each iteration is derived
+    pub seed: u64,
+}
+impl PresolveConfig {
+    /// Creates a configuration whose width starts at `first_width` and doubles
+    /// with each of the `iterations`
+    pub fn doubling(iterations: usize, first_width: usize, seed: u64) -> Self {
+        let width_schedule = (0..iterations)
+            .map(|i| first_width.max(1).saturating_mul(1_usize.checked_shl(i as u32).unwrap_or(usize::MAX)))
+            .collect();
+        Self { iterations, width_schedule, seed }
+    }
+    /// Returns the maximum width of the i-th iteration (`default` is the width
+    /// which the width heuristic of the solver gives for the root problem)
+    pub(crate) fn width(&self, i: usize, default: usize) -> usize {
+        self.width_schedule.get(i).or(self.width_schedule.last()).copied().unwrap_or(default).max(1)
+    }
+    /// Returns the seed of the i-th iteration
+    pub(crate) fn seed(&self, i: usize) -> u64 {
+        seed_of_run(self.seed, i)
+    }
+}
+
+/// The outcome of the presolve phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresolveRecord {
+    /// The number of restricted DDs which were compiled. It is lower than the
+    /// configured number of iterations when a DD turned out to be exact (the
+    /// problem is then solved) or when the search was stopped.
+    pub iterations: usize,
+    /// The number of iterations which improved the best lower bound
+    pub improving: usize,
+    /// The best lower bound before the presolve
+    pub lb_before: isize,
+    /// The best lower bound after the presolve
+    pub lb_after: isize,
+    /// The wall time spent presolving
+    pub elapsed: Duration,
+}
+impl PresolveRecord {
+    /// Returns how much the presolve improved the best lower bound. This is
+    /// None when there was no lower bound before the presolve (the bound 
+    /// which it then found, if any, is `lb_after`).
+    pub fn improvement(&self) -> Option<isize> {
+        (self.lb_before != isize::MIN).then(|| self.lb_after - self.lb_before)
+    }
+}
+impl fmt::Display for PresolveRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bound = |lb: isize| if lb == isize::MIN { "none".to_string() } else { lb.to_string() };
+        write!(f, "{} iterations ({} improving) in {:.3} seconds, lower bound {} -> {}",
+            self.iterations, self.improving, self.elapsed.as_secs_f32(), 
+            bound(self.lb_before), bound(self.lb_after))
+    }
+}
+
+/// Compiles a restricted DD rooted in the initial state of the problem with
+/// the given maximum width, and returns true iff that DD is exact.
+///
+/// # Note
+/// The compilation neither uses the cache nor the dominance checker of the 
+/// solver: when the variable ordering is randomized, what they would learn
+/// would not hold for the ordering of the actual search.
+pub(crate) fn compile_root_restriction<State, D>(
+    mdd: &mut D,
+    problem: &dyn Problem<State = State>,
+    relaxation: &dyn Relaxation<State = State>,
+    ranking: &dyn StateRanking<State = State>,
+    cutoff: &dyn Cutoff,
+    max_width: usize,
+    best_lb: isize,
+) -> Result<bool, Reason>
+where D: DecisionDiagram<State = State>
+{
+    let root = SubProblem {
+        state: Arc::new(problem.initial_state()),
+        value: problem.initial_value(),
+        path: vec![],
+        ub: isize::MAX,
+        depth: 0,
+        priority_bonus: 0,
+    };
+    compile_restriction(mdd, problem, relaxation, ranking, cutoff, max_width, best_lb, root)
+}
+
+/// Compiles a restricted DD rooted in the given `node` with the given maximum
+/// width, and returns true iff that DD is exact. Just like the restrictions
+/// of the presolve, those compiled ahead of the exploration of a node (see 
+/// `with_eager_restriction`) leave the cache and the dominance checker alone:
+/// the node still has to be explored afterwards.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn compile_restriction<State, D>(
+    mdd: &mut D,
+    problem: &dyn Problem<State = State>,
+    relaxation: &dyn Relaxation<State = State>,
+    ranking: &dyn StateRanking<State = State>,
+    cutoff: &dyn Cutoff,
+    max_width: usize,
+    best_lb: isize,
+    node: SubProblem<State>,
+) -> Result<bool, Reason>
+where D: DecisionDiagram<State = State>
+{
+    let cache = EmptyCache::default();
+    let dominance = EmptyDominanceChecker::default();
+    let compilation = CompilationInput {
+        comp_type: CompilationType::Restricted,
+        max_width,
+        problem,
+        relaxation,
+        ranking,
+        cutoff,
+        best_lb,
+        cache: &cache,
+        dominance: &dominance,
+    };
+    Ok(mdd.compile(&compilation, node)?.is_exact)
+}
+
+#[cfg(test)]
+mod test_presolve {
+    use crate::*;
+
+    #[test]
+    fn the_widths_past_the_end_of_the_schedule_reuse_its_last_width() {
+        let config = PresolveConfig { iterations: 4, width_schedule: vec![2, 8], seed: 0 };
+        assert_eq!(vec![2, 8, 8, 8], (0..4).map(|i| config.width(i, 100)).collect::<Vec<_>>());
+
+        let config = PresolveConfig { iterations: 2, width_schedule: vec![], seed: 0 };
+        assert_eq!(100, config.width(1, 100));
+    }
+    #[test]
+    fn the_doubling_schedule_doubles_the_width() {
+        let config = PresolveConfig::doubling(4, 3, 7);
+        assert_eq!(vec![3, 6, 12, 24], config.width_schedule);
+        assert_eq!(7, config.seed);
+    }
+    #[test]
+    fn the_improvement_is_unknown_without_a_previous_bound() {
+        let mut record = PresolveRecord { iterations: 3, improving: 1, lb_before: isize::MIN, lb_after: 10, elapsed: Default::default() };
+        assert_eq!(None, record.improvement());
+        record.lb_before = 4;
+        assert_eq!(Some(6), record.improvement());
+    }
+}
@@ -0,0 +1,375 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides a quick report of the structural features of an 
+//! instance which correlate with its difficulty (see `profile_instance`). It 
+//! is meant to be looked at before launching a long run: all the features are
+//! either read off the model or computed from a few samples, under a small 
+//! time budget.
+
+use std::{hash::Hash, iter, mem::size_of, sync::Arc, time::{Duration, Instant}};
+
+use fxhash::FxHashSet;
+
+use crate::{estimate_tree_size, repair, sample_solutions, CompilationInput, CompilationType, CutoffFn, Decision, DecisionDiagram, DefaultMDDLEL, EmptyCache, EmptyDominanceChecker, EstimateConfig, Problem, Relaxation, StateRanking, SubProblem};
+
+use super::{gap::relative_gap, restart::seed_of_run};
+
+/// This configures how much effort `profile_instance` puts in each feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfileConfig {
+    /// The number of random dives used to sample the domain sizes, and the 
+    /// number of dives used to estimate the size of the tree
+    pub samples: usize,
+    /// The seed of the random dives
+    pub seed: u64,
+    /// The number of exact layers over which the growth factor is measured
+    pub exact_layers: usize,
+    /// The maximum width of the relaxed DD compiled from the root. The exact 
+    /// layers stop being developed as soon as one of them is wider than this.
+    pub max_width: usize,
+}
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        Self { samples: 100, seed: 0, exact_layers: 5, max_width: 100 }
+    }
+}
+
+/// The structural features of an instance (see `profile_instance`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstanceProfile {
+    /// The number of variables of the problem
+    pub nb_variables: usize,
+    /// The average number of values in the domain of the variables, over the
+    /// states met by the sampled dives (zero when no dive could be sampled)
+    pub avg_domain_size: f64,
+    /// The size of a state in bytes (not counting the memory it owns on the
+    /// heap, if any)
+    pub state_bytes: usize,
+    /// The upper bound derived by a relaxed DD compiled from the root (None
+    /// when the budget got exhausted before it was compiled)
+    pub root_bound: Option<isize>,
+    /// The value of a greedy dive from the root (None when that dive reached
+    /// a dead end)
+    pub greedy_value: Option<isize>,
+    /// The relative gap between the greedy value and the root bound. It is 
+    /// infinite when either of them is unknown.
+    pub initial_gap: f64,
+    /// The estimated number of nodes in the search tree (see 
+    /// `estimate_tree_size`)
+    pub estimated_tree_size: f64,
+    /// The number of exact layers which were developed below the root
+    pub exact_layers: usize,
+    /// The factor by which the width of the exact layers grows on average 
+    /// from one layer to the next (one when no layer was developed)
+    pub growth_factor: f64,
+    /// The time it took to profile the instance
+    pub elapsed: Duration,
+}
+impl InstanceProfile {
+    /// The header of the CSV lines produced by `to_csv`
+    pub const CSV_HEADER: &'static str = "nb_variables,avg_domain_size,state_bytes,root_bound,greedy_value,initial_gap,estimated_tree_size,exact_layers,growth_factor,elapsed";
+
+    /// Returns the profile as a line of CSV (without its header). The unknown
+    /// values are left empty.
+    pub fn to_csv(&self) -> String {
+        let opt = |v: Option<isize>| v.map(|v| v.to_string()).unwrap_or_default();
+        format!("{},{:.3},{},{},{},{:.6},{:.1},{},{:.3},{:.3}",
+            self.nb_variables,
+            self.avg_domain_size,
+            self.state_bytes,
+            opt(self.root_bound),
+            opt(self.greedy_value),
+            self.initial_gap,
+            self.estimated_tree_size,
+            self.exact_layers,
+            self.growth_factor,
+            self.elapsed.as_secs_f64())
+    }
+}
+
+/// Computes the structural features of the given instance within the given
+/// time `budget`. The cheapest features are computed first: the number of 
+/// variables, the size of the states and the value of a greedy dive are 
+/// always computed. The budget is then spent on the sampled domain sizes, the
+/// estimated size of the tree, the growth of the exact layers and the bound
+/// of a relaxed DD (in that order). The features which could not be computed
+/// in time are reported as unknown (or based on fewer samples).
+pub fn profile_instance<State>(
+    problem: &dyn Problem<State = State>,
+    relaxation: &dyn Relaxation<State = State>,
+    ranking: &dyn StateRanking<State = State>,
+    config: &ProfileConfig,
+    budget: Duration,
+) -> InstanceProfile
+where State: Eq + Hash + Clone
+{
+    let start = Instant::now();
+    let deadline = start + budget;
+    let in_time = || Instant::now() < deadline;
+
+    let greedy_value = repair(problem, &[]).ok().map(|r| r.value);
+
+    // the domain sizes met by the random dives
+    let (mut domains, mut states) = (0, 0);
+    for i in (0..config.samples).take_while(|_| in_time()) {
+        let sampled = sample_solutions(problem, 1, seed_of_run(config.seed, i));
+        if let Some(Ok(sample)) = sampled.first() {
+            let mut state = problem.initial_state();
+            for decision in sample.solution.iter().copied() {
+                domains += domain_size(problem, &state, decision);
+                states  += 1;
+                state = problem.transition(&state, decision);
+            }
+        }
+    }
+    let avg_domain_size = if states == 0 { 0.0 } else { domains as f64 / states as f64 };
+
+    // the estimated size of the tree
+    let estimate = EstimateConfig { best_lb: greedy_value.unwrap_or(isize::MIN) };
+    let dives = (0..config.samples).take_while(|_| in_time())
+        .map(|i| estimate_tree_size(problem, relaxation, &estimate, 1, seed_of_run(config.seed, i)).mean)
+        .collect::<Vec<_>>();
+    let estimated_tree_size = if dives.is_empty() { 0.0 } else { dives.iter().sum::<f64>() / dives.len() as f64 };
+
+    // the growth of the exact layers
+    let mut layer = iter::once(problem.initial_state()).collect::<FxHashSet<_>>();
+    let mut exact_layers = 0;
+    while exact_layers < config.exact_layers && layer.len() <= config.max_width && in_time() {
+        let Some(var) = problem.next_variable(exact_layers, &mut layer.iter()) else { break };
+        let mask = problem.filter_layer(var, &mut layer.iter());
+        let mut next = FxHashSet::default();
+        for state in layer.iter() {
+            problem.for_each_in_domain(var, state, &mut |d: Decision| {
+                if mask.allows(d.value) {
+                    next.insert(problem.transition(state, d));
+                }
+            });
+        }
+        if next.is_empty() {
+            break;
+        }
+        layer = next;
+        exact_layers += 1;
+    }
+    let growth_factor = if exact_layers == 0 { 1.0 } else { (layer.len() as f64).powf(1.0 / exact_layers as f64) };
+
+    // the bound of a relaxed dd compiled from the root
+    let root_bound = if in_time() {
+        let cache = EmptyCache::default();
+        let dominance = EmptyDominanceChecker::default();
+        let cutoff = CutoffFn::new(|| !in_time());
+        let input = CompilationInput {
+            comp_type: CompilationType::Relaxed,
+            problem,
+            relaxation,
+            ranking,
+            cutoff: &cutoff,
+            max_width: config.max_width,
+            best_lb: isize::MIN,
+            cache: &cache,
+            dominance: &dominance,
+        };
+        let root = SubProblem {
+            state: Arc::new(problem.initial_state()),
+            value: problem.initial_value(),
+            path: vec![],
+            ub: isize::MAX,
+            depth: 0,
+            priority_bonus: 0,
+        };
+        let mut mdd = DefaultMDDLEL::default();
+        mdd.compile(&input, root).ok().and_then(|_| mdd.best_value())
+    } else {
+        None
+    };
+
+    let initial_gap = relative_gap(
+        greedy_value.unwrap_or(isize::MIN), 
+        root_bound.unwrap_or(isize::MAX));
+
+    InstanceProfile {
+        nb_variables: problem.nb_variables(),
+        avg_domain_size,
+        state_bytes: size_of::<State>(),
+        root_bound,
+        greedy_value,
+        initial_gap,
+        estimated_tree_size,
+        exact_layers,
+        growth_factor,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Returns the number of values in the domain of the variable of `decision`
+/// in the given state
+fn domain_size<State>(problem: &dyn Problem<State = State>, state: &State, decision: Decision) -> usize {
+    let mut size = 0;
+    problem.for_each_in_domain(decision.variable, state, &mut |_: Decision| size += 1);
+    size
+}
+
+#[cfg(test)]
+mod test_profile {
+    use std::{mem::size_of, time::Duration};
+
+    use crate::*;
+
+    /// Each binary decision is remembered in the state: the width of the 
+    /// exact layers doubles with each variable
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct DoublingState {
+        depth: usize,
+        code: u64,
+    }
+    struct Doubling(usize);
+    impl Problem for Doubling {
+        type State = DoublingState;
+        fn nb_variables(&self) -> usize {
+            self.0
+        }
+        fn initial_state(&self) -> Self::State {
+            DoublingState { depth: 0, code: 0 }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, decision: Decision) -> Self::State {
+            DoublingState { depth: state.depth + 1, code: 2 * state.code + decision.value as u64 }
+        }
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, decision: Decision) -> isize {
+            decision.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            (depth < self.0).then_some(Variable(depth))
+        }
+        fn for_each_in_domain(&self, variable: Variable, _: &Self::State, f: &mut dyn DecisionCallback) {
+            f.apply(Decision { variable, value: 0 });
+            f.apply(Decision { variable, value: 1 });
+        }
+    }
+    struct DoublingRelax;
+    impl Relaxation for DoublingRelax {
+        type State = DoublingState;
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            *states.max_by_key(|s| s.code).unwrap()
+        }
+        fn relax(&self, _: &Self::State, _: &Self::State, _: &Self::State, _: Decision, cost: isize) -> isize {
+            cost
+        }
+    }
+    struct DoublingRanking;
+    impl StateRanking for DoublingRanking {
+        type State = DoublingState;
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.code.cmp(&b.code)
+        }
+    }
+
+    /// Taking the first item (worth 1) forbids taking the second (worth 5): 
+    /// a greedy dive falls in that trap
+    struct Trap;
+    impl Problem for Trap {
+        type State = DoublingState;
+        fn nb_variables(&self) -> usize {
+            2
+        }
+        fn initial_state(&self) -> Self::State {
+            DoublingState { depth: 0, code: 0 }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, decision: Decision) -> Self::State {
+            DoublingState { depth: state.depth + 1, code: 2 * state.code + decision.value as u64 }
+        }
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, decision: Decision) -> isize {
+            [1, 5][decision.variable.id()] * decision.value
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            (depth < 2).then_some(Variable(depth))
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            f.apply(Decision { variable, value: 0 });
+            if state.code == 0 {
+                f.apply(Decision { variable, value: 1 });
+            }
+        }
+    }
+
+    #[test]
+    fn each_feature_is_computed_on_a_doubling_instance() {
+        let config = ProfileConfig { exact_layers: 3, ..Default::default() };
+        let profile = profile_instance(&Doubling(4), &DoublingRelax, &DoublingRanking, &config, Duration::from_secs(10));
+
+        assert_eq!(4, profile.nb_variables);
+        assert_eq!(2.0, profile.avg_domain_size);
+        assert_eq!(size_of::<DoublingState>(), profile.state_bytes);
+        assert_eq!(Some(4), profile.greedy_value);
+        assert_eq!(Some(4), profile.root_bound);
+        assert_eq!(0.0, profile.initial_gap);
+        // 1 + 2 + 4 + 8 + 16 nodes, whatever the dive
+        assert_eq!(31.0, profile.estimated_tree_size);
+        assert_eq!(3, profile.exact_layers);
+        assert!((profile.growth_factor - 2.0).abs() < 1e-9);
+    }
+    #[test]
+    fn the_initial_gap_tells_how_far_the_greedy_dive_is_from_the_bound() {
+        let profile = profile_instance(&Trap, &DoublingRelax, &DoublingRanking, &ProfileConfig::default(), Duration::from_secs(10));
+
+        assert_eq!(Some(1), profile.greedy_value);
+        assert_eq!(Some(5), profile.root_bound);
+        assert!((profile.initial_gap - 0.8).abs() < 1e-9);
+        // the root has two children, only one of which has two children
+        assert_eq!(2, profile.exact_layers);
+        assert!((profile.growth_factor - 3.0_f64.sqrt()).abs() < 1e-9);
+    }
+    #[test]
+    fn the_exact_layers_stop_growing_past_the_max_width() {
+        let config = ProfileConfig { exact_layers: 10, max_width: 3, ..Default::default() };
+        let profile = profile_instance(&Doubling(8), &DoublingRelax, &DoublingRanking, &config, Duration::from_secs(10));
+
+        assert_eq!(2, profile.exact_layers);
+        assert!((profile.growth_factor - 2.0).abs() < 1e-9);
+    }
+    #[test]
+    fn an_exhausted_budget_only_yields_the_cheap_features() {
+        let profile = profile_instance(&Doubling(4), &DoublingRelax, &DoublingRanking, &ProfileConfig::default(), Duration::ZERO);
+
+        assert_eq!(4, profile.nb_variables);
+        assert_eq!(Some(4), profile.greedy_value);
+        assert_eq!(0.0, profile.avg_domain_size);
+        assert_eq!(0.0, profile.estimated_tree_size);
+        assert_eq!(0, profile.exact_layers);
+        assert_eq!(1.0, profile.growth_factor);
+        assert_eq!(None, profile.root_bound);
+        assert_eq!(f64::INFINITY, profile.initial_gap);
+    }
+    #[test]
+    fn the_csv_line_has_one_field_per_column_of_the_header() {
+        let profile = profile_instance(&Doubling(4), &DoublingRelax, &DoublingRanking, &ProfileConfig::default(), Duration::ZERO);
+        let line = profile.to_csv();
+
+        assert_eq!(InstanceProfile::CSV_HEADER.split(',').count(), line.split(',').count());
+        assert!(line.starts_with("4,0.000,16,,4,inf,"));
+    }
+}
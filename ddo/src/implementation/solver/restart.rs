@@ -0,0 +1,275 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the *restart* policy which can optionally be used by
+//! the solvers. On some instances, the first variable ordering turns out to be
+//! pathological and the search would be better off starting all over again 
+//! with a different ordering. When restarts are enabled, the solver abandons
+//! the current run of the search after a given number of explored nodes. It 
+//! then clears its fringe, re-seeds the randomized heuristics and starts again
+//! from the root; while retaining its incumbent solution and best bounds.
+//!
+//! Each run is accounted for separately (see `RestartRecord`): the nodes it 
+//! explored, the time it took and the bounds it left behind. The counters of 
+//! all the runs add up to those of the whole search.
+
+use std::time::{Duration, Instant};
+
+/// This enum tells how many nodes can be explored during each run of the search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartSchedule {
+    /// Each run explores the same number of nodes
+    Fixed { nodes: usize },
+    /// The i-th run explores `unit * luby(i)` nodes where `luby` is the 
+    /// sequence 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, ... This sequence 
+    /// grows unboundedly, which guarantees that the search eventually completes.
+    Luby { unit: usize },
+}
+
+/// This structure configures when the solver should restart its search.
+///
+/// # Note
+/// By default, the cache and the dominance checker are cleared upon restart.
+/// Setting `keep_cache` retains the thresholds of the cache (only the states
+/// which were marked as explored are forgotten) and the dominance checker; 
+/// which is only safe if the state of a node identifies its residual problem
+/// regardless of the order in which variables are decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Restarts {
+    /// The number of nodes explored during each run
+    pub schedule: RestartSchedule,
+    /// The master seed from which the seed of each run is derived
+    pub seed: u64,
+    /// If set, the search is never restarted more than this many times. This
+    /// is especially useful with a fixed schedule since the search might 
+    /// otherwise never be given enough nodes to complete.
+    pub max_restarts: Option<usize>,
+    /// Whether the cache and the dominance checker are retained upon restart
+    pub keep_cache: bool,
+}
+impl Default for Restarts {
+    fn default() -> Self {
+        Self {
+            schedule: RestartSchedule::Luby { unit: 1000 },
+            seed: 0,
+            max_restarts: None,
+            keep_cache: false,
+        }
+    }
+}
+
+/// This records the progress made during one run of the search (which was 
+/// either interrupted by a restart or is the ongoing run)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartRecord {
+    /// The index of the run (the first run is numbered 0)
+    pub run: usize,
+    /// The seed which was used during the run
+    pub seed: u64,
+    /// The number of nodes explored during the run
+    pub explored: usize,
+    /// The best lower bound when the run was interrupted
+    pub best_lb: isize,
+    /// The best upper bound when the run was interrupted
+    pub best_ub: isize,
+    /// The wall time spent during the run
+    pub elapsed: Duration,
+}
+
+/// The bookkeeping required to know when the next restart is due and to keep
+/// track of the runs which already happened.
+pub(crate) struct RestartTracker {
+    pub(crate) policy: Restarts,
+    /// The index of the current run
+    run: usize,
+    /// The number of explored nodes when the current run started
+    run_start: usize,
+    /// The instant when the current run started (none until the search starts)
+    run_clock: Option<Instant>,
+    /// The progress made during each interrupted run
+    pub(crate) records: Vec<RestartRecord>,
+}
+impl RestartTracker {
+    pub(crate) fn new(policy: Restarts) -> Self {
+        Self { policy, run: 0, run_start: 0, run_clock: None, records: vec![] }
+    }
+    /// Returns the seed of the current run
+    pub(crate) fn seed(&self) -> u64 {
+        seed_of_run(self.policy.seed, self.run)
+    }
+    /// Returns the number of nodes which can be explored during the current run
+    fn budget(&self) -> usize {
+        match self.policy.schedule {
+            RestartSchedule::Fixed { nodes } => nodes.max(1),
+            RestartSchedule::Luby  { unit  } => unit.max(1).saturating_mul(luby(self.run + 1)),
+        }
+    }
+    /// Returns true iff the current run must be interrupted
+    pub(crate) fn is_due(&self, explored: usize) -> bool {
+        let allowed = self.policy.max_restarts.is_none_or(|max| self.run < max);
+        allowed && explored - self.run_start >= self.budget()
+    }
+    /// Starts measuring the time spent during the current run (unless it is
+    /// already being measured)
+    pub(crate) fn start_clock(&mut self) {
+        self.run_clock.get_or_insert_with(Instant::now);
+    }
+    /// Returns the progress made so far during the current run
+    pub(crate) fn ongoing(&self, explored: usize, best_lb: isize, best_ub: isize) -> RestartRecord {
+        RestartRecord {
+            run: self.run,
+            seed: self.seed(),
+            explored: explored - self.run_start,
+            best_lb,
+            best_ub,
+            elapsed: self.run_clock.map(|c| c.elapsed()).unwrap_or_default(),
+        }
+    }
+    /// Returns the progress made during each run, the current one included
+    pub(crate) fn runs(&self, explored: usize, best_lb: isize, best_ub: isize) -> Vec<RestartRecord> {
+        let mut runs = self.records.clone();
+        runs.push(self.ongoing(explored, best_lb, best_ub));
+        runs
+    }
+    /// Records the progress of the current run and starts the next one. This
+    /// method returns the seed of the new run.
+    pub(crate) fn restart(&mut self, explored: usize, best_lb: isize, best_ub: isize) -> u64 {
+        self.records.push(self.ongoing(explored, best_lb, best_ub));
+        self.run += 1;
+        self.run_start = explored;
+        self.run_clock = Some(Instant::now());
+        self.seed()
+    }
+}
+
+/// Returns the i-th term (starting at 1) of the Luby sequence
+fn luby(i: usize) -> usize {
+    let mut i = i.max(1);
+    loop {
+        // smallest k such that i <= 2^k - 1
+        let mut k = 1;
+        while (1_usize << k) - 1 < i {
+            k += 1;
+        }
+        if i == (1 << k) - 1 {
+            return 1 << (k - 1);
+        }
+        i -= (1 << (k - 1)) - 1;
+    }
+}
+
+/// Derives the seed of the given run from the master seed (splitmix64)
+pub(crate) fn seed_of_run(master: u64, run: usize) -> u64 {
+    let mut z = master.wrapping_add((run as u64).wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod test_restarts {
+    use super::*;
+
+    #[test]
+    fn luby_sequence() {
+        let expected = [1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, 1];
+        let actual   = (1..=16).map(luby).collect::<Vec<_>>();
+        assert_eq!(expected.to_vec(), actual);
+    }
+    #[test]
+    fn fixed_schedule_restarts_every_n_nodes() {
+        let mut tracker = RestartTracker::new(Restarts { 
+            schedule: RestartSchedule::Fixed { nodes: 10 }, ..Default::default() });
+        assert!(!tracker.is_due(9));
+        assert!(tracker.is_due(10));
+        tracker.restart(10, 0, 100);
+        assert!(!tracker.is_due(19));
+        assert!(tracker.is_due(20));
+    }
+    #[test]
+    fn luby_schedule_follows_the_luby_sequence() {
+        let mut tracker = RestartTracker::new(Restarts { 
+            schedule: RestartSchedule::Luby { unit: 10 }, ..Default::default() });
+        let mut explored = 0;
+        let mut budgets  = vec![];
+        for _ in 0..7 {
+            let start = explored;
+            while !tracker.is_due(explored) {
+                explored += 1;
+            }
+            budgets.push(explored - start);
+            tracker.restart(explored, 0, 0);
+        }
+        assert_eq!(vec![10, 10, 20, 10, 10, 20, 40], budgets);
+    }
+    #[test]
+    fn max_restarts_is_enforced() {
+        let mut tracker = RestartTracker::new(Restarts { 
+            schedule: RestartSchedule::Fixed { nodes: 1 }, max_restarts: Some(2), ..Default::default() });
+        tracker.restart(1, 0, 0);
+        tracker.restart(2, 0, 0);
+        assert!(!tracker.is_due(1000));
+        assert_eq!(2, tracker.records.len());
+    }
+    #[test]
+    fn records_track_the_progress_of_each_run() {
+        let mut tracker = RestartTracker::new(Restarts { 
+            schedule: RestartSchedule::Fixed { nodes: 5 }, ..Default::default() });
+        let seed0 = tracker.seed();
+        let seed1 = tracker.restart(7, 3, 10);
+        tracker.restart(15, 4, 9);
+        // the clock was never started
+        assert_eq!(vec![
+            RestartRecord { run: 0, seed: seed0, explored: 7, best_lb: 3, best_ub: 10, elapsed: Duration::ZERO },
+            RestartRecord { run: 1, seed: seed1, explored: 8, best_lb: 4, best_ub: 9, elapsed: tracker.records[1].elapsed },
+        ], tracker.records);
+    }
+    #[test]
+    fn the_runs_add_up_to_the_whole_search() {
+        let mut tracker = RestartTracker::new(Restarts { 
+            schedule: RestartSchedule::Fixed { nodes: 5 }, ..Default::default() });
+        let start = Instant::now();
+        tracker.start_clock();
+        tracker.restart(7, 3, 10);
+        tracker.restart(15, 4, 9);
+        let runs = tracker.runs(18, 5, 8);
+        let total = start.elapsed();
+
+        assert_eq!(vec![0, 1, 2], runs.iter().map(|r| r.run).collect::<Vec<_>>());
+        assert_eq!(vec![7, 8, 3], runs.iter().map(|r| r.explored).collect::<Vec<_>>());
+        assert_eq!(18, runs.iter().map(|r| r.explored).sum::<usize>());
+        assert!(runs.iter().map(|r| r.elapsed).sum::<Duration>() <= total);
+        assert_eq!((5, 8), (runs[2].best_lb, runs[2].best_ub));
+        // the ongoing run is not recorded
+        assert_eq!(2, tracker.records.len());
+    }
+    #[test]
+    fn the_same_master_seed_yields_the_same_seeds() {
+        let seeds = |master| {
+            let mut tracker = RestartTracker::new(Restarts { seed: master, ..Default::default() });
+            let mut seeds = vec![tracker.seed()];
+            for i in 1..10 { seeds.push(tracker.restart(i, 0, 0)); }
+            seeds
+        };
+        assert_eq!(seeds(42), seeds(42));
+        assert_ne!(seeds(42), seeds(43));
+        let s = seeds(42);
+        assert!(s.iter().enumerate().all(|(i, x)| s[i+1..].iter().all(|y| x != y)));
+    }
+}
@@ -26,9 +26,33 @@
 //! where multithreading is not an option; then you might want to use this 
 //! implementation instead.
 use std::clone::Clone;
-use std::{sync::Arc, hash::Hash};
+use std::{sync::Arc, hash::Hash, time::{Duration, Instant}};
+#[cfg(feature = "checkpoint")]
+use std::io::{self, Read, Write};
 
-use crate::{Fringe, Decision, Problem, Relaxation, StateRanking, WidthHeuristic, Cutoff, SubProblem, DecisionDiagram, CompilationInput, CompilationType, Solver, Solution, Completion, Reason, Cache, EmptyCache, DefaultMDDLEL, DominanceChecker};
+#[cfg(feature = "checkpoint")]
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{Fringe, Decision, Problem, Relaxation, StateRanking, WidthHeuristic, Cutoff, SubProblem, DecisionDiagram, CompilationInput, CompilationType, Solver, Solution, Completion, Reason, Termination, Cache, EmptyCache, DefaultMDDLEL, DominanceChecker, DualRefresh, DualRefreshRecord, Restarts, RestartRecord, PresolveConfig, PresolveRecord, Statistics, Reseed, CarryOver, IncumbentListener, Progress, ProgressEvent, SharedIncumbent, BoundsHandle, AbortHandle, MemoryStats, Diversity, DiverseSolution, EstimateReport, CompilationLatencies, LatencyWatch, SlowCompilation, FringeSampling, FringeSnapshot, TreeExport, PruneReason, WarmStartError};
+use crate::eval::check_warm_start;
+
+use super::dual_refresh::{DualRefreshSchedule, compile_root_relaxation, sweep_fringe};
+use super::manual::ManualRoots;
+use super::restart::RestartTracker;
+use super::presolve::{compile_root_restriction, compile_restriction};
+use super::diversity::DiversityPool;
+use super::solution_pool::SolutionPool;
+use super::fringe_snapshot::FringeSampler;
+use super::dedup::SeenSolutions;
+use super::estimate::TreeEstimate;
+use super::verify::NodeVerifier;
+use super::latency::LatencyTracker;
+use super::tree_tracker::TreeTracker;
+use super::deadline::{Deadline, TimeLimit};
+use super::gap::{relative_gap, target_reason, GapTolerance};
+use super::carry_over::{notify_carry_over, CompiledNode};
+#[cfg(feature = "checkpoint")]
+use super::checkpoint::{Checkpoint, SavedNode};
 
 /// The workload a thread can get from the shared state
 enum WorkLoad<T> {
@@ -40,6 +64,16 @@ enum WorkLoad<T> {
     WorkItem { node: SubProblem<T> },
 }
 
+/// What happened during one step of the search (see `SequentialSolver::step`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepResult {
+    /// One node was taken off the fringe and explored
+    Explored,
+    /// The search is over: it either completed or was stopped. The outcome
+    /// of the search is told by `finish`.
+    Finished,
+}
+
 /// This is the structure implementing an single-threaded MDD solver.
 ///
 /// # Example Usage
@@ -242,6 +276,9 @@ where D: DecisionDiagram<State = State> + Default,
     best_ub: isize,
     /// If set, this keeps the info about the best solution so far.
     best_sol: Option<Vec<Decision>>,
+    /// The state of the terminal node reached by the best solution so far
+    /// (None when that solution was set with `set_primal`)
+    best_state: Option<State>,
     /// If we decide not to go through a complete proof of optimality, this is
     /// the reason why we took that decision.
     abort_proof: Option<Reason>,
@@ -252,6 +289,98 @@ where D: DecisionDiagram<State = State> + Default,
     /// Data structure containing info about past compilations used to prune the search
     cache: C,
     dominance: &'a (dyn DominanceChecker<State = State>),
+    /// If set, this tells when the solver should recompile a wider relaxation of
+    /// the root problem in order to tighten its best upper bound.
+    dual_refresh: Option<DualRefreshSchedule>,
+    /// If set, this tells when the solver should restart its search
+    restarts: Option<RestartTracker>,
+    /// If set, this tells how to presolve the problem before the search
+    presolve: Option<PresolveConfig>,
+    /// The outcome of the presolve (if it was run)
+    presolve_record: Option<PresolveRecord>,
+    /// The randomized heuristics which must be re-seeded upon restart
+    reseed: Option<&'a dyn Reseed>,
+    /// The adaptive heuristic which learns from each compilation
+    carry_over: Option<&'a dyn CarryOver>,
+    /// The estimated memory usage of the solver
+    memory: MemoryStats,
+    /// The statistics of the search (see `Statistics`)
+    stats: Statistics,
+    /// If set, the pool of diverse solutions maintained by the solver
+    diversity: Option<DiversityPool>,
+    /// The pool of the k best distinct solutions (if enabled)
+    pool: Option<SolutionPool>,
+    /// Takes the snapshots of the composition of the fringe (if enabled)
+    fringe_sampler: Option<FringeSampler>,
+    /// If set, the solutions which were recently offered to the pool of 
+    /// diverse solutions are not offered again
+    dedup: Option<SeenSolutions>,
+    /// If set, a relaxed DD whose cut-set comprises more than this many nodes
+    /// is compiled once again with twice the width before being enqueued
+    wider_recompile: Option<usize>,
+    /// The number of relaxed DDs that have been compiled once again with a
+    /// wider width
+    recompiled: usize,
+    /// If set, the maximum width of the restricted DD which is compiled from
+    /// each cut-set node before it is pushed, and the size of the fringe from
+    /// which this is no longer done (see `with_eager_restriction`)
+    eager_restriction: Option<(usize, usize)>,
+    /// The number of cut-set nodes from which a restricted DD was compiled 
+    /// before they were pushed onto the fringe
+    eager_restricted: usize,
+    /// The number of cut-set nodes which were not pushed onto the fringe 
+    /// because of the restricted DD compiled from them
+    eager_pruned: usize,
+    /// The number of cut-set nodes whose upper bound was tightened by the 
+    /// best value of the relaxed DD they were taken from
+    clamped: usize,
+    /// The number of nodes which were pushed onto the fringe with a priority
+    /// bonus
+    boosted: usize,
+    /// The upper bound derived from the first compilation of the root problem
+    root_bound: Option<isize>,
+    /// The value of the first solution found by the search
+    first_incumbent: Option<isize>,
+    /// The subproblems whose upper bound does not exceed the best lower bound
+    /// by more than this slack are pruned
+    absolute_slack: isize,
+    /// If set, the search stops once this many nodes have been explored
+    node_budget: Option<usize>,
+    /// The wall-clock time `maximize` may run (see `Solver::set_time_limit`)
+    time_limit: TimeLimit,
+    /// The search stops once the relative gap drops below this tolerance
+    gap_tolerance: GapTolerance,
+    /// If set, the search stops once it is known whether a solution reaches
+    /// this value (see `with_target`)
+    target: Option<isize>,
+    /// The subproblems pushed with `push_subproblem` (see `with_manual_root`)
+    roots: ManualRoots<State>,
+    /// If set, the size of the search tree is estimated when the search starts
+    tree_estimate: Option<TreeEstimate>,
+    /// When set, each processed node is cross-checked (see `with_verify`)
+    verify: bool,
+    /// The latency histograms of the compilations
+    latency: LatencyTracker,
+    /// Where the explored search tree is reported (see `with_tree_export`)
+    tree: Option<TreeTracker<'a>>,
+    /// Who gets told about each new best solution (see `with_incumbent_listener`)
+    incumbent_listener: Option<&'a mut dyn IncumbentListener>,
+    /// Who gets told about the progress of the search (see `with_progress`)
+    progress: Option<&'a mut dyn Progress>,
+    /// The handle on the incumbent which other threads may read (see 
+    /// `with_shared_incumbent`)
+    shared_incumbent: Option<SharedIncumbent>,
+    /// The handle on the best bounds which other threads may read (see 
+    /// `bounds_handle`)
+    bounds: BoundsHandle,
+    /// The flag which other threads may set to stop the search (see 
+    /// `abort_handle`)
+    abort: AbortHandle,
+    /// The nodes which were left open when the search was stopped before its
+    /// end. It is None unless the solver keeps them (see `with_checkpointing`)
+    suspended: Option<Vec<SubProblem<State>>>,
+    /// Whether a search driven with `step` has been started and not finished
+    stepping: bool,
 }
 
 impl<'a, State, D, C>  SequentialSolver<'a, State, D, C>
@@ -289,6 +418,7 @@ where
             cutoff,
             //
             best_sol: None,
+            best_state: None,
             best_lb: isize::MIN,
             best_ub: isize::MAX,
             fringe,
@@ -299,17 +429,615 @@ where
             mdd: D::default(),
             cache: C::default(),
             dominance,
+            dual_refresh: None,
+            restarts: None,
+            presolve: None,
+            presolve_record: None,
+            reseed: None,
+            carry_over: None,
+            memory: MemoryStats::default(),
+            stats: Statistics::default(),
+            diversity: None,
+            pool: None,
+            fringe_sampler: None,
+            dedup: None,
+            wider_recompile: None,
+            recompiled: 0,
+            eager_restriction: None,
+            eager_restricted: 0,
+            eager_pruned: 0,
+            clamped: 0,
+            boosted: 0,
+            root_bound: None,
+            first_incumbent: None,
+            absolute_slack: 0,
+            node_budget: None,
+            time_limit: TimeLimit::default(),
+            gap_tolerance: GapTolerance::default(),
+            target: None,
+            roots: ManualRoots::default(),
+            tree_estimate: None,
+            verify: false,
+            latency: LatencyTracker::default(),
+            tree: None,
+            incumbent_listener: None,
+            progress: None,
+            shared_incumbent: None,
+            bounds: BoundsHandle::default(),
+            abort: AbortHandle::default(),
+            suspended: None,
+            stepping: false,
+        }
+    }
+    /// Enables the periodic refresh of the dual bound (see `DualRefresh`)
+    pub fn with_dual_refresh(mut self, policy: DualRefresh) -> Self {
+        self.dual_refresh = Some(DualRefreshSchedule::new(policy));
+        self
+    }
+    /// Returns the outcome of all the dual bound refreshes that have occurred
+    pub fn dual_refreshes(&self) -> &[DualRefreshRecord] {
+        self.dual_refresh.as_ref().map(|s| s.records.as_slice()).unwrap_or_default()
+    }
+    /// Enables the restarts of the search (see `Restarts`)
+    pub fn with_restarts(mut self, policy: Restarts) -> Self {
+        self.restarts = Some(RestartTracker::new(policy));
+        self
+    }
+    /// Enables the presolve phase which seeds the search with the best of a 
+    /// few restricted compilations of the root problem (see `PresolveConfig`)
+    pub fn with_presolve(mut self, config: PresolveConfig) -> Self {
+        self.presolve = Some(config);
+        self
+    }
+    /// Returns the outcome of the presolve phase (None until it has been run)
+    pub fn presolve_record(&self) -> Option<PresolveRecord> {
+        self.presolve_record
+    }
+    /// Sets the randomized heuristic which is re-seeded before each run of 
+    /// the search when restarts are enabled (and before each iteration of 
+    /// the presolve)
+    pub fn with_reseed(mut self, reseed: &'a dyn Reseed) -> Self {
+        self.reseed = Some(reseed);
+        self
+    }
+    /// Sets the adaptive heuristic which is told about the outcome of each
+    /// compilation (see `CarryOver` for the order of the calls)
+    pub fn with_carry_over(mut self, carry_over: &'a dyn CarryOver) -> Self {
+        self.carry_over = Some(carry_over);
+        self
+    }
+    /// Returns the progress made during each run of the search that was 
+    /// interrupted by a restart
+    pub fn restarts(&self) -> &[RestartRecord] {
+        self.restarts.as_ref().map(|r| r.records.as_slice()).unwrap_or_default()
+    }
+    /// Returns the progress made during each run of the search, the ongoing 
+    /// (or last) run included. The nodes explored by all these runs add up 
+    /// to `explored`. This is empty unless the restarts have been enabled.
+    pub fn runs(&self) -> Vec<RestartRecord> {
+        self.restarts.as_ref()
+            .map(|r| r.runs(self.explored, self.best_lb, self.best_ub))
+            .unwrap_or_default()
+    }
+    /// Enables the pool of diverse solutions (see `Diversity`)
+    pub fn with_diversity(mut self, policy: Diversity) -> Self {
+        self.diversity = Some(DiversityPool::new(policy));
+        self
+    }
+    /// Returns the solutions of the pool of diverse solutions by decreasing
+    /// value. This is empty unless the diversity mode has been enabled.
+    pub fn diverse_solutions(&self) -> Vec<DiverseSolution> {
+        self.diversity.as_ref().map(|p| p.solutions()).unwrap_or_default()
+    }
+    /// Keeps the `k` best distinct solutions (that is, which do not make the
+    /// same decisions) the search comes across. In order not to cut off any
+    /// of them, the search only prunes the subproblems whose upper bound does
+    /// not exceed the value of the `k`-th best solution found so far. This 
+    /// makes the search slower, but it does not affect the optimum.
+    ///
+    /// # Note
+    /// The pool only sees the solutions the decision diagrams represent: a 
+    /// solution whose prefix reaches the same state as a better prefix is
+    /// merged away by the diagrams (as is the one pruned by a cache or a 
+    /// dominance checker).
+    pub fn with_solution_pool(mut self, k: usize) -> Self {
+        self.pool = Some(SolutionPool::new(k));
+        self
+    }
+    /// Returns the solutions of the pool by decreasing value (see 
+    /// `with_solution_pool`). The decisions of each solution are sorted by 
+    /// variable. This is empty unless the solution pool has been enabled.
+    pub fn best_solutions(&self) -> &[(isize, Solution)] {
+        self.pool.as_ref().map(|p| p.solutions()).unwrap_or_default()
+    }
+    /// Takes a snapshot of the composition of the fringe every so many 
+    /// explored nodes (see `FringeSampling`). Only the nodes the fringe lets
+    /// the solver inspect are accounted for (see `Fringe::inspect`).
+    pub fn with_fringe_sampling(mut self, sampling: FringeSampling) -> Self {
+        self.fringe_sampler = Some(FringeSampler::new(sampling));
+        self
+    }
+    /// Returns the snapshots of the composition of the fringe which were 
+    /// taken during the search, in chronological order. This is empty unless
+    /// the fringe sampling has been enabled.
+    pub fn fringe_snapshots(&self) -> &[FringeSnapshot] {
+        self.fringe_sampler.as_ref().map(|s| s.snapshots()).unwrap_or_default()
+    }
+    /// Keeps the solver from offering the same solution to the pool of diverse
+    /// solutions more than once. The solver remembers the last `capacity` 
+    /// solutions it has offered to the pool; the duplicates of these are 
+    /// suppressed (see `suppressed_duplicates`).
+    pub fn with_solution_dedup(mut self, capacity: usize) -> Self {
+        self.dedup = Some(SeenSolutions::new(capacity));
+        self
+    }
+    /// Returns the number of duplicate solutions which were not offered to the
+    /// pool of diverse solutions (see `with_solution_dedup`)
+    pub fn suppressed_duplicates(&self) -> usize {
+        self.dedup.as_ref().map(|d| d.suppressed()).unwrap_or_default()
+    }
+    /// Whenever the exact cut-set of a relaxed DD comprises more than 
+    /// `threshold` nodes, the relaxed DD is compiled once again with twice
+    /// the maximum width before its cut-set is enqueued. Only the cut-set of
+    /// the wider DD ends up in the fringe.
+    pub fn with_wider_recompile(mut self, threshold: usize) -> Self {
+        self.wider_recompile = Some(threshold);
+        self
+    }
+    /// Returns the number of relaxed DDs that have been compiled once again
+    /// because their cut-set was too large (see `with_wider_recompile`)
+    pub fn wider_recompilations(&self) -> usize {
+        self.recompiled
+    }
+    /// Compiles a restricted DD of at most `width` nodes per layer from each
+    /// cut-set node before it is pushed onto the fringe rather than once it is
+    /// popped. The solutions of that DD improve the best lower bound right 
+    /// away; and the node is not pushed at all when that DD is exact or when
+    /// its upper bound no longer exceeds the improved lower bound. In order 
+    /// not to double the work of the search, this is only done while the 
+    /// fringe holds fewer than `max_fringe` nodes.
+    pub fn with_eager_restriction(mut self, width: usize, max_fringe: usize) -> Self {
+        self.eager_restriction = Some((width.max(1), max_fringe));
+        self
+    }
+    /// Returns the number of cut-set nodes from which a restricted DD was 
+    /// compiled before they were pushed (see `with_eager_restriction`)
+    pub fn eager_restrictions(&self) -> usize {
+        self.eager_restricted
+    }
+    /// Returns the number of cut-set nodes which were not pushed onto the 
+    /// fringe because of the restricted DD compiled from them (see 
+    /// `with_eager_restriction`)
+    pub fn eagerly_pruned_nodes(&self) -> usize {
+        self.eager_pruned
+    }
+    /// Returns the number of cut-set nodes whose upper bound was tightened by
+    /// the best value of the relaxed DD they came from. This is zero unless 
+    /// the DD derives looser bounds for its cut-set than its own best value.
+    pub fn clamped_cutset_ubs(&self) -> usize {
+        self.clamped
+    }
+    /// Returns the number of nodes which were pushed onto the fringe with a
+    /// priority bonus (see `push_boosted_subproblem`)
+    pub fn boosted_nodes(&self) -> usize {
+        self.boosted
+    }
+    /// Prunes every subproblem whose upper bound does not exceed the best
+    /// known lower bound by more than `slack`. This trades the proof of 
+    /// optimality for speed: the value of the solution which is eventually
+    /// returned lies within `slack` of the optimum (see `Completion`).
+    pub fn with_absolute_slack(mut self, slack: isize) -> Self {
+        self.absolute_slack = slack;
+        self
+    }
+    /// Stops the search once `budget` nodes have been explored. The node 
+    /// being processed when the budget runs out is processed to its end so
+    /// that the incumbent remains consistent (see `Solver::set_node_budget`).
+    pub fn with_max_explored(mut self, budget: usize) -> Self {
+        self.node_budget = Some(budget);
+        self
+    }
+    /// Stops the search as soon as the relative gap between the best upper
+    /// and lower bounds drops below `tolerance` (0.01 means within 1% of the 
+    /// optimum). The completion then tells so with `stopped_by` set to 
+    /// `Reason::GapReached` and reports the `achieved_gap`. A zero tolerance
+    /// (the default) lets the search run until it proves optimality.
+    pub fn with_gap_tolerance(mut self, tolerance: f64) -> Self {
+        self.gap_tolerance = GapTolerance(tolerance);
+        self
+    }
+    /// Stops the search as soon as it finds a solution whose value is at 
+    /// least `target`; the completion then tells so with `stopped_by` set to
+    /// `Reason::TargetReached`, and the best solution is the one that reached
+    /// the target. Conversely, the search stops with `Reason::TargetUnreachable`
+    /// as soon as the relaxed DDs prove that no solution can reach the target.
+    /// When the search is carried out to its end without reaching the target,
+    /// the best value is exact but `stopped_by` is `Reason::TargetUnreachable`
+    /// as well.
+    pub fn with_target(mut self, target: isize) -> Self {
+        self.target = Some(target);
+        self
+    }
+    /// Replaces the decision diagram which is used to compile the restricted
+    /// and relaxed DDs. This is only useful for DDs that need some data to be 
+    /// configured (a `ProjectedMdd`, for instance), since the solver otherwise
+    /// uses the default value of `D`.
+    pub fn with_mdd(mut self, mdd: D) -> Self {
+        self.mdd = mdd;
+        self
+    }
+    /// Starts the search with an empty fringe rather than with the root of 
+    /// the problem. Only the subproblems that are pushed with `push_subproblem`
+    /// are explored, which lets you split the root of the problem yourself
+    /// (e.g. by branching on some strategic variable).
+    ///
+    /// # Important
+    /// The search only proves optimality if the pushed subproblems cover all
+    /// the solutions of the problem.
+    pub fn with_manual_root(mut self) -> Self {
+        self.roots.manual = true;
+        self
+    }
+    /// Keeps the open nodes when the search is stopped before its end (by the
+    /// time limit, the node budget, the abort handle, ...) rather than 
+    /// dropping them; so that the search can be saved with `checkpoint` 
+    /// (feature `checkpoint`) and resumed later on. This costs one copy of 
+    /// each node while it is being processed.
+    pub fn with_checkpointing(mut self) -> Self {
+        self.suspended = Some(vec![]);
+        self
+    }
+    /// Pushes a subproblem which is explored as soon as the search starts (and
+    /// upon each restart). Its `state` is reached from the root of the problem 
+    /// by applying the decisions of `path`; which yields a value of `lp_len`.
+    /// The `ub` is an upper bound on the objective reachable in the subproblem.
+    /// The pushed subproblems are forgotten when the solver is `reset`.
+    ///
+    /// # Panics
+    /// This panics if the path decides on some variable more than once, or
+    /// on a variable the problem does not have. When the debug assertions are
+    /// enabled, it also panics if replaying the `path` does not yield the
+    /// given `state` and `lp_len`.
+    pub fn push_subproblem(&mut self, state: State, lp_len: isize, ub: isize, path: Vec<Decision>) {
+        self.roots.push(self.problem, state, lp_len, ub, path, 0);
+    }
+    /// Pushes a subproblem just like `push_subproblem` does, except that the
+    /// subproblem carries a priority bonus which its descendants inherit for
+    /// `bonus` generations (the bonus decreases by one at each generation).
+    /// The bonus only matters when the fringe is ordered by a `BonusFirst`
+    /// ranking, which explores the nodes carrying the largest bonus first.
+    pub fn push_boosted_subproblem(&mut self, state: State, lp_len: isize, ub: isize, path: Vec<Decision>, bonus: u8) {
+        self.roots.push(self.problem, state, lp_len, ub, path, bonus);
+    }
+    /// Returns the upper bound that was derived from the first compilation
+    /// of the root problem (that is, the value of its relaxed DD or its optimum
+    /// when the restricted DD is exact). It returns None as long as the root
+    /// has not been processed.
+    pub fn root_bound(&self) -> Option<isize> {
+        self.root_bound
+    }
+    /// Returns the value of the first solution that was found by the search,
+    /// if any. (A solution set with `set_primal` is not found by the search)
+    pub fn first_incumbent(&self) -> Option<isize> {
+        self.first_incumbent
+    }
+    /// Returns the terminal state which is reached by the best solution found
+    /// so far. This is None when no solution was found, or when the best
+    /// solution is the primal that was set with `set_primal`.
+    pub fn best_state(&self) -> Option<&State> {
+        self.best_state.as_ref()
+    }
+    /// Estimates the size of the search tree with `samples` random dives
+    /// (see `estimate_tree_size`) when the search starts. The dives prune the
+    /// nodes which cannot improve on the primal set with `set_primal`, if any.
+    pub fn with_tree_estimate(mut self, samples: usize, seed: u64) -> Self {
+        self.tree_estimate = Some(TreeEstimate::new(samples, seed));
+        self
+    }
+    /// Returns the estimated size of the search tree (see `with_tree_estimate`).
+    /// It returns None as long as the search has not started.
+    pub fn tree_estimate(&self) -> Option<EstimateReport> {
+        self.tree_estimate.and_then(|e| e.report)
+    }
+    /// Enables the verification mode, which is meant to debug new models. 
+    /// For each processed node, the solver checks that the value of the 
+    /// restricted DD does not exceed the one of the relaxed DD, that neither 
+    /// of them exceeds the upper bound of the node, and that the value of an 
+    /// exact DD is achieved by its best solution. The search
+    /// panics with a detailed report (rank of the node, path, values of both 
+    /// DDs) upon the first violation.
+    ///
+    /// # Important
+    /// This is heavy: the relaxed DDs are compiled without pruning on the 
+    /// best lower bound and the best solution of each exact DD is replayed
+    /// through the problem. Do not enable it in production.
+    pub fn with_verify(mut self) -> Self {
+        self.verify = true;
+        self
+    }
+    /// Returns the latency histograms of the restricted and relaxed 
+    /// compilations performed so far
+    pub fn compilation_latencies(&self) -> &CompilationLatencies {
+        &self.latency.histograms
+    }
+    /// Makes the solver remember the compilations which take much longer than
+    /// usual (see `LatencyWatch` and `slow_compilations`)
+    pub fn with_latency_watch(mut self, watch: LatencyWatch) -> Self {
+        self.latency.watch = Some(watch);
+        self
+    }
+    /// Returns the compilations which took much longer than usual. This is 
+    /// empty unless the latency watch has been enabled.
+    pub fn slow_compilations(&self) -> &[SlowCompilation] {
+        &self.latency.slow
+    }
+    /// Reports each event of the explored search tree to the given export: 
+    /// the creation of the nodes, their pruning or fathoming and the updates
+    /// of the best bounds (see `TreeEvent`).
+    pub fn with_tree_export(mut self, export: &'a mut dyn TreeExport) -> Self {
+        self.tree = Some(TreeTracker::new(export));
+        self
+    }
+    /// Tells the given listener about each new best solution found while 
+    /// searching, be it by a restricted or by a relaxed DD
+    pub fn with_incumbent_listener(mut self, listener: &'a mut dyn IncumbentListener) -> Self {
+        self.incumbent_listener = Some(listener);
+        self
+    }
+    /// Tells the given listener about the progress of the search: each node
+    /// which is explored, each new best solution and the end of the search 
+    /// (see `ProgressEvent`). By default, the progress is not reported.
+    pub fn with_progress(mut self, progress: &'a mut dyn Progress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+    /// Publishes each new incumbent to the given handle, which any thread may
+    /// read while the search is running
+    pub fn with_shared_incumbent(mut self, incumbent: &SharedIncumbent) -> Self {
+        self.shared_incumbent = Some(incumbent.clone());
+        self
+    }
+    /// Returns a handle on the best bounds of the solver, which any thread 
+    /// may poll while the search is running. The handle remains valid across
+    /// the calls to `maximize` and `reset`.
+    pub fn bounds_handle(&self) -> BoundsHandle {
+        self.bounds.clone()
+    }
+    /// Returns a handle which any thread may use to stop the search (see 
+    /// `AbortHandle`). The handle remains valid across the calls to 
+    /// `maximize` and `reset`.
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.abort.clone()
+    }
+    /// Returns the estimated memory usage of the solver (see `MemoryStats`)
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats { mdd_bytes: self.mdd.peak_layer_bytes(), ..self.memory }
+    }
+    /// Warm starts the search with a solution that is known beforehand. The
+    /// solution is replayed through the problem (see `evaluate`) and it is 
+    /// only recorded as the primal (see `Solver::set_primal`) when it is 
+    /// feasible and worth `value`. The first compilations are then pruned 
+    /// against that value, and the solution is returned when the search does
+    /// not find any better one.
+    pub fn set_initial_solution(&mut self, solution: Solution, value: isize) -> Result<(), WarmStartError<State>> {
+        check_warm_start(self.problem, &solution, value)?;
+        self.set_primal(value, solution);
+        Ok(())
+    }
+
+    /// Explores one single node of the search: the first call starts the 
+    /// search (just like `maximize` would), then each call pops one node from
+    /// the fringe, compiles its restricted and relaxed DDs and enqueues the 
+    /// nodes of the cut-set. This lets the caller drive the search (to yield
+    /// control to a GUI, to apply a custom stopping rule, ...) and read the 
+    /// current bounds in between (see `best_lower_bound` and 
+    /// `best_upper_bound`). Once `StepResult::Finished` is returned, `finish`
+    /// tells the outcome of the search.
+    ///
+    /// # Example
+    /// ```
+    /// # use ddo::*;
+    /// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    /// # struct State(usize);
+    /// # struct Binary;
+    /// # impl Problem for Binary {
+    /// #     type State = State;
+    /// #     fn nb_variables(&self) -> usize { 3 }
+    /// #     fn initial_state(&self) -> State { State(0) }
+    /// #     fn initial_value(&self) -> isize { 0 }
+    /// #     fn transition(&self, s: &State, _: Decision) -> State { State(s.0 + 1) }
+    /// #     fn transition_cost(&self, _: &State, _: &State, d: Decision) -> isize { d.value }
+    /// #     fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &State>) -> Option<Variable> {
+    /// #         (depth < 3).then_some(Variable(depth))
+    /// #     }
+    /// #     fn for_each_in_domain(&self, var: Variable, _: &State, f: &mut dyn DecisionCallback) {
+    /// #         f.apply(Decision { variable: var, value: 0 });
+    /// #         f.apply(Decision { variable: var, value: 1 });
+    /// #     }
+    /// # }
+    /// # struct Relax;
+    /// # impl Relaxation for Relax {
+    /// #     type State = State;
+    /// #     fn merge(&self, s: &mut dyn Iterator<Item = &State>) -> State { s.next().unwrap().clone() }
+    /// #     fn relax(&self, _: &State, _: &State, _: &State, _: Decision, cost: isize) -> isize { cost }
+    /// # }
+    /// # struct Ranking;
+    /// # impl StateRanking for Ranking {
+    /// #     type State = State;
+    /// #     fn compare(&self, a: &State, b: &State) -> std::cmp::Ordering { a.0.cmp(&b.0) }
+    /// # }
+    /// # let (problem, relaxation, ranking) = (Binary, Relax, Ranking);
+    /// # let width = FixedWidth(1);
+    /// # let dominance = EmptyDominanceChecker::default();
+    /// # let cutoff = NoCutoff;
+    /// let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+    /// let mut solver = SeqNoCachingSolverLel::new(
+    ///     &problem, &relaxation, &ranking, &width, &dominance, &cutoff, &mut fringe);
+    /// while solver.step() == StepResult::Explored {
+    ///     // the bounds only ever get tighter
+    ///     assert!(solver.best_lower_bound() <= solver.best_upper_bound());
+    /// }
+    /// let completion = solver.finish();
+    /// assert!(completion.is_exact);
+    /// assert_eq!(Some(3), completion.best_value);
+    /// ```
+    pub fn step(&mut self) -> StepResult {
+        if !self.stepping {
+            self.stepping = true;
+            self.initialize();
+            if let Some(tree) = self.tree.as_mut() {
+                tree.bounds(self.best_lb, self.best_ub);
+            }
         }
+        let node = match self.get_workload() {
+            WorkLoad::Complete => return StepResult::Finished,
+            WorkLoad::Aborted => return StepResult::Finished,
+            WorkLoad::WorkItem { node } => node,
+        };
+        let pending = self.suspended.as_ref().map(|_| node.clone());
+        let outcome = self.process_one_node(node);
+        if let Err(reason) = outcome {
+            if let Some(tree) = self.tree.as_mut() {
+                tree.pruned(PruneReason::Dropped);
+            }
+            let reason = self.time_limit.explain(self.abort.explain(reason), self.cutoff);
+            self.abort_search(reason); 
+            if let (Some(suspended), Some(node)) = (self.suspended.as_mut(), pending) {
+                suspended.push(node);
+            }
+            return StepResult::Finished;
+        }
+        self.refresh_best_ub();
+        self.maybe_refresh_dual();
+        if let Some(tree) = self.tree.as_mut() {
+            tree.bounds(self.best_lb, self.best_ub);
+        }
+        self.bounds.publish(self.best_lb, self.best_ub);
+        self.report(ProgressEvent::NodeExplored {
+            explored: self.explored,
+            lb: self.best_lb,
+            ub: self.best_ub,
+            fringe_len: self.fringe.len(),
+        });
+        self.maybe_sample_fringe();
+        StepResult::Explored
+    }
+    /// Concludes the search which was driven with `step` and returns its 
+    /// outcome (which is what `maximize` would have returned). A subsequent 
+    /// call to `step` starts a new search.
+    pub fn finish(&mut self) -> Completion {
+        self.stepping = false;
+        if let Some(tree) = self.tree.as_mut() {
+            tree.bounds(self.best_lb, self.best_ub);
+            tree.drop_open();
+        }
+        self.record_memory();
+        self.bounds.publish(self.best_lb, self.best_ub);
+        if let Some(sol) = self.best_sol.as_mut() { sol.sort_unstable_by_key(|d| d.variable.0) }
+        self.report(ProgressEvent::Finished { 
+            value: self.best_sol.as_ref().map(|_| self.best_lb), 
+            explored: self.explored,
+        });
+        self.stats.elapsed = self.time_limit.elapsed();
+        let guaranteed_within = Some(self.absolute_slack).filter(|slack| *slack != 0 && self.abort_proof.is_none());
+        let shed = self.fringe.shed();
+        // the shed nodes which could have improved the best solution were never explored
+        let lossy = shed.best_ub.is_some_and(|ub| ub > self.best_lb);
+        // a complete search which did not reach the target proved it unreachable
+        let stopped_by = self.abort_proof.clone().or_else(|| 
+            self.target.filter(|target| self.best_lb < *target).map(|_| Reason::TargetUnreachable));
+        Completion { 
+            is_exact: self.abort_proof.is_none() && guaranteed_within.is_none() && !lossy, 
+            best_value: self.best_sol.as_ref().map(|_| self.best_lb),
+            guaranteed_within,
+            stopped_by: stopped_by.clone(),
+            achieved_gap: Some(relative_gap(self.best_lb, self.best_ub))
+                .filter(|_| self.abort_proof == Some(Reason::GapReached)),
+            termination: Some(Termination {
+                reason: stopped_by,
+                elapsed: self.stats.elapsed,
+                explored: self.explored,
+                gap: relative_gap(self.best_lb, self.best_ub),
+                shed: shed.count,
+            }),
+        }
+    }
+    /// Returns the number of nodes which are still open in the fringe
+    pub fn open_nodes(&self) -> usize {
+        self.fringe.len()
     }
 
     /// This method initializes the problem resolution. Put more simply, this
-    /// method posts the root node of the mdd onto the fringe so that a thread
-    /// can pick it up and the processing can be bootstrapped.
+    /// method posts the root node of the mdd (or the subproblems pushed with
+    /// `push_subproblem`) onto the fringe so that a thread can pick it up and
+    /// the processing can be bootstrapped.
     fn initialize(&mut self) {
         let root = self.root_node();
+        self.time_limit.start();
+        if let Some(suspended) = self.suspended.as_mut() {
+            suspended.clear();
+        }
         self.cache.initialize(self.problem);
-        self.fringe.push(root);
-        self.open_by_layer[0] += 1;
+        self.run_presolve(&root);
+        if let Some(restarts) = self.restarts.as_mut() {
+            restarts.start_clock();
+        }
+        if let Some(estimate) = self.tree_estimate.as_mut() {
+            estimate.run(self.problem, self.relaxation, self.best_lb);
+        }
+        if let (Some(restarts), Some(reseed)) = (self.restarts.as_ref(), self.reseed) {
+            reseed.reseed(restarts.seed());
+        }
+        self.push_roots(root, isize::MAX);
+        self.memory.record_fringe(self.fringe.estimated_bytes());
+    }
+
+    /// Compiles the restricted DDs of the presolve phase (if it is enabled)
+    /// and records how much they improved the best lower bound
+    fn run_presolve(&mut self, root: &SubProblem<State>) {
+        let Some(config) = self.presolve.clone() else { return };
+        let start = Instant::now();
+        let lb_before = self.best_lb;
+        let default_width = self.width_heu.max_width(root);
+        let cutoff = Deadline { cutoff: self.cutoff, limit: self.time_limit, abort: self.abort.clone() };
+        let mut record = PresolveRecord { iterations: 0, improving: 0, lb_before, lb_after: lb_before, elapsed: Duration::ZERO };
+        for i in 0..config.iterations {
+            if let Some(reseed) = self.reseed {
+                reseed.reseed(config.seed(i));
+            }
+            let best_lb = self.best_lb;
+            let pruning_lb = self.pruning_lb();
+            let outcome = compile_root_restriction(&mut self.mdd, self.problem, self.relaxation, 
+                self.ranking, &cutoff, config.width(i, default_width), pruning_lb);
+            let Ok(is_exact) = outcome else { break };
+            self.maybe_update_best();
+            record.iterations += 1;
+            record.improving += usize::from(self.best_lb > best_lb);
+            if is_exact {
+                break;
+            }
+        }
+        record.lb_after = self.best_lb;
+        record.elapsed = start.elapsed();
+        self.presolve_record = Some(record);
+    }
+
+    /// Pushes the given `root` (unless the branching is manual) along with 
+    /// the subproblems pushed with `push_subproblem` onto the fringe
+    fn push_roots(&mut self, root: SubProblem<State>, ub: isize) {
+        for sub in self.roots.roots(root, ub) {
+            if let Some(tree) = self.tree.as_mut() {
+                tree.created(&sub, true);
+            }
+            let depth = sub.depth;
+            self.boosted += usize::from(sub.priority_bonus > 0);
+            let before = self.fringe.len();
+            self.fringe.push(sub);
+            self.open_by_layer[depth] += self.fringe.len() - before;
+        }
+        self.stats.peak_fringe = self.stats.peak_fringe.max(self.fringe.len());
+        let open_by_layer = &mut self.open_by_layer;
+        self.fringe.shed_excess(&mut |depth| open_by_layer[depth] -= 1);
     }
 
     fn root_node(&self) -> SubProblem<State> {
@@ -319,6 +1047,7 @@ where
             path: vec![],
             ub: isize::MAX,
             depth: 0,
+            priority_bonus: 0,
         }
     }
 
@@ -332,57 +1061,124 @@ where
     ) -> Result<(), Reason> {
         // 1. RESTRICTION
         let node_ub = node.ub;
-        let best_lb = self.best_lb;
+        let bonus = node.priority_bonus.saturating_sub(1);
+        let best_lb = self.pruning_lb();
+        if let Some(tree) = self.tree.as_mut() {
+            tree.popped(&node);
+        }
 
-        if node_ub <= best_lb {
+        if node_ub <= best_lb.saturating_add(self.absolute_slack) {
+            self.stats.pruned_by_bound += 1;
+            if let Some(tree) = self.tree.as_mut() {
+                tree.pruned(PruneReason::Bound);
+            }
             return Ok(());
         }
 
         if !self.cache.must_explore(&node) {
+            if let Some(tree) = self.tree.as_mut() {
+                tree.pruned(PruneReason::Cache);
+            }
             return Ok(());
         }
 
         let width = self.width_heu.max_width(&node);
+        let cutoff = Deadline { cutoff: self.cutoff, limit: self.time_limit, abort: self.abort.clone() };
         let compilation = CompilationInput {
             comp_type: CompilationType::Restricted,
             max_width: width,
             problem: self.problem,
             relaxation: self.relaxation,
             ranking: self.ranking,
-            cutoff: self.cutoff,
+            cutoff: &cutoff,
             cache: &self.cache,
             dominance: self.dominance,
-            residual: &node,
             //
             best_lb,
         };
 
-        let Completion{is_exact, ..} = self.mdd.compile(&compilation)?;
+        let is_root = node.depth == 0;
+        let depth = node.depth;
+        let compiled = CompiledNode { depth, path_len: node.path.len(), ub: node_ub };
+        let mut verifier = self.verify.then(|| NodeVerifier::new(self.explored, &node.path, node_ub));
+        let path = if self.latency.watching() { node.path.clone() } else { vec![] };
+        let start = Instant::now();
+        let Completion{is_exact, ..} = self.mdd.compile(&compilation, node)?;
+        let elapsed = start.elapsed();
+        self.stats.record_compilation(CompilationType::Restricted, elapsed);
+        self.latency.record(CompilationType::Restricted, elapsed, self.explored, depth, || path.clone());
+        if let Some(verifier) = verifier.as_mut() {
+            let solution = if is_exact { self.mdd.best_solution() } else { None };
+            verifier.restricted(self.problem, self.mdd.best_value(), solution);
+        }
         self.maybe_update_best();
+        notify_carry_over(self.carry_over, &self.mdd, CompilationType::Restricted, compiled, is_exact);
         if is_exact {
+            self.maybe_record_root_bound(is_root, best_lb);
+            if let Some(tree) = self.tree.as_mut() {
+                tree.fathomed(self.mdd.best_value());
+            }
             return Ok(());
         }
 
         // 2. RELAXATION
-        let best_lb = self.best_lb;
+        let best_lb = if self.verify { isize::MIN } else { self.pruning_lb() };
         let compilation = CompilationInput {
             comp_type: CompilationType::Relaxed,
             max_width: width,
             problem: self.problem,
             relaxation: self.relaxation,
             ranking: self.ranking,
-            cutoff: self.cutoff,
+            cutoff: &cutoff,
             cache: &self.cache,
             dominance: self.dominance,
-            residual: &node,
             //
             best_lb,
         };
 
-        let Completion{is_exact, ..} = self.mdd.compile(&compilation)?;
+        // the restricted dd gives the node back instead of it being cloned
+        let node = self.mdd.take_residual().expect("the restricted dd was compiled from the node");
+        let start = Instant::now();
+        let Completion{mut is_exact, ..} = self.mdd.compile(&compilation, node)?;
+        let elapsed = start.elapsed();
+        self.stats.record_compilation(CompilationType::Relaxed, elapsed);
+        self.latency.record(CompilationType::Relaxed, elapsed, self.explored, depth, || path);
+        if let Some(verifier) = verifier.as_mut() {
+            let solution = if is_exact { self.mdd.best_solution() } else { None };
+            verifier.relaxed(self.problem, self.mdd.best_value(), solution);
+        }
         self.maybe_update_best();
+        self.maybe_record_root_bound(is_root, best_lb);
+        notify_carry_over(self.carry_over, &self.mdd, CompilationType::Relaxed, compiled, is_exact);
+        
+        // 3. WIDER RELAXATION (when the cut-set is too large)
+        if let (false, Some(threshold)) = (is_exact, self.wider_recompile) {
+            if self.mdd.cutset_len() > threshold {
+                let node = self.mdd.take_residual().expect("the relaxed dd was compiled from the node");
+                let compilation = CompilationInput {
+                    comp_type: CompilationType::Relaxed,
+                    max_width: width.saturating_mul(2),
+                    problem: self.problem,
+                    relaxation: self.relaxation,
+                    ranking: self.ranking,
+                    cutoff: &cutoff,
+                    cache: &self.cache,
+                    dominance: self.dominance,
+                    //
+                    best_lb: self.pruning_lb(),
+                };
+                let start = Instant::now();
+                is_exact = self.mdd.compile(&compilation, node)?.is_exact;
+                self.stats.record_compilation(CompilationType::Relaxed, start.elapsed());
+                self.recompiled += 1;
+                self.maybe_update_best();
+                notify_carry_over(self.carry_over, &self.mdd, CompilationType::Relaxed, compiled, is_exact);
+            }
+        }
         if !is_exact {
-            self.enqueue_cutset(node_ub);
+            self.enqueue_cutset(node_ub, bonus);
+        } else if let Some(tree) = self.tree.as_mut() {
+            tree.fathomed(self.mdd.best_value());
         }
 
         Ok(())
@@ -392,31 +1188,233 @@ where
     /// case the best value of the current `mdd` expansion improves the current
     /// bounds.
     fn maybe_update_best(&mut self) {
+        if let Some(pool) = self.pool.as_mut() {
+            self.mdd.for_each_exact_solution(&mut |value, solution| { pool.offer(value, solution); });
+        }
+        if let Some(pool) = self.diversity.as_mut() {
+            let dedup = &mut self.dedup;
+            self.mdd.for_each_exact_solution(&mut |value, solution| {
+                if dedup.as_mut().is_none_or(|d| d.is_new(&solution)) {
+                    pool.offer(value, solution);
+                }
+            });
+        }
         let dd_best_value = self.mdd.best_exact_value().unwrap_or(isize::MIN);
         if dd_best_value > self.best_lb {
             self.best_lb = dd_best_value;
             self.best_sol = self.mdd.best_exact_solution();
+            self.best_state = self.mdd.best_exact_state().cloned();
+            self.first_incumbent.get_or_insert(dd_best_value);
+            if let (Some(listener), Some(solution)) = (self.incumbent_listener.as_mut(), self.best_sol.as_ref()) {
+                let mut solution = solution.clone();
+                solution.sort_unstable_by_key(|d| d.variable.0);
+                listener.on_new_best(dd_best_value, &solution);
+            }
+            if let (Some(incumbent), Some(solution)) = (self.shared_incumbent.as_ref(), self.best_sol.as_ref()) {
+                incumbent.offer(dd_best_value, solution);
+            }
+            self.report(ProgressEvent::NewIncumbent { value: dd_best_value });
+            self.record_memory();
+            let best_lb = self.pruning_lb().saturating_add(self.absolute_slack);
+            let open_by_layer = &mut self.open_by_layer;
+            let pruned = &mut self.stats.pruned_by_bound;
+            self.fringe.prune(best_lb, &mut |depth| {
+                open_by_layer[depth] -= 1;
+                *pruned += 1;
+            });
+        }
+    }
+    /// Returns the lower bound which is used to prune the subproblems. This is
+    /// the best lower bound, unless the solution pool is enabled, in which
+    /// case only the subproblems which cannot improve on the `k`-th best 
+    /// solution of the pool are pruned.
+    fn pruning_lb(&self) -> isize {
+        match self.pool.as_ref() {
+            Some(pool) => self.best_lb.min(pool.threshold()),
+            None => self.best_lb,
         }
     }
-    /// If necessary, tightens the bound of nodes in the cut-set of `mdd` and
-    /// then add the relevant nodes to the shared fringe.
-    fn enqueue_cutset(&mut self, ub: isize) {
-        let best_lb = self.best_lb;
-        let fringe = &mut self.fringe;
-        self.mdd.drain_cutset(|mut cutset_node| {
-            cutset_node.ub = ub.min(cutset_node.ub);
+    /// Takes a snapshot of the composition of the fringe if one is due
+    fn maybe_sample_fringe(&mut self) {
+        if let Some(sampler) = self.fringe_sampler.as_mut() {
+            if sampler.is_due(self.explored) {
+                let snapshot = FringeSnapshot::of(self.fringe, self.explored, self.best_lb, self.best_ub, &sampler.sampling);
+                sampler.record(snapshot);
+            }
+        }
+    }
+    /// Tells the progress listener (if any) about the given event
+    fn report(&mut self, event: ProgressEvent) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.report(&event);
+        }
+    }
+    /// Remembers the bound derived from the DD that was just compiled if it
+    /// is the first DD compiled for the root problem. Nothing better than the
+    /// `best_lb` used for that compilation needs to be represented by the DD.
+    fn maybe_record_root_bound(&mut self, is_root: bool, best_lb: isize) {
+        if is_root && self.root_bound.is_none() {
+            self.root_bound = Some(self.mdd.best_value().unwrap_or(isize::MIN).max(best_lb));
+        }
+    }
+    /// Samples the memory used by the cache, the dominance checker and (when
+    /// available) the whole process
+    fn record_memory(&mut self) {
+        self.memory.record_progress(self.cache.estimated_bytes(), self.dominance.estimated_bytes());
+    }
+    /// If necessary, tightens the bound of nodes in the cut-set of `mdd` (with
+    /// the best value of that dd and with the rough upper bound of their 
+    /// state) and then add the relevant nodes to the shared fringe. These 
+    /// nodes carry the given priority `bonus`.
+    fn enqueue_cutset(&mut self, ub: isize, bonus: u8) {
+        // the relaxed dd is a relaxation of the whole subproblem: its best 
+        // value bounds the value of all its cut-set nodes
+        let dd_ub = self.mdd.best_value().unwrap_or(isize::MIN);
+        let mut cutset = vec![];
+        self.mdd.drain_cutset(|node| cutset.push(node));
+        for mut cutset_node in cutset {
+            self.stats.cutset_nodes += 1;
+            let inherited = ub.min(cutset_node.ub);
+            cutset_node.ub = inherited.min(dd_ub);
+            cutset_node.priority_bonus = bonus;
+            self.clamped += usize::from(cutset_node.ub < inherited);
+            // the rough upper bound may be tighter than what the dd derived
+            let rough_ub = cutset_node.value.saturating_add(self.relaxation.fast_upper_bound(cutset_node.state.as_ref()));
+            cutset_node.ub = cutset_node.ub.min(rough_ub);
+            let mut best_lb = self.pruning_lb().saturating_add(self.absolute_slack);
+            let mut solved = false;
             if cutset_node.ub > best_lb {
+                let (node, outcome) = self.restrict_eagerly(cutset_node);
+                cutset_node = node;
+                if let Some(is_exact) = outcome {
+                    best_lb = self.pruning_lb().saturating_add(self.absolute_slack);
+                    solved = is_exact || cutset_node.ub <= best_lb;
+                    self.eager_pruned += usize::from(solved);
+                }
+            }
+            let open = !solved && cutset_node.ub > best_lb;
+            if let Some(tree) = self.tree.as_mut() {
+                tree.created(&cutset_node, open);
+            }
+            if open {
                 let depth = cutset_node.depth;
-                let before = fringe.len();
-                fringe.push(cutset_node);
-                let after = fringe.len();
-                self.open_by_layer[depth] += after - before;
+                self.boosted += usize::from(bonus > 0);
+                let before = self.fringe.len();
+                self.fringe.push(cutset_node);
+                self.open_by_layer[depth] += self.fringe.len() - before;
+            } else {
+                self.stats.pruned_by_bound += 1;
             }
+        }
+        self.stats.peak_fringe = self.stats.peak_fringe.max(self.fringe.len());
+        let open_by_layer = &mut self.open_by_layer;
+        self.fringe.shed_excess(&mut |depth| open_by_layer[depth] -= 1);
+        self.memory.record_fringe(self.fringe.estimated_bytes());
+    }
+    /// Compiles a narrow restricted DD rooted in the given cut-set node (see
+    /// `with_eager_restriction`) and lets its solutions improve the best 
+    /// lower bound. This gives the node back along with whether that DD is 
+    /// exact, or None when no DD was compiled (the option is disabled, the 
+    /// fringe is too large or the compilation was interrupted).
+    ///
+    /// # Note
+    /// The cut-set of the relaxed DD must have been drained already since the
+    /// restriction is compiled in that same DD.
+    fn restrict_eagerly(&mut self, node: SubProblem<State>) -> (SubProblem<State>, Option<bool>) {
+        let Some((width, max_fringe)) = self.eager_restriction else {
+            return (node, None);
+        };
+        if self.fringe.len() >= max_fringe {
+            return (node, None);
+        }
+        let cutoff = Deadline { cutoff: self.cutoff, limit: self.time_limit, abort: self.abort.clone() };
+        let best_lb = self.pruning_lb();
+        let start = Instant::now();
+        let outcome = compile_restriction(&mut self.mdd, self.problem, self.relaxation, 
+            self.ranking, &cutoff, width, best_lb, node);
+        self.stats.record_compilation(CompilationType::Restricted, start.elapsed());
+        if outcome.is_ok() {
+            self.eager_restricted += 1;
+            self.maybe_update_best();
+        }
+        let node = self.mdd.take_residual().expect("the restriction was compiled from the node");
+        (node, outcome.ok())
+    }
+
+    /// Tightens the best known upper bound based on the nodes that are still
+    /// open (see `Fringe::best_ub`) and on those the fringe shed.
+    ///
+    /// # Note
+    /// This method must only be called when no node is being processed (or
+    /// else, the ub of that node should be accounted for).
+    fn refresh_best_ub(&mut self) {
+        let fringe_ub = self.fringe.best_ub().max(self.fringe.shed().best_ub).unwrap_or(isize::MIN);
+        self.best_ub  = self.best_ub.min(fringe_ub.max(self.best_lb.saturating_add(self.absolute_slack)));
+    }
+
+    /// If a refresh of the dual bound is due, this method compiles a wider 
+    /// relaxation of the root problem and uses it to tighten the best upper 
+    /// bound as well as the upper bound of all the nodes in the fringe.
+    fn maybe_refresh_dual(&mut self) {
+        let explored = self.explored;
+        let pruning_lb = self.pruning_lb();
+        let Some(schedule) = self.dual_refresh.as_mut() else { return };
+        if !schedule.is_due(explored) {
+            return;
+        }
+
+        let start = Instant::now();
+        let ub_before = self.best_ub;
+        let root_ub = compile_root_relaxation(&mut self.mdd, self.problem, self.relaxation, 
+            self.ranking, self.width_heu, self.cutoff, &schedule.policy, self.best_lb);
+        
+        if let Some(ub) = root_ub {
+            self.best_ub = self.best_ub.min(ub);
+            let best_lb = pruning_lb.saturating_add(self.absolute_slack);
+            sweep_fringe(self.fringe, &mut self.open_by_layer, self.best_ub, best_lb);
+        }
+        schedule.records.push(DualRefreshRecord {
+            explored,
+            elapsed: start.elapsed(),
+            ub_before,
+            ub_after: root_ub.map(|_| self.best_ub),
         });
     }
 
+    /// Interrupts the current run of the search and starts a new one from the
+    /// root of the problem. The best solution and bounds are retained; and so 
+    /// are the cache and the dominance checker if the policy says so.
+    fn restart(&mut self) {
+        let Some(restarts) = self.restarts.as_mut() else { return };
+        let seed = restarts.restart(self.explored, self.best_lb, self.best_ub);
+        
+        self.fringe.clear();
+        if let Some(tree) = self.tree.as_mut() {
+            tree.drop_open();
+        }
+        self.open_by_layer.iter_mut().for_each(|o| *o = 0);
+        self.first_active_layer = 0;
+        if restarts.policy.keep_cache {
+            self.cache.forget_explored();
+        } else {
+            self.cache.clear();
+            (0..=self.problem.nb_variables()).for_each(|d| self.dominance.clear_layer(d));
+        }
+        if let Some(reseed) = self.reseed {
+            reseed.reseed(seed);
+        }
+
+        let root = self.root_node();
+        self.push_roots(root, self.best_ub);
+    }
+
     fn abort_search(&mut self, reason: Reason) {
         self.abort_proof = Some(reason);
+        if let Some(suspended) = self.suspended.as_mut() {
+            while let Some(node) = self.fringe.pop() {
+                suspended.push(node);
+            }
+        }
         self.fringe.clear();
         self.cache.clear();
     }
@@ -441,7 +1439,8 @@ where
 
         // Are we done ?
         if self.fringe.is_empty() {
-            self.best_ub = self.best_lb;
+            let shed_ub = self.fringe.shed().best_ub.unwrap_or(isize::MIN);
+            self.best_ub = self.best_ub.min(self.best_lb.saturating_add(self.absolute_slack).max(shed_ub));
             return WorkLoad::Complete;
         }
 
@@ -450,18 +1449,120 @@ where
             return WorkLoad::Aborted;
         }
 
+        // Was the search aborted ?
+        if self.abort.is_aborted() {
+            self.abort_search(Reason::Aborted);
+            return WorkLoad::Aborted;
+        }
+
+        // Is the node budget exhausted ?
+        if self.node_budget.is_some_and(|budget| self.explored >= budget) {
+            self.abort_search(Reason::NodeLimit);
+            return WorkLoad::Aborted;
+        }
+
+        // Is the time up ?
+        if self.time_limit.is_over() {
+            self.abort_search(Reason::Timeout);
+            return WorkLoad::Aborted;
+        }
+
+        // Is the solution good enough ?
+        if self.gap_tolerance.is_reached(self.best_lb, self.best_ub).is_some() {
+            self.abort_search(Reason::GapReached);
+            return WorkLoad::Aborted;
+        }
+
+        // Do we know whether the target can be reached ?
+        if let Some(reason) = self.target.and_then(|target| target_reason(target, self.best_lb, self.best_ub)) {
+            self.abort_search(reason);
+            return WorkLoad::Aborted;
+        }
+
+        // Did the cutoff kick in ?
+        if self.cutoff.must_stop() {
+            self.abort_search(Reason::CutoffOccurred);
+            return WorkLoad::Aborted;
+        }
+
+        // Should we start over ?
+        if self.restarts.as_ref().is_some_and(|r| r.is_due(self.explored)) {
+            self.restart();
+        }
+
         let nn = self.fringe.pop().unwrap();
 
         // Consume the current node and process it
         self.explored += 1;
         self.open_by_layer[nn.depth] -= 1;
-        self.best_ub   = nn.ub;
 
         WorkLoad::WorkItem { node: nn }
     }
 
 }
 
+#[cfg(feature = "checkpoint")]
+impl<State, D, C> SequentialSolver<'_, State, D, C>
+where 
+    State: Eq + Hash + Clone + Serialize + DeserializeOwned,
+    D: DecisionDiagram<State = State> + Default,
+    C: Cache<State = State> + Default,
+{
+    /// Writes the search to `out` so that it can be resumed later on, possibly
+    /// in another process (see `resume`). This is meant to be called once 
+    /// `maximize` has returned: the checkpoint holds the nodes which were left
+    /// open when the search was stopped (see `with_checkpointing`), the 
+    /// incumbent and the number of explored nodes. The checkpoint of a solver
+    /// which has not started its search yet holds the root of the problem (or
+    /// the subproblems pushed with `push_subproblem`); the one of a completed
+    /// search holds no open node at all.
+    ///
+    /// # Errors
+    /// This fails when the search was stopped before its end without keeping
+    /// its open nodes (see `with_checkpointing`), or when `out` fails.
+    pub fn checkpoint(&self, out: &mut impl Write) -> io::Result<()> {
+        let nodes = match (&self.abort_proof, self.suspended.as_ref()) {
+            (None, _) if self.explored == 0 => self.roots.roots(self.root_node(), isize::MAX),
+            (None, _) => vec![],
+            (Some(_), Some(suspended)) => suspended.clone(),
+            (Some(_), None) => return Err(io::Error::new(io::ErrorKind::InvalidInput, 
+                "the open nodes were dropped when the search stopped (see with_checkpointing)")),
+        };
+        let checkpoint = Checkpoint {
+            nodes: nodes.into_iter()
+                .map(|n| SavedNode { state: n.state.as_ref().clone(), lp_len: n.value, ub: n.ub, path: n.path })
+                .collect(),
+            best_lb: self.best_lb,
+            best_sol: self.best_sol.clone(),
+            explored: self.explored,
+        };
+        checkpoint.write(out)
+    }
+    /// Restores a search which was saved with `checkpoint`. The solver must 
+    /// have been created for the same problem, with the same heuristics: it 
+    /// forgets about its previous resolutions (see `Solver::reset`), takes up
+    /// the incumbent and the number of explored nodes of the checkpoint, and
+    /// its next call to `maximize` explores the open nodes of the checkpoint
+    /// rather than the root of the problem (as with `with_manual_root`).
+    ///
+    /// # Errors
+    /// This fails when `input` fails or does not hold a checkpoint.
+    pub fn resume(&mut self, input: &mut impl Read) -> io::Result<()> {
+        let checkpoint = Checkpoint::<State>::read(input)?;
+        self.reset();
+        self.roots.manual = true;
+        for node in checkpoint.nodes {
+            self.push_subproblem(node.state, node.lp_len, node.ub, node.path);
+        }
+        match checkpoint.best_sol {
+            Some(solution) => self.set_primal(checkpoint.best_lb, solution),
+            None => self.set_primal_bound(checkpoint.best_lb),
+        }
+        self.explored = checkpoint.explored;
+        Ok(())
+    }
+}
+
 impl<'a, State, D, C> Solver for SequentialSolver<'a, State, D, C>
 where
     State: Eq + PartialEq + Hash + Clone,
@@ -469,28 +1570,11 @@ where
     C: Cache<State = State> + Default,
 {
     /// Applies the branch and bound algorithm proposed by Bergman et al. to
-    /// solve the problem to optimality. To do so, it spawns `nb_threads` workers
-    /// (long running threads); each of which will continually get a workload
-    /// and process it until the problem is solved.
+    /// solve the problem to optimality. To do so, it explores one node after
+    /// the other (see `step`) until the problem is solved.
     fn maximize(&mut self) -> Completion {
-        self.initialize();
-
-        loop {
-            match self.get_workload() {
-                WorkLoad::Complete => break,
-                WorkLoad::Aborted => break, // this one cannot occur
-                WorkLoad::WorkItem { node } => {
-                    let outcome = self.process_one_node(node);
-                    if let Err(reason) = outcome {
-                        self.abort_search(reason); 
-                        break;
-                    }
-                }
-            }
-        }
-
-        if let Some(sol) = self.best_sol.as_mut() { sol.sort_unstable_by_key(|d| d.variable.0) }
-        Completion { is_exact: self.abort_proof.is_none(), best_value: self.best_sol.as_ref().map(|_| self.best_lb) }
+        while self.step() == StepResult::Explored {}
+        self.finish()
     }
 
     /// Returns the best solution that has been identified for this problem.
@@ -514,15 +1598,93 @@ where
     }
     /// Sets a primal (best known value and solution) of the problem.
     fn set_primal(&mut self, value: isize, solution: Solution) {
+        if let Some(pool) = self.pool.as_mut() {
+            pool.offer(value, solution.clone());
+        }
+        if let Some(pool) = self.diversity.as_mut() {
+            if self.dedup.as_mut().is_none_or(|d| d.is_new(&solution)) {
+                pool.offer(value, solution.clone());
+            }
+        }
         if value > self.best_lb {
+            if let Some(incumbent) = self.shared_incumbent.as_ref() {
+                incumbent.offer(value, &solution);
+            }
             self.best_sol = Some(solution);
+            self.best_state = None;
             self.best_lb  = value;
+            self.bounds.publish(self.best_lb, self.best_ub);
+        }
+    }
+    /// Only looks for solutions better than `bound` (see `Solver::set_primal_bound`)
+    fn set_primal_bound(&mut self, bound: isize) {
+        if bound > self.best_lb {
+            self.best_sol = None;
+            self.best_state = None;
+            self.best_lb = bound;
+            self.bounds.publish(self.best_lb, self.best_ub);
+        }
+    }
+    /// Forgets all about the previous resolutions (see `Solver::reset`)
+    fn reset(&mut self) {
+        let nb_vars = self.problem.nb_variables();
+        self.fringe.clear();
+        self.cache.clear();
+        (0..=nb_vars).for_each(|d| self.dominance.clear_layer(d));
+        //
+        self.explored = 0;
+        self.open_by_layer = vec![0; nb_vars + 1];
+        self.first_active_layer = 0;
+        self.best_lb = isize::MIN;
+        self.best_ub = isize::MAX;
+        self.bounds.publish(self.best_lb, self.best_ub);
+        self.abort.clear();
+        if let Some(suspended) = self.suspended.as_mut() {
+            suspended.clear();
+        }
+        self.best_sol = None;
+        self.best_state = None;
+        self.abort_proof = None;
+        //
+        self.dual_refresh = self.dual_refresh.as_ref().map(|s| DualRefreshSchedule::new(s.policy));
+        self.restarts = self.restarts.as_ref().map(|r| RestartTracker::new(r.policy));
+        self.presolve_record = None;
+        self.diversity = self.diversity.as_ref().map(|p| DiversityPool::new(p.policy));
+        self.pool = self.pool.as_ref().map(|p| SolutionPool::new(p.k));
+        self.fringe_sampler = self.fringe_sampler.as_ref().map(|s| FringeSampler::new(s.sampling));
+        self.dedup = self.dedup.as_ref().map(|d| d.renewed());
+        self.memory = MemoryStats::default();
+        self.stats = Statistics::default();
+        self.stepping = false;
+        self.recompiled = 0;
+        self.eager_restricted = 0;
+        self.eager_pruned = 0;
+        self.clamped = 0;
+        self.boosted = 0;
+        self.root_bound = None;
+        self.first_incumbent = None;
+        self.roots.clear();
+        self.tree_estimate = self.tree_estimate.map(|e| TreeEstimate::new(e.samples, e.seed));
+        self.latency.clear();
+        if let Some(tree) = self.tree.as_mut() {
+            tree.clear();
         }
     }
+    /// Limits the number of nodes that may be explored (see `Solver::set_node_budget`)
+    fn set_node_budget(&mut self, budget: Option<usize>) {
+        self.node_budget = budget;
+    }
+    /// Limits the time `maximize` may run (see `Solver::set_time_limit`)
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit.limit = limit;
+    }
     /// Returns the number of nodes that have been explored so far.
     fn explored(&self) -> usize {
         self.explored
     }
+    fn statistics(&self) -> Option<Statistics> {
+        Some(Statistics { explored: self.explored, ..self.stats })
+    }
 }
 
 
@@ -538,6 +1700,9 @@ where
 
 #[cfg(test)]
 mod test_solver {
+    use std::sync::{Mutex, atomic::{AtomicBool, Ordering as AtomicOrdering}};
+    use std::time::{Duration, Instant};
+
     use crate::*;
 
     type SeqSolver<'a, T> = SequentialSolver<'a, T, DefaultMDDLEL<T>, EmptyCache<T>>;
@@ -907,7 +2072,7 @@ mod test_solver {
     }
 
     #[test]
-    fn set_primal_overwrites_best_value_and_sol_if_it_improves() {
+    fn reset_forgets_about_the_previous_resolution() {
         let problem = Knapsack {
             capacity: 50,
             profit  : vec![60, 100, 120],
@@ -919,7 +2084,7 @@ mod test_solver {
         let width = NbUnassignedWidth(problem.nb_variables());
         let dominance = EmptyDominanceChecker::default();
         let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
-        let mut solver = SeqSolver::new(
+        let mut solver = SeqSolver::custom(
             &problem,
             &relax,
             &ranking,
@@ -929,44 +2094,41 @@ mod test_solver {
             &mut fringe,
         );
 
-        let d1  = Decision{variable: Variable(0), value: 10};
-        let sol = vec![d1];
-
-        solver.set_primal(10, sol.clone());
-        assert!(solver.best_sol.is_some());
-        assert_eq!(10, solver.best_lb);
-
-        // in this case, it wont update because there is no improvement
-        solver.set_primal(5, sol.clone());
-        assert!(solver.best_sol.is_some());
-        assert_eq!(10, solver.best_lb);
+        let first = solver.maximize();
+        let explored = solver.explored();
+        let solution = solver.best_solution();
+        solver.set_primal(10000, vec![]);
 
-        // but here, it will update as it improves the best known sol
-        solver.set_primal(10000, sol);
-        assert!(solver.best_sol.is_some());
-        assert_eq!(10000, solver.best_lb);
+        solver.reset();
+        assert_eq!(None, solver.best_value());
+        assert_eq!(None, solver.best_solution());
+        assert_eq!(None, solver.best_state());
+        assert_eq!(None, solver.root_bound());
+        assert_eq!(isize::MIN, solver.best_lower_bound());
+        assert_eq!(isize::MAX, solver.best_upper_bound());
+        assert_eq!(0, solver.explored());
 
-        // it wont do much as the primal is better than the actual feasible solution
-        let maximized = solver.maximize();
-        assert!(maximized.is_exact);
-        assert_eq!(maximized.best_value, Some(10000));
-        assert!(solver.best_solution().is_some());
+        // solving the problem once again yields the very same outcome
+        let second = solver.maximize();
+        assert_eq!(first.is_exact, second.is_exact);
+        assert_eq!(first.best_value, second.best_value);
+        assert_eq!(solution, solver.best_solution());
+        assert_eq!(explored, solver.explored());
     }
-
     #[test]
-    fn when_no_solution_is_found_the_gap_is_one() {
+    fn the_search_stops_when_the_node_budget_is_exhausted() {
         let problem = Knapsack {
-            capacity: 50,
-            profit  : vec![60, 100, 120],
-            weight  : vec![10,  20,  30]
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
         };
         let relax = KPRelax {pb: &problem};
         let ranking = KPRanking;
         let cutoff = NoCutoff;
-        let width = NbUnassignedWidth(problem.nb_variables());
+        let width = FixedWidth(1);
         let dominance = EmptyDominanceChecker::default();
         let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
-        let solver = SeqSolver::new(
+        let mut solver = SeqSolver::custom(
             &problem,
             &relax,
             &ranking,
@@ -976,22 +2138,36 @@ mod test_solver {
             &mut fringe,
         );
 
-        assert_eq!(1.0, solver.gap());
+        solver.set_node_budget(Some(3));
+        let partial = solver.maximize();
+        assert!(!partial.is_exact);
+        assert_eq!(Some(Reason::NodeLimit), partial.stopped_by);
+        assert!(solver.explored() <= 3);
+        assert!(solver.best_upper_bound() > solver.best_lower_bound());
+
+        // lifting the budget lets the solver reach the optimum
+        solver.reset();
+        solver.set_node_budget(None);
+        let complete = solver.maximize();
+        assert!(complete.is_exact);
+        assert_eq!(None, complete.stopped_by);
+        assert_eq!(Some(134), complete.best_value);
+        assert!(solver.explored() > 3);
     }
     #[test]
-    fn when_optimum_solution_is_found_the_gap_is_zero() {
+    fn the_best_incumbent_is_kept_when_the_node_limit_is_reached() {
         let problem = Knapsack {
-            capacity: 50,
-            profit  : vec![60, 100, 120],
-            weight  : vec![10,  20,  30]
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
         };
         let relax = KPRelax {pb: &problem};
         let ranking = KPRanking;
         let cutoff = NoCutoff;
-        let width = NbUnassignedWidth(problem.nb_variables());
+        let width = FixedWidth(1);
         let dominance = EmptyDominanceChecker::default();
         let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
-        let mut solver = SeqSolver::new(
+        let mut solver = SeqSolver::custom(
             &problem,
             &relax,
             &ranking,
@@ -999,18 +2175,2364 @@ mod test_solver {
             &dominance,
             &cutoff,
             &mut fringe,
-        );
+        ).with_max_explored(2);
 
-        let Completion{is_exact, best_value} = solver.maximize();
-        assert!(is_exact);
-        assert!(best_value.is_some());
-        assert_eq!(0.0, solver.gap());
+        let partial = solver.maximize();
+        assert!(!partial.is_exact);
+        assert_eq!(Some(Reason::NodeLimit), partial.stopped_by);
+        assert!(solver.explored() <= 2);
+        // the restriction of the root already yields a solution
+        assert!(partial.best_value.is_some_and(|value| value <= 134));
+        assert_eq!(partial.best_value, solver.best_value());
+        assert_eq!(partial.best_value, solver.best_solution().map(|sol| sol.iter()
+            .filter(|d| d.value == 1)
+            .map(|d| problem.profit[d.variable.id()] as isize)
+            .sum()));
     }
+    #[test]
+    fn the_best_ub_remains_valid_when_the_fringe_does_not_pop_by_ub() {
+        /// Pops the deepest nodes first (without telling it does not order by ub)
+        struct DeepestFirst;
+        impl SubProblemRanking for DeepestFirst {
+            type State = KnapsackState;
 
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    struct KnapsackState {
-        depth: usize,
-        capacity: usize
+            fn compare(&self, a: &SubProblem<KnapsackState>, b: &SubProblem<KnapsackState>) -> std::cmp::Ordering {
+                a.depth.cmp(&b.depth)
+            }
+        }
+        let problem = Knapsack {
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(1);
+        let dominance = EmptyDominanceChecker::default();
+        let solve = |budget: usize| {
+            let mut fringe = SimpleFringe::new(DeepestFirst);
+            let mut solver = SeqSolver::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+            ).with_max_explored(budget);
+            let completion = solver.maximize();
+            (completion, solver.best_upper_bound())
+        };
+
+        // the optimum is 134: no bound may ever drop below it
+        for budget in 1..=10 {
+            let (partial, best_ub) = solve(budget);
+            assert!(best_ub >= 134, "bogus bound {best_ub} after {budget} nodes");
+            assert!(partial.best_value.is_some_and(|value| value <= best_ub));
+        }
+        let (complete, best_ub) = solve(usize::MAX);
+        assert!(complete.is_exact);
+        assert_eq!(Some(134), complete.best_value);
+        assert_eq!(134, best_ub);
+    }
+    #[test]
+    fn the_cutoff_is_checked_before_each_node() {
+        let problem = Knapsack {
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let stop = AtomicBool::new(true);
+        let cutoff = CutoffFn::new(|| stop.load(AtomicOrdering::Relaxed));
+        let width = FixedWidth(1);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+
+        // the search stops before any dd is compiled
+        let partial = solver.maximize();
+        assert!(!partial.is_exact);
+        assert_eq!(Some(Reason::CutoffOccurred), partial.stopped_by);
+        assert_eq!(0, solver.explored());
+        assert_eq!(None, partial.best_value);
+
+        stop.store(false, AtomicOrdering::Relaxed);
+        solver.reset();
+        let complete = solver.maximize();
+        assert!(complete.is_exact);
+        assert_eq!(Some(134), complete.best_value);
+    }
+    #[test]
+    fn the_search_stops_when_the_time_is_up() {
+        let problem = Knapsack {
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(1);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+
+        solver.set_time_limit(Some(Duration::ZERO));
+        let partial = solver.maximize();
+        assert!(!partial.is_exact);
+        assert_eq!(Some(Reason::Timeout), partial.stopped_by);
+
+        // lifting the limit lets the solver reach the optimum
+        solver.reset();
+        solver.set_time_limit(None);
+        let complete = solver.maximize();
+        assert!(complete.is_exact);
+        assert_eq!(None, complete.stopped_by);
+        assert_eq!(Some(134), complete.best_value);
+    }
+    #[test]
+    fn manually_splitting_the_root_preserves_the_optimum() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120, 30, 45, 80],
+            weight  : vec![10,  20,  30,  5, 15, 25]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = solver.maximize();
+
+        // branch on the first item: either it is taken or it is left out
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_manual_root();
+        let take  = Decision { variable: Variable(0), value: TAKE_IT };
+        let leave = Decision { variable: Variable(0), value: LEAVE_IT_OUT };
+        solver.push_subproblem(KnapsackState { depth: 1, capacity: 40 }, 60, isize::MAX, vec![take]);
+        solver.push_subproblem(KnapsackState { depth: 1, capacity: 50 },  0, isize::MAX, vec![leave]);
+        let actual = solver.maximize();
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert_eq!(problem.nb_variables(), solver.best_solution().unwrap().len());
+    }
+    #[test]
+    fn with_a_manual_root_and_no_subproblem_nothing_is_explored() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_manual_root();
+
+        let outcome = solver.maximize();
+        assert_eq!(0, solver.explored());
+        assert_eq!(None, outcome.best_value);
+    }
+    #[test]
+    #[should_panic]
+    fn pushing_a_subproblem_which_decides_twice_on_a_variable_panics() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_manual_root();
+
+        let leave = Decision { variable: Variable(0), value: LEAVE_IT_OUT };
+        solver.push_subproblem(KnapsackState { depth: 2, capacity: 50 }, 0, isize::MAX, vec![leave, leave]);
+    }
+    #[test]
+    fn set_primal_overwrites_best_value_and_sol_if_it_improves() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+
+        let d1  = Decision{variable: Variable(0), value: 10};
+        let sol = vec![d1];
+
+        solver.set_primal(10, sol.clone());
+        assert!(solver.best_sol.is_some());
+        assert_eq!(10, solver.best_lb);
+
+        // in this case, it wont update because there is no improvement
+        solver.set_primal(5, sol.clone());
+        assert!(solver.best_sol.is_some());
+        assert_eq!(10, solver.best_lb);
+
+        // but here, it will update as it improves the best known sol
+        solver.set_primal(10000, sol);
+        assert!(solver.best_sol.is_some());
+        assert_eq!(10000, solver.best_lb);
+
+        // it wont do much as the primal is better than the actual feasible solution
+        let maximized = solver.maximize();
+        assert!(maximized.is_exact);
+        assert_eq!(maximized.best_value, Some(10000));
+        assert!(solver.best_solution().is_some());
+    }
+
+    #[test]
+    fn an_initial_solution_is_returned_when_nothing_beats_it() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut cold = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+
+        cold.maximize();
+        let optimal = vec![
+            Decision{variable: Variable(0), value: LEAVE_IT_OUT},
+            Decision{variable: Variable(1), value: TAKE_IT},
+            Decision{variable: Variable(2), value: TAKE_IT},
+        ];
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+
+        assert!(solver.set_initial_solution(optimal.clone(), 220).is_ok());
+        let warm = solver.maximize();
+        assert!(warm.is_exact);
+        assert_eq!(Some(220), warm.best_value);
+        assert_eq!(Some(optimal), solver.best_solution());
+        assert!(solver.explored() <= cold.explored());
+    }
+    #[test]
+    fn an_initial_solution_is_only_trusted_when_it_is_worth_its_value() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+
+        let optimal = vec![
+            Decision{variable: Variable(0), value: LEAVE_IT_OUT},
+            Decision{variable: Variable(1), value: TAKE_IT},
+            Decision{variable: Variable(2), value: TAKE_IT},
+        ];
+        assert_eq!(
+            Err(WarmStartError::WrongValue { announced: 300, actual: 220 }), 
+            solver.set_initial_solution(optimal, 300));
+        let overweight = vec![
+            Decision{variable: Variable(0), value: TAKE_IT},
+            Decision{variable: Variable(1), value: TAKE_IT},
+            Decision{variable: Variable(2), value: TAKE_IT},
+        ];
+        assert!(matches!(
+            solver.set_initial_solution(overweight, 280), 
+            Err(WarmStartError::Invalid(EvalError::Infeasible{depth: 2, ..}))));
+        assert_eq!(None, solver.best_value());
+        assert_eq!(isize::MIN, solver.best_lower_bound());
+    }
+    #[test]
+    fn a_primal_bound_prunes_the_search_without_providing_a_solution() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+
+        solver.set_primal_bound(220);
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert_eq!(None, outcome.best_value);
+        assert_eq!(None, solver.best_solution());
+
+        solver.reset();
+        solver.set_primal_bound(219);
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert_eq!(Some(220), outcome.best_value);
+    }
+
+    #[test]
+    fn when_no_solution_is_found_the_gap_is_one() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+
+        assert_eq!(1.0, solver.gap());
+    }
+    #[test]
+    fn when_optimum_solution_is_found_the_gap_is_zero() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+
+        let Completion{is_exact, best_value, ..} = solver.maximize();
+        assert!(is_exact);
+        assert!(best_value.is_some());
+        assert_eq!(0.0, solver.gap());
+    }
+    #[test]
+    fn the_gap_never_increases_during_the_search() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+
+        solver.initialize();
+        let mut ub  = solver.best_upper_bound();
+        let mut gap = solver.gap();
+        while let super::WorkLoad::WorkItem { node } = solver.get_workload() {
+            solver.process_one_node(node).unwrap();
+            solver.refresh_best_ub();
+
+            assert!(solver.best_upper_bound() <= ub);
+            assert!(solver.gap() <= gap);
+            ub  = solver.best_upper_bound();
+            gap = solver.gap();
+        }
+        assert!(solver.explored() > 1);
+        assert_eq!(solver.best_lower_bound(), solver.best_upper_bound());
+        assert_eq!(0.0, solver.gap());
+    }
+
+    #[test]
+    fn dual_refresh_preserves_the_optimum_and_never_loosens_the_bound() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = solver.maximize();
+        assert!(solver.dual_refreshes().is_empty());
+
+        let policy = DualRefresh { explored_factor: 2, ..DualRefresh::default() };
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_dual_refresh(policy);
+        let actual = solver.maximize();
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert_eq!(solver.best_lower_bound(), solver.best_upper_bound());
+        assert!(!solver.dual_refreshes().is_empty());
+        for refresh in solver.dual_refreshes().iter() {
+            assert!(refresh.ub_after.unwrap() <= refresh.ub_before);
+        }
+    }
+
+    fn solve_pairs(pairs: &Pairs, restarts: Option<Restarts>) -> (Completion, usize, Vec<RestartRecord>) {
+        let relax = PairsRelax;
+        let ranking = PairsRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqCachingSolver::new(
+            pairs,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_reseed(pairs);
+        if let Some(policy) = restarts {
+            solver = solver.with_restarts(policy);
+        }
+        let outcome = solver.maximize();
+        (outcome, solver.explored(), solver.restarts().to_vec())
+    }
+
+    #[test]
+    fn stepping_through_the_search_is_the_same_as_maximizing() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = solver.maximize();
+        let expected_explored = solver.explored();
+        solver.reset();
+
+        let (mut lb, mut ub) = (solver.best_lower_bound(), solver.best_upper_bound());
+        let mut steps = 0;
+        while solver.step() == StepResult::Explored {
+            steps += 1;
+            assert_eq!(steps, solver.explored());
+            assert!(solver.best_lower_bound() >= lb);
+            assert!(solver.best_upper_bound() <= ub);
+            (lb, ub) = (solver.best_lower_bound(), solver.best_upper_bound());
+        }
+        assert_eq!(0, solver.open_nodes());
+        assert_eq!(StepResult::Finished, solver.step());
+
+        let actual = solver.finish();
+        assert_eq!(expected_explored, steps);
+        assert_eq!(expected.is_exact, actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert_eq!(Some(380), actual.best_value);
+    }
+    #[test]
+    fn a_step_stopped_by_the_cutoff_finishes_the_search() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        assert_eq!(StepResult::Explored, solver.step());
+        solver.abort_handle().abort();
+        assert_eq!(StepResult::Finished, solver.step());
+        let completion = solver.finish();
+        assert!(!completion.is_exact);
+        assert_eq!(Some(Reason::Aborted), completion.stopped_by);
+    }
+    #[test]
+    fn the_statistics_account_for_the_whole_search() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        assert_eq!(Some(Statistics::default()), solver.statistics());
+        let outcome = solver.maximize();
+        assert_eq!(Some(380), outcome.best_value);
+
+        let stats = solver.statistics().unwrap();
+        assert_eq!(solver.explored(), stats.explored);
+        assert_eq!(outcome.termination.unwrap().elapsed, stats.elapsed);
+        // each explored node is either pruned when popped or compiled, and 
+        // all the explored nodes but the root come from a cut-set
+        assert!(stats.restricted >= 1 && stats.restricted <= stats.explored);
+        assert!(stats.relaxed <= stats.restricted);
+        assert!(stats.cutset_nodes >= stats.explored - 1);
+        assert!(stats.peak_fringe >= 1);
+        assert!(stats.compilation_time() <= stats.elapsed);
+        assert!(stats.to_string().contains(&format!("{} nodes explored", stats.explored)));
+
+        solver.reset();
+        assert_eq!(Some(Statistics::default()), solver.statistics());
+    }
+    #[test]
+    fn memory_stats_account_for_each_data_structure() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqCachingSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        assert_eq!(MemoryStats::default(), solver.memory_stats());
+        solver.maximize();
+
+        let stats = solver.memory_stats();
+        let node_bytes = subproblem_bytes::<KnapsackState>();
+        assert!(stats.fringe_bytes >= node_bytes);
+        assert_eq!(0, stats.fringe_bytes % node_bytes);
+        assert!(stats.mdd_bytes > 0);
+        assert!(stats.cache_bytes > 0);
+        assert_eq!(0, stats.dominance_bytes);
+        assert_eq!(stats.fringe_bytes + stats.mdd_bytes + stats.cache_bytes, stats.peak_bytes());
+        assert_eq!(cfg!(all(feature = "rss", target_os = "linux")), stats.rss_bytes.is_some());
+    }
+
+    /// Solves the knapsack instance of the memory test with a fringe which
+    /// holds at most `capacity` nodes
+    fn solve_with_bounded_fringe(capacity: usize) -> (Completion, isize) {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = BoundedFringe::new(SimpleFringe::new(MaxUB::new(&ranking)), capacity).with_retained(1);
+        let mut solver = SeqNoCachingSolverLel::new(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let outcome = solver.maximize();
+        (outcome, solver.best_upper_bound())
+    }
+    #[test]
+    fn a_bounded_fringe_which_sheds_nodes_keeps_a_valid_upper_bound() {
+        let (outcome, best_ub) = solve_with_bounded_fringe(1);
+        let termination = outcome.termination.unwrap();
+        assert!(termination.shed > 0);
+        assert!(outcome.stopped_by.is_none());
+        assert!(outcome.best_value.unwrap() <= 380);
+        assert!(best_ub >= 380);
+        assert_eq!(outcome.is_exact, best_ub == outcome.best_value.unwrap());
+    }
+    #[test]
+    fn a_bounded_fringe_which_never_fills_up_is_exact() {
+        let (outcome, best_ub) = solve_with_bounded_fringe(usize::MAX);
+        assert_eq!(0, outcome.termination.unwrap().shed);
+        assert!(outcome.is_exact);
+        assert_eq!(Some(380), outcome.best_value);
+        assert_eq!(380, best_ub);
+    }
+
+    #[cfg(feature = "ooc")]
+    #[test]
+    fn the_out_of_core_fringe_reaches_the_same_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqCachingSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = solver.maximize();
+
+        let config = OocConfig { working_set: 2, compact_every: 1, ..OocConfig::default() };
+        let mut fringe = OocFringe::new(MaxUB::new(&ranking), config).unwrap();
+        let mut solver = SeqCachingSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let actual = solver.maximize();
+        drop(solver);
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert!(fringe.stats().spilled_nodes > 0);
+        assert!(fringe.stats().reloaded_nodes > 0);
+    }
+
+    #[cfg(feature = "checkpoint")]
+    #[test]
+    fn a_search_resumed_from_its_checkpoints_reaches_the_same_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = solver.maximize();
+
+        // the search is preempted every other node
+        let mut checkpoint = vec![];
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_checkpointing();
+        solver.checkpoint(&mut checkpoint).unwrap();
+        drop(solver);
+
+        let (mut resumptions, mut explored) = (0, 0);
+        let actual = loop {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = SeqSolver::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+            ).with_checkpointing();
+            solver.resume(&mut checkpoint.as_slice()).unwrap();
+            assert_eq!(explored, solver.explored());
+            solver.set_node_budget(Some(explored + 2));
+
+            let completion = solver.maximize();
+            resumptions += 1;
+            if completion.is_exact {
+                let solution = solver.best_solution().unwrap();
+                assert_eq!(completion.best_value, evaluate(&problem, &solution).ok());
+                break completion;
+            }
+            assert_eq!(Some(Reason::NodeLimit), completion.stopped_by);
+            explored = solver.explored();
+            checkpoint.clear();
+            solver.checkpoint(&mut checkpoint).unwrap();
+        };
+
+        assert!(expected.is_exact);
+        assert!(resumptions > 1);
+        assert_eq!(expected.best_value, actual.best_value);
+    }
+    #[cfg(feature = "checkpoint")]
+    #[test]
+    fn the_open_nodes_must_be_kept_to_checkpoint_a_stopped_search() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        solver.set_node_budget(Some(1));
+        let completion = solver.maximize();
+
+        assert!(!completion.is_exact);
+        let error = solver.checkpoint(&mut Vec::<u8>::new()).unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, error.kind());
+    }
+
+    /// A fringe which remembers the path of each node that was pushed onto it
+    struct RecordingFringe<'a> {
+        fringe: SimpleFringe<MaxUB<'a, KPRanking>>,
+        pushed: Vec<Vec<Decision>>,
+    }
+    impl Fringe for RecordingFringe<'_> {
+        type State = KnapsackState;
+        fn push(&mut self, node: SubProblem<KnapsackState>) {
+            self.pushed.push(node.path.clone());
+            self.fringe.push(node)
+        }
+        fn pop(&mut self) -> Option<SubProblem<KnapsackState>> {
+            self.fringe.pop()
+        }
+        fn peek(&self) -> Option<&SubProblem<KnapsackState>> {
+            self.fringe.peek()
+        }
+        fn clear(&mut self) {
+            self.fringe.clear()
+        }
+        fn len(&self) -> usize {
+            self.fringe.len()
+        }
+    }
+
+    #[test]
+    fn recompiling_wider_relaxations_enqueues_each_node_once() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = solver.maximize();
+
+        let mut fringe = RecordingFringe { fringe: SimpleFringe::new(MaxUB::new(&ranking)), pushed: vec![] };
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_wider_recompile(0);
+        let actual = solver.maximize();
+        assert!(solver.wider_recompilations() > 0);
+        drop(solver);
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        let mut paths = fringe.pushed.clone();
+        paths.sort_unstable();
+        paths.dedup();
+        assert_eq!(fringe.pushed.len(), paths.len());
+    }
+
+    /// A fringe which remembers the priority bonus of each node pushed onto
+    /// it, along with the bonus of the node which was popped last (that is, 
+    /// the parent of the pushed node when it comes from a cut-set)
+    struct BonusFringe<'a> {
+        fringe: SimpleFringe<BonusFirst<MaxUB<'a, KPRanking>>>,
+        parent: Option<u8>,
+        pushed: Vec<(Option<u8>, u8)>,
+    }
+    impl Fringe for BonusFringe<'_> {
+        type State = KnapsackState;
+        fn push(&mut self, node: SubProblem<KnapsackState>) {
+            self.pushed.push((self.parent, node.priority_bonus));
+            self.fringe.push(node)
+        }
+        fn pop(&mut self) -> Option<SubProblem<KnapsackState>> {
+            let node = self.fringe.pop();
+            self.parent = node.as_ref().map(|n| n.priority_bonus);
+            node
+        }
+        fn peek(&self) -> Option<&SubProblem<KnapsackState>> {
+            self.fringe.peek()
+        }
+        fn best_ub(&self) -> Option<isize> {
+            self.fringe.best_ub()
+        }
+        fn clear(&mut self) {
+            self.parent = None;
+            self.fringe.clear()
+        }
+        fn len(&self) -> usize {
+            self.fringe.len()
+        }
+    }
+
+    #[test]
+    fn a_priority_bonus_reorders_the_search_without_changing_the_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = solver.maximize();
+        assert_eq!(0, solver.boosted_nodes());
+
+        let mut fringe = BonusFringe { fringe: SimpleFringe::new(BonusFirst::new(MaxUB::new(&ranking))), parent: None, pushed: vec![] };
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_manual_root();
+        let take  = Decision { variable: Variable(0), value: TAKE_IT };
+        let leave = Decision { variable: Variable(0), value: LEAVE_IT_OUT };
+        solver.push_subproblem(KnapsackState { depth: 1, capacity: 90 }, 60, isize::MAX, vec![take]);
+        solver.push_boosted_subproblem(KnapsackState { depth: 1, capacity: 100 }, 0, isize::MAX, vec![leave], 3);
+        let actual = solver.maximize();
+        assert!(solver.boosted_nodes() > 0);
+        drop(solver);
+
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        // the boosted root is explored first, its descendants inherit a bonus 
+        // which decays by one at each generation
+        assert_eq!(Some(3), fringe.pushed.iter().filter_map(|(parent, _)| *parent).next());
+        for (parent, bonus) in fringe.pushed.iter().copied() {
+            if let Some(parent) = parent {
+                assert_eq!(parent.saturating_sub(1), bonus);
+            }
+        }
+        assert!(fringe.pushed.iter().any(|(parent, bonus)| *parent == Some(3) && *bonus == 2));
+    }
+
+    #[test]
+    fn the_root_bound_and_the_first_incumbent_bracket_the_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        assert_eq!(None, solver.root_bound());
+        assert_eq!(None, solver.first_incumbent());
+
+        let best = solver.maximize().best_value.unwrap();
+        let root_bound = solver.root_bound().unwrap();
+        let first_incumbent = solver.first_incumbent().unwrap();
+        assert!(root_bound > best);
+        assert!(first_incumbent <= best);
+    }
+    #[test]
+    fn the_root_bound_is_the_optimum_when_the_root_is_solved_exactly() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10, 20, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        solver.maximize();
+        assert_eq!(Some(220), solver.root_bound());
+        assert_eq!(Some(220), solver.first_incumbent());
+    }
+    #[test]
+    fn the_tree_is_estimated_when_the_search_starts() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10, 20, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_tree_estimate(20, 42);
+        assert_eq!(None, solver.tree_estimate());
+
+        solver.maximize();
+        let expected = estimate_tree_size(&problem, &relax, &EstimateConfig::default(), 20, 42);
+        assert_eq!(Some(expected), solver.tree_estimate());
+
+        solver.reset();
+        assert_eq!(None, solver.tree_estimate());
+    }
+
+    /// A DD which forgets the bounds of its cut-set nodes, like a custom DD
+    /// which would derive them from coarser information
+    #[derive(Default)]
+    struct Inflating(DefaultMDDLEL<KnapsackState>);
+    impl DecisionDiagram for Inflating {
+        type State = KnapsackState;
+
+        fn compile(&mut self, input: &CompilationInput<KnapsackState>, residual: SubProblem<KnapsackState>) -> Result<Completion, Reason> {
+            self.0.compile(input, residual)
+        }
+        fn take_residual(&mut self) -> Option<SubProblem<KnapsackState>> { self.0.take_residual() }
+        fn is_exact(&self) -> bool { self.0.is_exact() }
+        fn best_value(&self) -> Option<isize> { self.0.best_value() }
+        fn best_solution(&self) -> Option<Solution> { self.0.best_solution() }
+        fn best_exact_value(&self) -> Option<isize> { self.0.best_exact_value() }
+        fn best_exact_solution(&self) -> Option<Solution> { self.0.best_exact_solution() }
+        fn cutset_len(&self) -> usize { self.0.cutset_len() }
+        fn for_each_cutset_node<F>(&self, mut func: F) where F: FnMut(SubProblem<KnapsackState>) {
+            self.0.for_each_cutset_node(|node| func(SubProblem { ub: isize::MAX, ..node }))
+        }
+        fn drain_cutset<F>(&mut self, mut func: F) where F: FnMut(SubProblem<KnapsackState>) {
+            self.0.drain_cutset(|node| func(SubProblem { ub: isize::MAX, ..node }))
+        }
+    }
+
+    #[test]
+    fn the_ub_of_the_cutset_nodes_is_clamped_to_the_best_value_of_the_relaxed_dd() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+
+        // the cut-set nodes of this dd would otherwise enter the fringe with
+        // the ub of their parent (infinite for the children of the root)
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut inflating = SequentialSolver::<_, Inflating, EmptyCache<_>>::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let outcome = inflating.maximize();
+        assert!(outcome.is_exact);
+        assert!(inflating.clamped_cutset_ubs() > 0);
+
+        // the default dd already bounds its cut-set by its best value
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut reference = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = reference.maximize();
+        assert_eq!(expected.best_value, outcome.best_value);
+        assert_eq!(0, reference.clamped_cutset_ubs());
+
+        inflating.reset();
+        assert_eq!(0, inflating.clamped_cutset_ubs());
+    }
+
+    #[test]
+    fn the_processed_nodes_are_moved_from_one_compilation_to_the_next() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        )
+        .with_wider_recompile(0)
+        .with_eager_restriction(1, usize::MAX);
+
+        crate::SUBPROBLEM_CLONES.with(|clones| clones.set(0));
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert!(solver.wider_recompilations() > 0);
+        assert!(solver.eager_restrictions() > 0);
+        // neither the restricted, the relaxed, the wider nor the eager 
+        // compilations need a copy of the node they are rooted in
+        assert_eq!(0, crate::SUBPROBLEM_CLONES.with(|clones| clones.get()));
+    }
+
+    /// The same relaxation as `KPRelax`, without any rough upper bound
+    struct KPBluntRelax<'a>(KPRelax<'a>);
+    impl Relaxation for KPBluntRelax<'_> {
+        type State = KnapsackState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            self.0.merge(states)
+        }
+        fn relax(&self, source: &Self::State, dest: &Self::State, merged: &Self::State, decision: Decision, cost: isize) -> isize {
+            self.0.relax(source, dest, merged, decision, cost)
+        }
+    }
+
+    #[test]
+    fn the_rough_upper_bound_keeps_cutset_nodes_out_of_the_fringe() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+
+        // the cut-set nodes of this dd only get the bound of the relaxed dd 
+        // unless the relaxation has a rough upper bound
+        let blunt = KPBluntRelax(KPRelax {pb: &problem});
+        let mut fringe = RecordingFringe { fringe: SimpleFringe::new(MaxUB::new(&ranking)), pushed: vec![] };
+        let mut solver = SequentialSolver::<_, Inflating, EmptyCache<_>>::custom(
+            &problem,
+            &blunt,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = solver.maximize();
+        drop(solver);
+        let blunt_pushes = fringe.pushed.len();
+
+        let relax = KPRelax {pb: &problem};
+        let mut fringe = RecordingFringe { fringe: SimpleFringe::new(MaxUB::new(&ranking)), pushed: vec![] };
+        let mut solver = SequentialSolver::<_, Inflating, EmptyCache<_>>::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let actual = solver.maximize();
+        drop(solver);
+
+        assert!(expected.is_exact);
+        assert!(actual.is_exact);
+        assert_eq!(Some(380), expected.best_value);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert!(fringe.pushed.len() < blunt_pushes);
+    }
+    #[test]
+    fn the_eager_restriction_of_the_cutset_nodes_preserves_the_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_eager_restriction(1, usize::MAX);
+
+        let Completion { is_exact, best_value, .. } = solver.maximize();
+        assert!(is_exact);
+        assert_eq!(Some(380), best_value);
+        assert!(solver.eager_restrictions() > 0);
+        assert!(solver.eagerly_pruned_nodes() <= solver.eager_restrictions());
+
+        solver.reset();
+        assert_eq!(0, solver.eager_restrictions());
+        assert_eq!(0, solver.eagerly_pruned_nodes());
+    }
+    #[test]
+    fn no_cutset_node_is_restricted_eagerly_once_the_fringe_is_large_enough() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_eager_restriction(1, 0);
+
+        let Completion { is_exact, best_value, .. } = solver.maximize();
+        assert!(is_exact);
+        assert_eq!(Some(380), best_value);
+        assert_eq!(0, solver.eager_restrictions());
+        assert_eq!(0, solver.eagerly_pruned_nodes());
+    }
+
+    #[test]
+    fn the_explored_tree_is_exported() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120, 30, 45],
+            weight  : vec![10, 20, 30, 15, 25]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut tree = SearchTree::new();
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_tree_export(&mut tree);
+        let Completion { best_value, .. } = solver.maximize();
+        let explored = solver.explored();
+        let (lb, ub) = (solver.best_lower_bound(), solver.best_upper_bound());
+        drop(solver);
+
+        assert_eq!(Some(220), best_value);
+        assert_eq!(vec![0], tree.roots().map(|n| n.id).collect::<Vec<_>>());
+        assert!(tree.len() >= explored);
+        for node in tree.nodes() {
+            assert_ne!(NodeStatus::Open, node.status);
+            for child in tree.children(node.id) {
+                assert_eq!(Some(node.id), child.parent);
+                assert_eq!(NodeStatus::Branched, node.status);
+            }
+        }
+        let processed = tree.nodes().iter()
+            .filter(|n| matches!(n.status, NodeStatus::Branched | NodeStatus::Fathomed(_)))
+            .count();
+        assert!(processed <= explored);
+        assert_eq!(Some(&(isize::MIN, isize::MAX)), tree.bounds().first());
+        assert_eq!(Some(&(lb, ub)), tree.bounds().last());
+    }
+
+    #[test]
+    fn the_latency_of_every_compilation_is_recorded() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10, 20, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(1);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_latency_watch(LatencyWatch { factor: 10, warmup: u64::MAX });
+        assert_eq!(0, solver.compilation_latencies().restricted.count());
+
+        solver.maximize();
+        let latencies = solver.compilation_latencies();
+        assert!(latencies.restricted.count() > 0);
+        assert!(latencies.relaxed.count() <= latencies.restricted.count());
+        assert_eq!(latencies.restricted.count(), latencies.restricted.buckets().iter().sum::<u64>());
+        assert!(solver.slow_compilations().is_empty());
+
+        solver.reset();
+        assert_eq!(0, solver.compilation_latencies().restricted.count());
+        assert_eq!(0, solver.compilation_latencies().relaxed.count());
+    }
+
+    #[test]
+    fn with_an_absolute_slack_the_best_value_is_within_that_slack_of_the_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let solve = |slack: isize| {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = SeqSolver::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+            ).with_absolute_slack(slack);
+            let completion = solver.maximize();
+            assert!(solver.best_upper_bound() >= completion.best_value.unwrap());
+            completion
+        };
+
+        let exact = solve(0);
+        assert!(exact.is_exact);
+        assert_eq!(None, exact.guaranteed_within);
+        let optimum = exact.best_value.unwrap();
+        assert_eq!(Some(optimum), solve(0).best_value);
+
+        for slack in [1, 5, 10, 25, 50, 100] {
+            let approx = solve(slack);
+            assert!(!approx.is_exact);
+            assert_eq!(Some(slack), approx.guaranteed_within);
+            assert!(approx.best_value.unwrap() >= optimum - slack);
+        }
+    }
+    #[test]
+    fn with_a_loose_gap_tolerance_the_search_stops_before_proving_optimality() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let solve = |tolerance: f64| {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = SeqSolver::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+            ).with_gap_tolerance(tolerance);
+            let completion = solver.maximize();
+            assert!(solver.best_upper_bound() >= completion.best_value.unwrap());
+            completion
+        };
+
+        let exact = solve(0.0);
+        assert!(exact.is_exact);
+        assert_eq!(None, exact.stopped_by);
+        assert_eq!(None, exact.achieved_gap);
+        let optimum = exact.best_value.unwrap();
+
+        let approx = solve(0.99);
+        assert!(!approx.is_exact);
+        assert_eq!(Some(Reason::GapReached), approx.stopped_by);
+        let gap = approx.achieved_gap.unwrap();
+        assert!(gap > 0.0 && gap < 0.99);
+        assert!(approx.best_value.unwrap() as f64 >= (1.0 - gap) * optimum as f64);
+    }
+    #[test]
+    fn with_a_target_the_search_stops_once_it_knows_whether_it_is_reachable() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let solve = |target: isize| {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = SeqSolver::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+            ).with_target(target);
+            let completion = solver.maximize();
+            (completion, solver.best_upper_bound(), solver.best_solution())
+        };
+
+        // the optimum is 380
+        let (reached, _, solution) = solve(300);
+        assert!(!reached.is_exact);
+        assert_eq!(Some(Reason::TargetReached), reached.stopped_by);
+        let value = reached.best_value.unwrap();
+        assert!(value >= 300);
+        let profit = solution.unwrap().iter()
+            .map(|d| problem.profit[d.variable.id()] as isize * d.value)
+            .sum::<isize>();
+        assert_eq!(value, profit);
+
+        let (unreachable, best_ub, _) = solve(400);
+        assert_eq!(Some(Reason::TargetUnreachable), unreachable.stopped_by);
+        assert!(best_ub < 400);
+        assert!(unreachable.best_value.unwrap() < 400);
+    }
+    #[test]
+    fn the_termination_tells_why_and_when_the_search_ended() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let solve = |cutoff: &dyn Cutoff, tolerance: f64, setup: fn(&mut dyn Solver)| {
+            let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+            let mut solver = SeqSolver::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                cutoff,
+                &mut fringe,
+            ).with_gap_tolerance(tolerance);
+            setup(&mut solver);
+            let completion = solver.maximize();
+            let termination = completion.termination.clone().unwrap();
+            assert_eq!(completion.stopped_by, termination.reason);
+            assert_eq!(solver.explored(), termination.explored);
+            termination
+        };
+
+        let complete = solve(&NoCutoff, 0.0, |_| {});
+        assert_eq!(None, complete.reason);
+        assert_eq!(0.0, complete.gap);
+        assert!(complete.explored > 1);
+
+        let nodes = solve(&NoCutoff, 0.0, |s| s.set_node_budget(Some(1)));
+        assert_eq!(Some(Reason::NodeLimit), nodes.reason);
+        assert!(nodes.explored <= 1);
+        assert!(nodes.gap > 0.0);
+
+        let time = solve(&NoCutoff, 0.0, |s| s.set_time_limit(Some(Duration::ZERO)));
+        assert_eq!(Some(Reason::Timeout), time.reason);
+
+        let gap = solve(&NoCutoff, 0.99, |_| {});
+        assert_eq!(Some(Reason::GapReached), gap.reason);
+        assert!(gap.gap < 0.99);
+
+        let cutoff = solve(&CutoffFn::new(|| true), 0.0, |_| {});
+        assert_eq!(Some(Reason::CutoffOccurred), cutoff.reason);
+        assert!(cutoff.to_string().starts_with("stopped by the cutoff after"));
+        assert!(complete.to_string().starts_with("completed after"));
+    }
+    #[test]
+    fn the_incumbent_listener_is_told_about_each_new_best_solution() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut found = vec![];
+        let mut listener = |value: isize, solution: &[Decision]| found.push((value, solution.to_vec()));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_incumbent_listener(&mut listener);
+
+        let Completion{best_value, ..} = solver.maximize();
+        let best_solution = solver.best_solution();
+        drop(solver);
+
+        assert!(!found.is_empty());
+        assert!(found.windows(2).all(|w| w[0].0 < w[1].0));
+        for (value, solution) in found.iter() {
+            assert_eq!(problem.nb_variables(), solution.len());
+            assert!(solution.windows(2).all(|w| w[0].variable < w[1].variable));
+            let profit = solution.iter().map(|d| problem.profit[d.variable.id()] as isize * d.value).sum::<isize>();
+            assert_eq!(*value, profit);
+        }
+        let (last_value, last_solution) = found.pop().unwrap();
+        assert_eq!(best_value, Some(last_value));
+        assert_eq!(best_solution, Some(last_solution));
+    }
+    #[test]
+    fn the_shared_incumbent_can_be_read_while_the_solver_runs() {
+        let problem = Knapsack {
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(1);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let incumbent = SharedIncumbent::default();
+        let done = AtomicBool::new(false);
+
+        let seen = std::thread::scope(|s| {
+            let reader = incumbent.clone();
+            let done = &done;
+            let handle = s.spawn(move || {
+                let mut seen: Vec<IncumbentSnapshot> = vec![];
+                while !done.load(AtomicOrdering::Acquire) {
+                    if let Some(snapshot) = reader.snapshot() {
+                        if seen.last().is_none_or(|last| last.0 != snapshot.0) {
+                            seen.push(snapshot);
+                        }
+                    }
+                    std::thread::yield_now();
+                }
+                seen
+            });
+
+            let mut solver = SeqSolver::custom(
+                &problem,
+                &relax,
+                &ranking,
+                &width,
+                &dominance,
+                &cutoff,
+                &mut fringe,
+            ).with_shared_incumbent(&incumbent);
+            let Completion{best_value, ..} = solver.maximize();
+            assert_eq!(Some(134), best_value);
+            done.store(true, AtomicOrdering::Release);
+            handle.join().unwrap()
+        });
+
+        assert!(seen.windows(2).all(|w| w[0].0 < w[1].0));
+        for snapshot in seen.iter() {
+            let (value, solution) = &**snapshot;
+            let profit = solution.iter().map(|d| problem.profit[d.variable.id()] as isize * d.value).sum::<isize>();
+            assert_eq!(*value, profit);
+        }
+        assert_eq!(Some(134), incumbent.value());
+    }
+    #[test]
+    fn the_live_bounds_can_be_polled_while_the_solver_runs() {
+        let problem = Knapsack {
+            capacity: 60,
+            profit  : vec![10, 24, 31, 12, 40, 22, 17, 29, 35, 8, 19, 27],
+            weight  : vec![ 6, 11, 14,  7, 19, 10,  9, 13, 16, 4,  8, 12],
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(1);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let done = AtomicBool::new(false);
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let bounds = solver.bounds_handle();
+        assert_eq!(f64::INFINITY, bounds.gap());
+
+        let gaps = std::thread::scope(|s| {
+            let reader = bounds.clone();
+            let done = &done;
+            let handle = s.spawn(move || {
+                let mut gaps = vec![];
+                while !done.load(AtomicOrdering::Acquire) {
+                    let gap = reader.gap();
+                    if gaps.last() != Some(&gap) {
+                        gaps.push(gap);
+                    }
+                    std::thread::yield_now();
+                }
+                gaps
+            });
+
+            let Completion{best_value, ..} = solver.maximize();
+            assert_eq!(Some(134), best_value);
+            done.store(true, AtomicOrdering::Release);
+            handle.join().unwrap()
+        });
+
+        assert!(gaps.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(134, bounds.best_lb());
+        assert_eq!(134, bounds.best_ub());
+        assert_eq!(0.0, bounds.gap());
+    }
+    #[test]
+    fn an_aborted_solver_returns_promptly_with_its_best_incumbent() {
+        /// A knapsack whose transitions are slow enough for the solver to be
+        /// aborted long before it is done
+        struct Sluggish<'a>(&'a Knapsack);
+        impl Problem for Sluggish<'_> {
+            type State = KnapsackState;
+            fn nb_variables(&self) -> usize {
+                self.0.nb_variables()
+            }
+            fn initial_state(&self) -> Self::State {
+                self.0.initial_state()
+            }
+            fn initial_value(&self) -> isize {
+                self.0.initial_value()
+            }
+            fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+                std::thread::sleep(Duration::from_millis(1));
+                self.0.transition(state, dec)
+            }
+            fn transition_cost(&self, state: &Self::State, next: &Self::State, dec: Decision) -> isize {
+                self.0.transition_cost(state, next, dec)
+            }
+            fn next_variable(&self, depth: usize, next_layer: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+                self.0.next_variable(depth, next_layer)
+            }
+            fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+                self.0.for_each_in_domain(variable, state, f)
+            }
+        }
+
+        let knapsack = Knapsack {
+            capacity: 200,
+            profit  : (0..40).map(|i| 10 + (i * 7) % 23).collect(),
+            weight  : (0..40).map(|i|  5 + (i * 11) % 17).collect(),
+        };
+        let problem = Sluggish(&knapsack);
+        let relax = KPRelax {pb: &knapsack};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(10);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let abort = solver.abort_handle();
+
+        let (completion, elapsed) = std::thread::scope(|s| {
+            s.spawn(move || {
+                std::thread::sleep(Duration::from_millis(10));
+                abort.abort();
+            });
+            let start = Instant::now();
+            let completion = solver.maximize();
+            (completion, start.elapsed())
+        });
+
+        assert!(!completion.is_exact);
+        assert_eq!(Some(Reason::Aborted), completion.stopped_by);
+        assert!(elapsed < Duration::from_secs(1));
+        assert_eq!(completion.best_value, solver.best_value());
+        
+        // the abort is forgotten once the solver is reset
+        solver.reset();
+        assert!(!solver.abort_handle().is_aborted());
+    }
+    #[test]
+    fn the_progress_of_the_search_is_reported_to_the_listener() {
+        struct Record(Vec<ProgressEvent>);
+        impl Progress for Record {
+            fn report(&mut self, event: &ProgressEvent) {
+                self.0.push(*event);
+            }
+        }
+
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut record = Record(vec![]);
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_progress(&mut record);
+
+        let Completion{best_value, ..} = solver.maximize();
+        let explored = solver.explored();
+        drop(solver);
+
+        let events = record.0;
+        assert_eq!(Some(&ProgressEvent::Finished { value: best_value, explored }), events.last());
+        let nodes = events.iter().filter_map(|e| match e {
+            ProgressEvent::NodeExplored { explored, lb, ub, .. } => Some((*explored, *lb, *ub)),
+            _ => None,
+        }).collect::<Vec<_>>();
+        assert_eq!(explored, nodes.len());
+        assert!(nodes.iter().enumerate().all(|(i, (explored, lb, ub))| *explored == i + 1 && lb <= ub));
+        let incumbents = events.iter().filter_map(|e| match e {
+            ProgressEvent::NewIncumbent { value } => Some(*value),
+            _ => None,
+        }).collect::<Vec<_>>();
+        assert!(incumbents.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(best_value, incumbents.last().copied());
+    }
+    #[test]
+    fn the_best_state_is_reached_by_replaying_the_best_solution() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        assert_eq!(None, solver.best_state());
+
+        solver.maximize();
+        let replayed = solver.best_solution().unwrap().iter()
+            .fold(problem.initial_state(), |state, decision| problem.transition(&state, *decision));
+        assert_eq!(Some(replayed), solver.best_state().copied());
+    }
+    #[test]
+    fn the_best_state_is_forgotten_when_a_primal_is_set() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10, 20, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        solver.maximize();
+        assert!(solver.best_state().is_some());
+
+        solver.set_primal(1000, vec![]);
+        assert_eq!(None, solver.best_state());
+    }
+
+    #[test]
+    fn diverse_solutions_are_pairwise_distant() {
+        // the profit of each item equals its weight: many subsets nearly fill the knapsack
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19],
+            weight  : vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqCachingSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_diversity(Diversity { k: 5, d: 4 });
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert_eq!(Some(50), outcome.best_value);
+
+        let pool = solver.diverse_solutions();
+        assert!(pool.len() > 1 && pool.len() <= 5);
+        assert_eq!(outcome.best_value, Some(pool[0].value));
+        for s in pool.iter() {
+            let chosen = s.solution.iter().filter(|d| d.value == 1).map(|d| d.variable.id());
+            assert_eq!(s.value, chosen.map(|i| problem.profit[i] as isize).sum::<isize>());
+        }
+        for (i, a) in pool.iter().enumerate() {
+            for b in pool[i+1..].iter() {
+                let distance = a.solution.iter().zip(b.solution.iter()).filter(|(x, y)| x != y).count();
+                assert!(distance >= 4);
+            }
+        }
+    }
+
+    #[test]
+    fn the_solution_pool_keeps_the_k_best_distinct_solutions() {
+        // the weights are powers of two: no two subsets of items lead to the
+        // same state, hence no solution is merged away by the dds
+        let problem = Knapsack {
+            capacity: 150,
+            profit  : vec![3, 5, 9, 13, 20, 31, 50, 77],
+            weight  : vec![1, 2, 4,  8, 16, 32, 64, 128]
+        };
+        let mut expected = (0..1_usize << problem.nb_variables())
+            .filter(|subset| (0..8).filter(|i| subset & (1 << i) != 0).map(|i| problem.weight[i]).sum::<usize>() <= problem.capacity)
+            .map(|subset| (0..8).filter(|i| subset & (1 << i) != 0).map(|i| problem.profit[i] as isize).sum::<isize>())
+            .collect::<Vec<_>>();
+        expected.sort_unstable_by(|a, b| b.cmp(a));
+        expected.truncate(5);
+
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_solution_pool(5);
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert_eq!(Some(expected[0]), outcome.best_value);
+
+        let pool = solver.best_solutions();
+        assert_eq!(expected, pool.iter().map(|(value, _)| *value).collect::<Vec<_>>());
+        for (value, solution) in pool.iter() {
+            let chosen = solution.iter().filter(|d| d.value == TAKE_IT).map(|d| d.variable.id());
+            assert_eq!(*value, chosen.map(|i| problem.profit[i] as isize).sum::<isize>());
+        }
+        for (i, (_, a)) in pool.iter().enumerate() {
+            assert!(pool[i+1..].iter().all(|(_, b)| a != b));
+        }
+    }
+    #[test]
+    fn the_solution_pool_does_not_change_the_optimum() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = solver.maximize();
+        assert!(solver.best_solutions().is_empty());
+
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_solution_pool(3);
+        let actual = solver.maximize();
+        assert!(actual.is_exact);
+        assert_eq!(expected.best_value, actual.best_value);
+        assert_eq!(3, solver.best_solutions().len());
+        assert_eq!(actual.best_value, Some(solver.best_solutions()[0].0));
+
+        solver.reset();
+        assert!(solver.best_solutions().is_empty());
+    }
+
+    #[test]
+    fn the_fringe_is_sampled_every_so_many_explored_nodes() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let sampling = FringeSampling { every: 2, max_snapshots: 3, ..Default::default() };
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_fringe_sampling(sampling);
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert!(solver.explored() >= 4);
+
+        let snapshots = solver.fringe_snapshots();
+        assert!(!snapshots.is_empty() && snapshots.len() <= 3);
+        assert!(snapshots.windows(2).all(|w| w[0].explored < w[1].explored));
+        for snapshot in snapshots.iter() {
+            assert_eq!(snapshot.nb_nodes, snapshot.depth_histogram.iter().sum::<usize>());
+            assert!(snapshot.ub_range.is_none_or(|(_, max)| max <= snapshot.best_ub));
+        }
+
+        solver.reset();
+        assert!(solver.fringe_snapshots().is_empty());
+    }
+
+    #[test]
+    fn duplicate_solutions_are_suppressed_before_they_reach_the_pool() {
+        let problem = Knapsack {
+            capacity: 50,
+            profit  : vec![60, 100, 120],
+            weight  : vec![10,  20,  30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = NbUnassignedWidth(problem.nb_variables());
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqCachingSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_diversity(Diversity { k: 5, d: 0 })
+         .with_solution_dedup(16);
+        let take = |v: usize, value: isize| Decision { variable: Variable(v), value };
+        let solution = vec![take(1, 1), take(2, 1)];
+        let shuffled = vec![take(2, 1), take(1, 1)];
+
+        solver.set_primal(220, solution.clone());
+        solver.set_primal(220, shuffled);
+        solver.set_primal(220, solution);
+        assert_eq!(1, solver.diverse_solutions().len());
+        assert_eq!(2, solver.suppressed_duplicates());
+    }
+
+    #[test]
+    fn a_restart_escapes_an_adversarial_ordering() {
+        let stubborn = Pairs::new(10);
+        let (outcome, explored_stubborn, records) = solve_pairs(&stubborn, None);
+        assert!(outcome.is_exact);
+        assert_eq!(Some(10), outcome.best_value);
+        assert!(records.is_empty());
+
+        let policy = Restarts { schedule: RestartSchedule::Fixed { nodes: 5 }, ..Restarts::default() };
+        let restarted = Pairs::new(10);
+        let (outcome, explored_restarted, records) = solve_pairs(&restarted, Some(policy));
+        assert!(outcome.is_exact);
+        assert_eq!(Some(10), outcome.best_value);
+        assert_eq!(1, records.len());
+        assert_eq!(5, records[0].explored);
+        assert!(explored_restarted < explored_stubborn);
+    }
+    #[test]
+    fn restarts_are_deterministic() {
+        let policy = Restarts { 
+            schedule: RestartSchedule::Luby { unit: 3 }, 
+            seed: 7, 
+            max_restarts: Some(4), 
+            keep_cache: false 
+        };
+        let first  = Pairs::always_adversarial(8);
+        let second = Pairs::always_adversarial(8);
+        let (outcome_1, explored_1, records_1) = solve_pairs(&first, Some(policy));
+        let (outcome_2, explored_2, records_2) = solve_pairs(&second, Some(policy));
+
+        assert_eq!(Some(8), outcome_1.best_value);
+        assert_eq!(outcome_1.best_value, outcome_2.best_value);
+        assert_eq!(explored_1, explored_2);
+        assert_eq!(4, records_1.len());
+        // only the time spent during each run may differ
+        let untimed = |records: Vec<RestartRecord>| records.into_iter()
+            .map(|r| RestartRecord { elapsed: Duration::ZERO, ..r })
+            .collect::<Vec<_>>();
+        assert_eq!(untimed(records_1), untimed(records_2));
+        assert_eq!(*first.seeds.lock().unwrap(), *second.seeds.lock().unwrap());
+        assert_eq!(5, first.seeds.lock().unwrap().len());
+    }
+    #[test]
+    fn the_presolve_seeds_the_search_with_its_best_solution() {
+        let pairs = Pairs::new(10);
+        let relax = PairsRelax;
+        let ranking = PairsRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let config = PresolveConfig { iterations: 5, width_schedule: vec![2], seed: 3 };
+        let mut solver = SeqCachingSolver::new(
+            &pairs,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_reseed(&pairs).with_presolve(config.clone());
+        assert_eq!(None, solver.presolve_record());
+
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert_eq!(Some(10), outcome.best_value);
+        // the second iteration no longer uses the adversarial ordering: its 
+        // restricted DD is exact, hence the presolve stops there
+        let record = solver.presolve_record().unwrap();
+        assert_eq!(2, record.iterations);
+        assert_eq!(isize::MIN, record.lb_before);
+        assert_eq!(10, record.lb_after);
+        assert_eq!(None, record.improvement());
+        assert_eq!(vec![config.seed(0), config.seed(1)], *pairs.seeds.lock().unwrap());
+
+        solver.reset();
+        assert_eq!(None, solver.presolve_record());
+    }
+    #[test]
+    fn the_presolve_tells_how_much_it_improved_a_known_bound() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqNoCachingSolverLel::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_presolve(PresolveConfig { iterations: 3, width_schedule: vec![1000], seed: 0 });
+        solver.set_primal_bound(100);
+
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert_eq!(Some(380), outcome.best_value);
+        // the first restricted DD is wide enough to be exact
+        let record = solver.presolve_record().unwrap();
+        assert_eq!(1, record.iterations);
+        assert_eq!(1, record.improving);
+        assert_eq!(100, record.lb_before);
+        assert_eq!(380, record.lb_after);
+        assert_eq!(Some(280), record.improvement());
+    }
+    #[test]
+    fn the_runs_add_up_to_the_whole_search() {
+        let pairs = Pairs::always_adversarial(8);
+        let relax = PairsRelax;
+        let ranking = PairsRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let policy = Restarts { 
+            schedule: RestartSchedule::Fixed { nodes: 2 }, 
+            max_restarts: Some(3), 
+            ..Restarts::default() 
+        };
+        let mut solver = SeqCachingSolver::new(
+            &pairs,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_reseed(&pairs).with_restarts(policy);
+        let start = Instant::now();
+        let outcome = solver.maximize();
+        let total = start.elapsed();
+
+        let runs = solver.runs();
+        assert_eq!(Some(8), outcome.best_value);
+        assert_eq!(solver.restarts().len() + 1, runs.len());
+        assert_eq!(solver.restarts(), &runs[..runs.len() - 1]);
+        assert_eq!(solver.explored(), runs.iter().map(|r| r.explored).sum::<usize>());
+        assert!(runs.iter().map(|r| r.elapsed).sum::<Duration>() <= total);
+        let last = runs.last().unwrap();
+        assert_eq!((solver.best_lower_bound(), solver.best_upper_bound()), (last.best_lb, last.best_ub));
+    }
+    #[test]
+    fn restarts_may_keep_the_cache() {
+        let policy = Restarts { 
+            schedule: RestartSchedule::Fixed { nodes: 2 }, 
+            max_restarts: Some(3), 
+            keep_cache: true,
+            ..Restarts::default() 
+        };
+        let pairs = Pairs::always_adversarial(8);
+        let (outcome, _, records) = solve_pairs(&pairs, Some(policy));
+        assert!(outcome.is_exact);
+        assert_eq!(Some(8), outcome.best_value);
+        assert!(!records.is_empty());
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    struct KnapsackState {
+        depth: usize,
+        capacity: usize
     }
     struct Knapsack {
         capacity: usize,
@@ -1089,4 +4611,343 @@ mod test_solver {
             a.capacity.cmp(&b.capacity)
         }
     }
+
+    /// This problem comprises k pairs of variables (x_i, x_{k+i}) which must
+    /// take the same value. Taking both items of a pair yields a profit of 1.
+    /// When the first item of each pair is decided before any second item (the
+    /// adversarial ordering), the exact DD grows exponentially wide and the 
+    /// relaxation is very weak. Whereas when both items of each pair are decided
+    /// in a row, the exact DD is never wider than 2.
+    struct Pairs {
+        k: usize,
+        /// When this is false, only the first run uses the adversarial ordering
+        always_adversarial: bool,
+        adversarial: AtomicBool,
+        seeds: Mutex<Vec<u64>>,
+    }
+    impl Pairs {
+        fn new(k: usize) -> Self {
+            Pairs { k, always_adversarial: false, adversarial: AtomicBool::new(true), seeds: Mutex::new(vec![]) }
+        }
+        fn always_adversarial(k: usize) -> Self {
+            Pairs { always_adversarial: true, ..Self::new(k) }
+        }
+        fn partner(&self, var: usize) -> usize {
+            if var < self.k { var + self.k } else { var - self.k }
+        }
+    }
+    impl Reseed for Pairs {
+        fn reseed(&self, seed: u64) {
+            let mut seeds = self.seeds.lock().unwrap();
+            self.adversarial.store(self.always_adversarial || seeds.is_empty(), AtomicOrdering::SeqCst);
+            seeds.push(seed);
+        }
+    }
+
+    const UNSET: i8 = -1;
+    const ANY  : i8 =  2;
+    const DONE : i8 =  3;
+
+    impl Problem for Pairs {
+        type State = Vec<i8>;
+        fn nb_variables(&self) -> usize {
+            2 * self.k
+        }
+        fn initial_state(&self) -> Self::State {
+            vec![UNSET; 2 * self.k]
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut ret = state.clone();
+            let var = dec.variable.id();
+            let partner = self.partner(var);
+            if state[partner] == UNSET {
+                ret[var] = dec.value as i8;
+            } else {
+                ret[var] = DONE;
+                ret[partner] = DONE;
+            }
+            ret
+        }
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            let var = dec.variable.id();
+            match (dec.value, var < self.k) {
+                (0, _)     => 0,
+                (_, true)  => 2 + var as isize,
+                (_, false) => -(1 + (var - self.k) as isize),
+            }
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            if depth >= self.nb_variables() {
+                None
+            } else if self.adversarial.load(AtomicOrdering::SeqCst) {
+                Some(Variable(depth))
+            } else if depth.is_multiple_of(2) {
+                Some(Variable(depth / 2))
+            } else {
+                Some(Variable(self.k + depth / 2))
+            }
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            match state[self.partner(variable.id())] {
+                0 => f.apply(Decision { variable, value: 0 }),
+                1 => f.apply(Decision { variable, value: 1 }),
+                _ => {
+                    f.apply(Decision { variable, value: 1 });
+                    f.apply(Decision { variable, value: 0 });
+                }
+            }
+        }
+    }
+    struct PairsRelax;
+    impl Relaxation for PairsRelax {
+        type State = Vec<i8>;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            let mut merged = states.next().unwrap().clone();
+            for s in states {
+                merged.iter_mut().zip(s.iter())
+                    .filter(|(m, x)| *m != *x)
+                    .for_each(|(m, _)| *m = ANY);
+            }
+            merged
+        }
+        fn relax(&self, _source: &Self::State, _dest: &Self::State, _merged: &Self::State, _decision: Decision, cost: isize) -> isize {
+            cost
+        }
+    }
+    struct PairsRanking;
+    impl StateRanking for PairsRanking {
+        type State = Vec<i8>;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.cmp(b)
+        }
+    }
+
+    /// This problem comprises `PROFIT.len()` prefix variables whose values 
+    /// determine the objective, followed by `TAIL` binary variables which only
+    /// matter for feasibility: the sum of all values must be a multiple of 5.
+    /// The tail states only differ from one another by their total, hence
+    /// the total modulo 5 is a valid feasibility class.
+    struct Prefix {
+        hinted: bool,
+    }
+    const PROFIT: [[isize; 3]; 4] = [[0, 3, 5], [0, 4, 1], [0, 2, 6], [0, 5, 3]];
+    const TAIL  : usize = 3;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct PrefixState {
+        picks: Vec<isize>,
+        /// None when the total is unknown (merged states)
+        total: Option<isize>,
+    }
+    impl Problem for Prefix {
+        type State = PrefixState;
+        fn nb_variables(&self) -> usize {
+            PROFIT.len() + TAIL
+        }
+        fn initial_state(&self) -> Self::State {
+            PrefixState { picks: vec![], total: Some(0) }
+        }
+        fn initial_value(&self) -> isize {
+            0
+        }
+        fn transition(&self, state: &Self::State, dec: Decision) -> Self::State {
+            let mut picks = state.picks.clone();
+            picks.push(dec.value);
+            PrefixState { picks, total: state.total.map(|t| t + dec.value) }
+        }
+        fn transition_cost(&self, _: &Self::State, _: &Self::State, dec: Decision) -> isize {
+            PROFIT.get(dec.variable.id()).map(|p| p[dec.value as usize]).unwrap_or(0)
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Self::State>) -> Option<Variable> {
+            if depth < self.nb_variables() {
+                Some(Variable(depth))
+            } else {
+                None
+            }
+        }
+        fn for_each_in_domain(&self, variable: Variable, state: &Self::State, f: &mut dyn DecisionCallback) {
+            let values = if variable.id() < PROFIT.len() { 0..=2 } else { 0..=1 };
+            let last = variable.id() + 1 == self.nb_variables();
+            for value in values {
+                if !last || state.total.is_none_or(|t| (t + value) % 5 == 0) {
+                    f.apply(Decision { variable, value });
+                }
+            }
+        }
+        fn objective_depth(&self) -> Option<usize> {
+            self.hinted.then_some(PROFIT.len())
+        }
+        fn feasibility_class(&self, state: &Self::State) -> Option<u64> {
+            state.total.map(|t| (t % 5) as u64)
+        }
+    }
+    struct PrefixRelax;
+    impl Relaxation for PrefixRelax {
+        type State = PrefixState;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &Self::State>) -> Self::State {
+            let picks = states.next().unwrap().picks.clone();
+            PrefixState { picks, total: None }
+        }
+        fn relax(&self, _source: &Self::State, _dest: &Self::State, _merged: &Self::State, _decision: Decision, cost: isize) -> isize {
+            cost
+        }
+    }
+    struct PrefixRanking;
+    impl StateRanking for PrefixRanking {
+        type State = PrefixState;
+
+        fn compare(&self, a: &Self::State, b: &Self::State) -> std::cmp::Ordering {
+            a.total.cmp(&b.total).then_with(|| a.picks.cmp(&b.picks))
+        }
+    }
+    fn solve_prefix(problem: &Prefix, width: usize) -> (Completion, usize) {
+        let relax = PrefixRelax;
+        let ranking = PrefixRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(width);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqCachingSolver::new(
+            problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let outcome = solver.maximize();
+        (outcome, solver.explored())
+    }
+
+    #[test]
+    fn objective_depth_hint_preserves_the_optimum() {
+        // brute force: any prefix whose total leaves a residue the tail can fix
+        let mut expected = isize::MIN;
+        for code in 0..3_usize.pow(PROFIT.len() as u32) {
+            let picks = (0..PROFIT.len()).map(|i| (code / 3_usize.pow(i as u32)) % 3);
+            let (total, value) = picks.enumerate()
+                .fold((0, 0), |(t, v), (i, x)| (t + x, v + PROFIT[i][x]));
+            if (5 - total % 5) % 5 <= TAIL {
+                expected = expected.max(value);
+            }
+        }
+
+        for width in 1..=8 {
+            let (plain,  explored_plain)  = solve_prefix(&Prefix { hinted: false }, width);
+            let (hinted, explored_hinted) = solve_prefix(&Prefix { hinted: true }, width);
+            assert!(plain.is_exact);
+            assert!(hinted.is_exact);
+            assert_eq!(Some(expected), plain.best_value);
+            assert_eq!(Some(expected), hinted.best_value);
+            assert!(explored_hinted <= explored_plain);
+        }
+    }
+
+    /// Each variable adds its value (0 or 1) to the count of ones. The nodes
+    /// having the fewest ones are ranked first.
+    struct Ones;
+    impl Problem for Ones {
+        type State = usize;
+
+        fn nb_variables(&self) -> usize { 3 }
+        fn initial_state(&self) -> usize { 0 }
+        fn initial_value(&self) -> isize { 0 }
+        fn transition(&self, state: &usize, d: Decision) -> usize { state + d.value as usize }
+        // the last one pays off much more when exactly one variable was set
+        fn transition_cost(&self, state: &usize, _: &usize, d: Decision) -> isize { 
+            if d.variable.id() == 2 && *state == 1 { 10 * d.value } else { d.value }
+        }
+        fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &usize>) -> Option<Variable> {
+            Some(Variable(depth)).filter(|_| depth < 3)
+        }
+        fn for_each_in_domain(&self, variable: Variable, _: &usize, f: &mut dyn DecisionCallback) {
+            f.apply(Decision { variable, value: 0 });
+            f.apply(Decision { variable, value: 1 });
+        }
+    }
+    struct FewestOnes;
+    impl StateRanking for FewestOnes {
+        type State = usize;
+
+        fn compare(&self, a: &usize, b: &usize) -> std::cmp::Ordering {
+            b.cmp(a)
+        }
+    }
+    /// This is not a relaxation: the arcs towards merged nodes lose value
+    struct BrokenRelax;
+    impl Relaxation for BrokenRelax {
+        type State = usize;
+
+        fn merge(&self, states: &mut dyn Iterator<Item = &usize>) -> usize {
+            states.copied().max().unwrap()
+        }
+        fn relax(&self, _: &usize, _: &usize, _: &usize, _: Decision, cost: isize) -> isize {
+            cost - 1000
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "verification failed on node #1: the restricted DD is better than the relaxed DD")]
+    fn verification_reports_a_broken_relaxation() {
+        let ranking = FewestOnes;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &Ones,
+            &BrokenRelax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_verify();
+        solver.maximize();
+    }
+    #[test]
+    fn verification_accepts_a_correct_model() {
+        let problem = Knapsack {
+            capacity: 100,
+            profit  : vec![60, 100, 120, 30, 45, 80, 70, 25, 90, 55],
+            weight  : vec![10,  20,  30, 15, 25, 35, 20, 10, 40, 30]
+        };
+        let relax = KPRelax {pb: &problem};
+        let ranking = KPRanking;
+        let cutoff = NoCutoff;
+        let width = FixedWidth(2);
+        let dominance = EmptyDominanceChecker::default();
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        );
+        let expected = solver.maximize().best_value;
+
+        let mut fringe = SimpleFringe::new(MaxUB::new(&ranking));
+        let mut solver = SeqSolver::custom(
+            &problem,
+            &relax,
+            &ranking,
+            &width,
+            &dominance,
+            &cutoff,
+            &mut fringe,
+        ).with_verify();
+        let outcome = solver.maximize();
+        assert!(outcome.is_exact);
+        assert_eq!(expected, outcome.best_value);
+    }
 }
@@ -0,0 +1,142 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the *solution pool* which can optionally be used by
+//! the sequential solver. Besides the best solution, the solver then keeps
+//! the `k` best distinct solutions it comes across. To that end, it only 
+//! prunes the subproblems which cannot improve on the `k`-th best solution
+//! of the pool.
+//!
+//! # Note
+//! The pool is fed with the exact solutions of each decision diagram that is
+//! compiled during the search (restricted and relaxed alike). A decision 
+//! diagram only keeps the best path to each of its nodes, though: a solution
+//! whose prefix leads to the same state as a better prefix is never seen by
+//! the pool. The same goes for the paths which are discarded by a cache or a
+//! dominance checker.
+
+use crate::Solution;
+
+/// The pool of (at most `k`) best distinct solutions. The solutions are kept
+/// by decreasing value and no two of them make the same decisions.
+#[derive(Debug, Clone)]
+pub(crate) struct SolutionPool {
+    /// The maximum number of solutions in the pool
+    pub(crate) k: usize,
+    /// The solutions of the pool by decreasing value. The decisions of each 
+    /// solution are sorted by variable.
+    solutions: Vec<(isize, Solution)>,
+}
+impl SolutionPool {
+    pub(crate) fn new(k: usize) -> Self {
+        Self { k, solutions: vec![] }
+    }
+    /// Returns the solutions of the pool, by decreasing value
+    pub(crate) fn solutions(&self) -> &[(isize, Solution)] {
+        &self.solutions
+    }
+    /// Returns the value a subproblem must improve on for one of its solutions
+    /// to enter the pool: the value of the `k`-th best solution once the pool
+    /// is full, and -inf otherwise.
+    pub(crate) fn threshold(&self) -> isize {
+        if self.k > 0 && self.solutions.len() >= self.k {
+            self.solutions[self.k - 1].0
+        } else if self.k == 0 {
+            isize::MAX
+        } else {
+            isize::MIN
+        }
+    }
+    /// Offers a candidate solution to the pool. It is added to the pool when it
+    /// is not already part of the pool and it is better than the `k`-th best
+    /// solution (if any). Among the solutions having the same value, the ones 
+    /// which entered the pool first come first.
+    ///
+    /// This method returns true iff the candidate was added to the pool.
+    pub(crate) fn offer(&mut self, value: isize, mut solution: Solution) -> bool {
+        if value <= self.threshold() && self.solutions.len() >= self.k {
+            return false;
+        }
+        solution.sort_unstable_by_key(|d| d.variable.id());
+        if self.solutions.iter().any(|(_, s)| *s == solution) {
+            return false;
+        }
+        let at = self.solutions.partition_point(|(v, _)| *v >= value);
+        self.solutions.insert(at, (value, solution));
+        self.solutions.truncate(self.k);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test_solution_pool {
+    use crate::{Decision, Variable, Solution};
+    use super::*;
+
+    fn sol(values: &[isize]) -> Solution {
+        values.iter().enumerate().map(|(i, v)| Decision { variable: Variable(i), value: *v }).collect()
+    }
+    fn values(pool: &SolutionPool) -> Vec<isize> {
+        pool.solutions().iter().map(|(v, _)| *v).collect()
+    }
+
+    #[test]
+    fn the_solutions_are_kept_by_decreasing_value() {
+        let mut pool = SolutionPool::new(3);
+        assert!(pool.offer(3, sol(&[0, 0])));
+        assert!(pool.offer(5, sol(&[0, 1])));
+        assert!(pool.offer(4, sol(&[1, 0])));
+        assert_eq!(vec![5, 4, 3], values(&pool));
+    }
+    #[test]
+    fn a_full_pool_only_accepts_candidates_better_than_its_worst_solution() {
+        let mut pool = SolutionPool::new(2);
+        assert!(pool.offer(3, sol(&[0, 0])));
+        assert!(pool.offer(5, sol(&[0, 1])));
+        assert!(!pool.offer(3, sol(&[1, 0])));
+        assert!(pool.offer(4, sol(&[1, 0])));
+        assert_eq!(vec![5, 4], values(&pool));
+    }
+    #[test]
+    fn the_duplicates_are_rejected_whatever_the_order_of_their_decisions() {
+        let mut pool = SolutionPool::new(3);
+        let mut reversed = sol(&[1, 2, 3]);
+        reversed.reverse();
+        assert!(pool.offer(5, sol(&[1, 2, 3])));
+        assert!(!pool.offer(5, reversed));
+        assert_eq!(vec![(5, sol(&[1, 2, 3]))], pool.solutions());
+    }
+    #[test]
+    fn the_threshold_is_the_value_of_the_kth_best_solution() {
+        let mut pool = SolutionPool::new(2);
+        assert_eq!(isize::MIN, pool.threshold());
+        pool.offer(5, sol(&[0, 1]));
+        assert_eq!(isize::MIN, pool.threshold());
+        pool.offer(3, sol(&[0, 0]));
+        assert_eq!(3, pool.threshold());
+        pool.offer(4, sol(&[1, 0]));
+        assert_eq!(4, pool.threshold());
+    }
+    #[test]
+    fn an_empty_pool_accepts_nothing() {
+        let mut pool = SolutionPool::new(0);
+        assert!(!pool.offer(5, sol(&[0, 0, 0])));
+        assert!(pool.solutions().is_empty());
+    }
+}
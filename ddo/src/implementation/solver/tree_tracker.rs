@@ -0,0 +1,116 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the bookkeeping which lets a solver report the search
+//! tree it explores to a `TreeExport`. The subproblems carry no identifier: 
+//! the nodes which are in the fringe are hence recognized by their path.
+
+use fxhash::FxHashMap;
+
+use crate::{Decision, PruneReason, SubProblem, TreeEvent, TreeExport};
+
+/// Assigns ids to the nodes of the search tree and reports their events to 
+/// the export
+pub(crate) struct TreeTracker<'a> {
+    /// Where the events are reported
+    sink: &'a mut dyn TreeExport,
+    /// The id of the nodes which have been created and not processed yet
+    open: FxHashMap<Vec<Decision>, usize>,
+    /// The id of the next node to be created
+    next: usize,
+    /// The id of the node being processed (the parent of the nodes created
+    /// from the cut-set of its relaxed DD)
+    current: Option<usize>,
+    /// The last bounds which were reported
+    bounds: Option<(isize, isize)>,
+}
+
+impl<'a> TreeTracker<'a> {
+    pub fn new(sink: &'a mut dyn TreeExport) -> Self {
+        Self { sink, open: FxHashMap::default(), next: 0, current: None, bounds: None }
+    }
+    /// Reports the creation of a child of the node being processed (or of a 
+    /// root when no node is being processed). When it is not `open`, the node
+    /// is pruned right away because of its bound.
+    pub fn created<T>(&mut self, node: &SubProblem<T>, open: bool) {
+        let id = self.announce(node);
+        if open {
+            self.open.insert(node.path.clone(), id);
+        } else {
+            self.sink.export(&TreeEvent::Pruned { node: id, reason: PruneReason::Bound });
+        }
+    }
+    /// Makes the given node (which was just popped off the fringe) the node 
+    /// being processed
+    pub fn popped<T>(&mut self, node: &SubProblem<T>) {
+        let id = match self.open.remove(&node.path) {
+            Some(id) => id,
+            None => {
+                self.current = None;
+                self.announce(node)
+            }
+        };
+        self.current = Some(id);
+    }
+    /// Reports that the node being processed was not explored
+    pub fn pruned(&mut self, reason: PruneReason) {
+        if let Some(node) = self.current.take() {
+            self.sink.export(&TreeEvent::Pruned { node, reason });
+        }
+    }
+    /// Reports that the node being processed was closed by an exact DD
+    pub fn fathomed(&mut self, value: Option<isize>) {
+        if let Some(node) = self.current.take() {
+            self.sink.export(&TreeEvent::Fathomed { node, value });
+        }
+    }
+    /// Reports the given bounds unless they were the last ones reported
+    pub fn bounds(&mut self, lb: isize, ub: isize) {
+        if self.bounds != Some((lb, ub)) {
+            self.bounds = Some((lb, ub));
+            self.sink.export(&TreeEvent::Bounds { lb, ub });
+        }
+    }
+    /// Reports all the nodes which are still open as dropped (the fringe no 
+    /// longer holds them)
+    pub fn drop_open(&mut self) {
+        let mut dropped = self.open.drain().map(|(_, id)| id).collect::<Vec<_>>();
+        dropped.sort_unstable();
+        for node in dropped {
+            self.sink.export(&TreeEvent::Pruned { node, reason: PruneReason::Dropped });
+        }
+        self.current = None;
+    }
+    /// Forgets about the previous resolution. The ids keep growing so that 
+    /// the nodes of distinct resolutions are never mistaken for one another.
+    pub fn clear(&mut self) {
+        self.drop_open();
+        self.bounds = None;
+    }
+    /// Reports the creation of the given node and returns its id
+    fn announce<T>(&mut self, node: &SubProblem<T>) -> usize {
+        let id = self.next;
+        self.next += 1;
+        self.sink.export(&TreeEvent::Created { 
+            node: id, parent: self.current, depth: node.depth, value: node.value, ub: node.ub 
+        });
+        id
+    }
+}
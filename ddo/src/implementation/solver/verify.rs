@@ -0,0 +1,214 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+
+//! This module provides the *verification* mode of the sequential solver 
+//! (see `with_verify`). It is meant to debug new models, and it is heavy: 
+//! each processed node is cross-checked as follows.
+//!
+//! 1. The value of the restricted DD may not exceed the one of the relaxed DD
+//!    (which, for that purpose, is compiled without pruning on the best lower
+//!    bound).
+//! 2. The values of the restricted and of the relaxed DD may not exceed the
+//!    upper bound the node was given when it was enqueued.
+//! 3. Whenever a DD is exact, its value must be achievable: its best solution
+//!    is replayed through the problem (see `evaluate`) and must yield the 
+//!    very same value.
+//!
+//! The search fails fast: it panics with a detailed report upon the first
+//! violation.
+
+use std::fmt;
+
+use crate::{evaluate, Decision, EvalError, Problem, Solution};
+
+/// The check which failed on a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Violation {
+    /// The restricted DD is better than the relaxed DD
+    RestrictedAboveRelaxed,
+    /// The DD is better than the upper bound of the node
+    AboveUpperBound { value: isize },
+    /// The value of an exact DD is not the one of its best solution
+    NotAchievable { value: isize, replayed: Result<isize, String> },
+}
+
+/// Everything there is to know about a node which failed the verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VerifyReport {
+    /// The rank of the node in the exploration order (the first node is #1)
+    pub node: usize,
+    /// The decisions leading to the node
+    pub path: Vec<Decision>,
+    /// The upper bound the node was given when it was enqueued
+    pub ub: isize,
+    /// The value of the restricted DD (if any)
+    pub restricted: Option<isize>,
+    /// The value of the relaxed DD (if any)
+    pub relaxed: Option<isize>,
+    /// The check which failed
+    pub violation: Violation,
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |v: Option<isize>| v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+        let path = self.path.iter()
+            .map(|d| format!("x{} = {}", d.variable.id(), d.value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "verification failed on node #{}: ", self.node)?;
+        match &self.violation {
+            Violation::RestrictedAboveRelaxed => 
+                writeln!(f, "the restricted DD is better than the relaxed DD")?,
+            Violation::AboveUpperBound { value } => 
+                writeln!(f, "the DD value {value} exceeds the upper bound of the node")?,
+            Violation::NotAchievable { value, replayed: Ok(replayed) } => 
+                writeln!(f, "the exact DD value {value} is not achievable (its best solution is worth {replayed})")?,
+            Violation::NotAchievable { value, replayed: Err(why) } => 
+                writeln!(f, "the exact DD value {value} is not achievable ({why})")?,
+        }
+        writeln!(f, "  path       : [{path}]")?;
+        writeln!(f, "  upper bound: {}", self.ub)?;
+        writeln!(f, "  restricted : {}", value(self.restricted))?;
+        write!(f,   "  relaxed    : {}", value(self.relaxed))
+    }
+}
+
+/// The verification of one node
+pub(crate) struct NodeVerifier {
+    report: VerifyReport,
+}
+impl NodeVerifier {
+    /// Starts the verification of the node with the given rank, path and ub
+    pub fn new(node: usize, path: &[Decision], ub: isize) -> Self {
+        Self {
+            report: VerifyReport {
+                node,
+                path: path.to_vec(),
+                ub,
+                restricted: None,
+                relaxed: None,
+                violation: Violation::RestrictedAboveRelaxed,
+            },
+        }
+    }
+    /// Checks the outcome of the restricted DD. Its best `solution` must only 
+    /// be given when the DD is exact.
+    ///
+    /// # Panics
+    /// This panics with the report of the node when a check fails
+    pub fn restricted<T>(&mut self, problem: &dyn Problem<State = T>, value: Option<isize>, solution: Option<Solution>) {
+        self.report.restricted = value;
+        if let Some(value) = value.filter(|v| *v > self.report.ub) {
+            self.fail(Violation::AboveUpperBound { value });
+        }
+        self.check_exact(problem, value, solution);
+    }
+    /// Checks the outcome of the relaxed DD. Its best `solution` must only be
+    /// given when the DD is exact.
+    ///
+    /// # Panics
+    /// This panics with the report of the node when a check fails
+    pub fn relaxed<T>(&mut self, problem: &dyn Problem<State = T>, value: Option<isize>, solution: Option<Solution>) {
+        self.report.relaxed = value;
+        if let (Some(restricted), Some(relaxed)) = (self.report.restricted, value) {
+            if restricted > relaxed {
+                self.fail(Violation::RestrictedAboveRelaxed);
+            }
+        }
+        if let Some(value) = value.filter(|v| *v > self.report.ub) {
+            self.fail(Violation::AboveUpperBound { value });
+        }
+        self.check_exact(problem, value, solution);
+    }
+
+    /// Checks that the value of an exact DD is the value of its best solution
+    fn check_exact<T>(&self, problem: &dyn Problem<State = T>, value: Option<isize>, solution: Option<Solution>) {
+        let (Some(value), Some(solution)) = (value, solution) else { return };
+        let replayed = evaluate(problem, &solution).map_err(|e| describe(&e));
+        if replayed != Ok(value) {
+            self.fail(Violation::NotAchievable { value, replayed });
+        }
+    }
+    fn fail(&self, violation: Violation) -> ! {
+        let report = VerifyReport { violation, ..self.report.clone() };
+        panic!("{report}")
+    }
+}
+
+/// Tells why a solution cannot be replayed (without the state, which needs
+/// not implement Debug)
+fn describe<S>(error: &EvalError<S>) -> String {
+    match error {
+        EvalError::Duplicate { variable } => 
+            format!("variable {} is assigned more than once", variable.id()),
+        EvalError::MissingValue { variable, depth, .. } => 
+            format!("variable {} is not assigned (depth {depth})", variable.id()),
+        EvalError::Infeasible { decision, depth, .. } => 
+            format!("infeasible decision x{} = {} (depth {depth})", decision.variable.id(), decision.value),
+        EvalError::DeadEnd { variable, depth, .. } => 
+            format!("no feasible value for variable {} (depth {depth})", variable.id()),
+        EvalError::Unused { decisions } => 
+            format!("{} decisions bear on variables that are never decided", decisions.len()),
+    }
+}
+
+#[cfg(test)]
+mod test_verify {
+    use crate::Variable;
+
+    use super::*;
+
+    fn report(violation: Violation) -> VerifyReport {
+        VerifyReport {
+            node: 3,
+            path: vec![Decision { variable: Variable(0), value: 1 }, Decision { variable: Variable(2), value: 0 }],
+            ub: 10,
+            restricted: Some(12),
+            relaxed: None,
+            violation,
+        }
+    }
+
+    #[test]
+    fn the_report_tells_the_node_its_path_and_both_values() {
+        let text = report(Violation::RestrictedAboveRelaxed).to_string();
+        assert_eq!(
+            "verification failed on node #3: the restricted DD is better than the relaxed DD\n  \
+             path       : [x0 = 1, x2 = 0]\n  \
+             upper bound: 10\n  \
+             restricted : 12\n  \
+             relaxed    : -",
+            text);
+    }
+    #[test]
+    fn the_report_tells_why_a_value_is_not_achievable() {
+        let text = report(Violation::NotAchievable { value: 7, replayed: Ok(5) }).to_string();
+        assert!(text.contains("the exact DD value 7 is not achievable (its best solution is worth 5)"));
+        let text = report(Violation::NotAchievable { value: 7, replayed: Err("oops".to_string()) }).to_string();
+        assert!(text.contains("the exact DD value 7 is not achievable (oops)"));
+    }
+    #[test]
+    fn the_unreplayable_solutions_are_described_without_their_state() {
+        let error = EvalError::Infeasible { decision: Decision { variable: Variable(1), value: 4 }, depth: 2, state: () };
+        assert_eq!("infeasible decision x1 = 4 (depth 2)", describe(&error));
+    }
+}
@@ -421,29 +421,20 @@
 //! // 3. Create a ranking to discriminate the promising and uninteresting states
 //! let heuristic = KPRanking;
 //! 
-//! // 4. Define the policy you will want to use regarding the maximum width of the DD
-//! let width = FixedWidth(100); // here we mean max 100 nodes per layer
+//! // 4. Assemble your solver. The builder picks a sensible default for each
+//! // part you leave out: the width, the dominance checker, the cutoff, the
+//! // fringe and the number of threads can all be customized.
+//! let mut builder = SolverBuilder::for_problem(&problem)
+//!     .with_relaxation(&relaxation)
+//!     .with_ranking(&heuristic)
+//!     // here we mean max 100 nodes per layer
+//!     .with_width(FixedWidth(100))
+//!     .with_dominance(SimpleDominanceChecker::new(KPDominance, problem.nb_variables()))
+//!     // might as well be a TimeBudget (or something else)
+//!     .with_cutoff(NoCutoff);
+//! let mut solver = builder.build();
 //! 
-//! // 5. Add a dominance relation checker
-//! let dominance = SimpleDominanceChecker::new(KPDominance, problem.nb_variables());
-//! 
-//! // 6. Decide of a cutoff heuristic (if you don't want to let the solver run for ever)
-//! let cutoff = NoCutoff; // might as well be a TimeBudget (or something else)
-//! 
-//! // 7. Create the solver fringe
-//! let mut fringe = SimpleFringe::new(MaxUB::new(&heuristic));
-//!  
-//! // 8. Instantiate your solver
-//! let mut solver = DefaultSolver::new(
-//!       &problem, 
-//!       &relaxation, 
-//!       &heuristic, 
-//!       &width, 
-//!       &dominance,
-//!       &cutoff, 
-//!       &mut fringe);
-//! 
-//! // 9. Maximize your objective function
+//! // 5. Maximize your objective function
 //! // the outcome provides the value of the best solution that was found for
 //! // the problem (if one was found) along with a flag indicating whether or
 //! // not the solution was proven optimal. Hence an unsatisfiable problem
@@ -454,7 +445,7 @@
 //! // The best solution (if one exist) is retrieved with
 //! let solution = solver.best_solution();
 //!
-//! // 10. Do whatever you like with the optimal solution.
+//! // 6. Do whatever you like with the optimal solution.
 //! assert_eq!(Some(220), outcome.best_value);
 //! println!("Solution");
 //! for decision in solution.unwrap().iter() {
@@ -476,6 +467,15 @@
 //! get you covered and you should be able to get a deep understanding of how 
 //! to use our library.
 //!
+//! ## Stability
+//! Within a major version (2.x), the traits you implement (`Problem`, 
+//! `Relaxation`, the heuristics, ...) never gain a required method nor a 
+//! tighter bound: new methods always come with a default implementation. 
+//! This promise is checked by `tests/api_stability.rs`, which implements all
+//! the public traits the way a third party would. The structures which are
+//! returned to you (`Completion`, `MddStats`, `MemoryStats`, `Config`, ...) 
+//! are non exhaustive; create them with their constructors or defaults.
+//!
 //! ## Citing DDO
 //! If you use DDO, or find it useful for your purpose (research, teaching,
 //! business, ...) please cite:
@@ -497,7 +497,17 @@
 mod common;
 mod abstraction;
 mod implementation;
+mod eval;
+mod build_info;
+mod registry;
+#[cfg(feature = "config-file")]
+mod config;
 
 pub use common::*;
 pub use abstraction::*;
-pub use implementation::*;
\ No newline at end of file
+pub use implementation::*;
+pub use eval::*;
+pub use build_info::*;
+pub use registry::*;
+#[cfg(feature = "config-file")]
+pub use config::*;
\ No newline at end of file
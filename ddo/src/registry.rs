@@ -0,0 +1,347 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This module provides the registry of the names of the components which can
+//! be picked at runtime (e.g. from the command line or from a configuration
+//! file). Each component has a canonical name and possibly a few aliases. 
+//! Looking up a name which is not registered yields an `UnknownComponent` 
+//! error which suggests the closest names (in terms of edit distance) and 
+//! lists all the valid ones, so that a typo never goes unnoticed.
+//!
+//! The registry only deals with names: a front-end looks the name given by 
+//! the user up, and then instantiates the component matching its canonical 
+//! name. Downstream crates can register their own components (see 
+//! `ComponentRegistry::register`) so that their front-ends benefit from the
+//! same checks.
+
+use std::{collections::HashMap, fmt};
+
+/// The kinds of components which are registered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ComponentKind {
+    /// The orderings of the subproblems in the fringe (e.g. `MaxUB`)
+    NodeOrdering,
+    /// The maximum width heuristics (e.g. `FixedWidth`)
+    Width,
+    /// The variable orderings (e.g. `ActivityOrder`)
+    VariableOrdering,
+    /// The fringes (e.g. `SimpleFringe`)
+    Fringe,
+    /// The types of cut-set (e.g. `LAST_EXACT_LAYER`)
+    Cutset,
+    /// The cutoff heuristics (e.g. `TimeBudget`)
+    Cutoff,
+}
+impl fmt::Display for ComponentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ComponentKind::NodeOrdering     => "node ordering",
+            ComponentKind::Width            => "width heuristic",
+            ComponentKind::VariableOrdering => "variable ordering",
+            ComponentKind::Fringe           => "fringe",
+            ComponentKind::Cutset           => "cut-set type",
+            ComponentKind::Cutoff           => "cutoff",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The error which is returned when looking up a name that is not registered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownComponent {
+    /// The kind of component which was looked up
+    pub kind: ComponentKind,
+    /// The name which was looked up
+    pub name: String,
+    /// The canonical names of the components whose name (or alias) is close
+    /// to the one which was looked up, the closest first
+    pub suggestions: Vec<String>,
+    /// The canonical names of all the components of that kind
+    pub valid: Vec<String>,
+}
+impl fmt::Display for UnknownComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown {} '{}'", self.kind, self.name)?;
+        if !self.suggestions.is_empty() {
+            write!(f, ", did you mean '{}'?", self.suggestions.join("' or '"))?;
+        }
+        write!(f, " (valid names: {})", self.valid.join(", "))
+    }
+}
+impl std::error::Error for UnknownComponent {}
+
+/// The error which is returned when registering a name that is already taken
+/// (be it as a canonical name or as an alias) by a component of the same kind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameTaken {
+    /// The kind of component which was registered
+    pub kind: ComponentKind,
+    /// The name which is already taken
+    pub name: String,
+}
+impl fmt::Display for NameTaken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the name '{}' is already taken by another {}", self.name, self.kind)
+    }
+}
+impl std::error::Error for NameTaken {}
+
+/// A registered component
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    /// The canonical name of the component
+    canonical: String,
+    /// The other names of the component
+    aliases: Vec<String>,
+}
+
+/// The names of the components which can be picked at runtime. Its default
+/// value knows about all the components of this crate; use `empty` to start
+/// from a blank registry.
+///
+/// # Example
+/// ```
+/// # use ddo::*;
+/// let mut registry = ComponentRegistry::default();
+/// assert_eq!(Ok("no_dup"), registry.lookup(ComponentKind::Fringe, "nodup"));
+///
+/// let error = registry.lookup(ComponentKind::NodeOrdering, "maxUb").unwrap_err();
+/// assert_eq!(vec!["max_ub".to_string()], error.suggestions);
+///
+/// // a downstream crate registers its own components
+/// registry.register(ComponentKind::NodeOrdering, "min_depth", &["shallow_first"]).unwrap();
+/// assert_eq!(Ok("min_depth"), registry.lookup(ComponentKind::NodeOrdering, "shallow_first"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentRegistry {
+    /// The registered components of each kind, in registration order
+    entries: HashMap<ComponentKind, Vec<Entry>>,
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        use ComponentKind::*;
+        let builtins: &[(ComponentKind, &str, &[&str])] = &[
+            (NodeOrdering,     "max_ub",           &["maxub", "max-ub"]),
+            (NodeOrdering,     "best_ub_per_cost", &["best-ub-per-cost"]),
+            (NodeOrdering,     "bonus_first",      &["bonus-first"]),
+            (Width,            "fixed",            &["fixed_width"]),
+            (Width,            "nb_unassigned",    &["nb-unassigned", "unassigned"]),
+            (VariableOrdering, "activity",         &["activity_order"]),
+            (Fringe,           "simple",           &["simple_fringe"]),
+            (Fringe,           "no_dup",           &["nodup", "no-dup", "no_duplicate"]),
+            (Fringe,           "dedup",            &["state_dedup"]),
+            (Fringe,           "depth_first",      &["depth-first", "dfs", "stack"]),
+            #[cfg(feature = "ooc")]
+            (Fringe,           "ooc",              &["out_of_core", "out-of-core"]),
+            (Cutset,           "last_exact_layer", &["lel", "last-exact-layer"]),
+            (Cutset,           "frontier",         &["fc", "frontier_cutset"]),
+            (Cutset,           "adaptive",         &[]),
+            (Cutoff,           "none",             &["no_cutoff"]),
+            (Cutoff,           "time_budget",      &["time-budget", "timeout"]),
+        ];
+        let mut registry = Self::empty();
+        for (kind, name, aliases) in builtins.iter().copied() {
+            registry.register(kind, name, aliases).expect("the builtin names are distinct");
+        }
+        registry
+    }
+}
+
+impl ComponentRegistry {
+    /// Creates a registry which knows about no component at all
+    pub fn empty() -> Self {
+        Self { entries: HashMap::new() }
+    }
+    /// Registers a component of the given kind under its canonical `name` and
+    /// the given `aliases`. This fails (and leaves the registry untouched) when
+    /// one of these names is already taken by a component of the same kind.
+    pub fn register(&mut self, kind: ComponentKind, name: &str, aliases: &[&str]) -> Result<(), NameTaken> {
+        let entries = self.entries.entry(kind).or_default();
+        let taken = |n: &str| entries.iter().any(|e| e.canonical == n || e.aliases.iter().any(|a| a == n));
+        let mut names = vec![name];
+        for alias in aliases.iter().copied() {
+            if taken(alias) || names.contains(&alias) {
+                return Err(NameTaken { kind, name: alias.to_string() });
+            }
+            names.push(alias);
+        }
+        if taken(name) {
+            return Err(NameTaken { kind, name: name.to_string() });
+        }
+        entries.push(Entry { 
+            canonical: name.to_string(), 
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+        });
+        Ok(())
+    }
+    /// Returns the canonical name of the component of the given kind which is
+    /// known as `name` (either its canonical name or one of its aliases).
+    pub fn lookup(&self, kind: ComponentKind, name: &str) -> Result<&str, UnknownComponent> {
+        let entries = self.entries.get(&kind).map(Vec::as_slice).unwrap_or_default();
+        entries.iter()
+            .find(|e| e.canonical == name || e.aliases.iter().any(|a| a == name))
+            .map(|e| e.canonical.as_str())
+            .ok_or_else(|| UnknownComponent {
+                kind,
+                name: name.to_string(),
+                suggestions: Self::suggestions(entries, name),
+                valid: entries.iter().map(|e| e.canonical.clone()).collect(),
+            })
+    }
+    /// Returns the canonical names of the registered components of the given
+    /// kind, in registration order
+    pub fn names(&self, kind: ComponentKind) -> Vec<&str> {
+        self.entries.get(&kind).into_iter().flatten()
+            .map(|e| e.canonical.as_str())
+            .collect()
+    }
+
+    /// Returns the canonical names of the components having a name or an 
+    /// alias which is close enough to `name` to be a likely typo, the closest
+    /// first. Case is ignored when comparing names.
+    fn suggestions(entries: &[Entry], name: &str) -> Vec<String> {
+        let name = name.to_lowercase();
+        let threshold = 2.max(name.chars().count() / 3);
+        let mut close = entries.iter()
+            .filter_map(|e| {
+                let dist = std::iter::once(&e.canonical).chain(e.aliases.iter())
+                    .map(|n| edit_distance(&name, &n.to_lowercase()))
+                    .min()?;
+                (dist <= threshold).then_some((dist, e.canonical.clone()))
+            })
+            .collect::<Vec<_>>();
+        close.sort_by_key(|(dist, _)| *dist);
+        close.into_iter().map(|(_, name)| name).collect()
+    }
+}
+
+/// Returns the Levenshtein distance between `a` and `b` (the number of 
+/// character insertions, deletions and substitutions turning `a` into `b`)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod test_registry {
+    use crate::*;
+    use super::edit_distance;
+
+    #[test]
+    fn the_edit_distance_counts_insertions_deletions_and_substitutions() {
+        assert_eq!(0, edit_distance("max_ub", "max_ub"));
+        assert_eq!(1, edit_distance("maxub", "max_ub"));
+        assert_eq!(1, edit_distance("max_ub", "max_ut"));
+        assert_eq!(3, edit_distance("", "abc"));
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+    }
+    #[test]
+    fn canonical_names_and_aliases_are_looked_up() {
+        let registry = ComponentRegistry::default();
+        assert_eq!(Ok("max_ub"),           registry.lookup(ComponentKind::NodeOrdering, "max_ub"));
+        assert_eq!(Ok("max_ub"),           registry.lookup(ComponentKind::NodeOrdering, "max-ub"));
+        assert_eq!(Ok("last_exact_layer"), registry.lookup(ComponentKind::Cutset, "lel"));
+        assert_eq!(Ok("frontier"),         registry.lookup(ComponentKind::Cutset, "fc"));
+        assert_eq!(Ok("nb_unassigned"),    registry.lookup(ComponentKind::Width, "unassigned"));
+        assert_eq!(Ok("time_budget"),      registry.lookup(ComponentKind::Cutoff, "timeout"));
+        assert_eq!(Ok("activity"),         registry.lookup(ComponentKind::VariableOrdering, "activity"));
+    }
+    #[test]
+    fn names_are_looked_up_among_the_components_of_the_given_kind_only() {
+        let registry = ComponentRegistry::default();
+        let error = registry.lookup(ComponentKind::Fringe, "max_ub").unwrap_err();
+        assert_eq!(ComponentKind::Fringe, error.kind);
+        assert_eq!(registry.names(ComponentKind::Fringe), error.valid);
+    }
+    #[test]
+    fn a_close_miss_comes_with_suggestions() {
+        let registry = ComponentRegistry::default();
+        let error = registry.lookup(ComponentKind::NodeOrdering, "maxUb").unwrap_err();
+        assert_eq!("maxUb", error.name);
+        assert_eq!(vec!["max_ub"], error.suggestions);
+        assert_eq!(vec!["max_ub", "best_ub_per_cost", "bonus_first"], error.valid);
+        assert_eq!(
+            "unknown node ordering 'maxUb', did you mean 'max_ub'? (valid names: max_ub, best_ub_per_cost, bonus_first)", 
+            error.to_string());
+
+        let error = registry.lookup(ComponentKind::Fringe, "no_dupp").unwrap_err();
+        assert_eq!(vec!["no_dup"], error.suggestions);
+    }
+    #[test]
+    fn the_closest_suggestions_come_first() {
+        let mut registry = ComponentRegistry::empty();
+        registry.register(ComponentKind::Width, "abcd", &[]).unwrap();
+        registry.register(ComponentKind::Width, "abcx", &[]).unwrap();
+        registry.register(ComponentKind::Width, "abxx", &[]).unwrap();
+        let error = registry.lookup(ComponentKind::Width, "abxx_").unwrap_err();
+        assert_eq!(vec!["abxx", "abcx"], error.suggestions);
+    }
+    #[test]
+    fn a_far_miss_comes_without_suggestions() {
+        let registry = ComponentRegistry::default();
+        let error = registry.lookup(ComponentKind::Cutset, "whatever").unwrap_err();
+        assert!(error.suggestions.is_empty());
+        assert_eq!(
+            "unknown cut-set type 'whatever' (valid names: last_exact_layer, frontier, adaptive)", 
+            error.to_string());
+    }
+    #[test]
+    fn third_party_components_can_be_registered() {
+        let mut registry = ComponentRegistry::default();
+        registry.register(ComponentKind::Width, "sqrt_width", &["sqrt"]).unwrap();
+        assert_eq!(Ok("sqrt_width"), registry.lookup(ComponentKind::Width, "sqrt"));
+        assert_eq!(vec!["fixed", "nb_unassigned", "sqrt_width"], registry.names(ComponentKind::Width));
+
+        let error = registry.lookup(ComponentKind::Width, "sqrt_widht").unwrap_err();
+        assert_eq!(vec!["sqrt_width"], error.suggestions);
+    }
+    #[test]
+    fn a_name_cannot_be_registered_twice_for_the_same_kind() {
+        let mut registry = ComponentRegistry::default();
+        assert_eq!(
+            Err(NameTaken { kind: ComponentKind::Fringe, name: "nodup".to_string() }),
+            registry.register(ComponentKind::Fringe, "my_fringe", &["nodup"]));
+        assert_eq!(
+            Err(NameTaken { kind: ComponentKind::Fringe, name: "simple".to_string() }),
+            registry.register(ComponentKind::Fringe, "simple", &[]));
+        // the failed registrations leave the registry untouched
+        assert!(registry.lookup(ComponentKind::Fringe, "my_fringe").is_err());
+        // the same name may be used for components of different kinds
+        assert!(registry.register(ComponentKind::Cutoff, "simple", &[]).is_ok());
+    }
+    #[test]
+    fn an_empty_registry_knows_no_name() {
+        let registry = ComponentRegistry::empty();
+        let error = registry.lookup(ComponentKind::Fringe, "simple").unwrap_err();
+        assert!(error.suggestions.is_empty());
+        assert!(error.valid.is_empty());
+        assert!(registry.names(ComponentKind::Fringe).is_empty());
+    }
+}
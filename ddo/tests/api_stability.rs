@@ -0,0 +1,383 @@
+// Copyright 2020 Xavier Gillard
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! This test pins the public API of ddo (2.x) the way a downstream crate sees
+//! it. Each public trait is implemented with its *required* methods only, for
+//! states which have no bound at all (unless the trait itself requires some).
+//! Hence, any accidental addition of a required method or tightening of the
+//! bounds breaks this test before it breaks the models of our users.
+//!
+//! Within a major version, this file may only ever grow. Having to change
+//! what is already written here means the change is a breaking one.
+
+use std::{cmp::Ordering, marker::PhantomData, sync::Arc, time::Duration};
+
+use ddo::{
+    Cache, Categorical, CompilationInput, Completion, Cutoff, Decision, DecisionCallback,
+    DecisionCodec, DecisionDiagram, DefaultMDDLEL, Dominance, DominanceCheckResult,
+    DominanceChecker, EnumCodec, Fringe, MddStats, MemoryStats, Preprocess, Problem,
+    Projection, Reason, Relaxation, Reseed, SequentialSolver, Solution, SolutionLifter,
+    Solver, StateRanking, SubProblem, SubProblemRanking, Threshold, TreeEvent, TreeExport,
+    TypedDecision, TypedSolution, Variable, WidthHeuristic,
+};
+
+// ----------------------------------------------------------------------------
+// --- THE TRAITS, IMPLEMENTED FOR ANY STATE ----------------------------------
+// ----------------------------------------------------------------------------
+
+/// A state which implements no trait at all
+struct Bare;
+
+/// Implements the traits whose implementations are never called for any state
+struct Any<S>(PhantomData<S>);
+
+impl<S> Problem for Any<S> {
+    type State = S;
+
+    fn nb_variables(&self) -> usize { 0 }
+    fn initial_state(&self) -> S { unimplemented!() }
+    fn initial_value(&self) -> isize { 0 }
+    fn transition(&self, _: &S, _: Decision) -> S { unimplemented!() }
+    fn transition_cost(&self, _: &S, _: &S, _: Decision) -> isize { 0 }
+    fn next_variable(&self, _: usize, _: &mut dyn Iterator<Item = &S>) -> Option<Variable> { None }
+    fn for_each_in_domain(&self, _: Variable, _: &S, _: &mut dyn DecisionCallback) {}
+}
+impl<S> Relaxation for Any<S> {
+    type State = S;
+
+    fn merge(&self, _: &mut dyn Iterator<Item = &S>) -> S { unimplemented!() }
+    fn relax(&self, _: &S, _: &S, _: &S, _: Decision, cost: isize) -> isize { cost }
+}
+impl<S> Dominance for Any<S> {
+    type State = S;
+    type Key = ();
+
+    fn get_key(&self, _: Arc<S>) -> Option<()> { None }
+    fn nb_dimensions(&self, _: &S) -> usize { 0 }
+    fn get_coordinate(&self, _: &S, _: usize) -> isize { 0 }
+}
+impl<S> Projection for Any<S> {
+    type State = S;
+    type Abstract = S;
+
+    fn project(&self, _: &S) -> S { unimplemented!() }
+    fn merge(&self, _: &[S]) -> S { unimplemented!() }
+    fn next_variable(&self, _: usize, _: &mut dyn Iterator<Item = &S>) -> Option<Variable> { None }
+    fn for_each_in_domain(&self, _: Variable, _: &S, _: &mut dyn DecisionCallback) {}
+    fn transition_abstract(&self, _: &S, _: Decision) -> S { unimplemented!() }
+    fn transition_cost_abstract(&self, _: &S, _: &S, _: Decision) -> isize { 0 }
+    fn compare(&self, _: &S, _: &S) -> Ordering { Ordering::Equal }
+}
+impl<S> DecisionDiagram for Any<S> {
+    type State = S;
+
+    fn compile(&mut self, _: &CompilationInput<S>, _: SubProblem<S>) -> Result<Completion, Reason> {
+        Ok(Completion::new(true, None))
+    }
+    fn take_residual(&mut self) -> Option<SubProblem<S>> { None }
+    fn is_exact(&self) -> bool { true }
+    fn best_value(&self) -> Option<isize> { None }
+    fn best_solution(&self) -> Option<Solution> { None }
+    fn best_exact_value(&self) -> Option<isize> { None }
+    fn best_exact_solution(&self) -> Option<Solution> { None }
+    fn cutset_len(&self) -> usize { 0 }
+    fn for_each_cutset_node<F>(&self, _: F) where F: FnMut(SubProblem<S>) {}
+    fn drain_cutset<F>(&mut self, _: F) where F: FnMut(SubProblem<S>) {}
+}
+impl<S> Solver for Any<S> {
+    fn maximize(&mut self) -> Completion { Completion::new(true, None) }
+    fn best_value(&self) -> Option<isize> { None }
+    fn best_solution(&self) -> Option<Solution> { None }
+    fn best_lower_bound(&self) -> isize { isize::MIN }
+    fn best_upper_bound(&self) -> isize { isize::MAX }
+    fn set_primal(&mut self, _: isize, _: Solution) {}
+    fn set_primal_bound(&mut self, _: isize) {}
+    fn reset(&mut self) {}
+    fn set_node_budget(&mut self, _: Option<usize>) {}
+    fn set_time_limit(&mut self, _: Option<Duration>) {}
+    fn explored(&self) -> usize { 0 }
+}
+impl<S, P: Problem<State = S>> Preprocess<P> for Any<S> {
+    type Reduced = Any<S>;
+    type Lifter = Any<S>;
+
+    fn preprocess(&self, _: &P) -> (Any<S>, Any<S>) { (Any(PhantomData), Any(PhantomData)) }
+}
+impl<S> SolutionLifter for Any<S> {
+    fn lift(&self, reduced: &[Decision]) -> Solution { reduced.to_vec() }
+}
+impl<S> DecisionCodec<S> for Any<S> {
+    fn encode(&self, _: &S) -> isize { 0 }
+    fn decode(&self, _: isize) -> Option<S> { None }
+}
+
+/// The solvers take these traits as trait objects: they must remain object safe
+#[test]
+fn the_traits_taken_as_trait_objects_remain_object_safe() {
+    fn pin<S: 'static>() {
+        let _: Option<&dyn Problem<State = S>> = None;
+        let _: Option<&dyn Relaxation<State = S>> = None;
+        let _: Option<&dyn StateRanking<State = S>> = None;
+        let _: Option<&dyn SubProblemRanking<State = S>> = None;
+        let _: Option<&dyn WidthHeuristic<S>> = None;
+        let _: Option<&dyn Fringe<State = S>> = None;
+        let _: Option<&dyn Cache<State = S>> = None;
+        let _: Option<&dyn DominanceChecker<State = S>> = None;
+        let _: Option<&dyn Projection<State = S, Abstract = S>> = None;
+        let _: Option<&dyn DecisionCodec<S>> = None;
+        let _: Option<&dyn Cutoff> = None;
+        let _: Option<&dyn Reseed> = None;
+        let _: Option<&dyn Solver> = None;
+        let _: Option<&dyn SolutionLifter> = None;
+        let _: Option<&dyn DecisionCallback> = None;
+        let _: Option<&dyn TreeExport> = None;
+    }
+    pin::<Bare>();
+
+    let mut solver = Any::<Bare>(PhantomData);
+    assert!(solver.maximize().is_exact);
+}
+
+// ----------------------------------------------------------------------------
+// --- THE TRAITS, IMPLEMENTED TO SOLVE A MODEL -------------------------------
+// ----------------------------------------------------------------------------
+
+/// Picks at most `k` out of `n` items, each of which is worth one
+struct Count {
+    n: usize,
+    k: usize,
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Picked {
+    depth: usize,
+    picked: usize,
+}
+impl Problem for Count {
+    type State = Picked;
+
+    fn nb_variables(&self) -> usize { self.n }
+    fn initial_state(&self) -> Picked { Picked { depth: 0, picked: 0 } }
+    fn initial_value(&self) -> isize { 0 }
+    fn transition(&self, state: &Picked, decision: Decision) -> Picked {
+        Picked { depth: state.depth + 1, picked: state.picked + decision.value as usize }
+    }
+    fn transition_cost(&self, _: &Picked, _: &Picked, decision: Decision) -> isize {
+        decision.value
+    }
+    fn next_variable(&self, depth: usize, _: &mut dyn Iterator<Item = &Picked>) -> Option<Variable> {
+        Some(Variable(depth)).filter(|_| depth < self.n)
+    }
+    fn for_each_in_domain(&self, variable: Variable, state: &Picked, f: &mut dyn DecisionCallback) {
+        f.apply(Decision { variable, value: Pick::No.code() });
+        if state.picked < self.k {
+            f.apply(Decision { variable, value: Pick::Yes.code() });
+        }
+    }
+}
+struct CountRelax;
+impl Relaxation for CountRelax {
+    type State = Picked;
+
+    fn merge(&self, states: &mut dyn Iterator<Item = &Picked>) -> Picked {
+        states.min_by_key(|s| s.picked).cloned().expect("cannot merge an empty layer")
+    }
+    fn relax(&self, _: &Picked, _: &Picked, _: &Picked, _: Decision, cost: isize) -> isize {
+        cost
+    }
+}
+struct FewerPicks;
+impl StateRanking for FewerPicks {
+    type State = Picked;
+
+    fn compare(&self, a: &Picked, b: &Picked) -> Ordering {
+        b.picked.cmp(&a.picked)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pick { No, Yes }
+impl Categorical for Pick {
+    fn code(&self) -> isize { *self as isize }
+    fn from_code(code: isize) -> Option<Self> {
+        match code {
+            0 => Some(Pick::No),
+            1 => Some(Pick::Yes),
+            _ => None,
+        }
+    }
+}
+
+/// A solution which is owned by the model
+struct Assignment(Solution);
+impl TypedSolution for Assignment {
+    fn value_of(&self, variable: Variable) -> Option<isize> {
+        self.0.value_of(variable)
+    }
+    fn typed_value_of<V>(&self, variable: Variable, codec: &impl DecisionCodec<V>) -> Option<V> {
+        self.0.typed_value_of(variable, codec)
+    }
+    fn typed_decisions<V>(&self, codec: &impl DecisionCodec<V>) -> Option<Vec<TypedDecision<V>>> {
+        self.0.typed_decisions(codec)
+    }
+}
+
+/// The components below work with any state
+struct Narrow(usize);
+impl<S> WidthHeuristic<S> for Narrow {
+    fn max_width(&self, _: &SubProblem<S>) -> usize { self.0 }
+}
+struct Never;
+impl Cutoff for Never {
+    fn must_stop(&self) -> bool { false }
+}
+struct Ignored;
+impl Reseed for Ignored {
+    fn reseed(&self, _: u64) {}
+}
+struct ByUb<S>(PhantomData<S>);
+impl<S> SubProblemRanking for ByUb<S> {
+    type State = S;
+
+    fn compare(&self, a: &SubProblem<S>, b: &SubProblem<S>) -> Ordering {
+        a.ub.cmp(&b.ub)
+    }
+}
+struct VecFringe<R: SubProblemRanking> {
+    ranking: R,
+    nodes: Vec<SubProblem<R::State>>,
+}
+impl<R: SubProblemRanking> VecFringe<R> {
+    fn best(&self) -> Option<usize> {
+        (0..self.nodes.len()).max_by(|a, b| self.ranking.compare(&self.nodes[*a], &self.nodes[*b]))
+    }
+}
+impl<R: SubProblemRanking> Fringe for VecFringe<R> {
+    type State = R::State;
+
+    fn push(&mut self, node: SubProblem<R::State>) { self.nodes.push(node) }
+    fn pop(&mut self) -> Option<SubProblem<R::State>> { self.best().map(|i| self.nodes.swap_remove(i)) }
+    fn peek(&self) -> Option<&SubProblem<R::State>> { self.best().map(|i| &self.nodes[i]) }
+    fn clear(&mut self) { self.nodes.clear() }
+    fn len(&self) -> usize { self.nodes.len() }
+}
+struct NoCache<S>(PhantomData<S>);
+impl<S> Default for NoCache<S> {
+    fn default() -> Self { NoCache(PhantomData) }
+}
+impl<S> Cache for NoCache<S> {
+    type State = S;
+
+    fn initialize(&mut self, _: &dyn Problem<State = S>) {}
+    fn get_threshold(&self, _: &S, _: usize) -> Option<Threshold> { None }
+    fn update_threshold(&self, _: Arc<S>, _: usize, _: isize, _: bool) {}
+    fn clear_layer(&self, _: usize) {}
+    fn clear(&self) {}
+}
+struct NoDominance<S>(PhantomData<S>);
+impl<S> DominanceChecker for NoDominance<S> {
+    type State = S;
+
+    fn clear_layer(&self, _: usize) {}
+    fn is_dominated_or_insert(&self, _: Arc<S>, _: usize, _: isize) -> DominanceCheckResult {
+        DominanceCheckResult { dominated: false, threshold: None }
+    }
+    fn cmp(&self, _: &S, _: isize, _: &S, _: isize) -> Ordering { Ordering::Equal }
+}
+struct Collect(Vec<Decision>);
+impl DecisionCallback for Collect {
+    fn apply(&mut self, decision: Decision) { self.0.push(decision) }
+}
+/// The events of the search tree may grow: they must be matched with a wildcard
+#[derive(Default)]
+struct Events { created: usize, others: usize }
+impl TreeExport for Events {
+    fn export(&mut self, event: &TreeEvent) {
+        match event {
+            TreeEvent::Created { .. } => self.created += 1,
+            _ => self.others += 1,
+        }
+    }
+}
+
+#[test]
+fn a_model_can_be_solved_with_components_of_its_own() {
+    let problem = Count { n: 6, k: 3 };
+    let relaxation = CountRelax;
+    let ranking = FewerPicks;
+    let width = Narrow(2);
+    let dominance = NoDominance(PhantomData);
+    let cutoff = Never;
+    let mut fringe = VecFringe { ranking: ByUb(PhantomData), nodes: vec![] };
+    let mut events = Events::default();
+    let mut solver = SequentialSolver::<Picked, DefaultMDDLEL<Picked>, NoCache<Picked>>::new(
+        &problem,
+        &relaxation,
+        &ranking,
+        &width,
+        &dominance,
+        &cutoff,
+        &mut fringe,
+    ).with_reseed(&Ignored).with_tree_export(&mut events);
+
+    let outcome = solver.maximize();
+    assert!(outcome.is_exact);
+    assert_eq!(Some(3), outcome.best_value);
+
+    let solution = Assignment(solver.best_solution().unwrap());
+    let picks = solution.typed_decisions(&EnumCodec::<Pick>::new()).unwrap();
+    assert_eq!(3, picks.iter().filter(|d| d.value == Pick::Yes).count());
+    assert!(solution.typed_value_of(Variable(0), &EnumCodec::<Pick>::new()).is_some());
+
+    let mut domain = Collect(vec![]);
+    problem.for_each_in_domain(Variable(0), &problem.initial_state(), &mut domain);
+    assert_eq!(2, domain.0.len());
+
+    drop(solver);
+    assert!(events.created >= 1);
+    assert!(events.others >= 1);
+}
+
+// ----------------------------------------------------------------------------
+// --- THE NON EXHAUSTIVE STRUCTURES ------------------------------------------
+// ----------------------------------------------------------------------------
+
+#[test]
+fn the_non_exhaustive_structures_can_be_created_from_the_outside() {
+    let completion = Completion::new(false, Some(4)).with_guarantee(1);
+    assert!(!completion.is_exact);
+    assert_eq!(Some(4), completion.best_value);
+    assert_eq!(Some(1), completion.guaranteed_within);
+    assert_eq!(None, completion.stopped_by);
+    let completion = Completion::new(false, None).with_stop_reason(Reason::Timeout);
+    assert_eq!(Some(Reason::Timeout), completion.stopped_by);
+
+    let mut stats = MddStats::default();
+    stats.nb_layers = 3;
+    assert_eq!(None, stats.first_inexact_layer);
+
+    let memory = MemoryStats::default();
+    assert_eq!(0, memory.peak_bytes());
+}
+#[test]
+#[cfg(feature = "config-file")]
+fn a_config_can_be_created_from_the_outside() {
+    let config = ddo::Config::default().with_threads(1).with_caching(true);
+    assert_eq!(1, config.threads);
+    assert!(config.caching);
+}
@@ -75,7 +75,7 @@ fn maximize(
         );
 
         let start = Instant::now();
-        let Completion{is_exact, best_value} = solver.maximize();
+        let Completion{is_exact, best_value, ..} = solver.maximize();
         
         let duration = start.elapsed().as_secs_f64();
         let gap = solver.gap();